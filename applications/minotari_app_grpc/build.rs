@@ -5,6 +5,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     tonic_build::configure().build_client(true).build_server(true).compile(
         &[
             "proto/base_node.proto",
+            "proto/error_details.proto",
             "proto/wallet.proto",
             "proto/validator_node.proto",
         ],
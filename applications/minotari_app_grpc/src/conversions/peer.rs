@@ -20,7 +20,12 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use tari_comms::{connectivity::ConnectivityStatus, net_address::MultiaddrWithStats, peer_manager::Peer};
+use tari_comms::{
+    bandwidth::{PeerTrafficStats, ProtocolTrafficStats, TrafficStats},
+    connectivity::{ConnectivityStatus, PeerReputation},
+    net_address::MultiaddrWithStats,
+    peer_manager::Peer,
+};
 use tari_utilities::ByteArray;
 
 use crate::tari_rpc as grpc;
@@ -93,3 +98,45 @@ impl From<ConnectivityStatus> for grpc::ConnectivityStatus {
         }
     }
 }
+
+impl From<PeerReputation> for grpc::PeerReputation {
+    fn from(reputation: PeerReputation) -> Self {
+        Self {
+            node_id: reputation.node_id().to_vec(),
+            score: reputation.score(),
+            num_positive_events: reputation.num_positive_events(),
+            num_negative_events: reputation.num_negative_events(),
+            num_bans: reputation.num_bans(),
+            last_event: reputation.last_event().map(|e| e.to_string()).unwrap_or_default(),
+            last_updated_secs_ago: reputation.last_updated().elapsed().as_secs(),
+        }
+    }
+}
+
+impl From<TrafficStats> for grpc::TrafficStats {
+    fn from(stats: TrafficStats) -> Self {
+        Self {
+            bytes_read: stats.bytes_read,
+            bytes_written: stats.bytes_written,
+        }
+    }
+}
+
+impl From<PeerTrafficStats> for grpc::PeerTrafficStats {
+    fn from(stats: PeerTrafficStats) -> Self {
+        Self {
+            node_id: stats.node_id.to_vec(),
+            stats: Some(stats.stats.into()),
+            num_active_substreams: stats.num_active_substreams as u64,
+        }
+    }
+}
+
+impl From<ProtocolTrafficStats> for grpc::ProtocolTrafficStats {
+    fn from(stats: ProtocolTrafficStats) -> Self {
+        Self {
+            protocol: String::from_utf8_lossy(&stats.protocol).into_owned(),
+            stats: Some(stats.stats.into()),
+        }
+    }
+}
@@ -0,0 +1,95 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Structured error details for gRPC statuses, following the `google.rpc.Status` rich error model
+
+use std::collections::HashMap;
+
+use prost::Message;
+use tonic::{Code, Status};
+
+use crate::tari_rpc::{ErrorInfo, RpcStatus};
+
+/// The type URL of `google.rpc.ErrorInfo`, which has the same layout as [ErrorInfo]
+pub const ERROR_INFO_TYPE_URL: &str = "type.googleapis.com/google.rpc.ErrorInfo";
+
+/// Creates a status that carries an [ErrorInfo] detail, so that clients can match on a stable `reason` instead of
+/// parsing the message
+pub fn status_with_error_info<T: Into<String>>(
+    code: Code,
+    message: T,
+    reason: &str,
+    domain: &str,
+    metadata: HashMap<String, String>,
+) -> Status {
+    let message = message.into();
+    let info = ErrorInfo {
+        reason: reason.to_string(),
+        domain: domain.to_string(),
+        metadata,
+    };
+    let details = RpcStatus {
+        code: code as i32,
+        message: message.clone(),
+        details: vec![prost_types::Any {
+            type_url: ERROR_INFO_TYPE_URL.to_string(),
+            value: info.encode_to_vec(),
+        }],
+    };
+    Status::with_details(code, message, details.encode_to_vec().into())
+}
+
+/// Returns the [ErrorInfo] detail of the status, if it has one
+pub fn error_info(status: &Status) -> Option<ErrorInfo> {
+    RpcStatus::decode(status.details())
+        .ok()?
+        .details
+        .into_iter()
+        .find(|detail| detail.type_url == ERROR_INFO_TYPE_URL)
+        .and_then(|detail| ErrorInfo::decode(detail.value.as_slice()).ok())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn it_round_trips_error_info() {
+        let metadata = HashMap::from([("tx_id".to_string(), "123".to_string())]);
+        let status = status_with_error_info(
+            Code::NotFound,
+            "Transaction not found",
+            "TRANSACTION_NOT_FOUND",
+            "wallet.transaction_service",
+            metadata.clone(),
+        );
+        assert_eq!(status.code(), Code::NotFound);
+        assert_eq!(status.message(), "Transaction not found");
+
+        let info = error_info(&status).unwrap();
+        assert_eq!(info.reason, "TRANSACTION_NOT_FOUND");
+        assert_eq!(info.domain, "wallet.transaction_service");
+        assert_eq!(info.metadata, metadata);
+
+        assert!(error_info(&Status::internal("no details")).is_none());
+    }
+}
@@ -23,6 +23,8 @@ pub mod authentication;
 
 pub mod conversions;
 
+pub mod error_details;
+
 pub mod tls;
 #[allow(clippy::all, clippy::pedantic)]
 pub mod tari_rpc {
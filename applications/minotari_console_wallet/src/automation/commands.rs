@@ -21,7 +21,9 @@
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 use std::{
-    convert::TryInto,
+    collections::HashMap,
+    convert::{TryFrom, TryInto},
+    fmt,
     fs,
     fs::File,
     io,
@@ -30,30 +32,40 @@ use std::{
     time::{Duration, Instant},
 };
 
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDateTime, Utc};
 use digest::Digest;
 use futures::FutureExt;
 use log::*;
 use minotari_app_grpc::tls::certs::{generate_self_signed_certs, print_warning, write_cert_to_disk};
 use minotari_wallet::{
+    accounts::AccountId,
     connectivity_service::WalletConnectivityInterface,
+    labels::LabelTarget,
     output_manager_service::{handle::OutputManagerHandle, UtxoSelectionCriteria},
     transaction_service::{
+        batch_payout::BatchPayoutRecipient,
         handle::{TransactionEvent, TransactionServiceHandle},
-        storage::models::WalletTransaction,
+        payment_request::PaymentType,
+        storage::models::{
+            BatchPayoutRow,
+            CompletedTransaction,
+            ScheduledPayment,
+            TransactionStatusChange,
+            WalletTransaction,
+        },
     },
     TransactionStage,
     WalletConfig,
     WalletSqlite,
 };
-use serde::{de::DeserializeOwned, Serialize};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use sha2::Sha256;
 use strum_macros::{Display, EnumIter, EnumString};
 use tari_common_types::{
     burnt_proof::BurntProof,
     emoji::EmojiId,
     tari_address::TariAddress,
-    transaction::TxId,
+    transaction::{TransactionDirection, TxId},
     types::{Commitment, FixedHash, PublicKey, Signature},
 };
 use tari_comms::{
@@ -75,7 +87,7 @@ use tokio::{
 
 use super::error::CommandError;
 use crate::{
-    cli::{CliCommands, MakeItRainTransactionType},
+    cli::{CliCommands, ExportFormat, MakeItRainTransactionType},
     utils::db::{CUSTOM_BASE_NODE_ADDRESS_KEY, CUSTOM_BASE_NODE_PUBLIC_KEY_KEY},
 };
 
@@ -117,6 +129,7 @@ pub async fn send_tari(
     mut wallet_transaction_service: TransactionServiceHandle,
     fee_per_gram: u64,
     amount: MicroMinotari,
+    selection_criteria: UtxoSelectionCriteria,
     destination: TariAddress,
     message: String,
 ) -> Result<TxId, CommandError> {
@@ -124,7 +137,7 @@ pub async fn send_tari(
         .send_transaction(
             destination,
             amount,
-            UtxoSelectionCriteria::default(),
+            selection_criteria,
             OutputFeatures::default(),
             fee_per_gram * uT,
             message,
@@ -424,7 +437,15 @@ pub async fn make_it_rain(
                     // Send transaction
                     let tx_id = match transaction_type {
                         MakeItRainTransactionType::Interactive => {
-                            send_tari(tx_service, fee, amount, address.clone(), msg.clone()).await
+                            send_tari(
+                                tx_service,
+                                fee,
+                                amount,
+                                UtxoSelectionCriteria::default(),
+                                address.clone(),
+                                msg.clone(),
+                            )
+                            .await
                         },
                         MakeItRainTransactionType::OneSided => {
                             send_one_sided(
@@ -611,6 +632,21 @@ pub async fn monitor_transactions(
     results
 }
 
+/// Builds the UTXO selection criteria for spending from the named account, or from the default account if no name is
+/// given
+fn account_selection_criteria(
+    wallet: &WalletSqlite,
+    account_name: Option<&str>,
+) -> Result<UtxoSelectionCriteria, CommandError> {
+    match account_name {
+        Some(name) => {
+            let account = wallet.get_account_by_name(name)?;
+            Ok(UtxoSelectionCriteria::default().for_account(account.id))
+        },
+        None => Ok(UtxoSelectionCriteria::default()),
+    }
+}
+
 #[allow(clippy::too_many_lines)]
 pub async fn command_runner(
     config: &WalletConfig,
@@ -683,10 +719,18 @@ pub async fn command_runner(
                 }
             },
             SendMinotari(args) => {
+                let selection_criteria = match account_selection_criteria(&wallet, args.from_account.as_deref()) {
+                    Ok(criteria) => criteria,
+                    Err(e) => {
+                        eprintln!("SendMinotari error! {}", e);
+                        continue;
+                    },
+                };
                 match send_tari(
                     transaction_service.clone(),
                     config.fee_per_gram,
                     args.amount,
+                    selection_criteria,
                     args.destination,
                     args.message,
                 )
@@ -700,11 +744,18 @@ pub async fn command_runner(
                 }
             },
             SendOneSided(args) => {
+                let selection_criteria = match account_selection_criteria(&wallet, args.from_account.as_deref()) {
+                    Ok(criteria) => criteria,
+                    Err(e) => {
+                        eprintln!("SendOneSided error! {}", e);
+                        continue;
+                    },
+                };
                 match send_one_sided(
                     transaction_service.clone(),
                     config.fee_per_gram,
                     args.amount,
-                    UtxoSelectionCriteria::default(),
+                    selection_criteria,
                     args.destination,
                     args.message,
                 )
@@ -718,11 +769,18 @@ pub async fn command_runner(
                 }
             },
             SendOneSidedToStealthAddress(args) => {
+                let selection_criteria = match account_selection_criteria(&wallet, args.from_account.as_deref()) {
+                    Ok(criteria) => criteria,
+                    Err(e) => {
+                        eprintln!("SendOneSidedToStealthAddress error! {}", e);
+                        continue;
+                    },
+                };
                 match send_one_sided_to_stealth_address(
                     transaction_service.clone(),
                     config.fee_per_gram,
                     args.amount,
-                    UtxoSelectionCriteria::default(),
+                    selection_criteria,
                     args.destination,
                     args.message,
                 )
@@ -916,11 +974,18 @@ pub async fn command_runner(
                 }
             },
             InitShaAtomicSwap(args) => {
+                let selection_criteria = match account_selection_criteria(&wallet, args.from_account.as_deref()) {
+                    Ok(criteria) => criteria,
+                    Err(e) => {
+                        eprintln!("InitShaAtomicSwap error! {}", e);
+                        continue;
+                    },
+                };
                 match init_sha_atomic_swap(
                     transaction_service.clone(),
                     config.fee_per_gram,
                     args.amount,
-                    UtxoSelectionCriteria::default(),
+                    selection_criteria,
                     args.destination,
                     args.message,
                 )
@@ -1031,6 +1096,298 @@ pub async fn command_runner(
                 },
                 Err(err) => eprintln!("Error generating certificates: {}", err),
             },
+            CreateAccount(args) => match wallet.clone().create_account(&args.name).await {
+                Ok(account) => match wallet.get_account_address(account.id).await {
+                    Ok(address) => {
+                        println!("Created account {}", account);
+                        println!("Account address: {}", address.to_hex());
+                    },
+                    Err(e) => eprintln!("CreateAccount error! {}", e),
+                },
+                Err(e) => eprintln!("CreateAccount error! {}", e),
+            },
+            ListAccounts => match wallet.get_accounts() {
+                Ok(accounts) => {
+                    for account in accounts {
+                        match output_service.get_account_balance(account.id).await {
+                            Ok(balance) => println!("{}: {}", account, balance.available_balance),
+                            Err(e) => eprintln!("ListAccounts error! {}", e),
+                        }
+                    }
+                },
+                Err(e) => eprintln!("ListAccounts error! {}", e),
+            },
+            GetAccountBalance(args) => match wallet.get_account_by_name(&args.name) {
+                Ok(account) => match output_service.get_account_balance(account.id).await {
+                    Ok(balance) => {
+                        debug!(target: LOG_TARGET, "get-account-balance concluded");
+                        println!("{}", balance);
+                    },
+                    Err(e) => eprintln!("GetAccountBalance error! {}", e),
+                },
+                Err(e) => eprintln!("GetAccountBalance error! {}", e),
+            },
+            CreateInvoice(args) => {
+                let expires_at = match args.expires_in.map(chrono::Duration::from_std).transpose() {
+                    Ok(expires_in) => expires_in.map(|expires_in| Utc::now().naive_utc() + expires_in),
+                    Err(e) => {
+                        eprintln!("CreateInvoice error! {}", e);
+                        continue;
+                    },
+                };
+                match transaction_service
+                    .create_invoice(args.payment_id, args.amount, args.message, expires_at)
+                    .await
+                {
+                    Ok(invoice) => {
+                        println!("Created invoice {} for {}", invoice.payment_id, invoice.amount);
+                        println!("Payment address: {}", invoice.address.to_hex());
+                        println!("Payment request: {}", invoice.to_payment_request());
+                    },
+                    Err(e) => eprintln!("CreateInvoice error! {}", e),
+                }
+            },
+            ListInvoices => match transaction_service.get_invoices().await {
+                Ok(invoices) => {
+                    for invoice in invoices {
+                        println!(
+                            "{}: {} ({} of {} received)",
+                            invoice.payment_id, invoice.status, invoice.amount_received, invoice.amount
+                        );
+                    }
+                },
+                Err(e) => eprintln!("ListInvoices error! {}", e),
+            },
+            CancelInvoice(args) => match transaction_service.cancel_invoice(args.payment_id.clone()).await {
+                Ok(()) => println!("Cancelled invoice {}", args.payment_id),
+                Err(e) => eprintln!("CancelInvoice error! {}", e),
+            },
+            PayInvoice(args) => {
+                let request = args.uri;
+                let is_expired = request.expires_at.map_or(false, |expires_at| {
+                    i64::try_from(expires_at).map_or(false, |expires_at| expires_at <= Utc::now().timestamp())
+                });
+                if is_expired {
+                    eprintln!("PayInvoice error! Invoice {} has expired", request.payment_id);
+                    continue;
+                }
+                // The payee only matches payments of the requested type to the invoice
+                if request.payment_type != PaymentType::OneSided {
+                    eprintln!(
+                        "PayInvoice error! Invoice {} requires a {} payment, which is not supported",
+                        request.payment_id, request.payment_type
+                    );
+                    continue;
+                }
+                let selection_criteria = match account_selection_criteria(&wallet, args.from_account.as_deref()) {
+                    Ok(criteria) => criteria,
+                    Err(e) => {
+                        eprintln!("PayInvoice error! {}", e);
+                        continue;
+                    },
+                };
+                match send_one_sided(
+                    transaction_service.clone(),
+                    config.fee_per_gram,
+                    request.amount,
+                    selection_criteria,
+                    request.address,
+                    request.payment_id,
+                )
+                .await
+                {
+                    Ok(tx_id) => {
+                        debug!(target: LOG_TARGET, "pay-invoice concluded with tx_id {}", tx_id);
+                        tx_ids.push(tx_id);
+                    },
+                    Err(e) => eprintln!("PayInvoice error! {}", e),
+                }
+            },
+            SendOneSidedToStealthSubaddress(args) => {
+                let selection_criteria = match account_selection_criteria(&wallet, args.from_account.as_deref()) {
+                    Ok(criteria) => criteria,
+                    Err(e) => {
+                        eprintln!("SendOneSidedToStealthSubaddress error! {}", e);
+                        continue;
+                    },
+                };
+                match transaction_service
+                    .send_one_sided_to_stealth_subaddress_transaction(
+                        args.destination,
+                        args.amount,
+                        selection_criteria,
+                        OutputFeatures::default(),
+                        config.fee_per_gram * uT,
+                        args.message,
+                    )
+                    .await
+                {
+                    Ok(tx_id) => {
+                        debug!(
+                            target: LOG_TARGET,
+                            "send-one-sided-to-stealth-subaddress concluded with tx_id {}", tx_id
+                        );
+                        tx_ids.push(tx_id);
+                    },
+                    Err(e) => eprintln!("SendOneSidedToStealthSubaddress error! {}", e),
+                }
+            },
+            CreateSubaddress(args) => match output_service.create_subaddress(args.label).await {
+                Ok(subaddress) => {
+                    println!("Created subaddress {}", subaddress.index);
+                    println!("{}", subaddress.to_address(config.network).to_hex());
+                },
+                Err(e) => eprintln!("CreateSubaddress error! {}", e),
+            },
+            ListSubaddresses => match output_service.get_subaddresses().await {
+                Ok(subaddresses) => {
+                    for subaddress in subaddresses {
+                        println!(
+                            "{}: {} {}",
+                            subaddress.index,
+                            subaddress.to_address(config.network).to_hex(),
+                            subaddress.label
+                        );
+                    }
+                },
+                Err(e) => eprintln!("ListSubaddresses error! {}", e),
+            },
+            SetSubaddressLabel(args) => match output_service.set_subaddress_label(args.index, args.label).await {
+                Ok(()) => println!("Updated the label of subaddress {}", args.index),
+                Err(e) => eprintln!("SetSubaddressLabel error! {}", e),
+            },
+            SetLabel(args) => {
+                if args.label.is_none() && args.note.is_none() {
+                    eprintln!("SetLabel error! A label or a note is required");
+                } else {
+                    match wallet.db.set_label(&args.target, args.label, args.note) {
+                        Ok(label) => println!("Labelled {}: {} {}", label.target, label.label, label.note),
+                        Err(e) => eprintln!("SetLabel error! {}", e),
+                    }
+                }
+            },
+            RemoveLabel(args) => match wallet.db.remove_label(&args.target) {
+                Ok(()) => println!("Removed the label of {}", args.target),
+                Err(e) => eprintln!("RemoveLabel error! {}", e),
+            },
+            ListLabels(args) => match wallet.db.get_labels() {
+                Ok(labels) => {
+                    for label in labels
+                        .iter()
+                        .filter(|label| args.search.as_ref().map_or(true, |query| label.matches(query)))
+                    {
+                        println!("{}: {} {}", label.target, label.label, label.note);
+                    }
+                },
+                Err(e) => eprintln!("ListLabels error! {}", e),
+            },
+            ExportTransactions(args) => match export_transactions(&wallet, &args.output_file, args.format).await {
+                Ok(count) => println!(
+                    "Exported {} transactions to {}",
+                    count,
+                    args.output_file.to_string_lossy()
+                ),
+                Err(e) => eprintln!("ExportTransactions error! {}", e),
+            },
+            BatchPayout(args) => {
+                let recipients = match load_batch_payout_file(&args.input_file) {
+                    Ok(recipients) => recipients,
+                    Err(e) => {
+                        eprintln!("BatchPayout error! {}", e);
+                        continue;
+                    },
+                };
+                let selection_criteria = match account_selection_criteria(&wallet, args.from_account.as_deref()) {
+                    Ok(criteria) => criteria,
+                    Err(e) => {
+                        eprintln!("BatchPayout error! {}", e);
+                        continue;
+                    },
+                };
+                match transaction_service
+                    .send_batch_payout(
+                        args.batch_id,
+                        recipients,
+                        selection_criteria,
+                        MicroMinotari::from(config.fee_per_gram),
+                    )
+                    .await
+                {
+                    Ok(rows) => {
+                        print_batch_payout(&rows);
+                        for tx_id in rows.iter().filter_map(|row| row.tx_id) {
+                            if !tx_ids.contains(&tx_id) {
+                                tx_ids.push(tx_id);
+                            }
+                        }
+                    },
+                    Err(e) => eprintln!("BatchPayout error! {}", e),
+                }
+            },
+            BatchPayoutStatus(args) => match transaction_service.get_batch_payout(args.batch_id.clone()).await {
+                Ok(rows) if rows.is_empty() => eprintln!("BatchPayoutStatus error! Batch {} not found", args.batch_id),
+                Ok(rows) => print_batch_payout(&rows),
+                Err(e) => eprintln!("BatchPayoutStatus error! {}", e),
+            },
+            SchedulePayment(args) => {
+                let account_id = match args
+                    .from_account
+                    .as_deref()
+                    .map(|name| wallet.get_account_by_name(name))
+                {
+                    Some(Ok(account)) => account.id,
+                    Some(Err(e)) => {
+                        eprintln!("SchedulePayment error! {}", e);
+                        continue;
+                    },
+                    None => AccountId::DEFAULT,
+                };
+                let now = Utc::now().naive_utc();
+                let payment = ScheduledPayment::new(
+                    args.schedule_id,
+                    args.destination,
+                    args.amount,
+                    args.message,
+                    account_id,
+                    MicroMinotari::from(config.fee_per_gram),
+                    args.cadence,
+                    args.start_time.map_or(now, |start_time| start_time.naive_utc()),
+                    args.end_time.map(|end_time| end_time.naive_utc()),
+                    args.max_payments,
+                    now,
+                );
+                match transaction_service.create_scheduled_payment(payment).await {
+                    Ok(payment) => println!(
+                        "Scheduled payment {} of {} {}, first payment at {}",
+                        payment.schedule_id, payment.amount, payment.cadence, payment.next_run_at
+                    ),
+                    Err(e) => eprintln!("SchedulePayment error! {}", e),
+                }
+            },
+            ListScheduledPayments => match transaction_service.get_scheduled_payments().await {
+                Ok(payments) => {
+                    for payment in payments {
+                        print_scheduled_payment(&payment);
+                    }
+                },
+                Err(e) => eprintln!("ListScheduledPayments error! {}", e),
+            },
+            PauseScheduledPayment(args) => match transaction_service.pause_scheduled_payment(args.schedule_id).await {
+                Ok(payment) => print_scheduled_payment(&payment),
+                Err(e) => eprintln!("PauseScheduledPayment error! {}", e),
+            },
+            ResumeScheduledPayment(args) => {
+                match transaction_service.resume_scheduled_payment(args.schedule_id).await {
+                    Ok(payment) => print_scheduled_payment(&payment),
+                    Err(e) => eprintln!("ResumeScheduledPayment error! {}", e),
+                }
+            },
+            CancelScheduledPayment(args) => {
+                match transaction_service.cancel_scheduled_payment(args.schedule_id).await {
+                    Ok(payment) => print_scheduled_payment(&payment),
+                    Err(e) => eprintln!("CancelScheduledPayment error! {}", e),
+                }
+            },
         }
     }
 
@@ -1121,6 +1478,352 @@ fn write_tx_to_csv_file(tx: WalletTransaction, file_path: PathBuf) -> Result<(),
     Ok(())
 }
 
+/// A single transaction in an accounting export. Amounts are in µT and timestamps are UTC, no fiat values are included.
+#[derive(Debug, Serialize)]
+struct AccountingRecord {
+    tx_id: u64,
+    timestamp: String,
+    mined_height: Option<u64>,
+    mined_timestamp: Option<String>,
+    direction: String,
+    amount: u64,
+    fee: u64,
+    counterparty: String,
+    counterparty_alias: String,
+    status: String,
+    cancelled: Option<String>,
+    label: String,
+    note: String,
+    message: String,
+    status_history: Vec<AccountingStatusChange>,
+}
+
+#[derive(Debug, Serialize)]
+struct AccountingStatusChange {
+    timestamp: String,
+    status: String,
+    cancelled: Option<String>,
+}
+
+impl From<TransactionStatusChange> for AccountingStatusChange {
+    fn from(change: TransactionStatusChange) -> Self {
+        Self {
+            timestamp: format_utc_timestamp(change.changed_at),
+            status: change.status.to_string(),
+            cancelled: change.cancelled.map(|reason| reason.to_string()),
+        }
+    }
+}
+
+impl fmt::Display for AccountingStatusChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.timestamp, self.status)?;
+        if let Some(reason) = &self.cancelled {
+            write!(f, " (cancelled: {})", reason)?;
+        }
+        Ok(())
+    }
+}
+
+fn format_utc_timestamp(timestamp: NaiveDateTime) -> String {
+    timestamp.format("%Y-%m-%dT%H:%M:%SZ").to_string()
+}
+
+/// Fetches all pending, completed and cancelled transactions, oldest first
+async fn fetch_all_transactions(
+    transaction_service: &mut TransactionServiceHandle,
+) -> Result<Vec<CompletedTransaction>, CommandError> {
+    let mut transactions: Vec<CompletedTransaction> = Vec::new();
+    transactions.extend(
+        transaction_service
+            .get_pending_inbound_transactions()
+            .await?
+            .into_values()
+            .map(CompletedTransaction::from),
+    );
+    transactions.extend(
+        transaction_service
+            .get_cancelled_pending_inbound_transactions()
+            .await?
+            .into_values()
+            .map(CompletedTransaction::from),
+    );
+    transactions.extend(
+        transaction_service
+            .get_pending_outbound_transactions()
+            .await?
+            .into_values()
+            .map(CompletedTransaction::from),
+    );
+    transactions.extend(
+        transaction_service
+            .get_cancelled_pending_outbound_transactions()
+            .await?
+            .into_values()
+            .map(CompletedTransaction::from),
+    );
+    transactions.extend(transaction_service.get_completed_transactions().await?.into_values());
+    transactions.extend(
+        transaction_service
+            .get_cancelled_completed_transactions()
+            .await?
+            .into_values(),
+    );
+    transactions.sort_by_key(|tx| (tx.timestamp, tx.tx_id.as_u64()));
+    Ok(transactions)
+}
+
+/// Writes every transaction in the wallet, including pending and cancelled ones, with its labels and status history
+/// to the given file. Returns the number of exported transactions.
+async fn export_transactions(
+    wallet: &WalletSqlite,
+    file_path: &Path,
+    format: ExportFormat,
+) -> Result<usize, CommandError> {
+    let mut transaction_service = wallet.transaction_service.clone();
+    let transactions = fetch_all_transactions(&mut transaction_service).await?;
+
+    let mut labels = HashMap::new();
+    for label in wallet.db.get_labels()? {
+        if let LabelTarget::Transaction(tx_id) = label.target {
+            labels.insert(tx_id, label);
+        }
+    }
+    let aliases = wallet
+        .contacts_service
+        .clone()
+        .get_contacts()
+        .await
+        .map_err(|e| CommandError::General(e.to_string()))?
+        .into_iter()
+        .map(|contact| (contact.address.to_hex(), contact.alias))
+        .collect::<HashMap<_, _>>();
+
+    let mut records = Vec::with_capacity(transactions.len());
+    for tx in transactions {
+        let counterparty = match tx.direction {
+            TransactionDirection::Inbound => tx.source_address.to_hex(),
+            _ => tx.destination_address.to_hex(),
+        };
+        let status_history = transaction_service
+            .get_transaction_status_history(tx.tx_id)
+            .await?
+            .into_iter()
+            .map(AccountingStatusChange::from)
+            .collect();
+        let (label, note) = labels
+            .remove(&tx.tx_id)
+            .map(|label| (label.label, label.note))
+            .unwrap_or_default();
+        records.push(AccountingRecord {
+            tx_id: tx.tx_id.as_u64(),
+            timestamp: format_utc_timestamp(tx.timestamp),
+            mined_height: tx.mined_height,
+            mined_timestamp: tx.mined_timestamp.map(format_utc_timestamp),
+            direction: tx.direction.to_string(),
+            amount: tx.amount.as_u64(),
+            fee: tx.fee.as_u64(),
+            counterparty_alias: aliases.get(&counterparty).cloned().unwrap_or_default(),
+            counterparty,
+            status: tx.status.to_string(),
+            cancelled: tx.cancelled.map(|reason| reason.to_string()),
+            label,
+            note,
+            message: tx.message,
+            status_history,
+        });
+    }
+
+    match format {
+        ExportFormat::Csv => write_accounting_records_to_csv_file(&records, file_path)?,
+        ExportFormat::Json => {
+            let file = File::create(file_path).map_err(|e| CommandError::JsonFile(e.to_string()))?;
+            serde_json::to_writer_pretty(file, &records).map_err(|e| CommandError::JsonFile(e.to_string()))?;
+        },
+    }
+    Ok(records.len())
+}
+
+fn write_accounting_records_to_csv_file(records: &[AccountingRecord], file_path: &Path) -> Result<(), CommandError> {
+    let file = File::create(file_path).map_err(|e| CommandError::CSVFile(e.to_string()))?;
+    let mut csv_file = LineWriter::new(file);
+    writeln!(
+        csv_file,
+        r##""tx_id","timestamp","mined_height","mined_timestamp","direction","amount","fee","counterparty","counterparty_alias","status","cancelled","label","note","message","status_history""##
+    )
+    .map_err(|e| CommandError::CSVFile(e.to_string()))?;
+    for record in records {
+        let status_history = record
+            .status_history
+            .iter()
+            .map(|change| change.to_string())
+            .collect::<Vec<_>>()
+            .join("; ");
+        let fields = [
+            record.tx_id.to_string(),
+            record.timestamp.clone(),
+            record.mined_height.map(|h| h.to_string()).unwrap_or_default(),
+            record.mined_timestamp.clone().unwrap_or_default(),
+            record.direction.clone(),
+            record.amount.to_string(),
+            record.fee.to_string(),
+            record.counterparty.clone(),
+            record.counterparty_alias.clone(),
+            record.status.clone(),
+            record.cancelled.clone().unwrap_or_default(),
+            record.label.clone(),
+            record.note.clone(),
+            record.message.clone(),
+            status_history,
+        ];
+        writeln!(
+            csv_file,
+            "{}",
+            fields.iter().map(|f| quote_csv_field(f)).collect::<Vec<_>>().join(",")
+        )
+        .map_err(|e| CommandError::CSVFile(e.to_string()))?;
+    }
+    Ok(())
+}
+
+fn quote_csv_field(value: &str) -> String {
+    format!("\"{}\"", value.replace('"', "\"\""))
+}
+
+#[derive(Deserialize)]
+struct BatchPayoutFileRow {
+    address: String,
+    /// Either a number of µT or a string in any of the formats accepted on the command line
+    amount: serde_json::Value,
+    #[serde(default)]
+    memo: String,
+}
+
+/// Read the recipients of a batch payout from a CSV or JSON file. All rows are checked so that every invalid row is
+/// reported at once.
+fn load_batch_payout_file(file_path: &Path) -> Result<Vec<BatchPayoutRecipient>, CommandError> {
+    let is_json = file_path
+        .extension()
+        .map_or(false, |extension| extension.eq_ignore_ascii_case("json"));
+    let mut errors = Vec::new();
+    let rows = if is_json {
+        read_json_file::<_, Vec<BatchPayoutFileRow>>(file_path)?
+            .into_iter()
+            .enumerate()
+            .map(|(i, row)| {
+                let amount = match row.amount {
+                    serde_json::Value::String(amount) => amount,
+                    amount => amount.to_string(),
+                };
+                (format!("row {}", i + 1), row.address, amount, row.memo)
+            })
+            .collect::<Vec<_>>()
+    } else {
+        read_batch_payout_csv(file_path, &mut errors)?
+    };
+
+    let mut recipients = Vec::with_capacity(rows.len());
+    for (location, address, amount, memo) in rows {
+        let address = address
+            .parse::<TariAddress>()
+            .map_err(|e| format!("{}: invalid address ({})", location, e));
+        let amount = amount
+            .parse::<MicroMinotari>()
+            .map_err(|e| format!("{}: invalid amount ({})", location, e));
+        match (address, amount) {
+            (Ok(address), Ok(amount)) => recipients.push(BatchPayoutRecipient::new(address, amount, memo)),
+            (address, amount) => errors.extend(address.err().into_iter().chain(amount.err())),
+        }
+    }
+    if !errors.is_empty() {
+        return Err(CommandError::General(errors.join(", ")));
+    }
+    if recipients.is_empty() {
+        return Err(CommandError::General("The batch payout file has no rows".to_string()));
+    }
+    Ok(recipients)
+}
+
+/// Reads the `address,amount[,memo]` records of a batch payout CSV file with their line numbers. Fields may be quoted
+/// to hold commas, and a header row starting with `address` is skipped. Records with the wrong number of fields are
+/// added to `errors`.
+fn read_batch_payout_csv(
+    file_path: &Path,
+    errors: &mut Vec<String>,
+) -> Result<Vec<(String, String, String, String)>, CommandError> {
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .trim(csv::Trim::All)
+        .from_path(file_path)
+        .map_err(|e| CommandError::CSVFile(e.to_string()))?;
+    let mut rows = Vec::new();
+    for (i, record) in reader.records().enumerate() {
+        let record = record.map_err(|e| CommandError::CSVFile(e.to_string()))?;
+        let location = format!("line {}", record.position().map_or(0, |position| position.line()));
+        if i == 0 &&
+            record
+                .get(0)
+                .map_or(false, |field| field.eq_ignore_ascii_case("address"))
+        {
+            continue;
+        }
+        match record.iter().collect::<Vec<_>>().as_slice() {
+            [address, amount] => rows.push((location, address.to_string(), amount.to_string(), String::new())),
+            [address, amount, memo] => rows.push((location, address.to_string(), amount.to_string(), memo.to_string())),
+            fields => errors.push(format!(
+                "{}: expected an address, an amount and an optional memo but found {} fields, memos containing commas \
+                 must be quoted",
+                location,
+                fields.len()
+            )),
+        }
+    }
+    Ok(rows)
+}
+
+fn print_batch_payout(rows: &[BatchPayoutRow]) {
+    for row in rows {
+        let tx_id = row.tx_id.map(|tx_id| format!(" (TxId: {})", tx_id)).unwrap_or_default();
+        let error = row.error.as_ref().map(|e| format!(": {}", e)).unwrap_or_default();
+        println!(
+            "{:>5}  {}  {}  {}{}{}",
+            row.index + 1,
+            row.recipient.address.to_hex(),
+            row.recipient.amount,
+            row.status,
+            tx_id,
+            error
+        );
+    }
+    let sent = rows.iter().filter(|row| row.tx_id.is_some()).count();
+    println!("{} of {} rows sent", sent, rows.len());
+}
+
+fn print_scheduled_payment(payment: &ScheduledPayment) {
+    let next_run = if payment.status.is_finished() {
+        String::new()
+    } else {
+        format!(", next payment at {}", payment.next_run_at)
+    };
+    let error = payment
+        .last_error
+        .as_ref()
+        .map(|e| format!(", last error: {}", e))
+        .unwrap_or_default();
+    println!(
+        "{}: {} {} to {} ({}, {} paid{}{})",
+        payment.schedule_id,
+        payment.amount,
+        payment.cadence,
+        payment.address.to_hex(),
+        payment.status,
+        payment.payments_made,
+        next_run,
+        error
+    );
+}
+
 fn load_tx_from_csv_file(file_path: PathBuf) -> Result<Vec<WalletTransaction>, CommandError> {
     let file_contents = fs::read_to_string(file_path).map_err(|e| CommandError::CSVFile(e.to_string()))?;
     let mut results = Vec::new();
@@ -23,14 +23,19 @@
 use std::{
     fmt::{Debug, Display, Formatter},
     path::PathBuf,
+    str::FromStr,
     time::Duration,
 };
 
 use chrono::{DateTime, Utc};
 use clap::{Args, Parser, Subcommand};
 use minotari_app_utilities::{common_cli_args::CommonCliArgs, utilities::UniPublicKey};
+use minotari_wallet::{
+    labels::LabelTarget,
+    transaction_service::{payment_request::PaymentRequest, scheduled_payment::PaymentCadence},
+};
 use tari_common::configuration::{ConfigOverrideProvider, Network};
-use tari_common_types::tari_address::TariAddress;
+use tari_common_types::{tari_address::TariAddress, tari_subaddress::TariSubaddress};
 use tari_comms::multiaddr::Multiaddr;
 use tari_core::transactions::{tari_amount, tari_amount::MicroMinotari};
 use tari_key_manager::SeedWords;
@@ -55,6 +60,12 @@ pub struct Cli {
     /// Change the password for the console wallet and exit
     #[clap(long, alias = "update-password")]
     pub change_password: bool,
+    /// Write an encrypted backup of the full wallet state, protected by the wallet password, to the file and exit
+    #[clap(long, parse(from_os_str))]
+    pub create_backup: Option<PathBuf>,
+    /// Restore the wallet from an encrypted backup file into a new data directory and exit
+    #[clap(long, parse(from_os_str))]
+    pub restore_backup: Option<PathBuf>,
     /// Force wallet recovery
     #[clap(long, alias = "recover")]
     pub recovery: bool,
@@ -136,6 +147,28 @@ pub enum CliCommands {
     RevalidateWalletDb,
     RegisterValidatorNode(RegisterValidatorNodeArgs),
     CreateTlsCerts,
+    CreateAccount(CreateAccountArgs),
+    ListAccounts,
+    GetAccountBalance(AccountArgs),
+    CreateInvoice(CreateInvoiceArgs),
+    ListInvoices,
+    CancelInvoice(InvoiceArgs),
+    PayInvoice(PayInvoiceArgs),
+    SendOneSidedToStealthSubaddress(SendToSubaddressArgs),
+    CreateSubaddress(CreateSubaddressArgs),
+    ListSubaddresses,
+    SetSubaddressLabel(SetSubaddressLabelArgs),
+    SetLabel(SetLabelArgs),
+    RemoveLabel(LabelTargetArgs),
+    ListLabels(ListLabelsArgs),
+    ExportTransactions(ExportTransactionsArgs),
+    BatchPayout(BatchPayoutArgs),
+    BatchPayoutStatus(BatchPayoutStatusArgs),
+    SchedulePayment(SchedulePaymentArgs),
+    ListScheduledPayments,
+    PauseScheduledPayment(ScheduledPaymentArgs),
+    ResumeScheduledPayment(ScheduledPaymentArgs),
+    CancelScheduledPayment(ScheduledPaymentArgs),
 }
 
 #[derive(Debug, Args, Clone)]
@@ -149,6 +182,68 @@ pub struct SendMinotariArgs {
     pub destination: TariAddress,
     #[clap(short, long, default_value = "<No message>")]
     pub message: String,
+    /// Name of the account to spend from. The default account is used if not provided.
+    #[clap(long)]
+    pub from_account: Option<String>,
+}
+
+#[derive(Debug, Args, Clone)]
+pub struct CreateAccountArgs {
+    pub name: String,
+}
+
+#[derive(Debug, Args, Clone)]
+pub struct AccountArgs {
+    pub name: String,
+}
+
+#[derive(Debug, Args, Clone)]
+pub struct SendToSubaddressArgs {
+    pub amount: MicroMinotari,
+    pub destination: TariSubaddress,
+    #[clap(short, long, default_value = "<No message>")]
+    pub message: String,
+    /// Name of the account to spend from. The default account is used if not provided.
+    #[clap(long)]
+    pub from_account: Option<String>,
+}
+
+#[derive(Debug, Args, Clone)]
+pub struct CreateSubaddressArgs {
+    #[clap(short, long, default_value = "")]
+    pub label: String,
+}
+
+#[derive(Debug, Args, Clone)]
+pub struct SetSubaddressLabelArgs {
+    pub index: u64,
+    pub label: String,
+}
+
+#[derive(Debug, Args, Clone)]
+pub struct CreateInvoiceArgs {
+    pub amount: MicroMinotari,
+    /// Unique identifier for the invoice, e.g. an order number
+    pub payment_id: String,
+    #[clap(short, long, default_value = "")]
+    pub message: String,
+    /// Number of seconds after which the invoice expires. The invoice does not expire if not provided.
+    #[clap(long, parse(try_from_str = parse_duration))]
+    pub expires_in: Option<Duration>,
+}
+
+#[derive(Debug, Args, Clone)]
+pub struct InvoiceArgs {
+    pub payment_id: String,
+}
+
+#[derive(Debug, Args, Clone)]
+pub struct PayInvoiceArgs {
+    /// The payment request URI of the invoice
+    pub uri: PaymentRequest,
+    /// Name of the account to spend from. The default account is used if not provided.
+    #[clap(long)]
+    pub from_account: Option<String>,
 }
 
 #[derive(Debug, Args, Clone)]
@@ -250,6 +345,106 @@ pub struct ExportTxArgs {
     pub output_file: Option<PathBuf>,
 }
 
+#[derive(Debug, Args, Clone)]
+pub struct ExportTransactionsArgs {
+    #[clap(short, long)]
+    pub output_file: PathBuf,
+    /// The format of the export, either `csv` or `json`
+    #[clap(short, long, default_value = "csv")]
+    pub format: ExportFormat,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Csv,
+    Json,
+}
+
+impl FromStr for ExportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "csv" => Ok(ExportFormat::Csv),
+            "json" => Ok(ExportFormat::Json),
+            _ => Err(format!("Unsupported export format `{}`, expected `csv` or `json`", s)),
+        }
+    }
+}
+
+#[derive(Debug, Args, Clone)]
+pub struct SetLabelArgs {
+    /// The item to label, one of `transaction:<tx id>`, `output:<commitment hex>` or `contact:<address>`
+    pub target: LabelTarget,
+    #[clap(short, long)]
+    pub label: Option<String>,
+    #[clap(short, long)]
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Args, Clone)]
+pub struct LabelTargetArgs {
+    /// The labelled item, one of `transaction:<tx id>`, `output:<commitment hex>` or `contact:<address>`
+    pub target: LabelTarget,
+}
+
+#[derive(Debug, Args, Clone)]
+pub struct ListLabelsArgs {
+    /// Only list labels whose label or note contains this text
+    #[clap(short, long)]
+    pub search: Option<String>,
+}
+
+#[derive(Debug, Args, Clone)]
+pub struct BatchPayoutArgs {
+    /// A `.csv` file with `address,amount,memo` rows, with an optional header row and memos containing commas quoted,
+    /// or a `.json` file with an array of `{"address", "amount", "memo"}` objects
+    #[clap(short, long)]
+    pub input_file: PathBuf,
+    /// Identifier of the batch. Submitting a batch again only pays the rows that were not paid yet. Derived from the
+    /// rows of the file if not provided.
+    #[clap(long)]
+    pub batch_id: Option<String>,
+    /// Name of the account to spend from. The default account is used if not provided.
+    #[clap(long)]
+    pub from_account: Option<String>,
+}
+
+#[derive(Debug, Args, Clone)]
+pub struct BatchPayoutStatusArgs {
+    pub batch_id: String,
+}
+
+#[derive(Debug, Args, Clone)]
+pub struct SchedulePaymentArgs {
+    /// Unique identifier for the schedule, e.g. an employee or contract number
+    pub schedule_id: String,
+    pub amount: MicroMinotari,
+    pub destination: TariAddress,
+    /// `daily`, `weekly`, `monthly` or an interval such as `90m`, `12h` or `2w`
+    #[clap(short, long)]
+    pub cadence: PaymentCadence,
+    /// The time of the first payment, e.g. `2024-01-31T09:00:00Z`. The first payment is sent now if not provided.
+    #[clap(long, parse(try_from_str=parse_start_time))]
+    pub start_time: Option<DateTime<Utc>>,
+    /// No further payments are sent after this time
+    #[clap(long)]
+    pub end_time: Option<DateTime<Utc>>,
+    /// The number of payments after which the schedule completes
+    #[clap(long)]
+    pub max_payments: Option<u64>,
+    #[clap(short, long, default_value = "<No message>")]
+    pub message: String,
+    /// Name of the account to spend from. The default account is used if not provided.
+    #[clap(long)]
+    pub from_account: Option<String>,
+}
+
+#[derive(Debug, Args, Clone)]
+pub struct ScheduledPaymentArgs {
+    pub schedule_id: String,
+}
+
 #[derive(Debug, Args, Clone)]
 pub struct ImportTxArgs {
     #[clap(short, long)]
@@ -0,0 +1,197 @@
+// Copyright 2023 The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+//! Maps wallet service errors onto gRPC statuses that carry a stable `ErrorInfo` reason
+
+use std::collections::HashMap;
+
+use minotari_app_grpc::error_details::status_with_error_info;
+use minotari_wallet::{
+    error::{WalletError, WalletStorageError},
+    output_manager_service::error::{OutputManagerError, OutputManagerStorageError},
+    transaction_service::error::{TransactionServiceError, TransactionStorageError},
+};
+use tari_contacts::contacts_service::error::{ContactsServiceError, ContactsServiceStorageError};
+use tonic::{Code, Status};
+
+const TRANSACTION_SERVICE_DOMAIN: &str = "wallet.transaction_service";
+const OUTPUT_MANAGER_DOMAIN: &str = "wallet.output_manager";
+const WALLET_DOMAIN: &str = "wallet";
+const CONTACTS_DOMAIN: &str = "wallet.contacts";
+
+pub fn transaction_service_error_to_status(err: TransactionServiceError) -> Status {
+    let (code, reason) = transaction_service_error_details(&err);
+    status_with_error_info(
+        code,
+        err.to_string(),
+        reason,
+        TRANSACTION_SERVICE_DOMAIN,
+        HashMap::new(),
+    )
+}
+
+pub fn output_manager_error_to_status(err: OutputManagerError) -> Status {
+    let (code, reason) = output_manager_error_details(&err);
+    status_with_error_info(code, err.to_string(), reason, OUTPUT_MANAGER_DOMAIN, HashMap::new())
+}
+
+pub fn wallet_error_to_status(err: WalletError) -> Status {
+    let (code, reason) = match &err {
+        WalletError::ArgumentError { .. } => (Code::InvalidArgument, "INVALID_ARGUMENT"),
+        WalletError::OutputManagerError(e) => output_manager_error_details(e),
+        WalletError::TransactionServiceError(e) => transaction_service_error_details(e),
+        WalletError::WalletStorageError(e) => wallet_storage_error_details(e),
+        WalletError::ContactsServiceError(e) => contacts_error_details(e),
+        WalletError::Shutdown => (Code::Unavailable, "SHUTDOWN"),
+        _ => (Code::Internal, "INTERNAL"),
+    };
+    status_with_error_info(code, err.to_string(), reason, WALLET_DOMAIN, HashMap::new())
+}
+
+pub fn wallet_storage_error_to_status(err: WalletStorageError) -> Status {
+    let (code, reason) = wallet_storage_error_details(&err);
+    status_with_error_info(code, err.to_string(), reason, WALLET_DOMAIN, HashMap::new())
+}
+
+pub fn contacts_error_to_status(err: ContactsServiceError) -> Status {
+    let (code, reason) = contacts_error_details(&err);
+    status_with_error_info(code, err.to_string(), reason, CONTACTS_DOMAIN, HashMap::new())
+}
+
+fn transaction_service_error_details(err: &TransactionServiceError) -> (Code, &'static str) {
+    match err {
+        TransactionServiceError::InvalidInvoice(_) |
+        TransactionServiceError::InvalidBatchPayout(_) |
+        TransactionServiceError::InvalidScheduledPayment(_) |
+        TransactionServiceError::InvalidNetwork |
+        TransactionServiceError::InvalidMessageError(_) => (Code::InvalidArgument, "INVALID_ARGUMENT"),
+        TransactionServiceError::TransactionDoesNotExistError => (Code::NotFound, "TRANSACTION_NOT_FOUND"),
+        TransactionServiceError::MempoolRejection |
+        TransactionServiceError::MempoolRejectionTimeLocked |
+        TransactionServiceError::MempoolRejectionOrphan |
+        TransactionServiceError::MempoolRejectionDoubleSpend |
+        TransactionServiceError::MempoolRejectionInvalidTransaction => (Code::FailedPrecondition, "MEMPOOL_REJECTION"),
+        TransactionServiceError::NoBaseNodeKeysProvided |
+        TransactionServiceError::BaseNodeNotSynced |
+        TransactionServiceError::ConnectivityError { .. } => (Code::Unavailable, "BASE_NODE_UNAVAILABLE"),
+        TransactionServiceError::WalletRecoveryInProgress => (Code::Unavailable, "RECOVERY_IN_PROGRESS"),
+        TransactionServiceError::TransactionValidationInProgress => (Code::Unavailable, "VALIDATION_IN_PROGRESS"),
+        TransactionServiceError::TransactionCancelled => (Code::Aborted, "TRANSACTION_CANCELLED"),
+        TransactionServiceError::Timeout => (Code::DeadlineExceeded, "TIMEOUT"),
+        TransactionServiceError::Shutdown => (Code::Unavailable, "SHUTDOWN"),
+        TransactionServiceError::OutputManagerError(e) => output_manager_error_details(e),
+        TransactionServiceError::TransactionStorageError(e) => transaction_storage_error_details(e),
+        TransactionServiceError::WalletStorageError(e) => wallet_storage_error_details(e),
+        _ => (Code::Internal, "INTERNAL"),
+    }
+}
+
+fn transaction_storage_error_details(err: &TransactionStorageError) -> (Code, &'static str) {
+    match err {
+        TransactionStorageError::ValueNotFound(_) | TransactionStorageError::ValuesNotFound => {
+            (Code::NotFound, "TRANSACTION_NOT_FOUND")
+        },
+        TransactionStorageError::InvoiceNotFound(_) => (Code::NotFound, "INVOICE_NOT_FOUND"),
+        TransactionStorageError::ScheduledPaymentNotFound(_) => (Code::NotFound, "SCHEDULED_PAYMENT_NOT_FOUND"),
+        TransactionStorageError::TransactionAlreadyExists => (Code::AlreadyExists, "TRANSACTION_ALREADY_EXISTS"),
+        TransactionStorageError::InvoiceAlreadyExists(_) => (Code::AlreadyExists, "INVOICE_ALREADY_EXISTS"),
+        TransactionStorageError::BatchPayoutAlreadyExists(_) => (Code::AlreadyExists, "BATCH_PAYOUT_ALREADY_EXISTS"),
+        TransactionStorageError::ScheduledPaymentAlreadyExists(_) => {
+            (Code::AlreadyExists, "SCHEDULED_PAYMENT_ALREADY_EXISTS")
+        },
+        TransactionStorageError::InvalidAccountId(_) | TransactionStorageError::TariAddressError(_) => {
+            (Code::InvalidArgument, "INVALID_ARGUMENT")
+        },
+        _ => (Code::Internal, "INTERNAL"),
+    }
+}
+
+fn output_manager_error_details(err: &OutputManagerError) -> (Code, &'static str) {
+    match err {
+        OutputManagerError::NotEnoughFunds => (Code::FailedPrecondition, "INSUFFICIENT_FUNDS"),
+        OutputManagerError::FundsPending => (Code::FailedPrecondition, "FUNDS_PENDING"),
+        OutputManagerError::NoUtxosSelected { .. } => (Code::FailedPrecondition, "NO_UTXOS_SELECTED"),
+        OutputManagerError::TransactionWeightExceeded { .. } => {
+            (Code::FailedPrecondition, "TRANSACTION_WEIGHT_EXCEEDED")
+        },
+        OutputManagerError::InvalidArgument(_) |
+        OutputManagerError::NoCommitmentsProvided |
+        OutputManagerError::ConversionError(_) => (Code::InvalidArgument, "INVALID_ARGUMENT"),
+        OutputManagerError::NoBaseNodeKeysProvided |
+        OutputManagerError::BaseNodeNotSynced |
+        OutputManagerError::ConnectivityError { .. } => (Code::Unavailable, "BASE_NODE_UNAVAILABLE"),
+        OutputManagerError::ValidationInProgress => (Code::Unavailable, "VALIDATION_IN_PROGRESS"),
+        OutputManagerError::Cancellation => (Code::Aborted, "TRANSACTION_CANCELLED"),
+        OutputManagerError::Shutdown => (Code::Unavailable, "SHUTDOWN"),
+        OutputManagerError::OutputManagerStorageError(e) => output_manager_storage_error_details(e),
+        _ => (Code::Internal, "INTERNAL"),
+    }
+}
+
+fn output_manager_storage_error_details(err: &OutputManagerStorageError) -> (Code, &'static str) {
+    match err {
+        OutputManagerStorageError::ValueNotFound |
+        OutputManagerStorageError::ValuesNotFound |
+        OutputManagerStorageError::PendingTransactionNotFound => (Code::NotFound, "NOT_FOUND"),
+        OutputManagerStorageError::DuplicateOutput | OutputManagerStorageError::DuplicateTransaction => {
+            (Code::AlreadyExists, "OUTPUT_ALREADY_EXISTS")
+        },
+        OutputManagerStorageError::OutputAlreadySpent | OutputManagerStorageError::OutputAlreadyEncumbered => {
+            (Code::FailedPrecondition, "OUTPUT_UNAVAILABLE")
+        },
+        _ => (Code::Internal, "INTERNAL"),
+    }
+}
+
+fn wallet_storage_error_details(err: &WalletStorageError) -> (Code, &'static str) {
+    match err {
+        WalletStorageError::InvalidPassphrase => (Code::PermissionDenied, "INVALID_PASSPHRASE"),
+        WalletStorageError::BurntProofNotFound(_) => (Code::NotFound, "BURNT_PROOF_NOT_FOUND"),
+        WalletStorageError::AccountNotFound(_) => (Code::NotFound, "ACCOUNT_NOT_FOUND"),
+        WalletStorageError::LabelNotFound(_) => (Code::NotFound, "LABEL_NOT_FOUND"),
+        WalletStorageError::ValueNotFound(_) | WalletStorageError::ValuesNotFound => (Code::NotFound, "NOT_FOUND"),
+        WalletStorageError::AccountAlreadyExists(_) => (Code::AlreadyExists, "ACCOUNT_ALREADY_EXISTS"),
+        WalletStorageError::DuplicateContact => (Code::AlreadyExists, "CONTACT_ALREADY_EXISTS"),
+        _ => (Code::Internal, "INTERNAL"),
+    }
+}
+
+fn contacts_error_details(err: &ContactsServiceError) -> (Code, &'static str) {
+    match err {
+        ContactsServiceError::ContactNotFound |
+        ContactsServiceError::ContactsServiceStorageError(ContactsServiceStorageError::ValueNotFound(_)) |
+        ContactsServiceError::ContactsServiceStorageError(ContactsServiceStorageError::ValuesNotFound) => {
+            (Code::NotFound, "CONTACT_NOT_FOUND")
+        },
+        ContactsServiceError::MessageParsingError(_) => (Code::InvalidArgument, "INVALID_ARGUMENT"),
+        _ => (Code::Internal, "INTERNAL"),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use minotari_app_grpc::error_details::error_info;
+
+    use super::*;
+
+    #[test]
+    fn it_maps_nested_errors_to_the_innermost_reason() {
+        let status = transaction_service_error_to_status(TransactionServiceError::OutputManagerError(
+            OutputManagerError::NotEnoughFunds,
+        ));
+        assert_eq!(status.code(), Code::FailedPrecondition);
+        let info = error_info(&status).unwrap();
+        assert_eq!(info.reason, "INSUFFICIENT_FUNDS");
+        assert_eq!(info.domain, TRANSACTION_SERVICE_DOMAIN);
+
+        let status = transaction_service_error_to_status(TransactionServiceError::TransactionStorageError(
+            TransactionStorageError::InvoiceNotFound("abc".to_string()),
+        ));
+        assert_eq!(status.code(), Code::NotFound);
+        assert_eq!(error_info(&status).unwrap().reason, "INVOICE_NOT_FOUND");
+
+        let status = wallet_error_to_status(WalletError::WalletStorageError(WalletStorageError::InvalidPassphrase));
+        assert_eq!(status.code(), Code::PermissionDenied);
+        assert_eq!(error_info(&status).unwrap().reason, "INVALID_PASSPHRASE");
+    }
+}
@@ -1,6 +1,7 @@
 // Copyright 2022 The Tari Project
 // SPDX-License-Identifier: BSD-3-Clause
 
+mod error;
 mod wallet_grpc_server;
 
 use minotari_app_grpc::tari_rpc::TransactionEvent;
@@ -29,6 +30,8 @@ pub fn convert_to_transaction_event(event: String, source: TransactionWrapper) -
             direction: completed.direction.to_string(),
             amount: completed.amount.as_u64(),
             message: completed.message.to_string(),
+            payment_id: String::default(),
+            schedule_id: String::default(),
         },
         TransactionWrapper::Outbound(outbound) => TransactionEvent {
             event,
@@ -39,6 +42,8 @@ pub fn convert_to_transaction_event(event: String, source: TransactionWrapper) -
             direction: "outbound".to_string(),
             amount: outbound.amount.as_u64(),
             message: outbound.message,
+            payment_id: String::default(),
+            schedule_id: String::default(),
         },
         TransactionWrapper::Inbound(inbound) => TransactionEvent {
             event,
@@ -49,6 +54,8 @@ pub fn convert_to_transaction_event(event: String, source: TransactionWrapper) -
             direction: "inbound".to_string(),
             amount: inbound.amount.as_u64(),
             message: inbound.message.clone(),
+            payment_id: String::default(),
+            schedule_id: String::default(),
         },
     }
 }
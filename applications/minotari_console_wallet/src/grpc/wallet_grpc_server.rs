@@ -22,6 +22,7 @@
 
 use std::convert::{TryFrom, TryInto};
 
+use chrono::{NaiveDateTime, Utc};
 use futures::{
     channel::mpsc::{self, Sender},
     future,
@@ -32,26 +33,51 @@ use minotari_app_grpc::tari_rpc::{
     self,
     payment_recipient::PaymentType,
     wallet_server,
+    BatchPayoutRequest,
+    BatchPayoutResponse,
+    CancelInvoiceRequest,
+    CancelInvoiceResponse,
     CheckConnectivityResponse,
     ClaimHtlcRefundRequest,
     ClaimHtlcRefundResponse,
     ClaimShaAtomicSwapRequest,
     ClaimShaAtomicSwapResponse,
+    CoinJoinRequest,
+    CoinJoinResponse,
     CoinSplitRequest,
     CoinSplitResponse,
     CommitmentSignature,
+    ConsolidationStatusResponse,
+    ContactRequest,
+    ContactResponse,
+    CreateAccountRequest,
+    CreateAccountResponse,
     CreateBurnTransactionRequest,
     CreateBurnTransactionResponse,
+    CreateInvoiceRequest,
+    CreateInvoiceResponse,
+    CreateScheduledPaymentRequest,
+    CreateSubaddressRequest,
+    CreateSubaddressResponse,
     CreateTemplateRegistrationRequest,
     CreateTemplateRegistrationResponse,
+    EstimateFeeRequest,
+    EstimateFeeResponse,
     GetAddressResponse,
     GetBalanceRequest,
     GetBalanceResponse,
+    GetBatchPayoutRequest,
+    GetBurntProofRequest,
+    GetBurntProofResponse,
     GetCompletedTransactionsRequest,
     GetCompletedTransactionsResponse,
     GetConnectivityRequest,
     GetIdentityRequest,
     GetIdentityResponse,
+    GetInvoiceRequest,
+    GetInvoiceResponse,
+    GetSeedWordsRequest,
+    GetSeedWordsResponse,
     GetTransactionInfoRequest,
     GetTransactionInfoResponse,
     GetUnspentAmountsResponse,
@@ -59,14 +85,39 @@ use minotari_app_grpc::tari_rpc::{
     GetVersionResponse,
     ImportUtxosRequest,
     ImportUtxosResponse,
+    ListAccountsResponse,
+    ListBurntProofsRequest,
+    ListBurntProofsResponse,
+    ListContactsRequest,
+    ListContactsResponse,
+    ListInvoicesResponse,
+    ListScheduledPaymentsResponse,
+    ListSubaddressesResponse,
+    ListUtxosRequest,
+    ListUtxosResponse,
+    NumConfirmationsRequiredResponse,
+    PageInfo,
+    PageRequest,
+    PreviewCoinJoinRequest,
+    PreviewCoinJoinResponse,
+    PreviewConsolidationRequest,
+    PreviewConsolidationResponse,
     RegisterValidatorNodeRequest,
     RegisterValidatorNodeResponse,
     RevalidateRequest,
     RevalidateResponse,
+    RunConsolidationResponse,
+    ScheduledPaymentRequest,
+    ScheduledPaymentResponse,
     SendShaAtomicSwapRequest,
     SendShaAtomicSwapResponse,
     SetBaseNodeRequest,
     SetBaseNodeResponse,
+    SetNumConfirmationsRequiredRequest,
+    SetSubaddressLabelRequest,
+    SetSubaddressLabelResponse,
+    SignMessageRequest,
+    SignMessageResponse,
     TransactionDirection,
     TransactionEvent,
     TransactionEventRequest,
@@ -76,25 +127,45 @@ use minotari_app_grpc::tari_rpc::{
     TransferRequest,
     TransferResponse,
     TransferResult,
+    UpsertContactRequest,
+    UtxoSorting,
     ValidateRequest,
     ValidateResponse,
+    VerifyMessageSignatureRequest,
+    VerifyMessageSignatureResponse,
+    WalletUtxo,
 };
 use minotari_wallet::{
+    accounts::{AccountId, WalletAccount},
     connectivity_service::{OnlineStatus, WalletConnectivityInterface},
     error::WalletStorageError,
-    output_manager_service::{handle::OutputManagerHandle, UtxoSelectionCriteria},
+    output_manager_service::{
+        consolidation::{ConsolidationOutcome, ConsolidationPreview},
+        handle::OutputManagerHandle,
+        storage::{
+            database::{OutputBackendQuery, SortDirection},
+            models::{DbWalletOutput, Subaddress},
+            OutputStatus,
+        },
+        UtxoSelectionCriteria,
+    },
     transaction_service::{
+        batch_payout::{batch_id_from_recipients, BatchPayoutRecipient},
         handle::TransactionServiceHandle,
-        storage::models::{self, WalletTransaction},
+        scheduled_payment::PaymentCadence,
+        storage::models::{self, BatchPayoutRow, Invoice, ScheduledPayment, WalletTransaction},
     },
+    wallet::WalletMessageSigningDomain,
     WalletSqlite,
 };
 use tari_common_types::{
     tari_address::TariAddress,
+    tari_subaddress::TariSubaddress,
     transaction::TxId,
-    types::{BlockHash, PublicKey, Signature},
+    types::{BlockHash, Commitment, PrivateKey, PublicKey, Signature, SignatureWithDomain},
 };
 use tari_comms::{multiaddr::Multiaddr, types::CommsPublicKey, CommsNode};
+use tari_contacts::contacts_service::types::Contact;
 use tari_core::{
     consensus::{ConsensusBuilderError, ConsensusConstants, ConsensusManager},
     transactions::{
@@ -108,14 +179,39 @@ use tari_core::{
         },
     },
 };
+use tari_key_manager::mnemonic::MnemonicLanguage;
 use tari_script::script;
-use tari_utilities::{hex::Hex, ByteArray};
+use tari_utilities::{hex::Hex, ByteArray, SafePassword};
 use tokio::{sync::broadcast, task};
 use tonic::{Request, Response, Status};
 
 use crate::{
-    grpc::{convert_to_transaction_event, TransactionWrapper},
-    notifier::{CANCELLED, CONFIRMATION, MINED, NEW_BLOCK_MINED, QUEUED, RECEIVED, SENT},
+    grpc::{
+        convert_to_transaction_event,
+        error::{
+            contacts_error_to_status,
+            output_manager_error_to_status,
+            transaction_service_error_to_status,
+            wallet_error_to_status,
+            wallet_storage_error_to_status,
+        },
+        TransactionWrapper,
+    },
+    notifier::{
+        CANCELLED,
+        CONFIRMATION,
+        INVOICE_EXPIRED,
+        INVOICE_PAID,
+        INVOICE_UNDERPAID,
+        MINED,
+        NEW_BLOCK_MINED,
+        QUEUED,
+        RECEIVED,
+        SCHEDULED_PAYMENT_COMPLETED,
+        SCHEDULED_PAYMENT_FAILED,
+        SCHEDULED_PAYMENT_SENT,
+        SENT,
+    },
 };
 
 const LOG_TARGET: &str = "wallet::ui::grpc";
@@ -154,6 +250,46 @@ impl WalletGrpcServer {
         self.wallet.output_manager_service.clone()
     }
 
+    /// Looks up the account with the given name. An empty name refers to no particular account.
+    fn get_account_id(&self, name: &str) -> Result<Option<AccountId>, Status> {
+        if name.is_empty() {
+            return Ok(None);
+        }
+        let account = self.wallet.get_account_by_name(name).map_err(wallet_error_to_status)?;
+        Ok(Some(account.id))
+    }
+
+    async fn convert_account(&self, account: WalletAccount) -> Result<tari_rpc::WalletAccount, Status> {
+        let address = self
+            .wallet
+            .get_account_address(account.id)
+            .await
+            .map_err(wallet_error_to_status)?;
+        let balance = self
+            .get_output_manager_service()
+            .get_account_balance(account.id)
+            .await
+            .map_err(output_manager_error_to_status)?;
+        Ok(tari_rpc::WalletAccount {
+            id: u64::from(account.id.as_u32()),
+            name: account.name,
+            address: address.to_bytes().to_vec(),
+            available_balance: balance.available_balance.as_u64(),
+        })
+    }
+
+    fn convert_subaddress(&self, subaddress: Subaddress) -> tari_rpc::Subaddress {
+        tari_rpc::Subaddress {
+            index: subaddress.index,
+            address: subaddress
+                .to_address(self.wallet.network.as_network())
+                .to_bytes()
+                .to_vec(),
+            created_at: subaddress.created_at.timestamp() as u64,
+            label: subaddress.label,
+        }
+    }
+
     fn comms(&self) -> &CommsNode {
         &self.wallet.comms
     }
@@ -252,16 +388,21 @@ impl wallet_server::Wallet for WalletGrpcServer {
         wallet
             .set_base_node_peer(public_key.clone(), net_address.clone())
             .await
-            .map_err(|e| Status::internal(format!("{:?}", e)))?;
+            .map_err(wallet_error_to_status)?;
 
         Ok(Response::new(SetBaseNodeResponse {}))
     }
 
-    async fn get_balance(&self, _request: Request<GetBalanceRequest>) -> Result<Response<GetBalanceResponse>, Status> {
+    async fn get_balance(&self, request: Request<GetBalanceRequest>) -> Result<Response<GetBalanceResponse>, Status> {
+        let account = self.get_account_id(&request.into_inner().account)?;
         let mut output_service = self.get_output_manager_service();
-        let balance = match output_service.get_balance().await {
+        let balance = match account {
+            Some(account) => output_service.get_account_balance(account).await,
+            None => output_service.get_balance().await,
+        };
+        let balance = match balance {
             Ok(b) => b,
-            Err(e) => return Err(Status::not_found(format!("GetBalance error! {}", e))),
+            Err(e) => return Err(output_manager_error_to_status(e)),
         };
         Ok(Response::new(GetBalanceResponse {
             available_balance: balance
@@ -281,7 +422,7 @@ impl wallet_server::Wallet for WalletGrpcServer {
         let mut output_service = self.get_output_manager_service();
         let unspent_amounts = match output_service.get_unspent_outputs().await {
             Ok(uo) => uo,
-            Err(e) => return Err(Status::not_found(format!("GetUnspentAmounts error! {}", e))),
+            Err(e) => return Err(output_manager_error_to_status(e)),
         };
         Ok(Response::new(GetUnspentAmountsResponse {
             amount: unspent_amounts
@@ -300,12 +441,12 @@ impl wallet_server::Wallet for WalletGrpcServer {
         output_service
             .revalidate_all_outputs()
             .await
-            .map_err(|e| Status::unknown(e.to_string()))?;
+            .map_err(output_manager_error_to_status)?;
         let mut tx_service = self.get_transaction_service();
         tx_service
             .revalidate_all_transactions()
             .await
-            .map_err(|e| Status::unknown(e.to_string()))?;
+            .map_err(transaction_service_error_to_status)?;
         Ok(Response::new(RevalidateResponse {}))
     }
 
@@ -317,12 +458,12 @@ impl wallet_server::Wallet for WalletGrpcServer {
         output_service
             .validate_txos()
             .await
-            .map_err(|e| Status::unknown(e.to_string()))?;
+            .map_err(output_manager_error_to_status)?;
         let mut tx_service = self.get_transaction_service();
         tx_service
             .validate_transactions()
             .await
-            .map_err(|e| Status::unknown(e.to_string()))?;
+            .map_err(transaction_service_error_to_status)?;
         Ok(Response::new(ValidateResponse {}))
     }
 
@@ -495,11 +636,30 @@ impl wallet_server::Wallet for WalletGrpcServer {
             .into_iter()
             .enumerate()
             .map(|(idx, dest)| -> Result<_, String> {
-                let address = TariAddress::from_hex(&dest.address)
-                    .map_err(|_| format!("Destination address at index {} is malformed", idx))?;
+                let (address, subaddress) = match TariAddress::from_hex(&dest.address) {
+                    Ok(address) => (address, None),
+                    Err(_) => {
+                        let subaddress = TariSubaddress::from_hex(&dest.address)
+                            .map_err(|_| format!("Destination address at index {} is malformed", idx))?;
+                        if dest.payment_type != PaymentType::OneSidedToStealthAddress as i32 {
+                            return Err(format!(
+                                "Destination subaddress at index {} can only receive one-sided stealth payments",
+                                idx
+                            ));
+                        }
+                        (subaddress.to_tari_address(), Some(subaddress))
+                    },
+                };
+                let selection_criteria = match self.get_account_id(&dest.from_account) {
+                    Ok(Some(account)) => UtxoSelectionCriteria::default().for_account(account),
+                    Ok(None) => UtxoSelectionCriteria::default(),
+                    Err(e) => return Err(e.message().to_string()),
+                };
                 Ok((
                     dest.address,
                     address,
+                    subaddress,
+                    selection_criteria,
                     dest.amount,
                     dest.fee_per_gram,
                     dest.message,
@@ -510,7 +670,9 @@ impl wallet_server::Wallet for WalletGrpcServer {
             .map_err(Status::invalid_argument)?;
 
         let mut transfers = Vec::new();
-        for (hex_address, address, amount, fee_per_gram, message, payment_type) in recipients {
+        for (hex_address, address, subaddress, selection_criteria, amount, fee_per_gram, message, payment_type) in
+            recipients
+        {
             let mut transaction_service = self.get_transaction_service();
             transfers.push(async move {
                 (
@@ -520,7 +682,7 @@ impl wallet_server::Wallet for WalletGrpcServer {
                             .send_transaction(
                                 address,
                                 amount.into(),
-                                UtxoSelectionCriteria::default(),
+                                selection_criteria,
                                 OutputFeatures::default(),
                                 fee_per_gram.into(),
                                 message,
@@ -531,7 +693,18 @@ impl wallet_server::Wallet for WalletGrpcServer {
                             .send_one_sided_transaction(
                                 address,
                                 amount.into(),
-                                UtxoSelectionCriteria::default(),
+                                selection_criteria,
+                                OutputFeatures::default(),
+                                fee_per_gram.into(),
+                                message,
+                            )
+                            .await
+                    } else if let Some(subaddress) = subaddress {
+                        transaction_service
+                            .send_one_sided_to_stealth_subaddress_transaction(
+                                subaddress,
+                                amount.into(),
+                                selection_criteria,
                                 OutputFeatures::default(),
                                 fee_per_gram.into(),
                                 message,
@@ -542,7 +715,7 @@ impl wallet_server::Wallet for WalletGrpcServer {
                             .send_one_sided_to_stealth_address_transaction(
                                 address,
                                 amount.into(),
-                                UtxoSelectionCriteria::default(),
+                                selection_criteria,
                                 OutputFeatures::default(),
                                 fee_per_gram.into(),
                                 message,
@@ -652,7 +825,7 @@ impl wallet_server::Wallet for WalletGrpcServer {
         let transactions = future::try_join_all(queries)
             .await
             .map(|tx| tx.into_iter())
-            .map_err(|err| Status::unknown(err.to_string()))?;
+            .map_err(transaction_service_error_to_status)?;
 
         let wallet_pk = self.wallet.comms.node_identity_ref().public_key();
         let wallet_network = self.wallet.network.as_network();
@@ -724,6 +897,26 @@ impl wallet_server::Wallet for WalletGrpcServer {
                                             let event = if is_sent { SENT } else { QUEUED };
                                             handle_pending_outbound(tx_id, event, &mut transaction_service, &mut sender).await;
                                         },
+                                        InvoicePaid { payment_id, tx_id } => handle_invoice_payment(tx_id, payment_id, INVOICE_PAID, &mut transaction_service, &mut sender).await,
+                                        InvoiceUnderpaid { payment_id, tx_id } => handle_invoice_payment(tx_id, payment_id, INVOICE_UNDERPAID, &mut transaction_service, &mut sender).await,
+                                        InvoiceExpired(payment_id) => {
+                                            let mut transaction_event = simple_event(INVOICE_EXPIRED);
+                                            transaction_event.payment_id = payment_id;
+                                            send_transaction_event(transaction_event, &mut sender).await;
+                                        },
+                                        ScheduledPaymentSent { schedule_id, tx_id } => handle_scheduled_payment(tx_id, schedule_id, &mut transaction_service, &mut sender).await,
+                                        ScheduledPaymentFailed { schedule_id, error, will_retry } => {
+                                            let mut transaction_event = simple_event(SCHEDULED_PAYMENT_FAILED);
+                                            transaction_event.schedule_id = schedule_id;
+                                            transaction_event.status = if will_retry { "retrying" } else { "skipped" }.to_string();
+                                            transaction_event.message = error;
+                                            send_transaction_event(transaction_event, &mut sender).await;
+                                        },
+                                        ScheduledPaymentCompleted(schedule_id) => {
+                                            let mut transaction_event = simple_event(SCHEDULED_PAYMENT_COMPLETED);
+                                            transaction_event.schedule_id = schedule_id;
+                                            send_transaction_event(transaction_event, &mut sender).await;
+                                        },
                                         TransactionValidationStateChanged(_t_operation_id) => {
                                             send_transaction_event(simple_event("unknown"), &mut sender).await;
                                         },
@@ -748,21 +941,35 @@ impl wallet_server::Wallet for WalletGrpcServer {
 
     async fn get_completed_transactions(
         &self,
-        _request: Request<GetCompletedTransactionsRequest>,
+        request: Request<GetCompletedTransactionsRequest>,
     ) -> Result<Response<Self::GetCompletedTransactionsStream>, Status> {
         debug!(
             target: LOG_TARGET,
             "Incoming GRPC request for GetAllCompletedTransactions"
         );
+        let request = request.into_inner();
+        let account = self.get_account_id(&request.account)?;
         let mut transaction_service = self.get_transaction_service();
-        let transactions = transaction_service
-            .get_completed_transactions()
-            .await
-            .map_err(|err| Status::not_found(format!("No completed transactions found: {:?}", err)))?;
+        let page = request.page.unwrap_or_default();
+        let transactions = match (page_bounds(&page), account) {
+            (Some((offset, limit)), account) => transaction_service
+                .get_completed_transactions_page(account, offset.unsigned_abs(), limit.unsigned_abs())
+                .await
+                .map(|(transactions, _)| transactions),
+            (None, Some(account)) => transaction_service
+                .get_account_completed_transactions(account)
+                .await
+                .map(|transactions| transactions.into_values().collect()),
+            (None, None) => transaction_service
+                .get_completed_transactions()
+                .await
+                .map(|transactions| transactions.into_values().collect()),
+        };
+        let transactions = transactions.map_err(transaction_service_error_to_status)?;
 
         let (mut sender, receiver) = mpsc::channel(transactions.len());
         task::spawn(async move {
-            for (_, txn) in transactions {
+            for txn in transactions {
                 let response = GetCompletedTransactionsResponse {
                     transaction: Some(TransactionInfo {
                         tx_id: txn.tx_id.into(),
@@ -817,7 +1024,7 @@ impl wallet_server::Wallet for WalletGrpcServer {
                 message.message,
             )
             .await
-            .map_err(|e| Status::internal(format!("{:?}", e)))?;
+            .map_err(wallet_error_to_status)?;
 
         Ok(Response::new(CoinSplitResponse { tx_id: tx_id.into() }))
     }
@@ -847,7 +1054,7 @@ impl wallet_server::Wallet for WalletGrpcServer {
                         "Imported via gRPC".to_string(),
                     )
                     .await
-                    .map_err(|e| Status::internal(format!("{:?}", e)))?
+                    .map_err(wallet_error_to_status)?
                     .into(),
             );
         }
@@ -962,14 +1169,14 @@ impl wallet_server::Wallet for WalletGrpcServer {
                 ..Default::default()
             })
             .await
-            .map_err(|e| Status::internal(e.to_string()))?;
+            .map_err(output_manager_error_to_status)?;
 
         output = output.with_script(script![Nop]);
 
         let (tx_id, transaction) = output_manager
             .create_send_to_self_with_output(vec![output], fee_per_gram.into(), UtxoSelectionCriteria::default())
             .await
-            .map_err(|e| Status::internal(e.to_string()))?;
+            .map_err(output_manager_error_to_status)?;
 
         debug!(
             target: LOG_TARGET,
@@ -987,7 +1194,7 @@ impl wallet_server::Wallet for WalletGrpcServer {
         transaction_service
             .submit_transaction(tx_id, transaction, 0.into(), message)
             .await
-            .map_err(|e| Status::internal(e.to_string()))?;
+            .map_err(transaction_service_error_to_status)?;
 
         Ok(Response::new(CreateTemplateRegistrationResponse {
             tx_id: tx_id.as_u64(),
@@ -1041,6 +1248,699 @@ impl wallet_server::Wallet for WalletGrpcServer {
         };
         Ok(Response::new(response))
     }
+
+    async fn create_account(
+        &self,
+        request: Request<CreateAccountRequest>,
+    ) -> Result<Response<CreateAccountResponse>, Status> {
+        let request = request.into_inner();
+        if request.name.is_empty() {
+            return Err(Status::invalid_argument("Account name must not be empty"));
+        }
+        let mut wallet = self.wallet.clone();
+        let account = wallet
+            .create_account(&request.name)
+            .await
+            .map_err(wallet_error_to_status)?;
+        Ok(Response::new(CreateAccountResponse {
+            account: Some(self.convert_account(account).await?),
+        }))
+    }
+
+    async fn list_accounts(&self, _: Request<tari_rpc::Empty>) -> Result<Response<ListAccountsResponse>, Status> {
+        let accounts = self.wallet.get_accounts().map_err(wallet_error_to_status)?;
+        let mut result = Vec::with_capacity(accounts.len());
+        for account in accounts {
+            result.push(self.convert_account(account).await?);
+        }
+        Ok(Response::new(ListAccountsResponse { accounts: result }))
+    }
+
+    async fn create_invoice(
+        &self,
+        request: Request<CreateInvoiceRequest>,
+    ) -> Result<Response<CreateInvoiceResponse>, Status> {
+        let request = request.into_inner();
+        let expires_at = if request.expires_at == 0 {
+            None
+        } else {
+            let timestamp = i64::try_from(request.expires_at)
+                .ok()
+                .and_then(|secs| NaiveDateTime::from_timestamp_opt(secs, 0))
+                .ok_or_else(|| Status::invalid_argument("Invalid expiry timestamp"))?;
+            Some(timestamp)
+        };
+        let invoice = self
+            .get_transaction_service()
+            .create_invoice(request.payment_id, request.amount.into(), request.message, expires_at)
+            .await
+            .map_err(transaction_service_error_to_status)?;
+        Ok(Response::new(CreateInvoiceResponse {
+            invoice: Some(convert_invoice(invoice)),
+        }))
+    }
+
+    async fn get_invoice(&self, request: Request<GetInvoiceRequest>) -> Result<Response<GetInvoiceResponse>, Status> {
+        let invoice = self
+            .get_transaction_service()
+            .get_invoice(request.into_inner().payment_id)
+            .await
+            .map_err(transaction_service_error_to_status)?;
+        Ok(Response::new(GetInvoiceResponse {
+            invoice: Some(convert_invoice(invoice)),
+        }))
+    }
+
+    async fn list_invoices(&self, _: Request<tari_rpc::Empty>) -> Result<Response<ListInvoicesResponse>, Status> {
+        let invoices = self
+            .get_transaction_service()
+            .get_invoices()
+            .await
+            .map_err(transaction_service_error_to_status)?;
+        Ok(Response::new(ListInvoicesResponse {
+            invoices: invoices.into_iter().map(convert_invoice).collect(),
+        }))
+    }
+
+    async fn cancel_invoice(
+        &self,
+        request: Request<CancelInvoiceRequest>,
+    ) -> Result<Response<CancelInvoiceResponse>, Status> {
+        self.get_transaction_service()
+            .cancel_invoice(request.into_inner().payment_id)
+            .await
+            .map_err(transaction_service_error_to_status)?;
+        Ok(Response::new(CancelInvoiceResponse {}))
+    }
+
+    async fn create_subaddress(
+        &self,
+        request: Request<CreateSubaddressRequest>,
+    ) -> Result<Response<CreateSubaddressResponse>, Status> {
+        let subaddress = self
+            .get_output_manager_service()
+            .create_subaddress(request.into_inner().label)
+            .await
+            .map_err(output_manager_error_to_status)?;
+        Ok(Response::new(CreateSubaddressResponse {
+            subaddress: Some(self.convert_subaddress(subaddress)),
+        }))
+    }
+
+    async fn list_subaddresses(
+        &self,
+        _: Request<tari_rpc::Empty>,
+    ) -> Result<Response<ListSubaddressesResponse>, Status> {
+        let subaddresses = self
+            .get_output_manager_service()
+            .get_subaddresses()
+            .await
+            .map_err(output_manager_error_to_status)?;
+        Ok(Response::new(ListSubaddressesResponse {
+            subaddresses: subaddresses
+                .into_iter()
+                .map(|subaddress| self.convert_subaddress(subaddress))
+                .collect(),
+        }))
+    }
+
+    async fn set_subaddress_label(
+        &self,
+        request: Request<SetSubaddressLabelRequest>,
+    ) -> Result<Response<SetSubaddressLabelResponse>, Status> {
+        let request = request.into_inner();
+        self.get_output_manager_service()
+            .set_subaddress_label(request.index, request.label)
+            .await
+            .map_err(output_manager_error_to_status)?;
+        Ok(Response::new(SetSubaddressLabelResponse {}))
+    }
+
+    async fn batch_payout(
+        &self,
+        request: Request<BatchPayoutRequest>,
+    ) -> Result<Response<BatchPayoutResponse>, Status> {
+        let request = request.into_inner();
+        let mut recipients = Vec::with_capacity(request.recipients.len());
+        let mut errors = Vec::new();
+        for (idx, recipient) in request.recipients.into_iter().enumerate() {
+            match recipient.address.parse::<TariAddress>() {
+                Ok(address) => recipients.push(BatchPayoutRecipient::new(
+                    address,
+                    recipient.amount.into(),
+                    recipient.memo,
+                )),
+                Err(_) => errors.push(format!("Recipient address at index {} is malformed", idx)),
+            }
+        }
+        if !errors.is_empty() {
+            return Err(Status::invalid_argument(errors.join(", ")));
+        }
+        let selection_criteria = match self.get_account_id(&request.from_account)? {
+            Some(account) => UtxoSelectionCriteria::default().for_account(account),
+            None => UtxoSelectionCriteria::default(),
+        };
+        let batch_id = if request.batch_id.is_empty() {
+            batch_id_from_recipients(&recipients)
+        } else {
+            request.batch_id
+        };
+        let rows = self
+            .get_transaction_service()
+            .send_batch_payout(
+                Some(batch_id.clone()),
+                recipients,
+                selection_criteria,
+                request.fee_per_gram.into(),
+            )
+            .await
+            .map_err(transaction_service_error_to_status)?;
+        Ok(Response::new(BatchPayoutResponse {
+            batch_id,
+            rows: rows.into_iter().map(convert_batch_payout_row).collect(),
+        }))
+    }
+
+    async fn get_batch_payout(
+        &self,
+        request: Request<GetBatchPayoutRequest>,
+    ) -> Result<Response<BatchPayoutResponse>, Status> {
+        let batch_id = request.into_inner().batch_id;
+        let rows = self
+            .get_transaction_service()
+            .get_batch_payout(batch_id.clone())
+            .await
+            .map_err(transaction_service_error_to_status)?;
+        if rows.is_empty() {
+            return Err(Status::not_found(format!("Batch payout `{}` not found", batch_id)));
+        }
+        Ok(Response::new(BatchPayoutResponse {
+            batch_id,
+            rows: rows.into_iter().map(convert_batch_payout_row).collect(),
+        }))
+    }
+
+    async fn create_scheduled_payment(
+        &self,
+        request: Request<CreateScheduledPaymentRequest>,
+    ) -> Result<Response<ScheduledPaymentResponse>, Status> {
+        let request = request.into_inner();
+        let address = request
+            .address
+            .parse::<TariAddress>()
+            .map_err(|_| Status::invalid_argument("Destination address is malformed"))?;
+        let cadence = request
+            .cadence
+            .parse::<PaymentCadence>()
+            .map_err(|e| Status::invalid_argument(e.to_string()))?;
+        let now = Utc::now().naive_utc();
+        let start_at = timestamp_from_request(request.start_at, "start")?.unwrap_or(now);
+        let end_at = timestamp_from_request(request.end_at, "end")?;
+        let account_id = self.get_account_id(&request.from_account)?.unwrap_or_default();
+        let payment = self
+            .get_transaction_service()
+            .create_scheduled_payment(ScheduledPayment::new(
+                request.schedule_id,
+                address,
+                request.amount.into(),
+                request.message,
+                account_id,
+                request.fee_per_gram.into(),
+                cadence,
+                start_at,
+                end_at,
+                Some(request.max_payments).filter(|max| *max > 0),
+                now,
+            ))
+            .await
+            .map_err(transaction_service_error_to_status)?;
+        Ok(Response::new(ScheduledPaymentResponse {
+            scheduled_payment: Some(convert_scheduled_payment(payment)),
+        }))
+    }
+
+    async fn get_scheduled_payment(
+        &self,
+        request: Request<ScheduledPaymentRequest>,
+    ) -> Result<Response<ScheduledPaymentResponse>, Status> {
+        let payment = self
+            .get_transaction_service()
+            .get_scheduled_payment(request.into_inner().schedule_id)
+            .await
+            .map_err(transaction_service_error_to_status)?;
+        Ok(Response::new(ScheduledPaymentResponse {
+            scheduled_payment: Some(convert_scheduled_payment(payment)),
+        }))
+    }
+
+    async fn list_scheduled_payments(
+        &self,
+        _: Request<tari_rpc::Empty>,
+    ) -> Result<Response<ListScheduledPaymentsResponse>, Status> {
+        let payments = self
+            .get_transaction_service()
+            .get_scheduled_payments()
+            .await
+            .map_err(transaction_service_error_to_status)?;
+        Ok(Response::new(ListScheduledPaymentsResponse {
+            scheduled_payments: payments.into_iter().map(convert_scheduled_payment).collect(),
+        }))
+    }
+
+    async fn pause_scheduled_payment(
+        &self,
+        request: Request<ScheduledPaymentRequest>,
+    ) -> Result<Response<ScheduledPaymentResponse>, Status> {
+        let payment = self
+            .get_transaction_service()
+            .pause_scheduled_payment(request.into_inner().schedule_id)
+            .await
+            .map_err(transaction_service_error_to_status)?;
+        Ok(Response::new(ScheduledPaymentResponse {
+            scheduled_payment: Some(convert_scheduled_payment(payment)),
+        }))
+    }
+
+    async fn resume_scheduled_payment(
+        &self,
+        request: Request<ScheduledPaymentRequest>,
+    ) -> Result<Response<ScheduledPaymentResponse>, Status> {
+        let payment = self
+            .get_transaction_service()
+            .resume_scheduled_payment(request.into_inner().schedule_id)
+            .await
+            .map_err(transaction_service_error_to_status)?;
+        Ok(Response::new(ScheduledPaymentResponse {
+            scheduled_payment: Some(convert_scheduled_payment(payment)),
+        }))
+    }
+
+    async fn cancel_scheduled_payment(
+        &self,
+        request: Request<ScheduledPaymentRequest>,
+    ) -> Result<Response<ScheduledPaymentResponse>, Status> {
+        let payment = self
+            .get_transaction_service()
+            .cancel_scheduled_payment(request.into_inner().schedule_id)
+            .await
+            .map_err(transaction_service_error_to_status)?;
+        Ok(Response::new(ScheduledPaymentResponse {
+            scheduled_payment: Some(convert_scheduled_payment(payment)),
+        }))
+    }
+
+    async fn preview_consolidation(
+        &self,
+        request: Request<PreviewConsolidationRequest>,
+    ) -> Result<Response<PreviewConsolidationResponse>, Status> {
+        let fee_per_gram = match request.into_inner().fee_per_gram {
+            0 => None,
+            fee_per_gram => Some(MicroMinotari::from(fee_per_gram)),
+        };
+        let previews = self
+            .get_output_manager_service()
+            .preview_consolidation(fee_per_gram)
+            .await
+            .map_err(output_manager_error_to_status)?;
+        Ok(Response::new(PreviewConsolidationResponse {
+            previews: previews.into_iter().map(convert_consolidation_preview).collect(),
+        }))
+    }
+
+    async fn get_consolidation_status(
+        &self,
+        _: Request<tari_rpc::Empty>,
+    ) -> Result<Response<ConsolidationStatusResponse>, Status> {
+        let status = self
+            .get_output_manager_service()
+            .get_consolidation_status()
+            .await
+            .map_err(output_manager_error_to_status)?;
+        Ok(Response::new(ConsolidationStatusResponse {
+            enabled: status.enabled,
+            max_utxo_count: status.policy.max_utxo_count as u64,
+            dust_threshold: status.policy.dust_threshold.as_u64(),
+            max_fee_per_gram: status.policy.max_fee_per_gram.as_u64(),
+            max_fee: status.policy.max_fee.as_u64(),
+            last_run: status.last_run.map(|t| t.timestamp() as u64).unwrap_or_default(),
+            last_outcome: status.last_outcome.map(convert_consolidation_outcome),
+            transactions_created: status.transactions_created,
+            outputs_consolidated: status.outputs_consolidated,
+            fees_paid: status.fees_paid.as_u64(),
+        }))
+    }
+
+    async fn run_consolidation(
+        &self,
+        _: Request<tari_rpc::Empty>,
+    ) -> Result<Response<RunConsolidationResponse>, Status> {
+        let outcome = self
+            .get_output_manager_service()
+            .run_consolidation()
+            .await
+            .map_err(output_manager_error_to_status)?;
+        Ok(Response::new(RunConsolidationResponse {
+            outcome: Some(convert_consolidation_outcome(outcome)),
+        }))
+    }
+
+    async fn coin_join(&self, request: Request<CoinJoinRequest>) -> Result<Response<CoinJoinResponse>, Status> {
+        let request = request.into_inner();
+        let commitments = commitments_from_request(request.commitments)?;
+        let message = Some(request.message).filter(|message| !message.is_empty());
+        let tx_id = self
+            .wallet
+            .clone()
+            .coin_join(commitments, request.fee_per_gram.into(), message)
+            .await
+            .map_err(wallet_error_to_status)?;
+        Ok(Response::new(CoinJoinResponse { tx_id: tx_id.as_u64() }))
+    }
+
+    async fn preview_coin_join(
+        &self,
+        request: Request<PreviewCoinJoinRequest>,
+    ) -> Result<Response<PreviewCoinJoinResponse>, Status> {
+        let request = request.into_inner();
+        let commitments = commitments_from_request(request.commitments)?;
+        let (expected_outputs, fee) = self
+            .get_output_manager_service()
+            .preview_coin_join_with_commitments(commitments, request.fee_per_gram.into())
+            .await
+            .map_err(output_manager_error_to_status)?;
+        Ok(Response::new(PreviewCoinJoinResponse {
+            expected_outputs: expected_outputs.into_iter().map(|value| value.as_u64()).collect(),
+            fee: fee.as_u64(),
+        }))
+    }
+
+    async fn estimate_fee(
+        &self,
+        request: Request<EstimateFeeRequest>,
+    ) -> Result<Response<EstimateFeeResponse>, Status> {
+        let request = request.into_inner();
+        let selection_criteria = match self.get_account_id(&request.from_account)? {
+            Some(account) => UtxoSelectionCriteria::default().for_account(account),
+            None => UtxoSelectionCriteria::default(),
+        };
+        let fee = self
+            .get_output_manager_service()
+            .fee_estimate(
+                request.amount.into(),
+                selection_criteria,
+                request.fee_per_gram.into(),
+                request.num_kernels.max(1) as usize,
+                request.num_outputs.max(1) as usize,
+            )
+            .await
+            .map_err(output_manager_error_to_status)?;
+        Ok(Response::new(EstimateFeeResponse { fee: fee.as_u64() }))
+    }
+
+    async fn list_utxos(&self, request: Request<ListUtxosRequest>) -> Result<Response<ListUtxosResponse>, Status> {
+        let request = request.into_inner();
+        let status = request
+            .statuses
+            .into_iter()
+            .map(OutputStatus::try_from)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| Status::invalid_argument(e.to_string()))?;
+        let sorting = match UtxoSorting::from_i32(request.sorting) {
+            Some(UtxoSorting::ValueAsc) => ("value", SortDirection::Asc),
+            Some(UtxoSorting::ValueDesc) => ("value", SortDirection::Desc),
+            Some(UtxoSorting::MinedHeightAsc) => ("mined_height", SortDirection::Asc),
+            Some(UtxoSorting::MinedHeightDesc) => ("mined_height", SortDirection::Desc),
+            None => return Err(Status::invalid_argument("Invalid UTXO sorting")),
+        };
+        let value_bound = |value: u64| Some((i64::try_from(value).unwrap_or(i64::MAX), true)).filter(|_| value > 0);
+        let page = request.page.unwrap_or_default();
+        let query = OutputBackendQuery {
+            tip_height: i64::MAX,
+            status,
+            commitments: vec![],
+            pagination: page_bounds(&page),
+            value_min: value_bound(request.min_value),
+            value_max: value_bound(request.max_value),
+            sorting: vec![sorting],
+        };
+        let total = self
+            .wallet
+            .output_db
+            .count_outputs_by(&query)
+            .map_err(|e| output_manager_error_to_status(e.into()))?;
+        let outputs = self
+            .wallet
+            .output_db
+            .fetch_outputs_by(query)
+            .map_err(|e| output_manager_error_to_status(e.into()))?;
+        Ok(Response::new(ListUtxosResponse {
+            utxos: outputs.into_iter().map(convert_wallet_utxo).collect(),
+            page: Some(PageInfo {
+                offset: page.offset,
+                limit: page.limit,
+                total,
+            }),
+        }))
+    }
+
+    async fn sign_message(
+        &self,
+        request: Request<SignMessageRequest>,
+    ) -> Result<Response<SignMessageResponse>, Status> {
+        let message = request.into_inner().message;
+        let mut wallet = self.wallet.clone();
+        let identity = wallet.comms.node_identity();
+        let signature = wallet
+            .sign_message(identity.secret_key(), &message)
+            .map_err(|e| Status::internal(e.to_string()))?;
+        Ok(Response::new(SignMessageResponse {
+            signature: Some(tari_rpc::Signature {
+                public_nonce: signature.get_public_nonce().to_vec(),
+                signature: signature.get_signature().to_vec(),
+            }),
+            public_key: identity.public_key().to_vec(),
+        }))
+    }
+
+    async fn verify_message_signature(
+        &self,
+        request: Request<VerifyMessageSignatureRequest>,
+    ) -> Result<Response<VerifyMessageSignatureResponse>, Status> {
+        let request = request.into_inner();
+        let signature = request
+            .signature
+            .ok_or_else(|| Status::invalid_argument("Signature is missing"))?;
+        let public_nonce = PublicKey::from_canonical_bytes(&signature.public_nonce)
+            .map_err(|_| Status::invalid_argument("Signature public nonce is malformed"))?;
+        let signature = PrivateKey::from_canonical_bytes(&signature.signature)
+            .map_err(|_| Status::invalid_argument("Signature is malformed"))?;
+        let public_key = if request.public_key.is_empty() {
+            self.wallet.comms.node_identity().public_key().clone()
+        } else {
+            PublicKey::from_canonical_bytes(&request.public_key)
+                .map_err(|_| Status::invalid_argument("Public key is malformed"))?
+        };
+        let is_valid = self.wallet.clone().verify_message_signature(
+            &public_key,
+            &SignatureWithDomain::<WalletMessageSigningDomain>::new(public_nonce, signature),
+            &request.message,
+        );
+        Ok(Response::new(VerifyMessageSignatureResponse { is_valid }))
+    }
+
+    async fn upsert_contact(
+        &self,
+        request: Request<UpsertContactRequest>,
+    ) -> Result<Response<ContactResponse>, Status> {
+        let request = request.into_inner();
+        if request.alias.is_empty() {
+            return Err(Status::invalid_argument("Contact alias must not be empty"));
+        }
+        let address = contact_address_from_request(&request.address)?;
+        let mut contacts_service = self.wallet.contacts_service.clone();
+        contacts_service
+            .upsert_contact(Contact::new(
+                request.alias,
+                address.clone(),
+                None,
+                None,
+                request.favourite,
+            ))
+            .await
+            .map_err(contacts_error_to_status)?;
+        let contact = contacts_service
+            .get_contact(address)
+            .await
+            .map_err(contacts_error_to_status)?;
+        Ok(Response::new(ContactResponse {
+            contact: Some(convert_contact(contact)),
+        }))
+    }
+
+    async fn get_contact(&self, request: Request<ContactRequest>) -> Result<Response<ContactResponse>, Status> {
+        let address = contact_address_from_request(&request.into_inner().address)?;
+        let contact = self
+            .wallet
+            .contacts_service
+            .clone()
+            .get_contact(address)
+            .await
+            .map_err(contacts_error_to_status)?;
+        Ok(Response::new(ContactResponse {
+            contact: Some(convert_contact(contact)),
+        }))
+    }
+
+    async fn list_contacts(
+        &self,
+        request: Request<ListContactsRequest>,
+    ) -> Result<Response<ListContactsResponse>, Status> {
+        let contacts = self
+            .wallet
+            .contacts_service
+            .clone()
+            .get_contacts()
+            .await
+            .map_err(contacts_error_to_status)?;
+        let (contacts, page) = paginate(contacts, request.into_inner().page);
+        Ok(Response::new(ListContactsResponse {
+            contacts: contacts.into_iter().map(convert_contact).collect(),
+            page: Some(page),
+        }))
+    }
+
+    async fn remove_contact(&self, request: Request<ContactRequest>) -> Result<Response<ContactResponse>, Status> {
+        let address = contact_address_from_request(&request.into_inner().address)?;
+        let contact = self
+            .wallet
+            .contacts_service
+            .clone()
+            .remove_contact(address)
+            .await
+            .map_err(contacts_error_to_status)?;
+        Ok(Response::new(ContactResponse {
+            contact: Some(convert_contact(contact)),
+        }))
+    }
+
+    async fn get_seed_words(
+        &self,
+        request: Request<GetSeedWordsRequest>,
+    ) -> Result<Response<GetSeedWordsResponse>, Status> {
+        let request = request.into_inner();
+        // Hashing the passphrase is deliberately slow, so it must not hold up the other requests on this runtime
+        let db = self.wallet.db.clone();
+        let passphrase = SafePassword::from(request.passphrase);
+        task::spawn_blocking(move || db.verify_passphrase(&passphrase))
+            .await
+            .map_err(|e| Status::internal(e.to_string()))?
+            .map_err(wallet_storage_error_to_status)?;
+        let language = if request.language.is_empty() {
+            MnemonicLanguage::English
+        } else {
+            request
+                .language
+                .parse::<MnemonicLanguage>()
+                .map_err(|_| Status::invalid_argument(format!("Unsupported language `{}`", request.language)))?
+        };
+        let seed_words = self.wallet.get_seed_words(&language).map_err(wallet_error_to_status)?;
+        let words = (0..seed_words.len())
+            .map(|i| seed_words.get_word(i).map(Clone::clone))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| Status::internal(e.to_string()))?;
+        Ok(Response::new(GetSeedWordsResponse { words }))
+    }
+
+    async fn get_burnt_proof(
+        &self,
+        request: Request<GetBurntProofRequest>,
+    ) -> Result<Response<GetBurntProofResponse>, Status> {
+        let proof = self
+            .wallet
+            .db
+            .fetch_burnt_proof(request.into_inner().id)
+            .map_err(wallet_storage_error_to_status)?;
+        Ok(Response::new(GetBurntProofResponse {
+            proof: Some(convert_burnt_proof(proof)),
+        }))
+    }
+
+    async fn list_burnt_proofs(
+        &self,
+        request: Request<ListBurntProofsRequest>,
+    ) -> Result<Response<ListBurntProofsResponse>, Status> {
+        let proofs = self
+            .wallet
+            .db
+            .fetch_burnt_proofs()
+            .map_err(wallet_storage_error_to_status)?;
+        let (proofs, page) = paginate(proofs, request.into_inner().page);
+        Ok(Response::new(ListBurntProofsResponse {
+            proofs: proofs.into_iter().map(convert_burnt_proof).collect(),
+            page: Some(page),
+        }))
+    }
+
+    async fn get_num_confirmations_required(
+        &self,
+        _: Request<tari_rpc::Empty>,
+    ) -> Result<Response<NumConfirmationsRequiredResponse>, Status> {
+        let num_confirmations = self
+            .get_transaction_service()
+            .get_num_confirmations_required()
+            .await
+            .map_err(transaction_service_error_to_status)?;
+        Ok(Response::new(NumConfirmationsRequiredResponse { num_confirmations }))
+    }
+
+    async fn set_num_confirmations_required(
+        &self,
+        request: Request<SetNumConfirmationsRequiredRequest>,
+    ) -> Result<Response<NumConfirmationsRequiredResponse>, Status> {
+        let num_confirmations = request.into_inner().num_confirmations;
+        self.get_transaction_service()
+            .set_num_confirmations_required(num_confirmations)
+            .await
+            .map_err(transaction_service_error_to_status)?;
+        Ok(Response::new(NumConfirmationsRequiredResponse { num_confirmations }))
+    }
+}
+
+async fn handle_invoice_payment(
+    tx_id: TxId,
+    payment_id: String,
+    event: &str,
+    transaction_service: &mut TransactionServiceHandle,
+    sender: &mut Sender<Result<TransactionEventResponse, Status>>,
+) {
+    match transaction_service.get_completed_transaction(tx_id).await {
+        Ok(completed) => {
+            let mut transaction_event =
+                convert_to_transaction_event(event.to_string(), TransactionWrapper::Completed(Box::new(completed)));
+            transaction_event.payment_id = payment_id;
+            send_transaction_event(transaction_event, sender).await;
+        },
+        Err(e) => error!(target: LOG_TARGET, "Transaction service error: {}", e),
+    }
+}
+
+async fn handle_scheduled_payment(
+    tx_id: TxId,
+    schedule_id: String,
+    transaction_service: &mut TransactionServiceHandle,
+    sender: &mut Sender<Result<TransactionEventResponse, Status>>,
+) {
+    match transaction_service.get_completed_transaction(tx_id).await {
+        Ok(completed) => {
+            let mut transaction_event = convert_to_transaction_event(
+                SCHEDULED_PAYMENT_SENT.to_string(),
+                TransactionWrapper::Completed(Box::new(completed)),
+            );
+            transaction_event.schedule_id = schedule_id;
+            send_transaction_event(transaction_event, sender).await;
+        },
+        Err(e) => error!(target: LOG_TARGET, "Transaction service error: {}", e),
+    }
 }
 
 async fn handle_completed_tx(
@@ -1089,6 +1989,183 @@ fn simple_event(event: &str) -> TransactionEvent {
         direction: event.to_string(),
         amount: 0,
         message: String::default(),
+        payment_id: String::default(),
+        schedule_id: String::default(),
+    }
+}
+
+fn convert_invoice(invoice: Invoice) -> tari_rpc::Invoice {
+    let payment_request = invoice.to_payment_request().to_uri();
+    tari_rpc::Invoice {
+        payment_id: invoice.payment_id,
+        address: invoice.address.to_bytes().to_vec(),
+        amount: invoice.amount.as_u64(),
+        amount_received: invoice.amount_received.as_u64(),
+        message: invoice.message,
+        status: invoice.status.to_string(),
+        tx_id: invoice.tx_id.map(u64::from).unwrap_or_default(),
+        created_at: invoice.created_at.timestamp() as u64,
+        expires_at: invoice.expires_at.map(|t| t.timestamp() as u64).unwrap_or_default(),
+        paid_at: invoice.paid_at.map(|t| t.timestamp() as u64).unwrap_or_default(),
+        payment_request,
+    }
+}
+
+/// Converts an optional unix timestamp (in seconds) from a request, where 0 means that it was not set
+fn timestamp_from_request(secs: u64, name: &str) -> Result<Option<NaiveDateTime>, Status> {
+    if secs == 0 {
+        return Ok(None);
+    }
+    i64::try_from(secs)
+        .ok()
+        .and_then(|secs| NaiveDateTime::from_timestamp_opt(secs, 0))
+        .map(Some)
+        .ok_or_else(|| Status::invalid_argument(format!("Invalid {} timestamp", name)))
+}
+
+fn convert_scheduled_payment(payment: ScheduledPayment) -> tari_rpc::ScheduledPayment {
+    tari_rpc::ScheduledPayment {
+        schedule_id: payment.schedule_id,
+        address: payment.address.to_bytes().to_vec(),
+        amount: payment.amount.as_u64(),
+        message: payment.message,
+        account_id: payment.account_id.as_u32(),
+        fee_per_gram: payment.fee_per_gram.as_u64(),
+        cadence: payment.cadence.to_string(),
+        start_at: payment.start_at.timestamp() as u64,
+        next_run_at: payment.next_run_at.timestamp() as u64,
+        end_at: payment.end_at.map(|t| t.timestamp() as u64).unwrap_or_default(),
+        max_payments: payment.max_payments.unwrap_or_default(),
+        payments_made: payment.payments_made,
+        failed_attempts: payment.failed_attempts,
+        last_error: payment.last_error.unwrap_or_default(),
+        last_tx_id: payment.last_tx_id.map(u64::from).unwrap_or_default(),
+        status: payment.status.to_string(),
+        created_at: payment.created_at.timestamp() as u64,
+    }
+}
+
+fn convert_consolidation_preview(preview: ConsolidationPreview) -> tari_rpc::ConsolidationPreview {
+    tari_rpc::ConsolidationPreview {
+        commitments: preview.commitments.iter().map(|c| c.to_vec()).collect(),
+        input_value: preview.input_value.as_u64(),
+        fee: preview.fee.as_u64(),
+        output_value: preview.output_value.as_u64(),
+        fee_per_gram: preview.fee_per_gram.as_u64(),
+        account_id: u64::from(preview.account_id.as_u32()),
+    }
+}
+
+fn convert_consolidation_outcome(outcome: ConsolidationOutcome) -> tari_rpc::ConsolidationOutcome {
+    match outcome {
+        ConsolidationOutcome::Submitted(transactions) => tari_rpc::ConsolidationOutcome {
+            transactions: transactions
+                .into_iter()
+                .map(|transaction| tari_rpc::ConsolidationTransaction {
+                    tx_id: transaction.tx_id.into(),
+                    preview: Some(convert_consolidation_preview(transaction.preview)),
+                })
+                .collect(),
+            skip_reason: String::new(),
+        },
+        ConsolidationOutcome::Skipped(reason) => tari_rpc::ConsolidationOutcome {
+            transactions: Vec::new(),
+            skip_reason: reason.to_string(),
+        },
+    }
+}
+
+fn convert_batch_payout_row(row: BatchPayoutRow) -> tari_rpc::BatchPayoutRow {
+    tari_rpc::BatchPayoutRow {
+        index: row.index,
+        address: row.recipient.address.to_bytes().to_vec(),
+        amount: row.recipient.amount.as_u64(),
+        memo: row.recipient.memo,
+        status: row.status.to_string(),
+        tx_id: row.tx_id.map(u64::from).unwrap_or_default(),
+        error: row.error.unwrap_or_default(),
+        updated_at: row.updated_at.timestamp() as u64,
+    }
+}
+
+/// Returns the offset and limit to pass to a database query for the page, or `None` if the whole list was requested
+fn page_bounds(page: &PageRequest) -> Option<(i64, i64)> {
+    if page.offset == 0 && page.limit == 0 {
+        return None;
+    }
+    let offset = i64::try_from(page.offset).unwrap_or(i64::MAX);
+    let limit = match page.limit {
+        0 => i64::MAX,
+        limit => i64::try_from(limit).unwrap_or(i64::MAX),
+    };
+    Some((offset, limit))
+}
+
+/// Returns the requested page of the items, or all the items if no page was requested. Only use this for small lists
+/// that are already held in memory; large lists should be paged in the database query with [page_bounds].
+fn paginate<T>(items: Vec<T>, page: Option<PageRequest>) -> (Vec<T>, PageInfo) {
+    let page = page.unwrap_or_default();
+    let total = items.len() as u64;
+    let offset = usize::try_from(page.offset).unwrap_or(usize::MAX);
+    let limit = match page.limit {
+        0 => usize::MAX,
+        limit => usize::try_from(limit).unwrap_or(usize::MAX),
+    };
+    let items = items.into_iter().skip(offset).take(limit).collect();
+    (items, PageInfo {
+        offset: page.offset,
+        limit: page.limit,
+        total,
+    })
+}
+
+fn commitments_from_request(commitments: Vec<Vec<u8>>) -> Result<Vec<Commitment>, Status> {
+    commitments
+        .into_iter()
+        .enumerate()
+        .map(|(idx, bytes)| {
+            Commitment::from_canonical_bytes(&bytes)
+                .map_err(|_| Status::invalid_argument(format!("Commitment at index {} is malformed", idx)))
+        })
+        .collect()
+}
+
+fn contact_address_from_request(address: &str) -> Result<TariAddress, Status> {
+    address
+        .parse::<TariAddress>()
+        .map_err(|_| Status::invalid_argument("Contact address is malformed"))
+}
+
+fn convert_contact(contact: Contact) -> tari_rpc::Contact {
+    tari_rpc::Contact {
+        alias: contact.alias,
+        address: contact.address.to_bytes().to_vec(),
+        favourite: contact.favourite,
+        last_seen: contact.last_seen.map(|t| t.timestamp() as u64).unwrap_or_default(),
+        latency: contact.latency.unwrap_or_default(),
+    }
+}
+
+fn convert_wallet_utxo(output: DbWalletOutput) -> WalletUtxo {
+    WalletUtxo {
+        commitment: output.commitment.to_vec(),
+        value: output.wallet_output.value.as_u64(),
+        status: output.status as i32,
+        mined_height: output.mined_height.unwrap_or_default(),
+        mined_timestamp: output.mined_timestamp.map(|t| t.timestamp() as u64).unwrap_or_default(),
+        maturity: output.wallet_output.features.maturity,
+        account_id: output.account_id.as_u32(),
+    }
+}
+
+fn convert_burnt_proof(
+    (id, reciprocal_claim_public_key, payload, burned_at): (u32, String, String, NaiveDateTime),
+) -> tari_rpc::BurntProof {
+    tari_rpc::BurntProof {
+        id,
+        reciprocal_claim_public_key,
+        payload,
+        burned_at: burned_at.timestamp() as u64,
     }
 }
 
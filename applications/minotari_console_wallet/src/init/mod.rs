@@ -22,7 +22,13 @@
 
 #![allow(dead_code, unused)]
 
-use std::{fs, path::PathBuf, str::FromStr, sync::Arc, time::Instant};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::Arc,
+    time::Instant,
+};
 
 use log::*;
 use minotari_app_utilities::identity_management::setup_node_identity;
@@ -30,6 +36,7 @@ use minotari_wallet::{
     error::{WalletError, WalletStorageError},
     output_manager_service::storage::database::OutputManagerDatabase,
     storage::{
+        backup::WalletBackup,
         database::{WalletBackend, WalletDatabase},
         sqlite_utilities::initialize_sqlite_database_backends,
     },
@@ -263,6 +270,32 @@ pub async fn change_password(
     })
 }
 
+/// Writes an encrypted backup of the full wallet state to a file. The wallet password protects the backup.
+pub fn create_backup(config: &WalletConfig, backup_file: &Path, passphrase: &SafePassword) -> Result<(), ExitError> {
+    if !config.db_file.exists() {
+        return Err(ExitError::new(
+            ExitCode::WalletError,
+            format!("No wallet found at {:#?}", config.db_file),
+        ));
+    }
+    WalletBackup::create(&config.db_file, passphrase, config.network)?.write_to_file(backup_file)?;
+    println!("Wallet backup written to {}", backup_file.display());
+    Ok(())
+}
+
+/// Restores a wallet from an encrypted backup file into the configured data directory, which must not contain a
+/// wallet yet.
+pub fn restore_backup(config: &WalletConfig, backup_file: &Path, passphrase: &SafePassword) -> Result<(), ExitError> {
+    let backup = WalletBackup::read_from_file(backup_file)?;
+    backup.restore(&config.db_file, passphrase, config.network)?;
+    println!(
+        "Wallet restored from the backup of {} into {}. Start the wallet with the same password to continue.",
+        backup.created_at,
+        config.db_file.display()
+    );
+    Ok(())
+}
+
 /// Populates the PeerConfig struct from:
 /// 1. The custom peer in the wallet config if it exists
 /// 2. The custom peer in the wallet db if it exists
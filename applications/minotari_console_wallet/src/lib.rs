@@ -64,7 +64,15 @@ use tokio::runtime::Runtime;
 use wallet_modes::{command_mode, grpc_mode, recovery_mode, script_mode, tui_mode, WalletMode};
 
 pub use crate::config::ApplicationConfig;
-use crate::init::{boot_with_password, confirm_direct_only_send, confirm_seed_words, wallet_mode};
+use crate::init::{
+    boot_with_password,
+    confirm_direct_only_send,
+    confirm_seed_words,
+    create_backup,
+    get_or_prompt_password,
+    restore_backup,
+    wallet_mode,
+};
 
 pub const LOG_TARGET: &str = "wallet::console_wallet::main";
 
@@ -86,6 +94,8 @@ pub fn run_wallet(shutdown: &mut Shutdown, runtime: Runtime, config: &mut Applic
         },
         password: None,
         change_password: false,
+        create_backup: None,
+        restore_backup: None,
         recovery: false,
         seed_words: None,
         seed_words_file_name: None,
@@ -125,6 +135,18 @@ pub fn run_wallet_with_cli(
         tari_splash_screen("Console Wallet");
     }
 
+    if let Some(backup_file) = cli.restore_backup.as_ref() {
+        info!(target: LOG_TARGET, "Restore from backup requested.");
+        let password = get_or_prompt_password(password, None)?;
+        return restore_backup(&config.wallet, backup_file, &password);
+    }
+
+    if let Some(backup_file) = cli.create_backup.as_ref() {
+        info!(target: LOG_TARGET, "Wallet backup requested.");
+        let password = get_or_prompt_password(password, None)?;
+        return create_backup(&config.wallet, backup_file, &password);
+    }
+
     // check for recovery based on existence of wallet file
     let (mut boot_mode, password) = boot_with_password(&cli, &config.wallet)?;
 
@@ -20,6 +20,8 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
+mod webhook;
+
 use std::{
     io::Error,
     path::PathBuf,
@@ -39,6 +41,7 @@ use minotari_wallet::{
 use tari_common_types::transaction::TxId;
 use tari_utilities::hex::Hex;
 use tokio::{runtime::Handle, sync::broadcast::Sender};
+pub use webhook::WebhookNotifier;
 pub const LOG_TARGET: &str = "wallet::notifier";
 pub const RECEIVED: &str = "received";
 pub const SENT: &str = "sent";
@@ -47,6 +50,12 @@ pub const CONFIRMATION: &str = "confirmation";
 pub const MINED: &str = "mined";
 pub const CANCELLED: &str = "cancelled";
 pub const NEW_BLOCK_MINED: &str = "new_block_mined";
+pub const INVOICE_PAID: &str = "invoice_paid";
+pub const INVOICE_UNDERPAID: &str = "invoice_underpaid";
+pub const INVOICE_EXPIRED: &str = "invoice_expired";
+pub const SCHEDULED_PAYMENT_SENT: &str = "scheduled_payment_sent";
+pub const SCHEDULED_PAYMENT_FAILED: &str = "scheduled_payment_failed";
+pub const SCHEDULED_PAYMENT_COMPLETED: &str = "scheduled_payment_completed";
 
 #[derive(Clone)]
 // FIXME
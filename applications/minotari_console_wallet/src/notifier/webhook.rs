@@ -0,0 +1,563 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Delivers transaction events to the configured webhook endpoints as signed JSON. Events are queued in the wallet
+//! database before delivery, so that they survive restarts and are retried in order when an endpoint is unavailable.
+
+use std::{collections::HashMap, sync::Arc, time::Duration};
+
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use log::*;
+use minotari_wallet::{
+    transaction_service::{
+        error::TransactionServiceError,
+        handle::{TransactionEvent, TransactionServiceHandle},
+        storage::models::{CompletedTransaction, InboundTransaction, OutboundTransaction, WalletTransaction},
+    },
+    webhooks::WebhookDelivery,
+    WalletConfig,
+    WalletSqlite,
+};
+use rand::{rngs::OsRng, RngCore};
+use reqwest::{header::CONTENT_TYPE, Client};
+use serde::Serialize;
+use sha2::Sha256;
+use tari_common::exit_codes::{ExitCode, ExitError};
+use tari_common_types::transaction::{TransactionDirection, TransactionStatus, TxId};
+use tari_utilities::{
+    hex::{to_hex, Hex},
+    SafePassword,
+};
+use tokio::{
+    runtime::Handle,
+    sync::{broadcast::error::RecvError, Notify},
+    time::{self, MissedTickBehavior},
+};
+
+use crate::notifier::{CANCELLED, CONFIRMATION, MINED, RECEIVED};
+
+const LOG_TARGET: &str = "wallet::notifier::webhook";
+
+pub const WEBHOOK_ID_HEADER: &str = "X-Tari-Webhook-Id";
+pub const WEBHOOK_EVENT_HEADER: &str = "X-Tari-Webhook-Event";
+pub const WEBHOOK_TIMESTAMP_HEADER: &str = "X-Tari-Webhook-Timestamp";
+pub const WEBHOOK_SIGNATURE_HEADER: &str = "X-Tari-Webhook-Signature";
+
+/// The number of queued events that are loaded per endpoint in a single delivery pass
+const DELIVERY_BATCH_SIZE: usize = 50;
+
+/// The last event queued for each transaction and its confirmation count
+type QueuedEvents = HashMap<TxId, (&'static str, Option<u64>)>;
+
+/// The JSON body POSTed to webhook endpoints
+#[derive(Debug, Clone, Serialize)]
+struct WebhookPayload {
+    event_id: String,
+    event: &'static str,
+    created_at: i64,
+    tx_id: u64,
+    direction: String,
+    status: String,
+    amount: u64,
+    fee: Option<u64>,
+    source_address: Option<String>,
+    destination_address: Option<String>,
+    message: String,
+    confirmations: Option<u64>,
+    mined_height: Option<u64>,
+}
+
+impl WebhookPayload {
+    fn new(event: &'static str, transaction: &WalletTransaction, confirmations: Option<u64>) -> Self {
+        let mut event_id = [0u8; 16];
+        OsRng.fill_bytes(&mut event_id);
+        let payload = Self {
+            event_id: to_hex(&event_id),
+            event,
+            created_at: Utc::now().timestamp(),
+            tx_id: 0,
+            direction: String::new(),
+            status: String::new(),
+            amount: 0,
+            fee: None,
+            source_address: None,
+            destination_address: None,
+            message: String::new(),
+            confirmations,
+            mined_height: None,
+        };
+        match transaction {
+            WalletTransaction::Completed(tx) => payload.with_completed(tx),
+            WalletTransaction::PendingInbound(tx) => payload.with_inbound(tx),
+            WalletTransaction::PendingOutbound(tx) => payload.with_outbound(tx),
+        }
+    }
+
+    fn with_completed(self, tx: &CompletedTransaction) -> Self {
+        Self {
+            tx_id: tx.tx_id.as_u64(),
+            direction: tx.direction.to_string(),
+            status: tx.status.to_string(),
+            amount: tx.amount.as_u64(),
+            fee: Some(tx.fee.as_u64()),
+            source_address: Some(tx.source_address.to_hex()),
+            destination_address: Some(tx.destination_address.to_hex()),
+            message: tx.message.clone(),
+            confirmations: self.confirmations.or(tx.confirmations),
+            mined_height: tx.mined_height,
+            ..self
+        }
+    }
+
+    fn with_inbound(self, tx: &InboundTransaction) -> Self {
+        Self {
+            tx_id: tx.tx_id.as_u64(),
+            direction: "inbound".to_string(),
+            status: tx.status.to_string(),
+            amount: tx.amount.as_u64(),
+            source_address: Some(tx.source_address.to_hex()),
+            message: tx.message.clone(),
+            ..self
+        }
+    }
+
+    fn with_outbound(self, tx: &OutboundTransaction) -> Self {
+        Self {
+            tx_id: tx.tx_id.as_u64(),
+            direction: "outbound".to_string(),
+            status: tx.status.to_string(),
+            amount: tx.amount.as_u64(),
+            fee: Some(tx.fee.as_u64()),
+            destination_address: Some(tx.destination_address.to_hex()),
+            message: tx.message.clone(),
+            ..self
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct WebhookNotifier {
+    urls: Vec<String>,
+    secret: SafePassword,
+    retry_delay: Duration,
+    max_retry_delay: Duration,
+    max_attempts: u32,
+    timeout: Duration,
+    wallet: WalletSqlite,
+    pending: Arc<Notify>,
+}
+
+impl WebhookNotifier {
+    /// Returns `None` if no webhook endpoints are configured. Endpoints are refused without a `webhook_secret`, as they
+    /// would have no way to tell the wallet's events from forged ones.
+    pub fn new(config: &WalletConfig, wallet: WalletSqlite) -> Result<Option<Self>, ExitError> {
+        if config.webhook_urls.is_empty() {
+            return Ok(None);
+        }
+        let secret = config.webhook_secret.clone().ok_or_else(|| {
+            ExitError::new(
+                ExitCode::ConfigError,
+                "`webhook_urls` are configured without a `webhook_secret` to sign the webhook payloads with",
+            )
+        })?;
+        Ok(Some(Self {
+            urls: config.webhook_urls.clone().into_vec(),
+            secret,
+            retry_delay: config.webhook_retry_delay,
+            max_retry_delay: config.webhook_max_retry_delay,
+            max_attempts: config.webhook_max_attempts,
+            timeout: config.webhook_timeout,
+            wallet,
+            pending: Arc::new(Notify::new()),
+        }))
+    }
+
+    /// Starts queueing transaction events and delivering them until the wallet shuts down
+    pub fn spawn(self, handle: &Handle) {
+        info!(
+            target: LOG_TARGET,
+            "Delivering transaction events to {} webhook endpoint(s)",
+            self.urls.len()
+        );
+        handle.spawn(self.clone().queue_events());
+        handle.spawn(self.deliver_events());
+    }
+
+    async fn queue_events(self) {
+        let mut transaction_service = self.wallet.transaction_service.clone();
+        let mut events = transaction_service.get_event_stream();
+        let mut shutdown_signal = self.wallet.comms.shutdown_signal();
+        // The state of the transactions when the stream was subscribed to, so that a lagged stream only replays the
+        // events of the transactions that changed since
+        let mut queued_events = match current_events(&mut transaction_service).await {
+            Ok(queued_events) => queued_events,
+            Err(e) => {
+                error!(
+                    target: LOG_TARGET,
+                    "Could not load the transactions that missed events are replayed from: {}", e
+                );
+                QueuedEvents::new()
+            },
+        };
+        loop {
+            tokio::select! {
+                result = events.recv() => match result {
+                    Ok(event) => {
+                        let (event, tx_id, confirmations) = match webhook_event(&event) {
+                            Some(event) => event,
+                            None => continue,
+                        };
+                        match transaction_service.get_any_transaction(tx_id).await {
+                            Ok(Some(transaction)) => {
+                                self.queue(WebhookPayload::new(event, &transaction, confirmations));
+                                queued_events.insert(tx_id, (event, confirmations));
+                            },
+                            Ok(None) => error!(target: LOG_TARGET, "Transaction not found tx_id: {}", tx_id),
+                            Err(e) => error!(target: LOG_TARGET, "Transaction service error: {}", e),
+                        }
+                    },
+                    Err(RecvError::Lagged(n)) => {
+                        warn!(
+                            target: LOG_TARGET,
+                            "Missed {} transaction events, replaying the webhooks of the transactions that changed", n
+                        );
+                        if let Err(e) = self.replay_missed_events(&mut transaction_service, &mut queued_events).await {
+                            error!(target: LOG_TARGET, "Could not replay missed webhooks: {}", e);
+                        }
+                    },
+                    Err(RecvError::Closed) => break,
+                },
+                _ = shutdown_signal.wait() => break,
+            }
+        }
+        debug!(target: LOG_TARGET, "Webhook event queue shutting down");
+    }
+
+    /// Queues the event that describes the current state of every transaction whose state changed since its last queued
+    /// event
+    async fn replay_missed_events(
+        &self,
+        transaction_service: &mut TransactionServiceHandle,
+        queued_events: &mut QueuedEvents,
+    ) -> Result<(), TransactionServiceError> {
+        let mut replayed = 0usize;
+        for transaction in transactions(transaction_service).await? {
+            let (tx_id, event, confirmations) = match current_event(&transaction) {
+                Some(event) => event,
+                None => continue,
+            };
+            if queued_events.get(&tx_id) == Some(&(event, confirmations)) {
+                continue;
+            }
+            self.queue(WebhookPayload::new(event, &transaction, confirmations));
+            queued_events.insert(tx_id, (event, confirmations));
+            replayed += 1;
+        }
+        info!(target: LOG_TARGET, "Replayed {} missed webhook event(s)", replayed);
+        Ok(())
+    }
+
+    /// Stores one delivery of the event per endpoint and wakes up the delivery task
+    fn queue(&self, payload: WebhookPayload) {
+        let body = match serde_json::to_string(&payload) {
+            Ok(body) => body,
+            Err(e) => {
+                error!(target: LOG_TARGET, "Could not serialize webhook payload: {}", e);
+                return;
+            },
+        };
+        let timestamp = Utc::now().naive_utc();
+        let deliveries = self
+            .urls
+            .iter()
+            .map(|url| {
+                WebhookDelivery::new(
+                    payload.event_id.clone(),
+                    url.clone(),
+                    payload.event.to_string(),
+                    body.clone(),
+                    timestamp,
+                )
+            })
+            .collect();
+        match self.wallet.db.queue_webhook_deliveries(deliveries) {
+            Ok(()) => self.pending.notify_one(),
+            Err(e) => error!(
+                target: LOG_TARGET,
+                "Could not queue `{}` webhook for tx_id {}: {}", payload.event, payload.tx_id, e
+            ),
+        }
+    }
+
+    async fn deliver_events(self) {
+        let client = match Client::builder().timeout(self.timeout).build() {
+            Ok(client) => client,
+            Err(e) => {
+                error!(target: LOG_TARGET, "Could not create webhook client: {}", e);
+                return;
+            },
+        };
+        let mut shutdown_signal = self.wallet.comms.shutdown_signal();
+        // Deliveries queued before a restart and retries are picked up on the next tick
+        let mut retry_interval = time::interval(self.retry_delay.max(Duration::from_secs(1)));
+        retry_interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        loop {
+            tokio::select! {
+                _ = self.pending.notified() => {},
+                _ = retry_interval.tick() => {},
+                _ = shutdown_signal.wait() => break,
+            }
+            for url in &self.urls {
+                self.deliver_pending(&client, url).await;
+            }
+        }
+        debug!(target: LOG_TARGET, "Webhook delivery shutting down");
+    }
+
+    /// Delivers the queued events of an endpoint in order, stopping at the first event that is not yet due or fails so
+    /// that the endpoint never receives events out of order. An event that fails `max_attempts` times is moved to the
+    /// dead letters so that it does not hold up the events after it.
+    async fn deliver_pending(&self, client: &Client, url: &str) {
+        let deliveries = match self.wallet.db.get_pending_webhook_deliveries(url, DELIVERY_BATCH_SIZE) {
+            Ok(deliveries) => deliveries,
+            Err(e) => {
+                error!(target: LOG_TARGET, "Could not load queued webhooks for {}: {}", url, e);
+                return;
+            },
+        };
+        for mut delivery in deliveries {
+            if !delivery.is_due(Utc::now().naive_utc()) {
+                return;
+            }
+            match self.post(client, &delivery).await {
+                Ok(()) => {
+                    debug!(
+                        target: LOG_TARGET,
+                        "Delivered `{}` webhook {} to {}", delivery.event, delivery.event_id, url
+                    );
+                    if let Err(e) = self.wallet.db.remove_webhook_delivery(&delivery.event_id, url) {
+                        error!(target: LOG_TARGET, "Could not remove delivered webhook: {}", e);
+                        return;
+                    }
+                },
+                Err(e) => {
+                    let will_retry = delivery.record_failure(
+                        e,
+                        self.max_attempts,
+                        self.retry_delay,
+                        self.max_retry_delay,
+                        Utc::now().naive_utc(),
+                    );
+                    if will_retry {
+                        warn!(
+                            target: LOG_TARGET,
+                            "Webhook {} to {} failed (attempt {}), retrying at {}: {}",
+                            delivery.event_id,
+                            url,
+                            delivery.attempts,
+                            delivery.next_attempt_at,
+                            delivery.last_error.as_deref().unwrap_or_default()
+                        );
+                    } else {
+                        error!(
+                            target: LOG_TARGET,
+                            "Webhook {} to {} failed {} times and is moved to the dead letters: {}",
+                            delivery.event_id,
+                            url,
+                            delivery.attempts,
+                            delivery.last_error.as_deref().unwrap_or_default()
+                        );
+                    }
+                    if let Err(e) = self.wallet.db.update_webhook_delivery(&delivery) {
+                        error!(target: LOG_TARGET, "Could not update queued webhook: {}", e);
+                        return;
+                    }
+                    if will_retry {
+                        return;
+                    }
+                },
+            }
+        }
+    }
+
+    async fn post(&self, client: &Client, delivery: &WebhookDelivery) -> Result<(), String> {
+        let timestamp = Utc::now().timestamp().to_string();
+        let request = client
+            .post(&delivery.url)
+            .header(CONTENT_TYPE, "application/json")
+            .header(WEBHOOK_ID_HEADER, &delivery.event_id)
+            .header(WEBHOOK_EVENT_HEADER, &delivery.event)
+            .header(WEBHOOK_TIMESTAMP_HEADER, &timestamp)
+            .header(
+                WEBHOOK_SIGNATURE_HEADER,
+                signature(self.secret.reveal(), &timestamp, &delivery.payload),
+            );
+        let response = request
+            .body(delivery.payload.clone())
+            .send()
+            .await
+            .map_err(|e| e.to_string())?;
+        if response.status().is_success() {
+            Ok(())
+        } else {
+            Err(format!("Endpoint responded with {}", response.status()))
+        }
+    }
+}
+
+/// The webhook event name, transaction and confirmation count of the transaction events that are sent to webhooks
+fn webhook_event(event: &TransactionEvent) -> Option<(&'static str, TxId, Option<u64>)> {
+    match event {
+        TransactionEvent::ReceivedFinalizedTransaction(tx_id) => Some((RECEIVED, *tx_id, None)),
+        TransactionEvent::TransactionMinedUnconfirmed {
+            tx_id,
+            num_confirmations,
+            ..
+        } |
+        TransactionEvent::DetectedTransactionUnconfirmed {
+            tx_id,
+            num_confirmations,
+            ..
+        } => Some((CONFIRMATION, *tx_id, Some(*num_confirmations))),
+        TransactionEvent::TransactionMined { tx_id, .. } |
+        TransactionEvent::DetectedTransactionConfirmed { tx_id, .. } => Some((MINED, *tx_id, None)),
+        TransactionEvent::TransactionCancelled(tx_id, _) => Some((CANCELLED, *tx_id, None)),
+        _ => None,
+    }
+}
+
+/// The completed transactions and the cancelled transactions, which are the ones that webhook events are sent for
+async fn transactions(
+    transaction_service: &mut TransactionServiceHandle,
+) -> Result<Vec<WalletTransaction>, TransactionServiceError> {
+    let mut transactions = Vec::new();
+    transactions.extend(
+        transaction_service
+            .get_completed_transactions()
+            .await?
+            .into_values()
+            .map(WalletTransaction::Completed),
+    );
+    transactions.extend(
+        transaction_service
+            .get_cancelled_completed_transactions()
+            .await?
+            .into_values()
+            .map(WalletTransaction::Completed),
+    );
+    transactions.extend(
+        transaction_service
+            .get_cancelled_pending_inbound_transactions()
+            .await?
+            .into_values()
+            .map(WalletTransaction::PendingInbound),
+    );
+    transactions.extend(
+        transaction_service
+            .get_cancelled_pending_outbound_transactions()
+            .await?
+            .into_values()
+            .map(WalletTransaction::PendingOutbound),
+    );
+    Ok(transactions)
+}
+
+/// The event that describes the current state of every transaction that has one
+async fn current_events(
+    transaction_service: &mut TransactionServiceHandle,
+) -> Result<QueuedEvents, TransactionServiceError> {
+    Ok(transactions(transaction_service)
+        .await?
+        .iter()
+        .filter_map(current_event)
+        .map(|(tx_id, event, confirmations)| (tx_id, (event, confirmations)))
+        .collect())
+}
+
+/// The webhook event that the transaction was last sent, going by its current state
+fn current_event(transaction: &WalletTransaction) -> Option<(TxId, &'static str, Option<u64>)> {
+    match transaction {
+        WalletTransaction::Completed(tx) if tx.cancelled.is_some() => Some((tx.tx_id, CANCELLED, None)),
+        WalletTransaction::Completed(tx) => match tx.status {
+            TransactionStatus::MinedConfirmed |
+            TransactionStatus::OneSidedConfirmed |
+            TransactionStatus::CoinbaseConfirmed => Some((tx.tx_id, MINED, None)),
+            TransactionStatus::MinedUnconfirmed |
+            TransactionStatus::OneSidedUnconfirmed |
+            TransactionStatus::CoinbaseUnconfirmed => Some((tx.tx_id, CONFIRMATION, tx.confirmations)),
+            TransactionStatus::Completed | TransactionStatus::Broadcast
+                if tx.direction == TransactionDirection::Inbound =>
+            {
+                Some((tx.tx_id, RECEIVED, None))
+            },
+            _ => None,
+        },
+        WalletTransaction::PendingInbound(tx) if tx.cancelled => Some((tx.tx_id, CANCELLED, None)),
+        WalletTransaction::PendingOutbound(tx) if tx.cancelled => Some((tx.tx_id, CANCELLED, None)),
+        WalletTransaction::PendingInbound(_) | WalletTransaction::PendingOutbound(_) => None,
+    }
+}
+
+/// `sha256=<hex HMAC-SHA256 of "<timestamp>.<body>">`
+fn signature(secret: &[u8], timestamp: &str, body: &str) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret).expect("HMAC can take a key of any size");
+    mac.update(timestamp.as_bytes());
+    mac.update(b".");
+    mac.update(body.as_bytes());
+    format!("sha256={}", to_hex(mac.finalize().into_bytes().as_slice()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn it_signs_the_timestamp_and_body() {
+        assert_eq!(
+            signature(b"whsec_test", "1700000000", r#"{"event":"received"}"#),
+            "sha256=131b1b1281e6286557ba7f4dfac50ce36a2deeaa9a7f9b8f11efcea634acdc9a"
+        );
+        assert_ne!(
+            signature(b"whsec_test", "1700000001", r#"{"event":"received"}"#),
+            signature(b"whsec_test", "1700000000", r#"{"event":"received"}"#)
+        );
+    }
+
+    #[test]
+    fn it_only_sends_final_transaction_events() {
+        let tx_id = TxId::from(1u64);
+        assert_eq!(
+            webhook_event(&TransactionEvent::TransactionMinedUnconfirmed {
+                tx_id,
+                num_confirmations: 2,
+                is_valid: true,
+            }),
+            Some((CONFIRMATION, tx_id, Some(2)))
+        );
+        assert_eq!(
+            webhook_event(&TransactionEvent::DetectedTransactionConfirmed { tx_id, is_valid: true }),
+            Some((MINED, tx_id, None))
+        );
+        assert_eq!(webhook_event(&TransactionEvent::ReceivedTransaction(tx_id)), None);
+    }
+}
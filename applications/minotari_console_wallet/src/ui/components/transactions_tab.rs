@@ -34,6 +34,8 @@ pub struct TransactionsTab {
     detailed_transaction: Option<CompletedTransactionInfo>,
     error_message: Option<String>,
     confirmation_dialog: bool,
+    search_mode: bool,
+    search_field: String,
 }
 
 impl TransactionsTab {
@@ -46,6 +48,8 @@ impl TransactionsTab {
             detailed_transaction: None,
             error_message: None,
             confirmation_dialog: false,
+            search_mode: false,
+            search_field: String::new(),
         }
     }
 
@@ -172,10 +176,15 @@ impl TransactionsTab {
         } else {
             Style::default().fg(Color::White).add_modifier(Modifier::BOLD)
         };
-        let block = Block::default().borders(Borders::ALL).title(Span::styled(
-            format!("Completed (T)ransactions ({}) ", app_state.get_completed_txs().len()),
-            style,
-        ));
+        let title = match app_state.get_transaction_filter() {
+            Some(filter) => format!(
+                "Completed (T)ransactions ({}) matching '{}' ",
+                app_state.get_completed_txs().len(),
+                filter
+            ),
+            None => format!("Completed (T)ransactions ({}) ", app_state.get_completed_txs().len()),
+        };
+        let block = Block::default().borders(Borders::ALL).title(Span::styled(title, style));
         f.render_widget(block, area);
 
         let completed_txs = app_state.get_completed_txs();
@@ -288,7 +297,7 @@ impl TransactionsTab {
             .split(area);
 
         // Labels
-        let constraints = [Constraint::Length(1); 14];
+        let constraints = [Constraint::Length(1); 15];
         let label_layout = Layout::default().constraints(constraints).split(columns[0]);
 
         let tx_id = Span::styled("TxID:", Style::default().fg(Color::Magenta));
@@ -305,6 +314,7 @@ impl TransactionsTab {
         let confirmations = Span::styled("Confirmations:", Style::default().fg(Color::Magenta));
         let mined_height = Span::styled("Mined Height:", Style::default().fg(Color::Magenta));
         let maturity = Span::styled("Maturity:", Style::default().fg(Color::Magenta));
+        let label = Span::styled("Label:", Style::default().fg(Color::Magenta));
 
         let trim = Wrap { trim: true };
         let paragraph = Paragraph::new(tx_id).wrap(trim);
@@ -335,11 +345,13 @@ impl TransactionsTab {
         f.render_widget(paragraph, label_layout[12]);
         let paragraph = Paragraph::new(maturity).wrap(trim);
         f.render_widget(paragraph, label_layout[13]);
+        let paragraph = Paragraph::new(label).wrap(trim);
+        f.render_widget(paragraph, label_layout[14]);
 
         // Content
         let required_confirmations = app_state.get_required_confirmations();
         if let Some(tx) = self.detailed_transaction.as_ref() {
-            let constraints = [Constraint::Length(1); 14];
+            let constraints = [Constraint::Length(1); 15];
             let content_layout = Layout::default().constraints(constraints).split(columns[1]);
             let tx_id = Span::styled(format!("{}", tx.tx_id), Style::default().fg(Color::White));
 
@@ -428,6 +440,12 @@ impl TransactionsTab {
                 "N/A".to_string()
             };
             let maturity = Span::styled(maturity, Style::default().fg(Color::White));
+            let label = match app_state.get_transaction_label(tx.tx_id) {
+                Some(label) if label.note.is_empty() => label.label.clone(),
+                Some(label) => format!("{} ({})", label.label, label.note),
+                None => String::new(),
+            };
+            let label = Span::styled(label, Style::default().fg(Color::White));
 
             let paragraph = Paragraph::new(tx_id).wrap(trim);
             f.render_widget(paragraph, content_layout[0]);
@@ -457,6 +475,8 @@ impl TransactionsTab {
             f.render_widget(paragraph, content_layout[12]);
             let paragraph = Paragraph::new(maturity).wrap(trim);
             f.render_widget(paragraph, content_layout[13]);
+            let paragraph = Paragraph::new(label).wrap(trim);
+            f.render_widget(paragraph, content_layout[14]);
         }
     }
 }
@@ -469,7 +489,7 @@ impl<B: Backend> Component<B> for TransactionsTab {
                     Constraint::Length(3),
                     Constraint::Length(1),
                     Constraint::Min(9),
-                    Constraint::Length(16),
+                    Constraint::Length(17),
                 ]
                 .as_ref(),
             )
@@ -497,9 +517,22 @@ impl<B: Backend> Component<B> for TransactionsTab {
         span_vec.push(Span::raw(" show/hide mining "));
         span_vec.push(Span::styled("(R)", Style::default().add_modifier(Modifier::BOLD)));
         span_vec.push(Span::raw(" rebroadcast Txs "));
+        span_vec.push(Span::styled("(S)", Style::default().add_modifier(Modifier::BOLD)));
+        span_vec.push(Span::raw(" search Txs "));
         span_vec.push(Span::styled("(Esc)", Style::default().add_modifier(Modifier::BOLD)));
         span_vec.push(Span::raw(" exit list"));
 
+        if self.search_mode {
+            span_vec = vec![
+                Span::styled("Search: ", Style::default().add_modifier(Modifier::BOLD)),
+                Span::styled(self.search_field.as_str(), Style::default().fg(Color::Magenta)),
+                Span::raw("  "),
+                Span::styled("(Enter)", Style::default().add_modifier(Modifier::BOLD)),
+                Span::raw(" apply "),
+                Span::styled("(Esc)", Style::default().add_modifier(Modifier::BOLD)),
+                Span::raw(" clear search"),
+            ];
+        }
         let instructions = Paragraph::new(Spans::from(span_vec)).wrap(Wrap { trim: false });
         f.render_widget(instructions, areas[1]);
 
@@ -529,6 +562,18 @@ impl<B: Backend> Component<B> for TransactionsTab {
             return;
         }
 
+        if self.search_mode {
+            if '\n' == c {
+                self.search_mode = false;
+                app_state.set_transaction_filter(Some(self.search_field.clone()));
+                self.completed_list_state.select(None);
+                self.detailed_transaction = None;
+            } else {
+                self.search_field.push(c);
+            }
+            return;
+        }
+
         if self.confirmation_dialog {
             if 'n' == c {
                 self.confirmation_dialog = false;
@@ -589,6 +634,10 @@ impl<B: Backend> Component<B> for TransactionsTab {
                     self.confirmation_dialog = true;
                 }
             },
+            's' => {
+                self.search_mode = true;
+                self.search_field = app_state.get_transaction_filter().cloned().unwrap_or_default();
+            },
             // Rebroadcast
             'r' => {
                 if let Err(e) = Handle::current().block_on(app_state.rebroadcast_all()) {
@@ -666,7 +715,18 @@ impl<B: Backend> Component<B> for TransactionsTab {
         }
     }
 
-    fn on_esc(&mut self, _app_state: &mut AppState) {
+    fn on_backspace(&mut self, _app_state: &mut AppState) {
+        if self.search_mode {
+            let _ = self.search_field.pop();
+        }
+    }
+
+    fn on_esc(&mut self, app_state: &mut AppState) {
+        if self.search_mode || app_state.get_transaction_filter().is_some() {
+            self.search_mode = false;
+            self.search_field.clear();
+            app_state.set_transaction_filter(None);
+        }
         self.selected_tx_list = SelectedTransactionList::None;
         self.pending_list_state.select(None);
         self.completed_list_state.select(None);
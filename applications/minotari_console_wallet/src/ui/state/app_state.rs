@@ -32,6 +32,7 @@ use log::*;
 use minotari_wallet::{
     base_node_service::{handle::BaseNodeEventReceiver, service::BaseNodeState},
     connectivity_service::{OnlineStatus, WalletConnectivityHandle, WalletConnectivityInterface},
+    labels::{LabelTarget, WalletLabel},
     output_manager_service::{handle::OutputManagerEventReceiver, service::Balance, UtxoSelectionCriteria},
     transaction_service::{
         handle::TransactionEventReceiver,
@@ -100,6 +101,7 @@ pub struct AppState {
     wallet_config: WalletConfig,
     wallet_connectivity: WalletConnectivityHandle,
     balance_enquiry_debouncer: BalanceEnquiryDebouncer,
+    transaction_filter: Option<String>,
 }
 
 impl AppState {
@@ -128,6 +130,7 @@ impl AppState {
                 output_manager_service,
             ),
             wallet_config,
+            transaction_filter: None,
         }
     }
 
@@ -556,7 +559,37 @@ impl AppState {
     }
 
     pub fn get_completed_txs(&self) -> Vec<&CompletedTransactionInfo> {
-        self.cached_data.completed_txs.iter().collect()
+        match self.transaction_filter.as_ref() {
+            Some(filter) => self
+                .cached_data
+                .completed_txs
+                .iter()
+                .filter(|tx| self.transaction_matches_filter(tx, filter))
+                .collect(),
+            None => self.cached_data.completed_txs.iter().collect(),
+        }
+    }
+
+    /// Only show completed transactions whose id, message, counterparty alias, label or note contain the filter text
+    pub fn set_transaction_filter(&mut self, filter: Option<String>) {
+        self.transaction_filter = filter.map(|f| f.to_lowercase()).filter(|f| !f.is_empty());
+    }
+
+    pub fn get_transaction_filter(&self) -> Option<&String> {
+        self.transaction_filter.as_ref()
+    }
+
+    fn transaction_matches_filter(&self, tx: &CompletedTransactionInfo, filter: &str) -> bool {
+        tx.tx_id.to_string().contains(filter) ||
+            tx.message.to_lowercase().contains(filter) ||
+            self.get_alias(&tx.source_address).to_lowercase().contains(filter) ||
+            self.get_alias(&tx.destination_address).to_lowercase().contains(filter) ||
+            self.get_transaction_label(tx.tx_id)
+                .map_or(false, |label| label.matches(filter))
+    }
+
+    pub fn get_transaction_label(&self, tx_id: TxId) -> Option<&WalletLabel> {
+        self.cached_data.transaction_labels.get(&tx_id)
     }
 
     pub fn get_confirmations(&self, tx_id: TxId) -> Option<&u64> {
@@ -795,6 +828,17 @@ impl AppStateInner {
                     .map_err(|e| UiError::TransactionError(e.to_string()))
             })
             .collect::<Result<Vec<_>, _>>()?;
+        self.data.transaction_labels = self
+            .wallet
+            .db
+            .get_labels()
+            .map_err(UiError::WalletStorageError)?
+            .into_iter()
+            .filter_map(|label| match label.target {
+                LabelTarget::Transaction(tx_id) => Some((tx_id, label)),
+                _ => None,
+            })
+            .collect();
         self.updated = true;
         Ok(())
     }
@@ -1234,6 +1278,7 @@ impl CompletedTransactionInfo {
 struct AppStateData {
     pending_txs: Vec<CompletedTransactionInfo>,
     completed_txs: Vec<CompletedTransactionInfo>,
+    transaction_labels: HashMap<TxId, WalletLabel>,
     confirmations: HashMap<TxId, u64>,
     my_identity: MyIdentity,
     contacts: Vec<UiContact>,
@@ -1313,6 +1358,7 @@ impl AppStateData {
         AppStateData {
             pending_txs: Vec::new(),
             completed_txs: Vec::new(),
+            transaction_labels: HashMap::new(),
             confirmations: HashMap::new(),
             my_identity: identity,
             contacts: Vec::new(),
@@ -166,6 +166,22 @@ impl WalletEventMonitor {
                                     self.trigger_full_tx_state_refresh().await;
                                     self.trigger_balance_refresh();
                                 },
+                                TransactionEvent::ScheduledPaymentSent{schedule_id, tx_id} => {
+                                    self.trigger_tx_state_refresh(tx_id).await;
+                                    self.trigger_balance_refresh();
+                                    self.add_notification(
+                                        format!("Scheduled Payment {} Sent - TxId: {}", schedule_id, tx_id)
+                                    ).await;
+                                },
+                                TransactionEvent::ScheduledPaymentFailed{schedule_id, error, will_retry} => {
+                                    let outcome = if will_retry { "will be retried" } else { "was skipped" };
+                                    self.add_notification(
+                                        format!("Scheduled Payment {} Failed and {}: {}", schedule_id, outcome, error)
+                                    ).await;
+                                },
+                                TransactionEvent::ScheduledPaymentCompleted(schedule_id) => {
+                                    self.add_notification(format!("Scheduled Payment {} Completed", schedule_id)).await;
+                                },
                                 // Only the above variants trigger state refresh
                                 _ => (),
                             }
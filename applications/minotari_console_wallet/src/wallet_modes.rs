@@ -40,7 +40,7 @@ use crate::{
     automation::commands::command_runner,
     cli::{Cli, CliCommands},
     grpc::WalletGrpcServer,
-    notifier::Notifier,
+    notifier::{Notifier, WebhookNotifier},
     recovery::wallet_recovery,
     ui,
     ui::App,
@@ -310,6 +310,9 @@ pub fn tui_mode(
         wallet.clone(),
         events_broadcaster,
     );
+    if let Some(webhooks) = WebhookNotifier::new(config, wallet.clone())? {
+        webhooks.spawn(&handle);
+    }
 
     let base_node_selected;
     if let Some(peer) = base_node_config.base_node_custom.clone() {
@@ -397,6 +400,9 @@ pub fn recovery_mode(
 
 pub fn grpc_mode(handle: Handle, config: &WalletConfig, wallet: WalletSqlite) -> Result<(), ExitError> {
     info!(target: LOG_TARGET, "Starting grpc server");
+    if let Some(webhooks) = WebhookNotifier::new(config, wallet.clone())? {
+        webhooks.spawn(&handle);
+    }
     if let Some(address) = config.grpc_address.as_ref().filter(|_| config.grpc_enabled).cloned() {
         #[cfg(feature = "grpc")]
         {
@@ -552,6 +558,28 @@ mod test {
                 CliCommands::RevalidateWalletDb => {},
                 CliCommands::RegisterValidatorNode(_) => {},
                 CliCommands::CreateTlsCerts => {},
+                CliCommands::CreateAccount(_) => {},
+                CliCommands::ListAccounts => {},
+                CliCommands::GetAccountBalance(_) => {},
+                CliCommands::CreateInvoice(_) => {},
+                CliCommands::ListInvoices => {},
+                CliCommands::CancelInvoice(_) => {},
+                CliCommands::PayInvoice(_) => {},
+                CliCommands::SendOneSidedToStealthSubaddress(_) => {},
+                CliCommands::CreateSubaddress(_) => {},
+                CliCommands::ListSubaddresses => {},
+                CliCommands::SetSubaddressLabel(_) => {},
+                CliCommands::SetLabel(_) => {},
+                CliCommands::RemoveLabel(_) => {},
+                CliCommands::ListLabels(_) => {},
+                CliCommands::ExportTransactions(_) => {},
+                CliCommands::BatchPayout(_) => {},
+                CliCommands::BatchPayoutStatus(_) => {},
+                CliCommands::SchedulePayment(_) => {},
+                CliCommands::ListScheduledPayments => {},
+                CliCommands::PauseScheduledPayment(_) => {},
+                CliCommands::ResumeScheduledPayment(_) => {},
+                CliCommands::CancelScheduledPayment(_) => {},
             }
         }
         assert!(
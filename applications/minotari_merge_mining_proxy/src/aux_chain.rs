@@ -0,0 +1,317 @@
+// Copyright 2024. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Merge mining of additional auxiliary chains alongside Minotari.
+//!
+//! Every chain is committed to in a single merge mining merkle tree whose root is inserted into the Monero coinbase.
+//! The position of a chain in the tree is derived from its chain id and the aux nonce, as done for Minotari with
+//! [aux_chain_position].
+//!
+//! Aux chain providers are JSON-RPC 2.0 servers implementing two methods:
+//! - `get_aux_block`, without params, returns `{ "hash": "<32 byte hex>", "difficulty": <u64>, "height": <u64> }`,
+//!   where `hash` is the merge mining hash of the block to mine.
+//! - `submit_aux_block` with params `{ "hash", "monero_block", "seed_hash", "aux_merkle_branch", "aux_merkle_path",
+//!   "aux_nonce", "aux_chain_count" }` submits a solution. `monero_block` is the hex encoded Monero block, and the
+//!   `aux_merkle_branch` (a list of hex hashes) and `aux_merkle_path` prove `hash` is in the tree committed to in its
+//!   coinbase. Any non-error result is treated as accepted.
+
+use std::{convert::TryFrom, sync::Arc, time::Duration};
+
+use serde::Deserialize;
+use serde_json as json;
+use serde_json::json;
+use tari_core::proof_of_work::monero_rx::{aux_chain_position, create_merkle_proof, tree_hash};
+use tracing::{debug, info, warn};
+
+use crate::{config::MergeMiningProxyConfig, error::MmProxyError};
+
+const LOG_TARGET: &str = "minotari_mm_proxy::aux_chain";
+
+/// Aux chain requests are made while a miner waits for a block template, so slow providers are skipped
+const AUX_CHAIN_REQUEST_TIMEOUT: Duration = Duration::from_secs(2);
+/// The number of aux nonces tried to find a tree layout in which no two chains share a position
+const MAX_AUX_NONCE_ATTEMPTS: u32 = 100_000;
+
+#[derive(Debug, Clone)]
+struct AuxChain {
+    name: String,
+    url: String,
+    chain_id: Vec<u8>,
+}
+
+/// A block to be merge mined on an aux chain
+#[derive(Debug, Clone)]
+pub struct AuxChainTemplate {
+    pub name: String,
+    pub chain_id: Vec<u8>,
+    pub hash: monero::Hash,
+    pub difficulty: u64,
+    pub height: u64,
+    url: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct AuxBlockResponse {
+    hash: String,
+    difficulty: u64,
+    height: u64,
+}
+
+/// The configured aux chain providers
+#[derive(Debug, Clone)]
+pub struct AuxChains {
+    http_client: reqwest::Client,
+    chains: Arc<Vec<AuxChain>>,
+}
+
+impl AuxChains {
+    pub fn new(config: &MergeMiningProxyConfig, http_client: reqwest::Client) -> Result<Self, MmProxyError> {
+        let chains = config
+            .aux_chains
+            .iter()
+            .map(|chain| {
+                Ok(AuxChain {
+                    name: chain.name.clone(),
+                    url: chain.url.clone(),
+                    chain_id: hex::decode(&chain.chain_id).map_err(|e| {
+                        MmProxyError::AuxChainError(format!("Invalid chain id for aux chain '{}': {}", chain.name, e))
+                    })?,
+                })
+            })
+            .collect::<Result<Vec<_>, MmProxyError>>()?;
+        Ok(Self {
+            http_client,
+            chains: Arc::new(chains),
+        })
+    }
+
+    /// Fetches a block to mine from every aux chain. Chains that fail to respond are left out of this template.
+    pub async fn get_templates(&self) -> Vec<AuxChainTemplate> {
+        let requests = self.chains.iter().map(|chain| async move {
+            match self.get_template(chain).await {
+                Ok(template) => Some(template),
+                Err(e) => {
+                    warn!(target: LOG_TARGET, "Could not get a block from aux chain '{}': {}", chain.name, e);
+                    None
+                },
+            }
+        });
+        futures::future::join_all(requests)
+            .await
+            .into_iter()
+            .flatten()
+            .collect()
+    }
+
+    async fn get_template(&self, chain: &AuxChain) -> Result<AuxChainTemplate, MmProxyError> {
+        let result = self.call(&chain.url, "get_aux_block", json!({})).await?;
+        let response = json::from_value::<AuxBlockResponse>(result)?;
+        let hash = hex::decode(&response.hash)
+            .map_err(|e| MmProxyError::AuxChainError(format!("Invalid aux block hash: {}", e)))?;
+        if hash.len() != monero::Hash::len_bytes() {
+            return Err(MmProxyError::AuxChainError(format!(
+                "Aux block hash is {} bytes, expected {}",
+                hash.len(),
+                monero::Hash::len_bytes()
+            )));
+        }
+        Ok(AuxChainTemplate {
+            name: chain.name.clone(),
+            chain_id: chain.chain_id.clone(),
+            hash: monero::Hash::from_slice(&hash),
+            difficulty: response.difficulty,
+            height: response.height,
+            url: chain.url.clone(),
+        })
+    }
+
+    /// Submits a solved Monero block to an aux chain whose target it meets
+    pub async fn submit(
+        &self,
+        template: &AuxChainTemplate,
+        monero_block: &str,
+        seed_hash: &str,
+        ordered_hashes: &[monero::Hash],
+        aux_nonce: u32,
+    ) -> Result<(), MmProxyError> {
+        let proof = create_merkle_proof(ordered_hashes, &template.hash).ok_or_else(|| {
+            MmProxyError::AuxChainError(format!("Aux chain '{}' is not part of the merkle tree", template.name))
+        })?;
+        let params = json!({
+            "hash": hex::encode(template.hash),
+            "monero_block": monero_block,
+            "seed_hash": seed_hash,
+            "aux_merkle_branch": proof.branch().iter().map(hex::encode).collect::<Vec<_>>(),
+            "aux_merkle_path": proof.path(),
+            "aux_nonce": aux_nonce,
+            "aux_chain_count": ordered_hashes.len(),
+        });
+        self.call(&template.url, "submit_aux_block", params).await?;
+        info!(
+            target: LOG_TARGET,
+            "Submitted block #{} to aux chain '{}'", template.height, template.name
+        );
+        Ok(())
+    }
+
+    async fn call(&self, url: &str, method: &str, params: json::Value) -> Result<json::Value, MmProxyError> {
+        let request = json!({ "jsonrpc": "2.0", "id": 0, "method": method, "params": params });
+        debug!(target: LOG_TARGET, "[aux chain] request to {}: {}", url, request);
+        let mut response = self
+            .http_client
+            .post(url)
+            .timeout(AUX_CHAIN_REQUEST_TIMEOUT)
+            .json(&request)
+            .send()
+            .await?
+            .json::<json::Value>()
+            .await?;
+        if !response["error"].is_null() {
+            return Err(MmProxyError::AuxChainError(format!(
+                "`{}` returned an error: {}",
+                method, response["error"]
+            )));
+        }
+        Ok(response["result"].take())
+    }
+}
+
+/// The merge mining merkle tree committed to in the Monero coinbase
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuxMerkleTree {
+    /// The merge mining hashes of all chains, each at the position given by its chain id and the aux nonce
+    pub ordered_hashes: Vec<monero::Hash>,
+    pub aux_nonce: u32,
+    pub root: monero::Hash,
+}
+
+impl AuxMerkleTree {
+    /// Builds the tree for the `(chain id, merge mining hash)` of every chain, searching for an aux nonce for which no
+    /// two chains share a position.
+    pub fn build(chains: &[(&[u8], monero::Hash)]) -> Result<Self, MmProxyError> {
+        let chain_count = u8::try_from(chains.len())
+            .map_err(|_| MmProxyError::AuxChainError(format!("Too many aux chains: {}", chains.len())))?;
+        if chain_count <= 1 {
+            let hash = chains
+                .first()
+                .map(|(_, hash)| *hash)
+                .ok_or_else(|| MmProxyError::AuxChainError("No chains to merge mine".to_string()))?;
+            return Ok(Self {
+                ordered_hashes: vec![hash],
+                aux_nonce: 0,
+                root: hash,
+            });
+        }
+
+        let tree_positions = merkle_proof_positions(chains.len())?;
+        for aux_nonce in 0..MAX_AUX_NONCE_ATTEMPTS {
+            let positions = chains
+                .iter()
+                .map(|(chain_id, _)| aux_chain_position(chain_id, aux_nonce, chain_count))
+                .collect::<Vec<_>>();
+            if (1..positions.len()).any(|i| positions[..i].contains(&positions[i])) {
+                continue;
+            }
+            let mut ordered_hashes = Vec::with_capacity(chains.len());
+            for tree_position in &tree_positions {
+                let index = positions
+                    .iter()
+                    .position(|p| p == tree_position)
+                    .ok_or_else(|| MmProxyError::AuxChainError("Inconsistent merkle tree positions".to_string()))?;
+                ordered_hashes.push(chains[index].1);
+            }
+            let root = tree_hash(&ordered_hashes)?;
+            return Ok(Self {
+                ordered_hashes,
+                aux_nonce,
+                root,
+            });
+        }
+        Err(MmProxyError::AuxChainError(format!(
+            "No aux nonce places the {} chains at distinct positions",
+            chain_count
+        )))
+    }
+
+    pub fn chain_count(&self) -> u8 {
+        // Checked when the tree is built
+        u8::try_from(self.ordered_hashes.len()).unwrap_or(u8::MAX)
+    }
+}
+
+/// The position that a merkle proof for each leaf of a tree with `count` leaves resolves to. This is the position a
+/// chain is checked against when it verifies its merge mining proof.
+fn merkle_proof_positions(count: usize) -> Result<Vec<u32>, MmProxyError> {
+    let leaves = (0..count)
+        .map(|i| {
+            let mut bytes = [0u8; 32];
+            bytes[..8].copy_from_slice(&(i as u64 + 1).to_le_bytes());
+            monero::Hash::from_slice(&bytes)
+        })
+        .collect::<Vec<_>>();
+    leaves
+        .iter()
+        .map(|leaf| {
+            create_merkle_proof(&leaves, leaf)
+                .map(|proof| proof.calculate_root_with_pos(leaf).1)
+                .ok_or_else(|| MmProxyError::AuxChainError("Could not create a merkle proof".to_string()))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn it_places_every_chain_at_its_position() {
+        let chain_ids = [vec![1u8; 32], vec![2u8; 32], vec![3u8; 32]];
+        let hashes = [
+            monero::Hash::from_slice(&[11u8; 32]),
+            monero::Hash::from_slice(&[12u8; 32]),
+            monero::Hash::from_slice(&[13u8; 32]),
+        ];
+        let chains = chain_ids
+            .iter()
+            .zip(hashes.iter())
+            .map(|(id, hash)| (id.as_slice(), *hash))
+            .collect::<Vec<_>>();
+        let tree = AuxMerkleTree::build(&chains).unwrap();
+        assert_eq!(tree.chain_count(), 3);
+        assert_eq!(tree.root, tree_hash(&tree.ordered_hashes).unwrap());
+        for (chain_id, hash) in &chains {
+            let proof = create_merkle_proof(&tree.ordered_hashes, hash).unwrap();
+            let (root, position) = proof.calculate_root_with_pos(hash);
+            assert_eq!(root, tree.root);
+            assert_eq!(position, aux_chain_position(chain_id, tree.aux_nonce, 3));
+        }
+    }
+
+    #[test]
+    fn it_commits_to_a_single_chain_directly() {
+        let hash = monero::Hash::from_slice(&[7u8; 32]);
+        let tree = AuxMerkleTree::build(&[(&[1u8; 32][..], hash)]).unwrap();
+        assert_eq!(tree.root, hash);
+        assert_eq!(tree.aux_nonce, 0);
+        assert!(AuxMerkleTree::build(&[]).is_err());
+    }
+}
@@ -22,7 +22,11 @@
 
 //! Provides methods for for building template data and storing them with timestamps.
 
-use std::{collections::HashMap, sync::Arc};
+use std::{
+    collections::HashMap,
+    sync::Arc,
+    time::{Duration as StdDuration, Instant},
+};
 
 #[cfg(not(test))]
 use chrono::Duration;
@@ -30,10 +34,10 @@ use chrono::{self, DateTime, Utc};
 use minotari_node_grpc_client::grpc;
 use tari_common_types::types::FixedHash;
 use tari_core::proof_of_work::monero_rx::FixedByteArray;
-use tokio::sync::RwLock;
+use tokio::sync::{Mutex, MutexGuard, RwLock};
 use tracing::trace;
 
-use crate::error::MmProxyError;
+use crate::{aux_chain::AuxChainTemplate, error::MmProxyError};
 
 const LOG_TARGET: &str = "minotari_mm_proxy::xmrig";
 
@@ -118,6 +122,51 @@ impl BlockTemplateRepository {
     }
 }
 
+/// A Minotari block with a coinbase, ready to be merge mined on top of the tip it was built for
+#[derive(Debug, Clone)]
+pub struct CachedTariBlock {
+    pub tip_hash: Vec<u8>,
+    pub new_block: grpc::GetNewBlockResult,
+    pub miner_data: grpc::MinerData,
+    created_at: Instant,
+}
+
+impl CachedTariBlock {
+    pub fn new(tip_hash: Vec<u8>, new_block: grpc::GetNewBlockResult, miner_data: grpc::MinerData) -> Self {
+        Self {
+            tip_hash,
+            new_block,
+            miner_data,
+            created_at: Instant::now(),
+        }
+    }
+
+    /// Whether the block can still be mined on `tip_hash`. Blocks older than `max_age` are rebuilt so that new mempool
+    /// transactions are included.
+    pub fn is_valid_for(&self, tip_hash: &[u8], max_age: StdDuration) -> bool {
+        self.tip_hash == tip_hash && self.created_at.elapsed() < max_age
+    }
+}
+
+/// Caches the Minotari block built for the current tip, so that `get_block_template` calls from many miners do not
+/// each build a new block on the base node
+#[derive(Debug, Clone, Default)]
+pub struct TariBlockCache {
+    block: Arc<Mutex<Option<CachedTariBlock>>>,
+}
+
+impl TariBlockCache {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Locks the cache. The lock is held while a new block is built, so that concurrent requests wait for that block
+    /// instead of building their own.
+    pub async fn lock(&self) -> MutexGuard<'_, Option<CachedTariBlock>> {
+        self.block.lock().await
+    }
+}
+
 /// Setup values for the new block.
 #[derive(Clone, Debug)]
 pub struct BlockTemplateData {
@@ -128,6 +177,9 @@ pub struct BlockTemplateData {
     pub tari_difficulty: u64,
     pub tari_hash: FixedHash,
     pub aux_chain_hashes: Vec<monero::Hash>,
+    /// The aux chains, other than Minotari, merge mined with this template
+    pub aux_chains: Vec<AuxChainTemplate>,
+    pub aux_nonce: u32,
 }
 
 impl BlockTemplateData {}
@@ -142,6 +194,8 @@ pub struct BlockTemplateDataBuilder {
     tari_difficulty: Option<u64>,
     tari_hash: Option<FixedHash>,
     aux_chain_hashes: Vec<monero::Hash>,
+    aux_chains: Vec<AuxChainTemplate>,
+    aux_nonce: u32,
 }
 
 impl BlockTemplateDataBuilder {
@@ -184,6 +238,12 @@ impl BlockTemplateDataBuilder {
         self
     }
 
+    pub fn aux_chains(mut self, aux_chains: Vec<AuxChainTemplate>, aux_nonce: u32) -> Self {
+        self.aux_chains = aux_chains;
+        self.aux_nonce = aux_nonce;
+        self
+    }
+
     /// Build a new [BlockTemplateData], all the values have to be set.
     ///
     /// # Errors
@@ -220,6 +280,8 @@ impl BlockTemplateDataBuilder {
             tari_difficulty,
             tari_hash,
             aux_chain_hashes: self.aux_chain_hashes,
+            aux_chains: self.aux_chains,
+            aux_nonce: self.aux_nonce,
         })
     }
 }
@@ -36,9 +36,11 @@ use tari_core::{
         transaction_components::{TransactionKernel, TransactionOutput},
     },
 };
+use tari_utilities::hex::Hex;
 
 use crate::{
-    block_template_data::{BlockTemplateData, BlockTemplateDataBuilder},
+    aux_chain::{AuxChainTemplate, AuxMerkleTree},
+    block_template_data::{BlockTemplateData, BlockTemplateDataBuilder, CachedTariBlock, TariBlockCache},
     common::merge_mining,
     config::MergeMiningProxyConfig,
     error::MmProxyError,
@@ -53,6 +55,7 @@ pub struct BlockTemplateProtocol<'a> {
     key_manager: MemoryDbKeyManager,
     wallet_payment_address: TariAddress,
     consensus_manager: ConsensusManager,
+    block_cache: TariBlockCache,
 }
 
 impl<'a> BlockTemplateProtocol<'a> {
@@ -61,6 +64,7 @@ impl<'a> BlockTemplateProtocol<'a> {
         config: Arc<MergeMiningProxyConfig>,
         consensus_manager: ConsensusManager,
         wallet_payment_address: TariAddress,
+        block_cache: TariBlockCache,
     ) -> Result<BlockTemplateProtocol<'a>, MmProxyError> {
         let key_manager = create_memory_db_key_manager();
         Ok(Self {
@@ -69,6 +73,7 @@ impl<'a> BlockTemplateProtocol<'a> {
             key_manager,
             wallet_payment_address,
             consensus_manager,
+            block_cache,
         })
     }
 }
@@ -78,7 +83,28 @@ impl BlockTemplateProtocol<'_> {
     pub async fn get_next_block_template(
         mut self,
         monero_mining_data: MoneroMiningData,
+        aux_chains: Vec<AuxChainTemplate>,
     ) -> Result<FinalBlockTemplateData, MmProxyError> {
+        let block_cache = self.block_cache.clone();
+        let mut cached_block = block_cache.lock().await;
+        let tip_hash = self.get_tip_hash().await?;
+        if let Some(cached) = cached_block
+            .as_ref()
+            .filter(|cached| cached.is_valid_for(&tip_hash, self.config.tari_template_cache_max_age))
+        {
+            debug!(
+                target: LOG_TARGET,
+                "Reusing the cached Minotari block for tip {}",
+                tip_hash.to_hex()
+            );
+            return self.add_monero_data(
+                cached.new_block.clone(),
+                monero_mining_data,
+                cached.miner_data.clone(),
+                aux_chains,
+            );
+        }
+
         loop {
             let new_template = self.get_new_block_template().await?;
             let (coinbase_output, coinbase_kernel) = self.get_coinbase(&new_template).await?;
@@ -118,7 +144,12 @@ impl BlockTemplateProtocol<'_> {
                 Err(err) => return Err(err),
             };
 
-            let final_block = self.add_monero_data(block, monero_mining_data, new_template)?;
+            *cached_block = Some(CachedTariBlock::new(
+                tip_hash,
+                block.clone(),
+                new_template.miner_data.clone(),
+            ));
+            let final_block = self.add_monero_data(block, monero_mining_data, new_template.miner_data, aux_chains)?;
             return Ok(final_block);
         }
     }
@@ -171,6 +202,14 @@ impl BlockTemplateProtocol<'_> {
         })
     }
 
+    /// Get the hash of the current tip, which identifies the cached block
+    async fn get_tip_hash(&mut self) -> Result<Vec<u8>, MmProxyError> {
+        let tip = self.base_node_client.get_tip_info(grpc::Empty {}).await?.into_inner();
+        tip.metadata
+            .map(|meta| meta.best_block_hash)
+            .ok_or(MmProxyError::GrpcResponseMissingField("base node metadata"))
+    }
+
     /// Check if the height is more than the actual tip. So if still makes sense to compute block for that height.
     async fn check_expected_tip(&mut self, height: u64) -> Result<bool, MmProxyError> {
         let tip = self
@@ -218,32 +257,39 @@ impl BlockTemplateProtocol<'_> {
         Ok((coinbase_output, coinbase_kernel))
     }
 
-    /// Build the [FinalBlockTemplateData] from [template](NewBlockTemplateData) and with
-    /// [tari](grpc::GetNewBlockResult) and [monero data](MoneroMiningData).
+    /// Build the [FinalBlockTemplateData] from the [miner data](grpc::MinerData) of the template and with
+    /// [tari](grpc::GetNewBlockResult) and [monero data](MoneroMiningData), merge mining the given aux chains.
     fn add_monero_data(
         &self,
         tari_block: grpc::GetNewBlockResult,
         monero_mining_data: MoneroMiningData,
-        template_data: NewBlockTemplateData,
+        miner_data: grpc::MinerData,
+        mut aux_chains: Vec<AuxChainTemplate>,
     ) -> Result<FinalBlockTemplateData, MmProxyError> {
         debug!(target: LOG_TARGET, "New block received from Minotari: {:?}", tari_block);
 
-        let tari_difficulty = template_data.miner_data.target_difficulty;
+        let tari_difficulty = miner_data.target_difficulty;
+        let merge_mining_hash = FixedHash::try_from(tari_block.merge_mining_hash.clone())
+            .map_err(|e| MmProxyError::MissingDataError(e.to_string()))?;
+        let aux_tree = self.build_aux_merkle_tree(&merge_mining_hash, &aux_chains)?;
+        if aux_tree.chain_count() == 1 {
+            aux_chains.clear();
+        }
+        let aux_difficulty = aux_chains.iter().map(|chain| chain.difficulty).min();
+
         let block_template_data = BlockTemplateDataBuilder::new()
             .tari_block(
                 tari_block
                     .block
                     .ok_or(MmProxyError::GrpcResponseMissingField("block"))?,
             )
-            .tari_miner_data(template_data.miner_data)
+            .tari_miner_data(miner_data)
             .monero_seed(monero_mining_data.seed_hash)
             .monero_difficulty(monero_mining_data.difficulty)
             .tari_difficulty(tari_difficulty)
-            .tari_hash(
-                FixedHash::try_from(tari_block.merge_mining_hash.clone())
-                    .map_err(|e| MmProxyError::MissingDataError(e.to_string()))?,
-            )
-            .aux_hashes(vec![monero::Hash::from_slice(&tari_block.merge_mining_hash)])
+            .tari_hash(merge_mining_hash)
+            .aux_hashes(aux_tree.ordered_hashes.clone())
+            .aux_chains(aux_chains, aux_tree.aux_nonce)
             .build()?;
 
         // Deserialize the block template blob
@@ -251,15 +297,13 @@ impl BlockTemplateProtocol<'_> {
         let mut monero_block = monero_rx::deserialize_monero_block_from_hex(&monero_mining_data.blocktemplate_blob)?;
 
         debug!(target: LOG_TARGET, "Insert Merged Mining Tag",);
-        // Add the Tari merge mining tag to the retrieved block template
-        // We need to send the MR al all aux chains, but a single chain, aka minotari only, means we only need the tari
-        // hash
-        let aux_chain_mr = tari_block.merge_mining_hash.clone();
+        // Add the merge mining tag to the retrieved block template. With a single chain, aka minotari only, the aux
+        // chain merkle root is the tari hash.
         monero_rx::insert_merge_mining_tag_and_aux_chain_merkle_root_into_block(
             &mut monero_block,
-            &aux_chain_mr,
-            1,
-            0,
+            aux_tree.root,
+            aux_tree.chain_count(),
+            aux_tree.aux_nonce,
         )?;
 
         debug!(target: LOG_TARGET, "Creating blockhashing blob from blocktemplate blob",);
@@ -269,25 +313,51 @@ impl BlockTemplateProtocol<'_> {
 
         let monero_difficulty = monero_mining_data.difficulty;
         let mining_difficulty = cmp::min(monero_difficulty, tari_difficulty);
+        let mining_difficulty = aux_difficulty.map_or(mining_difficulty, |aux| cmp::min(mining_difficulty, aux));
         info!(
             target: LOG_TARGET,
-            "Difficulties: Minotari ({}), Monero({}), Selected({})",
+            "Difficulties: Minotari ({}), Monero({}), Aux chains({:?}), Selected({})",
             tari_difficulty,
             monero_mining_data.difficulty,
+            aux_difficulty,
             mining_difficulty
         );
-        let merge_mining_hash = FixedHash::try_from(tari_block.merge_mining_hash.clone())
-            .map_err(|e| MmProxyError::MissingDataError(e.to_string()))?;
         Ok(FinalBlockTemplateData {
             template: block_template_data,
             target_difficulty: Difficulty::from_u64(mining_difficulty)?,
             blockhashing_blob,
             blocktemplate_blob,
             merge_mining_hash,
-            aux_chain_hashes: vec![monero::Hash::from_slice(&tari_block.merge_mining_hash)],
-            aux_chain_mr: tari_block.merge_mining_hash,
+            aux_chain_hashes: aux_tree.ordered_hashes,
+            aux_chain_mr: aux_tree.root.as_bytes().to_vec(),
         })
     }
+
+    /// Builds the merge mining merkle tree for Minotari and the aux chains. If the aux chains cannot be placed in the
+    /// tree, only Minotari is merge mined.
+    fn build_aux_merkle_tree(
+        &self,
+        merge_mining_hash: &FixedHash,
+        aux_chains: &[AuxChainTemplate],
+    ) -> Result<AuxMerkleTree, MmProxyError> {
+        let genesis_hash = *self.consensus_manager.get_genesis_block().hash();
+        let mut chains = vec![(
+            genesis_hash.as_slice(),
+            monero::Hash::from_slice(merge_mining_hash.as_slice()),
+        )];
+        chains.extend(aux_chains.iter().map(|chain| (chain.chain_id.as_slice(), chain.hash)));
+        match AuxMerkleTree::build(&chains) {
+            Ok(tree) => Ok(tree),
+            Err(e) if chains.len() > 1 => {
+                warn!(
+                    target: LOG_TARGET,
+                    "Could not merge mine aux chains, mining Minotari only: {}", e
+                );
+                AuxMerkleTree::build(&chains[..1])
+            },
+            Err(e) => Err(e),
+        }
+    }
 }
 
 /// Private convenience container struct for new template data
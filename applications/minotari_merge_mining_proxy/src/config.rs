@@ -20,18 +20,33 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use std::path::{Path, PathBuf};
+use std::{
+    path::{Path, PathBuf},
+    time::Duration,
+};
 
 use minotari_wallet_grpc_client::GrpcAuthentication;
 use serde::{Deserialize, Serialize};
 use tari_common::{
-    configuration::{Network, StringList},
+    configuration::{serializers, Network, StringList},
     SubConfigPath,
 };
 use tari_common_types::tari_address::TariAddress;
 use tari_comms::multiaddr::Multiaddr;
 use tari_core::transactions::transaction_components::RangeProofType;
 
+/// An additional chain merge mined alongside Minotari, see [crate::aux_chain] for the JSON-RPC contract it must serve
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct AuxChainConfig {
+    /// The name of the chain, used as its `id` in the `_aux` data returned to miners
+    pub name: String,
+    /// The JSON-RPC url of the chain's merge mining provider
+    pub url: String,
+    /// The hex encoded id of the chain, which determines its position in the merge mining merkle tree
+    pub chain_id: String,
+}
+
 #[derive(Clone, Debug, Deserialize, Serialize)]
 #[serde(deny_unknown_fields)]
 #[allow(clippy::struct_excessive_bools)]
@@ -45,6 +60,17 @@ pub struct MergeMiningProxyConfig {
     pub monerod_password: String,
     /// If authentication is being used for curl
     pub monerod_use_auth: bool,
+    /// How often all configured monerod servers are checked for their height and latency
+    #[serde(with = "serializers::seconds")]
+    pub monerod_health_check_interval: Duration,
+    /// A monerod server more than this many blocks behind the highest known monerod height is considered unhealthy
+    pub monerod_max_height_lag: u64,
+    /// A monerod server is considered unhealthy while more than this percentage of recent requests failed
+    pub monerod_max_error_rate_percent: u64,
+    /// The maximum time a Minotari block template is reused for `get_block_template` calls while the Minotari tip is
+    /// unchanged, after which it is rebuilt to include new mempool transactions
+    #[serde(with = "serializers::seconds")]
+    pub tari_template_cache_max_age: Duration,
     /// The Minotari base node's GRPC address
     pub base_node_grpc_address: Option<Multiaddr>,
     /// GRPC authentication for base node
@@ -83,6 +109,8 @@ pub struct MergeMiningProxyConfig {
     pub stealth_payment: bool,
     /// Range proof type - revealed_value or bullet_proof_plus: (default = revealed_value)
     pub range_proof_type: RangeProofType,
+    /// Additional chains to merge mine alongside Minotari
+    pub aux_chains: Vec<AuxChainConfig>,
 }
 
 impl Default for MergeMiningProxyConfig {
@@ -93,6 +121,10 @@ impl Default for MergeMiningProxyConfig {
             monerod_username: String::new(),
             monerod_password: String::new(),
             monerod_use_auth: false,
+            monerod_health_check_interval: Duration::from_secs(10),
+            monerod_max_height_lag: 2,
+            monerod_max_error_rate_percent: 50,
+            tari_template_cache_max_age: Duration::from_secs(30),
             base_node_grpc_address: None,
             base_node_grpc_authentication: GrpcAuthentication::default(),
             base_node_grpc_tls_domain_name: None,
@@ -108,6 +140,7 @@ impl Default for MergeMiningProxyConfig {
             wallet_payment_address: TariAddress::default().to_hex(),
             stealth_payment: true,
             range_proof_type: RangeProofType::RevealedValue,
+            aux_chains: Vec::new(),
         }
     }
 }
@@ -128,12 +161,12 @@ impl SubConfigPath for MergeMiningProxyConfig {
 
 #[cfg(test)]
 mod test {
-    use std::str::FromStr;
+    use std::{str::FromStr, time::Duration};
 
     use tari_common::DefaultConfigLoader;
     use tari_comms::multiaddr::Multiaddr;
 
-    use crate::config::MergeMiningProxyConfig;
+    use crate::config::{AuxChainConfig, MergeMiningProxyConfig};
 
     fn get_config(override_from: &str) -> config::Config {
         let s = r#"
@@ -147,7 +180,9 @@ mod test {
               base_node_grpc_address = "/dns4/base_node_a/tcp/8080"
             [config_b.merge_mining_proxy]
               submit_to_origin = false
-              monerod_url = [ "http://network.b.org" ]
+              monerod_url = [ "http://network.b.org", "http://network.c.org" ]
+              monerod_health_check_interval = 5
+              aux_chains = [{ name = "aux", url = "http://aux.b.org", chain_id = "0102" }]
               monerod_password = "password_stagenet"
               base_node_grpc_address = "/dns4/base_node_b/tcp/8080"
             "#;
@@ -164,7 +199,16 @@ mod test {
     fn merge_mining_proxy_configuration() {
         let cfg = get_config("config_b");
         let config = MergeMiningProxyConfig::load_from(&cfg).expect("Failed to load config");
-        assert_eq!(config.monerod_url.as_slice(), &["http://network.b.org".to_string()]);
+        assert_eq!(config.monerod_url.as_slice(), &[
+            "http://network.b.org".to_string(),
+            "http://network.c.org".to_string()
+        ]);
+        assert_eq!(config.monerod_health_check_interval, Duration::from_secs(5));
+        assert_eq!(config.aux_chains, vec![AuxChainConfig {
+            name: "aux".to_string(),
+            url: "http://aux.b.org".to_string(),
+            chain_id: "0102".to_string(),
+        }]);
         assert!(!config.submit_to_origin);
         assert_eq!(config.monerod_username.as_str(), "cmot");
         assert_eq!(config.monerod_password.as_str(), "password_stagenet");
@@ -191,5 +235,8 @@ mod test {
         assert_eq!(config.base_node_grpc_address, None);
         assert!(!config.monerod_use_auth);
         assert!(config.submit_to_origin);
+        assert_eq!(config.monerod_max_height_lag, 2);
+        assert_eq!(config.tari_template_cache_max_age, Duration::from_secs(30));
+        assert!(config.aux_chains.is_empty());
     }
 }
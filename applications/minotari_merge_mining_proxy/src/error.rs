@@ -111,6 +111,8 @@ pub enum MmProxyError {
     ParseInputError(#[from] ParseInputError),
     #[error("Base node not responding to gRPC requests: {0}")]
     BaseNodeNotResponding(String),
+    #[error("Aux chain error: {0}")]
+    AuxChainError(String),
 }
 
 impl From<tonic::Status> for MmProxyError {
@@ -23,6 +23,7 @@
 // non-64-bit not supported
 minotari_app_utilities::deny_non_64_bit_archs!();
 
+mod aux_chain;
 mod block_template_data;
 mod block_template_protocol;
 mod cli;
@@ -30,6 +31,7 @@ pub use cli::Cli;
 mod common;
 mod config;
 mod error;
+mod monerod_health;
 mod proxy;
 mod run_merge_miner;
 use run_merge_miner::start_merge_miner;
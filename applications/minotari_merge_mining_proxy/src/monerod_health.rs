@@ -0,0 +1,325 @@
+// Copyright 2024. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Health tracking for the configured monerod servers. Every server is polled for its height and latency, and requests
+//! are spread over the healthy servers, weighted towards those with a low latency and error rate.
+
+use std::{
+    sync::{Arc, RwLock},
+    time::{Duration, Instant},
+};
+
+use rand::Rng;
+use reqwest::Url;
+use serde_json as json;
+use tokio::time::{self, MissedTickBehavior};
+use tracing::{debug, info, warn};
+
+use crate::{config::MergeMiningProxyConfig, error::MmProxyError};
+
+const LOG_TARGET: &str = "minotari_mm_proxy::monerod_health";
+
+/// The weight of the newest observation in the latency and error rate moving averages
+const SMOOTHING_FACTOR: f64 = 0.2;
+/// The latency assumed for a server that has not responded yet
+const UNKNOWN_LATENCY_MS: f64 = 1_000.0;
+
+#[derive(Debug, Clone, Default)]
+struct ServerStats {
+    height: Option<u64>,
+    /// Moving average of the response latency in milliseconds
+    latency_ms: Option<f64>,
+    /// Moving average of the fraction of failed requests
+    error_rate: f64,
+    /// The health as of the last health check, used to log changes
+    healthy: bool,
+}
+
+impl ServerStats {
+    fn record_success(&mut self, latency: Duration) {
+        let latency_ms = latency.as_secs_f64() * 1_000.0;
+        self.latency_ms = Some(match self.latency_ms {
+            Some(average) => average + SMOOTHING_FACTOR * (latency_ms - average),
+            None => latency_ms,
+        });
+        self.error_rate *= 1.0 - SMOOTHING_FACTOR;
+    }
+
+    fn record_failure(&mut self) {
+        self.error_rate = self.error_rate * (1.0 - SMOOTHING_FACTOR) + SMOOTHING_FACTOR;
+    }
+
+    fn weight(&self) -> f64 {
+        (1.0 - self.error_rate) / self.latency_ms.unwrap_or(UNKNOWN_LATENCY_MS).max(1.0)
+    }
+}
+
+#[derive(Debug)]
+struct MonerodServer {
+    url: String,
+    stats: RwLock<ServerStats>,
+}
+
+impl MonerodServer {
+    fn stats(&self) -> ServerStats {
+        self.stats.read().expect("Read lock should not fail").clone()
+    }
+
+    fn update<F: FnOnce(&mut ServerStats)>(&self, f: F) {
+        f(&mut self.stats.write().expect("Write lock should not fail"));
+    }
+}
+
+/// The configured monerod servers and their health
+#[derive(Debug, Clone)]
+pub struct MonerodServers {
+    servers: Arc<Vec<MonerodServer>>,
+    check_interval: Duration,
+    max_height_lag: u64,
+    max_error_rate: f64,
+    credentials: Option<(String, String)>,
+}
+
+impl MonerodServers {
+    pub fn new(config: &MergeMiningProxyConfig) -> Self {
+        let servers = config
+            .monerod_url
+            .iter()
+            .map(|url| MonerodServer {
+                url: url.clone(),
+                stats: RwLock::new(ServerStats {
+                    healthy: true,
+                    ..Default::default()
+                }),
+            })
+            .collect();
+        Self {
+            servers: Arc::new(servers),
+            check_interval: config.monerod_health_check_interval,
+            max_height_lag: config.monerod_max_height_lag,
+            max_error_rate: config.monerod_max_error_rate_percent.min(100) as f64 / 100.0,
+            credentials: config
+                .monerod_use_auth
+                .then(|| (config.monerod_username.clone(), config.monerod_password.clone())),
+        }
+    }
+
+    /// Picks a healthy server at random, weighted by latency and error rate. If no server is healthy, the server with
+    /// the lowest error rate is returned so that requests are still attempted.
+    pub fn select(&self) -> Result<String, MmProxyError> {
+        let stats = self
+            .servers
+            .iter()
+            .map(|server| (server.url.as_str(), server.stats()))
+            .collect::<Vec<_>>();
+        let best_height = stats.iter().filter_map(|(_, s)| s.height).max();
+        let healthy = stats
+            .iter()
+            .filter(|(_, s)| self.is_healthy(s, best_height))
+            .collect::<Vec<_>>();
+
+        if healthy.is_empty() {
+            return stats
+                .iter()
+                .min_by(|(_, a), (_, b)| a.error_rate.total_cmp(&b.error_rate))
+                .map(|(url, _)| {
+                    debug!(target: LOG_TARGET, "No healthy monerod server, falling back to {}", url);
+                    (*url).to_string()
+                })
+                .ok_or(MmProxyError::ServersUnavailable);
+        }
+
+        let total_weight = healthy.iter().map(|(_, s)| s.weight()).sum::<f64>();
+        let mut choice = rand::thread_rng().gen_range(0.0..=total_weight);
+        for (url, s) in &healthy {
+            choice -= s.weight();
+            if choice <= 0.0 {
+                return Ok((*url).to_string());
+            }
+        }
+        // Only reachable through floating point rounding
+        Ok(healthy[healthy.len() - 1].0.to_string())
+    }
+
+    pub fn record_success(&self, url: &str, latency: Duration) {
+        self.update(url, |stats| stats.record_success(latency));
+    }
+
+    pub fn record_failure(&self, url: &str) {
+        self.update(url, ServerStats::record_failure);
+    }
+
+    /// Polls the height of every server at the configured interval
+    pub async fn run_health_checks(self, http_client: reqwest::Client) {
+        let mut interval = time::interval(self.check_interval);
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        loop {
+            interval.tick().await;
+            let checks = self
+                .servers
+                .iter()
+                .map(|server| self.check_server(&http_client, server));
+            futures::future::join_all(checks).await;
+            self.log_health_changes();
+        }
+    }
+
+    async fn check_server(&self, http_client: &reqwest::Client, server: &MonerodServer) {
+        let url = match format!("{}/get_height", server.url).parse::<Url>() {
+            Ok(url) => url,
+            Err(e) => {
+                warn!(target: LOG_TARGET, "Invalid monerod url '{}': {}", server.url, e);
+                server.update(ServerStats::record_failure);
+                return;
+            },
+        };
+        let mut request = http_client.get(url);
+        if let Some((username, password)) = self.credentials.as_ref() {
+            request = request.basic_auth(username, Some(password));
+        }
+
+        let start = Instant::now();
+        let height = match request.send().await {
+            Ok(response) => response
+                .json::<json::Value>()
+                .await
+                .ok()
+                .and_then(|v| v["height"].as_u64()),
+            Err(e) => {
+                debug!(target: LOG_TARGET, "Monerod server {} health check failed: {}", server.url, e);
+                None
+            },
+        };
+        match height {
+            Some(height) => server.update(|stats| {
+                stats.record_success(start.elapsed());
+                stats.height = Some(height);
+            }),
+            None => server.update(ServerStats::record_failure),
+        }
+    }
+
+    fn log_health_changes(&self) {
+        let best_height = self.servers.iter().filter_map(|server| server.stats().height).max();
+        for server in self.servers.iter() {
+            let stats = server.stats();
+            let healthy = self.is_healthy(&stats, best_height);
+            if healthy == stats.healthy {
+                continue;
+            }
+            if healthy {
+                info!(
+                    target: LOG_TARGET,
+                    "Monerod server {} is healthy again (height {:?})", server.url, stats.height
+                );
+            } else {
+                warn!(
+                    target: LOG_TARGET,
+                    "Monerod server {} is unhealthy: height {:?} of {:?}, {:.0}% errors, {:.0}ms latency",
+                    server.url,
+                    stats.height,
+                    best_height,
+                    stats.error_rate * 100.0,
+                    stats.latency_ms.unwrap_or_default()
+                );
+            }
+            server.update(|stats| stats.healthy = healthy);
+        }
+    }
+
+    /// A server is healthy when it is close enough to the highest known height and most recent requests succeeded.
+    /// Before any height is known, all servers are considered healthy.
+    fn is_healthy(&self, stats: &ServerStats, best_height: Option<u64>) -> bool {
+        let height_ok = match (stats.height, best_height) {
+            (Some(height), Some(best)) => best.saturating_sub(height) <= self.max_height_lag,
+            (None, Some(_)) => false,
+            (_, None) => true,
+        };
+        height_ok && stats.error_rate <= self.max_error_rate
+    }
+
+    fn update<F: FnOnce(&mut ServerStats)>(&self, url: &str, f: F) {
+        if let Some(server) = self.servers.iter().find(|server| server.url == url) {
+            server.update(f);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use tari_common::configuration::StringList;
+
+    use super::*;
+
+    fn servers(urls: &[&str]) -> MonerodServers {
+        let mut config = MergeMiningProxyConfig::default();
+        config.monerod_url = StringList::from(urls.iter().map(|url| url.to_string()).collect::<Vec<_>>());
+        MonerodServers::new(&config)
+    }
+
+    fn set_height(servers: &MonerodServers, url: &str, height: u64) {
+        servers.update(url, |stats| {
+            stats.record_success(Duration::from_millis(50));
+            stats.height = Some(height);
+        });
+    }
+
+    #[test]
+    fn it_excludes_lagging_and_failing_servers() {
+        let servers = servers(&["http://a", "http://b", "http://c"]);
+        set_height(&servers, "http://a", 100);
+        set_height(&servers, "http://b", 90);
+        set_height(&servers, "http://c", 100);
+        for _ in 0..10 {
+            servers.record_failure("http://c");
+        }
+        for _ in 0..20 {
+            assert_eq!(servers.select().unwrap(), "http://a");
+        }
+    }
+
+    #[test]
+    fn it_falls_back_to_the_least_failing_server() {
+        let servers = servers(&["http://a", "http://b"]);
+        for _ in 0..10 {
+            servers.record_failure("http://a");
+            servers.record_failure("http://b");
+        }
+        servers.record_success("http://b", Duration::from_millis(10));
+        assert_eq!(servers.select().unwrap(), "http://b");
+        assert!(matches!(
+            self::servers(&[]).select(),
+            Err(MmProxyError::ServersUnavailable)
+        ));
+    }
+
+    #[test]
+    fn it_prefers_low_latency_servers() {
+        let servers = servers(&["http://fast", "http://slow"]);
+        servers.record_success("http://fast", Duration::from_millis(10));
+        servers.record_success("http://slow", Duration::from_millis(1_000));
+        let fast = (0..1_000)
+            .filter(|_| servers.select().unwrap() == "http://fast")
+            .count();
+        assert!(fast > 900);
+    }
+}
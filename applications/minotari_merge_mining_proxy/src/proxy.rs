@@ -28,7 +28,6 @@ use std::{
     sync::{
         atomic::{AtomicBool, Ordering},
         Arc,
-        RwLock,
     },
     task::{Context, Poll},
     time::Instant,
@@ -52,11 +51,13 @@ use tari_utilities::hex::Hex;
 use tracing::{debug, error, info, instrument, trace, warn};
 
 use crate::{
-    block_template_data::BlockTemplateRepository,
+    aux_chain::AuxChains,
+    block_template_data::{BlockTemplateRepository, TariBlockCache},
     block_template_protocol::{BlockTemplateProtocol, MoneroMiningData},
     common::{json_rpc, monero_rpc::CoreRpcErrorCode, proxy, proxy::convert_json_to_hyper_json_response},
     config::MergeMiningProxyConfig,
     error::MmProxyError,
+    monerod_health::MonerodServers,
 };
 
 const LOG_TARGET: &str = "minotari_mm_proxy::proxy";
@@ -76,6 +77,8 @@ impl MergeMiningProxyService {
         http_client: reqwest::Client,
         base_node_client: BaseNodeGrpcClient,
         block_templates: BlockTemplateRepository,
+        monerod_servers: MonerodServers,
+        aux_chains: AuxChains,
         randomx_factory: RandomXFactory,
         wallet_payment_address: TariAddress,
     ) -> Result<Self, MmProxyError> {
@@ -85,11 +88,12 @@ impl MergeMiningProxyService {
             inner: InnerService {
                 config: Arc::new(config),
                 block_templates,
+                tari_block_cache: TariBlockCache::new(),
                 http_client,
                 base_node_client,
                 initial_sync_achieved: Arc::new(AtomicBool::new(false)),
-                current_monerod_server: Arc::new(RwLock::new(None)),
-                last_assigned_monerod_server: Arc::new(RwLock::new(None)),
+                monerod_servers,
+                aux_chains,
                 randomx_factory,
                 consensus_manager,
                 wallet_payment_address,
@@ -157,11 +161,12 @@ impl Service<Request<Body>> for MergeMiningProxyService {
 struct InnerService {
     config: Arc<MergeMiningProxyConfig>,
     block_templates: BlockTemplateRepository,
+    tari_block_cache: TariBlockCache,
     http_client: reqwest::Client,
     base_node_client: BaseNodeGrpcClient,
     initial_sync_achieved: Arc<AtomicBool>,
-    current_monerod_server: Arc<RwLock<Option<String>>>,
-    last_assigned_monerod_server: Arc<RwLock<Option<String>>>,
+    monerod_servers: MonerodServers,
+    aux_chains: AuxChains,
     randomx_factory: RandomXFactory,
     consensus_manager: ConsensusManager,
     wallet_payment_address: TariAddress,
@@ -284,7 +289,10 @@ impl InnerService {
             let tari_header = header_mut.clone().try_into().map_err(MmProxyError::ConversionError)?;
             let mut base_node_client = self.base_node_client.clone();
             let start = Instant::now();
-            let achieved_target = if self.config.check_tari_difficulty_before_submit {
+            // The achieved difficulty is also needed to decide which aux chains the block solves
+            let achieved_target = if self.config.check_tari_difficulty_before_submit ||
+                !block_data.aux_chains.is_empty()
+            {
                 trace!(target: LOG_TARGET, "Starting calculate achieved Tari difficultly");
                 let diff = randomx_difficulty(&tari_header, &self.randomx_factory, &gen_hash, &self.consensus_manager)?;
                 trace!(
@@ -298,6 +306,30 @@ impl InnerService {
                 block_data.tari_difficulty
             };
 
+            let seed_hash = block_data.monero_seed.to_hex();
+            for aux_chain in block_data.aux_chains.iter().filter(|c| achieved_target >= c.difficulty) {
+                match self
+                    .aux_chains
+                    .submit(
+                        aux_chain,
+                        param,
+                        &seed_hash,
+                        &block_data.aux_chain_hashes,
+                        block_data.aux_nonce,
+                    )
+                    .await
+                {
+                    Ok(()) => info!(
+                        target: LOG_TARGET,
+                        "Submitted block #{} to aux chain '{}'", aux_chain.height, aux_chain.name
+                    ),
+                    Err(err) => warn!(
+                        target: LOG_TARGET,
+                        "Problem submitting block #{} to aux chain '{}': {}", aux_chain.height, aux_chain.name, err
+                    ),
+                }
+            }
+
             if achieved_target >= block_data.tari_difficulty {
                 match base_node_client.submit_block(block_data.tari_block).await {
                     Ok(resp) => {
@@ -440,6 +472,7 @@ impl InnerService {
             self.config.clone(),
             self.consensus_manager.clone(),
             self.wallet_payment_address.clone(),
+            self.tari_block_cache.clone(),
         )
         .await?;
 
@@ -455,7 +488,10 @@ impl InnerService {
             difficulty,
         };
 
-        let final_block_template_data = new_block_protocol.get_next_block_template(monero_mining_data).await?;
+        let aux_chain_templates = self.aux_chains.get_templates().await;
+        let final_block_template_data = new_block_protocol
+            .get_next_block_template(monero_mining_data, aux_chain_templates)
+            .await?;
 
         monerod_resp["result"]["blocktemplate_blob"] = final_block_template_data.blocktemplate_blob.into();
         monerod_resp["result"]["blockhashing_blob"] = final_block_template_data.blockhashing_blob.into();
@@ -475,7 +511,7 @@ impl InnerService {
             monerod_resp,
             json!({ "base_difficulty": final_block_template_data.template.monero_difficulty }),
         );
-        let monerod_resp = append_aux_chain_data(
+        let mut monerod_resp = append_aux_chain_data(
             monerod_resp,
             json!({
                 "id": TARI_CHAIN_ID,
@@ -487,6 +523,18 @@ impl InnerService {
             }),
         );
 
+        for aux_chain in &final_block_template_data.template.aux_chains {
+            monerod_resp = append_aux_chain_data(
+                monerod_resp,
+                json!({
+                    "id": aux_chain.name,
+                    "difficulty": aux_chain.difficulty,
+                    "height": aux_chain.height,
+                    "mining_hash": hex::encode(aux_chain.hash),
+                }),
+            );
+        }
+
         self.block_templates
             .save(
                 final_block_template_data.aux_chain_mr,
@@ -606,63 +654,11 @@ impl InnerService {
         Ok(proxy::into_response(parts, &resp))
     }
 
-    async fn get_fully_qualified_monerod_url(&self, uri: &Uri) -> Result<Url, MmProxyError> {
-        {
-            let lock = self
-                .current_monerod_server
-                .read()
-                .expect("Read lock should not fail")
-                .clone();
-            if let Some(server) = lock {
-                let uri = format!("{}{}", server, uri.path()).parse::<Url>()?;
-                return Ok(uri);
-            }
-        }
-
-        let last_used_url = {
-            let lock = self
-                .last_assigned_monerod_server
-                .read()
-                .expect("Read lock should not fail")
-                .clone();
-            match lock {
-                Some(url) => url,
-                None => "".to_string(),
-            }
-        };
-
-        // Query the list twice before giving up, starting after the last used entry
-        let pos = if let Some(index) = self.config.monerod_url.iter().position(|x| x == &last_used_url) {
-            index
-        } else {
-            0
-        };
-        let (left, right) = self.config.monerod_url.split_at(pos);
-        let left = left.to_vec();
-        let right = right.to_vec();
-        let iter = right.iter().chain(left.iter()).chain(right.iter()).chain(left.iter());
-
-        for next_url in iter {
-            let uri = format!("{}{}", next_url, uri.path()).parse::<Url>()?;
-            match reqwest::get(uri.clone()).await {
-                Ok(_) => {
-                    let mut lock = self.current_monerod_server.write().expect("Write lock should not fail");
-                    *lock = Some(next_url.to_string());
-                    let mut lock = self
-                        .last_assigned_monerod_server
-                        .write()
-                        .expect("Write lock should not fail");
-                    *lock = Some(next_url.to_string());
-                    info!(target: LOG_TARGET, "Monerod server available: {:?}", uri.clone());
-                    return Ok(uri);
-                },
-                Err(_) => {
-                    warn!(target: LOG_TARGET, "Monerod server unavailable: {:?}", uri);
-                },
-            }
-        }
-
-        Err(MmProxyError::ServersUnavailable)
+    /// Selects a monerod server and returns it along with the full url for the request
+    fn get_fully_qualified_monerod_url(&self, uri: &Uri) -> Result<(String, Url), MmProxyError> {
+        let server = self.monerod_servers.select()?;
+        let url = format!("{}{}", server, uri.path()).parse::<Url>()?;
+        Ok((server, url))
     }
 
     /// Proxy a request received by this server to Monerod
@@ -670,7 +666,7 @@ impl InnerService {
         &self,
         request: Request<Bytes>,
     ) -> Result<(Request<Bytes>, Response<json::Value>), MmProxyError> {
-        let monerod_uri = self.get_fully_qualified_monerod_url(request.uri()).await?;
+        let (monerod_server, monerod_uri) = self.get_fully_qualified_monerod_url(request.uri())?;
 
         let mut headers = request.headers().clone();
         // Some public monerod setups (e.g. those that are reverse proxied by nginx) require the Host header.
@@ -750,12 +746,21 @@ impl InnerService {
 
             convert_json_to_hyper_json_response(accept_response, StatusCode::OK, monerod_uri.clone()).await?
         } else {
+            let start = Instant::now();
             let resp = builder
                 // This is a cheap clone of the request body
                 .body(body)
                 .send()
                 .await
-                .map_err(MmProxyError::MonerodRequestFailed)?;
+                .map_err(|e| {
+                    self.monerod_servers.record_failure(&monerod_server);
+                    MmProxyError::MonerodRequestFailed(e)
+                })?;
+            if resp.status().is_server_error() {
+                self.monerod_servers.record_failure(&monerod_server);
+            } else {
+                self.monerod_servers.record_success(&monerod_server, start.elapsed());
+            }
             convert_reqwest_response_to_hyper_json_response(resp).await?
         };
 
@@ -827,43 +832,34 @@ impl InnerService {
                 .join(","),
         );
 
-        match self.proxy_request_to_monerod(request).await {
-            Ok((request, monerod_resp)) => {
-                // Any failed (!= 200 OK) responses from Monero are immediately returned to the requester
-                let monerod_status = monerod_resp.status();
-                if !monerod_status.is_success() {
-                    // we dont break on monerod returning an error code.
-                    warn!(
-                        target: LOG_TARGET,
-                        "Monerod returned an error: {}",
-                        monerod_resp.status()
-                    );
-                    debug!(
-                        "Method: {}, MoneroD Status: {}, Proxy Status: N/A, Response Time: {}ms",
-                        method_name,
-                        monerod_status,
-                        start.elapsed().as_millis()
-                    );
-                    return Ok(monerod_resp.map(|json| json.to_string().into()));
-                }
-
-                let response = self.get_proxy_response(request, monerod_resp).await?;
-                debug!(
-                    "Method: {}, MoneroD Status: {}, Proxy Status: {}, Response Time: {}ms",
-                    method_name,
-                    monerod_status,
-                    response.status(),
-                    start.elapsed().as_millis()
-                );
-                Ok(response)
-            },
-            Err(e) => {
-                // Monero Server encountered a problem processing the request, reset the current monerod server
-                let mut lock = self.current_monerod_server.write().expect("Write lock should not fail");
-                *lock = None;
-                Err(e)
-            },
+        let (request, monerod_resp) = self.proxy_request_to_monerod(request).await?;
+        // Any failed (!= 200 OK) responses from Monero are immediately returned to the requester
+        let monerod_status = monerod_resp.status();
+        if !monerod_status.is_success() {
+            // we dont break on monerod returning an error code.
+            warn!(
+                target: LOG_TARGET,
+                "Monerod returned an error: {}",
+                monerod_resp.status()
+            );
+            debug!(
+                "Method: {}, MoneroD Status: {}, Proxy Status: N/A, Response Time: {}ms",
+                method_name,
+                monerod_status,
+                start.elapsed().as_millis()
+            );
+            return Ok(monerod_resp.map(|json| json.to_string().into()));
         }
+
+        let response = self.get_proxy_response(request, monerod_resp).await?;
+        debug!(
+            "Method: {}, MoneroD Status: {}, Proxy Status: {}, Response Time: {}ms",
+            method_name,
+            monerod_status,
+            response.status(),
+            start.elapsed().as_millis()
+        );
+        Ok(response)
     }
 }
 
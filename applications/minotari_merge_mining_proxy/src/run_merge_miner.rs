@@ -41,9 +41,11 @@ use tokio::time::Duration;
 use tonic::transport::{Certificate, ClientTlsConfig, Endpoint};
 
 use crate::{
+    aux_chain::AuxChains,
     block_template_data::BlockTemplateRepository,
     config::MergeMiningProxyConfig,
     error::MmProxyError,
+    monerod_health::MonerodServers,
     proxy::MergeMiningProxyService,
     Cli,
 };
@@ -90,11 +92,16 @@ pub async fn start_merge_miner(cli: Cli) -> Result<(), anyhow::Error> {
 
     let listen_addr = multiaddr_to_socketaddr(&config.listener_address)?;
     let randomx_factory = RandomXFactory::new(config.max_randomx_vms);
+    let monerod_servers = MonerodServers::new(&config);
+    tokio::spawn(monerod_servers.clone().run_health_checks(client.clone()));
+    let aux_chains = AuxChains::new(&config, client.clone())?;
     let randomx_service = MergeMiningProxyService::new(
         config,
         client,
         base_node_client,
         BlockTemplateRepository::new(),
+        monerod_servers,
+        aux_chains,
         randomx_factory,
         wallet_payment_address,
     )?;
@@ -31,7 +31,7 @@ use tari_common::{
 use tari_comms::{
     multiaddr::{Error as MultiaddrError, Multiaddr},
     peer_manager::Peer,
-    protocol::rpc::RpcServer,
+    protocol::rpc::{RpcMethodLimits, RpcServer},
     tor::TorIdentity,
     NodeIdentity,
     UnspawnedCommsNode,
@@ -49,7 +49,13 @@ use tari_core::{
     chain_storage::{async_db::AsyncBlockchainDb, BlockchainBackend, BlockchainDatabase},
     consensus::ConsensusManager,
     mempool,
-    mempool::{service::MempoolHandle, Mempool, MempoolServiceInitializer, MempoolSyncInitializer},
+    mempool::{
+        service::MempoolHandle,
+        Mempool,
+        MempoolReconciliationInitializer,
+        MempoolServiceInitializer,
+        MempoolSyncInitializer,
+    },
     proof_of_work::randomx_factory::RandomXFactory,
     transactions::CryptoFactories,
 };
@@ -109,6 +115,18 @@ where B: BlockchainBackend + 'static
 
         debug!(target: LOG_TARGET, "{} sync peer(s) configured", sync_peers.len());
 
+        let mempool_reconciliation = if mempool_config.enable_reconciliation {
+            Some(MempoolReconciliationInitializer::new(
+                mempool_config.clone(),
+                self.mempool.clone(),
+            ))
+        } else {
+            None
+        };
+        let mempool_reconciliation_protocol = mempool_reconciliation
+            .as_ref()
+            .map(|reconciliation| reconciliation.get_protocol_extension());
+
         let mempool_sync = MempoolSyncInitializer::new(mempool_config, self.mempool.clone());
         let mempool_protocol = mempool_sync.get_protocol_extension();
 
@@ -117,7 +135,7 @@ where B: BlockchainBackend + 'static
         p2p_config.transport.tor.identity = tor_identity;
         p2p_config.listener_liveness_check_interval = Some(Duration::from_secs(15));
 
-        let mut handles = StackBuilder::new(self.interrupt_signal)
+        let mut stack = StackBuilder::new(self.interrupt_signal)
             .add_initializer(P2pInitializer::new(
                 p2p_config.clone(),
                 peer_seeds.clone(),
@@ -142,6 +160,7 @@ where B: BlockchainBackend + 'static
                 base_node_config.state_machine.clone(),
             ))
             .add_initializer(MempoolServiceInitializer::new(
+                base_node_config.mempool.service.clone(),
                 self.mempool.clone(),
                 peer_message_subscriptions.clone(),
             ))
@@ -162,20 +181,29 @@ where B: BlockchainBackend + 'static
                 self.factories,
                 self.randomx_factory,
                 self.app_config.base_node.bypass_range_proof_verification,
-            ))
-            .build()
-            .await?;
+            ));
+        if let Some(mempool_reconciliation) = mempool_reconciliation {
+            stack = stack.add_initializer(mempool_reconciliation);
+        }
+        let mut handles = stack.build().await?;
 
         let comms = handles
             .take_handle::<UnspawnedCommsNode>()
             .expect("P2pInitializer was not added to the stack or did not add UnspawnedCommsNode");
 
         let comms = comms.add_protocol_extension(mempool_protocol);
+        let comms = match mempool_reconciliation_protocol {
+            Some(protocol) => comms.add_protocol_extension(protocol),
+            None => comms,
+        };
         let comms = Self::setup_rpc_services(comms, &handles, self.db.into(), &p2p_config);
 
         let comms = if p2p_config.transport.transport_type == TransportType::Tor {
             let path = base_node_config.tor_identity_file.clone();
+            let identity_file = base_node_config.identity_file.clone();
             let node_id = comms.node_identity();
+            // Called with the current identity on startup, when the onion key is rotated and when the previous onion
+            // address is retired at the end of the grace period
             let after_comms = move |identity: TorIdentity| {
                 let address_string = format!("/onion3/{}:{}", identity.service_id, identity.onion_port);
                 if let Err(e) = identity_management::save_as_json(&path, &identity) {
@@ -191,6 +219,9 @@ where B: BlockchainBackend + 'static
                 if !node_id.public_addresses().contains(&address) {
                     node_id.add_public_address(address);
                 }
+                if let Err(e) = identity_management::save_as_json(&identity_file, &*node_id) {
+                    error!(target: LOG_TARGET, "Failed to save node identity {:?}", e);
+                }
             };
             initialization::spawn_comms_using_transport(comms, p2p_config.transport.clone(), after_comms).await
         } else {
@@ -222,10 +253,18 @@ where B: BlockchainBackend + 'static
     ) -> UnspawnedCommsNode {
         let dht = handles.expect_handle::<Dht>();
         let base_node_service = handles.expect_handle::<LocalNodeCommsInterface>();
-        let rpc_server = RpcServer::builder()
+        let mut rpc_server = RpcServer::builder()
             .with_maximum_simultaneous_sessions(config.rpc_max_simultaneous_sessions)
             .with_maximum_sessions_per_client(config.rpc_max_sessions_per_peer)
-            .finish();
+            .with_default_method_limits(RpcMethodLimits {
+                max_requests_per_minute: config.rpc_max_requests_per_minute_per_peer,
+                max_concurrent_requests: config.rpc_max_concurrent_requests_per_peer,
+                max_bytes_per_minute: config.rpc_max_streamed_bytes_per_minute_per_peer,
+            });
+        if let Some(limit) = config.rpc_max_concurrent_streams {
+            rpc_server = rpc_server.with_maximum_concurrent_streams(limit);
+        }
+        let rpc_server = rpc_server.finish();
 
         // Add your RPC services here ‍🏴‍☠️️☮️🌊
         let rpc_server = rpc_server
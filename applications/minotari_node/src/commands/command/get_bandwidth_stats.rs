@@ -0,0 +1,109 @@
+//  Copyright 2024, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use anyhow::Error;
+use async_trait::async_trait;
+use clap::Parser;
+
+use super::{CommandContext, HandleCommand};
+use crate::table::Table;
+
+/// Displays total comms traffic, the peers that have transferred the most data and traffic per protocol
+#[derive(Debug, Parser)]
+pub struct Args {
+    /// The number of peers to display
+    #[clap(default_value = "10")]
+    num_peers: usize,
+}
+
+#[async_trait]
+impl HandleCommand<Args> for CommandContext {
+    async fn handle_command(&mut self, args: Args) -> Result<(), Error> {
+        self.get_bandwidth_stats(args.num_peers)
+    }
+}
+
+const BYTES_PER_KIB: f64 = 1024.0;
+
+fn format_kib(num_bytes: u64) -> String {
+    format!("{:.2}", num_bytes as f64 / BYTES_PER_KIB)
+}
+
+impl CommandContext {
+    /// Function to process the get-bandwidth-stats command
+    pub fn get_bandwidth_stats(&self, num_peers: usize) -> Result<(), Error> {
+        let bandwidth = self.comms.bandwidth();
+        let total = bandwidth.total_traffic();
+        println!(
+            "Total traffic: {} KiB received, {} KiB sent",
+            format_kib(total.bytes_read),
+            format_kib(total.bytes_written)
+        );
+        let config = bandwidth.config();
+        let format_limit = |limit: Option<u64>| {
+            limit
+                .map(|l| format!("{} KiB/s", format_kib(l)))
+                .unwrap_or_else(|| "unlimited".to_string())
+        };
+        println!(
+            "Limits: upload {}, download {}, per-peer upload {}, per-peer download {}",
+            format_limit(config.max_upload_rate),
+            format_limit(config.max_download_rate),
+            format_limit(config.max_peer_upload_rate),
+            format_limit(config.max_peer_download_rate),
+        );
+        println!();
+
+        let peers = bandwidth.top_peers(num_peers);
+        if peers.is_empty() {
+            println!("No peer traffic recorded.");
+        } else {
+            let mut table = Table::new();
+            table.set_titles(vec!["NodeId", "Received (KiB)", "Sent (KiB)", "Active Substreams"]);
+            for peer in peers {
+                table.add_row(row![
+                    peer.node_id,
+                    format_kib(peer.stats.bytes_read),
+                    format_kib(peer.stats.bytes_written),
+                    peer.num_active_substreams,
+                ]);
+            }
+            table.print_stdout();
+        }
+        println!();
+
+        let protocols = bandwidth.protocol_traffic();
+        if !protocols.is_empty() {
+            let mut table = Table::new();
+            table.set_titles(vec!["Protocol", "Received (KiB)", "Sent (KiB)"]);
+            for protocol in protocols {
+                table.add_row(row![
+                    String::from_utf8_lossy(&protocol.protocol),
+                    format_kib(protocol.stats.bytes_read),
+                    format_kib(protocol.stats.bytes_written),
+                ]);
+            }
+            table.print_stdout();
+        }
+        Ok(())
+    }
+}
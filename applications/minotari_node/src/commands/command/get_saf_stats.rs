@@ -0,0 +1,86 @@
+//  Copyright 2024, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use anyhow::Error;
+use async_trait::async_trait;
+use clap::Parser;
+
+use super::{CommandContext, HandleCommand};
+use crate::table::Table;
+
+/// Displays store and forward storage statistics and the destinations with the most stored messages
+#[derive(Debug, Parser)]
+pub struct Args {
+    /// The number of destinations to display
+    #[clap(default_value = "10")]
+    num_destinations: usize,
+}
+
+#[async_trait]
+impl HandleCommand<Args> for CommandContext {
+    async fn handle_command(&mut self, args: Args) -> Result<(), Error> {
+        self.get_saf_stats(args.num_destinations).await
+    }
+}
+
+impl CommandContext {
+    /// Function to process the get-saf-stats command
+    pub async fn get_saf_stats(&mut self, num_destinations: usize) -> Result<(), Error> {
+        let stats = self.saf_requester.get_storage_stats(num_destinations).await?;
+        let config = &self.config.base_node.p2p.dht.saf;
+        println!(
+            "Stored messages: {} of {} ({} high priority, {} low priority), {} bytes",
+            stats.num_messages,
+            config.msg_storage_capacity,
+            stats.num_high_priority,
+            stats.num_low_priority,
+            stats.total_bytes
+        );
+        println!(
+            "Destinations: {} (quota {} messages per destination, {} per source peer, {} anonymous messages)",
+            stats.num_destinations,
+            config.msg_storage_capacity_per_destination,
+            config.msg_storage_capacity_per_source,
+            config.anonymous_msg_storage_capacity
+        );
+        println!();
+
+        if stats.top_destinations.is_empty() {
+            println!("No stored messages.");
+            return Ok(());
+        }
+        let mut table = Table::new();
+        table.set_titles(vec!["Destination", "Messages", "Bytes"]);
+        for destination in stats.top_destinations {
+            table.add_row(row![
+                destination
+                    .destination
+                    .map(|node_id| node_id.to_string())
+                    .unwrap_or_else(|| "<Anonymous>".to_string()),
+                destination.num_messages,
+                destination.total_bytes,
+            ]);
+        }
+        table.print_stdout();
+        Ok(())
+    }
+}
@@ -0,0 +1,63 @@
+//  Copyright 2024, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use anyhow::Error;
+use async_trait::async_trait;
+use clap::Parser;
+use tari_comms::peer_manager::PeerExport;
+use tokio::fs;
+
+use super::{CommandContext, HandleCommand};
+
+/// Imports peers from a file created by export-peers. Peers whose signed identity claims do not verify are rejected.
+#[derive(Debug, Parser)]
+pub struct Args {
+    /// The file to import peers from
+    #[clap(default_value = "peers.json")]
+    filename: String,
+}
+
+#[async_trait]
+impl HandleCommand<Args> for CommandContext {
+    async fn handle_command(&mut self, args: Args) -> Result<(), Error> {
+        self.import_peers(args.filename).await
+    }
+}
+
+impl CommandContext {
+    /// Function to process the import-peers command
+    pub async fn import_peers(&self, filename: String) -> Result<(), Error> {
+        let json = fs::read_to_string(&filename).await?;
+        let export = PeerExport::from_json(&json)?;
+        let exported_by = export.exported_by.clone();
+        let summary = self
+            .comms
+            .peer_manager()
+            .import_peers(export, &self.comms.node_identity())
+            .await?;
+        println!(
+            "Imported peers exported by {} from {}: {}",
+            exported_by, filename, summary
+        );
+        Ok(())
+    }
+}
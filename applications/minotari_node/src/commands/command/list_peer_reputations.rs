@@ -0,0 +1,80 @@
+//  Copyright 2024, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use anyhow::Error;
+use async_trait::async_trait;
+use clap::Parser;
+
+use super::{CommandContext, HandleCommand};
+use crate::{table::Table, utils::format_duration_basic};
+
+/// Lists the reputation scores of peers that have had reputation events recorded
+#[derive(Debug, Parser)]
+pub struct Args {}
+
+#[async_trait]
+impl HandleCommand<Args> for CommandContext {
+    async fn handle_command(&mut self, _: Args) -> Result<(), Error> {
+        self.list_peer_reputations().await
+    }
+}
+
+impl CommandContext {
+    /// Function to process the list-peer-reputations command
+    pub async fn list_peer_reputations(&mut self) -> Result<(), Error> {
+        let reputations = self.comms.connectivity().get_all_peer_reputations().await?;
+        if reputations.is_empty() {
+            println!("No peer reputations recorded.");
+            return Ok(());
+        }
+
+        let num_reputations = reputations.len();
+        let mut table = Table::new();
+        table.set_titles(vec![
+            "NodeId",
+            "Score",
+            "Positive",
+            "Negative",
+            "Bans",
+            "Last Event",
+            "Updated",
+        ]);
+        for reputation in reputations {
+            table.add_row(row![
+                reputation.node_id(),
+                format!("{:.1}", reputation.score()),
+                reputation.num_positive_events(),
+                reputation.num_negative_events(),
+                reputation.num_bans(),
+                reputation
+                    .last_event()
+                    .map(|e| e.to_string())
+                    .unwrap_or_else(|| "--".to_string()),
+                format!("{} ago", format_duration_basic(reputation.last_updated().elapsed())),
+            ]);
+        }
+        table.print_stdout();
+
+        println!("{} peer reputation(s)", num_reputations);
+        Ok(())
+    }
+}
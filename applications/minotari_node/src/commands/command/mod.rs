@@ -28,6 +28,8 @@ mod check_for_updates;
 mod create_tls_certs;
 mod dial_peer;
 mod discover_peer;
+mod export_peers;
+mod get_bandwidth_stats;
 mod get_block;
 mod get_chain_metadata;
 mod get_db_stats;
@@ -35,11 +37,14 @@ mod get_mempool_state;
 mod get_mempool_stats;
 mod get_network_stats;
 mod get_peer;
+mod get_saf_stats;
 mod get_state_info;
 mod header_stats;
+mod import_peers;
 mod list_banned_peers;
 mod list_connections;
 mod list_headers;
+mod list_peer_reputations;
 mod list_peers;
 mod list_reorgs;
 mod list_validator_nodes;
@@ -72,7 +77,7 @@ use tari_comms::{
     CommsNode,
     NodeIdentity,
 };
-use tari_comms_dht::{DhtDiscoveryRequester, MetricsCollectorHandle};
+use tari_comms_dht::{store_forward::StoreAndForwardRequester, DhtDiscoveryRequester, MetricsCollectorHandle};
 use tari_core::{
     base_node::{state_machine_service::states::StatusInfo, LocalNodeCommsInterface},
     blocks::ChainHeader,
@@ -116,6 +121,11 @@ pub enum Command {
     UnbanPeer(ban_peer::ArgsUnban),
     UnbanAllPeers(unban_all_peers::Args),
     ListBannedPeers(list_banned_peers::Args),
+    ListPeerReputations(list_peer_reputations::Args),
+    GetBandwidthStats(get_bandwidth_stats::Args),
+    GetSafStats(get_saf_stats::Args),
+    ExportPeers(export_peers::Args),
+    ImportPeers(import_peers::Args),
     ListConnections(list_connections::Args),
     ListHeaders(list_headers::Args),
     CheckDb(check_db::Args),
@@ -157,6 +167,7 @@ pub struct CommandContext {
     blockchain_db: AsyncBlockchainDb<LMDBDatabase>,
     discovery_service: DhtDiscoveryRequester,
     dht_metrics_collector: MetricsCollectorHandle,
+    saf_requester: StoreAndForwardRequester,
     rpc_server: RpcServerHandle,
     base_node_identity: Arc<NodeIdentity>,
     comms: CommsNode,
@@ -177,6 +188,7 @@ impl CommandContext {
             blockchain_db: ctx.blockchain_db().into(),
             discovery_service: ctx.base_node_dht().discovery_service_requester(),
             dht_metrics_collector: ctx.base_node_dht().metrics_collector(),
+            saf_requester: ctx.base_node_dht().store_and_forward_requester(),
             rpc_server: ctx.rpc_server(),
             base_node_identity: ctx.base_node_identity(),
             comms: ctx.base_node_comms().clone(),
@@ -212,6 +224,11 @@ impl CommandContext {
                 Command::DiscoverPeer(_) |
                 Command::ListPeers(_) |
                 Command::ListBannedPeers(_) |
+                Command::ListPeerReputations(_) |
+                Command::GetBandwidthStats(_) |
+                Command::GetSafStats(_) |
+                Command::ExportPeers(_) |
+                Command::ImportPeers(_) |
                 Command::ListConnections(_) |
                 Command::GetNetworkStats(_) |
                 Command::BlockTiming(_) |
@@ -293,6 +310,11 @@ impl HandleCommand<Command> for CommandContext {
             Command::GetMempoolTx(args) => self.handle_command(args).await,
             Command::Whoami(args) => self.handle_command(args).await,
             Command::ListBannedPeers(args) => self.handle_command(args).await,
+            Command::ListPeerReputations(args) => self.handle_command(args).await,
+            Command::GetBandwidthStats(args) => self.handle_command(args).await,
+            Command::GetSafStats(args) => self.handle_command(args).await,
+            Command::ExportPeers(args) => self.handle_command(args).await,
+            Command::ImportPeers(args) => self.handle_command(args).await,
             Command::Quit(args) | Command::Exit(args) => self.handle_command(args).await,
             Command::Watch(args) => self.handle_command(args).await,
             Command::ListValidatorNodes(args) => self.handle_command(args).await,
@@ -94,6 +94,8 @@ pub struct BaseNodeConfig {
     pub grpc_authentication: GrpcAuthentication,
     /// GRPC tls enabled
     pub grpc_tls_enabled: bool,
+    /// The number of recent events per GRPC event stream that are kept so that subscribers can resume from a cursor
+    pub grpc_event_buffer_size: usize,
     /// Enable mining on the base node, overriding other settings regarding mining
     pub mining_enabled: bool,
     /// A path to the file that stores the base node identity and secret key
@@ -163,9 +165,15 @@ impl Default for BaseNodeConfig {
                 GrpcMethod::GetTipInfo,
                 GrpcMethod::Identify,
                 GrpcMethod::GetNetworkStatus,
+                GrpcMethod::GetPeerReputations,
+                GrpcMethod::GetBandwidthStats,
+                GrpcMethod::StreamChainEvents,
+                GrpcMethod::StreamMempoolEvents,
+                GrpcMethod::StreamSyncStateEvents,
             ],
             grpc_authentication: GrpcAuthentication::default(),
             grpc_tls_enabled: false,
+            grpc_event_buffer_size: 1_000,
             mining_enabled: false,
             identity_file: PathBuf::from("config/base_node_id.json"),
             use_libtor: true,
@@ -257,11 +265,16 @@ pub enum GrpcMethod {
     Identify,
     GetNetworkStatus,
     ListConnectedPeers,
+    GetPeerReputations,
+    GetBandwidthStats,
     GetMempoolStats,
     GetActiveValidatorNodes,
     GetShardKey,
     GetTemplateRegistrations,
     GetSideChainUtxos,
+    StreamChainEvents,
+    StreamMempoolEvents,
+    StreamSyncStateEvents,
 }
 
 #[cfg(test)]
@@ -61,6 +61,7 @@ use crate::{
     config::GrpcMethod,
     grpc::{
         blocks::{block_fees, block_heights, block_size, GET_BLOCKS_MAX_HEIGHTS, GET_BLOCKS_PAGE_SIZE},
+        event_streams::{stream_events, NodeEventFeeds},
         hash_rate::HashRateMovingAverage,
         helpers::{mean, median},
     },
@@ -97,6 +98,7 @@ pub struct BaseNodeGrpcServer {
     liveness: LivenessHandle,
     report_grpc_error: bool,
     config: BaseNodeConfig,
+    event_feeds: NodeEventFeeds,
 }
 
 impl BaseNodeGrpcServer {
@@ -111,6 +113,7 @@ impl BaseNodeGrpcServer {
             comms: ctx.base_node_comms().clone(),
             liveness: ctx.liveness(),
             report_grpc_error: ctx.get_report_grpc_error(),
+            event_feeds: NodeEventFeeds::spawn(ctx, config.grpc_event_buffer_size),
             config,
         }
     }
@@ -166,6 +169,9 @@ impl tari_rpc::base_node_server::BaseNode for BaseNodeGrpcServer {
     type ListHeadersStream = mpsc::Receiver<Result<tari_rpc::BlockHeaderResponse, Status>>;
     type SearchKernelsStream = mpsc::Receiver<Result<tari_rpc::HistoricalBlock, Status>>;
     type SearchUtxosStream = mpsc::Receiver<Result<tari_rpc::HistoricalBlock, Status>>;
+    type StreamChainEventsStream = mpsc::Receiver<Result<tari_rpc::ChainEvent, Status>>;
+    type StreamMempoolEventsStream = mpsc::Receiver<Result<tari_rpc::MempoolEvent, Status>>;
+    type StreamSyncStateEventsStream = mpsc::Receiver<Result<tari_rpc::SyncStateEvent, Status>>;
 
     #[allow(clippy::too_many_lines)]
     async fn get_network_difficulty(
@@ -1587,6 +1593,68 @@ impl tari_rpc::base_node_server::BaseNode for BaseNodeGrpcServer {
         Ok(Response::new(response))
     }
 
+    async fn stream_chain_events(
+        &self,
+        request: Request<tari_rpc::StreamEventsRequest>,
+    ) -> Result<Response<Self::StreamChainEventsStream>, Status> {
+        if !self.is_method_enabled(GrpcMethod::StreamChainEvents) {
+            return Err(Status::permission_denied(
+                "`StreamChainEvents` method not made available",
+            ));
+        }
+        let request = request.into_inner();
+        debug!(
+            target: LOG_TARGET,
+            "Incoming GRPC request for StreamChainEvents: resume_after: {:?}", request.resume_after
+        );
+        let rx = stream_events(&self.event_feeds.chain, request.resume_after, self.report_error_flag())?;
+        Ok(Response::new(rx))
+    }
+
+    async fn stream_mempool_events(
+        &self,
+        request: Request<tari_rpc::StreamEventsRequest>,
+    ) -> Result<Response<Self::StreamMempoolEventsStream>, Status> {
+        if !self.is_method_enabled(GrpcMethod::StreamMempoolEvents) {
+            return Err(Status::permission_denied(
+                "`StreamMempoolEvents` method not made available",
+            ));
+        }
+        let request = request.into_inner();
+        debug!(
+            target: LOG_TARGET,
+            "Incoming GRPC request for StreamMempoolEvents: resume_after: {:?}", request.resume_after
+        );
+        let rx = stream_events(
+            &self.event_feeds.mempool,
+            request.resume_after,
+            self.report_error_flag(),
+        )?;
+        Ok(Response::new(rx))
+    }
+
+    async fn stream_sync_state_events(
+        &self,
+        request: Request<tari_rpc::StreamEventsRequest>,
+    ) -> Result<Response<Self::StreamSyncStateEventsStream>, Status> {
+        if !self.is_method_enabled(GrpcMethod::StreamSyncStateEvents) {
+            return Err(Status::permission_denied(
+                "`StreamSyncStateEvents` method not made available",
+            ));
+        }
+        let request = request.into_inner();
+        debug!(
+            target: LOG_TARGET,
+            "Incoming GRPC request for StreamSyncStateEvents: resume_after: {:?}", request.resume_after
+        );
+        let rx = stream_events(
+            &self.event_feeds.sync_state,
+            request.resume_after,
+            self.report_error_flag(),
+        )?;
+        Ok(Response::new(rx))
+    }
+
     // casting here is okay as we cannot have more than u32 kernels in a block
     #[allow(clippy::cast_possible_truncation)]
     async fn get_header_by_hash(
@@ -1726,6 +1794,51 @@ impl tari_rpc::base_node_server::BaseNode for BaseNodeGrpcServer {
         Ok(Response::new(resp))
     }
 
+    async fn get_peer_reputations(
+        &self,
+        _: Request<tari_rpc::Empty>,
+    ) -> Result<Response<tari_rpc::GetPeerReputationsResponse>, Status> {
+        if !self.is_method_enabled(GrpcMethod::GetPeerReputations) {
+            return Err(Status::permission_denied(
+                "`GetPeerReputations` method not made available",
+            ));
+        }
+        let report_error_flag = self.report_error_flag();
+        let reputations = self
+            .comms
+            .connectivity()
+            .get_all_peer_reputations()
+            .await
+            .map_err(|err| obscure_error_if_true(report_error_flag, Status::internal(err.to_string())))?;
+
+        Ok(Response::new(tari_rpc::GetPeerReputationsResponse {
+            reputations: reputations.into_iter().map(Into::into).collect(),
+        }))
+    }
+
+    async fn get_bandwidth_stats(
+        &self,
+        request: Request<tari_rpc::GetBandwidthStatsRequest>,
+    ) -> Result<Response<tari_rpc::GetBandwidthStatsResponse>, Status> {
+        if !self.is_method_enabled(GrpcMethod::GetBandwidthStats) {
+            return Err(Status::permission_denied(
+                "`GetBandwidthStats` method not made available",
+            ));
+        }
+        let request = request.into_inner();
+        let num_peers = match request.num_peers {
+            0 => 10,
+            n => usize::try_from(n).unwrap_or(usize::MAX),
+        };
+        let bandwidth = self.comms.bandwidth();
+
+        Ok(Response::new(tari_rpc::GetBandwidthStatsResponse {
+            total: Some(bandwidth.total_traffic().into()),
+            top_peers: bandwidth.top_peers(num_peers).into_iter().map(Into::into).collect(),
+            protocols: bandwidth.protocol_traffic().into_iter().map(Into::into).collect(),
+        }))
+    }
+
     async fn get_mempool_stats(
         &self,
         _: Request<tari_rpc::Empty>,
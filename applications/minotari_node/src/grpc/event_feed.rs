@@ -0,0 +1,216 @@
+// Copyright 2024. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{
+    collections::VecDeque,
+    convert::TryFrom,
+    sync::{Arc, Mutex},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use thiserror::Error;
+use tokio::sync::broadcast;
+
+/// The position of an event within an [EventFeed]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FeedCursor {
+    pub feed_id: u64,
+    pub sequence: u64,
+}
+
+#[derive(Debug, Clone)]
+pub struct SequencedEvent<T> {
+    pub cursor: FeedCursor,
+    pub event: T,
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum EventFeedError {
+    #[error("Cursor belongs to a previous event feed (feed id {0})")]
+    UnknownFeed(u64),
+    #[error("Events following sequence {requested} are no longer buffered (oldest buffered sequence is {oldest})")]
+    CursorExpired { requested: u64, oldest: u64 },
+}
+
+/// A subscription to an [EventFeed]. The `replay` events must be delivered before any events received on `live`.
+pub struct EventSubscription<T> {
+    pub replay: Vec<SequencedEvent<T>>,
+    pub live: broadcast::Receiver<SequencedEvent<T>>,
+}
+
+/// A broadcast of events in which every event is assigned a sequence number. The most recent events are buffered so
+/// that a subscriber that was disconnected can resume from the last event it received without missing any.
+#[derive(Clone)]
+pub struct EventFeed<T> {
+    feed_id: u64,
+    state: Arc<Mutex<FeedState<T>>>,
+    sender: broadcast::Sender<SequencedEvent<T>>,
+}
+
+struct FeedState<T> {
+    next_sequence: u64,
+    capacity: usize,
+    buffer: VecDeque<SequencedEvent<T>>,
+}
+
+impl<T: Clone> EventFeed<T> {
+    pub fn new(capacity: usize) -> Self {
+        let feed_id = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| u64::try_from(d.as_nanos()).unwrap_or(u64::MAX))
+            .unwrap_or_default();
+        Self::with_feed_id(feed_id, capacity)
+    }
+
+    pub fn with_feed_id(feed_id: u64, capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+        let (sender, _) = broadcast::channel(capacity);
+        Self {
+            feed_id,
+            state: Arc::new(Mutex::new(FeedState {
+                next_sequence: 0,
+                capacity,
+                buffer: VecDeque::with_capacity(capacity),
+            })),
+            sender,
+        }
+    }
+
+    /// Assigns the next sequence number to the event, buffers it and sends it to all live subscribers
+    pub fn publish(&self, event: T) -> FeedCursor {
+        let mut state = self.lock_state();
+        let cursor = FeedCursor {
+            feed_id: self.feed_id,
+            sequence: state.next_sequence,
+        };
+        state.next_sequence += 1;
+        let event = SequencedEvent { cursor, event };
+        if state.buffer.len() >= state.capacity {
+            state.buffer.pop_front();
+        }
+        state.buffer.push_back(event.clone());
+        // The state lock is held while sending so that a concurrent subscriber either receives this event in its replay
+        // or on its live receiver, but never both or neither. An error only means there are no live subscribers.
+        let _result = self.sender.send(event);
+        cursor
+    }
+
+    /// Subscribes to the feed. If `resume_after` is given, all buffered events after that cursor are returned for
+    /// replay, otherwise only events published after this call are received.
+    pub fn subscribe(&self, resume_after: Option<FeedCursor>) -> Result<EventSubscription<T>, EventFeedError> {
+        let state = self.lock_state();
+        let replay = match resume_after {
+            Some(cursor) => {
+                if cursor.feed_id != self.feed_id {
+                    return Err(EventFeedError::UnknownFeed(cursor.feed_id));
+                }
+                let oldest = state
+                    .buffer
+                    .front()
+                    .map(|e| e.cursor.sequence)
+                    .unwrap_or(state.next_sequence);
+                // The subscriber must have received the event immediately before the oldest buffered event
+                if cursor.sequence.saturating_add(1) < oldest {
+                    return Err(EventFeedError::CursorExpired {
+                        requested: cursor.sequence,
+                        oldest,
+                    });
+                }
+                state
+                    .buffer
+                    .iter()
+                    .filter(|e| e.cursor.sequence > cursor.sequence)
+                    .cloned()
+                    .collect()
+            },
+            None => Vec::new(),
+        };
+        Ok(EventSubscription {
+            replay,
+            live: self.sender.subscribe(),
+        })
+    }
+
+    fn lock_state(&self) -> std::sync::MutexGuard<'_, FeedState<T>> {
+        // The state is always left consistent, so it is safe to continue using it if a publisher panicked
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn cursor(sequence: u64) -> FeedCursor {
+        FeedCursor { feed_id: 1, sequence }
+    }
+
+    #[tokio::test]
+    async fn it_streams_new_events_to_live_subscribers() {
+        let feed = EventFeed::with_feed_id(1, 10);
+        feed.publish("before");
+        let mut sub = feed.subscribe(None).unwrap();
+        assert!(sub.replay.is_empty());
+        feed.publish("after");
+        let event = sub.live.recv().await.unwrap();
+        assert_eq!(event.event, "after");
+        assert_eq!(event.cursor, cursor(1));
+    }
+
+    #[test]
+    fn it_replays_buffered_events_after_the_cursor() {
+        let feed = EventFeed::with_feed_id(1, 10);
+        for i in 0..5 {
+            feed.publish(i);
+        }
+        let sub = feed.subscribe(Some(cursor(2))).unwrap();
+        assert_eq!(sub.replay.iter().map(|e| e.event).collect::<Vec<_>>(), vec![3, 4]);
+
+        let sub = feed.subscribe(Some(cursor(4))).unwrap();
+        assert!(sub.replay.is_empty());
+    }
+
+    #[test]
+    fn it_rejects_expired_and_foreign_cursors() {
+        let feed = EventFeed::with_feed_id(1, 3);
+        for i in 0..6 {
+            feed.publish(i);
+        }
+        // Sequences 3, 4 and 5 are buffered, so a subscriber must have seen at least sequence 2
+        assert!(feed.subscribe(Some(cursor(2))).is_ok());
+        assert_eq!(
+            feed.subscribe(Some(cursor(1))).err(),
+            Some(EventFeedError::CursorExpired {
+                requested: 1,
+                oldest: 3
+            })
+        );
+        assert_eq!(
+            feed.subscribe(Some(FeedCursor {
+                feed_id: 2,
+                sequence: 5
+            }))
+            .err(),
+            Some(EventFeedError::UnknownFeed(2))
+        );
+    }
+}
@@ -0,0 +1,305 @@
+// Copyright 2024. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::convert::TryFrom;
+
+use futures::{channel::mpsc, SinkExt};
+use log::*;
+use minotari_app_grpc::tari_rpc;
+use tari_core::{
+    base_node::{
+        comms_interface::{BlockEvent, BlockEventReceiver},
+        state_machine_service::states::{StateInfo, StatusInfo},
+    },
+    chain_storage::BlockAddResult,
+    mempool::{MempoolEvent, MempoolEventReceiver, MempoolRemovalReason},
+};
+use tokio::{
+    sync::{broadcast::error::RecvError, watch},
+    task,
+};
+use tonic::Status;
+
+use crate::{
+    builder::BaseNodeContext,
+    grpc::{
+        base_node_grpc_server::obscure_error_if_true,
+        event_feed::{EventFeed, EventSubscription, FeedCursor},
+    },
+};
+
+const LOG_TARGET: &str = "minotari::base_node::grpc::event_streams";
+const EVENT_STREAM_CHANNEL_SIZE: usize = 100;
+
+/// The event feeds backing the streaming GRPC methods. Events are collected from the node's services for as long as
+/// the node runs, so that subscribers can resume from a cursor after a disconnect.
+#[derive(Clone)]
+pub struct NodeEventFeeds {
+    pub chain: EventFeed<tari_rpc::ChainEvent>,
+    pub mempool: EventFeed<tari_rpc::MempoolEvent>,
+    pub sync_state: EventFeed<tari_rpc::SyncStateEvent>,
+}
+
+impl NodeEventFeeds {
+    pub fn spawn(ctx: &BaseNodeContext, buffer_size: usize) -> Self {
+        let feeds = Self {
+            chain: EventFeed::new(buffer_size),
+            mempool: EventFeed::new(buffer_size),
+            sync_state: EventFeed::new(buffer_size),
+        };
+        task::spawn(collect_chain_events(
+            ctx.local_node().get_block_event_stream(),
+            feeds.chain.clone(),
+        ));
+        task::spawn(collect_mempool_events(
+            ctx.local_mempool().get_mempool_event_stream(),
+            feeds.mempool.clone(),
+        ));
+        task::spawn(collect_sync_state_events(
+            ctx.state_machine().get_status_info_watch(),
+            feeds.sync_state.clone(),
+        ));
+        feeds
+    }
+}
+
+/// GRPC event messages that carry the cursor of the event
+pub trait CursorEvent {
+    fn with_cursor(self, cursor: FeedCursor) -> Self;
+}
+
+impl CursorEvent for tari_rpc::ChainEvent {
+    fn with_cursor(mut self, cursor: FeedCursor) -> Self {
+        self.cursor = Some(cursor.into());
+        self
+    }
+}
+
+impl CursorEvent for tari_rpc::MempoolEvent {
+    fn with_cursor(mut self, cursor: FeedCursor) -> Self {
+        self.cursor = Some(cursor.into());
+        self
+    }
+}
+
+impl CursorEvent for tari_rpc::SyncStateEvent {
+    fn with_cursor(mut self, cursor: FeedCursor) -> Self {
+        self.cursor = Some(cursor.into());
+        self
+    }
+}
+
+impl From<FeedCursor> for tari_rpc::EventCursor {
+    fn from(cursor: FeedCursor) -> Self {
+        Self {
+            feed_id: cursor.feed_id,
+            sequence: cursor.sequence,
+        }
+    }
+}
+
+impl From<tari_rpc::EventCursor> for FeedCursor {
+    fn from(cursor: tari_rpc::EventCursor) -> Self {
+        Self {
+            feed_id: cursor.feed_id,
+            sequence: cursor.sequence,
+        }
+    }
+}
+
+/// Subscribes to the feed and streams its events to the GRPC client, starting with any buffered events that follow
+/// `resume_after`. The stream is ended with an error if the client falls too far behind the feed, after which the
+/// client can resubscribe using the cursor of the last event it received.
+pub fn stream_events<T>(
+    feed: &EventFeed<T>,
+    resume_after: Option<tari_rpc::EventCursor>,
+    report_error_flag: bool,
+) -> Result<mpsc::Receiver<Result<T, Status>>, Status>
+where
+    T: CursorEvent + Clone + Send + 'static,
+{
+    let EventSubscription { replay, mut live } = feed
+        .subscribe(resume_after.map(Into::into))
+        .map_err(|e| obscure_error_if_true(report_error_flag, Status::out_of_range(e.to_string())))?;
+
+    let (mut tx, rx) = mpsc::channel(EVENT_STREAM_CHANNEL_SIZE);
+    task::spawn(async move {
+        for event in replay {
+            if tx.send(Ok(event.event.with_cursor(event.cursor))).await.is_err() {
+                return;
+            }
+        }
+        loop {
+            match live.recv().await {
+                Ok(event) => {
+                    if tx.send(Ok(event.event.with_cursor(event.cursor))).await.is_err() {
+                        // Sender has closed i.e the connection has dropped/request was abandoned
+                        debug!(target: LOG_TARGET, "Event stream subscriber disconnected");
+                        return;
+                    }
+                },
+                Err(RecvError::Lagged(n)) => {
+                    warn!(target: LOG_TARGET, "Event stream subscriber lagged by {} events", n);
+                    let _result = tx
+                        .send(Err(Status::aborted(format!(
+                            "Subscriber fell behind by {} events. Resubscribe with the last received cursor.",
+                            n
+                        ))))
+                        .await;
+                    return;
+                },
+                Err(RecvError::Closed) => return,
+            }
+        }
+    });
+    Ok(rx)
+}
+
+async fn collect_chain_events(mut block_events: BlockEventReceiver, feed: EventFeed<tari_rpc::ChainEvent>) {
+    loop {
+        let event = match block_events.recv().await {
+            Ok(event) => event,
+            Err(RecvError::Lagged(n)) => {
+                warn!(target: LOG_TARGET, "Chain event feed missed {} block events", n);
+                feed.publish(tari_rpc::ChainEvent {
+                    cursor: None,
+                    event: Some(tari_rpc::chain_event::Event::EventsMissed(tari_rpc::EventsMissed {
+                        count: n,
+                    })),
+                });
+                continue;
+            },
+            Err(RecvError::Closed) => break,
+        };
+        if let Some(event) = chain_event_from_block_event(&event) {
+            feed.publish(tari_rpc::ChainEvent {
+                cursor: None,
+                event: Some(event),
+            });
+        }
+    }
+    debug!(target: LOG_TARGET, "Chain event feed stopped");
+}
+
+fn chain_event_from_block_event(event: &BlockEvent) -> Option<tari_rpc::chain_event::Event> {
+    use tari_rpc::chain_event::Event;
+    match event {
+        BlockEvent::ValidBlockAdded(_, BlockAddResult::Ok(block)) | BlockEvent::BlockSyncComplete(block, _) => {
+            Some(Event::TipChanged(tari_rpc::ChainTipChanged {
+                header: Some(block.header().clone().into()),
+            }))
+        },
+        BlockEvent::ValidBlockAdded(_, BlockAddResult::ChainReorg { added, removed }) => {
+            Some(Event::Reorg(tari_rpc::ChainReorg {
+                added: added.iter().map(|b| b.header().clone().into()).collect(),
+                removed: removed.iter().map(|b| b.header().clone().into()).collect(),
+            }))
+        },
+        BlockEvent::BlockSyncRewind(removed) => Some(Event::Reorg(tari_rpc::ChainReorg {
+            added: vec![],
+            removed: removed.iter().map(|b| b.header().clone().into()).collect(),
+        })),
+        BlockEvent::ValidBlockAdded(_, BlockAddResult::BlockExists | BlockAddResult::OrphanBlock) |
+        BlockEvent::AddBlockValidationFailed { .. } |
+        BlockEvent::AddBlockErrored { .. } => None,
+    }
+}
+
+async fn collect_mempool_events(mut mempool_events: MempoolEventReceiver, feed: EventFeed<tari_rpc::MempoolEvent>) {
+    use tari_rpc::mempool_event::Event;
+    loop {
+        let event = match mempool_events.recv().await {
+            Ok(event) => event,
+            Err(RecvError::Lagged(n)) => {
+                warn!(target: LOG_TARGET, "Mempool event feed missed {} mempool events", n);
+                feed.publish(tari_rpc::MempoolEvent {
+                    cursor: None,
+                    event: Some(Event::EventsMissed(tari_rpc::EventsMissed { count: n })),
+                });
+                continue;
+            },
+            Err(RecvError::Closed) => break,
+        };
+        let event = match &*event {
+            MempoolEvent::TransactionAdded(tx) => match tari_rpc::Transaction::try_from(tx.clone()) {
+                Ok(transaction) => Event::TransactionAdded(tari_rpc::MempoolTransactionAdded {
+                    transaction: Some(transaction),
+                }),
+                Err(e) => {
+                    warn!(target: LOG_TARGET, "Error converting mempool transaction: {}", e);
+                    continue;
+                },
+            },
+            MempoolEvent::TransactionRemoved { excess_sig, reason } => {
+                let reason = match reason {
+                    MempoolRemovalReason::Mined => tari_rpc::MempoolRemovalReason::Mined,
+                    MempoolRemovalReason::DoubleSpend => tari_rpc::MempoolRemovalReason::DoubleSpend,
+                    MempoolRemovalReason::Evicted => tari_rpc::MempoolRemovalReason::Evicted,
+                    MempoolRemovalReason::Invalid => tari_rpc::MempoolRemovalReason::Invalid,
+                };
+                Event::TransactionRemoved(tari_rpc::MempoolTransactionRemoved {
+                    excess_sig: Some(excess_sig.into()),
+                    reason: reason.into(),
+                })
+            },
+        };
+        feed.publish(tari_rpc::MempoolEvent {
+            cursor: None,
+            event: Some(event),
+        });
+    }
+    debug!(target: LOG_TARGET, "Mempool event feed stopped");
+}
+
+async fn collect_sync_state_events(
+    mut status_watch: watch::Receiver<StatusInfo>,
+    feed: EventFeed<tari_rpc::SyncStateEvent>,
+) {
+    let mut last_published = None;
+    loop {
+        let event = sync_state_event(&status_watch.borrow());
+        // Progress updates within the same state are not transitions
+        let state = (event.base_node_state, event.initial_sync_achieved);
+        if last_published != Some(state) {
+            last_published = Some(state);
+            feed.publish(event);
+        }
+        if status_watch.changed().await.is_err() {
+            break;
+        }
+    }
+    debug!(target: LOG_TARGET, "Sync state event feed stopped");
+}
+
+fn sync_state_event(status: &StatusInfo) -> tari_rpc::SyncStateEvent {
+    let (tip_height, local_height) = match &status.state_info {
+        StateInfo::HeaderSync(Some(info)) | StateInfo::BlockSync(info) => (info.tip_height, info.local_height),
+        _ => (0, 0),
+    };
+    tari_rpc::SyncStateEvent {
+        cursor: None,
+        base_node_state: tari_rpc::BaseNodeState::from(&status.state_info).into(),
+        initial_sync_achieved: status.bootstrapped,
+        tip_height,
+        local_height,
+    }
+}
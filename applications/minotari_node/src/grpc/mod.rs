@@ -22,5 +22,7 @@
 
 pub mod base_node_grpc_server;
 pub mod blocks;
+pub mod event_feed;
+pub mod event_streams;
 pub mod hash_rate;
 pub mod helpers;
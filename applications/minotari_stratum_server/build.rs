@@ -0,0 +1,8 @@
+// Copyright 2024 The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+use tari_features::resolver::build_features;
+
+fn main() {
+    build_features();
+}
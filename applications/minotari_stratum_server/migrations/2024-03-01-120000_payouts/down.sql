@@ -0,0 +1,4 @@
+DROP TABLE IF EXISTS payouts;
+DROP TABLE IF EXISTS balances;
+DROP TABLE IF EXISTS blocks;
+DROP TABLE IF EXISTS shares;
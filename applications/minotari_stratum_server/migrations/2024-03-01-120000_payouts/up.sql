@@ -0,0 +1,36 @@
+CREATE TABLE shares (
+    id                  INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    worker              TEXT     NOT NULL,
+    address             BLOB     NOT NULL,
+    difficulty          BIGINT   NOT NULL,
+    height              BIGINT   NOT NULL,
+    created_at          DATETIME NOT NULL
+);
+
+CREATE TABLE blocks (
+    hash                BLOB PRIMARY KEY NOT NULL,
+    height              BIGINT   NOT NULL,
+    coinbase_amount     BIGINT   NOT NULL,
+    network_difficulty  BIGINT   NOT NULL,
+    found_by            TEXT     NOT NULL,
+    share_id            INTEGER  NOT NULL,
+    status              INTEGER  NOT NULL,
+    created_at          DATETIME NOT NULL
+);
+
+CREATE INDEX blocks_status ON blocks (status);
+
+CREATE TABLE balances (
+    address             BLOB PRIMARY KEY NOT NULL,
+    amount              BIGINT   NOT NULL
+);
+
+CREATE TABLE payouts (
+    id                  INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    address             BLOB     NOT NULL,
+    amount              BIGINT   NOT NULL,
+    tx_id               BIGINT   NULL,
+    status              INTEGER  NOT NULL,
+    failure_message     TEXT     NULL,
+    created_at          DATETIME NOT NULL
+);
@@ -0,0 +1,2 @@
+DROP INDEX IF EXISTS payouts_status;
+ALTER TABLE payouts DROP COLUMN batch_id;
@@ -0,0 +1,4 @@
+-- The wallet batch payout that pays a payout, used to reconcile pending payouts with the wallet
+ALTER TABLE payouts ADD COLUMN batch_id TEXT NULL;
+
+CREATE INDEX payouts_status ON payouts (status);
@@ -0,0 +1,273 @@
+// Copyright 2024. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use serde::{Deserialize, Serialize};
+use tari_common::{
+    configuration::{serializers, Network},
+    SubConfigPath,
+};
+use tari_common_types::{grpc_authentication::GrpcAuthentication, tari_address::TariAddress};
+use tari_comms::multiaddr::Multiaddr;
+use tari_core::transactions::transaction_components::RangeProofType;
+
+/// Who receives the block reward for blocks found through the stratum server
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum MiningMode {
+    /// Every worker mines to the wallet address it logged in with, so the finder of a block receives the full reward
+    Solo,
+    /// All workers mine to `wallet_payment_address` and shares are accounted per worker
+    Pool,
+}
+
+/// How block rewards are shared between workers in pool mode
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PayoutScheme {
+    /// Pay-per-last-N-shares: once a block matures its coinbase is shared between the shares in the window leading up
+    /// to it, in proportion to their difficulty
+    Pplns,
+    /// Pay-per-share: every accepted share is credited with its expected value of the emission schedule block reward,
+    /// regardless of whether the pool finds blocks
+    Pps,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct StratumServerConfig {
+    override_from: Option<String>,
+    /// The Minotari base node's GRPC address
+    pub base_node_grpc_address: Option<Multiaddr>,
+    /// GRPC authentication for base node
+    pub base_node_grpc_authentication: GrpcAuthentication,
+    /// GRPC domain name for node TLS validation
+    pub base_node_grpc_tls_domain_name: Option<String>,
+    /// GRPC ca cert name for TLS
+    pub base_node_grpc_ca_cert_filename: String,
+    /// Address the stratum server listens on for miner connections
+    pub listener_address: Multiaddr,
+    /// Solo or pool mining
+    pub mining_mode: MiningMode,
+    /// The maximum number of concurrent miner connections
+    pub max_connections: usize,
+    /// Miners that send nothing for this long are disconnected (the stratum client sends a keepalive every 30s)
+    #[serde(with = "serializers::seconds")]
+    pub connection_timeout: Duration,
+    /// How often the base node tip is polled to detect new blocks
+    #[serde(with = "serializers::seconds")]
+    pub tip_poll_interval: Duration,
+    /// A new template is requested at least this often, so that new mempool transactions are picked up
+    #[serde(with = "serializers::seconds")]
+    pub template_refresh_interval: Duration,
+    /// The share difficulty assigned to a worker when it logs in
+    pub initial_share_difficulty: u64,
+    /// The lowest share difficulty vardiff will assign
+    pub min_share_difficulty: u64,
+    /// The highest share difficulty vardiff will assign (always capped by the network difficulty)
+    pub max_share_difficulty: u64,
+    /// The share interval vardiff aims for
+    #[serde(with = "serializers::seconds")]
+    pub vardiff_target_share_time: Duration,
+    /// How often vardiff re-evaluates a worker's share difficulty
+    #[serde(with = "serializers::seconds")]
+    pub vardiff_retarget_interval: Duration,
+    /// The allowed deviation from the target share time, in percent, before the share difficulty is adjusted
+    pub vardiff_variance_percent: u64,
+    /// How often per-worker share statistics are written to the log. Set to 0 to disable.
+    #[serde(with = "serializers::seconds")]
+    pub stats_log_interval: Duration,
+    /// The extra data to store in the coinbase, usually some data about the mining pool.
+    /// Note that this data is publicly readable, but it is suggested you populate it so that
+    /// pool dominance can be seen before any one party has more than 51%.
+    pub coinbase_extra: String,
+    /// Selected network
+    pub network: Network,
+    /// The relative path to store persistent config
+    pub config_dir: PathBuf,
+    /// The Tari wallet address (valid address in hex) where pool mining funds will be sent to - must be assigned in
+    /// pool mode
+    pub wallet_payment_address: String,
+    /// Stealth payment yes or no
+    pub stealth_payment: bool,
+    /// Range proof type - revealed_value or bullet_proof_plus: (default = revealed_value)
+    pub range_proof_type: RangeProofType,
+    /// How rewards are shared between workers in pool mode
+    pub payout_scheme: PayoutScheme,
+    /// The pool fee in basis points (1/100th of a percent) deducted from worker rewards
+    pub pool_fee_basis_points: u64,
+    /// The PPLNS window, as a multiple of the network difficulty of the block being paid out
+    pub pplns_window_factor: u64,
+    /// The path of the SQLite database that records shares, blocks, balances and payout history
+    pub payout_db_path: PathBuf,
+    /// The console wallet's GRPC address used to pay out balances with its `BatchPayout` method. Payouts are disabled if
+    /// not set, in which case balances only accumulate.
+    pub wallet_grpc_address: Option<Multiaddr>,
+    /// GRPC authentication for the wallet
+    pub wallet_grpc_authentication: GrpcAuthentication,
+    /// GRPC domain name for wallet TLS validation
+    pub wallet_grpc_tls_domain_name: Option<String>,
+    /// GRPC ca cert name for wallet TLS
+    pub wallet_grpc_ca_cert_filename: String,
+    /// How often matured blocks are credited and balances are paid out
+    #[serde(with = "serializers::seconds")]
+    pub payout_interval: Duration,
+    /// Balances below this amount (in µT) are carried over to the next payout
+    pub minimum_payout: u64,
+    /// The maximum number of recipients paid in a single wallet batch payout
+    pub max_payout_recipients: usize,
+    /// The fee per gram (in µT) for payout transactions
+    pub payout_fee_per_gram: u64,
+}
+
+impl Default for StratumServerConfig {
+    fn default() -> Self {
+        Self {
+            override_from: None,
+            base_node_grpc_address: None,
+            base_node_grpc_authentication: GrpcAuthentication::default(),
+            base_node_grpc_tls_domain_name: None,
+            base_node_grpc_ca_cert_filename: "node_ca.pem".to_string(),
+            listener_address: "/ip4/127.0.0.1/tcp/18090".parse().unwrap(),
+            mining_mode: MiningMode::Pool,
+            max_connections: 256,
+            connection_timeout: Duration::from_secs(300),
+            tip_poll_interval: Duration::from_secs(1),
+            template_refresh_interval: Duration::from_secs(30),
+            initial_share_difficulty: 10_000,
+            min_share_difficulty: 1_000,
+            max_share_difficulty: u64::MAX,
+            vardiff_target_share_time: Duration::from_secs(15),
+            vardiff_retarget_interval: Duration::from_secs(90),
+            vardiff_variance_percent: 30,
+            stats_log_interval: Duration::from_secs(60),
+            coinbase_extra: "minotari_stratum_server".to_string(),
+            network: Default::default(),
+            config_dir: PathBuf::from("config/stratum_server"),
+            wallet_payment_address: TariAddress::default().to_hex(),
+            stealth_payment: true,
+            range_proof_type: RangeProofType::RevealedValue,
+            payout_scheme: PayoutScheme::Pplns,
+            pool_fee_basis_points: 100,
+            pplns_window_factor: 2,
+            payout_db_path: PathBuf::from("data/stratum_server/payouts.sqlite"),
+            wallet_grpc_address: None,
+            wallet_grpc_authentication: GrpcAuthentication::default(),
+            wallet_grpc_tls_domain_name: None,
+            wallet_grpc_ca_cert_filename: "wallet_ca.pem".to_string(),
+            payout_interval: Duration::from_secs(600),
+            minimum_payout: 1_000_000,
+            max_payout_recipients: 50,
+            payout_fee_per_gram: 5,
+        }
+    }
+}
+
+impl StratumServerConfig {
+    pub fn set_base_path<P: AsRef<Path>>(&mut self, base_path: P) {
+        if !self.config_dir.is_absolute() {
+            self.config_dir = base_path.as_ref().join(self.config_dir.as_path());
+        }
+        if !self.payout_db_path.is_absolute() {
+            self.payout_db_path = base_path.as_ref().join(self.payout_db_path.as_path());
+        }
+    }
+}
+
+impl SubConfigPath for StratumServerConfig {
+    fn main_key_prefix() -> &'static str {
+        "stratum_server"
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{str::FromStr, time::Duration};
+
+    use tari_common::DefaultConfigLoader;
+    use tari_comms::multiaddr::Multiaddr;
+
+    use crate::config::{MiningMode, PayoutScheme, StratumServerConfig};
+
+    fn get_config(override_from: &str) -> config::Config {
+        let s = r#"
+            [common]
+              baz = "foo"
+            [stratum_server]
+              initial_share_difficulty = 5000
+            [config_a.stratum_server]
+              mining_mode = "solo"
+              base_node_grpc_address = "/dns4/base_node_a/tcp/8080"
+            [config_b.stratum_server]
+              mining_mode = "pool"
+              vardiff_target_share_time = 10
+              payout_scheme = "pps"
+              minimum_payout = 250000
+              base_node_grpc_address = "/dns4/base_node_b/tcp/8080"
+            "#;
+
+        config::Config::builder()
+            .set_override("stratum_server.override_from", override_from)
+            .unwrap()
+            .add_source(config::File::from_str(s, config::FileFormat::Toml))
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn stratum_server_configuration() {
+        let cfg = get_config("config_a");
+        let config = StratumServerConfig::load_from(&cfg).expect("Failed to load config");
+        assert_eq!(config.mining_mode, MiningMode::Solo);
+        assert_eq!(config.payout_scheme, PayoutScheme::Pplns);
+        assert_eq!(config.initial_share_difficulty, 5000);
+        assert_eq!(
+            config.base_node_grpc_address,
+            Some(Multiaddr::from_str("/dns4/base_node_a/tcp/8080").unwrap())
+        );
+
+        let cfg = get_config("config_b");
+        let config = StratumServerConfig::load_from(&cfg).expect("Failed to load config");
+        assert_eq!(config.mining_mode, MiningMode::Pool);
+        assert_eq!(config.vardiff_target_share_time, Duration::from_secs(10));
+        assert_eq!(config.payout_scheme, PayoutScheme::Pps);
+        assert_eq!(config.minimum_payout, 250_000);
+        assert_eq!(config.initial_share_difficulty, 5000);
+        assert_eq!(
+            config.base_node_grpc_address,
+            Some(Multiaddr::from_str("/dns4/base_node_b/tcp/8080").unwrap())
+        );
+    }
+
+    #[test]
+    fn default_config() {
+        let config = StratumServerConfig::default();
+        assert_eq!(config.base_node_grpc_address, None);
+        assert_eq!(config.mining_mode, MiningMode::Pool);
+        assert!(config.min_share_difficulty <= config.initial_share_difficulty);
+        assert_eq!(config.wallet_grpc_address, None);
+    }
+}
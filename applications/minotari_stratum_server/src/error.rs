@@ -0,0 +1,105 @@
+// Copyright 2024. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! All errors that can occur in the `Stratum server`.
+
+use std::io;
+
+use minotari_app_grpc::authentication::BasicAuthError;
+use minotari_app_utilities::parse_miner_input::ParseInputError;
+use tari_common::{ConfigError, ConfigurationError};
+use tari_common_sqlite::error::StorageError;
+use tari_core::{
+    consensus::ConsensusBuilderError,
+    proof_of_work::DifficultyError,
+    transactions::{key_manager::CoreKeyManagerError, CoinbaseBuildError},
+};
+use tari_key_manager::key_manager_service::KeyManagerServiceError;
+use thiserror::Error;
+use tonic::{codegen::http::uri::InvalidUri, transport};
+
+#[derive(Debug, Error)]
+pub enum StratumServerError {
+    #[error("Configuration error: {0}")]
+    ConfigurationError(#[from] ConfigurationError),
+    #[error("Configuration error: {0}")]
+    ConfigError(#[from] ConfigError),
+    #[error("Invalid URI: {0}")]
+    InvalidUriError(#[from] InvalidUri),
+    #[error("An IO error occurred: {0}")]
+    IoError(#[from] io::Error),
+    #[error("Tonic transport error: {0}")]
+    TonicTransportError(#[from] transport::Error),
+    #[error("Grpc authentication error: {0}")]
+    GRPCAuthenticationError(#[from] BasicAuthError),
+    #[error("GRPC response did not contain the expected field: `{0}`")]
+    GrpcResponseMissingField(&'static str),
+    #[error("GRPC request failed with `{status}` {details}")]
+    GrpcRequestError {
+        #[source]
+        status: tonic::Status,
+        details: String,
+    },
+    #[error("JSON error: {0}")]
+    JsonError(#[from] serde_json::Error),
+    #[error("Coinbase builder error: {0}")]
+    CoinbaseBuilderError(#[from] CoinbaseBuildError),
+    #[error("Could not convert data:{0}")]
+    ConversionError(String),
+    #[error("Invalid difficulty: {0}")]
+    DifficultyError(#[from] DifficultyError),
+    #[error("TLS connection error: {0}")]
+    TlsConnectionError(String),
+    #[error("Key manager service error: `{0}`")]
+    KeyManagerServiceError(String),
+    #[error("Key manager error: {0}")]
+    CoreKeyManagerError(#[from] CoreKeyManagerError),
+    #[error("Consensus build error: {0}")]
+    ConsensusBuilderError(#[from] ConsensusBuilderError),
+    #[error("Input error: {0}")]
+    ParseInputError(#[from] ParseInputError),
+    #[error("Base node not responding to gRPC requests: {0}")]
+    BaseNodeNotResponding(String),
+    #[error("No block template is available yet")]
+    NoTemplateAvailable,
+    #[error("Storage error: {0}")]
+    StorageError(#[from] StorageError),
+    #[error("Database error: {0}")]
+    DieselError(#[from] diesel::result::Error),
+    #[error("Payout error: {0}")]
+    PayoutError(String),
+}
+
+impl From<tonic::Status> for StratumServerError {
+    fn from(status: tonic::Status) -> Self {
+        Self::GrpcRequestError {
+            details: String::from_utf8_lossy(status.details()).to_string(),
+            status,
+        }
+    }
+}
+
+impl From<KeyManagerServiceError> for StratumServerError {
+    fn from(err: KeyManagerServiceError) -> Self {
+        StratumServerError::KeyManagerServiceError(err.to_string())
+    }
+}
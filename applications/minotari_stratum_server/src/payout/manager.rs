@@ -0,0 +1,307 @@
+// Copyright 2024. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::sync::Arc;
+
+use chrono::Utc;
+use log::*;
+use minotari_app_grpc::{authentication::ClientAuthenticationInterceptor, tari_rpc as grpc};
+use minotari_app_utilities::parse_miner_input::BaseNodeGrpcClient;
+use tari_common_types::tari_address::TariAddress;
+use tari_core::{consensus::ConsensusManager, transactions::tari_amount::MicroMinotari};
+use tari_utilities::hex::Hex;
+use tokio::time::{self, MissedTickBehavior};
+use tonic::{codegen::InterceptedService, transport::Channel, Code};
+
+use crate::{
+    config::{PayoutScheme, StratumServerConfig},
+    error::StratumServerError,
+    payout::{
+        scheme::{fill_window, pplns_rewards},
+        storage::{BlockStatus, PendingBlock, PendingPayout},
+        PayoutDatabase,
+    },
+};
+
+const LOG_TARGET: &str = "minotari::stratum_server::payout";
+
+/// The number of shares loaded at a time while filling a PPLNS window
+const SHARE_PAGE_SIZE: i64 = 1_000;
+/// The statuses of a wallet batch payout row that settle a payout
+const BATCH_ROW_SENT: &str = "Sent";
+const BATCH_ROW_FAILED: &str = "Failed";
+
+pub type WalletGrpcClient =
+    grpc::wallet_client::WalletClient<InterceptedService<Channel, ClientAuthenticationInterceptor>>;
+
+/// Credits matured blocks to worker balances and pays out balances through the console wallet's batch payouts
+pub struct PayoutManager {
+    db: PayoutDatabase,
+    config: Arc<StratumServerConfig>,
+    base_node_client: BaseNodeGrpcClient,
+    /// Balances only accumulate when no wallet is configured
+    wallet_client: Option<WalletGrpcClient>,
+    consensus_manager: ConsensusManager,
+}
+
+impl PayoutManager {
+    pub fn new(
+        db: PayoutDatabase,
+        config: Arc<StratumServerConfig>,
+        base_node_client: BaseNodeGrpcClient,
+        wallet_client: Option<WalletGrpcClient>,
+        consensus_manager: ConsensusManager,
+    ) -> Self {
+        Self {
+            db,
+            config,
+            base_node_client,
+            wallet_client,
+            consensus_manager,
+        }
+    }
+
+    pub async fn run(self) {
+        let mut interval = time::interval(self.config.payout_interval);
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        loop {
+            interval.tick().await;
+            if let Err(e) = self.credit_matured_blocks().await {
+                warn!(target: LOG_TARGET, "Could not credit matured blocks: {}", e);
+            }
+            if let Some(wallet_client) = self.wallet_client.clone() {
+                if let Err(e) = self.pay_balances(wallet_client).await {
+                    warn!(target: LOG_TARGET, "Could not pay out balances: {}", e);
+                }
+            }
+        }
+    }
+
+    async fn credit_matured_blocks(&self) -> Result<(), StratumServerError> {
+        for block in self.db.pending_blocks()? {
+            let maturity = self
+                .consensus_manager
+                .consensus_constants(block.height)
+                .coinbase_min_maturity();
+            let response = self
+                .base_node_client
+                .clone()
+                .get_header_by_hash(grpc::GetHeaderByHashRequest {
+                    hash: block.hash.clone(),
+                })
+                .await;
+            match response {
+                Ok(response) => {
+                    if response.into_inner().confirmations < maturity {
+                        continue;
+                    }
+                    self.credit_block(&block)?;
+                },
+                Err(status) if status.code() == Code::NotFound => {
+                    warn!(
+                        target: LOG_TARGET,
+                        "Block {} at height {} is no longer part of the main chain",
+                        block.hash.to_hex(),
+                        block.height
+                    );
+                    self.db.set_block_status(&block.hash, BlockStatus::Orphaned)?;
+                },
+                Err(status) => return Err(status.into()),
+            }
+        }
+        Ok(())
+    }
+
+    fn credit_block(&self, block: &PendingBlock) -> Result<(), StratumServerError> {
+        // Under PPS shares were credited when they were accepted, so the pool keeps the block reward
+        let credits = match self.config.payout_scheme {
+            PayoutScheme::Pplns => pplns_rewards(
+                &self.pplns_window(block)?,
+                block.coinbase_amount,
+                self.config.pool_fee_basis_points,
+            ),
+            PayoutScheme::Pps => Vec::new(),
+        };
+        self.db.credit_block(&block.hash, &credits)?;
+        info!(
+            target: LOG_TARGET,
+            "Block {} at height {} matured, {} credited to {} workers",
+            block.hash.to_hex(),
+            block.height,
+            credits.iter().map(|(_, amount)| *amount).sum::<MicroMinotari>(),
+            credits.len()
+        );
+        Ok(())
+    }
+
+    /// Loads the shares, newest first, that make up the PPLNS window ending with the share that found the block
+    fn pplns_window(&self, block: &PendingBlock) -> Result<Vec<(TariAddress, u128)>, StratumServerError> {
+        let window = u128::from(block.network_difficulty) * u128::from(self.config.pplns_window_factor);
+        let mut filled = 0;
+        let mut shares = Vec::new();
+        let mut max_id = block.share_id;
+        loop {
+            let page = self.db.shares_up_to(max_id, SHARE_PAGE_SIZE)?;
+            let last_id = match page.last() {
+                Some(share) => share.id,
+                None => break,
+            };
+            let page = page.into_iter().map(|share| (share.address, share.difficulty));
+            if fill_window(window, &mut filled, page, &mut shares) {
+                break;
+            }
+            max_id = last_id - 1;
+        }
+        Ok(shares)
+    }
+
+    /// Pays the due balances with a single wallet batch payout. The batch id is stored with the payouts before the
+    /// wallet is called, so that payouts left pending by a crash or an unknown outcome are reconciled by submitting
+    /// the same batch again, which the wallet only pays where it has not paid already.
+    async fn pay_balances(&self, mut wallet_client: WalletGrpcClient) -> Result<(), StratumServerError> {
+        for (batch_id, payouts) in self.db.pending_payout_batches()? {
+            info!(
+                target: LOG_TARGET,
+                "Reconciling {} pending payouts of batch {} with the wallet",
+                payouts.len(),
+                batch_id
+            );
+            // A batch that can not be reconciled now is retried on the next interval and must not hold up new payouts
+            if let Err(e) = self.submit_batch(&mut wallet_client, batch_id, payouts, true).await {
+                warn!(target: LOG_TARGET, "Could not reconcile pending payouts: {}", e);
+            }
+        }
+
+        let due = self.db.balances_due(
+            MicroMinotari::from(self.config.minimum_payout),
+            self.config.max_payout_recipients,
+        )?;
+        if due.is_empty() {
+            return Ok(());
+        }
+        // Balances are debited before the transfer, so that a crash can never lead to paying the same balance twice
+        let batch_id = format!("stratum-payout-{}", Utc::now().timestamp_millis());
+        let payout_ids = self.db.reserve_payouts(&batch_id, &due)?;
+        let payouts = due
+            .into_iter()
+            .zip(payout_ids)
+            .map(|((address, amount), id)| PendingPayout { id, address, amount })
+            .collect();
+        self.submit_batch(&mut wallet_client, batch_id, payouts, false).await
+    }
+
+    async fn submit_batch(
+        &self,
+        wallet_client: &mut WalletGrpcClient,
+        batch_id: String,
+        payouts: Vec<PendingPayout>,
+        is_resubmission: bool,
+    ) -> Result<(), StratumServerError> {
+        let recipients = payouts
+            .iter()
+            .map(|payout| grpc::BatchPayoutRecipient {
+                address: payout.address.to_hex(),
+                amount: payout.amount.as_u64(),
+                memo: "Mining pool payout".to_string(),
+            })
+            .collect();
+        let request = grpc::BatchPayoutRequest {
+            batch_id: batch_id.clone(),
+            recipients,
+            fee_per_gram: self.config.payout_fee_per_gram,
+            from_account: String::new(),
+        };
+        let rows = match wallet_client.batch_payout(request).await {
+            Ok(response) => response.into_inner().rows,
+            Err(status) if batch_never_sent(&status, is_resubmission) => {
+                if status.code() == Code::Unimplemented {
+                    error!(
+                        target: LOG_TARGET,
+                        "The wallet does not support batch payouts, payouts require a console wallet with the \
+                         `BatchPayout` GRPC method"
+                    );
+                }
+                for payout in &payouts {
+                    self.db.fail_payout(payout.id, status.message())?;
+                }
+                return Err(status.into());
+            },
+            Err(status) => {
+                error!(
+                    target: LOG_TARGET,
+                    "The outcome of batch payout {} is unknown, its payouts are left pending and will be reconciled \
+                     with the wallet",
+                    batch_id
+                );
+                return Err(status.into());
+            },
+        };
+
+        // Resubmitting a batch also retries its failed rows, so failures are only settled once no row is left pending
+        let is_settled = rows.len() == payouts.len() &&
+            rows.iter()
+                .all(|row| row.status == BATCH_ROW_SENT || row.status == BATCH_ROW_FAILED);
+        for (index, payout) in (0u64..).zip(&payouts) {
+            match rows.iter().find(|row| row.index == index) {
+                Some(row) if row.status == BATCH_ROW_SENT => {
+                    info!(
+                        target: LOG_TARGET,
+                        "Paid {} to {} in transaction {}", payout.amount, payout.address, row.tx_id
+                    );
+                    self.db.complete_payout(payout.id, row.tx_id)?;
+                },
+                Some(row) if row.status == BATCH_ROW_FAILED && is_settled => {
+                    warn!(
+                        target: LOG_TARGET,
+                        "Payout of {} to {} failed: {}", payout.amount, payout.address, row.error
+                    );
+                    self.db.fail_payout(payout.id, &row.error)?;
+                },
+                Some(_) => debug!(
+                    target: LOG_TARGET,
+                    "Payout of {} to {} is not settled in batch {} yet", payout.amount, payout.address, batch_id
+                ),
+                None => {
+                    warn!(
+                        target: LOG_TARGET,
+                        "The wallet returned no row for the payout of {} to {} in batch {}",
+                        payout.amount,
+                        payout.address,
+                        batch_id
+                    );
+                },
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Whether the wallet certainly did not send any transaction of a batch payout that failed with `status`. A rejected
+/// batch is never paid, while connection and authentication errors, or a wallet without batch payouts, only rule out
+/// payments when the batch has not been submitted before.
+fn batch_never_sent(status: &tonic::Status, is_resubmission: bool) -> bool {
+    match status.code() {
+        Code::InvalidArgument => true,
+        Code::Unauthenticated | Code::PermissionDenied | Code::Unavailable | Code::Unimplemented => !is_resubmission,
+        _ => false,
+    }
+}
@@ -0,0 +1,97 @@
+// Copyright 2024. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Pool mode reward accounting: shares and found blocks are recorded in a SQLite database, rewards are credited to
+//! worker balances according to the configured [PayoutScheme](crate::config::PayoutScheme) and balances are paid out
+//! through the console wallet.
+
+mod manager;
+pub use manager::{PayoutManager, WalletGrpcClient};
+
+mod scheme;
+
+mod storage;
+use std::sync::Arc;
+
+pub use storage::PayoutDatabase;
+use tari_common_types::{tari_address::TariAddress, types::FixedHash};
+use tari_core::consensus::ConsensusManager;
+
+use crate::{
+    config::{PayoutScheme, StratumServerConfig},
+    error::StratumServerError,
+    stratum::MiningJob,
+};
+
+/// Records accepted pool shares and found blocks for the payout manager
+#[derive(Clone)]
+pub struct ShareRecorder {
+    db: PayoutDatabase,
+    config: Arc<StratumServerConfig>,
+    consensus_manager: ConsensusManager,
+}
+
+impl ShareRecorder {
+    pub fn new(db: PayoutDatabase, config: Arc<StratumServerConfig>, consensus_manager: ConsensusManager) -> Self {
+        Self {
+            db,
+            config,
+            consensus_manager,
+        }
+    }
+
+    /// Records a share credited to `address`. Under PPS the share is valued and credited straight away. If the share
+    /// found a block that the base node accepted, `block_hash` is set and the block is recorded to be credited once it
+    /// matures.
+    pub fn record_share(
+        &self,
+        worker: &str,
+        address: &TariAddress,
+        share_difficulty: u64,
+        job: &MiningJob,
+        block_hash: Option<FixedHash>,
+    ) -> Result<(), StratumServerError> {
+        let credit = match self.config.payout_scheme {
+            PayoutScheme::Pps => Some(scheme::pps_share_value(
+                share_difficulty,
+                job.network_difficulty,
+                self.consensus_manager.get_block_reward_at(job.height),
+                self.config.pool_fee_basis_points,
+            )),
+            PayoutScheme::Pplns => None,
+        };
+        let share_id = self
+            .db
+            .insert_share(worker, address, share_difficulty, job.height, credit)?;
+        if let Some(hash) = block_hash {
+            self.db.insert_block(
+                hash.as_slice(),
+                job.height,
+                job.coinbase_amount,
+                job.network_difficulty,
+                worker,
+                share_id,
+            )?;
+        }
+        Ok(())
+    }
+}
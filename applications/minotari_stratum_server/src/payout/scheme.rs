@@ -0,0 +1,156 @@
+// Copyright 2024. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Pure reward calculations for the supported payout schemes.
+
+use std::convert::TryFrom;
+
+use tari_common_types::tari_address::TariAddress;
+use tari_core::transactions::tari_amount::MicroMinotari;
+
+const BASIS_POINTS: u128 = 10_000;
+
+/// Deducts the pool fee from `amount`
+fn after_fee(amount: u128, pool_fee_basis_points: u64) -> u128 {
+    let fee = u128::from(pool_fee_basis_points).min(BASIS_POINTS);
+    amount * (BASIS_POINTS - fee) / BASIS_POINTS
+}
+
+fn to_micro_minotari(value: u128) -> MicroMinotari {
+    MicroMinotari::from(u64::try_from(value).unwrap_or(u64::MAX))
+}
+
+/// The amount a single share earns under PPS: its share of the expected block reward, less the pool fee. Shares above
+/// the network difficulty are valued as a full block.
+pub fn pps_share_value(
+    share_difficulty: u64,
+    network_difficulty: u64,
+    block_reward: MicroMinotari,
+    pool_fee_basis_points: u64,
+) -> MicroMinotari {
+    if network_difficulty == 0 {
+        return MicroMinotari::from(0);
+    }
+    let share_difficulty = u128::from(share_difficulty.min(network_difficulty));
+    let value = u128::from(block_reward.as_u64()) * share_difficulty / u128::from(network_difficulty);
+    to_micro_minotari(after_fee(value, pool_fee_basis_points))
+}
+
+/// Takes shares, newest first, until their combined difficulty reaches `window`. The oldest share is clipped so that
+/// the window is not exceeded. Returns true once the window is full.
+pub fn fill_window<I>(window: u128, filled: &mut u128, shares: I, output: &mut Vec<(TariAddress, u128)>) -> bool
+where I: IntoIterator<Item = (TariAddress, u64)> {
+    for (address, difficulty) in shares {
+        if *filled >= window {
+            return true;
+        }
+        let weight = u128::from(difficulty).min(window - *filled);
+        *filled += weight;
+        output.push((address, weight));
+    }
+    *filled >= window
+}
+
+/// Splits the block `amount`, less the pool fee, between the shares in the PPLNS window in proportion to their
+/// weight. Amounts for the same address are combined. Rounding dust stays with the pool.
+pub fn pplns_rewards(
+    shares: &[(TariAddress, u128)],
+    amount: MicroMinotari,
+    pool_fee_basis_points: u64,
+) -> Vec<(TariAddress, MicroMinotari)> {
+    let total_weight = shares.iter().map(|(_, weight)| *weight).sum::<u128>();
+    if total_weight == 0 {
+        return Vec::new();
+    }
+    let mut weights: Vec<(TariAddress, u128)> = Vec::new();
+    for (address, weight) in shares {
+        match weights.iter_mut().find(|(a, _)| a == address) {
+            Some((_, total)) => *total += *weight,
+            None => weights.push((address.clone(), *weight)),
+        }
+    }
+    let distributable = after_fee(u128::from(amount.as_u64()), pool_fee_basis_points);
+    weights
+        .into_iter()
+        .map(|(address, weight)| (address, to_micro_minotari(distributable * weight / total_weight)))
+        .filter(|(_, reward)| reward.as_u64() > 0)
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use tari_common::configuration::Network;
+    use tari_common_types::types::PublicKey;
+    use tari_crypto::keys::PublicKey as PublicKeyTrait;
+
+    use super::*;
+
+    fn random_address() -> TariAddress {
+        let (_, public_key) = PublicKey::random_keypair(&mut rand::thread_rng());
+        TariAddress::new(public_key, Network::LocalNet)
+    }
+
+    #[test]
+    fn it_values_pps_shares() {
+        let reward = MicroMinotari::from(1_000_000);
+        assert_eq!(pps_share_value(100, 1_000, reward, 0), MicroMinotari::from(100_000));
+        assert_eq!(pps_share_value(100, 1_000, reward, 100), MicroMinotari::from(99_000));
+        assert_eq!(pps_share_value(5_000, 1_000, reward, 0), reward);
+        assert_eq!(pps_share_value(100, 0, reward, 0), MicroMinotari::from(0));
+        assert_eq!(pps_share_value(100, 1_000, reward, 20_000), MicroMinotari::from(0));
+    }
+
+    #[test]
+    fn it_clips_the_pplns_window() {
+        let alice = random_address();
+        let bob = random_address();
+        let mut filled = 0;
+        let mut window = Vec::new();
+        assert!(!fill_window(
+            1_000,
+            &mut filled,
+            vec![(alice.clone(), 400)],
+            &mut window
+        ));
+        assert!(fill_window(
+            1_000,
+            &mut filled,
+            vec![(bob.clone(), 500), (alice.clone(), 500), (bob.clone(), 500)],
+            &mut window
+        ));
+        assert_eq!(filled, 1_000);
+        assert_eq!(window, vec![(alice.clone(), 400), (bob, 500), (alice, 100)]);
+    }
+
+    #[test]
+    fn it_splits_pplns_rewards() {
+        let alice = random_address();
+        let bob = random_address();
+        let shares = vec![(alice.clone(), 300), (bob.clone(), 500), (alice.clone(), 200)];
+        let rewards = pplns_rewards(&shares, MicroMinotari::from(10_000), 1_000);
+        assert_eq!(rewards, vec![
+            (alice, MicroMinotari::from(4_500)),
+            (bob, MicroMinotari::from(4_500))
+        ]);
+        assert!(pplns_rewards(&[], MicroMinotari::from(10_000), 0).is_empty());
+    }
+}
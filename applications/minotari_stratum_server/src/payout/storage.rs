@@ -0,0 +1,544 @@
+// Copyright 2024. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{collections::BTreeMap, convert::TryFrom, fs, path::Path};
+
+use chrono::{NaiveDateTime, Utc};
+use diesel::{prelude::*, SqliteConnection};
+use diesel_migrations::{embed_migrations, EmbeddedMigrations};
+use tari_common_sqlite::connection::{DbConnection, DbConnectionUrl};
+use tari_common_types::tari_address::TariAddress;
+use tari_core::transactions::tari_amount::MicroMinotari;
+
+use crate::{
+    error::StratumServerError,
+    schema::{balances, blocks, payouts, shares},
+};
+
+const MIGRATIONS: EmbeddedMigrations = embed_migrations!("./migrations");
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlockStatus {
+    /// Found, but the coinbase has not matured yet
+    Pending = 0,
+    /// Matured and credited to worker balances
+    Credited = 1,
+    /// No longer part of the main chain
+    Orphaned = 2,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PayoutStatus {
+    /// Debited from the balance, but the wallet has not confirmed the transfer yet
+    Pending = 0,
+    Sent = 1,
+    /// The transfer failed and the amount was credited back to the balance
+    Failed = 2,
+}
+
+/// A block found by the pool that has not matured yet
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PendingBlock {
+    pub hash: Vec<u8>,
+    pub height: u64,
+    pub coinbase_amount: MicroMinotari,
+    pub network_difficulty: u64,
+    /// The id of the share that found the block, the PPLNS window ends with it
+    pub share_id: i32,
+}
+
+/// A share as used in reward calculations
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShareEntry {
+    pub id: i32,
+    pub address: TariAddress,
+    pub difficulty: u64,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = shares)]
+struct ShareSqlInsert {
+    worker: String,
+    address: Vec<u8>,
+    difficulty: i64,
+    height: i64,
+    created_at: NaiveDateTime,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = blocks)]
+struct BlockSqlInsert {
+    hash: Vec<u8>,
+    height: i64,
+    coinbase_amount: i64,
+    network_difficulty: i64,
+    found_by: String,
+    share_id: i32,
+    status: i32,
+    created_at: NaiveDateTime,
+}
+
+/// A payout that was reserved from a balance but not confirmed by the wallet yet
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PendingPayout {
+    pub id: i32,
+    pub address: TariAddress,
+    pub amount: MicroMinotari,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = payouts)]
+struct PayoutSqlInsert {
+    address: Vec<u8>,
+    amount: i64,
+    status: i32,
+    created_at: NaiveDateTime,
+    batch_id: Option<String>,
+}
+
+/// The SQLite store for shares, found blocks, worker balances and payout history
+#[derive(Clone)]
+pub struct PayoutDatabase {
+    connection: DbConnection,
+}
+
+impl PayoutDatabase {
+    pub fn connect(path: &Path) -> Result<Self, StratumServerError> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let connection = DbConnection::connect_and_migrate(&DbConnectionUrl::file(path), MIGRATIONS)?;
+        Ok(Self { connection })
+    }
+
+    #[cfg(test)]
+    pub fn connect_memory(name: &str) -> Self {
+        let connection = DbConnection::connect_memory(name.to_string()).unwrap();
+        connection.migrate(MIGRATIONS).unwrap();
+        Self { connection }
+    }
+
+    /// Records an accepted share, crediting `credit` to the address balance in the same transaction, and returns the
+    /// share id
+    pub fn insert_share(
+        &self,
+        worker: &str,
+        address: &TariAddress,
+        difficulty: u64,
+        height: u64,
+        credit: Option<MicroMinotari>,
+    ) -> Result<i32, StratumServerError> {
+        let mut conn = self.connection.get_pooled_connection()?;
+        conn.transaction::<_, StratumServerError, _>(|conn| {
+            diesel::insert_into(shares::table)
+                .values(ShareSqlInsert {
+                    worker: worker.to_string(),
+                    address: address.to_bytes().to_vec(),
+                    difficulty: to_i64(difficulty)?,
+                    height: to_i64(height)?,
+                    created_at: Utc::now().naive_utc(),
+                })
+                .execute(conn)?;
+            if let Some(credit) = credit {
+                credit_balance(conn, address, credit)?;
+            }
+            Ok(shares::table
+                .select(shares::id)
+                .order(shares::id.desc())
+                .first::<i32>(conn)?)
+        })
+    }
+
+    pub fn insert_block(
+        &self,
+        hash: &[u8],
+        height: u64,
+        coinbase_amount: MicroMinotari,
+        network_difficulty: u64,
+        found_by: &str,
+        share_id: i32,
+    ) -> Result<(), StratumServerError> {
+        let mut conn = self.connection.get_pooled_connection()?;
+        diesel::insert_into(blocks::table)
+            .values(BlockSqlInsert {
+                hash: hash.to_vec(),
+                height: to_i64(height)?,
+                coinbase_amount: to_i64(coinbase_amount.as_u64())?,
+                network_difficulty: to_i64(network_difficulty)?,
+                found_by: found_by.to_string(),
+                share_id,
+                status: BlockStatus::Pending as i32,
+                created_at: Utc::now().naive_utc(),
+            })
+            .execute(&mut conn)?;
+        Ok(())
+    }
+
+    pub fn pending_blocks(&self) -> Result<Vec<PendingBlock>, StratumServerError> {
+        let mut conn = self.connection.get_pooled_connection()?;
+        blocks::table
+            .filter(blocks::status.eq(BlockStatus::Pending as i32))
+            .order(blocks::height.asc())
+            .select((
+                blocks::hash,
+                blocks::height,
+                blocks::coinbase_amount,
+                blocks::network_difficulty,
+                blocks::share_id,
+            ))
+            .load::<(Vec<u8>, i64, i64, i64, i32)>(&mut conn)?
+            .into_iter()
+            .map(|(hash, height, coinbase_amount, network_difficulty, share_id)| {
+                Ok(PendingBlock {
+                    hash,
+                    height: to_u64(height)?,
+                    coinbase_amount: MicroMinotari::from(to_u64(coinbase_amount)?),
+                    network_difficulty: to_u64(network_difficulty)?,
+                    share_id,
+                })
+            })
+            .collect()
+    }
+
+    /// Returns up to `limit` shares with an id of at most `max_id`, newest first
+    pub fn shares_up_to(&self, max_id: i32, limit: i64) -> Result<Vec<ShareEntry>, StratumServerError> {
+        let mut conn = self.connection.get_pooled_connection()?;
+        shares::table
+            .filter(shares::id.le(max_id))
+            .order(shares::id.desc())
+            .limit(limit)
+            .select((shares::id, shares::address, shares::difficulty))
+            .load::<(i32, Vec<u8>, i64)>(&mut conn)?
+            .into_iter()
+            .map(|(id, address, difficulty)| {
+                Ok(ShareEntry {
+                    id,
+                    address: to_address(&address)?,
+                    difficulty: to_u64(difficulty)?,
+                })
+            })
+            .collect()
+    }
+
+    pub fn set_block_status(&self, hash: &[u8], status: BlockStatus) -> Result<(), StratumServerError> {
+        let mut conn = self.connection.get_pooled_connection()?;
+        diesel::update(blocks::table.filter(blocks::hash.eq(hash)))
+            .set(blocks::status.eq(status as i32))
+            .execute(&mut conn)?;
+        Ok(())
+    }
+
+    /// Credits the rewards of a matured block to the worker balances and marks the block as credited
+    pub fn credit_block(
+        &self,
+        hash: &[u8],
+        credits: &[(TariAddress, MicroMinotari)],
+    ) -> Result<(), StratumServerError> {
+        let mut conn = self.connection.get_pooled_connection()?;
+        conn.transaction::<_, StratumServerError, _>(|conn| {
+            for (address, amount) in credits {
+                credit_balance(conn, address, *amount)?;
+            }
+            diesel::update(blocks::table.filter(blocks::hash.eq(hash)))
+                .set(blocks::status.eq(BlockStatus::Credited as i32))
+                .execute(conn)?;
+            Ok(())
+        })
+    }
+
+    #[cfg(test)]
+    fn balance(&self, address: &TariAddress) -> Result<MicroMinotari, StratumServerError> {
+        let mut conn = self.connection.get_pooled_connection()?;
+        let amount = balances::table
+            .filter(balances::address.eq(address.to_bytes().to_vec()))
+            .select(balances::amount)
+            .first::<i64>(&mut conn)
+            .optional()?
+            .unwrap_or_default();
+        Ok(MicroMinotari::from(to_u64(amount)?))
+    }
+
+    /// Returns up to `limit` balances of at least `minimum`, largest first
+    pub fn balances_due(
+        &self,
+        minimum: MicroMinotari,
+        limit: usize,
+    ) -> Result<Vec<(TariAddress, MicroMinotari)>, StratumServerError> {
+        let mut conn = self.connection.get_pooled_connection()?;
+        balances::table
+            .filter(balances::amount.ge(to_i64(minimum.as_u64())?))
+            .order(balances::amount.desc())
+            .limit(i64::try_from(limit).unwrap_or(i64::MAX))
+            .load::<(Vec<u8>, i64)>(&mut conn)?
+            .into_iter()
+            .map(|(address, amount)| Ok((to_address(&address)?, MicroMinotari::from(to_u64(amount)?))))
+            .collect()
+    }
+
+    /// Debits the payout amounts from the balances and records them as pending payouts of the wallet batch payout
+    /// `batch_id`, returning the payout ids in the same order
+    pub fn reserve_payouts(
+        &self,
+        batch_id: &str,
+        payouts: &[(TariAddress, MicroMinotari)],
+    ) -> Result<Vec<i32>, StratumServerError> {
+        let mut conn = self.connection.get_pooled_connection()?;
+        conn.transaction::<_, StratumServerError, _>(|conn| {
+            let mut ids = Vec::with_capacity(payouts.len());
+            for (address, amount) in payouts {
+                debit_balance(conn, address, *amount)?;
+                diesel::insert_into(payouts::table)
+                    .values(PayoutSqlInsert {
+                        address: address.to_bytes().to_vec(),
+                        amount: to_i64(amount.as_u64())?,
+                        status: PayoutStatus::Pending as i32,
+                        created_at: Utc::now().naive_utc(),
+                        batch_id: Some(batch_id.to_string()),
+                    })
+                    .execute(conn)?;
+                ids.push(
+                    payouts::table
+                        .select(payouts::id)
+                        .order(payouts::id.desc())
+                        .first::<i32>(conn)?,
+                );
+            }
+            Ok(ids)
+        })
+    }
+
+    /// Returns the pending payouts grouped by the wallet batch payout that pays them, in the order they were reserved
+    pub fn pending_payout_batches(&self) -> Result<BTreeMap<String, Vec<PendingPayout>>, StratumServerError> {
+        let mut conn = self.connection.get_pooled_connection()?;
+        let rows = payouts::table
+            .filter(payouts::status.eq(PayoutStatus::Pending as i32))
+            .filter(payouts::batch_id.is_not_null())
+            .order(payouts::id.asc())
+            .select((payouts::id, payouts::address, payouts::amount, payouts::batch_id))
+            .load::<(i32, Vec<u8>, i64, Option<String>)>(&mut conn)?;
+        let mut batches = BTreeMap::<_, Vec<_>>::new();
+        for (id, address, amount, batch_id) in rows {
+            batches
+                .entry(batch_id.unwrap_or_default())
+                .or_default()
+                .push(PendingPayout {
+                    id,
+                    address: to_address(&address)?,
+                    amount: MicroMinotari::from(to_u64(amount)?),
+                });
+        }
+        Ok(batches)
+    }
+
+    pub fn complete_payout(&self, id: i32, tx_id: u64) -> Result<(), StratumServerError> {
+        let mut conn = self.connection.get_pooled_connection()?;
+        diesel::update(payouts::table.filter(payouts::id.eq(id)))
+            .set((
+                payouts::status.eq(PayoutStatus::Sent as i32),
+                payouts::tx_id.eq(Some(to_i64(tx_id)?)),
+            ))
+            .execute(&mut conn)?;
+        Ok(())
+    }
+
+    /// Marks a pending payout as failed and credits the amount back to the balance
+    pub fn fail_payout(&self, id: i32, message: &str) -> Result<(), StratumServerError> {
+        let mut conn = self.connection.get_pooled_connection()?;
+        conn.transaction::<_, StratumServerError, _>(|conn| {
+            let (address, amount) = payouts::table
+                .filter(payouts::id.eq(id))
+                .filter(payouts::status.eq(PayoutStatus::Pending as i32))
+                .select((payouts::address, payouts::amount))
+                .first::<(Vec<u8>, i64)>(conn)?;
+            credit_balance(conn, &to_address(&address)?, MicroMinotari::from(to_u64(amount)?))?;
+            diesel::update(payouts::table.filter(payouts::id.eq(id)))
+                .set((
+                    payouts::status.eq(PayoutStatus::Failed as i32),
+                    payouts::failure_message.eq(Some(message)),
+                ))
+                .execute(conn)?;
+            Ok(())
+        })
+    }
+
+    #[cfg(test)]
+    fn payout_status(&self, id: i32) -> PayoutStatus {
+        let mut conn = self.connection.get_pooled_connection().unwrap();
+        match payouts::table
+            .filter(payouts::id.eq(id))
+            .select(payouts::status)
+            .first::<i32>(&mut conn)
+            .unwrap()
+        {
+            0 => PayoutStatus::Pending,
+            1 => PayoutStatus::Sent,
+            _ => PayoutStatus::Failed,
+        }
+    }
+}
+
+fn credit_balance(
+    conn: &mut SqliteConnection,
+    address: &TariAddress,
+    amount: MicroMinotari,
+) -> Result<(), StratumServerError> {
+    let key = address.to_bytes().to_vec();
+    let current = balances::table
+        .filter(balances::address.eq(&key))
+        .select(balances::amount)
+        .first::<i64>(conn)
+        .optional()?;
+    let amount = to_i64(amount.as_u64())?;
+    match current {
+        Some(current) => {
+            diesel::update(balances::table.filter(balances::address.eq(&key)))
+                .set(balances::amount.eq(current.saturating_add(amount)))
+                .execute(conn)?;
+        },
+        None => {
+            diesel::insert_into(balances::table)
+                .values((balances::address.eq(&key), balances::amount.eq(amount)))
+                .execute(conn)?;
+        },
+    }
+    Ok(())
+}
+
+fn debit_balance(
+    conn: &mut SqliteConnection,
+    address: &TariAddress,
+    amount: MicroMinotari,
+) -> Result<(), StratumServerError> {
+    let key = address.to_bytes().to_vec();
+    let current = balances::table
+        .filter(balances::address.eq(&key))
+        .select(balances::amount)
+        .first::<i64>(conn)
+        .optional()?
+        .unwrap_or_default();
+    let amount = to_i64(amount.as_u64())?;
+    if current < amount {
+        return Err(StratumServerError::PayoutError(format!(
+            "Balance of {} is lower than the payout of {}",
+            address, amount
+        )));
+    }
+    diesel::update(balances::table.filter(balances::address.eq(&key)))
+        .set(balances::amount.eq(current - amount))
+        .execute(conn)?;
+    Ok(())
+}
+
+fn to_i64(value: u64) -> Result<i64, StratumServerError> {
+    i64::try_from(value).map_err(|_| StratumServerError::ConversionError(format!("{} does not fit in an i64", value)))
+}
+
+fn to_u64(value: i64) -> Result<u64, StratumServerError> {
+    u64::try_from(value).map_err(|_| StratumServerError::ConversionError(format!("{} is negative", value)))
+}
+
+fn to_address(bytes: &[u8]) -> Result<TariAddress, StratumServerError> {
+    TariAddress::from_bytes(bytes).map_err(|e| StratumServerError::ConversionError(e.to_string()))
+}
+
+#[cfg(test)]
+mod test {
+    use tari_common::configuration::Network;
+    use tari_common_types::types::PublicKey;
+    use tari_crypto::keys::PublicKey as PublicKeyTrait;
+
+    use super::*;
+
+    fn random_address() -> TariAddress {
+        let (_, public_key) = PublicKey::random_keypair(&mut rand::thread_rng());
+        TariAddress::new(public_key, Network::LocalNet)
+    }
+
+    #[test]
+    fn it_records_shares_and_blocks() {
+        let db = PayoutDatabase::connect_memory("it_records_shares_and_blocks");
+        let alice = random_address();
+        let bob = random_address();
+        let first = db.insert_share("alice", &alice, 100, 10, None).unwrap();
+        let second = db.insert_share("bob", &bob, 200, 10, None).unwrap();
+        let third = db.insert_share("alice", &alice, 300, 10, None).unwrap();
+        assert!(first < second && second < third);
+
+        let shares = db.shares_up_to(second, 10).unwrap();
+        assert_eq!(shares.len(), 2);
+        assert_eq!(shares[0].address, bob);
+        assert_eq!(shares[0].difficulty, 200);
+        assert_eq!(shares[1].id, first);
+
+        db.insert_block(&[1u8; 32], 10, MicroMinotari::from(1_000), 1_000, "bob", second)
+            .unwrap();
+        let pending = db.pending_blocks().unwrap();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].share_id, second);
+
+        db.credit_block(&[1u8; 32], &[
+            (alice.clone(), MicroMinotari::from(400)),
+            (bob.clone(), MicroMinotari::from(600)),
+        ])
+        .unwrap();
+        assert!(db.pending_blocks().unwrap().is_empty());
+        assert_eq!(db.balance(&alice).unwrap(), MicroMinotari::from(400));
+        assert_eq!(db.balance(&bob).unwrap(), MicroMinotari::from(600));
+    }
+
+    #[test]
+    fn it_reserves_and_settles_payouts() {
+        let db = PayoutDatabase::connect_memory("it_reserves_and_settles_payouts");
+        let alice = random_address();
+        let bob = random_address();
+        db.insert_share("alice", &alice, 1, 1, Some(MicroMinotari::from(500)))
+            .unwrap();
+        db.insert_share("bob", &bob, 1, 1, Some(MicroMinotari::from(50)))
+            .unwrap();
+
+        let due = db.balances_due(MicroMinotari::from(100), 10).unwrap();
+        assert_eq!(due, vec![(alice.clone(), MicroMinotari::from(500))]);
+
+        let ids = db.reserve_payouts("batch-1", &due).unwrap();
+        assert_eq!(db.balance(&alice).unwrap(), MicroMinotari::from(0));
+        assert_eq!(db.payout_status(ids[0]), PayoutStatus::Pending);
+        let pending = db.pending_payout_batches().unwrap();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending["batch-1"], vec![PendingPayout {
+            id: ids[0],
+            address: alice.clone(),
+            amount: MicroMinotari::from(500),
+        }]);
+
+        db.fail_payout(ids[0], "Wallet offline").unwrap();
+        assert_eq!(db.payout_status(ids[0]), PayoutStatus::Failed);
+        assert_eq!(db.balance(&alice).unwrap(), MicroMinotari::from(500));
+        assert!(db.pending_payout_batches().unwrap().is_empty());
+
+        let ids = db.reserve_payouts("batch-2", &due).unwrap();
+        db.complete_payout(ids[0], 1234).unwrap();
+        assert_eq!(db.payout_status(ids[0]), PayoutStatus::Sent);
+        assert_eq!(db.balance(&alice).unwrap(), MicroMinotari::from(0));
+        assert!(db.pending_payout_batches().unwrap().is_empty());
+        assert!(db.reserve_payouts("batch-3", &due).is_err());
+    }
+}
@@ -0,0 +1,219 @@
+// Copyright 2024. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{net::SocketAddr, path::Path, str::FromStr, sync::Arc};
+
+use log::*;
+use minotari_app_grpc::{
+    authentication::ClientAuthenticationInterceptor,
+    tari_rpc::{self as grpc, base_node_client::BaseNodeClient, wallet_client::WalletClient},
+    tls::protocol_string,
+};
+use minotari_app_utilities::parse_miner_input::{
+    base_node_socket_address,
+    verify_base_node_grpc_mining_responses,
+    wallet_payment_address,
+    BaseNodeGrpcClient,
+};
+use tari_common::{load_configuration, DefaultConfigLoader};
+use tari_comms::{multiaddr::Multiaddr, utils::multiaddr::multiaddr_to_socketaddr};
+use tari_core::{consensus::ConsensusManager, transactions::key_manager::create_memory_db_key_manager};
+use tokio::net::TcpListener;
+use tonic::transport::{Certificate, Channel, ClientTlsConfig, Endpoint};
+
+use crate::{
+    config::{MiningMode, StratumServerConfig},
+    error::StratumServerError,
+    payout::{PayoutDatabase, PayoutManager, ShareRecorder, WalletGrpcClient},
+    stratum::{JobManager, ShareLedger, StratumServer},
+    Cli,
+};
+
+const LOG_TARGET: &str = "minotari::stratum_server::main";
+
+pub async fn start_stratum_server(cli: Cli) -> Result<(), anyhow::Error> {
+    let config_path = cli.common.config_path();
+    let cfg = load_configuration(&config_path, true, cli.non_interactive_mode, &cli)?;
+    let mut config = StratumServerConfig::load_from(&cfg)?;
+    config.set_base_path(cli.common.get_base_path());
+
+    info!(target: LOG_TARGET, "Configuration: {:?}", config);
+    let pool_address = match config.mining_mode {
+        MiningMode::Pool => Some(wallet_payment_address(
+            config.wallet_payment_address.clone(),
+            config.network,
+        )?),
+        MiningMode::Solo => None,
+    };
+    let mut base_node_client = match connect_base_node(&config).await {
+        Ok(client) => client,
+        Err(e) => {
+            error!(target: LOG_TARGET, "Could not connect to base node: {}", e);
+            let msg = "Could not connect to base node. \nIs the base node's gRPC running? Try running it with \
+                       `--enable-grpc` or enable it in the config.";
+            println!("{}", msg);
+            return Err(e.into());
+        },
+    };
+    if let Err(e) = verify_base_node_responses(&mut base_node_client).await {
+        error!(target: LOG_TARGET, "{}", e);
+        println!();
+        let msg = "Are the base node's gRPC mining methods denied in its 'config.toml'? Please ensure these methods \
+                   are commented out:\n  'grpc_server_deny_methods': \"get_new_block_template\", \"get_tip_info\", \
+                   \"get_new_block\", \"submit_block\"";
+        println!("{}", msg);
+        println!();
+        return Err(e.into());
+    }
+
+    let consensus_manager = ConsensusManager::builder(config.network)
+        .build()
+        .map_err(StratumServerError::from)?;
+    let listen_addr = multiaddr_to_socketaddr(&config.listener_address)?;
+    let config = Arc::new(config);
+    let share_recorder = match config.mining_mode {
+        MiningMode::Pool => {
+            let db = PayoutDatabase::connect(&config.payout_db_path)?;
+            let wallet_client = match config.wallet_grpc_address.as_ref() {
+                Some(address) => Some(connect_wallet(&config, address).await?),
+                None => {
+                    warn!(
+                        target: LOG_TARGET,
+                        "No wallet GRPC address is configured, balances will accumulate without being paid out"
+                    );
+                    None
+                },
+            };
+            let payout_manager = PayoutManager::new(
+                db.clone(),
+                config.clone(),
+                base_node_client.clone(),
+                wallet_client,
+                consensus_manager.clone(),
+            );
+            tokio::spawn(payout_manager.run());
+            Some(ShareRecorder::new(db, config.clone(), consensus_manager.clone()))
+        },
+        MiningMode::Solo => None,
+    };
+    let job_manager = JobManager::new(
+        base_node_client,
+        create_memory_db_key_manager(),
+        consensus_manager,
+        config.clone(),
+    );
+    let server = StratumServer::new(config, job_manager, ShareLedger::new(), share_recorder, pool_address);
+
+    match TcpListener::bind(listen_addr).await {
+        Ok(listener) => {
+            info!(target: LOG_TARGET, "Stratum server listening on {}...", listen_addr);
+            println!("Stratum server listening on {}...", listen_addr);
+            server.run(listener).await?;
+            Ok(())
+        },
+        Err(err) => {
+            error!(target: LOG_TARGET, "Fatal: Cannot bind to '{}'.", listen_addr);
+            println!("Fatal: Cannot bind to '{}'.", listen_addr);
+            println!("It may be part of a Port Exclusion Range. Please try to use another port for the");
+            println!("'listener_address' in 'config/config.toml'.");
+            println!();
+            Err(err.into())
+        },
+    }
+}
+
+async fn verify_base_node_responses(node_conn: &mut BaseNodeGrpcClient) -> Result<(), StratumServerError> {
+    if let Err(e) = verify_base_node_grpc_mining_responses(node_conn, grpc::NewBlockTemplateRequest {
+        algo: Some(grpc::PowAlgo {
+            pow_algo: grpc::pow_algo::PowAlgos::Sha3x.into(),
+        }),
+        max_weight: 0,
+    })
+    .await
+    {
+        return Err(StratumServerError::BaseNodeNotResponding(e));
+    }
+    Ok(())
+}
+
+async fn connect_base_node(config: &StratumServerConfig) -> Result<BaseNodeGrpcClient, StratumServerError> {
+    let socketaddr = base_node_socket_address(config.base_node_grpc_address.clone(), config.network)?;
+    info!(target: LOG_TARGET, "👛 Connecting to base node at {}", socketaddr);
+    let channel = connect_channel(
+        socketaddr,
+        config.base_node_grpc_tls_domain_name.as_deref(),
+        &config.config_dir.join(&config.base_node_grpc_ca_cert_filename),
+    )
+    .await?;
+    let node_conn = BaseNodeClient::with_interceptor(
+        channel,
+        ClientAuthenticationInterceptor::create(&config.base_node_grpc_authentication)?,
+    );
+
+    Ok(node_conn)
+}
+
+async fn connect_wallet(
+    config: &StratumServerConfig,
+    address: &Multiaddr,
+) -> Result<WalletGrpcClient, StratumServerError> {
+    let socketaddr = multiaddr_to_socketaddr(address)?;
+    info!(target: LOG_TARGET, "👛 Connecting to wallet at {}", socketaddr);
+    let channel = connect_channel(
+        socketaddr,
+        config.wallet_grpc_tls_domain_name.as_deref(),
+        &config.config_dir.join(&config.wallet_grpc_ca_cert_filename),
+    )
+    .await?;
+    let wallet_conn = WalletClient::with_interceptor(
+        channel,
+        ClientAuthenticationInterceptor::create(&config.wallet_grpc_authentication)?,
+    );
+
+    Ok(wallet_conn)
+}
+
+async fn connect_channel(
+    socketaddr: SocketAddr,
+    tls_domain_name: Option<&str>,
+    ca_cert_path: &Path,
+) -> Result<Channel, StratumServerError> {
+    let addr = format!("{}{}", protocol_string(tls_domain_name.is_some()), socketaddr);
+    let mut endpoint = Endpoint::from_str(&addr)?;
+
+    if let Some(domain_name) = tls_domain_name {
+        let pem = tokio::fs::read(ca_cert_path)
+            .await
+            .map_err(|e| StratumServerError::TlsConnectionError(e.to_string()))?;
+        let ca = Certificate::from_pem(pem);
+
+        let tls = ClientTlsConfig::new().ca_certificate(ca).domain_name(domain_name);
+        endpoint = endpoint
+            .tls_config(tls)
+            .map_err(|e| StratumServerError::TlsConnectionError(e.to_string()))?;
+    }
+
+    endpoint
+        .connect()
+        .await
+        .map_err(|e| StratumServerError::TlsConnectionError(e.to_string()))
+}
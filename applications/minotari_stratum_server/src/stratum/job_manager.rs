@@ -0,0 +1,398 @@
+// Copyright 2024. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{
+    collections::{HashMap, HashSet},
+    convert::TryFrom,
+    sync::{Arc, Mutex},
+    time::Instant,
+};
+
+use borsh::BorshSerialize;
+use log::*;
+use minotari_app_grpc::tari_rpc as grpc;
+use minotari_app_utilities::parse_miner_input::BaseNodeGrpcClient;
+use tari_common_types::tari_address::TariAddress;
+use tari_core::{
+    blocks::BlockHeader,
+    consensus::ConsensusManager,
+    proof_of_work::sha3x_difficulty,
+    transactions::{generate_coinbase, key_manager::MemoryDbKeyManager, tari_amount::MicroMinotari},
+};
+use tari_utilities::hex::Hex;
+use tokio::{sync::watch, time, time::MissedTickBehavior};
+
+use crate::{
+    config::StratumServerConfig,
+    error::StratumServerError,
+    stratum::{protocol::JobParams, share_ledger::ShareRejection},
+};
+
+const LOG_TARGET: &str = "minotari::stratum_server::job_manager";
+/// The number of times a job is rebuilt when the template changes while the job is built
+const MAX_JOB_BUILD_ATTEMPTS: usize = 3;
+
+/// A block template with a coinbase paying to a single address, handed out to miners as a stratum job
+#[derive(Debug)]
+pub struct MiningJob {
+    pub job_id: u64,
+    pub height: u64,
+    pub network_difficulty: u64,
+    /// The block reward plus the fees of the template, paid out by the coinbase
+    pub coinbase_amount: MicroMinotari,
+    generation: u64,
+    header: BlockHeader,
+    block: grpc::Block,
+    blob: String,
+    submitted_nonces: Mutex<HashSet<u64>>,
+}
+
+/// A share that passed validation, with the header that was mined
+#[derive(Debug)]
+pub struct ValidShare {
+    pub header: BlockHeader,
+    pub difficulty: u64,
+}
+
+impl MiningJob {
+    /// The job as sent to a miner with the given share target
+    pub fn job_params(&self, share_target: u64) -> JobParams {
+        JobParams {
+            job_id: self.job_id.to_string(),
+            blob: self.blob.clone(),
+            target: share_target.to_string(),
+            height: self.height,
+        }
+    }
+
+    /// Checks that `hash` is the hash of this job's header with `nonce` applied, that it meets `share_target` and that
+    /// the nonce was not submitted before, and returns the achieved difficulty. Only nonces of accepted shares are
+    /// recorded, so a rejected share can not be used to block a later submission of the same nonce.
+    pub fn validate_share(&self, nonce: u64, hash: &str, share_target: u64) -> Result<ValidShare, ShareRejection> {
+        let mut header = self.header.clone();
+        header.nonce = nonce;
+        if !header.hash().to_hex().eq_ignore_ascii_case(hash) {
+            return Err(ShareRejection::Invalid);
+        }
+        let difficulty = sha3x_difficulty(&header).map_err(|_| ShareRejection::Invalid)?;
+        if difficulty.as_u64() < share_target {
+            return Err(ShareRejection::LowDifficulty);
+        }
+        let is_new = self
+            .submitted_nonces
+            .lock()
+            .expect("submitted nonces lock poisoned")
+            .insert(nonce);
+        if !is_new {
+            return Err(ShareRejection::Duplicate);
+        }
+        Ok(ValidShare {
+            header,
+            difficulty: difficulty.as_u64(),
+        })
+    }
+
+    /// The complete block for a share that meets the network difficulty
+    pub fn block_with_header(&self, header: BlockHeader) -> grpc::Block {
+        let mut block = self.block.clone();
+        block.header = Some(header.into());
+        block
+    }
+}
+
+#[derive(Debug)]
+struct TemplateData {
+    generation: u64,
+    tip_hash: Vec<u8>,
+    height: u64,
+    template: grpc::NewBlockTemplate,
+    miner_data: grpc::MinerData,
+    created_at: Instant,
+}
+
+#[derive(Debug, Default)]
+struct JobState {
+    template: Option<Arc<TemplateData>>,
+    jobs: HashMap<u64, Arc<MiningJob>>,
+    /// The job for the current template per payout address
+    current_jobs: HashMap<String, u64>,
+    next_job_id: u64,
+}
+
+/// Tracks the base node tip, keeps a current block template and builds jobs from it.
+#[derive(Clone)]
+pub struct JobManager {
+    base_node_client: BaseNodeGrpcClient,
+    key_manager: MemoryDbKeyManager,
+    consensus_manager: ConsensusManager,
+    config: Arc<StratumServerConfig>,
+    state: Arc<tokio::sync::Mutex<JobState>>,
+    generation_sender: Arc<watch::Sender<u64>>,
+    generation_receiver: watch::Receiver<u64>,
+}
+
+impl JobManager {
+    pub fn new(
+        base_node_client: BaseNodeGrpcClient,
+        key_manager: MemoryDbKeyManager,
+        consensus_manager: ConsensusManager,
+        config: Arc<StratumServerConfig>,
+    ) -> Self {
+        let (generation_sender, generation_receiver) = watch::channel(0);
+        Self {
+            base_node_client,
+            key_manager,
+            consensus_manager,
+            config,
+            state: Arc::new(tokio::sync::Mutex::new(JobState::default())),
+            generation_sender: Arc::new(generation_sender),
+            generation_receiver,
+        }
+    }
+
+    /// Notifies subscribers with the template generation whenever a new template is available. Jobs from the previous
+    /// templates should be replaced with a call to `job_for`.
+    pub fn subscribe(&self) -> watch::Receiver<u64> {
+        self.generation_receiver.clone()
+    }
+
+    /// Polls the base node tip and refreshes the block template when the tip changes or the template is older than
+    /// `template_refresh_interval`.
+    pub async fn run(self) {
+        let mut interval = time::interval(self.config.tip_poll_interval);
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        loop {
+            interval.tick().await;
+            if let Err(e) = self.refresh_if_required().await {
+                warn!(target: LOG_TARGET, "Could not refresh the block template: {}", e);
+            }
+        }
+    }
+
+    /// Returns the job for the current template that pays the block reward to `payout_address`, building it if
+    /// required. Only jobs that are registered for share submission are returned.
+    pub async fn job_for(&self, payout_address: &TariAddress) -> Result<Arc<MiningJob>, StratumServerError> {
+        let key = payout_address.to_hex();
+        for _ in 0..MAX_JOB_BUILD_ATTEMPTS {
+            let (template, job_id) = {
+                let mut state = self.state.lock().await;
+                let template = state.template.clone().ok_or(StratumServerError::NoTemplateAvailable)?;
+                if let Some(job) = state.current_jobs.get(&key).and_then(|job_id| state.jobs.get(job_id)) {
+                    return Ok(job.clone());
+                }
+                let job_id = state.next_job_id;
+                state.next_job_id += 1;
+                (template, job_id)
+            };
+
+            // The state is not locked while the job is built, so that share validation and other sessions are not held
+            // up by the base node
+            let job = Arc::new(self.build_job(job_id, &template, payout_address).await?);
+
+            let mut state = self.state.lock().await;
+            if state.template.as_ref().map(|t| t.generation) != Some(template.generation) {
+                // The template changed while the job was built, shares for the job could not be validated
+                debug!(
+                    target: LOG_TARGET,
+                    "Template changed while building job {} for {}, building it again", job_id, payout_address
+                );
+                continue;
+            }
+            if let Some(existing) = state.current_jobs.get(&key).and_then(|job_id| state.jobs.get(job_id)) {
+                // Another session built a job for the same address in the meantime
+                return Ok(existing.clone());
+            }
+            state.jobs.insert(job_id, job.clone());
+            state.current_jobs.insert(key, job_id);
+            drop(state);
+            debug!(
+                target: LOG_TARGET,
+                "Built job {} at height {} for {}", job_id, job.height, payout_address
+            );
+            return Ok(job);
+        }
+        warn!(
+            target: LOG_TARGET,
+            "The template kept changing while building a job for {}", payout_address
+        );
+        Err(StratumServerError::NoTemplateAvailable)
+    }
+
+    /// Looks up a job that is still valid for share submission
+    pub async fn get_job(&self, job_id: u64) -> Option<Arc<MiningJob>> {
+        self.state.lock().await.jobs.get(&job_id).cloned()
+    }
+
+    pub async fn submit_block(&self, block: grpc::Block) -> Result<Vec<u8>, StratumServerError> {
+        let response = self.base_node_client.clone().submit_block(block).await?.into_inner();
+        Ok(response.block_hash)
+    }
+
+    async fn refresh_if_required(&self) -> Result<(), StratumServerError> {
+        let tip_info = self
+            .base_node_client
+            .clone()
+            .get_tip_info(grpc::Empty {})
+            .await?
+            .into_inner();
+        if !tip_info.initial_sync_achieved {
+            debug!(target: LOG_TARGET, "Base node has not achieved initial sync, not refreshing jobs");
+            return Ok(());
+        }
+        let tip_hash = tip_info
+            .metadata
+            .ok_or(StratumServerError::GrpcResponseMissingField("metadata"))?
+            .best_block_hash;
+
+        let (is_new_tip, is_expired) = match self.state.lock().await.template.as_ref() {
+            Some(template) => (
+                template.tip_hash != tip_hash,
+                template.created_at.elapsed() >= self.config.template_refresh_interval,
+            ),
+            None => (true, true),
+        };
+        if is_new_tip || is_expired {
+            self.refresh_template(tip_hash, is_new_tip).await?;
+        }
+        Ok(())
+    }
+
+    async fn refresh_template(&self, tip_hash: Vec<u8>, is_new_tip: bool) -> Result<(), StratumServerError> {
+        let response = self
+            .base_node_client
+            .clone()
+            .get_new_block_template(grpc::NewBlockTemplateRequest {
+                algo: Some(grpc::PowAlgo {
+                    pow_algo: grpc::pow_algo::PowAlgos::Sha3x.into(),
+                }),
+                max_weight: 0,
+            })
+            .await?
+            .into_inner();
+        let template = response
+            .new_block_template
+            .ok_or(StratumServerError::GrpcResponseMissingField("new_block_template"))?;
+        let miner_data = response
+            .miner_data
+            .ok_or(StratumServerError::GrpcResponseMissingField("miner_data"))?;
+        let height = template
+            .header
+            .as_ref()
+            .ok_or(StratumServerError::GrpcResponseMissingField(
+                "new_block_template.header",
+            ))?
+            .height;
+
+        let mut state = self.state.lock().await;
+        let generation = state.template.as_ref().map_or(1, |t| t.generation + 1);
+        if is_new_tip {
+            // Shares for jobs on the previous tip can never become blocks
+            state.jobs.clear();
+        } else {
+            // Jobs from the previous template at the same tip remain valid until miners have switched over
+            state.jobs.retain(|_, job| job.generation + 1 >= generation);
+        }
+        state.current_jobs.clear();
+        state.template = Some(Arc::new(TemplateData {
+            generation,
+            tip_hash,
+            height,
+            template,
+            miner_data: miner_data.clone(),
+            created_at: Instant::now(),
+        }));
+        drop(state);
+
+        info!(
+            target: LOG_TARGET,
+            "New {} template at height {} with network difficulty {}",
+            if is_new_tip { "tip" } else { "refreshed" },
+            height,
+            miner_data.target_difficulty
+        );
+        let _result = self.generation_sender.send(generation);
+        Ok(())
+    }
+
+    async fn build_job(
+        &self,
+        job_id: u64,
+        template: &TemplateData,
+        payout_address: &TariAddress,
+    ) -> Result<MiningJob, StratumServerError> {
+        let height = template.height;
+        let total_fees = MicroMinotari::from(template.miner_data.total_fees);
+        let reward = MicroMinotari::from(template.miner_data.reward);
+        let (coinbase_output, coinbase_kernel) = generate_coinbase(
+            total_fees,
+            reward,
+            height,
+            self.config.coinbase_extra.as_bytes(),
+            &self.key_manager,
+            payout_address,
+            self.config.stealth_payment,
+            self.consensus_manager.consensus_constants(height),
+            self.config.range_proof_type,
+        )
+        .await?;
+
+        let mut new_template = template.template.clone();
+        let body = new_template
+            .body
+            .as_mut()
+            .ok_or(StratumServerError::GrpcResponseMissingField("new_block_template.body"))?;
+        body.outputs
+            .push(grpc::TransactionOutput::try_from(coinbase_output).map_err(StratumServerError::ConversionError)?);
+        body.kernels.push(coinbase_kernel.into());
+
+        let block = self
+            .base_node_client
+            .clone()
+            .get_new_block(new_template)
+            .await?
+            .into_inner()
+            .block
+            .ok_or(StratumServerError::GrpcResponseMissingField("block"))?;
+        let header = BlockHeader::try_from(
+            block
+                .header
+                .clone()
+                .ok_or(StratumServerError::GrpcResponseMissingField("block.header"))?,
+        )
+        .map_err(StratumServerError::ConversionError)?;
+        let mut header_bytes = Vec::new();
+        BorshSerialize::serialize(&header, &mut header_bytes)
+            .map_err(|e| StratumServerError::ConversionError(e.to_string()))?;
+
+        Ok(MiningJob {
+            job_id,
+            height,
+            network_difficulty: template.miner_data.target_difficulty,
+            coinbase_amount: reward + total_fees,
+            generation: template.generation,
+            header,
+            block,
+            blob: base64::encode(header_bytes),
+            submitted_nonces: Mutex::new(HashSet::new()),
+        })
+    }
+}
@@ -0,0 +1,206 @@
+// Copyright 2024. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! The JSON-RPC line protocol spoken by `minotari_miner` (see `minotari_miner::stratum::stratum_types`).
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+pub const METHOD_LOGIN: &str = "login";
+pub const METHOD_GET_JOB: &str = "getjob";
+pub const METHOD_SUBMIT: &str = "submit";
+pub const METHOD_KEEP_ALIVE: &str = "keepalive";
+pub const METHOD_JOB: &str = "job";
+
+#[derive(Debug, Deserialize)]
+pub struct RpcRequest {
+    #[serde(default)]
+    pub id: Option<Value>,
+    pub method: String,
+    #[serde(default)]
+    pub params: Option<Value>,
+}
+
+impl RpcRequest {
+    /// The request id as echoed back in the response. The stratum client expects it to be a string.
+    pub fn response_id(&self) -> String {
+        match &self.id {
+            Some(Value::String(id)) => id.clone(),
+            Some(Value::Null) | None => "0".to_string(),
+            Some(id) => id.to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct RpcResponse {
+    pub id: String,
+    pub jsonrpc: &'static str,
+    pub result: Option<Value>,
+    pub error: Option<RpcError>,
+}
+
+impl RpcResponse {
+    pub fn success(id: String, result: Value) -> Self {
+        Self {
+            id,
+            jsonrpc: "2.0",
+            result: Some(result),
+            error: None,
+        }
+    }
+
+    /// `minotari_miner` only inspects errors that are nested in the result object, while other clients expect the
+    /// JSON-RPC error member, so errors are reported in both places.
+    pub fn error(id: String, error: RpcError) -> Self {
+        Self {
+            id,
+            jsonrpc: "2.0",
+            result: Some(serde_json::json!({ "status": "REJECTED", "error": error })),
+            error: Some(error),
+        }
+    }
+}
+
+/// A server initiated message, used to push new jobs to connected miners
+#[derive(Debug, Serialize)]
+pub struct RpcNotification {
+    pub jsonrpc: &'static str,
+    pub method: &'static str,
+    pub params: Value,
+}
+
+impl RpcNotification {
+    pub fn job(params: &JobParams) -> Result<Self, serde_json::Error> {
+        Ok(Self {
+            jsonrpc: "2.0",
+            method: METHOD_JOB,
+            params: serde_json::to_value(params)?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct RpcError {
+    pub code: i32,
+    pub message: String,
+}
+
+impl RpcError {
+    // The stratum client logs in again on -1 and 24, and requests a new job on 20 to 25. Errors that would not be
+    // resolved by either, like an invalid login, must use other codes so that the client does not retry in a loop.
+    pub const DUPLICATE_SHARE: i32 = 22;
+    pub const INTERNAL_ERROR: i32 = -32603;
+    pub const INVALID_PARAMS: i32 = -32602;
+    pub const INVALID_SOLUTION: i32 = 25;
+    pub const LOW_DIFFICULTY_SHARE: i32 = 23;
+    pub const METHOD_NOT_FOUND: i32 = -32601;
+    pub const NOT_READY: i32 = -32000;
+    pub const PARSE_ERROR: i32 = -32700;
+    pub const STALE_JOB: i32 = 21;
+    pub const UNAUTHORIZED: i32 = -1;
+
+    pub fn new<T: Into<String>>(code: i32, message: T) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LoginParams {
+    pub login: String,
+    #[serde(default)]
+    pub agent: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct LoginResponse {
+    pub id: String,
+    /// `None` if no block template is available yet, the job is then sent as a notification once it is
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub job: Option<JobParams>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct JobParams {
+    pub job_id: String,
+    pub blob: String,
+    pub target: String,
+    pub height: u64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct WorkerIdentifier {
+    pub id: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SubmitParams {
+    pub id: String,
+    pub job_id: u64,
+    pub nonce: u64,
+    pub hash: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SubmitResponse {
+    pub status: &'static str,
+}
+
+impl SubmitResponse {
+    pub fn ok() -> Self {
+        Self { status: "OK" }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn it_parses_client_requests() {
+        let login = r#"{"id":"0","jsonrpc":"2.0","method":"login","params":{"login":"abcd.rig1","pass":"","agent":"minotari-miner"}}"#;
+        let request = serde_json::from_str::<RpcRequest>(login).unwrap();
+        assert_eq!(request.method, METHOD_LOGIN);
+        assert_eq!(request.response_id(), "0");
+        let params = serde_json::from_value::<LoginParams>(request.params.unwrap()).unwrap();
+        assert_eq!(params.login, "abcd.rig1");
+
+        let submit =
+            r#"{"id":7,"jsonrpc":"2.0","method":"submit","params":{"id":"3","job_id":12,"nonce":99,"hash":"00ff"}}"#;
+        let request = serde_json::from_str::<RpcRequest>(submit).unwrap();
+        assert_eq!(request.response_id(), "7");
+        let params = serde_json::from_value::<SubmitParams>(request.params.unwrap()).unwrap();
+        assert_eq!(params.job_id, 12);
+        assert_eq!(params.nonce, 99);
+    }
+
+    #[test]
+    fn it_nests_errors_in_the_result() {
+        let response = RpcResponse::error("1".to_string(), RpcError::new(RpcError::STALE_JOB, "Stale job"));
+        let value = serde_json::to_value(&response).unwrap();
+        assert_eq!(value["result"]["error"]["code"], RpcError::STALE_JOB);
+        assert_eq!(value["error"]["message"], "Stale job");
+    }
+}
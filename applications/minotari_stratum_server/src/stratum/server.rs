@@ -0,0 +1,154 @@
+// Copyright 2024. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use log::*;
+use tari_common_types::tari_address::TariAddress;
+use tokio::{net::TcpListener, sync::Semaphore, time};
+
+use crate::{
+    config::StratumServerConfig,
+    error::StratumServerError,
+    payout::ShareRecorder,
+    stratum::{job_manager::JobManager, session::Session, share_ledger::ShareLedger},
+};
+
+const LOG_TARGET: &str = "minotari::stratum_server::server";
+const ACCEPT_ERROR_BACKOFF: Duration = Duration::from_millis(100);
+
+/// State shared by all miner sessions
+#[derive(Clone)]
+pub struct StratumContext {
+    pub config: Arc<StratumServerConfig>,
+    pub job_manager: JobManager,
+    pub ledger: ShareLedger,
+    /// Records shares for reward payouts in pool mode, `None` in solo mode
+    pub share_recorder: Option<ShareRecorder>,
+    /// The address all block rewards are paid to in pool mode, `None` in solo mode
+    pool_address: Option<TariAddress>,
+    next_worker_id: Arc<AtomicU64>,
+}
+
+impl StratumContext {
+    /// The address the coinbase of a worker's jobs pays to
+    pub fn payout_address(&self, miner_address: &TariAddress) -> TariAddress {
+        self.pool_address.clone().unwrap_or_else(|| miner_address.clone())
+    }
+
+    pub fn next_worker_id(&self) -> String {
+        self.next_worker_id.fetch_add(1, Ordering::Relaxed).to_string()
+    }
+}
+
+pub struct StratumServer {
+    context: StratumContext,
+    connections: Arc<Semaphore>,
+}
+
+impl StratumServer {
+    pub fn new(
+        config: Arc<StratumServerConfig>,
+        job_manager: JobManager,
+        ledger: ShareLedger,
+        share_recorder: Option<ShareRecorder>,
+        pool_address: Option<TariAddress>,
+    ) -> Self {
+        let connections = Arc::new(Semaphore::new(config.max_connections));
+        Self {
+            context: StratumContext {
+                config,
+                job_manager,
+                ledger,
+                share_recorder,
+                pool_address,
+                next_worker_id: Arc::new(AtomicU64::new(1)),
+            },
+            connections,
+        }
+    }
+
+    pub async fn run(self, listener: TcpListener) -> Result<(), StratumServerError> {
+        tokio::spawn(self.context.job_manager.clone().run());
+        if !self.context.config.stats_log_interval.is_zero() {
+            tokio::spawn(log_worker_stats(self.context.clone()));
+        }
+
+        loop {
+            let (stream, peer) = match listener.accept().await {
+                Ok(connection) => connection,
+                Err(e) => {
+                    // Accept errors such as running out of file descriptors are transient, the listener remains usable
+                    warn!(target: LOG_TARGET, "Could not accept a connection: {}", e);
+                    time::sleep(ACCEPT_ERROR_BACKOFF).await;
+                    continue;
+                },
+            };
+            let permit = match self.connections.clone().try_acquire_owned() {
+                Ok(permit) => permit,
+                Err(_) => {
+                    warn!(
+                        target: LOG_TARGET,
+                        "Rejecting connection from {}, the maximum of {} connections is reached",
+                        peer,
+                        self.context.config.max_connections
+                    );
+                    continue;
+                },
+            };
+            let session = Session::new(self.context.clone(), peer);
+            tokio::spawn(async move {
+                session.run(stream).await;
+                drop(permit);
+            });
+        }
+    }
+}
+
+async fn log_worker_stats(context: StratumContext) {
+    let mut interval = time::interval(context.config.stats_log_interval);
+    loop {
+        interval.tick().await;
+        for (worker, stats) in context.ledger.worker_stats() {
+            info!(
+                target: LOG_TARGET,
+                "Worker {}: {} accepted shares ({} difficulty), {} rejected ({} stale, {} duplicate, {} low \
+                 difficulty, {} invalid), {} blocks found",
+                worker,
+                stats.accepted_shares,
+                stats.accepted_difficulty,
+                stats.rejected_shares(),
+                stats.stale_shares,
+                stats.duplicate_shares,
+                stats.low_difficulty_shares,
+                stats.invalid_shares,
+                stats.blocks_found,
+            );
+        }
+    }
+}
@@ -0,0 +1,407 @@
+// Copyright 2024. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{net::SocketAddr, str::FromStr, time::Instant};
+
+use futures::StreamExt;
+use log::*;
+use serde::Serialize;
+use serde_json::Value;
+use tari_common_types::{tari_address::TariAddress, types::PublicKey};
+use tari_utilities::hex::Hex;
+use tokio::{
+    io::AsyncWriteExt,
+    net::{tcp::OwnedWriteHalf, TcpStream},
+    time,
+};
+use tokio_util::codec::{FramedRead, LinesCodec};
+
+use crate::{
+    error::StratumServerError,
+    stratum::{
+        protocol::{
+            JobParams,
+            LoginParams,
+            LoginResponse,
+            RpcError,
+            RpcNotification,
+            RpcRequest,
+            RpcResponse,
+            SubmitParams,
+            SubmitResponse,
+            WorkerIdentifier,
+            METHOD_GET_JOB,
+            METHOD_KEEP_ALIVE,
+            METHOD_LOGIN,
+            METHOD_SUBMIT,
+        },
+        server::StratumContext,
+        share_ledger::ShareRejection,
+        vardiff::{Vardiff, VardiffConfig},
+    },
+};
+
+const LOG_TARGET: &str = "minotari::stratum_server::session";
+/// The longest request line accepted from a miner, well above the size of any valid stratum request
+const MAX_LINE_LENGTH: usize = 16 * 1024;
+
+struct Worker {
+    id: String,
+    login: String,
+    /// The address the worker logged in with, credited with its shares in pool mode
+    miner_address: TariAddress,
+    /// The address the coinbase of the worker's jobs pays to
+    payout_address: TariAddress,
+    vardiff: Vardiff,
+    /// The share target of the last job sent to the worker
+    current_target: u64,
+    /// The share target of the job before that, still accepted while the miner switches over
+    previous_target: u64,
+}
+
+impl Worker {
+    fn set_target(&mut self, target: u64) {
+        self.previous_target = self.current_target;
+        self.current_target = target;
+    }
+
+    fn accepted_target(&self) -> u64 {
+        self.current_target.min(self.previous_target)
+    }
+}
+
+/// A single miner connection
+pub struct Session {
+    context: StratumContext,
+    peer: SocketAddr,
+    worker: Option<Worker>,
+    /// Set when the worker should be sent a new job after the current response, e.g. after a vardiff retarget
+    job_push_required: bool,
+}
+
+impl Session {
+    pub fn new(context: StratumContext, peer: SocketAddr) -> Self {
+        Self {
+            context,
+            peer,
+            worker: None,
+            job_push_required: false,
+        }
+    }
+
+    pub async fn run(mut self, stream: TcpStream) {
+        debug!(target: LOG_TARGET, "Miner connected from {}", self.peer);
+        let (reader, mut writer) = stream.into_split();
+        let mut lines = FramedRead::new(reader, LinesCodec::new_with_max_length(MAX_LINE_LENGTH));
+        let mut new_jobs = self.context.job_manager.subscribe();
+        let mut retarget = time::interval(self.context.config.vardiff_retarget_interval);
+        let mut last_activity = time::Instant::now();
+
+        loop {
+            tokio::select! {
+                line = lines.next() => match line {
+                    Some(Ok(line)) => {
+                        last_activity = time::Instant::now();
+                        let response = self.handle_message(&line).await;
+                        if let Err(e) = send(&mut writer, &response).await {
+                            debug!(target: LOG_TARGET, "Could not send response to {}: {}", self.peer, e);
+                            break;
+                        }
+                    },
+                    None => break,
+                    Some(Err(e)) => {
+                        debug!(target: LOG_TARGET, "Could not read from {}: {}", self.peer, e);
+                        break;
+                    },
+                },
+                changed = new_jobs.changed() => {
+                    if changed.is_err() {
+                        break;
+                    }
+                    self.job_push_required = true;
+                },
+                _ = retarget.tick() => {
+                    if let Some(worker) = self.worker.as_mut() {
+                        if worker.vardiff.retarget_if_due(Instant::now()).is_some() {
+                            self.job_push_required = true;
+                        }
+                    }
+                },
+                _ = time::sleep_until(last_activity + self.context.config.connection_timeout) => {
+                    info!(target: LOG_TARGET, "Disconnecting idle miner {}", self.peer);
+                    break;
+                },
+            }
+
+            if self.job_push_required {
+                self.job_push_required = false;
+                if let Err(e) = self.push_job(&mut writer).await {
+                    debug!(target: LOG_TARGET, "Could not send job to {}: {}", self.peer, e);
+                    break;
+                }
+            }
+        }
+
+        if let Some(worker) = self.worker {
+            info!(target: LOG_TARGET, "Worker {} ({}) disconnected", worker.login, self.peer);
+        }
+    }
+
+    async fn handle_message(&mut self, line: &str) -> RpcResponse {
+        let request = match serde_json::from_str::<RpcRequest>(line) {
+            Ok(request) => request,
+            Err(e) => {
+                return RpcResponse::error(
+                    "0".to_string(),
+                    RpcError::new(RpcError::PARSE_ERROR, format!("Invalid request: {}", e)),
+                )
+            },
+        };
+        let id = request.response_id();
+        let params = request.params.unwrap_or(Value::Null);
+        let result = match request.method.as_str() {
+            METHOD_LOGIN => self.handle_login(params).await,
+            METHOD_GET_JOB => self.handle_get_job(params).await,
+            METHOD_SUBMIT => self.handle_submit(params).await,
+            METHOD_KEEP_ALIVE => to_result(&serde_json::json!({ "status": "KEEPALIVED" })),
+            method => Err(RpcError::new(
+                RpcError::METHOD_NOT_FOUND,
+                format!("Unknown method '{}'", method),
+            )),
+        };
+        match result {
+            Ok(result) => RpcResponse::success(id, result),
+            Err(error) => RpcResponse::error(id, error),
+        }
+    }
+
+    async fn handle_login(&mut self, params: Value) -> Result<Value, RpcError> {
+        let params = parse_params::<LoginParams>(params)?;
+        let (address, worker_name) = params.login.split_once('.').unwrap_or((params.login.as_str(), ""));
+        let miner_address = parse_miner_address(address, &self.context)?;
+        let now = Instant::now();
+        let vardiff = Vardiff::new(
+            VardiffConfig::from(self.context.config.as_ref()),
+            self.context.config.initial_share_difficulty,
+            now,
+        );
+        let worker = Worker {
+            id: self.context.next_worker_id(),
+            login: params.login.clone(),
+            payout_address: self.context.payout_address(&miner_address),
+            miner_address: miner_address.clone(),
+            current_target: vardiff.difficulty(),
+            previous_target: vardiff.difficulty(),
+            vardiff,
+        };
+        info!(
+            target: LOG_TARGET,
+            "Worker '{}' ({}) logged in from {} using '{}'",
+            if worker_name.is_empty() { "default" } else { worker_name },
+            miner_address,
+            self.peer,
+            params.agent
+        );
+        let id = worker.id.clone();
+        self.worker = Some(worker);
+        // The worker is registered even when no job is available yet, it is sent one as soon as a template is ready
+        let job = match self.next_job().await {
+            Ok(job) => Some(job),
+            Err(e) if e.code == RpcError::NOT_READY => {
+                debug!(target: LOG_TARGET, "No job for {} yet: {}", self.peer, e.message);
+                None
+            },
+            Err(e) => return Err(e),
+        };
+        to_result(&LoginResponse { id, job })
+    }
+
+    async fn handle_get_job(&mut self, params: Value) -> Result<Value, RpcError> {
+        let params = parse_params::<WorkerIdentifier>(params)?;
+        self.authorized_worker(&params.id)?;
+        let job = self.next_job().await?;
+        to_result(&job)
+    }
+
+    async fn handle_submit(&mut self, params: Value) -> Result<Value, RpcError> {
+        let params = parse_params::<SubmitParams>(params)?;
+        let login = self.authorized_worker(&params.id)?.login.clone();
+        match self.validate_and_submit(&params).await {
+            Ok(()) => to_result(&SubmitResponse::ok()),
+            Err(reason) => {
+                debug!(
+                    target: LOG_TARGET,
+                    "{} share from {} for job {}", reason, login, params.job_id
+                );
+                self.context.ledger.record_rejected(&login, reason);
+                Err(rejection_error(reason))
+            },
+        }
+    }
+
+    async fn validate_and_submit(&mut self, params: &SubmitParams) -> Result<(), ShareRejection> {
+        let job = self
+            .context
+            .job_manager
+            .get_job(params.job_id)
+            .await
+            .ok_or(ShareRejection::Stale)?;
+        let worker = self.worker.as_mut().ok_or(ShareRejection::Invalid)?;
+        let share_target = worker.accepted_target().min(job.network_difficulty);
+        let share = job.validate_share(params.nonce, &params.hash, share_target)?;
+
+        let is_block = share.difficulty >= job.network_difficulty;
+        let mut accepted_block = None;
+        if is_block {
+            let hash = share.header.hash();
+            match self
+                .context
+                .job_manager
+                .submit_block(job.block_with_header(share.header))
+                .await
+            {
+                Ok(_) => {
+                    info!(
+                        target: LOG_TARGET,
+                        "Block {} at height {} found by {}",
+                        hash.to_hex(),
+                        job.height,
+                        worker.login
+                    );
+                    accepted_block = Some(hash);
+                },
+                Err(e) => warn!(
+                    target: LOG_TARGET,
+                    "Block {} at height {} found by {} was not accepted by the base node: {}",
+                    hash.to_hex(),
+                    job.height,
+                    worker.login,
+                    e
+                ),
+            }
+        }
+        self.context
+            .ledger
+            .record_accepted(&worker.login, share_target, is_block);
+        if let Some(recorder) = self.context.share_recorder.as_ref() {
+            if let Err(e) =
+                recorder.record_share(&worker.login, &worker.miner_address, share_target, &job, accepted_block)
+            {
+                error!(target: LOG_TARGET, "Could not record share from {}: {}", worker.login, e);
+            }
+        }
+        if worker.vardiff.record_share(Instant::now()).is_some() {
+            self.job_push_required = true;
+        }
+        Ok(())
+    }
+
+    /// Builds the current job for the logged in worker at its current share difficulty
+    async fn next_job(&mut self) -> Result<JobParams, RpcError> {
+        let worker = self
+            .worker
+            .as_mut()
+            .ok_or_else(|| RpcError::new(RpcError::UNAUTHORIZED, "Unauthorized"))?;
+        let job = self
+            .context
+            .job_manager
+            .job_for(&worker.payout_address)
+            .await
+            .map_err(|e| match e {
+                StratumServerError::NoTemplateAvailable => RpcError::new(RpcError::NOT_READY, e.to_string()),
+                e => {
+                    warn!(target: LOG_TARGET, "Could not build a job: {}", e);
+                    RpcError::new(RpcError::INTERNAL_ERROR, "Could not build a job")
+                },
+            })?;
+        // The share target is never higher than the network difficulty, so that every block found is also submitted
+        let target = worker.vardiff.difficulty().min(job.network_difficulty);
+        worker.set_target(target);
+        Ok(job.job_params(target))
+    }
+
+    async fn push_job(&mut self, writer: &mut OwnedWriteHalf) -> Result<(), StratumServerError> {
+        if self.worker.is_none() {
+            return Ok(());
+        }
+        match self.next_job().await {
+            Ok(job) => send(writer, &RpcNotification::job(&job)?).await,
+            Err(e) => {
+                debug!(target: LOG_TARGET, "No job for {}: {}", self.peer, e.message);
+                Ok(())
+            },
+        }
+    }
+
+    fn authorized_worker(&self, id: &str) -> Result<&Worker, RpcError> {
+        self.worker
+            .as_ref()
+            .filter(|worker| worker.id == id)
+            .ok_or_else(|| RpcError::new(RpcError::UNAUTHORIZED, "Unauthorized"))
+    }
+}
+
+async fn send<T: Serialize>(writer: &mut OwnedWriteHalf, message: &T) -> Result<(), StratumServerError> {
+    let mut line = serde_json::to_string(message)?;
+    line.push('\n');
+    writer.write_all(line.as_bytes()).await?;
+    Ok(())
+}
+
+fn parse_params<T: serde::de::DeserializeOwned>(params: Value) -> Result<T, RpcError> {
+    serde_json::from_value(params)
+        .map_err(|e| RpcError::new(RpcError::INVALID_PARAMS, format!("Invalid params: {}", e)))
+}
+
+fn to_result<T: Serialize>(result: &T) -> Result<Value, RpcError> {
+    serde_json::to_value(result).map_err(|e| RpcError::new(RpcError::INTERNAL_ERROR, e.to_string()))
+}
+
+/// Miners log in with either a Tari address or, like `minotari_miner`, a wallet public key in hex
+fn parse_miner_address(address: &str, context: &StratumContext) -> Result<TariAddress, RpcError> {
+    let network = context.config.network;
+    let miner_address = TariAddress::from_str(address)
+        .ok()
+        .or_else(|| {
+            PublicKey::from_hex(address)
+                .ok()
+                .map(|public_key| TariAddress::new(public_key, network))
+        })
+        .ok_or_else(|| RpcError::new(RpcError::INVALID_PARAMS, "Login is not a valid wallet address"))?;
+    if miner_address.network() != network {
+        return Err(RpcError::new(
+            RpcError::INVALID_PARAMS,
+            format!("Wallet address is not for network '{}'", network),
+        ));
+    }
+    Ok(miner_address)
+}
+
+fn rejection_error(reason: ShareRejection) -> RpcError {
+    let code = match reason {
+        ShareRejection::Stale => RpcError::STALE_JOB,
+        ShareRejection::Duplicate => RpcError::DUPLICATE_SHARE,
+        ShareRejection::LowDifficulty => RpcError::LOW_DIFFICULTY_SHARE,
+        ShareRejection::Invalid => RpcError::INVALID_SOLUTION,
+    };
+    RpcError::new(code, format!("{} share", reason))
+}
@@ -0,0 +1,148 @@
+// Copyright 2024. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{
+    collections::HashMap,
+    fmt,
+    sync::{Arc, Mutex},
+};
+
+/// Why a submitted share was not credited to the worker
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShareRejection {
+    /// The job was built on a tip that is no longer current, or is unknown
+    Stale,
+    /// The same nonce was already submitted for the job
+    Duplicate,
+    /// The share does not meet the worker's share difficulty
+    LowDifficulty,
+    /// The submitted hash does not match the header, or the submission is malformed
+    Invalid,
+}
+
+impl fmt::Display for ShareRejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ShareRejection::Stale => write!(f, "Stale"),
+            ShareRejection::Duplicate => write!(f, "Duplicate"),
+            ShareRejection::LowDifficulty => write!(f, "Low difficulty"),
+            ShareRejection::Invalid => write!(f, "Invalid"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WorkerStats {
+    pub accepted_shares: u64,
+    /// The sum of the share difficulties of all accepted shares, i.e. the work credited to the worker
+    pub accepted_difficulty: u128,
+    pub stale_shares: u64,
+    pub duplicate_shares: u64,
+    pub low_difficulty_shares: u64,
+    pub invalid_shares: u64,
+    pub blocks_found: u64,
+}
+
+impl WorkerStats {
+    pub fn rejected_shares(&self) -> u64 {
+        self.stale_shares + self.duplicate_shares + self.low_difficulty_shares + self.invalid_shares
+    }
+}
+
+/// Per-worker share accounting, keyed by the worker login (`<wallet address>.<worker name>`)
+#[derive(Debug, Clone, Default)]
+pub struct ShareLedger {
+    workers: Arc<Mutex<HashMap<String, WorkerStats>>>,
+}
+
+impl ShareLedger {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record_accepted(&self, worker: &str, share_difficulty: u64, is_block: bool) {
+        self.update(worker, |stats| {
+            stats.accepted_shares += 1;
+            stats.accepted_difficulty += u128::from(share_difficulty);
+            if is_block {
+                stats.blocks_found += 1;
+            }
+        });
+    }
+
+    pub fn record_rejected(&self, worker: &str, reason: ShareRejection) {
+        self.update(worker, |stats| match reason {
+            ShareRejection::Stale => stats.stale_shares += 1,
+            ShareRejection::Duplicate => stats.duplicate_shares += 1,
+            ShareRejection::LowDifficulty => stats.low_difficulty_shares += 1,
+            ShareRejection::Invalid => stats.invalid_shares += 1,
+        });
+    }
+
+    /// Returns a snapshot of all worker statistics, ordered by worker
+    pub fn worker_stats(&self) -> Vec<(String, WorkerStats)> {
+        let workers = self.workers.lock().expect("share ledger lock poisoned");
+        let mut stats = workers
+            .iter()
+            .map(|(worker, stats)| (worker.clone(), stats.clone()))
+            .collect::<Vec<_>>();
+        stats.sort_by(|a, b| a.0.cmp(&b.0));
+        stats
+    }
+
+    fn update<F: FnOnce(&mut WorkerStats)>(&self, worker: &str, f: F) {
+        let mut workers = self.workers.lock().expect("share ledger lock poisoned");
+        f(workers.entry(worker.to_string()).or_default());
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn it_accounts_shares_per_worker() {
+        let ledger = ShareLedger::new();
+        ledger.record_accepted("alice.rig1", 1_000, false);
+        ledger.record_accepted("alice.rig1", 2_000, true);
+        ledger.record_rejected("alice.rig1", ShareRejection::Stale);
+        ledger.record_accepted("bob", 500, false);
+        ledger.record_rejected("bob", ShareRejection::Duplicate);
+        ledger.record_rejected("bob", ShareRejection::LowDifficulty);
+
+        let stats = ledger.worker_stats();
+        assert_eq!(stats.len(), 2);
+        let (worker, alice) = &stats[0];
+        assert_eq!(worker, "alice.rig1");
+        assert_eq!(alice.accepted_shares, 2);
+        assert_eq!(alice.accepted_difficulty, 3_000);
+        assert_eq!(alice.blocks_found, 1);
+        assert_eq!(alice.rejected_shares(), 1);
+
+        let (worker, bob) = &stats[1];
+        assert_eq!(worker, "bob");
+        assert_eq!(bob.accepted_difficulty, 500);
+        assert_eq!(bob.duplicate_shares, 1);
+        assert_eq!(bob.low_difficulty_shares, 1);
+        assert_eq!(bob.rejected_shares(), 2);
+    }
+}
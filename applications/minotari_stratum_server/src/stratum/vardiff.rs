@@ -0,0 +1,160 @@
+// Copyright 2024. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::time::{Duration, Instant};
+
+use crate::config::StratumServerConfig;
+
+#[derive(Debug, Clone, Copy)]
+pub struct VardiffConfig {
+    pub target_share_time: Duration,
+    pub retarget_interval: Duration,
+    pub variance_percent: u64,
+    pub min_difficulty: u64,
+    pub max_difficulty: u64,
+}
+
+impl From<&StratumServerConfig> for VardiffConfig {
+    fn from(config: &StratumServerConfig) -> Self {
+        Self {
+            target_share_time: config.vardiff_target_share_time,
+            retarget_interval: config.vardiff_retarget_interval,
+            variance_percent: config.vardiff_variance_percent,
+            min_difficulty: config.min_share_difficulty,
+            max_difficulty: config.max_share_difficulty,
+        }
+    }
+}
+
+/// Per-worker variable share difficulty. The difficulty is scaled so that the worker submits a share roughly every
+/// `target_share_time`, re-evaluated once per `retarget_interval`.
+#[derive(Debug, Clone)]
+pub struct Vardiff {
+    config: VardiffConfig,
+    difficulty: u64,
+    window_start: Instant,
+    shares_in_window: u64,
+}
+
+impl Vardiff {
+    pub fn new(config: VardiffConfig, initial_difficulty: u64, now: Instant) -> Self {
+        Self {
+            difficulty: initial_difficulty
+                .clamp(config.min_difficulty, config.max_difficulty.max(config.min_difficulty)),
+            config,
+            window_start: now,
+            shares_in_window: 0,
+        }
+    }
+
+    pub fn difficulty(&self) -> u64 {
+        self.difficulty
+    }
+
+    /// Records an accepted share. Returns the new difficulty if the worker was retargeted.
+    pub fn record_share(&mut self, now: Instant) -> Option<u64> {
+        self.shares_in_window += 1;
+        self.retarget_if_due(now)
+    }
+
+    /// Returns the new difficulty if the retarget interval has elapsed and the share rate was outside the allowed
+    /// variance.
+    pub fn retarget_if_due(&mut self, now: Instant) -> Option<u64> {
+        let elapsed = now.saturating_duration_since(self.window_start);
+        if elapsed < self.config.retarget_interval {
+            return None;
+        }
+        let shares = self.shares_in_window;
+        self.window_start = now;
+        self.shares_in_window = 0;
+
+        let target_ms = self.config.target_share_time.as_millis().max(1);
+        let new_difficulty = if shares == 0 {
+            // No shares at all in the window, the worker is far too slow for this difficulty
+            self.difficulty / 2
+        } else {
+            let average_ms = elapsed.as_millis() / u128::from(shares);
+            let variance = target_ms * u128::from(self.config.variance_percent) / 100;
+            if average_ms >= target_ms.saturating_sub(variance) && average_ms <= target_ms + variance {
+                return None;
+            }
+            let scaled = u128::from(self.difficulty) * target_ms / average_ms.max(1);
+            u64::try_from(scaled).unwrap_or(u64::MAX)
+        };
+        let new_difficulty = new_difficulty.clamp(
+            self.config.min_difficulty,
+            self.config.max_difficulty.max(self.config.min_difficulty),
+        );
+        if new_difficulty == self.difficulty {
+            return None;
+        }
+        self.difficulty = new_difficulty;
+        Some(new_difficulty)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn config() -> VardiffConfig {
+        VardiffConfig {
+            target_share_time: Duration::from_secs(10),
+            retarget_interval: Duration::from_secs(60),
+            variance_percent: 30,
+            min_difficulty: 100,
+            max_difficulty: 1_000_000,
+        }
+    }
+
+    #[test]
+    fn it_raises_difficulty_for_fast_workers() {
+        let start = Instant::now();
+        let mut vardiff = Vardiff::new(config(), 1_000, start);
+        for i in 1..60 {
+            assert_eq!(vardiff.record_share(start + Duration::from_secs(i)), None);
+        }
+        // 60 shares in 60 seconds is one share per second, 10 times faster than the target
+        assert_eq!(vardiff.record_share(start + Duration::from_secs(60)), Some(10_000));
+        assert_eq!(vardiff.difficulty(), 10_000);
+    }
+
+    #[test]
+    fn it_keeps_difficulty_within_variance() {
+        let start = Instant::now();
+        let mut vardiff = Vardiff::new(config(), 1_000, start);
+        for i in 1..=5 {
+            assert_eq!(vardiff.record_share(start + Duration::from_secs(i * 12)), None);
+        }
+        assert_eq!(vardiff.difficulty(), 1_000);
+    }
+
+    #[test]
+    fn it_lowers_difficulty_for_idle_workers_down_to_the_minimum() {
+        let start = Instant::now();
+        let mut vardiff = Vardiff::new(config(), 300, start);
+        assert_eq!(vardiff.retarget_if_due(start + Duration::from_secs(30)), None);
+        assert_eq!(vardiff.retarget_if_due(start + Duration::from_secs(60)), Some(150));
+        assert_eq!(vardiff.retarget_if_due(start + Duration::from_secs(120)), Some(100));
+        assert_eq!(vardiff.retarget_if_due(start + Duration::from_secs(180)), None);
+    }
+}
@@ -29,6 +29,7 @@ pub mod epoch;
 pub mod grpc_authentication;
 pub mod serializers;
 pub mod tari_address;
+pub mod tari_subaddress;
 pub mod transaction;
 mod tx_id;
 pub mod types;
@@ -0,0 +1,244 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{
+    convert::TryFrom,
+    fmt::{Display, Error, Formatter},
+    str::FromStr,
+};
+
+use serde::{Deserialize, Serialize};
+use tari_common::configuration::Network;
+use tari_crypto::tari_utilities::ByteArray;
+use tari_utilities::hex::{from_hex, Hex};
+
+use crate::{
+    dammsum::{compute_checksum, validate_checksum},
+    emoji::{EMOJI, REVERSE_EMOJI},
+    tari_address::{TariAddress, TariAddressError},
+    types::PublicKey,
+};
+
+const INTERNAL_SIZE: usize = 65; // number of bytes used for the internal representation
+
+/// A subaddress that can receive one-sided stealth payments on behalf of a wallet. It consists of the subaddress spend
+/// key `D` and view key `C = a⋅D`, where `a` is the private key of the wallet's primary address. A sender uses the
+/// nonce `R = r⋅D` and the shared secret `r⋅C`, which lets the wallet recover `D` from any stealth output with a single
+/// Diffie-Hellman operation, regardless of how many subaddresses it has handed out.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TariSubaddress {
+    network: Network,
+    view_public_key: PublicKey,
+    spend_public_key: PublicKey,
+}
+
+impl TariSubaddress {
+    pub fn new(view_public_key: PublicKey, spend_public_key: PublicKey, network: Network) -> Self {
+        Self {
+            network,
+            view_public_key,
+            spend_public_key,
+        }
+    }
+
+    /// Gets the network from the subaddress
+    pub fn network(&self) -> Network {
+        self.network
+    }
+
+    /// The public key used by senders to compute the stealth shared secret
+    pub fn view_public_key(&self) -> &PublicKey {
+        &self.view_public_key
+    }
+
+    /// The public key that stealth outputs sent to this subaddress are spendable from
+    pub fn spend_public_key(&self) -> &PublicKey {
+        &self.spend_public_key
+    }
+
+    /// The plain Tari address of the subaddress spend key, used to record the counterparty of a transaction
+    pub fn to_tari_address(&self) -> TariAddress {
+        TariAddress::new(self.spend_public_key.clone(), self.network)
+    }
+
+    /// Construct a subaddress from bytes with network
+    pub fn from_bytes_with_network(bytes: &[u8], network: Network) -> Result<Self, TariAddressError> {
+        if bytes.len() != INTERNAL_SIZE {
+            return Err(TariAddressError::InvalidSize);
+        }
+        let mut fixed_data = bytes.to_vec();
+        fixed_data[INTERNAL_SIZE - 1] ^= network.as_byte();
+        if validate_checksum(&fixed_data).is_err() {
+            return Err(TariAddressError::InvalidNetworkOrChecksum);
+        }
+        Self::from_keys(bytes, network)
+    }
+
+    /// Construct a subaddress from bytes and try to calculate the network
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, TariAddressError> {
+        if bytes.len() != INTERNAL_SIZE {
+            return Err(TariAddressError::InvalidSize);
+        }
+        let checksum = compute_checksum(&bytes[0..INTERNAL_SIZE - 1].to_vec());
+        let network = Network::try_from(checksum ^ bytes[INTERNAL_SIZE - 1])
+            .map_err(|_| TariAddressError::InvalidNetworkOrChecksum)?;
+        Self::from_keys(bytes, network)
+    }
+
+    fn from_keys(bytes: &[u8], network: Network) -> Result<Self, TariAddressError> {
+        let view_public_key =
+            PublicKey::from_canonical_bytes(&bytes[0..32]).map_err(|_| TariAddressError::CannotRecoverPublicKey)?;
+        let spend_public_key =
+            PublicKey::from_canonical_bytes(&bytes[32..64]).map_err(|_| TariAddressError::CannotRecoverPublicKey)?;
+        Ok(Self {
+            network,
+            view_public_key,
+            spend_public_key,
+        })
+    }
+
+    /// Convert the subaddress to bytes
+    pub fn to_bytes(&self) -> [u8; INTERNAL_SIZE] {
+        let mut buf = [0u8; INTERNAL_SIZE];
+        buf[0..32].copy_from_slice(self.view_public_key.as_bytes());
+        buf[32..64].copy_from_slice(self.spend_public_key.as_bytes());
+        let checksum = compute_checksum(&buf[0..INTERNAL_SIZE - 1].to_vec());
+        buf[INTERNAL_SIZE - 1] = self.network.as_byte() ^ checksum;
+        buf
+    }
+
+    /// Construct a subaddress from an emoji string with checksum, trying to calculate the network
+    pub fn from_emoji_string(emoji: &str) -> Result<Self, TariAddressError> {
+        if emoji.chars().count() != INTERNAL_SIZE {
+            return Err(TariAddressError::InvalidSize);
+        }
+        let bytes = emoji
+            .chars()
+            .map(|c| REVERSE_EMOJI.get(&c).copied().ok_or(TariAddressError::InvalidEmoji))
+            .collect::<Result<Vec<u8>, _>>()?;
+        Self::from_bytes(&bytes)
+    }
+
+    /// Convert the subaddress to an emoji string with checksum
+    pub fn to_emoji_string(&self) -> String {
+        self.to_bytes().iter().map(|b| EMOJI[*b as usize]).collect::<String>()
+    }
+
+    /// Construct a subaddress from hex and try to calculate the network
+    pub fn from_hex(hex_str: &str) -> Result<Self, TariAddressError> {
+        let buf = from_hex(hex_str).map_err(|_| TariAddressError::CannotRecoverPublicKey)?;
+        Self::from_bytes(buf.as_slice())
+    }
+
+    /// Convert the subaddress to hex
+    pub fn to_hex(&self) -> String {
+        self.to_bytes().to_hex()
+    }
+}
+
+impl FromStr for TariSubaddress {
+    type Err = TariAddressError;
+
+    fn from_str(key: &str) -> Result<Self, Self::Err> {
+        if let Ok(address) = TariSubaddress::from_emoji_string(&key.trim().replace('|', "")) {
+            Ok(address)
+        } else if let Ok(address) = TariSubaddress::from_hex(key) {
+            Ok(address)
+        } else {
+            Err(TariAddressError::CannotRecoverPublicKey)
+        }
+    }
+}
+
+impl Display for TariSubaddress {
+    fn fmt(&self, fmt: &mut Formatter<'_>) -> Result<(), Error> {
+        fmt.write_str(&self.to_emoji_string())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use tari_crypto::keys::{PublicKey, SecretKey};
+
+    use super::*;
+    use crate::types::PrivateKey;
+
+    fn random_subaddress(network: Network) -> TariSubaddress {
+        let mut rng = rand::thread_rng();
+        TariSubaddress::new(
+            PublicKey::from_secret_key(&PrivateKey::random(&mut rng)),
+            PublicKey::from_secret_key(&PrivateKey::random(&mut rng)),
+            network,
+        )
+    }
+
+    #[test]
+    /// Test encoding for tari subaddress
+    fn encoding() {
+        let address = random_subaddress(Network::Esmeralda);
+
+        assert_eq!(TariSubaddress::from_bytes(&address.to_bytes()), Ok(address.clone()));
+        assert_eq!(
+            TariSubaddress::from_bytes_with_network(&address.to_bytes(), Network::Esmeralda),
+            Ok(address.clone())
+        );
+        assert_eq!(TariSubaddress::from_hex(&address.to_hex()), Ok(address.clone()));
+        assert_eq!(
+            TariSubaddress::from_emoji_string(&address.to_emoji_string()),
+            Ok(address.clone())
+        );
+        assert_eq!(address.to_string().parse::<TariSubaddress>(), Ok(address.clone()));
+        assert_eq!(address.to_tari_address().public_key(), address.spend_public_key());
+    }
+
+    #[test]
+    /// Test that subaddresses and addresses are not interchangeable
+    fn invalid_size() {
+        let address = random_subaddress(Network::Esmeralda);
+        let tari_address = address.to_tari_address();
+        assert_eq!(
+            TariSubaddress::from_bytes(&tari_address.to_bytes()),
+            Err(TariAddressError::InvalidSize)
+        );
+        assert_eq!(
+            TariAddress::from_bytes(&address.to_bytes()),
+            Err(TariAddressError::InvalidSize)
+        );
+    }
+
+    #[test]
+    /// Test invalid network
+    fn invalid_network() {
+        let address = random_subaddress(Network::Esmeralda);
+        assert_eq!(
+            TariSubaddress::from_bytes_with_network(&address.to_bytes(), Network::Igor),
+            Err(TariAddressError::InvalidNetworkOrChecksum)
+        );
+
+        let mut address_bytes = address.to_bytes();
+        address_bytes[INTERNAL_SIZE - 1] ^= 0xFF;
+        assert_eq!(
+            TariSubaddress::from_bytes_with_network(&address_bytes, Network::Esmeralda),
+            Err(TariAddressError::InvalidNetworkOrChecksum)
+        );
+    }
+}
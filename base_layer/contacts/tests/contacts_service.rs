@@ -77,6 +77,7 @@ pub fn setup_contacts_service<T: ContactsBackend + 'static>(
             ..Default::default()
         },
         auxiliary_tcp_listener_address: None,
+        quic_listener_address: None,
         datastore_path: tempdir().unwrap().into_path(),
         peer_database_name: random::string(8),
         max_concurrent_inbound_tasks: 10,
@@ -96,6 +97,20 @@ pub fn setup_contacts_service<T: ContactsBackend + 'static>(
         user_agent: "tari/test-contacts-service".to_string(),
         rpc_max_simultaneous_sessions: 0,
         rpc_max_sessions_per_peer: 0,
+        rpc_max_requests_per_minute_per_peer: None,
+        rpc_max_concurrent_requests_per_peer: None,
+        rpc_max_streamed_bytes_per_minute_per_peer: None,
+        rpc_max_concurrent_streams: None,
+        reputation_ban_threshold: -100.0,
+        reputation_decay_half_life: Duration::from_secs(30 * 60),
+        reputation_ban_duration: Duration::from_secs(10 * 60),
+        max_upload_rate: None,
+        max_download_rate: None,
+        max_peer_upload_rate: None,
+        max_peer_download_rate: None,
+        enable_port_mapping: false,
+        nat_gateway_address: None,
+        advertise_observed_address: false,
         listener_liveness_check_interval: None,
     };
     let peer_message_subscription_factory = Arc::new(subscription_factory);
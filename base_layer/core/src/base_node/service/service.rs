@@ -29,7 +29,10 @@ use futures::{pin_mut, stream::StreamExt, Stream};
 use log::*;
 use rand::rngs::OsRng;
 use tari_common_types::types::BlockHash;
-use tari_comms::{connectivity::ConnectivityRequester, peer_manager::NodeId};
+use tari_comms::{
+    connectivity::{ConnectivityRequester, ReputationEvent},
+    peer_manager::NodeId,
+};
 use tari_comms_dht::{
     domain_message::OutboundDomainMessage,
     envelope::NodeDestination,
@@ -60,6 +63,7 @@ use crate::{
     common::{
         waiting_requests::{generate_request_key, RequestKey, WaitingRequests},
         BanPeriod,
+        BanReason,
     },
     proto as shared_protos,
     proto::base_node as proto,
@@ -261,7 +265,6 @@ where B: BlockchainBackend + 'static
         let outbound_message_service = self.outbound_message_service.clone();
         let state_machine_handle = self.state_machine_handle.clone();
         let mut connectivity = self.connectivity.clone();
-        let short_ban = self.base_node_config.blockchain_sync_config.short_ban_period;
         let long_ban = self.base_node_config.blockchain_sync_config.ban_period;
         task::spawn(async move {
             let result = handle_incoming_request(
@@ -273,14 +276,13 @@ where B: BlockchainBackend + 'static
             .await;
             if let Err(e) = result {
                 if let Some(ban_reason) = e.get_ban_reason() {
-                    let duration = match ban_reason.ban_duration {
-                        BanPeriod::Short => short_ban,
-                        BanPeriod::Long => long_ban,
-                    };
-                    let _drop = connectivity
-                        .ban_peer_until(domain_msg.source_peer.node_id.clone(), duration, ban_reason.reason)
-                        .await
-                        .map_err(|e| error!(target: LOG_TARGET, "Failed to ban peer: {:?}", e));
+                    penalise_peer(
+                        &mut connectivity,
+                        domain_msg.source_peer.node_id.clone(),
+                        ban_reason,
+                        long_ban,
+                    )
+                    .await;
                 }
                 error!(target: LOG_TARGET, "Failed to handle incoming request message: {:?}", e);
             }
@@ -294,7 +296,6 @@ where B: BlockchainBackend + 'static
         let waiting_requests = self.waiting_requests.clone();
         let mut connectivity_requester = self.connectivity.clone();
 
-        let short_ban = self.base_node_config.blockchain_sync_config.short_ban_period;
         let long_ban = self.base_node_config.blockchain_sync_config.ban_period;
         task::spawn(async move {
             let source_peer = domain_msg.source_peer.clone();
@@ -302,14 +303,7 @@ where B: BlockchainBackend + 'static
 
             if let Err(e) = result {
                 if let Some(ban_reason) = e.get_ban_reason() {
-                    let duration = match ban_reason.ban_duration {
-                        BanPeriod::Short => short_ban,
-                        BanPeriod::Long => long_ban,
-                    };
-                    let _drop = connectivity_requester
-                        .ban_peer_until(source_peer.node_id, duration, ban_reason.reason)
-                        .await
-                        .map_err(|e| error!(target: LOG_TARGET, "Failed to ban peer: {:?}", e));
+                    penalise_peer(&mut connectivity_requester, source_peer.node_id, ban_reason, long_ban).await;
                 }
                 error!(
                     target: LOG_TARGET,
@@ -345,13 +339,17 @@ where B: BlockchainBackend + 'static
         let inbound_nch = self.inbound_nch.clone();
         let mut connectivity_requester = self.connectivity.clone();
         let source_peer = new_block.source_peer.clone();
-        let short_ban = self.base_node_config.blockchain_sync_config.short_ban_period;
         let long_ban = self.base_node_config.blockchain_sync_config.ban_period;
         task::spawn(async move {
             let result = handle_incoming_block(inbound_nch, new_block).await;
 
             match result {
-                Ok(()) => {},
+                Ok(()) => {
+                    let _drop = connectivity_requester
+                        .report_peer(source_peer.node_id, ReputationEvent::ValidBlock)
+                        .await
+                        .map_err(|e| error!(target: LOG_TARGET, "Failed to report peer: {:?}", e));
+                },
                 Err(BaseNodeServiceError::CommsInterfaceError(CommsInterfaceError::ChainStorageError(
                     ChainStorageError::AddBlockOperationLocked,
                 ))) => {
@@ -359,14 +357,7 @@ where B: BlockchainBackend + 'static
                 },
                 Err(e) => {
                     if let Some(ban_reason) = e.get_ban_reason() {
-                        let duration = match ban_reason.ban_duration {
-                            BanPeriod::Short => short_ban,
-                            BanPeriod::Long => long_ban,
-                        };
-                        let _drop = connectivity_requester
-                            .ban_peer_until(source_peer.node_id, duration, ban_reason.reason)
-                            .await
-                            .map_err(|e| error!(target: LOG_TARGET, "Failed to ban peer: {:?}", e));
+                        penalise_peer(&mut connectivity_requester, source_peer.node_id, ban_reason, long_ban).await;
                     }
                     error!(target: LOG_TARGET, "Failed to handle incoming block message: {}", e)
                 },
@@ -692,6 +683,31 @@ async fn handle_request_timeout(
     Ok(())
 }
 
+/// Penalises a peer for an offence. Offences that are likely not malicious are reported to the peer reputation
+/// service, so that the peer is only banned if it repeatedly misbehaves. Other offences result in an immediate ban.
+async fn penalise_peer(
+    connectivity: &mut ConnectivityRequester,
+    node_id: NodeId,
+    ban_reason: BanReason,
+    long_ban: Duration,
+) {
+    let result = match ban_reason.ban_duration {
+        BanPeriod::Short => {
+            debug!(
+                target: LOG_TARGET,
+                "Reporting peer {} because: {}", node_id, ban_reason.reason
+            );
+            connectivity
+                .report_peer(node_id, BanPeriod::SHORT_BAN_REPUTATION_EVENT)
+                .await
+        },
+        BanPeriod::Long => connectivity.ban_peer_until(node_id, long_ban, ban_reason.reason).await,
+    };
+    if let Err(e) = result {
+        error!(target: LOG_TARGET, "Failed to penalise peer: {:?}", e);
+    }
+}
+
 fn spawn_request_timeout(timeout_sender: Sender<RequestKey>, request_key: RequestKey, timeout: Duration) {
     task::spawn(async move {
         tokio::time::sleep(timeout).await;
@@ -20,16 +20,22 @@
 //  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 //  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use std::time::Duration;
-
 use log::*;
-use tari_comms::{connectivity::ConnectivityRequester, peer_manager::NodeId};
+use tari_comms::{
+    connectivity::{ConnectivityRequester, ReputationEvent},
+    peer_manager::NodeId,
+};
 
-use crate::base_node::BlockchainSyncConfig;
+use crate::{
+    base_node::BlockchainSyncConfig,
+    common::{BanPeriod, BanReason},
+};
 
 const LOG_TARGET: &str = "c::bn::sync";
 
-// Sync peers are banned if there exists a ban reason for the error and the peer is not on the allow list for sync.
+// Sync peers are penalised if there exists a ban reason for the error and the peer is not on the allow list for sync.
+// Infractions that are likely not malicious (slow responses, timeouts etc.) are reported to the peer reputation
+// service, which only bans the peer if it repeatedly misbehaves. Other infractions result in an immediate ban.
 
 pub struct PeerBanManager {
     config: BlockchainSyncConfig,
@@ -41,25 +47,46 @@ impl PeerBanManager {
         Self { config, connectivity }
     }
 
-    pub async fn ban_peer_if_required(&mut self, node_id: &NodeId, ban_reason: String, ban_duration: Duration) {
+    pub async fn ban_peer_if_required(&mut self, node_id: &NodeId, ban_reason: BanReason) {
         if self.config.forced_sync_peers.contains(node_id) {
             debug!(
                 target: LOG_TARGET,
-                "Not banning peer that is on the allow list for sync. Ban reason = {}", ban_reason
+                "Not banning peer that is on the allow list for sync. Ban reason = {}", ban_reason.reason
             );
             return;
         }
-        debug!(target: LOG_TARGET, "Sync peer {} removed from the sync peer list because {}", node_id, ban_reason);
+        debug!(
+            target: LOG_TARGET,
+            "Sync peer {} removed from the sync peer list because {}", node_id, ban_reason.reason
+        );
 
-        match self
-            .connectivity
-            .ban_peer_until(node_id.clone(), ban_duration, ban_reason.clone())
-            .await
-        {
-            Ok(_) => {
-                warn!(target: LOG_TARGET, "Banned sync peer {} for {:?} because {}", node_id, ban_duration, ban_reason)
+        match ban_reason.ban_duration {
+            BanPeriod::Short => {
+                self.report_peer(node_id, BanPeriod::SHORT_BAN_REPUTATION_EVENT).await;
+            },
+            BanPeriod::Long => {
+                let ban_duration = self.config.ban_period;
+                match self
+                    .connectivity
+                    .ban_peer_until(node_id.clone(), ban_duration, ban_reason.reason.clone())
+                    .await
+                {
+                    Ok(_) => {
+                        warn!(
+                            target: LOG_TARGET,
+                            "Banned sync peer {} for {:?} because {}", node_id, ban_duration, ban_reason.reason
+                        )
+                    },
+                    Err(err) => error!(target: LOG_TARGET, "Failed to ban sync peer {}: {}", node_id, err),
+                }
             },
-            Err(err) => error!(target: LOG_TARGET, "Failed to ban sync peer {}: {}", node_id, err),
+        }
+    }
+
+    /// Reports a reputation event for a sync peer.
+    pub async fn report_peer(&mut self, node_id: &NodeId, event: ReputationEvent) {
+        if let Err(err) = self.connectivity.report_peer(node_id.clone(), event).await {
+            error!(target: LOG_TARGET, "Failed to report sync peer {}: {}", node_id, err);
         }
     }
 }
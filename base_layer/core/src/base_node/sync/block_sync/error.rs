@@ -116,6 +116,9 @@ impl BlockSyncError {
             BlockSyncError::PeerNotFound |
             BlockSyncError::SyncRoundFailed => None,
             BlockSyncError::ChainStorageError(e) => e.get_ban_reason(),
+            // The peer asked us to back off, which is not an offence. Another sync peer is tried instead.
+            BlockSyncError::RpcError(e) if e.is_back_off() => None,
+            BlockSyncError::RpcRequestError(status) if status.is_back_off() => None,
             // short ban
             err @ BlockSyncError::MaxLatencyExceeded { .. } |
             err @ BlockSyncError::PeerDidNotSupplyAllClaimedBlocks(_) |
@@ -28,7 +28,12 @@ use std::{
 
 use futures::StreamExt;
 use log::*;
-use tari_comms::{connectivity::ConnectivityRequester, peer_manager::NodeId, protocol::rpc::RpcClient, PeerConnection};
+use tari_comms::{
+    connectivity::{ConnectivityRequester, ReputationEvent},
+    peer_manager::NodeId,
+    protocol::rpc::RpcClient,
+    PeerConnection,
+};
 use tari_utilities::hex::Hex;
 use tokio::task;
 
@@ -40,7 +45,7 @@ use crate::{
     },
     blocks::{Block, ChainBlock},
     chain_storage::{async_db::AsyncBlockchainDb, BlockchainBackend},
-    common::{rolling_avg::RollingAverageTime, BanPeriod},
+    common::rolling_avg::RollingAverageTime,
     proto::base_node::SyncBlocksRequest,
     transactions::aggregated_body::AggregateBody,
     validation::{BlockBodyValidator, ValidationError},
@@ -184,19 +189,18 @@ impl<'a, B: BlockchainBackend + 'static> BlockSynchronizer<'a, B> {
                 "Attempting to synchronize blocks with `{}` latency: {:.2?}", node_id, latency
             );
             match self.synchronize_blocks(sync_peer, client, max_latency).await {
-                Ok(_) => return Ok(()),
+                Ok(_) => {
+                    self.peer_ban_manager
+                        .report_peer(&node_id, ReputationEvent::UsefulResponse)
+                        .await;
+                    return Ok(());
+                },
                 Err(err) => {
                     warn!(target: LOG_TARGET, "{}", err);
                     let ban_reason = BlockSyncError::get_ban_reason(&err);
                     if let Some(reason) = ban_reason {
-                        let duration = match reason.ban_duration {
-                            BanPeriod::Short => self.config.short_ban_period,
-                            BanPeriod::Long => self.config.ban_period,
-                        };
                         warn!(target: LOG_TARGET, "{}", err);
-                        self.peer_ban_manager
-                            .ban_peer_if_required(&node_id, reason.reason, duration)
-                            .await;
+                        self.peer_ban_manager.ban_peer_if_required(&node_id, reason).await;
                     }
                     if let BlockSyncError::MaxLatencyExceeded { .. } = err {
                         latency_counter += 1;
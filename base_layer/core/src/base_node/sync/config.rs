@@ -37,12 +37,14 @@ pub struct BlockchainSyncConfig {
     /// If all sync peers exceed latency, increase allowed latency by this value
     #[serde(with = "serializers::seconds")]
     pub max_latency_increase: Duration,
-    /// Longer ban period for potentially malicious infractions (protocol violations etc.)
+    /// Ban period for potentially malicious infractions (protocol violations etc.). Infractions that are likely not
+    /// malicious (slow to respond, spotty connections etc) are penalised through the peer's reputation instead.
     #[serde(with = "serializers::seconds")]
     pub ban_period: Duration,
-    /// Short ban period for infractions that are likely not malicious (slow to respond, spotty connections etc)
-    #[serde(with = "serializers::seconds")]
-    pub short_ban_period: Duration,
+    /// Deprecated and ignored, infractions that are likely not malicious are penalised through the peer's reputation.
+    /// Kept so that existing config files that set it remain valid.
+    #[serde(default, with = "serializers::optional_seconds", skip_serializing)]
+    pub short_ban_period: Option<Duration>,
     /// An allowlist of sync peers from which to sync. No other peers will be selected for sync. If empty, sync peers
     /// are chosen based on their advertised chain metadata.
     pub forced_sync_peers: Vec<NodeId>,
@@ -59,7 +61,7 @@ impl Default for BlockchainSyncConfig {
             initial_max_sync_latency: Duration::from_secs(15),
             max_latency_increase: Duration::from_secs(2),
             ban_period: Duration::from_secs(60 * 60 * 2), // 2 hours
-            short_ban_period: Duration::from_secs(240),   // 4 mins
+            short_ban_period: None,
             forced_sync_peers: Default::default(),
             validation_concurrency: 6,
             rpc_deadline: Duration::from_secs(15),
@@ -111,6 +111,9 @@ impl BlockHeaderSyncError {
             BlockHeaderSyncError::NotInSync |
             BlockHeaderSyncError::PeerNotFound => None,
             BlockHeaderSyncError::ChainStorageError(e) => e.get_ban_reason(),
+            // The peer asked us to back off, which is not an offence. Another sync peer is tried instead.
+            BlockHeaderSyncError::RpcError(e) if e.is_back_off() => None,
+            BlockHeaderSyncError::RpcRequestError(status) if status.is_back_off() => None,
 
             // short ban
             err @ BlockHeaderSyncError::MaxLatencyExceeded { .. } |
@@ -30,7 +30,7 @@ use log::*;
 use primitive_types::U256;
 use tari_common_types::{chain_metadata::ChainMetadata, types::HashOutput};
 use tari_comms::{
-    connectivity::ConnectivityRequester,
+    connectivity::{ConnectivityRequester, ReputationEvent},
     peer_manager::NodeId,
     protocol::rpc::{RpcClient, RpcError},
     PeerConnection,
@@ -49,7 +49,7 @@ use crate::{
     },
     blocks::{BlockHeader, ChainBlock, ChainHeader},
     chain_storage::{async_db::AsyncBlockchainDb, BlockchainBackend, ChainStorageError},
-    common::{rolling_avg::RollingAverageTime, BanPeriod},
+    common::rolling_avg::RollingAverageTime,
     consensus::ConsensusManager,
     proof_of_work::randomx_factory::RandomXFactory,
     proto::{
@@ -154,18 +154,17 @@ impl<'a, B: BlockchainBackend + 'static> HeaderSynchronizer<'a, B> {
         let mut latency_counter = 0usize;
         for node_id in sync_peer_node_ids {
             match self.connect_and_attempt_sync(&node_id, max_latency).await {
-                Ok((peer, sync_result)) => return Ok((peer, sync_result)),
+                Ok((peer, sync_result)) => {
+                    self.peer_ban_manager
+                        .report_peer(&node_id, ReputationEvent::UsefulResponse)
+                        .await;
+                    return Ok((peer, sync_result));
+                },
                 Err(err) => {
                     let ban_reason = BlockHeaderSyncError::get_ban_reason(&err);
                     if let Some(reason) = ban_reason {
                         warn!(target: LOG_TARGET, "{}", err);
-                        let duration = match reason.ban_duration {
-                            BanPeriod::Short => self.config.short_ban_period,
-                            BanPeriod::Long => self.config.ban_period,
-                        };
-                        self.peer_ban_manager
-                            .ban_peer_if_required(&node_id, reason.reason, duration)
-                            .await;
+                        self.peer_ban_manager.ban_peer_if_required(&node_id, reason).await;
                     }
                     if let BlockHeaderSyncError::MaxLatencyExceeded { .. } = err {
                         latency_counter += 1;
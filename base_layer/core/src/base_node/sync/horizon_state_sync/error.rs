@@ -132,6 +132,9 @@ impl HorizonSyncError {
             HorizonSyncError::NoMoreSyncPeers(_) |
             HorizonSyncError::PeerNotFound |
             HorizonSyncError::JoinError(_) => None,
+            // The peer asked us to back off, which is not an offence. Another sync peer is tried instead.
+            HorizonSyncError::RpcError(e) if e.is_back_off() => None,
+            HorizonSyncError::RpcStatus(status) if status.is_back_off() => None,
 
             // short ban
             err @ HorizonSyncError::MaxLatencyExceeded { .. } |
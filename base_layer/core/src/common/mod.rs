@@ -22,6 +22,7 @@
 
 use blake2::Blake2b;
 use digest::consts::U64;
+use tari_comms::connectivity::ReputationEvent;
 use tari_hash_domains::ConfidentialOutputHashDomain;
 
 use crate::consensus::DomainSeparatedConsensusHasher;
@@ -51,10 +52,18 @@ pub struct BanReason {
 
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub enum BanPeriod {
+    /// An offence that is likely not malicious (slow to respond, spotty connections etc). The peer is not banned
+    /// immediately, instead the offence is reported to the peer reputation service as `SHORT_BAN_REPUTATION_EVENT`.
     Short,
+    /// A potentially malicious offence (protocol violations etc.), the peer is banned immediately
     Long,
 }
 
+impl BanPeriod {
+    /// The reputation event reported for offences with a short ban period
+    pub const SHORT_BAN_REPUTATION_EVENT: ReputationEvent = ReputationEvent::MinorOffence;
+}
+
 impl BanReason {
     /// Create a new ban reason
     pub fn reason(&self) -> &str {
@@ -20,8 +20,10 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
+use std::time::Duration;
+
 use serde::{Deserialize, Serialize};
-use tari_common::SubConfigPath;
+use tari_common::{configuration::serializers, SubConfigPath};
 
 use crate::mempool::{reorg_pool::ReorgPoolConfig, unconfirmed_pool::UnconfirmedPoolConfig};
 
@@ -52,6 +54,18 @@ pub struct MempoolServiceConfig {
     pub initial_sync_max_transactions: usize,
     /// The maximum number of blocks added via sync or re-org to triggering a sync
     pub block_sync_trigger: usize,
+    /// Periodically reconcile the set of unconfirmed transactions with connected base nodes instead of flooding every
+    /// new transaction to all peers. Default: true
+    pub enable_reconciliation: bool,
+    /// The time between reconciliation rounds. Each round reconciles with every outbound base node peer.
+    /// Default: 2 seconds
+    #[serde(with = "serializers::seconds")]
+    pub reconciliation_interval: Duration,
+    /// The number of outbound base node peers that new transactions are still flooded to when reconciliation is
+    /// enabled. Peers that do not support reconciliation always receive flooded transactions. Default: 2
+    pub num_flood_peers: usize,
+    /// Dandelion++ transaction relay settings
+    pub dandelion: DandelionConfig,
 }
 
 impl Default for MempoolServiceConfig {
@@ -60,6 +74,44 @@ impl Default for MempoolServiceConfig {
             initial_sync_num_peers: 2,
             initial_sync_max_transactions: 10_000,
             block_sync_trigger: 5,
+            enable_reconciliation: true,
+            reconciliation_interval: Duration::from_secs(2),
+            num_flood_peers: 2,
+            dandelion: DandelionConfig::default(),
+        }
+    }
+}
+
+/// Configuration for Dandelion++ transaction relay. New transactions are first relayed along a random path of single
+/// peers (the stem phase) before being flooded (the fluff phase), which hides the node that created the transaction.
+#[derive(Clone, Deserialize, Serialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct DandelionConfig {
+    /// Relay transactions using Dandelion++. Default: false
+    pub enabled: bool,
+    /// The time after which new stem peers are selected and this node decides again whether to stem or fluff relayed
+    /// transactions. Default: 10 minutes
+    #[serde(with = "serializers::seconds")]
+    pub epoch_duration: Duration,
+    /// The number of outbound base node peers that stem transactions are relayed to in each epoch. Default: 2
+    pub num_stem_peers: usize,
+    /// The probability that this node fluffs all transactions it receives in the stem phase for an epoch. Transactions
+    /// created by this node are always stemmed. Default: 0.1
+    pub fluff_probability: f64,
+    /// The minimum time to wait for a stemmed transaction to be fluffed by another node before this node fluffs it. A
+    /// random delay of up to the same duration is added to each transaction. Default: 30 seconds
+    #[serde(with = "serializers::seconds")]
+    pub embargo_timeout: Duration,
+}
+
+impl Default for DandelionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            epoch_duration: Duration::from_secs(10 * 60),
+            num_stem_peers: 2,
+            fluff_probability: 0.1,
+            embargo_timeout: Duration::from_secs(30),
         }
     }
 }
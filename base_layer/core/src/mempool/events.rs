@@ -0,0 +1,78 @@
+// Copyright 2024. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{
+    fmt::{Display, Error, Formatter},
+    sync::Arc,
+};
+
+use tari_common_types::types::Signature;
+use tokio::sync::broadcast;
+
+use crate::transactions::transaction_components::Transaction;
+
+pub type MempoolEventSender = broadcast::Sender<Arc<MempoolEvent>>;
+pub type MempoolEventReceiver = broadcast::Receiver<Arc<MempoolEvent>>;
+
+/// The reason a transaction left the unconfirmed pool
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MempoolRemovalReason {
+    /// The transaction was included in a block added to the chain
+    Mined,
+    /// An input of the transaction was spent by another transaction in a block
+    DoubleSpend,
+    /// The transaction was removed to make space for higher priority transactions
+    Evicted,
+    /// The transaction failed revalidation after a chain state change
+    Invalid,
+}
+
+impl Display for MempoolRemovalReason {
+    fn fmt(&self, fmt: &mut Formatter<'_>) -> Result<(), Error> {
+        let reason = match self {
+            MempoolRemovalReason::Mined => "Mined",
+            MempoolRemovalReason::DoubleSpend => "Double spend",
+            MempoolRemovalReason::Evicted => "Evicted",
+            MempoolRemovalReason::Invalid => "Invalid",
+        };
+        fmt.write_str(reason)
+    }
+}
+
+/// Events that are published when the contents of the unconfirmed pool change
+#[derive(Debug, Clone)]
+pub enum MempoolEvent {
+    TransactionAdded(Arc<Transaction>),
+    TransactionRemoved {
+        excess_sig: Signature,
+        reason: MempoolRemovalReason,
+    },
+}
+
+impl MempoolEvent {
+    pub(crate) fn removed(tx: &Transaction, reason: MempoolRemovalReason) -> Self {
+        MempoolEvent::TransactionRemoved {
+            excess_sig: tx.first_kernel_excess_sig().cloned().unwrap_or_default(),
+            reason,
+        }
+    }
+}
@@ -23,13 +23,14 @@
 use std::sync::{Arc, RwLock};
 
 use tari_common_types::types::{PrivateKey, Signature};
-use tokio::task;
+use tokio::{sync::broadcast, task};
 
 use crate::{
     blocks::Block,
     consensus::ConsensusManager,
     mempool::{
         error::MempoolError,
+        events::{MempoolEventReceiver, MempoolEventSender},
         mempool_storage::MempoolStorage,
         FeePerGramStat,
         MempoolConfig,
@@ -41,22 +42,41 @@ use crate::{
     validation::TransactionValidator,
 };
 
+const MEMPOOL_EVENT_CHANNEL_SIZE: usize = 1000;
+
 /// The Mempool consists of an Unconfirmed Transaction Pool, Pending Pool, Orphan Pool and Reorg Pool and is responsible
 /// for managing and maintaining all unconfirmed transactions that have not yet been included in a block, and
 /// transactions that have recently been included in a block.
 #[derive(Clone)]
 pub struct Mempool {
     pool_storage: Arc<RwLock<MempoolStorage>>,
+    event_sender: MempoolEventSender,
 }
 
 impl Mempool {
     /// Create a new Mempool with an UnconfirmedPool and ReOrgPool.
     pub fn new(config: MempoolConfig, rules: ConsensusManager, validator: Box<dyn TransactionValidator>) -> Self {
+        let (event_sender, _) = broadcast::channel(MEMPOOL_EVENT_CHANNEL_SIZE);
         Self {
-            pool_storage: Arc::new(RwLock::new(MempoolStorage::new(config, rules, validator))),
+            pool_storage: Arc::new(RwLock::new(MempoolStorage::new(
+                config,
+                rules,
+                validator,
+                event_sender.clone(),
+            ))),
+            event_sender,
         }
     }
 
+    /// Subscribe to events describing changes to the unconfirmed pool
+    pub fn get_event_stream(&self) -> MempoolEventReceiver {
+        self.event_sender.subscribe()
+    }
+
+    pub(crate) fn event_sender(&self) -> MempoolEventSender {
+        self.event_sender.clone()
+    }
+
     /// Insert an unconfirmed transaction into the Mempool.
     pub async fn insert(&self, tx: Arc<Transaction>) -> Result<TxStorageResponse, MempoolError> {
         self.with_write_access(|storage| {
@@ -67,6 +87,12 @@ impl Mempool {
         .await
     }
 
+    /// Checks whether a transaction would be accepted into the unconfirmed pool without inserting it.
+    pub async fn check_transaction(&self, tx: Arc<Transaction>) -> Result<TxStorageResponse, MempoolError> {
+        self.with_read_access(move |storage| Ok(storage.check_transaction(&tx)))
+            .await
+    }
+
     /// Inserts all transactions into the mempool.
     pub async fn insert_all(&self, transactions: Vec<Arc<Transaction>>) -> Result<(), MempoolError> {
         self.with_write_access(|storage| {
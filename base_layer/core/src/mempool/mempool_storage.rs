@@ -20,10 +20,14 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use std::{sync::Arc, time::Instant};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+    time::Instant,
+};
 
 use log::*;
-use tari_common_types::types::{PrivateKey, Signature};
+use tari_common_types::types::{HashOutput, PrivateKey, Signature};
 use tari_utilities::hex::Hex;
 
 use crate::{
@@ -31,6 +35,7 @@ use crate::{
     consensus::ConsensusManager,
     mempool::{
         error::MempoolError,
+        events::{MempoolEvent, MempoolEventSender, MempoolRemovalReason},
         reorg_pool::ReorgPool,
         unconfirmed_pool::{UnconfirmedPool, UnconfirmedPoolError},
         FeePerGramStat,
@@ -57,22 +62,49 @@ pub struct MempoolStorage {
     validator: Box<dyn TransactionValidator>,
     rules: ConsensusManager,
     last_seen_height: u64,
+    event_sender: MempoolEventSender,
 }
 
 impl MempoolStorage {
     /// Create a new Mempool with an UnconfirmedPool and ReOrgPool.
-    pub fn new(config: MempoolConfig, rules: ConsensusManager, validator: Box<dyn TransactionValidator>) -> Self {
+    pub fn new(
+        config: MempoolConfig,
+        rules: ConsensusManager,
+        validator: Box<dyn TransactionValidator>,
+        event_sender: MempoolEventSender,
+    ) -> Self {
         Self {
             unconfirmed_pool: UnconfirmedPool::new(config.unconfirmed_pool),
             reorg_pool: ReorgPool::new(config.reorg_pool),
             validator,
             rules,
             last_seen_height: 0,
+            event_sender,
         }
     }
 
     /// Insert an unconfirmed transaction into the Mempool.
     pub fn insert(&mut self, tx: Arc<Transaction>) -> Result<TxStorageResponse, UnconfirmedPoolError> {
+        if !self.has_subscribers() {
+            return self.insert_unpublished(tx).map(|(response, _)| response);
+        }
+        let was_stored = self.is_in_unconfirmed_pool(&tx);
+        let (response, evicted) = self.insert_unpublished(tx.clone())?;
+        if let Some(evicted) = evicted {
+            self.publish_event(MempoolEvent::removed(&evicted, MempoolRemovalReason::Evicted));
+        }
+        if !was_stored && self.is_in_unconfirmed_pool(&tx) {
+            self.publish_event(MempoolEvent::TransactionAdded(tx));
+        }
+        Ok(response)
+    }
+
+    /// Insert an unconfirmed transaction into the Mempool without publishing any events, returning the storage
+    /// response and any transaction that was evicted to make space for it.
+    fn insert_unpublished(
+        &mut self,
+        tx: Arc<Transaction>,
+    ) -> Result<(TxStorageResponse, Option<Arc<Transaction>>), UnconfirmedPoolError> {
         let tx_id = tx
             .body
             .kernels()
@@ -81,70 +113,96 @@ impl MempoolStorage {
             .unwrap_or_else(|| "None?!".into());
         let timer = Instant::now();
         debug!(target: LOG_TARGET, "Inserting tx into mempool: {}", tx_id);
+        let dependent_outputs = match self.validate_unpublished(&tx, &tx_id) {
+            Ok(dependent_outputs) => dependent_outputs,
+            Err(response) => return Ok((response, None)),
+        };
+        debug!(
+            target: LOG_TARGET,
+            "Transaction {} is VALID ({:.2?}), inserting in unconfirmed pool in",
+            tx_id,
+            timer.elapsed()
+        );
+        let timer = Instant::now();
+        let weight = self.get_transaction_weighting();
+        let evicted = self.unconfirmed_pool.insert(tx, dependent_outputs, &weight)?;
+        debug!(
+            target: LOG_TARGET,
+            "Transaction {} inserted in {:.2?}",
+            tx_id,
+            timer.elapsed()
+        );
+        Ok((TxStorageResponse::UnconfirmedPool, evicted))
+    }
+
+    /// Checks whether a transaction would be accepted into the unconfirmed pool, without inserting it. Returns
+    /// `TxStorageResponse::UnconfirmedPool` if the transaction is valid, otherwise the reason that it would not be
+    /// stored.
+    pub fn check_transaction(&self, tx: &Transaction) -> TxStorageResponse {
+        let tx_id = tx
+            .body
+            .kernels()
+            .first()
+            .map(|k| k.excess_sig.get_signature().to_hex())
+            .unwrap_or_else(|| "None?!".into());
+        match self.validate_unpublished(tx, &tx_id) {
+            Ok(_) => TxStorageResponse::UnconfirmedPool,
+            Err(response) => response,
+        }
+    }
+
+    /// Validates an unconfirmed transaction, returning the outputs in the unconfirmed pool that it depends on, or the
+    /// reason that it cannot be stored.
+    fn validate_unpublished(
+        &self,
+        tx: &Transaction,
+        tx_id: &str,
+    ) -> Result<Option<Vec<HashOutput>>, TxStorageResponse> {
         let tx_fee = match tx.body.get_total_fee() {
             Ok(fee) => fee,
             Err(e) => {
                 warn!(target: LOG_TARGET, "Invalid transaction: {}", e);
-                return Ok(TxStorageResponse::NotStoredConsensus);
+                return Err(TxStorageResponse::NotStoredConsensus);
             },
         };
         // This check is almost free, so lets check this before we do any expensive validation.
         if tx_fee.as_u64() < self.unconfirmed_pool.config.min_fee {
             debug!(target: LOG_TARGET, "Tx: ({}) fee too low, rejecting",tx_id);
-            return Ok(TxStorageResponse::NotStoredFeeTooLow);
+            return Err(TxStorageResponse::NotStoredFeeTooLow);
         }
-        match self.validator.validate(&tx) {
-            Ok(()) => {
-                debug!(
-                    target: LOG_TARGET,
-                    "Transaction {} is VALID ({:.2?}), inserting in unconfirmed pool in",
-                    tx_id,
-                    timer.elapsed()
-                );
-                let timer = Instant::now();
-                let weight = self.get_transaction_weighting();
-                self.unconfirmed_pool.insert(tx, None, &weight)?;
-                debug!(
-                    target: LOG_TARGET,
-                    "Transaction {} inserted in {:.2?}",
-                    tx_id,
-                    timer.elapsed()
-                );
-                Ok(TxStorageResponse::UnconfirmedPool)
-            },
+        match self.validator.validate(tx) {
+            Ok(()) => Ok(None),
             Err(ValidationError::UnknownInputs(dependent_outputs)) => {
                 if self.unconfirmed_pool.contains_all_outputs(&dependent_outputs) {
-                    let weight = self.get_transaction_weighting();
-                    self.unconfirmed_pool.insert(tx, Some(dependent_outputs), &weight)?;
-                    Ok(TxStorageResponse::UnconfirmedPool)
+                    Ok(Some(dependent_outputs))
                 } else {
                     warn!(target: LOG_TARGET, "Validation failed due to unknown inputs");
-                    Ok(TxStorageResponse::NotStoredOrphan)
+                    Err(TxStorageResponse::NotStoredOrphan)
                 }
             },
             Err(ValidationError::ContainsSTxO) => {
                 warn!(target: LOG_TARGET, "Validation failed due to already spent input");
-                Ok(TxStorageResponse::NotStoredAlreadySpent)
+                Err(TxStorageResponse::NotStoredAlreadySpent)
             },
             Err(ValidationError::MaturityError) => {
                 warn!(target: LOG_TARGET, "Validation failed due to maturity error");
-                Ok(TxStorageResponse::NotStoredTimeLocked)
+                Err(TxStorageResponse::NotStoredTimeLocked)
             },
             Err(ValidationError::ConsensusError(msg)) => {
                 warn!(target: LOG_TARGET, "Validation failed due to consensus rule: {}", msg);
-                Ok(TxStorageResponse::NotStoredConsensus)
+                Err(TxStorageResponse::NotStoredConsensus)
             },
             Err(ValidationError::DuplicateKernelError(msg)) => {
                 debug!(
                     target: LOG_TARGET,
                     "Validation failed due to already mined kernel: {}", msg
                 );
-                Ok(TxStorageResponse::NotStoredAlreadyMined)
+                Err(TxStorageResponse::NotStoredAlreadyMined)
             },
             Err(e) => {
                 eprintln!("Validation failed due to error: {}", e);
                 warn!(target: LOG_TARGET, "Validation failed due to error: {}", e);
-                Ok(TxStorageResponse::NotStored)
+                Err(TxStorageResponse::NotStored)
             },
         }
     }
@@ -156,14 +214,69 @@ impl MempoolStorage {
             .transaction_weight_params()
     }
 
-    // Insert a set of new transactions into the UTxPool.
+    // Insert a set of new transactions into the UTxPool. Callers are responsible for publishing the resulting changes.
     fn insert_txs(&mut self, txs: Vec<Arc<Transaction>>) -> Result<(), UnconfirmedPoolError> {
         for tx in txs {
-            self.insert(tx)?;
+            self.insert_unpublished(tx)?;
         }
         Ok(())
     }
 
+    fn has_subscribers(&self) -> bool {
+        self.event_sender.receiver_count() > 0
+    }
+
+    fn publish_event(&self, event: MempoolEvent) {
+        // An error only means that there are no subscribers
+        let _result = self.event_sender.send(Arc::new(event));
+    }
+
+    fn is_in_unconfirmed_pool(&self, tx: &Transaction) -> bool {
+        tx.first_kernel_excess_sig()
+            .map(|sig| self.unconfirmed_pool.has_tx_with_excess_sig(sig))
+            .unwrap_or(false)
+    }
+
+    /// Takes a snapshot of the unconfirmed pool keyed by excess signature, if anyone is listening for changes.
+    fn snapshot_for_events(&self) -> Option<HashMap<PrivateKey, Arc<Transaction>>> {
+        if !self.has_subscribers() {
+            return None;
+        }
+        Some(self.unconfirmed_txs_by_excess_sig())
+    }
+
+    fn unconfirmed_txs_by_excess_sig(&self) -> HashMap<PrivateKey, Arc<Transaction>> {
+        self.unconfirmed_pool
+            .snapshot()
+            .into_iter()
+            .filter_map(|tx| Some((tx.first_kernel_excess_sig()?.get_signature().clone(), tx)))
+            .collect()
+    }
+
+    /// Publishes the difference between a previous snapshot of the unconfirmed pool and its current contents.
+    /// Transactions that are no longer in the pool are reported as removed for the given reason.
+    fn publish_changes_since(
+        &self,
+        before: Option<HashMap<PrivateKey, Arc<Transaction>>>,
+        removal_reason: MempoolRemovalReason,
+    ) {
+        let before = match before {
+            Some(before) => before,
+            None => return,
+        };
+        let after = self.unconfirmed_txs_by_excess_sig();
+        for (sig, tx) in &before {
+            if !after.contains_key(sig) {
+                self.publish_event(MempoolEvent::removed(tx, removal_reason));
+            }
+        }
+        for (sig, tx) in after {
+            if !before.contains_key(&sig) {
+                self.publish_event(MempoolEvent::TransactionAdded(tx));
+            }
+        }
+    }
+
     /// Update the Mempool based on the received published block.
     pub fn process_published_block(&mut self, published_block: &Block) -> Result<(), MempoolError> {
         debug!(
@@ -178,6 +291,27 @@ impl MempoolStorage {
         let removed_transactions = self
             .unconfirmed_pool
             .remove_published_and_discard_deprecated_transactions(published_block)?;
+        if self.has_subscribers() {
+            let mined_sigs = published_block
+                .body
+                .kernels()
+                .iter()
+                .map(|k| k.excess_sig.get_signature())
+                .collect::<HashSet<_>>();
+            for tx in &removed_transactions {
+                let reason = if tx
+                    .body
+                    .kernels()
+                    .iter()
+                    .any(|k| mined_sigs.contains(k.excess_sig.get_signature()))
+                {
+                    MempoolRemovalReason::Mined
+                } else {
+                    MempoolRemovalReason::DoubleSpend
+                };
+                self.publish_event(MempoolEvent::removed(tx, reason));
+            }
+        }
         debug!(
             target: LOG_TARGET,
             "{} transactions removed from unconfirmed pool in {:.2?}, moving them to reorg pool for block #{} ({}) {}",
@@ -218,6 +352,7 @@ impl MempoolStorage {
             failed_block.header.height,
             failed_block.hash().to_hex()
         );
+        let before = self.snapshot_for_events();
         let txs = self
             .unconfirmed_pool
             .remove_published_and_discard_deprecated_transactions(failed_block)?;
@@ -226,6 +361,7 @@ impl MempoolStorage {
         self.insert_txs(txs)
             .map_err(|e| MempoolError::InternalError(e.to_string()))?;
         self.unconfirmed_pool.compact();
+        self.publish_changes_since(before, MempoolRemovalReason::Invalid);
 
         Ok(())
     }
@@ -238,6 +374,7 @@ impl MempoolStorage {
         new_blocks: &[Arc<Block>],
     ) -> Result<(), MempoolError> {
         debug!(target: LOG_TARGET, "Mempool processing reorg");
+        let before = self.snapshot_for_events();
 
         // Clear out all transactions from the unconfirmed pool and re-submit them to the unconfirmed mempool for
         // validation. This is important as invalid transactions that have not been mined yet may remain in the mempool
@@ -259,12 +396,14 @@ impl MempoolStorage {
         {
             self.last_seen_height = height;
         }
+        self.publish_changes_since(before, MempoolRemovalReason::Invalid);
         Ok(())
     }
 
     /// After a sync event, we need to try to add in all the transaction form the reorg pool.
     pub fn process_sync(&mut self) -> Result<(), MempoolError> {
         debug!(target: LOG_TARGET, "Mempool processing sync finished");
+        let before = self.snapshot_for_events();
         // lets remove and revalidate all transactions from the mempool. All we know is that the state has changed, but
         // we dont have the data to know what.
         let txs = self.unconfirmed_pool.drain_all_mempool_transactions();
@@ -275,6 +414,7 @@ impl MempoolStorage {
         let txs = self.reorg_pool.clear_and_retrieve_all();
         self.insert_txs(txs)
             .map_err(|e| MempoolError::InternalError(e.to_string()))?;
+        self.publish_changes_since(before, MempoolRemovalReason::Invalid);
         Ok(())
     }
 
@@ -287,8 +427,13 @@ impl MempoolStorage {
     /// Will only return transactions that will fit into the given weight
     pub fn retrieve_and_revalidate(&mut self, total_weight: u64) -> Result<Vec<Arc<Transaction>>, MempoolError> {
         let results = self.unconfirmed_pool.fetch_highest_priority_txs(total_weight)?;
+        if results.transactions_to_insert.is_empty() {
+            return Ok(results.retrieved_transactions);
+        }
+        let before = self.snapshot_for_events();
         self.insert_txs(results.transactions_to_insert)
             .map_err(|e| MempoolError::InternalError(e.to_string()))?;
+        self.publish_changes_since(before, MempoolRemovalReason::Invalid);
         Ok(results.retrieved_transactions)
     }
 
@@ -75,3 +75,29 @@ pub fn reorg_pool_size() -> IntGauge {
 
     METER.clone()
 }
+
+pub fn relayed_bytes(method: &str) -> IntCounter {
+    static METER: Lazy<IntCounterVec> = Lazy::new(|| {
+        tari_metrics::register_int_counter_vec(
+            "base_node::mempool::relayed_bytes",
+            "Number of bytes sent to peers to relay transactions, by relay method",
+            &["method"],
+        )
+        .unwrap()
+    });
+
+    METER.with_label_values(&[method])
+}
+
+pub fn reconciliations(result: &str) -> IntCounter {
+    static METER: Lazy<IntCounterVec> = Lazy::new(|| {
+        tari_metrics::register_int_counter_vec(
+            "base_node::mempool::reconciliations",
+            "Number of mempool set reconciliations initiated by this node",
+            &["result"],
+        )
+        .unwrap()
+    });
+
+    METER.with_label_values(&[result])
+}
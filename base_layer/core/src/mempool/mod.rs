@@ -28,6 +28,8 @@ mod config;
 #[cfg(feature = "base_node")]
 mod error;
 #[cfg(feature = "base_node")]
+mod events;
+#[cfg(feature = "base_node")]
 #[allow(clippy::module_inception)]
 mod mempool;
 #[cfg(feature = "base_node")]
@@ -35,6 +37,10 @@ mod mempool_storage;
 #[cfg(feature = "base_node")]
 mod priority;
 #[cfg(feature = "base_node")]
+mod reconciliation;
+#[cfg(feature = "base_node")]
+pub use reconciliation::{MempoolReconciliationInitializer, TransactionRelayHandle, TransactionRelayStats};
+#[cfg(feature = "base_node")]
 mod reorg_pool;
 #[cfg(feature = "base_node")]
 mod rpc;
@@ -53,10 +59,12 @@ mod unconfirmed_pool;
 #[cfg(feature = "base_node")]
 pub use error::MempoolError;
 #[cfg(feature = "base_node")]
+pub use events::{MempoolEvent, MempoolEventReceiver, MempoolEventSender, MempoolRemovalReason};
+#[cfg(feature = "base_node")]
 pub use mempool::Mempool;
 
 #[cfg(feature = "base_node")]
-pub use self::config::{MempoolConfig, MempoolServiceConfig};
+pub use self::config::{DandelionConfig, MempoolConfig, MempoolServiceConfig};
 
 #[cfg(any(feature = "base_node", feature = "mempool_proto"))]
 pub mod proto;
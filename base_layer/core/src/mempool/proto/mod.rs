@@ -20,7 +20,15 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-pub use mempool::{InventoryIndexes, TransactionInventory, TransactionItem};
+pub use mempool::{
+    InventoryIndexes,
+    ReconciliationDifference,
+    ReconciliationRequest,
+    ReconciliationSketch,
+    ShortTransactionIds,
+    TransactionInventory,
+    TransactionItem,
+};
 
 use crate::proto::mempool;
 
@@ -0,0 +1,139 @@
+// Copyright 2024. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{
+    fmt::{Display, Error, Formatter},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+        RwLock,
+    },
+};
+
+use tari_comms::peer_manager::NodeId;
+
+#[cfg(feature = "metrics")]
+use crate::mempool::metrics;
+
+/// Handle shared between the mempool service and the reconciliation protocol. Once the reconciliation protocol is
+/// running, the mempool service only floods new transactions to the peers returned by
+/// [TransactionRelayHandle::flood_peers] and all other base node peers receive them through set reconciliation.
+#[derive(Debug, Clone, Default)]
+pub struct TransactionRelayHandle {
+    flood_peers: Arc<RwLock<Option<Vec<NodeId>>>>,
+    stats: Arc<RelayCounters>,
+}
+
+impl TransactionRelayHandle {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the peers that new transactions should be flooded to, or None if the reconciliation protocol is not
+    /// running yet, in which case transactions should be flooded to all peers.
+    pub fn flood_peers(&self) -> Option<Vec<NodeId>> {
+        self.flood_peers.read().expect("flood_peers lock poisoned").clone()
+    }
+
+    pub(super) fn set_flood_peers(&self, peers: Vec<NodeId>) {
+        *self.flood_peers.write().expect("flood_peers lock poisoned") = Some(peers);
+    }
+
+    pub fn record_flooded_bytes(&self, num_bytes: usize) {
+        self.stats.flooded_bytes.fetch_add(num_bytes as u64, Ordering::Relaxed);
+        #[cfg(feature = "metrics")]
+        metrics::relayed_bytes("flood").inc_by(num_bytes as u64);
+    }
+
+    pub(super) fn record_overhead_bytes(&self, num_bytes: usize) {
+        self.stats
+            .reconciliation_overhead_bytes
+            .fetch_add(num_bytes as u64, Ordering::Relaxed);
+        #[cfg(feature = "metrics")]
+        metrics::relayed_bytes("reconciliation_overhead").inc_by(num_bytes as u64);
+    }
+
+    pub(super) fn record_transaction_bytes(&self, num_bytes: usize) {
+        self.stats
+            .reconciled_transaction_bytes
+            .fetch_add(num_bytes as u64, Ordering::Relaxed);
+        #[cfg(feature = "metrics")]
+        metrics::relayed_bytes("reconciliation_transactions").inc_by(num_bytes as u64);
+    }
+
+    pub(super) fn record_reconciliation(&self, sketch_decoded: bool) {
+        self.stats.num_reconciliations.fetch_add(1, Ordering::Relaxed);
+        if !sketch_decoded {
+            self.stats.num_sketch_failures.fetch_add(1, Ordering::Relaxed);
+        }
+        #[cfg(feature = "metrics")]
+        metrics::reconciliations(if sketch_decoded { "decoded" } else { "sketch_failed" }).inc();
+    }
+
+    /// Returns a snapshot of the bytes relayed by flooding and by reconciliation since this node started
+    pub fn stats(&self) -> TransactionRelayStats {
+        TransactionRelayStats {
+            flooded_bytes: self.stats.flooded_bytes.load(Ordering::Relaxed),
+            reconciliation_overhead_bytes: self.stats.reconciliation_overhead_bytes.load(Ordering::Relaxed),
+            reconciled_transaction_bytes: self.stats.reconciled_transaction_bytes.load(Ordering::Relaxed),
+            num_reconciliations: self.stats.num_reconciliations.load(Ordering::Relaxed),
+            num_sketch_failures: self.stats.num_sketch_failures.load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct RelayCounters {
+    flooded_bytes: AtomicU64,
+    reconciliation_overhead_bytes: AtomicU64,
+    reconciled_transaction_bytes: AtomicU64,
+    num_reconciliations: AtomicU64,
+    num_sketch_failures: AtomicU64,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TransactionRelayStats {
+    /// Bytes of transactions sent to flood peers
+    pub flooded_bytes: u64,
+    /// Bytes of sketches, short ids and other protocol messages sent while reconciling
+    pub reconciliation_overhead_bytes: u64,
+    /// Bytes of transactions sent to peers that were missing them after reconciliation
+    pub reconciled_transaction_bytes: u64,
+    pub num_reconciliations: u64,
+    /// Number of reconciliations that fell back to exchanging all short ids because the sketch could not be decoded
+    pub num_sketch_failures: u64,
+}
+
+impl Display for TransactionRelayStats {
+    fn fmt(&self, fmt: &mut Formatter<'_>) -> Result<(), Error> {
+        write!(
+            fmt,
+            "Transaction relay: flooded: {} bytes, reconciliation: {} bytes ({} bytes overhead), reconciliations: {} \
+             ({} sketch failures)",
+            self.flooded_bytes,
+            self.reconciled_transaction_bytes + self.reconciliation_overhead_bytes,
+            self.reconciliation_overhead_bytes,
+            self.num_reconciliations,
+            self.num_sketch_failures
+        )
+    }
+}
@@ -0,0 +1,800 @@
+// Copyright 2024. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! # Mempool Set Reconciliation Protocol
+//!
+//! Flooding every new transaction to every connected base node means that each node receives each transaction once
+//! from almost every peer. To reduce this redundancy, a node only floods new transactions to a small number of outbound
+//! peers (`MempoolServiceConfig::num_flood_peers`). Every `MempoolServiceConfig::reconciliation_interval`, the node
+//! reconciles its mempool with each of its outbound base node peers, so that the transactions propagate to all peers
+//! while only the set difference is transferred.
+//!
+//! Transactions are identified by 64-bit short ids, derived from the first kernel excess signature and a salt chosen by
+//! the initiator for each round. The responder sends a sketch of its short ids (an invertible bloom lookup table) whose
+//! size depends only on the expected set difference. If the initiator is unable to decode the difference, it falls back
+//! to requesting all of the responder's short ids.
+//!
+//! Peers that do not support this protocol, and inbound peers that never initiate a reconciliation, are always flooded.
+//!
+//! ## Protocol Flow
+//!
+//! Alice initiates (initiator) the connection to Bob (responder).
+//!
+//! ```text
+//!  +-------+                                +-----+
+//!  | Alice |                                | Bob |
+//!  +-------+                                +-----+
+//!  |                                            |
+//!  | Request(salt, set size, expected diff)     |
+//!  |------------------------------------------->|
+//!  |                                Sketch      |
+//!  |<-------------------------------------------|
+//!  |  (if the sketch cannot be decoded)         |
+//!  | Difference(request_short_ids)              |
+//!  |------------------------------------------->|
+//!  |                       ShortTransactionIds  |
+//!  |<-------------------------------------------|
+//!  |  (end if)                                  |
+//!  | Difference(missing from Alice, sending)    |
+//!  |------------------------------------------->|
+//!  | TransactionItem(tx_a1)                     |
+//!  |------------------------------------------->|
+//!  |             ...streaming...                |
+//!  | TransactionItem(empty)                     |
+//!  |------------------------------------------->|
+//!  |                     TransactionItem(tx_b1) |
+//!  |<-------------------------------------------|
+//!  |             ...streaming...                |
+//!  |                     TransactionItem(empty) |
+//!  |<-------------------------------------------|
+//!  |                                            |
+//!  |             END                            |
+//! ```
+
+use std::{
+    collections::{HashMap, HashSet},
+    convert::TryFrom,
+    iter,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use error::ReconciliationError;
+use futures::{stream, SinkExt, StreamExt};
+pub use handle::{TransactionRelayHandle, TransactionRelayStats};
+pub use initializer::MempoolReconciliationInitializer;
+use log::*;
+use prost::Message;
+use rand::{rngs::OsRng, RngCore};
+use sketch::{short_id, Sketch};
+use tari_comms::{
+    connectivity::{ConnectivityEvent, ConnectivityRequester},
+    framing,
+    framing::CanonicalFraming,
+    message::MessageExt,
+    peer_manager::{NodeId, PeerFeatures},
+    protocol::{ProtocolError, ProtocolEvent, ProtocolNotification, ProtocolNotificationRx},
+    Bytes,
+    PeerConnection,
+    PeerConnectionError,
+};
+use tari_utilities::hex::Hex;
+use tokio::{
+    io::{AsyncRead, AsyncWrite},
+    sync::mpsc,
+    task,
+    time,
+    time::MissedTickBehavior,
+};
+
+#[cfg(feature = "metrics")]
+use crate::mempool::metrics;
+use crate::{
+    mempool::{proto, Mempool, MempoolServiceConfig},
+    proto as shared_proto,
+    transactions::transaction_components::Transaction,
+};
+
+#[cfg(test)]
+mod test;
+
+mod error;
+mod handle;
+mod initializer;
+mod sketch;
+
+const MAX_FRAME_SIZE: usize = 3 * 1024 * 1024; // 3 MiB
+const LOG_TARGET: &str = "c::mempool::reconciliation";
+/// Extra capacity added to each sketch to allow for transactions that arrived since the last round
+const SKETCH_CAPACITY_MARGIN: usize = 8;
+/// Upper bound on the capacity of a sketch requested by a peer (approx. 300KiB)
+const MAX_SKETCH_CAPACITY: usize = 10_000;
+/// The maximum time to wait for each transaction streamed by a peer
+const TRANSACTION_ITEM_TIMEOUT: Duration = Duration::from_secs(10);
+/// Inbound base node peers that have not initiated a reconciliation within this many intervals are flooded instead
+const INBOUND_GRACE_INTERVALS: u32 = 5;
+
+pub static MEMPOOL_RECONCILIATION_PROTOCOL: Bytes = Bytes::from_static(b"t/mempool-recon/1");
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RoundOutcome {
+    /// Reconciliation completed with the given set difference
+    Reconciled(usize),
+    /// The peer does not speak the reconciliation protocol
+    NotSupported,
+    Failed,
+}
+
+pub struct MempoolReconciliationProtocol<TSubstream> {
+    config: MempoolServiceConfig,
+    protocol_notifier: ProtocolNotificationRx<TSubstream>,
+    mempool: Mempool,
+    connectivity: ConnectivityRequester,
+    relay: TransactionRelayHandle,
+    /// Outbound base node connections, in the order they were established
+    outbound_peers: Vec<PeerConnection>,
+    /// Inbound base node peers that have not yet initiated a reconciliation with this node
+    pending_inbound_peers: HashMap<NodeId, Instant>,
+    non_reconciling_peers: HashSet<NodeId>,
+    in_progress: HashSet<NodeId>,
+    expected_differences: HashMap<NodeId, usize>,
+    round_outcome_tx: mpsc::Sender<(NodeId, RoundOutcome)>,
+    round_outcome_rx: mpsc::Receiver<(NodeId, RoundOutcome)>,
+}
+
+impl<TSubstream> MempoolReconciliationProtocol<TSubstream>
+where TSubstream: AsyncRead + AsyncWrite + Unpin + Send + Sync + 'static
+{
+    pub fn new(
+        config: MempoolServiceConfig,
+        protocol_notifier: ProtocolNotificationRx<TSubstream>,
+        mempool: Mempool,
+        connectivity: ConnectivityRequester,
+        relay: TransactionRelayHandle,
+    ) -> Self {
+        let (round_outcome_tx, round_outcome_rx) = mpsc::channel(10);
+        Self {
+            config,
+            protocol_notifier,
+            mempool,
+            connectivity,
+            relay,
+            outbound_peers: Vec::new(),
+            pending_inbound_peers: HashMap::new(),
+            non_reconciling_peers: HashSet::new(),
+            in_progress: HashSet::new(),
+            expected_differences: HashMap::new(),
+            round_outcome_tx,
+            round_outcome_rx,
+        }
+    }
+
+    pub async fn run(mut self) {
+        info!(target: LOG_TARGET, "Mempool reconciliation protocol handler has started");
+
+        let mut connectivity_events = self.connectivity.get_event_subscription();
+        match self.connectivity.get_active_connections().await {
+            Ok(connections) => {
+                for conn in connections {
+                    self.add_peer(conn);
+                }
+            },
+            Err(err) => warn!(target: LOG_TARGET, "Failed to get active connections: {}", err),
+        }
+        self.update_flood_peers();
+
+        let mut ticker = time::interval(self.config.reconciliation_interval);
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        loop {
+            tokio::select! {
+                Ok(event) = connectivity_events.recv() => {
+                    self.handle_connectivity_event(event);
+                },
+
+                Some((node_id, outcome)) = self.round_outcome_rx.recv() => {
+                    self.handle_round_outcome(node_id, outcome);
+                },
+
+                Some(notif) = self.protocol_notifier.recv() => {
+                    self.handle_protocol_notification(notif);
+                },
+
+                _ = ticker.tick() => {
+                    self.flood_silent_inbound_peers();
+                    self.reconcile_with_outbound_peers();
+                },
+            }
+        }
+    }
+
+    fn handle_connectivity_event(&mut self, event: ConnectivityEvent) {
+        match event {
+            ConnectivityEvent::PeerConnected(conn) => {
+                self.add_peer(*conn);
+                self.update_flood_peers();
+            },
+            ConnectivityEvent::PeerDisconnected(node_id) | ConnectivityEvent::PeerBanned(node_id) => {
+                self.remove_peer(&node_id);
+                self.update_flood_peers();
+            },
+            _ => {},
+        }
+    }
+
+    fn add_peer(&mut self, conn: PeerConnection) {
+        // This protocol is only spoken between base nodes
+        if !conn.peer_features().contains(PeerFeatures::COMMUNICATION_NODE) {
+            return;
+        }
+        self.remove_peer(conn.peer_node_id());
+        if conn.direction().is_outbound() {
+            self.outbound_peers.push(conn);
+        } else {
+            self.pending_inbound_peers
+                .insert(conn.peer_node_id().clone(), Instant::now());
+        }
+    }
+
+    fn remove_peer(&mut self, node_id: &NodeId) {
+        self.outbound_peers.retain(|conn| conn.peer_node_id() != node_id);
+        self.pending_inbound_peers.remove(node_id);
+        self.non_reconciling_peers.remove(node_id);
+        self.expected_differences.remove(node_id);
+    }
+
+    /// Flood to the first `num_flood_peers` outbound peers and to all peers that do not reconcile with this node
+    fn update_flood_peers(&self) {
+        let flood_peers = self
+            .outbound_peers
+            .iter()
+            .map(|conn| conn.peer_node_id())
+            .filter(|node_id| !self.non_reconciling_peers.contains(*node_id))
+            .take(self.config.num_flood_peers)
+            .chain(self.non_reconciling_peers.iter())
+            .cloned()
+            .collect();
+        self.relay.set_flood_peers(flood_peers);
+    }
+
+    fn flood_silent_inbound_peers(&mut self) {
+        let grace_period = self.config.reconciliation_interval * INBOUND_GRACE_INTERVALS;
+        let silent_peers = self
+            .pending_inbound_peers
+            .iter()
+            .filter(|(_, connected_at)| connected_at.elapsed() >= grace_period)
+            .map(|(node_id, _)| node_id.clone())
+            .collect::<Vec<_>>();
+        if silent_peers.is_empty() {
+            return;
+        }
+        for node_id in silent_peers {
+            debug!(
+                target: LOG_TARGET,
+                "Inbound peer `{}` has not reconciled with this node. Flooding transactions to this peer instead.",
+                node_id.short_str()
+            );
+            self.pending_inbound_peers.remove(&node_id);
+            self.non_reconciling_peers.insert(node_id);
+        }
+        self.update_flood_peers();
+    }
+
+    fn reconcile_with_outbound_peers(&mut self) {
+        let connections = self
+            .outbound_peers
+            .iter()
+            .filter(|conn| {
+                !self.non_reconciling_peers.contains(conn.peer_node_id()) &&
+                    !self.in_progress.contains(conn.peer_node_id())
+            })
+            .cloned()
+            .collect::<Vec<_>>();
+        for conn in connections {
+            self.spawn_initiator_protocol(conn);
+        }
+    }
+
+    fn handle_round_outcome(&mut self, node_id: NodeId, outcome: RoundOutcome) {
+        self.in_progress.remove(&node_id);
+        match outcome {
+            RoundOutcome::Reconciled(difference) => {
+                self.expected_differences.insert(node_id, difference);
+            },
+            RoundOutcome::NotSupported => {
+                // Only track peers that are still connected
+                if self.outbound_peers.iter().any(|conn| *conn.peer_node_id() == node_id) {
+                    self.non_reconciling_peers.insert(node_id);
+                    self.update_flood_peers();
+                }
+            },
+            RoundOutcome::Failed => {},
+        }
+    }
+
+    fn handle_protocol_notification(&mut self, notification: ProtocolNotification<TSubstream>) {
+        match notification.event {
+            ProtocolEvent::NewInboundSubstream(node_id, substream) => {
+                self.pending_inbound_peers.remove(&node_id);
+                if self.non_reconciling_peers.remove(&node_id) {
+                    self.update_flood_peers();
+                }
+                self.spawn_inbound_handler(node_id, substream);
+            },
+        }
+    }
+
+    fn spawn_initiator_protocol(&mut self, mut conn: PeerConnection) {
+        let node_id = conn.peer_node_id().clone();
+        self.in_progress.insert(node_id.clone());
+        let expected_difference = self.expected_differences.get(&node_id).copied().unwrap_or(0);
+        let mempool = self.mempool.clone();
+        let relay = self.relay.clone();
+        let round_outcome_tx = self.round_outcome_tx.clone();
+        task::spawn(async move {
+            let outcome = match conn
+                .open_framed_substream(&MEMPOOL_RECONCILIATION_PROTOCOL, MAX_FRAME_SIZE)
+                .await
+            {
+                Ok(framed) => {
+                    let protocol = PeerReconciliation::new(framed, node_id.clone(), mempool, relay);
+                    match protocol.start_initiator(expected_difference).await {
+                        Ok(difference) => {
+                            debug!(
+                                target: LOG_TARGET,
+                                "Mempool reconciliation with peer `{}` completed successfully (difference = {})",
+                                node_id.short_str(),
+                                difference
+                            );
+                            RoundOutcome::Reconciled(difference)
+                        },
+                        Err(err) => {
+                            debug!(
+                                target: LOG_TARGET,
+                                "Mempool reconciliation initiator protocol failed for peer `{}`: {}",
+                                node_id.short_str(),
+                                err
+                            );
+                            RoundOutcome::Failed
+                        },
+                    }
+                },
+                Err(PeerConnectionError::ProtocolError(ProtocolError::ProtocolOutboundNegotiationFailed {
+                    ..
+                })) => {
+                    debug!(
+                        target: LOG_TARGET,
+                        "Peer `{}` does not support mempool reconciliation. Flooding transactions to this peer instead.",
+                        node_id.short_str()
+                    );
+                    RoundOutcome::NotSupported
+                },
+                Err(err) => {
+                    debug!(
+                        target: LOG_TARGET,
+                        "Unable to establish mempool reconciliation substream to peer `{}`: {}",
+                        node_id.short_str(),
+                        err
+                    );
+                    RoundOutcome::Failed
+                },
+            };
+            let _result = round_outcome_tx.send((node_id, outcome)).await;
+        });
+    }
+
+    fn spawn_inbound_handler(&self, node_id: NodeId, substream: TSubstream) {
+        let mempool = self.mempool.clone();
+        let relay = self.relay.clone();
+        task::spawn(async move {
+            let framed = framing::canonical(substream, MAX_FRAME_SIZE);
+            let mut protocol = PeerReconciliation::new(framed, node_id.clone(), mempool, relay);
+            match protocol.start_responder().await {
+                Ok(_) => {
+                    debug!(
+                        target: LOG_TARGET,
+                        "Mempool reconciliation responder protocol succeeded for peer `{}`",
+                        node_id.short_str()
+                    );
+                },
+                Err(err) => {
+                    debug!(
+                        target: LOG_TARGET,
+                        "Mempool reconciliation responder protocol failed for peer `{}`: {}",
+                        node_id.short_str(),
+                        err
+                    );
+                },
+            }
+        });
+    }
+}
+
+struct PeerReconciliation<TSubstream> {
+    framed: CanonicalFraming<TSubstream>,
+    mempool: Mempool,
+    peer_node_id: NodeId,
+    relay: TransactionRelayHandle,
+}
+
+impl<TSubstream> PeerReconciliation<TSubstream>
+where TSubstream: AsyncRead + AsyncWrite + Unpin
+{
+    pub fn new(
+        framed: CanonicalFraming<TSubstream>,
+        peer_node_id: NodeId,
+        mempool: Mempool,
+        relay: TransactionRelayHandle,
+    ) -> Self {
+        Self {
+            framed,
+            mempool,
+            peer_node_id,
+            relay,
+        }
+    }
+
+    /// Runs a reconciliation round as the initiator and returns the size of the set difference
+    pub async fn start_initiator(mut self, expected_difference: usize) -> Result<usize, ReconciliationError> {
+        match self.start_initiator_inner(expected_difference).await {
+            Ok(difference) => Ok(difference),
+            Err(err) => {
+                self.close().await;
+                Err(err)
+            },
+        }
+    }
+
+    async fn start_initiator_inner(&mut self, expected_difference: usize) -> Result<usize, ReconciliationError> {
+        let salt = OsRng.next_u64();
+        let transactions = self.short_id_map(salt).await?;
+        self.write_overhead(proto::ReconciliationRequest {
+            salt,
+            set_size: saturating_u32(transactions.len()),
+            expected_difference: saturating_u32(expected_difference),
+        })
+        .await?;
+
+        let msg: proto::ReconciliationSketch = self.read_message().await?;
+        let remote_sketch = Sketch::try_from(msg).map_err(|message| ReconciliationError::MessageConversionFailed {
+            peer: self.peer_node_id.clone(),
+            message,
+        })?;
+        let mut local_sketch = Sketch::matching(&remote_sketch);
+        for id in transactions.keys() {
+            local_sketch.insert(*id);
+        }
+
+        let (local_only, remote_only) = match local_sketch.decode_difference(&remote_sketch) {
+            Some(diff) => {
+                self.relay.record_reconciliation(true);
+                (diff.local_only, diff.remote_only)
+            },
+            None => {
+                debug!(
+                    target: LOG_TARGET,
+                    "Unable to decode sketch from peer `{}` ({} cells). Requesting all short ids.",
+                    self.peer_node_id.short_str(),
+                    remote_sketch.num_cells()
+                );
+                self.relay.record_reconciliation(false);
+                self.write_overhead(proto::ReconciliationDifference {
+                    request_short_ids: true,
+                    missing: Vec::new(),
+                    sending: Vec::new(),
+                })
+                .await?;
+                let remote_ids: proto::ShortTransactionIds = self.read_message().await?;
+                let remote_ids = remote_ids.ids.into_iter().collect::<HashSet<_>>();
+                let local_only = transactions
+                    .keys()
+                    .filter(|id| !remote_ids.contains(*id))
+                    .copied()
+                    .collect();
+                let remote_only = remote_ids
+                    .into_iter()
+                    .filter(|id| !transactions.contains_key(id))
+                    .collect();
+                (local_only, remote_only)
+            },
+        };
+
+        let difference = local_only.len() + remote_only.len();
+        let num_missing = remote_only.len();
+        debug!(
+            target: LOG_TARGET,
+            "Requesting {} missing transaction(s) from peer `{}` and sending {}",
+            num_missing,
+            self.peer_node_id.short_str(),
+            local_only.len()
+        );
+        let requested = remote_only.iter().copied().collect();
+        let (sending, local_only): (Vec<_>, Vec<_>) = local_only
+            .iter()
+            .filter_map(|id| transactions.get(id).map(|txn| (*id, txn.clone())))
+            .unzip();
+        self.write_overhead(proto::ReconciliationDifference {
+            request_short_ids: false,
+            missing: remote_only,
+            sending,
+        })
+        .await?;
+        self.write_transactions(local_only).await?;
+
+        if num_missing > 0 {
+            self.read_and_insert_transactions_until_complete(salt, requested)
+                .await?;
+        }
+
+        // Close the stream after reading
+        self.framed.close().await?;
+
+        Ok(difference)
+    }
+
+    pub async fn start_responder(&mut self) -> Result<(), ReconciliationError> {
+        match self.start_responder_inner().await {
+            Ok(_) => Ok(()),
+            Err(err) => {
+                self.close().await;
+                Err(err)
+            },
+        }
+    }
+
+    async fn start_responder_inner(&mut self) -> Result<(), ReconciliationError> {
+        let request: proto::ReconciliationRequest = self.read_message().await?;
+        let transactions = self.short_id_map(request.salt).await?;
+
+        let set_size_difference = (request.set_size as usize).abs_diff(transactions.len());
+        let capacity = (request.expected_difference as usize).max(set_size_difference) + SKETCH_CAPACITY_MARGIN;
+        let sketch = Sketch::from_ids(capacity.min(MAX_SKETCH_CAPACITY), transactions.keys().copied());
+        debug!(
+            target: LOG_TARGET,
+            "Sending sketch with {} cells to peer `{}`",
+            sketch.num_cells(),
+            self.peer_node_id.short_str()
+        );
+        let mut msg = proto::ReconciliationSketch::from(sketch);
+        msg.set_size = saturating_u32(transactions.len());
+        self.write_overhead(msg).await?;
+
+        let mut difference: proto::ReconciliationDifference = self.read_message().await?;
+        if difference.request_short_ids {
+            let ids = transactions.keys().copied().collect();
+            self.write_overhead(proto::ShortTransactionIds { ids }).await?;
+            difference = self.read_message().await?;
+            if difference.request_short_ids {
+                return Err(ReconciliationError::ProtocolViolation {
+                    peer: self.peer_node_id.clone(),
+                    details: "Short ids requested more than once".to_string(),
+                });
+            }
+        }
+        if difference.missing.len() > transactions.len() {
+            return Err(ReconciliationError::ProtocolViolation {
+                peer: self.peer_node_id.clone(),
+                details: format!(
+                    "Requested {} transaction(s) but only {} are known",
+                    difference.missing.len(),
+                    transactions.len()
+                ),
+            });
+        }
+        if difference.sending.len() > request.set_size as usize {
+            return Err(ReconciliationError::ProtocolViolation {
+                peer: self.peer_node_id.clone(),
+                details: format!(
+                    "Announced {} transaction(s) but has a set size of {}",
+                    difference.sending.len(),
+                    request.set_size
+                ),
+            });
+        }
+
+        let announced = difference.sending.iter().copied().collect();
+        self.read_and_insert_transactions_until_complete(request.salt, announced)
+            .await?;
+
+        if !difference.missing.is_empty() {
+            let missing = difference
+                .missing
+                .iter()
+                .filter_map(|id| transactions.get(id).cloned())
+                .collect();
+            self.write_transactions(missing).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn short_id_map(&self, salt: u64) -> Result<HashMap<u64, Arc<Transaction>>, ReconciliationError> {
+        let transactions = self.mempool.snapshot().await?;
+        Ok(transactions
+            .into_iter()
+            .filter_map(|txn| {
+                let id = short_id(salt, txn.first_kernel_excess_sig()?);
+                Some((id, txn))
+            })
+            .collect())
+    }
+
+    async fn close(&mut self) {
+        if let Err(err) = self.framed.flush().await {
+            debug!(target: LOG_TARGET, "IO error when flushing stream: {}", err);
+        }
+        if let Err(err) = self.framed.close().await {
+            debug!(target: LOG_TARGET, "IO error when closing stream: {}", err);
+        }
+    }
+
+    /// Reads transactions until the peer signals that it is done. Only transactions with one of the `expected` short
+    /// ids are accepted, each at most once.
+    async fn read_and_insert_transactions_until_complete(
+        &mut self,
+        salt: u64,
+        mut expected: HashSet<u64>,
+    ) -> Result<(), ReconciliationError> {
+        let mut num_recv = 0;
+        while let Some(result) = time::timeout(TRANSACTION_ITEM_TIMEOUT, self.framed.next())
+            .await
+            .map_err(|_| ReconciliationError::RecvTimeout)?
+        {
+            let bytes = result?;
+            let item = proto::TransactionItem::decode(&mut bytes.freeze()).map_err(|err| {
+                ReconciliationError::DecodeFailed {
+                    source: err,
+                    peer: self.peer_node_id.clone(),
+                }
+            })?;
+
+            match item.transaction {
+                Some(txn) => {
+                    self.validate_and_insert_transaction(txn, salt, &mut expected).await?;
+                    num_recv += 1;
+                },
+                None => {
+                    debug!(
+                        target: LOG_TARGET,
+                        "All transaction(s) (count={}) received from peer `{}`. ",
+                        num_recv,
+                        self.peer_node_id.short_str()
+                    );
+                    break;
+                },
+            }
+        }
+
+        #[allow(clippy::cast_possible_truncation)]
+        #[allow(clippy::cast_possible_wrap)]
+        #[cfg(feature = "metrics")]
+        {
+            let stats = self.mempool.stats().await?;
+            metrics::unconfirmed_pool_size().set(stats.unconfirmed_txs as i64);
+            metrics::reorg_pool_size().set(stats.reorg_txs as i64);
+        }
+
+        Ok(())
+    }
+
+    async fn validate_and_insert_transaction(
+        &mut self,
+        txn: shared_proto::types::Transaction,
+        salt: u64,
+        expected: &mut HashSet<u64>,
+    ) -> Result<(), ReconciliationError> {
+        let txn = Transaction::try_from(txn).map_err(|err| ReconciliationError::MessageConversionFailed {
+            peer: self.peer_node_id.clone(),
+            message: err,
+        })?;
+        let excess_sig = txn
+            .first_kernel_excess_sig()
+            .ok_or_else(|| ReconciliationError::ExcessSignatureMissing(self.peer_node_id.clone()))?;
+        if !expected.remove(&short_id(salt, excess_sig)) {
+            return Err(ReconciliationError::ProtocolViolation {
+                peer: self.peer_node_id.clone(),
+                details: "Sent a transaction that was not requested or announced".to_string(),
+            });
+        }
+        let excess_sig_hex = excess_sig.get_signature().to_hex();
+
+        let txn = Arc::new(txn);
+        let store_state = self.mempool.has_transaction(txn.clone()).await?;
+        if store_state.is_stored() {
+            return Ok(());
+        }
+
+        let stored_result = self.mempool.insert(txn).await?;
+        if stored_result.is_stored() {
+            #[cfg(feature = "metrics")]
+            metrics::inbound_transactions(Some(&self.peer_node_id)).inc();
+            debug!(
+                target: LOG_TARGET,
+                "Inserted reconciled transaction `{}` from peer `{}`",
+                excess_sig_hex,
+                self.peer_node_id.short_str()
+            );
+        } else {
+            #[cfg(feature = "metrics")]
+            metrics::rejected_inbound_transactions(Some(&self.peer_node_id)).inc();
+            debug!(
+                target: LOG_TARGET,
+                "Did not store reconciled transaction `{}` in mempool: {}", excess_sig_hex, stored_result
+            )
+        }
+
+        Ok(())
+    }
+
+    async fn write_transactions(&mut self, transactions: Vec<Arc<Transaction>>) -> Result<(), ReconciliationError> {
+        let mut num_bytes = 0;
+        let items = transactions
+            .into_iter()
+            .filter_map(|txn| match shared_proto::types::Transaction::try_from(txn) {
+                Ok(txn) => Some(proto::TransactionItem {
+                    transaction: Some(txn),
+                }),
+                Err(e) => {
+                    warn!(target: LOG_TARGET, "Could not convert transaction: {}", e);
+                    None
+                },
+            })
+            // Write an empty `TransactionItem` to indicate we're done
+            .chain(iter::once(proto::TransactionItem::empty()))
+            .map(|item| {
+                let bytes = Bytes::from(item.to_encoded_bytes());
+                num_bytes += bytes.len();
+                Ok(bytes)
+            })
+            .collect::<Vec<_>>();
+
+        self.framed.send_all(&mut stream::iter(items)).await?;
+        self.relay.record_transaction_bytes(num_bytes);
+        Ok(())
+    }
+
+    async fn read_message<T: prost::Message + Default>(&mut self) -> Result<T, ReconciliationError> {
+        let msg = time::timeout(Duration::from_secs(10), self.framed.next())
+            .await
+            .map_err(|_| ReconciliationError::RecvTimeout)?
+            .ok_or_else(|| ReconciliationError::SubstreamClosed(self.peer_node_id.clone()))??;
+
+        T::decode(&mut msg.freeze()).map_err(|err| ReconciliationError::DecodeFailed {
+            source: err,
+            peer: self.peer_node_id.clone(),
+        })
+    }
+
+    /// Writes a protocol message and records its size as reconciliation overhead
+    async fn write_overhead<T: prost::Message>(&mut self, message: T) -> Result<(), ReconciliationError> {
+        let bytes = Bytes::from(message.to_encoded_bytes());
+        let num_bytes = bytes.len();
+        time::timeout(Duration::from_secs(10), self.framed.send(bytes))
+            .await
+            .map_err(|_| ReconciliationError::SendTimeout)??;
+        self.relay.record_overhead_bytes(num_bytes);
+        Ok(())
+    }
+}
+
+fn saturating_u32(n: usize) -> u32 {
+    u32::try_from(n).unwrap_or(u32::MAX)
+}
@@ -0,0 +1,316 @@
+// Copyright 2024. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Invertible bloom lookup table (IBLT) over 64-bit short transaction ids.
+//!
+//! Two peers each insert their own short ids into a sketch with the same number of cells. Subtracting one sketch from
+//! the other cancels out the ids that both peers have, and the remaining ids (the set difference) can be recovered by
+//! repeatedly "peeling" cells that contain a single id. The size of a sketch depends only on the size of the set
+//! difference that it must be able to decode, not on the size of the sets.
+
+use std::{collections::VecDeque, convert::TryFrom};
+
+use blake2::Blake2b;
+use digest::{consts::U32, Digest};
+use tari_common_types::types::Signature;
+use tari_utilities::ByteArray;
+
+use crate::mempool::proto;
+
+/// Each id is inserted into one cell in each of this many partitions of the table
+const NUM_HASHES: usize = 3;
+/// The minimum number of cells in a sketch
+const MIN_CELLS: usize = 4 * NUM_HASHES;
+const CHECK_HASH_SEED: u64 = 0x9e37_79b9_7f4a_7c15;
+
+/// Returns the short id of a transaction, identified by its first kernel excess signature, for the given salt
+pub fn short_id(salt: u64, excess_sig: &Signature) -> u64 {
+    let hash = Blake2b::<U32>::new()
+        .chain_update(salt.to_le_bytes())
+        .chain_update(excess_sig.get_public_nonce().as_bytes())
+        .chain_update(excess_sig.get_signature().as_bytes())
+        .finalize();
+    let mut bytes = [0u8; 8];
+    bytes.copy_from_slice(&hash[..8]);
+    u64::from_le_bytes(bytes)
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct Cell {
+    count: i32,
+    key_sum: u64,
+    hash_sum: u64,
+}
+
+impl Cell {
+    fn is_empty(&self) -> bool {
+        self.count == 0 && self.key_sum == 0 && self.hash_sum == 0
+    }
+
+    fn is_pure(&self) -> bool {
+        (self.count == 1 || self.count == -1) && self.hash_sum == check_hash(self.key_sum)
+    }
+}
+
+/// The ids recovered from the difference of two sketches
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SetDifference {
+    /// Ids in the local sketch that are not in the remote sketch
+    pub local_only: Vec<u64>,
+    /// Ids in the remote sketch that are not in the local sketch
+    pub remote_only: Vec<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Sketch {
+    cells: Vec<Cell>,
+}
+
+impl Sketch {
+    /// Creates a sketch that is very likely to decode a set difference of up to `capacity` ids
+    pub fn with_capacity(capacity: usize) -> Self {
+        // 1.5 cells per id decodes with high probability for 3 hash functions
+        let num_cells = (capacity + capacity / 2).max(MIN_CELLS);
+        // Round up so that every partition has the same number of cells
+        Self::with_num_cells((num_cells + NUM_HASHES - 1) / NUM_HASHES * NUM_HASHES)
+    }
+
+    /// Creates a sketch with the same number of cells as `other`, so that the two sketches can be subtracted
+    pub fn matching(other: &Sketch) -> Self {
+        Self::with_num_cells(other.num_cells())
+    }
+
+    fn with_num_cells(num_cells: usize) -> Self {
+        Self {
+            cells: vec![Cell::default(); num_cells],
+        }
+    }
+
+    /// Creates a sketch containing the given ids
+    pub fn from_ids<I: IntoIterator<Item = u64>>(capacity: usize, ids: I) -> Self {
+        let mut sketch = Self::with_capacity(capacity);
+        for id in ids {
+            sketch.insert(id);
+        }
+        sketch
+    }
+
+    pub fn num_cells(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn insert(&mut self, id: u64) {
+        self.update(id, 1);
+    }
+
+    fn update(&mut self, id: u64, delta: i32) {
+        let hash = check_hash(id);
+        for index in self.indexes(id) {
+            let cell = &mut self.cells[index];
+            cell.count = cell.count.wrapping_add(delta);
+            cell.key_sum ^= id;
+            cell.hash_sum ^= hash;
+        }
+    }
+
+    fn indexes(&self, id: u64) -> [usize; NUM_HASHES] {
+        let partition_size = self.cells.len() / NUM_HASHES;
+        let mut indexes = [0usize; NUM_HASHES];
+        for (i, index) in indexes.iter_mut().enumerate() {
+            #[allow(clippy::cast_possible_truncation)]
+            let offset = (mix(id, i as u64) % partition_size as u64) as usize;
+            *index = i * partition_size + offset;
+        }
+        indexes
+    }
+
+    /// Subtracts the remote sketch from this sketch and decodes the difference. Returns None if the sketches have a
+    /// different size or the difference is too large to decode. A difference can never contain more ids than the
+    /// sketch has cells, so decoding is aborted once that many ids have been recovered.
+    pub fn decode_difference(&self, remote: &Sketch) -> Option<SetDifference> {
+        if self.cells.len() != remote.cells.len() {
+            return None;
+        }
+        let mut diff = Sketch {
+            cells: self
+                .cells
+                .iter()
+                .zip(&remote.cells)
+                .map(|(local, remote)| Cell {
+                    count: local.count.wrapping_sub(remote.count),
+                    key_sum: local.key_sum ^ remote.key_sum,
+                    hash_sum: local.hash_sum ^ remote.hash_sum,
+                })
+                .collect(),
+        };
+
+        let mut result = SetDifference::default();
+        let mut pure_cells = (0..diff.cells.len())
+            .filter(|i| diff.cells[*i].is_pure())
+            .collect::<VecDeque<_>>();
+        while let Some(index) = pure_cells.pop_front() {
+            let cell = diff.cells[index];
+            // The cell may have been peeled since it was queued
+            if !cell.is_pure() {
+                continue;
+            }
+            if result.local_only.len() + result.remote_only.len() >= diff.cells.len() {
+                return None;
+            }
+            if cell.count == 1 {
+                result.local_only.push(cell.key_sum);
+            } else {
+                result.remote_only.push(cell.key_sum);
+            }
+            diff.update(cell.key_sum, -cell.count);
+            for i in diff.indexes(cell.key_sum) {
+                if diff.cells[i].is_pure() {
+                    pure_cells.push_back(i);
+                }
+            }
+        }
+
+        if diff.cells.iter().all(Cell::is_empty) {
+            Some(result)
+        } else {
+            None
+        }
+    }
+}
+
+impl From<Sketch> for proto::ReconciliationSketch {
+    fn from(sketch: Sketch) -> Self {
+        let mut msg = proto::ReconciliationSketch::default();
+        for cell in sketch.cells {
+            msg.counts.push(cell.count);
+            msg.key_sums.push(cell.key_sum);
+            msg.hash_sums.push(cell.hash_sum);
+        }
+        msg
+    }
+}
+
+impl TryFrom<proto::ReconciliationSketch> for Sketch {
+    type Error = String;
+
+    fn try_from(msg: proto::ReconciliationSketch) -> Result<Self, Self::Error> {
+        let num_cells = msg.counts.len();
+        if msg.key_sums.len() != num_cells || msg.hash_sums.len() != num_cells {
+            return Err("Sketch cell fields have different lengths".to_string());
+        }
+        if num_cells < MIN_CELLS || num_cells % NUM_HASHES != 0 {
+            return Err(format!("Invalid number of sketch cells {}", num_cells));
+        }
+        let cells = msg
+            .counts
+            .into_iter()
+            .zip(msg.key_sums)
+            .zip(msg.hash_sums)
+            .map(|((count, key_sum), hash_sum)| Cell {
+                count,
+                key_sum,
+                hash_sum,
+            })
+            .collect();
+        Ok(Self { cells })
+    }
+}
+
+fn check_hash(id: u64) -> u64 {
+    mix(id, CHECK_HASH_SEED)
+}
+
+/// A fast 64-bit mixing function (SplitMix64 finaliser). Short ids are already uniformly distributed, so this only
+/// needs to decorrelate the indexes and check hash of an id.
+fn mix(id: u64, seed: u64) -> u64 {
+    let mut z = id ^ seed.wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashSet;
+
+    use rand::{rngs::OsRng, RngCore};
+
+    use super::*;
+
+    fn random_ids(n: usize) -> Vec<u64> {
+        (0..n).map(|_| OsRng.next_u64()).collect()
+    }
+
+    #[test]
+    fn it_decodes_the_set_difference() {
+        let common = random_ids(1000);
+        let local_only = random_ids(20);
+        let remote_only = random_ids(15);
+        let capacity = local_only.len() + remote_only.len();
+        let local = Sketch::from_ids(capacity, common.iter().chain(&local_only).copied());
+        let remote = Sketch::from_ids(capacity, common.iter().chain(&remote_only).copied());
+
+        let diff = local.decode_difference(&remote).unwrap();
+        assert_eq!(
+            diff.local_only.into_iter().collect::<HashSet<_>>(),
+            local_only.into_iter().collect()
+        );
+        assert_eq!(
+            diff.remote_only.into_iter().collect::<HashSet<_>>(),
+            remote_only.into_iter().collect()
+        );
+    }
+
+    #[test]
+    fn it_decodes_identical_sets() {
+        let ids = random_ids(100);
+        let local = Sketch::from_ids(0, ids.clone());
+        let remote = Sketch::from_ids(0, ids);
+        assert_eq!(local.decode_difference(&remote).unwrap(), SetDifference::default());
+    }
+
+    #[test]
+    fn it_fails_to_decode_a_difference_that_is_too_large() {
+        let local = Sketch::from_ids(10, random_ids(200));
+        let remote = Sketch::from_ids(10, random_ids(200));
+        assert!(local.decode_difference(&remote).is_none());
+        assert!(local.decode_difference(&Sketch::with_capacity(100)).is_none());
+    }
+
+    #[test]
+    fn it_converts_to_and_from_proto() {
+        let sketch = Sketch::from_ids(10, random_ids(10));
+        let msg = proto::ReconciliationSketch::from(sketch.clone());
+        assert_eq!(Sketch::try_from(msg).unwrap(), sketch);
+
+        let mut msg = proto::ReconciliationSketch::from(sketch);
+        msg.counts.pop();
+        Sketch::try_from(msg).unwrap_err();
+    }
+
+    #[test]
+    fn it_derives_salted_short_ids() {
+        let sig = Signature::default();
+        assert_eq!(short_id(1, &sig), short_id(1, &sig));
+        assert_ne!(short_id(1, &sig), short_id(2, &sig));
+    }
+}
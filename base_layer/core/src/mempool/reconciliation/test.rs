@@ -0,0 +1,377 @@
+// Copyright 2024. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{
+    convert::{TryFrom, TryInto},
+    fmt,
+    io,
+    sync::Arc,
+    time::Duration,
+};
+
+use futures::{Sink, SinkExt, Stream, StreamExt};
+use tari_common::configuration::Network;
+use tari_comms::{
+    connectivity::ConnectivityEvent,
+    framing,
+    memsocket::MemorySocket,
+    message::MessageExt,
+    peer_manager::PeerFeatures,
+    protocol::{ProtocolEvent, ProtocolNotification, ProtocolNotificationTx},
+    test_utils::{
+        mocks::{create_connectivity_mock, create_peer_connection_mock_pair, ConnectivityManagerMockState},
+        node_identity::build_node_identity,
+    },
+    Bytes,
+    BytesMut,
+};
+use tokio::{sync::mpsc, task, time};
+
+use crate::{
+    consensus::ConsensusManager,
+    mempool::{
+        proto,
+        reconciliation::{
+            sketch::short_id,
+            MempoolReconciliationProtocol,
+            PeerReconciliation,
+            TransactionRelayHandle,
+            MAX_FRAME_SIZE,
+            MEMPOOL_RECONCILIATION_PROTOCOL,
+        },
+        Mempool,
+        MempoolServiceConfig,
+    },
+    transactions::{
+        key_manager::create_memory_db_key_manager,
+        tari_amount::uT,
+        test_helpers::create_tx,
+        transaction_components::Transaction,
+    },
+    validation::mocks::MockValidator,
+};
+
+async fn create_transactions(n: usize) -> Vec<Transaction> {
+    let key_manager = create_memory_db_key_manager();
+    let mut transactions = Vec::new();
+    for _i in 0..n {
+        let (transaction, _, _) = create_tx(5000 * uT, 3 * uT, 1, 2, 1, 3, Default::default(), &key_manager)
+            .await
+            .expect("Failed to get transaction");
+        transactions.push(transaction);
+    }
+    transactions
+}
+
+async fn new_mempool_with_transactions(n: usize) -> (Mempool, Vec<Transaction>) {
+    let mempool = Mempool::new(
+        Default::default(),
+        ConsensusManager::builder(Network::LocalNet).build().unwrap(),
+        Box::new(MockValidator::new(true)),
+    );
+
+    let transactions = create_transactions(n).await;
+    for txn in &transactions {
+        mempool.insert(Arc::new(txn.clone())).await.unwrap();
+    }
+
+    (mempool, transactions)
+}
+
+async fn setup(
+    num_txns: usize,
+) -> (
+    ProtocolNotificationTx<MemorySocket>,
+    ConnectivityManagerMockState,
+    TransactionRelayHandle,
+    Mempool,
+    Vec<Transaction>,
+) {
+    let (protocol_notif_tx, protocol_notif_rx) = mpsc::channel(1);
+    let (mempool, transactions) = new_mempool_with_transactions(num_txns).await;
+    let (connectivity, connectivity_manager_mock) = create_connectivity_mock();
+    let connectivity_manager_mock_state = connectivity_manager_mock.spawn();
+    let relay = TransactionRelayHandle::new();
+    let config = MempoolServiceConfig {
+        reconciliation_interval: Duration::from_millis(50),
+        num_flood_peers: 1,
+        ..Default::default()
+    };
+    let protocol =
+        MempoolReconciliationProtocol::new(config, protocol_notif_rx, mempool.clone(), connectivity, relay.clone());
+
+    task::spawn(protocol.run());
+    connectivity_manager_mock_state.wait_until_event_receivers_ready().await;
+    (
+        protocol_notif_tx,
+        connectivity_manager_mock_state,
+        relay,
+        mempool,
+        transactions,
+    )
+}
+
+#[tokio::test]
+async fn reconcile_as_initiator() {
+    let (_, connectivity_manager_state, relay, mempool1, transactions1) = setup(5).await;
+
+    let node1 = build_node_identity(PeerFeatures::COMMUNICATION_NODE);
+    let node2 = build_node_identity(PeerFeatures::COMMUNICATION_NODE);
+    let (_node1_conn, node1_mock, node2_conn, _) =
+        create_peer_connection_mock_pair(node1.to_peer(), node2.to_peer()).await;
+
+    // This node connected to a peer, so it should initiate reconciliation on the next tick
+    connectivity_manager_state.publish_event(ConnectivityEvent::PeerConnected(node2_conn.into()));
+
+    let substream = node1_mock.next_incoming_substream().await.unwrap();
+    let framed = framing::canonical(substream, MAX_FRAME_SIZE);
+
+    let (mempool2, transactions2) = new_mempool_with_transactions(3).await;
+    mempool2.insert(Arc::new(transactions1[0].clone())).await.unwrap();
+    PeerReconciliation::new(
+        framed,
+        node2.node_id().clone(),
+        mempool2.clone(),
+        TransactionRelayHandle::new(),
+    )
+    .start_responder()
+    .await
+    .unwrap();
+
+    let transactions = wait_for_snapshot(&mempool2, 8).await;
+    assert!(transactions1.iter().all(|txn| transactions.contains(txn)));
+    assert!(transactions2.iter().all(|txn| transactions.contains(txn)));
+
+    let transactions = wait_for_snapshot(&mempool1, 8).await;
+    assert!(transactions1.iter().all(|txn| transactions.contains(txn)));
+    assert!(transactions2.iter().all(|txn| transactions.contains(txn)));
+
+    let stats = relay.stats();
+    assert!(stats.num_reconciliations >= 1);
+    assert!(stats.reconciliation_overhead_bytes > 0);
+    assert!(stats.reconciled_transaction_bytes > 0);
+}
+
+#[tokio::test]
+async fn reconcile_as_responder() {
+    let (protocol_notif, _, _, _, transactions1) = setup(2).await;
+
+    let node1 = build_node_identity(PeerFeatures::COMMUNICATION_NODE);
+    let node2 = build_node_identity(PeerFeatures::COMMUNICATION_NODE);
+
+    let (sock_in, sock_out) = MemorySocket::new_pair();
+    protocol_notif
+        .send(ProtocolNotification::new(
+            MEMPOOL_RECONCILIATION_PROTOCOL.clone(),
+            ProtocolEvent::NewInboundSubstream(node1.node_id().clone(), sock_in),
+        ))
+        .await
+        .unwrap();
+
+    let (mempool2, transactions2) = new_mempool_with_transactions(1).await;
+    mempool2.insert(Arc::new(transactions1[0].clone())).await.unwrap();
+    let framed = framing::canonical(sock_out, MAX_FRAME_SIZE);
+    let relay = TransactionRelayHandle::new();
+    let difference = PeerReconciliation::new(framed, node2.node_id().clone(), mempool2.clone(), relay.clone())
+        .start_initiator(0)
+        .await
+        .unwrap();
+    assert_eq!(difference, 2);
+    assert_eq!(relay.stats().num_sketch_failures, 0);
+
+    let transactions = wait_for_snapshot(&mempool2, 3).await;
+    assert!(transactions1.iter().all(|txn| transactions.contains(txn)));
+    assert!(transactions2.iter().all(|txn| transactions.contains(txn)));
+}
+
+#[tokio::test]
+async fn responder_sends_short_ids_if_sketch_cannot_be_decoded() {
+    let (protocol_notif, _, _, _, transactions1) = setup(2).await;
+
+    let node1 = build_node_identity(PeerFeatures::COMMUNICATION_NODE);
+
+    let (sock_in, sock_out) = MemorySocket::new_pair();
+    protocol_notif
+        .send(ProtocolNotification::new(
+            MEMPOOL_RECONCILIATION_PROTOCOL.clone(),
+            ProtocolEvent::NewInboundSubstream(node1.node_id().clone(), sock_in),
+        ))
+        .await
+        .unwrap();
+
+    let mut framed = framing::canonical(sock_out, MAX_FRAME_SIZE);
+    let salt = 123;
+    write_message(&mut framed, proto::ReconciliationRequest {
+        salt,
+        set_size: 2,
+        expected_difference: 0,
+    })
+    .await;
+    let sketch: proto::ReconciliationSketch = read_message(&mut framed).await;
+    assert_eq!(sketch.set_size, 2);
+    assert!(!sketch.counts.is_empty());
+
+    // Pretend that the sketch could not be decoded
+    write_message(&mut framed, proto::ReconciliationDifference {
+        request_short_ids: true,
+        missing: vec![],
+        sending: vec![],
+    })
+    .await;
+    let short_ids: proto::ShortTransactionIds = read_message(&mut framed).await;
+    let expected_id = short_id(salt, transactions1[0].first_kernel_excess_sig().unwrap());
+    assert_eq!(short_ids.ids.len(), 2);
+    assert!(short_ids.ids.contains(&expected_id));
+
+    write_message(&mut framed, proto::ReconciliationDifference {
+        request_short_ids: false,
+        missing: vec![expected_id],
+        sending: vec![],
+    })
+    .await;
+    // This peer has no transactions to send
+    write_message(&mut framed, proto::TransactionItem::empty()).await;
+
+    let transaction: proto::TransactionItem = read_message(&mut framed).await;
+    let transaction = Transaction::try_from(transaction.transaction.unwrap()).unwrap();
+    assert_eq!(transaction, transactions1[0]);
+    let stop: proto::TransactionItem = read_message(&mut framed).await;
+    assert!(stop.transaction.is_none());
+    assert!(framed.next().await.is_none());
+}
+
+#[tokio::test]
+async fn it_rejects_transactions_that_were_not_announced() {
+    let (_, transactions2) = new_mempool_with_transactions(1).await;
+    let (protocol_notif, _, _, mempool1, _) = setup(1).await;
+    let (sock_in, sock_out) = MemorySocket::new_pair();
+    let peer = build_node_identity(PeerFeatures::COMMUNICATION_NODE);
+    protocol_notif
+        .send(ProtocolNotification::new(
+            MEMPOOL_RECONCILIATION_PROTOCOL.clone(),
+            ProtocolEvent::NewInboundSubstream(peer.node_id().clone(), sock_in),
+        ))
+        .await
+        .unwrap();
+
+    let mut framed = framing::canonical(sock_out, MAX_FRAME_SIZE);
+    write_message(&mut framed, proto::ReconciliationRequest {
+        salt: 123,
+        set_size: 1,
+        expected_difference: 0,
+    })
+    .await;
+    let _sketch: proto::ReconciliationSketch = read_message(&mut framed).await;
+    write_message(&mut framed, proto::ReconciliationDifference {
+        request_short_ids: false,
+        missing: vec![],
+        sending: vec![],
+    })
+    .await;
+    write_message(&mut framed, proto::TransactionItem {
+        transaction: Some(transactions2[0].clone().try_into().unwrap()),
+    })
+    .await;
+
+    // The responder aborts the session without inserting the transaction
+    assert!(framed.next().await.is_none());
+    let excess_sig = transactions2[0].first_kernel_excess_sig().unwrap();
+    assert!(!mempool1
+        .has_tx_with_excess_sig(excess_sig.clone())
+        .await
+        .unwrap()
+        .is_stored());
+}
+
+#[tokio::test]
+async fn it_limits_flooding_to_outbound_peers() {
+    let (_, connectivity_manager_state, relay, _, _) = setup(0).await;
+
+    let node1 = build_node_identity(PeerFeatures::COMMUNICATION_NODE);
+    let mut outbound_peers = Vec::new();
+    let mut mocks = Vec::new();
+    for _ in 0..3 {
+        let peer = build_node_identity(PeerFeatures::COMMUNICATION_NODE);
+        let (_, peer_mock, conn, node1_mock) = create_peer_connection_mock_pair(peer.to_peer(), node1.to_peer()).await;
+        connectivity_manager_state.publish_event(ConnectivityEvent::PeerConnected(conn.into()));
+        outbound_peers.push(peer.node_id().clone());
+        mocks.push((peer_mock, node1_mock));
+    }
+
+    // Only the first outbound peer is flooded (num_flood_peers = 1)
+    let flood_peers = wait_for(|| relay.flood_peers().filter(|peers| !peers.is_empty())).await;
+    assert_eq!(flood_peers, vec![outbound_peers[0].clone()]);
+
+    connectivity_manager_state.publish_event(ConnectivityEvent::PeerDisconnected(outbound_peers[0].clone()));
+    let flood_peers = wait_for(|| {
+        relay
+            .flood_peers()
+            .filter(|peers| !peers.is_empty() && peers[0] != outbound_peers[0])
+    })
+    .await;
+    assert_eq!(flood_peers, vec![outbound_peers[1].clone()]);
+}
+
+async fn wait_for<T, F: Fn() -> Option<T>>(f: F) -> T {
+    time::timeout(Duration::from_secs(10), async {
+        loop {
+            if let Some(v) = f() {
+                break v;
+            }
+            time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .unwrap()
+}
+
+async fn wait_for_snapshot(mempool: &Mempool, num_txns: usize) -> Vec<Transaction> {
+    time::timeout(Duration::from_secs(10), async {
+        loop {
+            let transactions = mempool.snapshot().await.unwrap();
+            if transactions.len() >= num_txns {
+                break transactions.iter().map(|t| &**t).cloned().collect::<Vec<_>>();
+            }
+            time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .unwrap()
+}
+
+async fn read_message<S, T>(reader: &mut S) -> T
+where
+    S: Stream<Item = io::Result<BytesMut>> + Unpin,
+    T: prost::Message + Default,
+{
+    let msg = reader.next().await.unwrap().unwrap();
+    T::decode(&mut msg.freeze()).unwrap()
+}
+
+async fn write_message<S, T>(writer: &mut S, message: T)
+where
+    S: Sink<Bytes> + Unpin,
+    S::Error: fmt::Debug,
+    T: prost::Message,
+{
+    writer.send(message.to_encoded_bytes().into()).await.unwrap();
+}
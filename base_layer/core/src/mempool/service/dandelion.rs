@@ -0,0 +1,327 @@
+// Copyright 2024. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Dandelion++ transaction relay.
+//!
+//! Time is divided into epochs (`DandelionConfig::epoch_duration`). At the start of each epoch a node selects a few of
+//! its outbound base node peers as stem peers, and decides with probability `DandelionConfig::fluff_probability`
+//! whether it is a fluff node for the epoch. Transactions received in the stem phase are relayed to a single stem peer,
+//! chosen once per epoch for each inbound peer, until they reach a fluff node which inserts them into its mempool and
+//! floods them as usual. Transactions created by this node are always stemmed.
+//!
+//! Stemmed transactions are kept in a stem pool rather than the mempool, so that they are not revealed by mempool sync
+//! or reconciliation. Each stemmed transaction has an embargo timer; if it has not been received in the fluff phase
+//! before the timer expires, this node fluffs it itself.
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, MutexGuard},
+    time::Instant,
+};
+
+use log::*;
+use rand::{rngs::OsRng, seq::SliceRandom, Rng};
+use tari_common_types::types::Signature;
+use tari_comms::peer_manager::NodeId;
+use tari_utilities::ByteArray;
+
+use crate::{mempool::DandelionConfig, transactions::transaction_components::Transaction};
+
+const LOG_TARGET: &str = "c::mp::service::dandelion";
+/// The maximum number of transactions in the stem phase. Once full, new transactions are fluffed immediately.
+const MAX_STEM_POOL_SIZE: usize = 1_000;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RelayDecision {
+    /// Relay the transaction to this stem peer only
+    Stem(NodeId),
+    /// Insert the transaction into the mempool and flood it to peers
+    Fluff,
+}
+
+#[derive(Clone)]
+pub struct DandelionRelay {
+    config: DandelionConfig,
+    state: Arc<Mutex<DandelionState>>,
+}
+
+#[derive(Default)]
+struct DandelionState {
+    epoch: Option<Epoch>,
+    stem_pool: HashMap<Vec<u8>, StemTransaction>,
+}
+
+struct Epoch {
+    expires_at: Instant,
+    is_fluff: bool,
+    stem_peers: Vec<NodeId>,
+    /// The stem peer that transactions from each inbound peer (or this node, if None) are relayed to
+    routes: HashMap<Option<NodeId>, NodeId>,
+}
+
+struct StemTransaction {
+    transaction: Arc<Transaction>,
+    embargo_expires_at: Instant,
+}
+
+impl DandelionRelay {
+    pub fn new(config: DandelionConfig) -> Self {
+        Self {
+            config,
+            state: Default::default(),
+        }
+    }
+
+    /// Starts a new epoch if the current epoch has expired or one of its stem peers is no longer connected
+    pub fn update_epoch(&self, outbound_peers: Vec<NodeId>) {
+        let mut state = self.lock_state();
+        let is_current = state.epoch.as_ref().map_or(false, |epoch| {
+            epoch.expires_at > Instant::now() &&
+                !epoch.stem_peers.is_empty() &&
+                epoch.stem_peers.iter().all(|peer| outbound_peers.contains(peer))
+        });
+        if is_current {
+            return;
+        }
+
+        let mut stem_peers = outbound_peers;
+        stem_peers.shuffle(&mut OsRng);
+        stem_peers.truncate(self.config.num_stem_peers);
+        let is_fluff = OsRng.gen_bool(self.config.fluff_probability.clamp(0.0, 1.0));
+        debug!(
+            target: LOG_TARGET,
+            "Starting new dandelion epoch in {} mode with {} stem peer(s)",
+            if is_fluff { "fluff" } else { "stem" },
+            stem_peers.len()
+        );
+        state.epoch = Some(Epoch {
+            expires_at: Instant::now() + self.config.epoch_duration,
+            is_fluff,
+            stem_peers,
+            routes: HashMap::new(),
+        });
+    }
+
+    /// Decides how to relay a transaction in the stem phase received from `source_peer`, or created by this node if
+    /// None
+    pub fn route(&self, source_peer: Option<&NodeId>) -> RelayDecision {
+        let mut state = self.lock_state();
+        let epoch = match state.epoch.as_mut() {
+            Some(epoch) => epoch,
+            None => return RelayDecision::Fluff,
+        };
+        if epoch.is_fluff && source_peer.is_some() {
+            return RelayDecision::Fluff;
+        }
+
+        // Never send a transaction back to the peer it came from
+        let candidates = epoch
+            .stem_peers
+            .iter()
+            .filter(|peer| Some(*peer) != source_peer)
+            .collect::<Vec<_>>();
+        let key = source_peer.cloned();
+        if let Some(route) = epoch.routes.get(&key) {
+            if candidates.contains(&route) {
+                return RelayDecision::Stem(route.clone());
+            }
+        }
+        let route = match candidates.choose(&mut OsRng) {
+            Some(peer) => (*peer).clone(),
+            None => return RelayDecision::Fluff,
+        };
+        epoch.routes.insert(key, route.clone());
+        RelayDecision::Stem(route)
+    }
+
+    /// Adds a transaction to the stem pool and starts its embargo timer. Returns false if the transaction is already in
+    /// the stem pool or the stem pool is full.
+    pub fn add_stem_transaction(&self, transaction: Arc<Transaction>) -> bool {
+        let key = match stem_pool_key(&transaction) {
+            Some(key) => key,
+            None => return false,
+        };
+        let mut state = self.lock_state();
+        if state.stem_pool.len() >= MAX_STEM_POOL_SIZE || state.stem_pool.contains_key(&key) {
+            return false;
+        }
+        let embargo = self.config.embargo_timeout.mul_f64(OsRng.gen_range(1.0..2.0));
+        state.stem_pool.insert(key, StemTransaction {
+            transaction,
+            embargo_expires_at: Instant::now() + embargo,
+        });
+        true
+    }
+
+    /// Returns true if a transaction with the given excess signature is in the stem phase
+    pub fn contains(&self, excess_sig: &Signature) -> bool {
+        self.lock_state()
+            .stem_pool
+            .contains_key(excess_sig.get_signature().as_bytes())
+    }
+
+    /// Removes a transaction from the stem pool, typically because it has been received in the fluff phase
+    pub fn remove(&self, transaction: &Transaction) {
+        if let Some(key) = stem_pool_key(transaction) {
+            self.lock_state().stem_pool.remove(&key);
+        }
+    }
+
+    /// Removes and returns the transactions whose embargo timer has expired
+    pub fn take_expired(&self) -> Vec<Arc<Transaction>> {
+        let now = Instant::now();
+        let mut state = self.lock_state();
+        let expired = state
+            .stem_pool
+            .iter()
+            .filter(|(_, stem_tx)| stem_tx.embargo_expires_at <= now)
+            .map(|(key, _)| key.clone())
+            .collect::<Vec<_>>();
+        expired
+            .iter()
+            .filter_map(|key| state.stem_pool.remove(key))
+            .map(|stem_tx| stem_tx.transaction)
+            .collect()
+    }
+
+    fn lock_state(&self) -> MutexGuard<'_, DandelionState> {
+        self.state.lock().expect("dandelion state lock poisoned")
+    }
+}
+
+fn stem_pool_key(transaction: &Transaction) -> Option<Vec<u8>> {
+    transaction
+        .first_kernel_excess_sig()
+        .map(|sig| sig.get_signature().to_vec())
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use tari_comms::{peer_manager::PeerFeatures, test_utils::node_identity::build_node_identity};
+
+    use super::*;
+    use crate::transactions::{key_manager::create_memory_db_key_manager, tari_amount::uT, test_helpers::create_tx};
+
+    fn random_node_id() -> NodeId {
+        build_node_identity(PeerFeatures::COMMUNICATION_NODE).node_id().clone()
+    }
+
+    fn stem_config() -> DandelionConfig {
+        DandelionConfig {
+            enabled: true,
+            fluff_probability: 0.0,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn it_fluffs_without_an_epoch() {
+        let relay = DandelionRelay::new(stem_config());
+        assert_eq!(relay.route(None), RelayDecision::Fluff);
+        relay.update_epoch(vec![]);
+        assert_eq!(relay.route(None), RelayDecision::Fluff);
+    }
+
+    #[test]
+    fn it_uses_the_same_stem_peer_for_each_source_within_an_epoch() {
+        let relay = DandelionRelay::new(stem_config());
+        let peers = (0..5).map(|_| random_node_id()).collect::<Vec<_>>();
+        relay.update_epoch(peers.clone());
+
+        let route = relay.route(None);
+        let stem_peer = match &route {
+            RelayDecision::Stem(peer) => peer.clone(),
+            RelayDecision::Fluff => panic!("Expected stem"),
+        };
+        assert!(peers.contains(&stem_peer));
+        for _ in 0..10 {
+            assert_eq!(relay.route(None), route);
+        }
+
+        let source = stem_peer.clone();
+        for _ in 0..10 {
+            assert_ne!(relay.route(Some(&source)), RelayDecision::Stem(source.clone()));
+        }
+    }
+
+    #[test]
+    fn it_fluffs_relayed_transactions_in_a_fluff_epoch() {
+        let relay = DandelionRelay::new(DandelionConfig {
+            fluff_probability: 1.0,
+            ..stem_config()
+        });
+        let peers = (0..2).map(|_| random_node_id()).collect::<Vec<_>>();
+        relay.update_epoch(peers);
+        assert_eq!(relay.route(Some(&random_node_id())), RelayDecision::Fluff);
+        // Transactions created by this node are always stemmed
+        assert!(matches!(relay.route(None), RelayDecision::Stem(_)));
+    }
+
+    #[test]
+    fn it_starts_a_new_epoch_when_a_stem_peer_disconnects() {
+        let relay = DandelionRelay::new(DandelionConfig {
+            num_stem_peers: 1,
+            ..stem_config()
+        });
+        let peers = (0..2).map(|_| random_node_id()).collect::<Vec<_>>();
+        relay.update_epoch(peers.clone());
+        let stem_peer = match relay.route(None) {
+            RelayDecision::Stem(peer) => peer,
+            RelayDecision::Fluff => panic!("Expected stem"),
+        };
+
+        let remaining = peers.into_iter().filter(|p| *p != stem_peer).collect::<Vec<_>>();
+        relay.update_epoch(remaining.clone());
+        assert_eq!(relay.route(None), RelayDecision::Stem(remaining[0].clone()));
+    }
+
+    #[tokio::test]
+    async fn it_expires_embargoed_transactions() {
+        let relay = DandelionRelay::new(DandelionConfig {
+            embargo_timeout: Duration::from_secs(0),
+            ..stem_config()
+        });
+        let key_manager = create_memory_db_key_manager();
+        let (tx, _, _) = create_tx(5000 * uT, 3 * uT, 1, 2, 1, 3, Default::default(), &key_manager)
+            .await
+            .unwrap();
+        let tx = Arc::new(tx);
+        let excess_sig = tx.first_kernel_excess_sig().unwrap().clone();
+
+        assert!(relay.add_stem_transaction(tx.clone()));
+        assert!(!relay.add_stem_transaction(tx.clone()));
+        assert!(relay.contains(&excess_sig));
+
+        let expired = relay.take_expired();
+        assert_eq!(expired, vec![tx.clone()]);
+        assert!(!relay.contains(&excess_sig));
+
+        assert!(relay.add_stem_transaction(tx.clone()));
+        relay.remove(&tx);
+        assert!(!relay.contains(&excess_sig));
+        // A transaction without kernels cannot be stemmed
+        let no_kernels = Transaction::new(vec![], vec![], vec![], Default::default(), Default::default());
+        assert!(!relay.add_stem_transaction(Arc::new(no_kernels)));
+    }
+}
@@ -59,6 +59,8 @@ impl MempoolHandle {
         }
     }
 
+    /// The state of the transaction as it may be reported to remote peers, see
+    /// [MempoolRequest::GetTxStateByExcessSig]
     pub async fn get_tx_state_by_excess_sig(
         &mut self,
         sig: Signature,
@@ -32,7 +32,13 @@ use crate::{
     base_node::comms_interface::{BlockEvent, BlockEvent::AddBlockErrored},
     chain_storage::BlockAddResult,
     mempool::{
-        service::{MempoolRequest, MempoolResponse, MempoolServiceError, OutboundMempoolServiceInterface},
+        service::{
+            dandelion::{DandelionRelay, RelayDecision},
+            MempoolRequest,
+            MempoolResponse,
+            MempoolServiceError,
+            OutboundMempoolServiceInterface,
+        },
         Mempool,
         TxStorageResponse,
     },
@@ -47,27 +53,50 @@ pub const LOG_TARGET: &str = "c::mp::service::inbound_handlers";
 pub struct MempoolInboundHandlers {
     mempool: Mempool,
     outbound_service: OutboundMempoolServiceInterface,
+    dandelion: Option<DandelionRelay>,
 }
 
 impl MempoolInboundHandlers {
-    /// Construct the MempoolInboundHandlers.
-    pub fn new(mempool: Mempool, outbound_service: OutboundMempoolServiceInterface) -> Self {
+    /// Construct the MempoolInboundHandlers. If `dandelion` is set, transactions are relayed using Dandelion++.
+    pub fn new(
+        mempool: Mempool,
+        outbound_service: OutboundMempoolServiceInterface,
+        dandelion: Option<DandelionRelay>,
+    ) -> Self {
         Self {
             mempool,
             outbound_service,
+            dandelion,
         }
     }
 
     /// Handle inbound Mempool service requests from remote nodes and local services.
     pub async fn handle_request(&mut self, request: MempoolRequest) -> Result<MempoolResponse, MempoolServiceError> {
         debug!(target: LOG_TARGET, "Handling remote request: {}", request);
-        use MempoolRequest::{GetFeePerGramStats, GetState, GetStats, GetTxStateByExcessSig, SubmitTransaction};
+        use MempoolRequest::{
+            GetFeePerGramStats,
+            GetLocalTxStateByExcessSig,
+            GetState,
+            GetStats,
+            GetTxStateByExcessSig,
+            SubmitTransaction,
+        };
         match request {
             GetStats => Ok(MempoolResponse::Stats(self.mempool.stats().await?)),
             GetState => Ok(MempoolResponse::State(self.mempool.state().await?)),
+            // Stem transactions are not in the mempool yet, so they are reported as not stored
             GetTxStateByExcessSig(excess_sig) => Ok(MempoolResponse::TxStorage(
                 self.mempool.has_tx_with_excess_sig(excess_sig).await?,
             )),
+            GetLocalTxStateByExcessSig(excess_sig) => {
+                let is_stem = self.dandelion.as_ref().map_or(false, |d| d.contains(&excess_sig));
+                let tx_storage = self.mempool.has_tx_with_excess_sig(excess_sig).await?;
+                // Transactions in the Dandelion stem phase are added to the unconfirmed pool once they are fluffed
+                if is_stem && !tx_storage.is_stored() {
+                    return Ok(MempoolResponse::TxStorage(TxStorageResponse::UnconfirmedPool));
+                }
+                Ok(MempoolResponse::TxStorage(tx_storage))
+            },
             SubmitTransaction(tx) => {
                 let first_tx_kernel_excess_sig = tx
                     .first_kernel_excess_sig()
@@ -79,7 +108,12 @@ impl MempoolInboundHandlers {
                     "Transaction ({}) submitted using request.",
                     first_tx_kernel_excess_sig,
                 );
-                Ok(MempoolResponse::TxStorage(self.submit_transaction(tx, None).await?))
+                let tx = Arc::new(tx);
+                let tx_storage = match self.dandelion.as_ref().map(|d| d.route(None)) {
+                    Some(RelayDecision::Stem(stem_peer)) => self.stem_transaction(tx, stem_peer, None).await?,
+                    _ => self.submit_transaction(tx, None).await?,
+                };
+                Ok(MempoolResponse::TxStorage(tx_storage))
             },
             GetFeePerGramStats { count, tip_height } => {
                 let stats = self.mempool.get_fee_per_gram_stats(count, tip_height).await?;
@@ -108,19 +142,112 @@ impl MempoolInboundHandlers {
                 .map(|p| format!("remote peer: {}", p))
                 .unwrap_or_else(|| "local services".to_string())
         );
-        self.submit_transaction(tx, source_peer).await?;
+        // The transaction has been fluffed by another node, so its embargo timer can be cancelled
+        if let Some(dandelion) = self.dandelion.as_ref() {
+            dandelion.remove(&tx);
+        }
+        self.submit_transaction(Arc::new(tx), source_peer).await?;
         Ok(())
     }
 
+    /// Handle transactions received from remote peers in the Dandelion stem phase.
+    pub async fn handle_stem_transaction(
+        &mut self,
+        tx: Transaction,
+        source_peer: NodeId,
+    ) -> Result<(), MempoolServiceError> {
+        debug!(
+            target: LOG_TARGET,
+            "Stem transaction ({}) received from remote peer: {}.",
+            tx.first_kernel_excess_sig()
+                .ok_or(MempoolServiceError::TransactionNoKernels)?
+                .get_signature()
+                .to_hex(),
+            source_peer
+        );
+        let tx = Arc::new(tx);
+        let decision = self
+            .dandelion
+            .as_ref()
+            .map(|d| d.route(Some(&source_peer)))
+            .unwrap_or(RelayDecision::Fluff);
+        match decision {
+            RelayDecision::Stem(stem_peer) => self.stem_transaction(tx, stem_peer, Some(source_peer)).await?,
+            RelayDecision::Fluff => self.submit_transaction(tx, Some(source_peer)).await?,
+        };
+        Ok(())
+    }
+
+    /// Ends the stem phase of a transaction, either because its embargo timer expired or it could not be sent to the
+    /// stem peer, by inserting it into the mempool and propagating it.
+    pub async fn fluff_transaction(&mut self, tx: Arc<Transaction>) -> Result<TxStorageResponse, MempoolServiceError> {
+        if let Some(dandelion) = self.dandelion.as_ref() {
+            dandelion.remove(&tx);
+        }
+        self.submit_transaction(tx, None).await
+    }
+
+    /// Validates a transaction and relays it to the stem peer, without inserting it into the mempool.
+    async fn stem_transaction(
+        &mut self,
+        tx: Arc<Transaction>,
+        stem_peer: NodeId,
+        source_peer: Option<NodeId>,
+    ) -> Result<TxStorageResponse, MempoolServiceError> {
+        let dandelion = match self.dandelion.clone() {
+            Some(dandelion) => dandelion,
+            None => return self.submit_transaction(tx, source_peer).await,
+        };
+        let excess_sig = tx
+            .first_kernel_excess_sig()
+            .ok_or(MempoolServiceError::TransactionNoKernels)?
+            .clone();
+        // Already relayed or fluffed
+        if dandelion.contains(&excess_sig) {
+            return Ok(TxStorageResponse::UnconfirmedPool);
+        }
+        let tx_storage = self.mempool.has_transaction(tx.clone()).await?;
+        if tx_storage.is_stored() {
+            return Ok(tx_storage);
+        }
+
+        let tx_storage = self.mempool.check_transaction(tx.clone()).await?;
+        if !matches!(tx_storage, TxStorageResponse::UnconfirmedPool) {
+            debug!(
+                target: LOG_TARGET,
+                "Not relaying invalid stem transaction {}: {}",
+                excess_sig.get_signature().to_hex(),
+                tx_storage
+            );
+            return Ok(tx_storage);
+        }
+        if !dandelion.add_stem_transaction(tx.clone()) {
+            debug!(
+                target: LOG_TARGET,
+                "Stem pool is full. Fluffing transaction {}",
+                excess_sig.get_signature().to_hex()
+            );
+            return self.submit_transaction(tx, source_peer).await;
+        }
+
+        debug!(
+            target: LOG_TARGET,
+            "Relaying stem transaction ({}) to peer {}.",
+            excess_sig.get_signature().to_hex(),
+            stem_peer
+        );
+        self.outbound_service.stem_tx(tx, stem_peer).await?;
+        Ok(tx_storage)
+    }
+
     /// Submits a transaction to the mempool and propagate valid transactions.
     async fn submit_transaction(
         &mut self,
-        tx: Transaction,
+        tx: Arc<Transaction>,
         source_peer: Option<NodeId>,
     ) -> Result<TxStorageResponse, MempoolServiceError> {
         trace!(target: LOG_TARGET, "submit_transaction: {}.", tx);
 
-        let tx = Arc::new(tx);
         let tx_storage = self.mempool.has_transaction(tx.clone()).await?;
         let kernel_excess_sig = tx
             .first_kernel_excess_sig()
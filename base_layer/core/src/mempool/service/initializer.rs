@@ -24,6 +24,7 @@ use std::{convert::TryFrom, sync::Arc};
 
 use futures::{Stream, StreamExt};
 use log::*;
+use tari_comms::connectivity::ConnectivityRequester;
 use tari_comms_dht::Dht;
 use tari_p2p::{
     comms_connector::{PeerMessage, SubscriptionFactory},
@@ -44,12 +45,15 @@ use crate::{
     mempool::{
         mempool::Mempool,
         service::{
+            dandelion::DandelionRelay,
             inbound_handlers::MempoolInboundHandlers,
             local_service::LocalMempoolService,
             outbound_interface::OutboundMempoolServiceInterface,
             service::{MempoolService, MempoolStreams},
             MempoolHandle,
         },
+        MempoolServiceConfig,
+        TransactionRelayHandle,
     },
     proto,
     transactions::transaction_components::Transaction,
@@ -60,23 +64,32 @@ const SUBSCRIPTION_LABEL: &str = "Mempool";
 
 /// Initializer for the Mempool service and service future.
 pub struct MempoolServiceInitializer {
+    config: MempoolServiceConfig,
     mempool: Mempool,
     inbound_message_subscription_factory: Arc<SubscriptionFactory>,
 }
 
 impl MempoolServiceInitializer {
     /// Create a new MempoolServiceInitializer from the inbound message subscriber.
-    pub fn new(mempool: Mempool, inbound_message_subscription_factory: Arc<SubscriptionFactory>) -> Self {
+    pub fn new(
+        config: MempoolServiceConfig,
+        mempool: Mempool,
+        inbound_message_subscription_factory: Arc<SubscriptionFactory>,
+    ) -> Self {
         Self {
+            config,
             mempool,
             inbound_message_subscription_factory,
         }
     }
 
-    /// Create a stream of 'New Transaction` messages
-    fn inbound_transaction_stream(&self) -> impl Stream<Item = DomainMessage<Transaction>> {
+    /// Create a stream of transaction messages of the given type ('New Transaction` or 'Stem Transaction`)
+    fn inbound_transaction_stream(
+        &self,
+        message_type: TariMessageType,
+    ) -> impl Stream<Item = DomainMessage<Transaction>> {
         self.inbound_message_subscription_factory
-            .get_subscription(TariMessageType::NewTransaction, SUBSCRIPTION_LABEL)
+            .get_subscription(message_type, SUBSCRIPTION_LABEL)
             .filter_map(extract_transaction)
     }
 }
@@ -116,7 +129,8 @@ async fn extract_transaction(msg: Arc<PeerMessage>) -> Option<DomainMessage<Tran
 impl ServiceInitializer for MempoolServiceInitializer {
     async fn initialize(&mut self, context: ServiceInitializerContext) -> Result<(), ServiceInitializationError> {
         // Create streams for receiving Mempool service requests and response messages from comms
-        let inbound_transaction_stream = self.inbound_transaction_stream();
+        let inbound_transaction_stream = self.inbound_transaction_stream(TariMessageType::NewTransaction);
+        let inbound_stem_transaction_stream = self.inbound_transaction_stream(TariMessageType::StemTransaction);
 
         // Connect MempoolOutboundServiceHandle to MempoolService
         let (request_sender, request_receiver) = reply_channel::unbounded();
@@ -124,10 +138,17 @@ impl ServiceInitializer for MempoolServiceInitializer {
         context.register_handle(mempool_handle);
 
         let (outbound_tx_sender, outbound_tx_stream) = mpsc::unbounded_channel();
+        let (outbound_stem_tx_sender, outbound_stem_tx_stream) = mpsc::unbounded_channel();
         let (local_request_sender_service, local_request_stream) = reply_channel::unbounded();
-        let outbound_mp_interface = OutboundMempoolServiceInterface::new(outbound_tx_sender);
-        let local_mp_interface = LocalMempoolService::new(local_request_sender_service);
-        let inbound_handlers = MempoolInboundHandlers::new(self.mempool.clone(), outbound_mp_interface.clone());
+        let outbound_mp_interface = OutboundMempoolServiceInterface::new(outbound_tx_sender, outbound_stem_tx_sender);
+        let local_mp_interface = LocalMempoolService::new(local_request_sender_service, self.mempool.event_sender());
+        let dandelion = if self.config.dandelion.enabled {
+            Some(DandelionRelay::new(self.config.dandelion.clone()))
+        } else {
+            None
+        };
+        let inbound_handlers =
+            MempoolInboundHandlers::new(self.mempool.clone(), outbound_mp_interface.clone(), dandelion.clone());
 
         // Register handle to OutboundMempoolServiceInterface before waiting for handles to be ready
         context.register_handle(outbound_mp_interface);
@@ -136,16 +157,28 @@ impl ServiceInitializer for MempoolServiceInitializer {
         context.spawn_until_shutdown(move |handles| {
             let outbound_message_service = handles.expect_handle::<Dht>().outbound_requester();
             let base_node = handles.expect_handle::<LocalNodeCommsInterface>();
+            let connectivity = handles.expect_handle::<ConnectivityRequester>();
+            // Only present if mempool reconciliation is enabled
+            let relay = handles.get_handle::<TransactionRelayHandle>();
 
             let streams = MempoolStreams {
                 outbound_tx_stream,
+                outbound_stem_tx_stream,
                 inbound_transaction_stream,
+                inbound_stem_transaction_stream,
                 local_request_stream,
                 block_event_stream: base_node.get_block_event_stream(),
                 request_receiver,
             };
             debug!(target: LOG_TARGET, "Mempool service started");
-            MempoolService::new(outbound_message_service, inbound_handlers).start(streams)
+            MempoolService::new(
+                outbound_message_service,
+                inbound_handlers,
+                relay,
+                connectivity,
+                dandelion,
+            )
+            .start(streams)
         });
 
         Ok(())
@@ -26,6 +26,8 @@ use tari_service_framework::{reply_channel::SenderService, Service};
 use crate::{
     mempool::{
         service::{MempoolRequest, MempoolResponse, MempoolServiceError},
+        MempoolEventReceiver,
+        MempoolEventSender,
         StateResponse,
         StatsResponse,
         TxStorageResponse,
@@ -45,6 +47,7 @@ pub type LocalMempoolRequester = SenderService<MempoolRequest, Result<MempoolRes
 #[derive(Clone)]
 pub struct LocalMempoolService {
     request_sender: LocalMempoolRequester,
+    event_sender: MempoolEventSender,
 }
 
 impl LocalMempoolService {
@@ -54,8 +57,16 @@ impl LocalMempoolService {
     ///
     /// To make things a little more ergonomic, the channel handling is done for you in the other member functions,
     /// such that the request behaves like a standard future.
-    pub fn new(request_sender: LocalMempoolRequester) -> Self {
-        LocalMempoolService { request_sender }
+    pub fn new(request_sender: LocalMempoolRequester, event_sender: MempoolEventSender) -> Self {
+        LocalMempoolService {
+            request_sender,
+            event_sender,
+        }
+    }
+
+    /// Subscribe to events describing changes to the unconfirmed pool
+    pub fn get_mempool_event_stream(&self) -> MempoolEventReceiver {
+        self.event_sender.subscribe()
     }
 
     /// Returns a future that resolves to the current mempool statistics
@@ -87,13 +98,14 @@ impl LocalMempoolService {
         }
     }
 
+    /// The state of the transaction, including the Dandelion stem phase. Not to be exposed to remote peers.
     pub async fn get_transaction_state_by_excess_sig(
         &mut self,
         sig: Signature,
     ) -> Result<TxStorageResponse, MempoolServiceError> {
         match self
             .request_sender
-            .call(MempoolRequest::GetTxStateByExcessSig(sig))
+            .call(MempoolRequest::GetLocalTxStateByExcessSig(sig))
             .await??
         {
             MempoolResponse::TxStorage(s) => Ok(s),
@@ -106,7 +118,7 @@ impl LocalMempoolService {
 mod test {
     use futures::StreamExt;
     use tari_service_framework::reply_channel::{unbounded, Receiver};
-    use tokio::task;
+    use tokio::{sync::broadcast, task};
 
     use crate::mempool::{
         service::{local_service::LocalMempoolService, MempoolRequest, MempoolResponse},
@@ -138,7 +150,8 @@ mod test {
     #[tokio::test]
     async fn mempool_stats() {
         let (tx, rx) = unbounded();
-        let mut service = LocalMempoolService::new(tx);
+        let (event_sender, _) = broadcast::channel(1);
+        let mut service = LocalMempoolService::new(tx, event_sender);
         task::spawn(mock_handler(rx));
         let stats = service.get_mempool_stats().await;
         let stats = stats.expect("get_mempool_stats should have succeeded");
@@ -148,7 +161,8 @@ mod test {
     #[tokio::test]
     async fn mempool_stats_from_multiple() {
         let (tx, rx) = unbounded();
-        let mut service = LocalMempoolService::new(tx);
+        let (event_sender, _) = broadcast::channel(1);
+        let mut service = LocalMempoolService::new(tx, event_sender);
         let mut service2 = service.clone();
         task::spawn(mock_handler(rx));
         let stats = service.get_mempool_stats().await;
@@ -25,6 +25,9 @@ mod error;
 #[cfg(feature = "base_node")]
 pub use error::MempoolServiceError;
 
+#[cfg(feature = "base_node")]
+mod dandelion;
+
 #[cfg(feature = "base_node")]
 mod inbound_handlers;
 
@@ -35,12 +35,19 @@ pub const LOG_TARGET: &str = "c::mp::service::outbound_interface";
 #[derive(Clone)]
 pub struct OutboundMempoolServiceInterface {
     tx_sender: UnboundedSender<(Arc<Transaction>, Vec<NodeId>)>,
+    stem_tx_sender: UnboundedSender<(Arc<Transaction>, NodeId)>,
 }
 
 impl OutboundMempoolServiceInterface {
     /// Construct a new OutboundMempoolServiceInterface with the specified SenderService.
-    pub fn new(tx_sender: UnboundedSender<(Arc<Transaction>, Vec<NodeId>)>) -> Self {
-        Self { tx_sender }
+    pub fn new(
+        tx_sender: UnboundedSender<(Arc<Transaction>, Vec<NodeId>)>,
+        stem_tx_sender: UnboundedSender<(Arc<Transaction>, NodeId)>,
+    ) -> Self {
+        Self {
+            tx_sender,
+            stem_tx_sender,
+        }
     }
 
     /// Transmit a transaction to remote base nodes, excluding the provided peers.
@@ -54,4 +61,16 @@ impl OutboundMempoolServiceInterface {
             MempoolServiceError::BroadcastFailed
         })
     }
+
+    /// Relay a transaction in the Dandelion stem phase to a single stem peer.
+    pub async fn stem_tx(
+        &mut self,
+        transaction: Arc<Transaction>,
+        stem_peer: NodeId,
+    ) -> Result<(), MempoolServiceError> {
+        self.stem_tx_sender.send((transaction, stem_peer)).map_err(|e| {
+            error!(target: LOG_TARGET, "Could not relay stem transaction. {:?}", e);
+            MempoolServiceError::BroadcastFailed
+        })
+    }
 }
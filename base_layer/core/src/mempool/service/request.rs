@@ -34,9 +34,16 @@ use crate::{common::waiting_requests::RequestKey, transactions::transaction_comp
 pub enum MempoolRequest {
     GetStats,
     GetState,
+    /// The state of a transaction as it may be reported to remote peers. Transactions that are only in the Dandelion
+    /// stem phase are reported as not stored, so that peers can not trace the stem path.
     GetTxStateByExcessSig(Signature),
+    /// The state of a transaction including the Dandelion stem phase. Only for local callers.
+    GetLocalTxStateByExcessSig(Signature),
     SubmitTransaction(Transaction),
-    GetFeePerGramStats { count: usize, tip_height: u64 },
+    GetFeePerGramStats {
+        count: usize,
+        tip_height: u64,
+    },
 }
 
 impl Display for MempoolRequest {
@@ -47,6 +54,9 @@ impl Display for MempoolRequest {
             MempoolRequest::GetTxStateByExcessSig(sig) => {
                 write!(f, "GetTxStateByExcessSig ({})", sig.get_signature().to_hex())
             },
+            MempoolRequest::GetLocalTxStateByExcessSig(sig) => {
+                write!(f, "GetLocalTxStateByExcessSig ({})", sig.get_signature().to_hex())
+            },
             MempoolRequest::SubmitTransaction(tx) => {
                 let sig_hex = tx
                     .first_kernel_excess_sig()
@@ -20,39 +20,50 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use std::{convert::TryFrom, sync::Arc};
+use std::{convert::TryFrom, sync::Arc, time::Duration};
 
 use futures::{pin_mut, stream::StreamExt, Stream};
 use log::*;
-use tari_comms::peer_manager::NodeId;
+use prost::Message;
+use tari_comms::{
+    connectivity::ConnectivityRequester,
+    peer_manager::{NodeId, PeerFeatures},
+};
 use tari_comms_dht::{
     domain_message::OutboundDomainMessage,
     envelope::NodeDestination,
-    outbound::{DhtOutboundError, OutboundEncryption, OutboundMessageRequester},
+    outbound::{DhtOutboundError, MessageSendStates, OutboundEncryption, OutboundMessageRequester, SendMessageParams},
 };
 use tari_p2p::{domain_message::DomainMessage, tari_message::TariMessageType};
 use tari_service_framework::{reply_channel, reply_channel::RequestContext};
 use tari_utilities::hex::Hex;
-use tokio::{sync::mpsc, task};
+use tokio::{sync::mpsc, task, time, time::MissedTickBehavior};
 
 use crate::{
     base_node::comms_interface::{BlockEvent, BlockEventReceiver},
-    mempool::service::{
-        error::MempoolServiceError,
-        inbound_handlers::MempoolInboundHandlers,
-        MempoolRequest,
-        MempoolResponse,
+    mempool::{
+        service::{
+            error::MempoolServiceError,
+            inbound_handlers::MempoolInboundHandlers,
+            MempoolRequest,
+            MempoolResponse,
+        },
+        TransactionRelayHandle,
     },
     proto,
     transactions::transaction_components::Transaction,
 };
 
 const LOG_TARGET: &str = "c::mempool::service::service";
+/// How often Dandelion epochs and embargo timers are checked
+const DANDELION_TICK_INTERVAL: Duration = Duration::from_secs(5);
 
 /// A convenience struct to hold all the Mempool service streams
 pub struct MempoolStreams<STxIn, SLocalReq> {
     pub outbound_tx_stream: mpsc::UnboundedReceiver<(Arc<Transaction>, Vec<NodeId>)>,
+    pub outbound_stem_tx_stream: mpsc::UnboundedReceiver<(Arc<Transaction>, NodeId)>,
     pub inbound_transaction_stream: STxIn,
+    pub inbound_stem_transaction_stream: STxIn,
     pub local_request_stream: SLocalReq,
     pub block_event_stream: BlockEventReceiver,
     pub request_receiver: reply_channel::TryReceiver<MempoolRequest, MempoolResponse, MempoolServiceError>,
@@ -63,13 +74,28 @@ pub struct MempoolStreams<STxIn, SLocalReq> {
 pub struct MempoolService {
     outbound_message_service: OutboundMessageRequester,
     inbound_handlers: MempoolInboundHandlers,
+    relay: Option<TransactionRelayHandle>,
+    connectivity: ConnectivityRequester,
+    dandelion: Option<DandelionRelay>,
 }
 
 impl MempoolService {
-    pub fn new(outbound_message_service: OutboundMessageRequester, inbound_handlers: MempoolInboundHandlers) -> Self {
+    /// Creates a new mempool service. If a `TransactionRelayHandle` is given, new transactions are only flooded to the
+    /// peers selected by the mempool reconciliation protocol. If a `DandelionRelay` is given, this service manages its
+    /// epochs and embargo timers.
+    pub fn new(
+        outbound_message_service: OutboundMessageRequester,
+        inbound_handlers: MempoolInboundHandlers,
+        relay: Option<TransactionRelayHandle>,
+        connectivity: ConnectivityRequester,
+        dandelion: Option<DandelionRelay>,
+    ) -> Self {
         Self {
             outbound_message_service,
             inbound_handlers,
+            relay,
+            connectivity,
+            dandelion,
         }
     }
 
@@ -82,8 +108,14 @@ impl MempoolService {
         SLocalReq: Stream<Item = RequestContext<MempoolRequest, Result<MempoolResponse, MempoolServiceError>>>,
     {
         let mut outbound_tx_stream = streams.outbound_tx_stream;
+        let mut outbound_stem_tx_stream = streams.outbound_stem_tx_stream;
         let inbound_transaction_stream = streams.inbound_transaction_stream.fuse();
         pin_mut!(inbound_transaction_stream);
+        let inbound_stem_transaction_stream = streams.inbound_stem_transaction_stream.fuse();
+        pin_mut!(inbound_stem_transaction_stream);
+        let mut dandelion_ticker = time::interval(DANDELION_TICK_INTERVAL);
+        dandelion_ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        let is_dandelion_enabled = self.dandelion.is_some();
         let local_request_stream = streams.local_request_stream.fuse();
         pin_mut!(local_request_stream);
         let mut block_event_stream = streams.block_event_stream;
@@ -104,9 +136,26 @@ impl MempoolService {
                     );
                 },
 
+                // Outbound stem tx messages from the OutboundMempoolServiceInterface
+                Some((txn, stem_peer)) = outbound_stem_tx_stream.recv() => {
+                    let _res = self.handle_outbound_stem_tx(txn, stem_peer).await.map_err(|e|
+                        error!(target: LOG_TARGET, "Error sending outbound stem tx message: {}", e)
+                    );
+                },
+
                 // Incoming transaction messages from the Comms layer
                 Some(transaction_msg) = inbound_transaction_stream.next() => self.handle_incoming_tx(transaction_msg),
 
+                // Incoming Dandelion stem transaction messages from the Comms layer
+                Some(transaction_msg) = inbound_stem_transaction_stream.next() => {
+                    self.handle_incoming_stem_tx(transaction_msg);
+                },
+
+                // Dandelion epoch and embargo timers
+                _ = dandelion_ticker.tick(), if is_dandelion_enabled => {
+                    self.handle_dandelion_tick().await;
+                },
+
                 // Incoming local request messages from the LocalMempoolServiceInterface and other local services
                 Some(local_request_context) = local_request_stream.next() => {
                     self.spawn_handle_local_request(local_request_context);
@@ -194,30 +243,151 @@ impl MempoolService {
         });
     }
 
-    async fn handle_outbound_tx(
+    fn handle_incoming_stem_tx(&self, domain_transaction_msg: DomainMessage<Transaction>) {
+        let DomainMessage::<_> { source_peer, inner, .. } = domain_transaction_msg;
+        let mut inbound_handlers = self.inbound_handlers.clone();
+        task::spawn(async move {
+            let result = inbound_handlers
+                .handle_stem_transaction(inner, source_peer.node_id)
+                .await;
+            if let Err(e) = result {
+                error!(
+                    target: LOG_TARGET,
+                    "Failed to handle incoming stem transaction message: {:?}", e
+                );
+            }
+        });
+    }
+
+    /// Starts a new Dandelion epoch when required and fluffs stem transactions whose embargo timer has expired
+    async fn handle_dandelion_tick(&mut self) {
+        let dandelion = match self.dandelion.clone() {
+            Some(dandelion) => dandelion,
+            None => return,
+        };
+        match self.connectivity.get_active_connections().await {
+            Ok(connections) => {
+                let outbound_peers = connections
+                    .into_iter()
+                    .filter(|conn| {
+                        conn.direction().is_outbound() &&
+                            conn.peer_features().contains(PeerFeatures::COMMUNICATION_NODE)
+                    })
+                    .map(|conn| conn.peer_node_id().clone())
+                    .collect();
+                dandelion.update_epoch(outbound_peers);
+            },
+            Err(e) => warn!(target: LOG_TARGET, "Failed to get active connections: {}", e),
+        }
+
+        for tx in dandelion.take_expired() {
+            debug!(
+                target: LOG_TARGET,
+                "Embargo expired for stem transaction {}. Fluffing.",
+                tx.first_kernel_excess_sig()
+                    .map(|s| s.get_signature().to_hex())
+                    .unwrap_or_else(|| "No kernels!".to_string())
+            );
+            self.spawn_fluff_transaction(tx);
+        }
+    }
+
+    fn spawn_fluff_transaction(&self, tx: Arc<Transaction>) {
+        let mut inbound_handlers = self.inbound_handlers.clone();
+        task::spawn(async move {
+            if let Err(e) = inbound_handlers.fluff_transaction(tx).await {
+                error!(target: LOG_TARGET, "Failed to fluff stem transaction: {}", e);
+            }
+        });
+    }
+
+    async fn handle_outbound_stem_tx(
         &mut self,
         tx: Arc<Transaction>,
-        exclude_peers: Vec<NodeId>,
+        stem_peer: NodeId,
     ) -> Result<(), MempoolServiceError> {
+        let msg = proto::types::Transaction::try_from(tx.clone()).map_err(MempoolServiceError::ConversionError)?;
         let result = self
             .outbound_message_service
-            .flood(
-                NodeDestination::Unknown,
-                OutboundEncryption::ClearText,
-                exclude_peers,
-                OutboundDomainMessage::new(
-                    &TariMessageType::NewTransaction,
-                    proto::types::Transaction::try_from(tx.clone()).map_err(MempoolServiceError::ConversionError)?,
-                ),
-                format!(
-                    "Outbound mempool tx: {}",
-                    tx.first_kernel_excess_sig()
-                        .map(|s| s.get_signature().to_hex())
-                        .unwrap_or_else(|| "No kernels!".to_string())
-                ),
+            .send_message(
+                SendMessageParams::new()
+                    .direct_node_id(stem_peer.clone())
+                    .with_debug_info(format!(
+                        "Outbound stem tx: {}",
+                        tx.first_kernel_excess_sig()
+                            .map(|s| s.get_signature().to_hex())
+                            .unwrap_or_else(|| "No kernels!".to_string())
+                    ))
+                    .finish(),
+                OutboundDomainMessage::new(&TariMessageType::StemTransaction, msg),
             )
             .await;
 
+        // Fall back to fluffing the transaction if it could not be sent to the stem peer
+        let mut inbound_handlers = self.inbound_handlers.clone();
+        task::spawn(async move {
+            let is_sent = match result {
+                Ok(response) => match response.resolve().await {
+                    Ok(send_states) => send_states.wait_single().await,
+                    Err(_) => false,
+                },
+                Err(_) => false,
+            };
+            if !is_sent {
+                debug!(
+                    target: LOG_TARGET,
+                    "Failed to send stem transaction to peer `{}`. Fluffing.", stem_peer
+                );
+                if let Err(e) = inbound_handlers.fluff_transaction(tx).await {
+                    error!(target: LOG_TARGET, "Failed to fluff stem transaction: {}", e);
+                }
+            }
+        });
+        Ok(())
+    }
+
+    async fn handle_outbound_tx(
+        &mut self,
+        tx: Arc<Transaction>,
+        exclude_peers: Vec<NodeId>,
+    ) -> Result<(), MempoolServiceError> {
+        let msg = proto::types::Transaction::try_from(tx.clone()).map_err(MempoolServiceError::ConversionError)?;
+        let debug_info = format!(
+            "Outbound mempool tx: {}",
+            tx.first_kernel_excess_sig()
+                .map(|s| s.get_signature().to_hex())
+                .unwrap_or_else(|| "No kernels!".to_string())
+        );
+
+        let flood_peers = self.relay.as_ref().and_then(|relay| relay.flood_peers());
+        let result = match flood_peers {
+            // Remaining peers receive this transaction through mempool reconciliation
+            Some(flood_peers) => {
+                let peers = flood_peers
+                    .into_iter()
+                    .filter(|node_id| !exclude_peers.contains(node_id))
+                    .collect::<Vec<_>>();
+                if peers.is_empty() {
+                    return Ok(());
+                }
+                if let Some(relay) = self.relay.as_ref() {
+                    relay.record_flooded_bytes(msg.encoded_len() * peers.len());
+                }
+                self.send_to_flood_peers(peers, msg, debug_info).await
+            },
+            None => {
+                self.outbound_message_service
+                    .flood(
+                        NodeDestination::Unknown,
+                        OutboundEncryption::ClearText,
+                        exclude_peers,
+                        OutboundDomainMessage::new(&TariMessageType::NewTransaction, msg),
+                        debug_info,
+                    )
+                    .await
+            },
+        };
+
         match result {
             Ok(_) => Ok(()),
             Err(DhtOutboundError::NoMessagesQueued) => Ok(()),
@@ -227,4 +397,26 @@ impl MempoolService {
             },
         }
     }
+
+    async fn send_to_flood_peers(
+        &mut self,
+        peers: Vec<NodeId>,
+        msg: proto::types::Transaction,
+        debug_info: String,
+    ) -> Result<MessageSendStates, DhtOutboundError> {
+        self.outbound_message_service
+            .send_message(
+                SendMessageParams::new()
+                    .selected_peers(peers)
+                    .with_destination(NodeDestination::Unknown)
+                    .with_encryption(OutboundEncryption::ClearText)
+                    .with_debug_info(debug_info)
+                    .finish(),
+                OutboundDomainMessage::new(&TariMessageType::NewTransaction, msg),
+            )
+            .await?
+            .resolve()
+            .await
+            .map_err(Into::into)
+    }
 }
@@ -124,13 +124,20 @@ impl MempoolServiceMock {
     }
 
     async fn handle_request(&self, req: MempoolRequest) -> Result<MempoolResponse, MempoolServiceError> {
-        use MempoolRequest::{GetFeePerGramStats, GetState, GetStats, GetTxStateByExcessSig, SubmitTransaction};
+        use MempoolRequest::{
+            GetFeePerGramStats,
+            GetLocalTxStateByExcessSig,
+            GetState,
+            GetStats,
+            GetTxStateByExcessSig,
+            SubmitTransaction,
+        };
 
         self.state.inc_call_count();
         match req {
             GetStats => Ok(MempoolResponse::Stats(self.state.get_stats.lock().await.clone())),
             GetState => Ok(MempoolResponse::State(self.state.get_state.lock().await.clone())),
-            GetTxStateByExcessSig(_) => Ok(MempoolResponse::TxStorage(
+            GetTxStateByExcessSig(_) | GetLocalTxStateByExcessSig(_) => Ok(MempoolResponse::TxStorage(
                 self.state.get_tx_state_by_excess_sig.lock().await.clone(),
             )),
             SubmitTransaction(_) => Ok(MempoolResponse::TxStorage(
@@ -116,28 +116,30 @@ impl UnconfirmedPool {
     /// Insert a new transaction into the UnconfirmedPool. Low priority transactions will be removed to make space for
     /// higher priority transactions. The lowest priority transactions will be removed when the maximum capacity is
     /// reached and the new transaction has a higher priority than the currently stored lowest priority transaction.
+    /// The evicted transaction, if any, is returned.
     pub fn insert(
         &mut self,
         tx: Arc<Transaction>,
         dependent_outputs: Option<Vec<HashOutput>>,
         transaction_weighting: &TransactionWeight,
-    ) -> Result<(), UnconfirmedPoolError> {
+    ) -> Result<Option<Arc<Transaction>>, UnconfirmedPoolError> {
         if tx
             .body
             .kernels()
             .iter()
             .all(|k| self.txs_by_signature.contains_key(k.excess_sig.get_signature()))
         {
-            return Ok(());
+            return Ok(None);
         }
 
         let new_key = self.get_next_key();
         let prioritized_tx = PrioritizedTransaction::new(new_key, transaction_weighting, tx, dependent_outputs)?;
+        let mut evicted = None;
         if self.tx_by_key.len() >= self.config.storage_capacity {
             if prioritized_tx.priority < *self.lowest_priority()? {
-                return Ok(());
+                return Ok(None);
             }
-            self.remove_lowest_priority_tx()?;
+            evicted = self.remove_lowest_priority_tx()?;
         }
 
         self.tx_by_priority.insert(prioritized_tx.priority.clone(), new_key);
@@ -155,7 +157,7 @@ impl UnconfirmedPool {
         );
         self.tx_by_key.insert(new_key, prioritized_tx);
 
-        Ok(())
+        Ok(evicted)
     }
 
     /// This will search the unconfirmed pool for the set of outputs and return true if all of them are found
@@ -561,11 +563,11 @@ impl UnconfirmedPool {
             .ok_or(UnconfirmedPoolError::StorageOutofSync)
     }
 
-    fn remove_lowest_priority_tx(&mut self) -> Result<(), UnconfirmedPoolError> {
-        if let Some(tx_key) = self.tx_by_priority.values().next().copied() {
-            self.remove_transaction(tx_key)?;
+    fn remove_lowest_priority_tx(&mut self) -> Result<Option<Arc<Transaction>>, UnconfirmedPoolError> {
+        match self.tx_by_priority.values().next().copied() {
+            Some(tx_key) => self.remove_transaction(tx_key),
+            None => Ok(None),
         }
-        Ok(())
     }
 
     /// Remove all current mempool transactions from the UnconfirmedPoolStorage, returning that which have been removed
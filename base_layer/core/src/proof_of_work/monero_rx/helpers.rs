@@ -164,15 +164,11 @@ fn check_aux_chains(
     if merkle_tree_params.number_of_chains == 0 {
         return false;
     }
-    let hash_position = U256::from_little_endian(
-        &Sha256::new()
-            .chain_update(tari_genesis_block_hash)
-            .chain_update(merkle_tree_params.aux_nonce.to_le_bytes())
-            .chain_update((109_u8).to_le_bytes())
-            .finalize(),
-    )
-    .low_u32() %
-        u32::from(merkle_tree_params.number_of_chains);
+    let hash_position = aux_chain_position(
+        tari_genesis_block_hash.as_slice(),
+        merkle_tree_params.aux_nonce,
+        merkle_tree_params.number_of_chains,
+    );
     let (merkle_root, pos) = monero_data.aux_chain_merkle_proof.calculate_root_with_pos(&t_hash);
     if hash_position != pos {
         return false;
@@ -181,6 +177,20 @@ fn check_aux_chains(
     merkle_root == *aux_chain_merkle_root
 }
 
+/// Calculates the position of an aux chain in the merge mining merkle tree from its chain id, e.g. the Minotari genesis
+/// block hash, and the aux nonce committed to in the merge mining tag. `number_of_chains` must not be zero.
+pub fn aux_chain_position(chain_id: &[u8], aux_nonce: u32, number_of_chains: u8) -> u32 {
+    U256::from_little_endian(
+        &Sha256::new()
+            .chain_update(chain_id)
+            .chain_update(aux_nonce.to_le_bytes())
+            .chain_update((109_u8).to_le_bytes())
+            .finalize(),
+    )
+    .low_u32() %
+        u32::from(number_of_chains)
+}
+
 /// Extracts the Monero block hash from the coinbase transaction's extra field
 pub fn extract_aux_merkle_root_from_block(monero: &monero::Block) -> Result<Option<monero::Hash>, MergeMineError> {
     // When we extract the merge mining hash, we do not care if the extra field can be parsed without error.
@@ -24,6 +24,7 @@ pub use error::MergeMineError;
 
 mod helpers;
 pub use helpers::{
+    aux_chain_position,
     construct_monero_data,
     create_blockhashing_blob_from_block,
     create_ordered_transaction_hashes_from_block,
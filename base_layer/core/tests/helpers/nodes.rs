@@ -221,6 +221,7 @@ impl BaseNodeBuilder {
             blockchain_db,
             mempool,
             consensus_manager.clone(),
+            self.mempool_service_config.unwrap_or_default(),
             self.liveness_service_config.unwrap_or_default(),
             self.p2p_config.unwrap_or_default(),
             data_path,
@@ -334,6 +335,7 @@ async fn setup_base_node_services(
     blockchain_db: BlockchainDatabase<TempDatabase>,
     mempool: Mempool,
     consensus_manager: ConsensusManager,
+    mempool_service_config: MempoolServiceConfig,
     liveness_service_config: LivenessConfig,
     p2p_config: P2pConfig,
     data_path: &str,
@@ -363,7 +365,11 @@ async fn setup_base_node_services(
             randomx_factory,
             Default::default(),
         ))
-        .add_initializer(MempoolServiceInitializer::new(mempool.clone(), subscription_factory))
+        .add_initializer(MempoolServiceInitializer::new(
+            mempool_service_config,
+            mempool.clone(),
+            subscription_factory,
+        ))
         .add_initializer(mock_state_machine.get_initializer())
         .add_initializer(ChainMetadataServiceInitializer)
         .build()
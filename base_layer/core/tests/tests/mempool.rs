@@ -30,7 +30,7 @@ use tari_core::{
     base_node::state_machine_service::states::{ListeningInfo, StateInfo, StatusInfo},
     chain_storage::BlockchainDatabaseConfig,
     consensus::{ConsensusConstantsBuilder, ConsensusManager},
-    mempool::{Mempool, MempoolConfig, MempoolServiceConfig, TxStorageResponse},
+    mempool::{Mempool, MempoolConfig, MempoolEvent, MempoolRemovalReason, MempoolServiceConfig, TxStorageResponse},
     proof_of_work::Difficulty,
     proto,
     transactions::{
@@ -264,6 +264,65 @@ async fn test_insert_and_process_published_block() {
     assert_eq!(stats.unconfirmed_weight, 0);
 }
 
+#[tokio::test]
+#[allow(clippy::identity_op)]
+async fn test_mempool_events() {
+    let network = Network::LocalNet;
+    let (mut store, mut blocks, mut outputs, consensus_manager, key_manager) = create_new_blockchain(network).await;
+    let mempool_validator = TransactionChainLinkedValidator::new(store.clone(), consensus_manager.clone());
+    let mempool = Mempool::new(
+        MempoolConfig::default(),
+        consensus_manager.clone(),
+        Box::new(mempool_validator),
+    );
+    let mut events = mempool.get_event_stream();
+    let txs = vec![txn_schema!(
+        from: vec![outputs[0][0].clone()],
+        to: vec![2 * T, 2 * T],fee: 5.into(), lock: 0, features: OutputFeatures::default()
+    )];
+    generate_new_block(
+        &mut store,
+        &mut blocks,
+        &mut outputs,
+        txs,
+        &consensus_manager,
+        &key_manager,
+    )
+    .await
+    .unwrap();
+
+    let tx1 = txn_schema!(from: vec![outputs[1][0].clone()], to: vec![1*T], fee: 20*uT, lock: 0, features: OutputFeatures::default());
+    let tx1 = Arc::new(spend_utxos(tx1, &key_manager).await.0);
+    mempool.insert(tx1.clone()).await.unwrap();
+    // Inserting the same transaction again must not publish a second event
+    mempool.insert(tx1.clone()).await.unwrap();
+
+    match &*events.recv().await.unwrap() {
+        MempoolEvent::TransactionAdded(tx) => assert_eq!(tx, &tx1),
+        event => panic!("Unexpected event {:?}", event),
+    }
+
+    generate_block(
+        &store,
+        &mut blocks,
+        vec![tx1.deref().clone()],
+        &consensus_manager,
+        &key_manager,
+    )
+    .await
+    .unwrap();
+    mempool.process_published_block(blocks[2].to_arc_block()).await.unwrap();
+
+    match &*events.recv().await.unwrap() {
+        MempoolEvent::TransactionRemoved { excess_sig, reason } => {
+            assert_eq!(excess_sig, &tx1.body.kernels()[0].excess_sig);
+            assert_eq!(*reason, MempoolRemovalReason::Mined);
+        },
+        event => panic!("Unexpected event {:?}", event),
+    }
+    assert!(events.try_recv().is_err());
+}
+
 #[tokio::test]
 #[allow(clippy::identity_op)]
 async fn test_time_locked() {
@@ -21,6 +21,8 @@
 //  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 use std::{
+    collections::HashMap,
+    net::Ipv4Addr,
     path::{Path, PathBuf},
     time::Duration,
 };
@@ -59,6 +61,10 @@ pub struct PeerSeedsConfig {
     pub dns_seeds_name_server: DnsNameServer,
     /// All DNS seed records must pass DNSSEC validation
     pub dns_seeds_use_dnssec: bool,
+    /// Named groups of static peers, in the same format as `peer_seeds`. Connectivity always attempts to maintain
+    /// connections to static peers and never bans them.
+    #[serde(default)]
+    pub static_peer_groups: HashMap<String, StringList>,
 }
 
 impl Default for PeerSeedsConfig {
@@ -69,6 +75,7 @@ impl Default for PeerSeedsConfig {
             dns_seeds: StringList::default(),
             dns_seeds_name_server: DEFAULT_DNS_NAME_SERVER.parse().unwrap(),
             dns_seeds_use_dnssec: false,
+            static_peer_groups: HashMap::new(),
         }
     }
 }
@@ -121,12 +128,67 @@ pub struct P2pConfig {
     /// for direct comms between a wallet and base node. If this is set to None, no listener will be bound.
     /// Default: None
     pub auxiliary_tcp_listener_address: Option<Multiaddr>,
+    /// The UDP address to bind a QUIC listener on _in addition to_ the primary transport. Setting this also enables
+    /// dialing peers on their QUIC addresses, so it should not be set on nodes that must only be reachable over Tor.
+    /// If this is set to None, QUIC is disabled. e.g. `/ip4/0.0.0.0/udp/18189/quic`
+    /// Default: None
+    pub quic_listener_address: Option<Multiaddr>,
     /// The global maximum allowed RPC sessions.
     /// Default: 100
     pub rpc_max_simultaneous_sessions: usize,
     /// The maximum allowed RPC sessions per peer.
     /// Default: 10
     pub rpc_max_sessions_per_peer: usize,
+    /// The maximum number of requests per minute a peer may make to each RPC method. None for unlimited.
+    /// Default: 600
+    pub rpc_max_requests_per_minute_per_peer: Option<u32>,
+    /// The maximum number of requests to each RPC method that a peer may have in progress at once. None for unlimited.
+    /// Default: 4
+    pub rpc_max_concurrent_requests_per_peer: Option<usize>,
+    /// The maximum number of response bytes per minute that may be streamed to a peer from each RPC method. None for
+    /// unlimited.
+    /// Default: None
+    pub rpc_max_streamed_bytes_per_minute_per_peer: Option<u64>,
+    /// The maximum number of RPC response streams that are sent at once across all peers. Waiting streams are served
+    /// to each peer in turn. None for unlimited.
+    /// Default: 50
+    pub rpc_max_concurrent_streams: Option<usize>,
+    /// A peer is temporarily banned when its reputation score falls to or below this value. Reputation scores are
+    /// increased by useful behaviour and decreased by slow responses, timeouts and invalid messages.
+    /// Default: -100
+    pub reputation_ban_threshold: f64,
+    /// The time it takes for a peer's reputation score to decay to half its value.
+    /// Default: 30 minutes
+    #[serde(with = "serializers::seconds")]
+    pub reputation_decay_half_life: Duration,
+    /// The duration of the first reputation ban for a peer. Each subsequent reputation ban doubles the duration, up
+    /// to 2 hours.
+    /// Default: 10 minutes
+    #[serde(with = "serializers::seconds")]
+    pub reputation_ban_duration: Duration,
+    /// The maximum total upload rate in bytes per second across all peers. If not set, uploads are not limited.
+    /// Default: None
+    pub max_upload_rate: Option<u64>,
+    /// The maximum total download rate in bytes per second across all peers. If not set, downloads are not limited.
+    /// Default: None
+    pub max_download_rate: Option<u64>,
+    /// The maximum upload rate in bytes per second to a single peer. If not set, uploads are not limited.
+    /// Default: None
+    pub max_peer_upload_rate: Option<u64>,
+    /// The maximum download rate in bytes per second from a single peer. If not set, downloads are not limited.
+    /// Default: None
+    pub max_peer_download_rate: Option<u64>,
+    /// Attempt to forward the TCP listener port on the NAT gateway using UPnP or NAT-PMP, and advertise the mapped
+    /// external address.
+    /// Default: false
+    pub enable_port_mapping: bool,
+    /// The gateway to which NAT-PMP requests are sent. If not set, the default gateway is used.
+    /// Default: None
+    pub nat_gateway_address: Option<Ipv4Addr>,
+    /// Advertise the external IP address that peers observe for this node, together with the listener port. Use this
+    /// if the listener port has been forwarded manually and the external IP address is dynamic.
+    /// Default: false
+    pub advertise_observed_address: bool,
 }
 
 impl Default for P2pConfig {
@@ -150,8 +212,23 @@ impl Default for P2pConfig {
             listener_liveness_allowlist_cidrs: StringList::default(),
             user_agent: String::new(),
             auxiliary_tcp_listener_address: None,
+            quic_listener_address: None,
             rpc_max_simultaneous_sessions: 100,
             rpc_max_sessions_per_peer: 10,
+            rpc_max_requests_per_minute_per_peer: Some(600),
+            rpc_max_concurrent_requests_per_peer: Some(4),
+            rpc_max_streamed_bytes_per_minute_per_peer: None,
+            rpc_max_concurrent_streams: Some(50),
+            reputation_ban_threshold: -100.0,
+            reputation_decay_half_life: Duration::from_secs(30 * 60),
+            reputation_ban_duration: Duration::from_secs(10 * 60),
+            max_upload_rate: None,
+            max_download_rate: None,
+            max_peer_upload_rate: None,
+            max_peer_download_rate: None,
+            enable_port_mapping: false,
+            nat_gateway_address: None,
+            advertise_observed_address: false,
         }
     }
 }
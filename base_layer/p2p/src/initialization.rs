@@ -26,7 +26,7 @@ use std::{
     iter,
     path::Path,
     str::FromStr,
-    sync::Arc,
+    sync::{Arc, Mutex},
     time::{Duration, Instant},
 };
 
@@ -41,7 +41,10 @@ use tari_common::{
 };
 use tari_comms::{
     backoff::ConstantBackoff,
-    multiaddr::multiaddr,
+    bandwidth::BandwidthConfig,
+    connectivity::{ConnectivityError, ConnectivityRequester, PeerReputationConfig},
+    multiaddr::{multiaddr, Multiaddr},
+    nat::NatConfig,
     peer_manager::{NodeIdentity, Peer, PeerFeatures, PeerFlags, PeerManagerError},
     pipeline,
     protocol::{
@@ -56,6 +59,7 @@ use tari_comms::{
         predicate::FalsePredicate,
         HiddenServiceTransport,
         MemoryTransport,
+        RetiredIdentityCallback,
         SocksConfig,
         SocksTransport,
         TcpWithTorTransport,
@@ -110,8 +114,12 @@ pub enum CommsInitializationError {
     CannotAcquireFileLock,
     #[error("Invalid tor forward address: `{0}`")]
     InvalidTorForwardAddress(std::io::Error),
+    #[error("Invalid onion client authorization credentials: `{0}`")]
+    InvalidOnionClientAuth(String),
     #[error("IO Error: `{0}`")]
     IoError(#[from] std::io::Error),
+    #[error("Connectivity error: `{0}`")]
+    ConnectivityError(#[from] ConnectivityError),
 }
 
 impl CommsInitializationError {
@@ -258,9 +266,11 @@ pub async fn spawn_comms_using_transport<F: Fn(TorIdentity) + Send + Sync + Unpi
             debug!(target: LOG_TARGET, "Building TOR comms stack ({:?})", tor_config);
             let listener_address_override = tor_config.listener_address_override.clone();
             let hidden_service_ctl = initialize_hidden_service(tor_config)?;
+            let (after_comms, after_retire) = retire_previous_onion_addresses(comms.node_identity(), after_comms);
             // Set the listener address to be the address (usually local) to which tor will forward all traffic
             let instant = Instant::now();
-            let transport = HiddenServiceTransport::new(hidden_service_ctl, after_comms);
+            let transport =
+                HiddenServiceTransport::new(hidden_service_ctl, after_comms).with_after_retire(after_retire);
             debug!(target: LOG_TARGET, "TOR transport initialized in {:.0?}", instant.elapsed());
 
             comms
@@ -283,21 +293,79 @@ pub async fn spawn_comms_using_transport<F: Fn(TorIdentity) + Send + Sync + Unpi
     Ok(comms)
 }
 
+/// Wraps the `after_comms` callback so that the onion address of an identity that was replaced by key rotation is no
+/// longer advertised once its onion service is deleted at the end of the grace period. `after_comms` is then called
+/// again with the current identity, so that the updated node identity can be saved. Changing the public addresses
+/// re-signs the node's identity claim, which is then announced to the network.
+fn retire_previous_onion_addresses<F: Fn(TorIdentity) + Send + Sync + Unpin + Clone + 'static>(
+    node_identity: Arc<NodeIdentity>,
+    after_comms: F,
+) -> (
+    impl Fn(TorIdentity) + Send + Sync + Unpin + Clone + 'static,
+    RetiredIdentityCallback,
+) {
+    let current_identity = Arc::new(Mutex::new(None::<TorIdentity>));
+    let after_init = {
+        let current_identity = current_identity.clone();
+        let after_comms = after_comms.clone();
+        move |identity: TorIdentity| {
+            *current_identity.lock().expect("current onion identity lock poisoned") = Some(identity.clone());
+            after_comms(identity);
+        }
+    };
+    let after_retire: RetiredIdentityCallback = Arc::new(move |retired: TorIdentity| {
+        let current = current_identity
+            .lock()
+            .expect("current onion identity lock poisoned")
+            .clone();
+        let retired_address = match retired.try_get_onion_address() {
+            Ok(address) => address,
+            Err(err) => {
+                warn!(target: LOG_TARGET, "Retired onion identity has an invalid address: {}", err);
+                return;
+            },
+        };
+        if current.as_ref().map_or(false, |c| c.service_id == retired.service_id) {
+            return;
+        }
+        info!(
+            target: LOG_TARGET,
+            "Grace period of onion address {} ended. No longer advertising it.", retired_address
+        );
+        node_identity.remove_public_address(&retired_address);
+        if let Some(current) = current {
+            after_comms(current);
+        }
+    });
+    (after_init, after_retire)
+}
+
 fn initialize_hidden_service(
     mut config: TorTransportConfig,
 ) -> Result<tor::HiddenServiceController, CommsInitializationError> {
+    let onion_client_auth = config.to_onion_client_auth()?;
+    let onion_services = config.to_onion_services()?;
     let mut builder = tor::HiddenServiceBuilder::new()
         .with_port_mapping(config.to_port_mapping()?)
         .with_socks_authentication(config.to_socks_auth())
         .with_control_server_auth(config.to_control_auth()?)
         .with_socks_address_override(config.socks_address_override)
         .with_control_server_address(config.control_address)
-        .with_bypass_proxy_addresses(config.proxy_bypass_addresses.into());
+        .with_bypass_proxy_addresses(config.proxy_bypass_addresses.into())
+        .with_onion_client_auth(onion_client_auth);
 
     if config.proxy_bypass_for_outbound_tcp {
         builder = builder.bypass_tor_for_tcp_addresses();
     }
 
+    for service in onion_services {
+        builder = builder.add_onion_service(service);
+    }
+
+    if let Some(interval) = config.key_rotation_interval {
+        builder = builder.with_key_rotation(interval, config.key_rotation_grace_period);
+    }
+
     if let Some(identity) = config.identity.take() {
         builder = builder.with_tor_identity(identity);
     }
@@ -327,16 +395,37 @@ async fn configure_comms_and_dht(
     let listener_liveness_allowlist_cidrs = parse_cidrs(&config.listener_liveness_allowlist_cidrs)
         .map_err(CommsInitializationError::InvalidLivenessCidrs)?;
 
-    let builder = builder
+    let mut builder = builder
         .with_listener_liveness_max_sessions(config.listener_liveness_max_sessions)
         .with_listener_liveness_allowlist_cidrs(listener_liveness_allowlist_cidrs)
         .with_dial_backoff(ConstantBackoff::new(Duration::from_millis(500)))
+        .with_peer_reputation_config(PeerReputationConfig {
+            ban_threshold: config.reputation_ban_threshold,
+            decay_half_life: config.reputation_decay_half_life,
+            ban_duration: config.reputation_ban_duration,
+            ..Default::default()
+        })
+        .with_bandwidth_config(BandwidthConfig {
+            max_upload_rate: config.max_upload_rate,
+            max_download_rate: config.max_download_rate,
+            max_peer_upload_rate: config.max_peer_upload_rate,
+            max_peer_download_rate: config.max_peer_download_rate,
+        })
+        .with_nat_config(NatConfig {
+            enable_port_mapping: config.enable_port_mapping,
+            gateway_address: config.nat_gateway_address,
+            advertise_observed_address: config.advertise_observed_address,
+            ..Default::default()
+        })
         .with_peer_storage(peer_database, Some(file_lock));
 
-    let mut comms = match config.auxiliary_tcp_listener_address {
-        Some(ref addr) => builder.with_auxiliary_tcp_listener_address(addr.clone()).build()?,
-        None => builder.build()?,
-    };
+    if let Some(ref addr) = config.auxiliary_tcp_listener_address {
+        builder = builder.with_auxiliary_tcp_listener_address(addr.clone());
+    }
+    if let Some(ref addr) = config.quic_listener_address {
+        builder = builder.with_quic_listener_address(addr.clone());
+    }
+    let mut comms = builder.build()?;
 
     let peer_manager = comms.peer_manager();
     let connectivity = comms.connectivity();
@@ -443,6 +532,36 @@ pub async fn add_seed_peers(
     Ok(())
 }
 
+/// Adds the peers in each static peer group to the peer manager and registers the group with connectivity, which will
+/// then maintain connections to them.
+pub async fn add_static_peer_groups(
+    peer_manager: &PeerManager,
+    connectivity: &mut ConnectivityRequester,
+    node_identity: &NodeIdentity,
+    groups: Vec<(String, Vec<Peer>)>,
+) -> Result<(), CommsInitializationError> {
+    for (name, peers) in groups {
+        let mut node_ids = Vec::with_capacity(peers.len());
+        for peer in peers {
+            if &peer.public_key == node_identity.public_key() {
+                debug!(
+                    target: LOG_TARGET,
+                    "Ignoring own node identity [{}] in static peer group '{}'", peer, name
+                );
+                continue;
+            }
+            debug!(target: LOG_TARGET, "Adding static peer [{}] in group '{}'", peer, name);
+            node_ids.push(peer.node_id.clone());
+            peer_manager
+                .add_peer(peer)
+                .await
+                .map_err(CommsInitializationError::FailedToAddSeedPeer)?;
+        }
+        connectivity.set_static_peer_group(name, node_ids).await?;
+    }
+    Ok(())
+}
+
 pub struct P2pInitializer {
     config: P2pConfig,
     seed_config: PeerSeedsConfig,
@@ -586,6 +705,14 @@ impl ServiceInitializer for P2pInitializer {
 
         add_seed_peers(&peer_manager, &node_identity, peers).await?;
 
+        let groups = self
+            .seed_config
+            .static_peer_groups
+            .iter()
+            .map(|(name, peers)| Ok((name.clone(), Self::try_parse_seed_peers(peers)?)))
+            .collect::<Result<Vec<_>, ServiceInitializationError>>()?;
+        add_static_peer_groups(&peer_manager, &mut comms.connectivity(), &node_identity, groups).await?;
+
         context.register_handle(comms.connectivity());
         context.register_handle(peer_manager);
         context.register_handle(comms);
@@ -19,9 +19,10 @@
 //  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
 //  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 //  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
-use std::{num::NonZeroU16, sync::Arc};
+use std::{num::NonZeroU16, sync::Arc, time::Duration};
 
 use serde::{Deserialize, Serialize};
+use tari_common::configuration::serializers;
 use tari_comms::{
     multiaddr::Multiaddr,
     socks,
@@ -155,6 +156,20 @@ pub struct TorTransportConfig {
     /// The tor identity to use to create the hidden service. If None, a new one will be generated.
     #[serde(skip)]
     pub identity: Option<TorIdentity>,
+    /// If set, the onion service key is replaced with a new one at this interval and the new onion address is
+    /// announced to the network.
+    #[serde(with = "serializers::optional_seconds")]
+    pub key_rotation_interval: Option<Duration>,
+    /// The time for which the previous onion address remains reachable after a key rotation. Must be less than
+    /// `key_rotation_interval`.
+    #[serde(with = "serializers::seconds")]
+    pub key_rotation_grace_period: Duration,
+    /// Client authorization credentials for restricted onion services that this node connects to, in the form
+    /// `<service_id>:<base64 x25519 secret key>`.
+    pub onion_client_auth: Vec<String>,
+    /// Additional onion services that are created alongside the node's hidden service, for example to expose the gRPC
+    /// server over tor.
+    pub onion_services: Vec<OnionServiceSettings>,
 }
 
 impl TorTransportConfig {
@@ -185,6 +200,30 @@ impl TorTransportConfig {
     pub fn to_socks_auth(&self) -> socks::Authentication {
         self.socks_auth.clone().into()
     }
+
+    pub fn to_onion_client_auth(&self) -> Result<Vec<tor::OnionClientAuth>, CommsInitializationError> {
+        self.onion_client_auth
+            .iter()
+            .map(|s| {
+                let (service_id, secret_key) = s
+                    .split_once(':')
+                    .ok_or_else(|| CommsInitializationError::InvalidOnionClientAuth(s.clone()))?;
+                let secret_key = tor::ClientAuthSecretKey::from_base64(secret_key)
+                    .map_err(|_| CommsInitializationError::InvalidOnionClientAuth(s.clone()))?;
+                Ok(tor::OnionClientAuth {
+                    service_id: service_id.trim_end_matches(".onion").to_string(),
+                    secret_key,
+                })
+            })
+            .collect()
+    }
+
+    pub fn to_onion_services(&self) -> Result<Vec<tor::OnionServiceConfig>, CommsInitializationError> {
+        self.onion_services
+            .iter()
+            .map(OnionServiceSettings::to_onion_service_config)
+            .collect()
+    }
 }
 
 impl Default for TorTransportConfig {
@@ -200,10 +239,48 @@ impl Default for TorTransportConfig {
             forward_address: None,
             listener_address_override: None,
             identity: None,
+            key_rotation_interval: None,
+            key_rotation_grace_period: Duration::from_secs(60 * 60),
+            onion_client_auth: vec![],
+            onion_services: vec![],
         }
     }
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct OnionServiceSettings {
+    /// The port on which the onion service is reachable
+    pub onion_port: NonZeroU16,
+    /// The address to which tor forwards traffic for this onion service
+    pub forward_address: Multiaddr,
+    /// If not empty, only clients holding the secret key for one of these base32 encoded x25519 public keys are able
+    /// to connect to the onion service (v3 client authorization).
+    #[serde(default)]
+    pub authorized_clients: Vec<String>,
+}
+
+impl OnionServiceSettings {
+    pub fn to_onion_service_config(&self) -> Result<tor::OnionServiceConfig, CommsInitializationError> {
+        let forward_addr = multiaddr_to_socketaddr(&self.forward_address)
+            .map_err(CommsInitializationError::InvalidTorForwardAddress)?;
+        let authorized_clients = self
+            .authorized_clients
+            .iter()
+            .map(|s| {
+                // Accept the contents of a tor `.auth` file (`descriptor:x25519:<base32>`) as well as the bare key
+                let key = s.rsplit(':').next().unwrap_or(s);
+                tor::ClientAuthPublicKey::from_base32(key)
+                    .map_err(|_| CommsInitializationError::InvalidOnionClientAuth(s.clone()))
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(
+            tor::OnionServiceConfig::new(tor::PortMapping::new(self.onion_port.get(), forward_addr))
+                .with_authorized_clients(authorized_clients),
+        )
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(deny_unknown_fields)]
 pub struct Socks5TransportConfig {
@@ -0,0 +1,7 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX outputs_account_id_index;
+ALTER TABLE outputs DROP COLUMN account_id;
+ALTER TABLE completed_transactions DROP COLUMN account_id;
+ALTER TABLE inbound_transactions DROP COLUMN account_id;
+ALTER TABLE outbound_transactions DROP COLUMN account_id;
+DROP TABLE accounts;
@@ -0,0 +1,17 @@
+CREATE TABLE accounts
+(
+    id         INTEGER PRIMARY KEY NOT NULL,
+    name       TEXT                NOT NULL,
+    created_at DATETIME            NOT NULL,
+    CONSTRAINT unique_name UNIQUE (name)
+);
+
+-- Every existing output and transaction belongs to the default account
+INSERT INTO accounts (id, name, created_at) VALUES (0, 'default', CURRENT_TIMESTAMP);
+
+ALTER TABLE outputs ADD COLUMN account_id BIGINT NOT NULL DEFAULT 0;
+ALTER TABLE completed_transactions ADD COLUMN account_id BIGINT NOT NULL DEFAULT 0;
+ALTER TABLE inbound_transactions ADD COLUMN account_id BIGINT NOT NULL DEFAULT 0;
+ALTER TABLE outbound_transactions ADD COLUMN account_id BIGINT NOT NULL DEFAULT 0;
+
+CREATE INDEX outputs_account_id_index ON outputs (account_id);
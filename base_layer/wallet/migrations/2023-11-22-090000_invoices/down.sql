@@ -0,0 +1,2 @@
+DROP INDEX invoices_status_index;
+DROP TABLE invoices;
@@ -0,0 +1,16 @@
+CREATE TABLE invoices
+(
+    payment_id      TEXT PRIMARY KEY NOT NULL,
+    address         BLOB             NOT NULL,
+    amount          BIGINT           NOT NULL,
+    amount_received BIGINT           NOT NULL DEFAULT 0,
+    message         TEXT             NOT NULL,
+    status          INTEGER          NOT NULL,
+    tx_id           BIGINT           NULL,
+    created_at      DATETIME         NOT NULL,
+    expires_at      DATETIME         NULL,
+    paid_at         DATETIME         NULL,
+    CONSTRAINT unique_address UNIQUE (address)
+);
+
+CREATE INDEX invoices_status_index ON invoices (status);
@@ -0,0 +1,9 @@
+CREATE TABLE subaddresses
+(
+    key_index        BIGINT PRIMARY KEY NOT NULL,
+    spend_public_key BLOB               NOT NULL,
+    view_public_key  BLOB               NOT NULL,
+    label            TEXT               NOT NULL DEFAULT '',
+    created_at       DATETIME           NOT NULL,
+    CONSTRAINT unique_spend_public_key UNIQUE (spend_public_key)
+);
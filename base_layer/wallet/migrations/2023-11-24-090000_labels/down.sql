@@ -0,0 +1,8 @@
+DROP TRIGGER completed_transactions_status_history_update;
+DROP TRIGGER completed_transactions_status_history_insert;
+DROP TRIGGER outbound_transactions_status_history_cancel;
+DROP TRIGGER outbound_transactions_status_history_insert;
+DROP TRIGGER inbound_transactions_status_history_cancel;
+DROP TRIGGER inbound_transactions_status_history_insert;
+DROP TABLE transaction_status_history;
+DROP TABLE labels;
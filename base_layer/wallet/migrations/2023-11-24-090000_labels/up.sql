@@ -0,0 +1,75 @@
+CREATE TABLE labels
+(
+    target_type TEXT     NOT NULL,
+    target_id   TEXT     NOT NULL,
+    label       TEXT     NOT NULL DEFAULT '',
+    note        TEXT     NOT NULL DEFAULT '',
+    updated_at  DATETIME NOT NULL,
+    PRIMARY KEY (target_type, target_id)
+);
+
+CREATE TABLE transaction_status_history
+(
+    id         INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    tx_id      BIGINT                            NOT NULL,
+    status     INTEGER                           NOT NULL,
+    cancelled  INTEGER                           NULL,
+    changed_at DATETIME                          NOT NULL
+);
+
+CREATE INDEX transaction_status_history_tx_id_index ON transaction_status_history (tx_id);
+
+-- Seed the history of existing transactions with their current state
+INSERT INTO transaction_status_history (tx_id, status, cancelled, changed_at)
+SELECT tx_id, 4, CASE WHEN cancelled = 0 THEN NULL ELSE 0 END, timestamp FROM inbound_transactions;
+INSERT INTO transaction_status_history (tx_id, status, cancelled, changed_at)
+SELECT tx_id, 4, CASE WHEN cancelled = 0 THEN NULL ELSE 0 END, timestamp FROM outbound_transactions;
+INSERT INTO transaction_status_history (tx_id, status, cancelled, changed_at)
+SELECT tx_id, status, cancelled, COALESCE(mined_timestamp, timestamp) FROM completed_transactions;
+
+-- Status changes are recorded by triggers so that every code path that updates a transaction is covered. Pending
+-- transactions only store a cancelled flag, so their cancellations are recorded with an unknown reason (0).
+CREATE TRIGGER inbound_transactions_status_history_insert
+    AFTER INSERT ON inbound_transactions
+BEGIN
+    INSERT INTO transaction_status_history (tx_id, status, cancelled, changed_at)
+    VALUES (NEW.tx_id, 4, NULL, CURRENT_TIMESTAMP);
+END;
+
+CREATE TRIGGER inbound_transactions_status_history_cancel
+    AFTER UPDATE OF cancelled ON inbound_transactions
+    WHEN OLD.cancelled = 0 AND NEW.cancelled != 0
+BEGIN
+    INSERT INTO transaction_status_history (tx_id, status, cancelled, changed_at)
+    VALUES (NEW.tx_id, 4, 0, CURRENT_TIMESTAMP);
+END;
+
+CREATE TRIGGER outbound_transactions_status_history_insert
+    AFTER INSERT ON outbound_transactions
+BEGIN
+    INSERT INTO transaction_status_history (tx_id, status, cancelled, changed_at)
+    VALUES (NEW.tx_id, 4, NULL, CURRENT_TIMESTAMP);
+END;
+
+CREATE TRIGGER outbound_transactions_status_history_cancel
+    AFTER UPDATE OF cancelled ON outbound_transactions
+    WHEN OLD.cancelled = 0 AND NEW.cancelled != 0
+BEGIN
+    INSERT INTO transaction_status_history (tx_id, status, cancelled, changed_at)
+    VALUES (NEW.tx_id, 4, 0, CURRENT_TIMESTAMP);
+END;
+
+CREATE TRIGGER completed_transactions_status_history_insert
+    AFTER INSERT ON completed_transactions
+BEGIN
+    INSERT INTO transaction_status_history (tx_id, status, cancelled, changed_at)
+    VALUES (NEW.tx_id, NEW.status, NEW.cancelled, CURRENT_TIMESTAMP);
+END;
+
+CREATE TRIGGER completed_transactions_status_history_update
+    AFTER UPDATE OF status, cancelled ON completed_transactions
+    WHEN OLD.status IS NOT NEW.status OR OLD.cancelled IS NOT NEW.cancelled
+BEGIN
+    INSERT INTO transaction_status_history (tx_id, status, cancelled, changed_at)
+    VALUES (NEW.tx_id, NEW.status, NEW.cancelled, CURRENT_TIMESTAMP);
+END;
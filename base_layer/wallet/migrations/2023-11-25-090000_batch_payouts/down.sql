@@ -0,0 +1,2 @@
+DROP INDEX batch_payouts_tx_id_index;
+DROP TABLE batch_payouts;
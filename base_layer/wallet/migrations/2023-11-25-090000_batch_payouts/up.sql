@@ -0,0 +1,15 @@
+CREATE TABLE batch_payouts
+(
+    batch_id   TEXT     NOT NULL,
+    row_index  BIGINT   NOT NULL,
+    address    BLOB     NOT NULL,
+    amount     BIGINT   NOT NULL,
+    memo       TEXT     NOT NULL,
+    status     INTEGER  NOT NULL,
+    tx_id      BIGINT   NULL,
+    error      TEXT     NULL,
+    updated_at DATETIME NOT NULL,
+    PRIMARY KEY (batch_id, row_index)
+);
+
+CREATE INDEX batch_payouts_tx_id_index ON batch_payouts (tx_id);
@@ -0,0 +1,2 @@
+DROP INDEX scheduled_payments_next_run_at_index;
+DROP TABLE scheduled_payments;
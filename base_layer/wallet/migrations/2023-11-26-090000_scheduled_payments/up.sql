@@ -0,0 +1,24 @@
+CREATE TABLE scheduled_payments
+(
+    schedule_id     TEXT PRIMARY KEY NOT NULL,
+    address         BLOB     NOT NULL,
+    amount          BIGINT   NOT NULL,
+    message         TEXT     NOT NULL,
+    account_id      BIGINT   NOT NULL,
+    fee_per_gram    BIGINT   NOT NULL,
+    cadence         TEXT     NOT NULL,
+    start_at        DATETIME NOT NULL,
+    occurrence      BIGINT   NOT NULL,
+    next_run_at     DATETIME NOT NULL,
+    end_at          DATETIME NULL,
+    max_payments    BIGINT   NULL,
+    payments_made   BIGINT   NOT NULL,
+    failed_attempts INTEGER  NOT NULL,
+    last_error      TEXT     NULL,
+    last_tx_id      BIGINT   NULL,
+    status          INTEGER  NOT NULL,
+    created_at      DATETIME NOT NULL,
+    updated_at      DATETIME NOT NULL
+);
+
+CREATE INDEX scheduled_payments_next_run_at_index ON scheduled_payments (status, next_run_at);
@@ -0,0 +1,2 @@
+DROP INDEX webhook_deliveries_url_created_at_index;
+DROP TABLE webhook_deliveries;
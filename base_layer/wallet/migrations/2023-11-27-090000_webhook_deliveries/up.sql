@@ -0,0 +1,14 @@
+CREATE TABLE webhook_deliveries
+(
+    event_id        TEXT     NOT NULL,
+    url             TEXT     NOT NULL,
+    event           TEXT     NOT NULL,
+    payload         TEXT     NOT NULL,
+    attempts        INTEGER  NOT NULL DEFAULT 0,
+    next_attempt_at DATETIME NOT NULL,
+    last_error      TEXT     NULL,
+    created_at      DATETIME NOT NULL,
+    PRIMARY KEY (event_id, url)
+);
+
+CREATE INDEX webhook_deliveries_url_created_at_index ON webhook_deliveries (url, created_at);
@@ -0,0 +1 @@
+ALTER TABLE scheduled_payments DROP COLUMN pending_tx_id;
@@ -0,0 +1,3 @@
+-- The transaction of an occurrence is recorded before it is sent, so that a payment that was interrupted by a restart
+-- can be reconciled instead of being paid twice
+ALTER TABLE scheduled_payments ADD COLUMN pending_tx_id BIGINT NULL;
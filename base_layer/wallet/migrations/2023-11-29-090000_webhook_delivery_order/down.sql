@@ -0,0 +1,23 @@
+CREATE TABLE webhook_deliveries_unordered
+(
+    event_id        TEXT     NOT NULL,
+    url             TEXT     NOT NULL,
+    event           TEXT     NOT NULL,
+    payload         TEXT     NOT NULL,
+    attempts        INTEGER  NOT NULL DEFAULT 0,
+    next_attempt_at DATETIME NOT NULL,
+    last_error      TEXT     NULL,
+    created_at      DATETIME NOT NULL,
+    PRIMARY KEY (event_id, url)
+);
+
+INSERT INTO webhook_deliveries_unordered (event_id, url, event, payload, attempts, next_attempt_at, last_error, created_at)
+SELECT event_id, url, event, payload, attempts, next_attempt_at, last_error, created_at
+FROM webhook_deliveries
+WHERE dead_lettered_at IS NULL;
+
+DROP INDEX webhook_deliveries_url_id_index;
+DROP TABLE webhook_deliveries;
+ALTER TABLE webhook_deliveries_unordered RENAME TO webhook_deliveries;
+
+CREATE INDEX webhook_deliveries_url_created_at_index ON webhook_deliveries (url, created_at);
@@ -0,0 +1,27 @@
+-- Deliveries are ordered by an autoincrement id rather than by their timestamp, which is not unique, and deliveries
+-- that keep failing are moved to a dead-letter state instead of blocking the endpoint forever
+CREATE TABLE webhook_deliveries_ordered
+(
+    id               INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    event_id         TEXT     NOT NULL,
+    url              TEXT     NOT NULL,
+    event            TEXT     NOT NULL,
+    payload          TEXT     NOT NULL,
+    attempts         INTEGER  NOT NULL DEFAULT 0,
+    next_attempt_at  DATETIME NOT NULL,
+    last_error       TEXT     NULL,
+    dead_lettered_at DATETIME NULL,
+    created_at       DATETIME NOT NULL,
+    CONSTRAINT unique_event_id_url UNIQUE (event_id, url)
+);
+
+INSERT INTO webhook_deliveries_ordered (event_id, url, event, payload, attempts, next_attempt_at, last_error, created_at)
+SELECT event_id, url, event, payload, attempts, next_attempt_at, last_error, created_at
+FROM webhook_deliveries
+ORDER BY created_at, event_id;
+
+DROP INDEX webhook_deliveries_url_created_at_index;
+DROP TABLE webhook_deliveries;
+ALTER TABLE webhook_deliveries_ordered RENAME TO webhook_deliveries;
+
+CREATE INDEX webhook_deliveries_url_id_index ON webhook_deliveries (url, dead_lettered_at, id);
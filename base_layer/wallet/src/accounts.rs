@@ -0,0 +1,188 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{
+    convert::TryFrom,
+    fmt,
+    fmt::{Display, Formatter},
+    str::FromStr,
+};
+
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use tari_common_types::types::PublicKey;
+use tari_key_manager::key_manager_service::{KeyManagerInterface, KeyManagerServiceError};
+
+/// The name given to the account that owns all funds of a wallet that has never created any other accounts
+pub const DEFAULT_ACCOUNT_NAME: &str = "default";
+
+const ACCOUNT_KEY_BRANCH_PREFIX: &str = "account_";
+const ACCOUNT_SPEND_KEY_BRANCH_SUFFIX: &str = "_spend";
+const ACCOUNT_SCRIPT_KEY_BRANCH_SUFFIX: &str = "_script";
+
+/// Identifies an account (sub-wallet) within a single wallet database. Account `0` is the default account and owns
+/// the wallet's primary address.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct AccountId(u32);
+
+impl AccountId {
+    pub const DEFAULT: AccountId = AccountId(0);
+
+    pub fn as_u32(self) -> u32 {
+        self.0
+    }
+
+    pub fn is_default(self) -> bool {
+        self == Self::DEFAULT
+    }
+
+    /// The key manager branch from which this account's address key is derived
+    pub fn key_branch(self) -> String {
+        format!("{}{}", ACCOUNT_KEY_BRANCH_PREFIX, self.0)
+    }
+
+    /// The key manager branch from which the commitment masks of this account's outputs are derived
+    pub fn spend_key_branch(self) -> String {
+        format!("{}{}", self.key_branch(), ACCOUNT_SPEND_KEY_BRANCH_SUFFIX)
+    }
+
+    /// The key manager branch from which the script keys of this account's outputs are derived. The script key of an
+    /// output has the same index as its spend key.
+    pub fn script_key_branch(self) -> String {
+        format!("{}{}", self.key_branch(), ACCOUNT_SCRIPT_KEY_BRANCH_SUFFIX)
+    }
+
+    /// Returns the account that owns the given key manager branch, if it is one of the account branches
+    pub fn from_key_branch(branch: &str) -> Option<Self> {
+        let id = branch.strip_prefix(ACCOUNT_KEY_BRANCH_PREFIX)?;
+        let id = id
+            .strip_suffix(ACCOUNT_SPEND_KEY_BRANCH_SUFFIX)
+            .or_else(|| id.strip_suffix(ACCOUNT_SCRIPT_KEY_BRANCH_SUFFIX))
+            .unwrap_or(id);
+        id.parse().ok().map(Self)
+    }
+}
+
+impl From<u32> for AccountId {
+    fn from(id: u32) -> Self {
+        Self(id)
+    }
+}
+
+impl From<AccountId> for u32 {
+    fn from(id: AccountId) -> Self {
+        id.0
+    }
+}
+
+impl From<AccountId> for i64 {
+    fn from(id: AccountId) -> Self {
+        i64::from(id.0)
+    }
+}
+
+impl TryFrom<i64> for AccountId {
+    type Error = String;
+
+    fn try_from(id: i64) -> Result<Self, Self::Error> {
+        u32::try_from(id)
+            .map(Self)
+            .map_err(|_| format!("Invalid account id: {}", id))
+    }
+}
+
+impl FromStr for AccountId {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.parse().map(Self).map_err(|_| format!("Invalid account id: {}", s))
+    }
+}
+
+impl Display for AccountId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Returns the non-default accounts whose key branches have been added to the key manager. Account ids are assigned
+/// sequentially and accounts are never removed, so the search stops at the first unknown branch.
+pub async fn registered_accounts<TKeyManagerInterface: KeyManagerInterface<PublicKey>>(
+    key_manager: &TKeyManagerInterface,
+) -> Result<Vec<AccountId>, KeyManagerServiceError> {
+    let mut accounts = Vec::new();
+    let mut id = 1;
+    loop {
+        let account = AccountId(id);
+        match key_manager.get_static_key(account.key_branch()).await {
+            Ok(_) => accounts.push(account),
+            Err(KeyManagerServiceError::UnknownKeyBranch) => return Ok(accounts),
+            Err(e) => return Err(e),
+        }
+        id += 1;
+    }
+}
+
+/// An account (sub-wallet) stored in the wallet database
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WalletAccount {
+    pub id: AccountId,
+    pub name: String,
+    pub created_at: NaiveDateTime,
+}
+
+impl WalletAccount {
+    pub fn is_default(&self) -> bool {
+        self.id.is_default()
+    }
+}
+
+impl Display for WalletAccount {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({})", self.name, self.id)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn it_round_trips_the_key_branch() {
+        let id = AccountId::from(12);
+        assert_eq!(id.key_branch(), "account_12");
+        assert_eq!(AccountId::from_key_branch(&id.key_branch()), Some(id));
+        assert_eq!(id.spend_key_branch(), "account_12_spend");
+        assert_eq!(AccountId::from_key_branch(&id.spend_key_branch()), Some(id));
+        assert_eq!(AccountId::from_key_branch(&id.script_key_branch()), Some(id));
+        assert_eq!(AccountId::from_key_branch("account_12_other"), None);
+        assert_eq!(AccountId::from_key_branch("account_"), None);
+        assert_eq!(AccountId::from_key_branch("comms"), None);
+    }
+
+    #[test]
+    fn it_rejects_out_of_range_ids() {
+        assert!(AccountId::try_from(-1i64).is_err());
+        assert_eq!(AccountId::try_from(7i64).unwrap(), AccountId::from(7));
+        assert!(AccountId::DEFAULT.is_default());
+    }
+}
@@ -44,6 +44,8 @@ use crate::{
 };
 
 pub const KEY_MANAGER_COMMS_SECRET_KEY_BRANCH_KEY: &str = "comms";
+pub const KEY_MANAGER_INVOICE_BRANCH_KEY: &str = "invoice";
+pub const KEY_MANAGER_SUBADDRESS_BRANCH_KEY: &str = "subaddress";
 
 fn deserialize_safe_password_option<'de, D>(deserializer: D) -> Result<Option<SafePassword>, D::Error>
 where D: serde::Deserializer<'de> {
@@ -95,6 +97,22 @@ pub struct WalletConfig {
     /// transaction events are received by the console wallet .
     /// (see example at 'applications/minotari_console_wallet/src/notifier/notify_example.sh')
     pub notify_file: Option<PathBuf>,
+    /// Endpoints that transaction events are POSTed to as signed JSON by the console wallet
+    pub webhook_urls: StringList,
+    /// The shared secret used to sign webhook payloads with HMAC-SHA256. Required if `webhook_urls` are set.
+    #[serde(deserialize_with = "deserialize_safe_password_option")]
+    pub webhook_secret: Option<SafePassword>,
+    /// How long to wait before retrying a failed webhook delivery, doubling with every further attempt
+    #[serde(with = "serializers::seconds")]
+    pub webhook_retry_delay: Duration,
+    /// The longest delay between webhook delivery attempts
+    #[serde(with = "serializers::seconds")]
+    pub webhook_max_retry_delay: Duration,
+    /// The number of delivery attempts after which a webhook event is moved to the dead letters, 0 means no limit
+    pub webhook_max_attempts: u32,
+    /// How long to wait for a webhook endpoint to respond
+    #[serde(with = "serializers::seconds")]
+    pub webhook_timeout: Duration,
     /// If true, a GRPC server will bind to the configured address and listen for incoming GRPC requests.
     pub grpc_enabled: bool,
     /// GRPC bind address of the wallet
@@ -145,6 +163,12 @@ impl Default for WalletConfig {
             command_send_wait_stage: TransactionStage::Broadcast,
             command_send_wait_timeout: Duration::from_secs(300),
             notify_file: None,
+            webhook_urls: StringList::default(),
+            webhook_secret: None,
+            webhook_retry_delay: Duration::from_secs(10),
+            webhook_max_retry_delay: Duration::from_secs(3600),
+            webhook_max_attempts: 20,
+            webhook_timeout: Duration::from_secs(10),
             grpc_enabled: false,
             grpc_address: None,
             grpc_authentication: GrpcAuthentication::default(),
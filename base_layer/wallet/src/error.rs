@@ -145,6 +145,12 @@ pub enum WalletStorageError {
     ValueNotFound(DbKey),
     #[error("Burnt proof not found: `{0}`")]
     BurntProofNotFound(u32),
+    #[error("An account named `{0}` already exists")]
+    AccountAlreadyExists(String),
+    #[error("Account not found: `{0}`")]
+    AccountNotFound(String),
+    #[error("No label found for `{0}`")]
+    LabelNotFound(String),
     #[error("Unexpected result: `{0}`")]
     UnexpectedResult(String),
     #[error("Blocking task spawn error: `{0}`")]
@@ -183,6 +189,10 @@ pub enum WalletStorageError {
     RecoverySeedError(String),
     #[error("Bad encryption version: `{0}`")]
     BadEncryptionVersion(String),
+    #[error("Invalid wallet backup: {0}")]
+    InvalidBackup(String),
+    #[error("Unsupported wallet backup version: `{0}`")]
+    UnsupportedBackupVersion(u8),
 }
 
 impl From<HexError> for WalletStorageError {
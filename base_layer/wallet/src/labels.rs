@@ -0,0 +1,151 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{
+    fmt,
+    fmt::{Display, Formatter},
+    str::FromStr,
+};
+
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use tari_common_types::{tari_address::TariAddress, transaction::TxId, types::Commitment};
+use tari_utilities::hex::Hex;
+
+const TRANSACTION_TARGET: &str = "transaction";
+const OUTPUT_TARGET: &str = "output";
+const CONTACT_TARGET: &str = "contact";
+
+/// The wallet item that a user label or note is attached to
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LabelTarget {
+    Transaction(TxId),
+    Output(Commitment),
+    Contact(TariAddress),
+}
+
+impl LabelTarget {
+    /// The name of the kind of item this target refers to, as stored in the database
+    pub fn target_type(&self) -> &'static str {
+        match self {
+            LabelTarget::Transaction(_) => TRANSACTION_TARGET,
+            LabelTarget::Output(_) => OUTPUT_TARGET,
+            LabelTarget::Contact(_) => CONTACT_TARGET,
+        }
+    }
+
+    /// The identifier of the item within its kind, as stored in the database
+    pub fn target_id(&self) -> String {
+        match self {
+            LabelTarget::Transaction(tx_id) => tx_id.to_string(),
+            LabelTarget::Output(commitment) => commitment.to_hex(),
+            LabelTarget::Contact(address) => address.to_hex(),
+        }
+    }
+
+    /// Reconstructs a target from its stored type and identifier
+    pub fn from_parts(target_type: &str, target_id: &str) -> Result<Self, String> {
+        match target_type {
+            TRANSACTION_TARGET => target_id
+                .parse::<u64>()
+                .map(|id| LabelTarget::Transaction(id.into()))
+                .map_err(|_| format!("Invalid transaction id: {}", target_id)),
+            OUTPUT_TARGET => Commitment::from_hex(target_id)
+                .map(LabelTarget::Output)
+                .map_err(|_| format!("Invalid output commitment: {}", target_id)),
+            CONTACT_TARGET => TariAddress::from_str(target_id)
+                .map(LabelTarget::Contact)
+                .map_err(|_| format!("Invalid contact address: {}", target_id)),
+            _ => Err(format!("Unknown label target type: {}", target_type)),
+        }
+    }
+}
+
+/// Parses targets of the form `transaction:<tx id>`, `output:<commitment hex>` or `contact:<address>`
+impl FromStr for LabelTarget {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (target_type, target_id) = s
+            .split_once(':')
+            .ok_or_else(|| format!("Expected <transaction|output|contact>:<id>, got `{}`", s))?;
+        let target_type = match target_type.trim().to_lowercase().as_str() {
+            "tx" | TRANSACTION_TARGET => TRANSACTION_TARGET,
+            "utxo" | OUTPUT_TARGET => OUTPUT_TARGET,
+            CONTACT_TARGET => CONTACT_TARGET,
+            other => return Err(format!("Unknown label target type: {}", other)),
+        };
+        Self::from_parts(target_type, target_id.trim())
+    }
+}
+
+impl Display for LabelTarget {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.target_type(), self.target_id())
+    }
+}
+
+/// A user-editable label and free-text note attached to a transaction, output or contact
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WalletLabel {
+    pub target: LabelTarget,
+    pub label: String,
+    pub note: String,
+    pub updated_at: NaiveDateTime,
+}
+
+impl WalletLabel {
+    /// Case-insensitive match of the query against the label and note
+    pub fn matches(&self, query: &str) -> bool {
+        let query = query.to_lowercase();
+        self.label.to_lowercase().contains(&query) || self.note.to_lowercase().contains(&query)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use tari_common_types::types::PublicKey;
+
+    use super::*;
+
+    #[test]
+    fn it_round_trips_targets() {
+        let target = LabelTarget::Transaction(TxId::from(42u64));
+        assert_eq!(target.to_string(), "transaction:42");
+        assert_eq!("tx:42".parse::<LabelTarget>().unwrap(), target);
+        assert_eq!(
+            LabelTarget::from_parts(target.target_type(), &target.target_id()).unwrap(),
+            target
+        );
+
+        let target = LabelTarget::Output(Commitment::from_public_key(&PublicKey::default()));
+        assert_eq!(target.to_string().parse::<LabelTarget>().unwrap(), target);
+    }
+
+    #[test]
+    fn it_rejects_invalid_targets() {
+        assert!("42".parse::<LabelTarget>().is_err());
+        assert!("block:42".parse::<LabelTarget>().is_err());
+        assert!("transaction:abc".parse::<LabelTarget>().is_err());
+        assert!("output:zz".parse::<LabelTarget>().is_err());
+    }
+}
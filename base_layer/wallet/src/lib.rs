@@ -9,9 +9,11 @@
 
 #[macro_use]
 mod macros;
+pub mod accounts;
 pub mod base_node_service;
 pub mod connectivity_service;
 pub mod error;
+pub mod labels;
 mod operation_id;
 pub mod output_manager_service;
 pub mod storage;
@@ -22,6 +24,7 @@ pub mod types;
 pub use tari_common_types::types::WalletHasher;
 pub mod util;
 pub mod wallet;
+pub mod webhooks;
 
 pub use operation_id::OperationId;
 
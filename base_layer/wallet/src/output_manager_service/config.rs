@@ -45,6 +45,18 @@ pub struct OutputManagerServiceConfig {
     pub autoignore_onesided_utxos: bool,
     /// The number of seconds that have to pass for the wallet to run revalidation of invalid UTXOs on startup.
     pub num_of_seconds_to_revalidate_invalid_utxos: u64,
+    /// If set to `true`, the wallet will periodically join small outputs according to the consolidation policy below
+    pub consolidation_enabled: bool,
+    /// The number of seconds between automatic consolidation runs
+    pub consolidation_check_interval: u64,
+    /// Outputs are joined when an account holds more than this number of unspent outputs, 0 means no limit
+    pub consolidation_max_utxo_count: usize,
+    /// Outputs up to and including this value, in micro MinoTari, are always joined
+    pub consolidation_dust_threshold: u64,
+    /// Consolidation is skipped while the mempool fee-per-gram is above this value
+    pub consolidation_max_fee_per_gram: u64,
+    /// The maximum fee, in micro MinoTari, paid per consolidation run, summed over the transactions of all accounts
+    pub consolidation_max_fee: u64,
 }
 
 impl Default for OutputManagerServiceConfig {
@@ -57,6 +69,12 @@ impl Default for OutputManagerServiceConfig {
             tx_validator_batch_size: 100,
             autoignore_onesided_utxos: false,
             num_of_seconds_to_revalidate_invalid_utxos: 60 * 60 * 24 * 3,
+            consolidation_enabled: false,
+            consolidation_check_interval: 60 * 60,
+            consolidation_max_utxo_count: 500,
+            consolidation_dust_threshold: 10_000,
+            consolidation_max_fee_per_gram: 5,
+            consolidation_max_fee: 250_000,
         }
     }
 }
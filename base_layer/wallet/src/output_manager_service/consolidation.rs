@@ -0,0 +1,349 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{
+    cmp::max,
+    fmt::{Display, Formatter},
+};
+
+use chrono::NaiveDateTime;
+use tari_common_types::{transaction::TxId, types::Commitment};
+use tari_core::transactions::{fee::Fee, tari_amount::MicroMinotari};
+
+use crate::{accounts::AccountId, output_manager_service::config::OutputManagerServiceConfig};
+
+/// The rules used to decide which unspent outputs are joined by the automatic consolidation task
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConsolidationPolicy {
+    /// Outputs are joined when an account holds more than this number of unspent outputs, 0 means no limit
+    pub max_utxo_count: usize,
+    /// Outputs with a value up to and including this value are always joined
+    pub dust_threshold: MicroMinotari,
+    /// Consolidation is skipped while the mempool fee-per-gram is above this value
+    pub max_fee_per_gram: MicroMinotari,
+    /// The maximum fee paid per consolidation run, summed over the transactions of all accounts
+    pub max_fee: MicroMinotari,
+}
+
+impl From<&OutputManagerServiceConfig> for ConsolidationPolicy {
+    fn from(config: &OutputManagerServiceConfig) -> Self {
+        Self {
+            max_utxo_count: config.consolidation_max_utxo_count,
+            dust_threshold: MicroMinotari(config.consolidation_dust_threshold),
+            max_fee_per_gram: MicroMinotari(config.consolidation_max_fee_per_gram),
+            max_fee: MicroMinotari(config.consolidation_max_fee),
+        }
+    }
+}
+
+/// The coin join a consolidation run would create. Outputs are only joined with outputs of the same account.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConsolidationPreview {
+    pub account_id: AccountId,
+    pub commitments: Vec<Commitment>,
+    pub input_value: MicroMinotari,
+    pub fee: MicroMinotari,
+    pub output_value: MicroMinotari,
+    pub fee_per_gram: MicroMinotari,
+}
+
+/// Selects the outputs that should be joined according to `policy`. Dust is always joined, and when there are more
+/// than `max_utxo_count` outputs the smallest outputs are joined to get back under the limit. Outputs that are worth
+/// less than the fee to spend them are left alone, and the join stops growing once it reaches the maximum fee or the
+/// maximum transaction weight. Returns `None` when there is nothing worth joining.
+pub fn select_outputs_to_consolidate(
+    account_id: AccountId,
+    policy: &ConsolidationPolicy,
+    mut outputs: Vec<(Commitment, MicroMinotari)>,
+    fee_per_gram: MicroMinotari,
+    fee_calc: &Fee,
+    features_and_scripts_size: usize,
+    max_weight: u64,
+) -> Option<ConsolidationPreview> {
+    outputs.sort_by_key(|(_, value)| *value);
+
+    let num_dust = outputs
+        .iter()
+        .take_while(|(_, value)| *value <= policy.dust_threshold)
+        .count();
+    // Joining n outputs into one reduces the output count by n - 1
+    let num_excess = match policy.max_utxo_count {
+        0 => 0,
+        limit => match outputs.len().saturating_sub(limit) {
+            0 => 0,
+            excess => excess + 1,
+        },
+    };
+
+    let input_fee = fee_calc.calculate(fee_per_gram, 0, 1, 0, 0);
+    let mut commitments = Vec::new();
+    let mut input_value = MicroMinotari::zero();
+    for (commitment, value) in outputs.into_iter().take(max(num_dust, num_excess)) {
+        if value <= input_fee {
+            continue;
+        }
+        let num_inputs = commitments.len() + 1;
+        if fee_calc
+            .weighting()
+            .calculate(1, num_inputs, 1, features_and_scripts_size) >
+            max_weight
+        {
+            break;
+        }
+        if fee_calc.calculate(fee_per_gram, 1, num_inputs, 1, features_and_scripts_size) > policy.max_fee {
+            break;
+        }
+        commitments.push(commitment);
+        input_value += value;
+    }
+
+    if commitments.len() < 2 {
+        return None;
+    }
+    let fee = fee_calc.calculate(fee_per_gram, 1, commitments.len(), 1, features_and_scripts_size);
+    if input_value <= fee {
+        return None;
+    }
+
+    Some(ConsolidationPreview {
+        account_id,
+        commitments,
+        input_value,
+        fee,
+        output_value: input_value - fee,
+        fee_per_gram,
+    })
+}
+
+/// Why a consolidation run did not create a transaction
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConsolidationSkipReason {
+    NothingToConsolidate,
+    FeeTooHigh {
+        fee_per_gram: MicroMinotari,
+        max_fee_per_gram: MicroMinotari,
+    },
+    Failed(String),
+}
+
+impl Display for ConsolidationSkipReason {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NothingToConsolidate => write!(f, "Nothing to consolidate"),
+            Self::FeeTooHigh {
+                fee_per_gram,
+                max_fee_per_gram,
+            } => write!(
+                f,
+                "Mempool fee-per-gram {} is above the maximum of {}",
+                fee_per_gram, max_fee_per_gram
+            ),
+            Self::Failed(e) => write!(f, "Failed: {}", e),
+        }
+    }
+}
+
+/// A coin join created by a consolidation run
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConsolidationTransaction {
+    pub tx_id: TxId,
+    pub preview: ConsolidationPreview,
+}
+
+/// The result of a single consolidation run
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConsolidationOutcome {
+    /// One transaction for every account whose outputs were joined
+    Submitted(Vec<ConsolidationTransaction>),
+    Skipped(ConsolidationSkipReason),
+}
+
+/// The state of the automatic consolidation task since the wallet was started
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConsolidationStatus {
+    pub enabled: bool,
+    pub policy: ConsolidationPolicy,
+    pub last_run: Option<NaiveDateTime>,
+    pub last_outcome: Option<ConsolidationOutcome>,
+    pub transactions_created: u64,
+    pub outputs_consolidated: u64,
+    pub fees_paid: MicroMinotari,
+}
+
+impl ConsolidationStatus {
+    pub fn new(enabled: bool, policy: ConsolidationPolicy) -> Self {
+        Self {
+            enabled,
+            policy,
+            last_run: None,
+            last_outcome: None,
+            transactions_created: 0,
+            outputs_consolidated: 0,
+            fees_paid: MicroMinotari::zero(),
+        }
+    }
+
+    pub fn record(&mut self, outcome: ConsolidationOutcome, timestamp: NaiveDateTime) {
+        if let ConsolidationOutcome::Submitted(transactions) = &outcome {
+            for ConsolidationTransaction { preview, .. } in transactions {
+                self.transactions_created += 1;
+                self.outputs_consolidated += preview.commitments.len() as u64;
+                self.fees_paid += preview.fee;
+            }
+        }
+        self.last_run = Some(timestamp);
+        self.last_outcome = Some(outcome);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use tari_common_types::types::{CommitmentFactory, PrivateKey};
+    use tari_core::transactions::weight::TransactionWeight;
+    use tari_crypto::commitment::HomomorphicCommitmentFactory;
+
+    use super::*;
+
+    fn outputs(values: &[u64]) -> Vec<(Commitment, MicroMinotari)> {
+        let factory = CommitmentFactory::default();
+        values
+            .iter()
+            .map(|v| (factory.commit_value(&PrivateKey::default(), *v), MicroMinotari(*v)))
+            .collect()
+    }
+
+    fn policy(max_utxo_count: usize, dust_threshold: u64, max_fee: u64) -> ConsolidationPolicy {
+        ConsolidationPolicy {
+            max_utxo_count,
+            dust_threshold: MicroMinotari(dust_threshold),
+            max_fee_per_gram: MicroMinotari(10),
+            max_fee: MicroMinotari(max_fee),
+        }
+    }
+
+    #[test]
+    fn it_joins_dust_that_covers_its_own_fee() {
+        let fee_calc = Fee::new(TransactionWeight::latest());
+        // A 1uT/g fee costs 8uT per input and 63uT for the kernel and output
+        let preview = select_outputs_to_consolidate(
+            AccountId::DEFAULT,
+            &policy(0, 500, 10_000),
+            outputs(&[1_000_000, 300, 5, 100, 200]),
+            MicroMinotari(1),
+            &fee_calc,
+            0,
+            u64::MAX,
+        )
+        .unwrap();
+        assert_eq!(
+            preview.commitments,
+            outputs(&[100, 200, 300]).into_iter().map(|o| o.0).collect::<Vec<_>>()
+        );
+        assert_eq!(preview.input_value, MicroMinotari(600));
+        assert_eq!(preview.fee, MicroMinotari(87));
+        assert_eq!(preview.output_value, MicroMinotari(513));
+
+        assert!(select_outputs_to_consolidate(
+            AccountId::DEFAULT,
+            &policy(0, 150, 10_000),
+            outputs(&[100, 200, 300]),
+            MicroMinotari(1),
+            &fee_calc,
+            0,
+            u64::MAX,
+        )
+        .is_none());
+    }
+
+    #[test]
+    fn it_joins_the_smallest_outputs_above_the_utxo_limit() {
+        let fee_calc = Fee::new(TransactionWeight::latest());
+        let values = (1..=10).map(|v| v * 1000).collect::<Vec<_>>();
+
+        let preview = select_outputs_to_consolidate(
+            AccountId::DEFAULT,
+            &policy(6, 0, 10_000),
+            outputs(&values),
+            MicroMinotari(1),
+            &fee_calc,
+            0,
+            u64::MAX,
+        )
+        .unwrap();
+        assert_eq!(preview.commitments.len(), 5);
+        assert_eq!(preview.input_value, MicroMinotari(15_000));
+
+        assert!(select_outputs_to_consolidate(
+            AccountId::DEFAULT,
+            &policy(10, 0, 10_000),
+            outputs(&values),
+            MicroMinotari(1),
+            &fee_calc,
+            0,
+            u64::MAX
+        )
+        .is_none());
+    }
+
+    #[test]
+    fn it_limits_the_fee_and_weight_of_a_run() {
+        let fee_calc = Fee::new(TransactionWeight::latest());
+        let values = (1..=10).map(|v| v * 1000).collect::<Vec<_>>();
+
+        let preview = select_outputs_to_consolidate(
+            AccountId::DEFAULT,
+            &policy(1, 0, 87),
+            outputs(&values),
+            MicroMinotari(1),
+            &fee_calc,
+            0,
+            u64::MAX,
+        )
+        .unwrap();
+        assert_eq!(preview.commitments.len(), 3);
+        assert_eq!(preview.fee, MicroMinotari(87));
+
+        let preview = select_outputs_to_consolidate(
+            AccountId::DEFAULT,
+            &policy(1, 0, 10_000),
+            outputs(&values),
+            MicroMinotari(1),
+            &fee_calc,
+            0,
+            79,
+        )
+        .unwrap();
+        assert_eq!(preview.commitments.len(), 2);
+
+        let mut status = ConsolidationStatus::new(true, policy(1, 0, 87));
+        status.record(
+            ConsolidationOutcome::Submitted(vec![ConsolidationTransaction {
+                tx_id: TxId::from(1u64),
+                preview,
+            }]),
+            NaiveDateTime::default(),
+        );
+        assert_eq!(status.transactions_created, 1);
+        assert_eq!(status.outputs_consolidated, 2);
+        assert_eq!(status.fees_paid, MicroMinotari(79));
+    }
+}
@@ -144,6 +144,8 @@ pub enum OutputManagerError {
     NoCommitmentsProvided,
     #[error("Invalid argument: {0}")]
     InvalidArgument(String),
+    #[error("Transaction weight of {weight} exceeds the maximum block transaction weight of {max}")]
+    TransactionWeightExceeded { weight: u64, max: u64 },
     #[error("Validation in progress")]
     ValidationInProgress,
     #[error("Invalid data: `{0}`")]
@@ -206,6 +208,8 @@ pub enum OutputManagerStorageError {
     AeadError(String),
     #[error("Tried to insert a script that already exists in the database")]
     DuplicateScript,
+    #[error("Tried to insert a subaddress that already exists in the database")]
+    DuplicateSubaddress,
     #[error("Tari script error: {0}")]
     ScriptError(#[from] ScriptError),
     #[error("Binary not stored as valid hex:{0}")]
@@ -23,6 +23,7 @@
 use std::{fmt, fmt::Formatter, sync::Arc};
 
 use tari_common_types::{
+    tari_address::TariAddress,
     transaction::TxId,
     types::{Commitment, FixedHash, HashOutput, PublicKey},
 };
@@ -42,20 +43,25 @@ use tari_utilities::hex::Hex;
 use tokio::sync::broadcast;
 use tower::Service;
 
-use crate::output_manager_service::{
-    error::OutputManagerError,
-    service::{Balance, OutputInfoByTxId},
-    storage::{
-        database::OutputBackendQuery,
-        models::{DbWalletOutput, KnownOneSidedPaymentScript, SpendingPriority},
+use crate::{
+    accounts::AccountId,
+    output_manager_service::{
+        consolidation::{ConsolidationOutcome, ConsolidationPreview, ConsolidationStatus},
+        error::OutputManagerError,
+        service::{Balance, OutputInfoByTxId},
+        storage::{
+            database::OutputBackendQuery,
+            models::{DbWalletOutput, KnownOneSidedPaymentScript, SpendingPriority, Subaddress},
+        },
+        UtxoSelectionCriteria,
     },
-    UtxoSelectionCriteria,
 };
 
 /// API Request enum
 #[allow(clippy::large_enum_variant)]
 pub enum OutputManagerRequest {
     GetBalance,
+    GetAccountBalance(AccountId),
     AddOutput((Box<WalletOutput>, Option<SpendingPriority>)),
     AddOutputWithTxId((TxId, Box<WalletOutput>, Option<SpendingPriority>)),
     AddUnvalidatedOutput((TxId, Box<WalletOutput>, Option<SpendingPriority>)),
@@ -81,12 +87,19 @@ pub enum OutputManagerRequest {
         output_features: Box<OutputFeatures>,
         fee_per_gram: MicroMinotari,
         lock_height: Option<u64>,
+        destination_account: AccountId,
     },
     CreatePayToSelfWithOutputs {
         outputs: Vec<WalletOutputBuilder>,
         fee_per_gram: MicroMinotari,
         selection_criteria: UtxoSelectionCriteria,
     },
+    CreateOneSidedBatchTransaction {
+        tx_id: TxId,
+        recipients: Vec<(TariAddress, MicroMinotari)>,
+        fee_per_gram: MicroMinotari,
+        selection_criteria: UtxoSelectionCriteria,
+    },
     CancelTransaction(TxId),
     GetSpentOutputs,
     GetUnspentOutputs,
@@ -122,6 +135,15 @@ pub enum OutputManagerRequest {
     CreateClaimShaAtomicSwapTransaction(HashOutput, PublicKey, MicroMinotari),
     CreateHtlcRefundTransaction(HashOutput, MicroMinotari),
     GetOutputInfoByTxId(TxId),
+    CreateSubaddress(String),
+    GetSubaddresses,
+    SetSubaddressLabel {
+        index: u64,
+        label: String,
+    },
+    PreviewConsolidation(Option<MicroMinotari>),
+    GetConsolidationStatus,
+    RunConsolidation,
 }
 
 impl fmt::Display for OutputManagerRequest {
@@ -131,6 +153,7 @@ impl fmt::Display for OutputManagerRequest {
         use OutputManagerRequest::*;
         match self {
             GetBalance => write!(f, "GetBalance"),
+            GetAccountBalance(account) => write!(f, "GetAccountBalance ({})", account),
             AddOutput((v, _)) => write!(f, "AddOutput ({})", v.value),
             AddOutputWithTxId((t, v, _)) => write!(f, "AddOutputWithTxId ({}: {})", t, v.value),
             AddUnvalidatedOutput((t, v, _)) => {
@@ -194,6 +217,12 @@ impl fmt::Display for OutputManagerRequest {
                 write!(f, "CreateOutputWithFeatures({}, {})", value, features,)
             },
             CreatePayToSelfWithOutputs { .. } => write!(f, "CreatePayToSelfWithOutputs"),
+            CreateOneSidedBatchTransaction { tx_id, recipients, .. } => write!(
+                f,
+                "CreateOneSidedBatchTransaction (TxId: {}, recipients: {})",
+                tx_id,
+                recipients.len()
+            ),
             ReinstateCancelledInboundTx(_) => write!(f, "ReinstateCancelledInboundTx"),
             CreateClaimShaAtomicSwapTransaction(output, pre_image, fee_per_gram) => write!(
                 f,
@@ -207,6 +236,12 @@ impl fmt::Display for OutputManagerRequest {
             ),
 
             GetOutputInfoByTxId(t) => write!(f, "GetOutputInfoByTxId: {}", t),
+            CreateSubaddress(label) => write!(f, "CreateSubaddress({})", label),
+            GetSubaddresses => write!(f, "GetSubaddresses"),
+            SetSubaddressLabel { index, label } => write!(f, "SetSubaddressLabel({}: {})", index, label),
+            PreviewConsolidation(fee_per_gram) => write!(f, "PreviewConsolidation(fee_per_gram: {:?})", fee_per_gram),
+            GetConsolidationStatus => write!(f, "GetConsolidationStatus"),
+            RunConsolidation => write!(f, "RunConsolidation"),
         }
     }
 }
@@ -239,10 +274,17 @@ pub enum OutputManagerResponse {
     AddKnownOneSidedPaymentScript,
     CreateOutputWithFeatures { output: Box<WalletOutputBuilder> },
     CreatePayToSelfWithOutputs { transaction: Box<Transaction>, tx_id: TxId },
+    OneSidedBatchTransaction((MicroMinotari, Transaction)),
     ReinstatedCancelledInboundTx,
     ClaimHtlcTransaction((TxId, MicroMinotari, MicroMinotari, Transaction)),
     OutputInfoByTxId(OutputInfoByTxId),
     CoinPreview((Vec<MicroMinotari>, MicroMinotari)),
+    Subaddress(Box<Subaddress>),
+    Subaddresses(Vec<Subaddress>),
+    SubaddressLabelSet,
+    ConsolidationPreviews(Vec<ConsolidationPreview>),
+    ConsolidationStatus(Box<ConsolidationStatus>),
+    ConsolidationRun(ConsolidationOutcome),
 }
 
 pub type OutputManagerEventSender = broadcast::Sender<Arc<OutputManagerEvent>>;
@@ -255,6 +297,13 @@ pub enum OutputManagerEvent {
     TxoValidationInternalFailure(u64),
     TxoValidationCommunicationFailure(u64),
     TxoValidationAlreadyBusy(u64),
+    /// A consolidation transaction was created by the automatic consolidation task and needs to be submitted
+    ConsolidationTransactionCreated {
+        tx_id: TxId,
+        transaction: Box<Transaction>,
+        amount: MicroMinotari,
+        account_id: AccountId,
+    },
 }
 
 impl fmt::Display for OutputManagerEvent {
@@ -272,6 +321,9 @@ impl fmt::Display for OutputManagerEvent {
             OutputManagerEvent::TxoValidationAlreadyBusy(tx) => {
                 write!(f, "Txo is already running, stopping {}", tx)
             },
+            OutputManagerEvent::ConsolidationTransactionCreated { tx_id, .. } => {
+                write!(f, "ConsolidationTransactionCreated for {}", tx_id)
+            },
         }
     }
 }
@@ -403,6 +455,18 @@ impl OutputManagerHandle {
         }
     }
 
+    /// Returns the balance of the outputs belonging to the given account
+    pub async fn get_account_balance(&mut self, account: AccountId) -> Result<Balance, OutputManagerError> {
+        match self
+            .handle
+            .call(OutputManagerRequest::GetAccountBalance(account))
+            .await??
+        {
+            OutputManagerResponse::Balance(b) => Ok(b),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
     pub async fn revalidate_all_outputs(&mut self) -> Result<u64, OutputManagerError> {
         match self.handle.call(OutputManagerRequest::RevalidateTxos).await?? {
             OutputManagerResponse::TxoValidationStarted(request_key) => Ok(request_key),
@@ -722,6 +786,31 @@ impl OutputManagerHandle {
         }
     }
 
+    /// Creates a single transaction paying one-sided outputs to all of the provided recipients. The inputs are
+    /// encumbered and confirmed against `tx_id`; the returned transaction is ready to be broadcast.
+    pub async fn create_one_sided_batch_transaction(
+        &mut self,
+        tx_id: TxId,
+        recipients: Vec<(TariAddress, MicroMinotari)>,
+        selection_criteria: UtxoSelectionCriteria,
+        fee_per_gram: MicroMinotari,
+    ) -> Result<(MicroMinotari, Transaction), OutputManagerError> {
+        match self
+            .handle
+            .call(OutputManagerRequest::CreateOneSidedBatchTransaction {
+                tx_id,
+                recipients,
+                fee_per_gram,
+                selection_criteria,
+            })
+            .await??
+        {
+            OutputManagerResponse::OneSidedBatchTransaction(result) => Ok(result),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Pay an amount from the account in `utxo_selection` to `destination_account` of this wallet
     pub async fn create_pay_to_self_transaction(
         &mut self,
         tx_id: TxId,
@@ -730,6 +819,7 @@ impl OutputManagerHandle {
         output_features: OutputFeatures,
         fee_per_gram: MicroMinotari,
         lock_height: Option<u64>,
+        destination_account: AccountId,
     ) -> Result<(MicroMinotari, Transaction), OutputManagerError> {
         match self
             .handle
@@ -740,6 +830,7 @@ impl OutputManagerHandle {
                 output_features: Box::new(output_features),
                 fee_per_gram,
                 lock_height,
+                destination_account,
             })
             .await??
         {
@@ -772,4 +863,65 @@ impl OutputManagerHandle {
             _ => Err(OutputManagerError::UnexpectedApiResponse),
         }
     }
+
+    /// Derive the next stealth subaddress from the wallet keys and store it with the given label
+    pub async fn create_subaddress(&mut self, label: String) -> Result<Subaddress, OutputManagerError> {
+        match self
+            .handle
+            .call(OutputManagerRequest::CreateSubaddress(label))
+            .await??
+        {
+            OutputManagerResponse::Subaddress(subaddress) => Ok(*subaddress),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
+    pub async fn get_subaddresses(&mut self) -> Result<Vec<Subaddress>, OutputManagerError> {
+        match self.handle.call(OutputManagerRequest::GetSubaddresses).await?? {
+            OutputManagerResponse::Subaddresses(subaddresses) => Ok(subaddresses),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
+    pub async fn set_subaddress_label(&mut self, index: u64, label: String) -> Result<(), OutputManagerError> {
+        match self
+            .handle
+            .call(OutputManagerRequest::SetSubaddressLabel { index, label })
+            .await??
+        {
+            OutputManagerResponse::SubaddressLabelSet => Ok(()),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Preview the coin joins the consolidation policy would create at the given fee-per-gram, or at the current
+    /// mempool fee-per-gram when none is given. There is one coin join for every account with outputs to consolidate.
+    pub async fn preview_consolidation(
+        &mut self,
+        fee_per_gram: Option<MicroMinotari>,
+    ) -> Result<Vec<ConsolidationPreview>, OutputManagerError> {
+        match self
+            .handle
+            .call(OutputManagerRequest::PreviewConsolidation(fee_per_gram))
+            .await??
+        {
+            OutputManagerResponse::ConsolidationPreviews(previews) => Ok(previews),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
+    pub async fn get_consolidation_status(&mut self) -> Result<ConsolidationStatus, OutputManagerError> {
+        match self.handle.call(OutputManagerRequest::GetConsolidationStatus).await?? {
+            OutputManagerResponse::ConsolidationStatus(status) => Ok(*status),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Run the consolidation policy now, even if automatic consolidation is disabled
+    pub async fn run_consolidation(&mut self) -> Result<ConsolidationOutcome, OutputManagerError> {
+        match self.handle.call(OutputManagerRequest::RunConsolidation).await?? {
+            OutputManagerResponse::ConsolidationRun(outcome) => Ok(outcome),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
 }
@@ -27,6 +27,8 @@ use std::{
 
 use tari_common_types::types::Commitment;
 
+use crate::accounts::AccountId;
+
 #[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
 pub enum UtxoSelectionMode {
     #[default]
@@ -42,6 +44,8 @@ pub struct UtxoSelectionCriteria {
     pub excluding: Vec<Commitment>,
     pub min_dust: u64,
     pub excluding_onesided: bool,
+    /// Only outputs belonging to this account are selected
+    pub account: AccountId,
 }
 
 impl UtxoSelectionCriteria {
@@ -71,11 +75,21 @@ impl UtxoSelectionCriteria {
             ..Default::default()
         }
     }
+
+    /// Restrict the selection to the outputs of the given account
+    pub fn for_account(mut self, account: AccountId) -> Self {
+        self.account = account;
+        self
+    }
 }
 
 impl Display for UtxoSelectionCriteria {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        write!(f, "filter: {}, ordering: {}", self.filter, self.ordering)
+        write!(
+            f,
+            "filter: {}, ordering: {}, account: {}",
+            self.filter, self.ordering, self.account
+        )
     }
 }
 
@@ -23,22 +23,29 @@
 use std::time::Instant;
 
 use log::*;
-use tari_common_types::{transaction::TxId, types::FixedHash};
+use tari_common_types::{
+    transaction::TxId,
+    types::{FixedHash, PublicKey},
+};
 use tari_core::transactions::{
     key_manager::{TariKeyId, TransactionKeyManagerBranch, TransactionKeyManagerInterface},
     tari_amount::MicroMinotari,
     transaction_components::{OutputType, TransactionError, TransactionOutput, WalletOutput},
 };
+use tari_key_manager::key_manager_service::KeyManagerServiceError;
 use tari_script::{inputs, script, ExecutionStack, Opcode, TariScript};
 use tari_utilities::hex::Hex;
 
-use crate::output_manager_service::{
-    error::{OutputManagerError, OutputManagerStorageError},
-    handle::RecoveredOutput,
-    storage::{
-        database::{OutputManagerBackend, OutputManagerDatabase},
-        models::{DbWalletOutput, KnownOneSidedPaymentScript},
-        OutputSource,
+use crate::{
+    accounts::{registered_accounts, AccountId},
+    output_manager_service::{
+        error::{OutputManagerError, OutputManagerStorageError},
+        handle::RecoveredOutput,
+        storage::{
+            database::{OutputManagerBackend, OutputManagerDatabase},
+            models::{DbWalletOutput, KnownOneSidedPaymentScript},
+            OutputSource,
+        },
     },
 };
 
@@ -123,6 +130,7 @@ where
 
         let mut rewound_outputs_with_tx_id: Vec<RecoveredOutput> = Vec::new();
         for (output, has_known_script, hash) in &mut rewound_outputs {
+            let account_id = Self::output_account(output);
             let db_output = DbWalletOutput::from_wallet_output(
                 output.clone(),
                 &self.master_key_manager,
@@ -131,7 +139,8 @@ where
                 None,
                 None,
             )
-            .await?;
+            .await?
+            .with_account_id(account_id);
             let tx_id = TxId::new_random();
             let output_hex = db_output.commitment.to_hex();
             if let Err(e) = self.db.add_unspent_output_with_tx_id(tx_id, db_output) {
@@ -162,6 +171,15 @@ where
         Ok(rewound_outputs_with_tx_id)
     }
 
+    // Outputs of the non-default accounts have script keys derived from one of the account's key branches
+    fn output_account(output: &WalletOutput) -> AccountId {
+        output
+            .script_key_id
+            .managed_branch()
+            .and_then(|branch| AccountId::from_key_branch(&branch))
+            .unwrap_or_default()
+    }
+
     // Helper function to get the output source for a given output
     fn output_source(output: &WalletOutput, has_known_script: bool) -> OutputSource {
         match output.features.output_type {
@@ -212,6 +230,10 @@ where
                         .master_key_manager
                         .find_script_key_id_from_spend_key_id(spending_key, Some(public_key))
                         .await?;
+                    let result = match result {
+                        Some(script_key_id) => Some(script_key_id),
+                        None => self.find_account_script_key_id(public_key).await?,
+                    };
                     if let Some(script_key_id) = result {
                         (ExecutionStack::default(), script_key_id)
                     } else {
@@ -228,6 +250,30 @@ where
         Ok(Some((input_data, script_key)))
     }
 
+    /// Searches the script key branches of the wallet's accounts for the given script public key
+    async fn find_account_script_key_id(
+        &self,
+        public_key: &PublicKey,
+    ) -> Result<Option<TariKeyId>, OutputManagerError> {
+        for account in registered_accounts(&self.master_key_manager).await? {
+            match self
+                .master_key_manager
+                .find_key_index(account.script_key_branch(), public_key)
+                .await
+            {
+                Ok(index) => {
+                    return Ok(Some(TariKeyId::Managed {
+                        branch: account.script_key_branch(),
+                        index,
+                    }))
+                },
+                Err(KeyManagerServiceError::KeyNotFoundInKeyChain) => {},
+                Err(e) => return Err(e.into()),
+            }
+        }
+        Ok(None)
+    }
+
     async fn attempt_output_recovery(
         &self,
         output: &TransactionOutput,
@@ -256,6 +302,19 @@ where
         &mut self,
         output: &mut WalletOutput,
     ) -> Result<(), OutputManagerError> {
+        // The script key of an account output is already known, the account's key indexes only need to move past it
+        let account = Self::output_account(output);
+        if !account.is_default() {
+            if let Some(index) = output.script_key_id.managed_index() {
+                self.master_key_manager
+                    .update_current_key_index_if_higher(account.spend_key_branch(), index)
+                    .await?;
+                self.master_key_manager
+                    .update_current_key_index_if_higher(account.script_key_branch(), index)
+                    .await?;
+            }
+            return Ok(());
+        }
         let public_key = self
             .master_key_manager
             .get_public_key_at_key_id(&output.spending_key_id)
@@ -20,13 +20,17 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use std::{convert::TryInto, fmt, sync::Arc};
+use std::{collections::HashMap, convert::TryInto, fmt, sync::Arc, time::Duration};
 
+use blake2::Blake2b;
+use chrono::Utc;
 use diesel::result::{DatabaseErrorKind, Error as DieselError};
-use futures::{pin_mut, StreamExt};
+use digest::consts::U32;
+use futures::{pin_mut, stream::FuturesUnordered, StreamExt};
 use log::*;
 use rand::{rngs::OsRng, RngCore};
 use tari_common_types::{
+    tari_address::TariAddress,
     transaction::TxId,
     types::{BlockHash, Commitment, HashOutput, PrivateKey, PublicKey},
 };
@@ -35,8 +39,12 @@ use tari_core::{
     borsh::SerializedSize,
     consensus::ConsensusConstants,
     covenants::Covenant,
-    one_sided::{shared_secret_to_output_encryption_key, stealth_address_script_spending_key},
-    proto::base_node::FetchMatchingUtxos,
+    one_sided::{
+        shared_secret_to_output_encryption_key,
+        shared_secret_to_output_spending_key,
+        stealth_address_script_spending_key,
+    },
+    proto::base_node::{FetchMatchingUtxos, GetMempoolFeePerGramStatsRequest},
     transactions::{
         fee::Fee,
         key_manager::{TariKeyId, TransactionKeyManagerBranch, TransactionKeyManagerInterface},
@@ -58,18 +66,34 @@ use tari_core::{
         SenderTransactionProtocol,
     },
 };
-use tari_crypto::keys::SecretKey;
-use tari_script::{inputs, script, ExecutionStack, Opcode, TariScript};
+use tari_crypto::keys::{PublicKey as PublicKeyTrait, SecretKey};
+use tari_key_manager::key_manager_service::KeyManagerServiceError;
+use tari_script::{inputs, one_sided_payment_script, script, ExecutionStack, Opcode, TariScript};
 use tari_service_framework::reply_channel;
 use tari_shutdown::ShutdownSignal;
 use tari_utilities::{hex::Hex, ByteArray};
-use tokio::sync::Mutex;
+use tokio::{
+    sync::Mutex,
+    task::JoinHandle,
+    time::{self, MissedTickBehavior},
+};
 
 use crate::{
+    accounts::{registered_accounts, AccountId},
     base_node_service::handle::{BaseNodeEvent, BaseNodeServiceHandle},
+    config::{KEY_MANAGER_INVOICE_BRANCH_KEY, KEY_MANAGER_SUBADDRESS_BRANCH_KEY},
     connectivity_service::WalletConnectivityInterface,
     output_manager_service::{
         config::OutputManagerServiceConfig,
+        consolidation::{
+            select_outputs_to_consolidate,
+            ConsolidationOutcome,
+            ConsolidationPolicy,
+            ConsolidationPreview,
+            ConsolidationSkipReason,
+            ConsolidationStatus,
+            ConsolidationTransaction,
+        },
         error::{OutputManagerError, OutputManagerProtocolError, OutputManagerStorageError},
         handle::{
             OutputManagerEvent,
@@ -83,7 +107,7 @@ use crate::{
         resources::OutputManagerResources,
         storage::{
             database::{OutputBackendQuery, OutputManagerBackend, OutputManagerDatabase},
-            models::{DbWalletOutput, KnownOneSidedPaymentScript, SpendingPriority},
+            models::{DbWalletOutput, KnownOneSidedPaymentScript, SpendingPriority, Subaddress},
             OutputSource,
             OutputStatus,
         },
@@ -93,6 +117,15 @@ use crate::{
 };
 
 const LOG_TARGET: &str = "wallet::output_manager_service";
+/// The number of subaddresses beyond the last known one that are checked when scanning, so that payments to
+/// subaddresses are found when a wallet is recovered from its seed words
+const SUBADDRESS_LOOKAHEAD: u64 = 20;
+/// The number of invoice payment addresses beyond the last known one that are checked when scanning, so that payments
+/// to invoices are found when a wallet is recovered from its seed words
+const INVOICE_LOOKAHEAD: u64 = 20;
+
+/// The mempool fee-per-gram a consolidation task found and the coin joins it selected at that fee
+type ConsolidationSelection = (MicroMinotari, Vec<ConsolidationPreview>);
 
 /// This service will manage a wallet's available outputs and the key manager that produces the keys for these outputs.
 /// The service will assemble transactions to be sent from the wallets available outputs and provide keys to receive
@@ -105,6 +138,7 @@ pub struct OutputManagerService<TBackend, TWalletConnectivity, TKeyManagerInterf
     base_node_service: BaseNodeServiceHandle,
     last_seen_tip_height: Option<u64>,
     validation_in_progress: Arc<Mutex<()>>,
+    consolidation_status: ConsolidationStatus,
 }
 
 impl<TBackend, TWalletConnectivity, TKeyManagerInterface>
@@ -130,6 +164,8 @@ where
         wallet_identity: WalletIdentity,
         key_manager: TKeyManagerInterface,
     ) -> Result<Self, OutputManagerError> {
+        let consolidation_status =
+            ConsolidationStatus::new(config.consolidation_enabled, ConsolidationPolicy::from(&config));
         let resources = OutputManagerResources {
             config,
             db,
@@ -148,6 +184,7 @@ where
             base_node_service,
             last_seen_tip_height: None,
             validation_in_progress: Arc::new(Mutex::new(())),
+            consolidation_status,
         })
     }
 
@@ -170,6 +207,18 @@ where
 
         let mut base_node_service_event_stream = self.base_node_service.get_event_stream();
 
+        let consolidation_enabled = self.resources.config.consolidation_enabled;
+        let consolidation_check_interval =
+            Duration::from_secs(self.resources.config.consolidation_check_interval.max(1));
+        let mut consolidation_interval = time::interval_at(
+            time::Instant::now() + consolidation_check_interval,
+            consolidation_check_interval,
+        );
+        consolidation_interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        let mut consolidation_tasks: FuturesUnordered<JoinHandle<Result<ConsolidationSelection, OutputManagerError>>> =
+            FuturesUnordered::new();
+        let mut consolidation_replies = Vec::new();
+
         debug!(target: LOG_TARGET, "Output Manager Service started");
         // Outputs marked as shorttermencumbered are not yet stored as transactions in the TMS, so lets clear them
         self.resources.db.clear_short_term_encumberances()?;
@@ -181,17 +230,35 @@ where
                         Err(e) => debug!(target: LOG_TARGET, "Lagging read on base node event broadcast channel: {}", e),
                     }
                 },
+                _ = consolidation_interval.tick(), if consolidation_enabled => {
+                    self.spawn_consolidation_task(&mut consolidation_tasks);
+                },
+                Some(result) = consolidation_tasks.next() => {
+                    let selection = result.unwrap_or_else(|e| {
+                        Err(OutputManagerError::ServiceError(format!("Consolidation task failed: {}", e)))
+                    });
+                    let outcome = self.complete_consolidation(selection).await;
+                    for reply_tx in consolidation_replies.drain(..) {
+                        let _result = reply_tx.send(Ok(OutputManagerResponse::ConsolidationRun(outcome.clone())));
+                    }
+                },
                 Some(request_context) = request_stream.next() => {
                 trace!(target: LOG_TARGET, "Handling Service API Request");
                     let (request, reply_tx) = request_context.split();
-                    let response = self.handle_request(request).await.map_err(|e| {
-                        warn!(target: LOG_TARGET, "Error handling request: {:?}", e);
-                        e
-                    });
-                    let _result = reply_tx.send(response).map_err(|e| {
-                        warn!(target: LOG_TARGET, "Failed to send reply");
-                        e
-                    });
+                    if let OutputManagerRequest::RunConsolidation = request {
+                        // The reply is sent once the consolidation task has completed
+                        self.spawn_consolidation_task(&mut consolidation_tasks);
+                        consolidation_replies.push(reply_tx);
+                    } else {
+                        let response = self.handle_request(request).await.map_err(|e| {
+                            warn!(target: LOG_TARGET, "Error handling request: {:?}", e);
+                            e
+                        });
+                        let _result = reply_tx.send(response).map_err(|e| {
+                            warn!(target: LOG_TARGET, "Failed to send reply");
+                            e
+                        });
+                    }
                 },
                 _ = shutdown.wait() => {
                     info!(target: LOG_TARGET, "Output manager service shutting down because it received the shutdown signal");
@@ -231,7 +298,15 @@ where
                     Ok(metadata) => metadata.map(|m| m.best_block_height()),
                     Err(_) => None,
                 };
-                self.get_balance(current_tip_for_time_lock_calculation)
+                self.get_balance(None, current_tip_for_time_lock_calculation)
+                    .map(OutputManagerResponse::Balance)
+            },
+            OutputManagerRequest::GetAccountBalance(account) => {
+                let current_tip_for_time_lock_calculation = match self.base_node_service.get_chain_metadata().await {
+                    Ok(metadata) => metadata.map(|m| m.best_block_height()),
+                    Err(_) => None,
+                };
+                self.get_balance(Some(account), current_tip_for_time_lock_calculation)
                     .map(OutputManagerResponse::Balance)
             },
             OutputManagerRequest::GetRecipientTransaction(tsm) => self
@@ -271,6 +346,7 @@ where
                 output_features,
                 fee_per_gram,
                 lock_height,
+                destination_account,
             } => self
                 .create_pay_to_self_transaction(
                     tx_id,
@@ -279,6 +355,7 @@ where
                     *output_features,
                     fee_per_gram,
                     lock_height,
+                    destination_account,
                 )
                 .await
                 .map(OutputManagerResponse::PayToSelfTransaction),
@@ -363,7 +440,7 @@ where
                 commitments,
                 fee_per_gram,
             } => self
-                .create_coin_join(commitments, fee_per_gram)
+                .create_coin_join(commitments, fee_per_gram, AccountId::DEFAULT)
                 .await
                 .map(OutputManagerResponse::Transaction),
 
@@ -402,6 +479,15 @@ where
                     tx_id,
                 })
             },
+            OutputManagerRequest::CreateOneSidedBatchTransaction {
+                tx_id,
+                recipients,
+                fee_per_gram,
+                selection_criteria,
+            } => self
+                .create_one_sided_batch_transaction(tx_id, recipients, selection_criteria, fee_per_gram)
+                .await
+                .map(OutputManagerResponse::OneSidedBatchTransaction),
             OutputManagerRequest::CreateClaimShaAtomicSwapTransaction(output_hash, pre_image, fee_per_gram) => {
                 self.claim_sha_atomic_swap_with_hash(output_hash, pre_image, fee_per_gram)
                     .await
@@ -414,6 +500,38 @@ where
                 let output_statuses_by_tx_id = self.get_output_info_by_tx_id(tx_id)?;
                 Ok(OutputManagerResponse::OutputInfoByTxId(output_statuses_by_tx_id))
             },
+            OutputManagerRequest::CreateSubaddress(label) => self
+                .create_subaddress(label)
+                .await
+                .map(|subaddress| OutputManagerResponse::Subaddress(Box::new(subaddress))),
+            OutputManagerRequest::GetSubaddresses => Ok(OutputManagerResponse::Subaddresses(
+                self.resources.db.get_subaddresses()?,
+            )),
+            OutputManagerRequest::SetSubaddressLabel { index, label } => {
+                self.resources.db.set_subaddress_label(index, &label)?;
+                Ok(OutputManagerResponse::SubaddressLabelSet)
+            },
+            OutputManagerRequest::PreviewConsolidation(fee_per_gram) => {
+                let fee_per_gram = match fee_per_gram {
+                    Some(fee_per_gram) => fee_per_gram,
+                    None => Self::get_mempool_fee_per_gram(&mut self.resources.connectivity).await?,
+                };
+                Self::preview_consolidation(
+                    &self.resources,
+                    self.consolidation_status.policy,
+                    fee_per_gram,
+                    self.last_seen_tip_height,
+                )
+                .await
+                .map(OutputManagerResponse::ConsolidationPreviews)
+            },
+            OutputManagerRequest::GetConsolidationStatus => Ok(OutputManagerResponse::ConsolidationStatus(Box::new(
+                self.consolidation_status.clone(),
+            ))),
+            // Runs are answered by the consolidation task that the service loop spawns for them
+            OutputManagerRequest::RunConsolidation => Err(OutputManagerError::ServiceError(
+                "Consolidation runs are not handled as a request".to_string(),
+            )),
         }
     }
 
@@ -662,8 +780,48 @@ where
             .with_script_key(script_key_id))
     }
 
-    fn get_balance(&self, current_tip_for_time_lock_calculation: Option<u64>) -> Result<Balance, OutputManagerError> {
-        let balance = self.resources.db.get_balance(current_tip_for_time_lock_calculation)?;
+    /// Returns the next spend and script key ids for an output owned by the given account. The keys of non-default
+    /// accounts are derived from the account's own branches, so that recovered outputs can be attributed to it.
+    async fn get_next_spend_and_script_key_ids(
+        &self,
+        account: AccountId,
+    ) -> Result<(TariKeyId, PublicKey, TariKeyId, PublicKey), OutputManagerError> {
+        if account.is_default() {
+            return Ok(self.resources.key_manager.get_next_spend_and_script_key_ids().await?);
+        }
+        let (spend_key_id, spend_public_key) = self
+            .resources
+            .key_manager
+            .get_next_key(account.spend_key_branch())
+            .await?;
+        let index = spend_key_id
+            .managed_index()
+            .ok_or(KeyManagerServiceError::KyeIdWithoutIndex)?;
+        self.resources
+            .key_manager
+            .update_current_key_index_if_higher(account.script_key_branch(), index)
+            .await?;
+        let script_key_id = TariKeyId::Managed {
+            branch: account.script_key_branch(),
+            index,
+        };
+        let script_public_key = self
+            .resources
+            .key_manager
+            .get_public_key_at_key_id(&script_key_id)
+            .await?;
+        Ok((spend_key_id, spend_public_key, script_key_id, script_public_key))
+    }
+
+    fn get_balance(
+        &self,
+        account: Option<AccountId>,
+        current_tip_for_time_lock_calculation: Option<u64>,
+    ) -> Result<Balance, OutputManagerError> {
+        let balance = self
+            .resources
+            .db
+            .get_balance(account, current_tip_for_time_lock_calculation)?;
         trace!(target: LOG_TARGET, "Balance: {:?}", balance);
         Ok(balance)
     }
@@ -878,6 +1036,7 @@ where
         recipient_covenant: Covenant,
         recipient_minimum_value_promise: MicroMinotari,
     ) -> Result<SenderTransactionProtocol, OutputManagerError> {
+        let account_id = selection_criteria.account;
         debug!(
             target: LOG_TARGET,
             "Preparing to send transaction. Amount: {}. UTXO Selection: {}. Fee per gram: {}. ",
@@ -942,7 +1101,7 @@ where
         );
 
         let (change_spending_key_id, _, change_script_key_id, change_script_public_key) =
-            self.resources.key_manager.get_next_spend_and_script_key_ids().await?;
+            self.get_next_spend_and_script_key_ids(account_id).await?;
         builder.with_change_data(
             script!(PushPubKey(Box::new(change_script_public_key.clone()))),
             ExecutionStack::default(),
@@ -973,7 +1132,8 @@ where
                     Some(tx_id),
                     None,
                 )
-                .await?,
+                .await?
+                .with_account_id(account_id),
             );
         }
 
@@ -995,6 +1155,7 @@ where
         selection_criteria: UtxoSelectionCriteria,
         fee_per_gram: MicroMinotari,
     ) -> Result<(TxId, Transaction), OutputManagerError> {
+        let account_id = selection_criteria.account;
         let total_value = outputs.iter().map(|o| o.value()).sum();
         let nop_script = script![Nop];
         let weighting = self.resources.consensus_constants.transaction_weight_params();
@@ -1046,7 +1207,7 @@ where
 
         if input_selection.requires_change_output() {
             let (change_spending_key_id, _, change_script_key_id, change_script_public_key) =
-                self.resources.key_manager.get_next_spend_and_script_key_ids().await?;
+                self.get_next_spend_and_script_key_ids(account_id).await?;
             builder.with_change_data(
                 script!(PushPubKey(Box::new(change_script_public_key))),
                 ExecutionStack::default(),
@@ -1081,7 +1242,8 @@ where
                     None,
                     None,
                 )
-                .await?,
+                .await?
+                .with_account_id(account_id),
             )
         }
 
@@ -1100,7 +1262,8 @@ where
                     Some(tx_id),
                     None,
                 )
-                .await?,
+                .await?
+                .with_account_id(account_id),
             );
         }
 
@@ -1112,6 +1275,165 @@ where
         Ok((tx_id, stp.into_transaction()?))
     }
 
+    /// Build a single transaction that pays a one-sided output to each recipient. Only the change output is stored,
+    /// the recipient outputs belong to the recipients and will be found by their wallets when scanning. The
+    /// transaction is rejected (and its encumberance released) if it would not fit into a block.
+    #[allow(clippy::too_many_lines)]
+    async fn create_one_sided_batch_transaction(
+        &mut self,
+        tx_id: TxId,
+        recipients: Vec<(TariAddress, MicroMinotari)>,
+        selection_criteria: UtxoSelectionCriteria,
+        fee_per_gram: MicroMinotari,
+    ) -> Result<(MicroMinotari, Transaction), OutputManagerError> {
+        if recipients.is_empty() {
+            return Err(OutputManagerError::InvalidArgument(
+                "A batch transaction requires at least one recipient".to_string(),
+            ));
+        }
+        let account_id = selection_criteria.account;
+        let total_value = recipients.iter().map(|(_, amount)| *amount).sum();
+        let output_features = OutputFeatures::default();
+        let covenant = Covenant::default();
+        let weighting = self.resources.consensus_constants.transaction_weight_params();
+        let mut features_and_scripts_byte_size = 0;
+        for (address, _) in &recipients {
+            features_and_scripts_byte_size += weighting.round_up_features_and_scripts_size(
+                output_features
+                    .get_serialized_size()
+                    .map_err(|e| OutputManagerError::ConversionError(e.to_string()))? +
+                    one_sided_payment_script(address.public_key())
+                        .get_serialized_size()
+                        .map_err(|e| OutputManagerError::ConversionError(e.to_string()))? +
+                    covenant
+                        .get_serialized_size()
+                        .map_err(|e| OutputManagerError::ConversionError(e.to_string()))?,
+            );
+        }
+
+        let input_selection = self
+            .select_utxos(
+                total_value,
+                selection_criteria,
+                fee_per_gram,
+                recipients.len(),
+                features_and_scripts_byte_size,
+            )
+            .await?;
+
+        let mut builder = SenderTransactionProtocol::builder(
+            self.resources.consensus_constants.clone(),
+            self.resources.key_manager.clone(),
+        );
+        builder
+            .with_lock_height(0)
+            .with_fee_per_gram(fee_per_gram)
+            .with_prevent_fee_gt_amount(false)
+            .with_kernel_features(KernelFeatures::empty())
+            .with_tx_id(tx_id);
+
+        for uo in input_selection.iter() {
+            builder.with_input(uo.wallet_output.clone()).await?;
+        }
+
+        if input_selection.requires_change_output() {
+            let (change_spending_key_id, _, change_script_key_id, change_script_public_key) =
+                self.get_next_spend_and_script_key_ids(account_id).await?;
+            builder.with_change_data(
+                script!(PushPubKey(Box::new(change_script_public_key))),
+                ExecutionStack::default(),
+                change_script_key_id,
+                change_spending_key_id,
+                Covenant::default(),
+            );
+        }
+
+        for (address, amount) in recipients {
+            let (sender_offset_key_id, sender_offset_public_key) = self
+                .resources
+                .key_manager
+                .get_next_key(&TransactionKeyManagerBranch::SenderOffset.get_branch_key())
+                .await?;
+            // Diffie-Hellman shared secret `k_Ob * K_Sb = K_Ob * k_Sb` from which the recipient derives the
+            // spending and encryption keys when scanning
+            let shared_secret = self
+                .resources
+                .key_manager
+                .get_diffie_hellman_shared_secret(&sender_offset_key_id, address.public_key())
+                .await?;
+            let spending_key_id = self
+                .resources
+                .key_manager
+                .import_key(shared_secret_to_output_spending_key(&shared_secret)?)
+                .await?;
+            let encryption_key_id = self
+                .resources
+                .key_manager
+                .import_key(shared_secret_to_output_encryption_key(&shared_secret)?)
+                .await?;
+
+            let output = WalletOutputBuilder::new(amount, spending_key_id)
+                .with_features(output_features.clone())
+                .with_script(one_sided_payment_script(address.public_key()))
+                .encrypt_data_for_recovery(&self.resources.key_manager, Some(&encryption_key_id))
+                .await?
+                .with_input_data(inputs!(PublicKey::from_secret_key(
+                    self.resources.wallet_identity.node_identity.secret_key()
+                )))
+                .with_sender_offset_public_key(sender_offset_public_key)
+                .with_script_key(self.resources.wallet_identity.wallet_node_key_id.clone())
+                .with_minimum_value_promise(MicroMinotari::zero())
+                .sign_as_sender_and_receiver(&self.resources.key_manager, &sender_offset_key_id)
+                .await?
+                .try_build(&self.resources.key_manager)
+                .await?;
+            builder
+                .with_output(output, sender_offset_key_id)
+                .await
+                .map_err(|e| OutputManagerError::BuildError(e.to_string()))?;
+        }
+
+        let mut stp = builder
+            .build()
+            .await
+            .map_err(|e| OutputManagerError::BuildError(e.message))?;
+
+        let mut db_outputs = vec![];
+        if let Some(wallet_output) = stp.get_change_output()? {
+            db_outputs.push(
+                DbWalletOutput::from_wallet_output(
+                    wallet_output,
+                    &self.resources.key_manager,
+                    None,
+                    OutputSource::default(),
+                    Some(tx_id),
+                    None,
+                )
+                .await?
+                .with_account_id(account_id),
+            );
+        }
+
+        self.resources
+            .db
+            .encumber_outputs(tx_id, input_selection.into_selected(), db_outputs)?;
+        stp.finalize(&self.resources.key_manager).await?;
+        let fee = stp.get_fee_amount()?;
+        let transaction = stp.into_transaction()?;
+
+        let weight = transaction.calculate_weight(self.resources.consensus_constants.transaction_weight_params())?;
+        let max = self.resources.consensus_constants.max_block_transaction_weight();
+        if weight > max {
+            self.cancel_transaction(tx_id)?;
+            return Err(OutputManagerError::TransactionWeightExceeded { weight, max });
+        }
+        self.confirm_encumberance(tx_id)?;
+
+        Ok((fee, transaction))
+    }
+
+    /// Pay an amount from the account in the selection criteria to `destination_account`, which may be the same
+    /// account
     async fn create_pay_to_self_transaction(
         &mut self,
         tx_id: TxId,
@@ -1120,7 +1442,9 @@ where
         output_features: OutputFeatures,
         fee_per_gram: MicroMinotari,
         lock_height: Option<u64>,
+        destination_account: AccountId,
     ) -> Result<(MicroMinotari, Transaction), OutputManagerError> {
+        let account_id = selection_criteria.account;
         let covenant = Covenant::default();
 
         let features_and_scripts_byte_size = self
@@ -1165,17 +1489,19 @@ where
             builder.with_input(kmo.wallet_output.clone()).await?;
         }
 
-        let (output, sender_offset_key_id) = self.output_to_self(output_features, amount, covenant).await?;
+        let (output, sender_offset_key_id) = self
+            .output_to_self(output_features, amount, covenant, destination_account)
+            .await?;
 
         builder
             .with_output(output.wallet_output.clone(), sender_offset_key_id.clone())
             .await
             .map_err(|e| OutputManagerError::BuildError(e.to_string()))?;
 
-        let mut outputs = vec![output];
+        let mut outputs = vec![output.with_account_id(destination_account)];
 
         let (change_spending_key_id, _spend_public_key, change_script_key_id, change_script_public_key) =
-            self.resources.key_manager.get_next_spend_and_script_key_ids().await?;
+            self.get_next_spend_and_script_key_ids(account_id).await?;
         builder.with_change_data(
             script!(PushPubKey(Box::new(change_script_public_key.clone()))),
             ExecutionStack::default(),
@@ -1203,7 +1529,8 @@ where
                 Some(tx_id),
                 None,
             )
-            .await?;
+            .await?
+            .with_account_id(account_id);
             outputs.push(change_output);
         }
 
@@ -1355,7 +1682,7 @@ where
 
         if !perfect_utxo_selection && !enough_spendable {
             let current_tip_for_time_lock_calculation = chain_metadata.map(|cm| cm.best_block_height());
-            let balance = self.get_balance(current_tip_for_time_lock_calculation)?;
+            let balance = self.get_balance(Some(selection_criteria.account), current_tip_for_time_lock_calculation)?;
             let pending_incoming = balance.pending_incoming_balance;
             if utxos_total_value + pending_incoming >= amount + fee_with_change {
                 return Err(OutputManagerError::FundsPending);
@@ -1390,9 +1717,13 @@ where
     }
 
     fn default_features_and_scripts_size(&self) -> Result<usize, OutputManagerError> {
-        Ok(self
-            .resources
-            .consensus_constants
+        Self::features_and_scripts_size(&self.resources.consensus_constants)
+    }
+
+    /// The size of the default output features and script, rounded up as it is when the transaction weight is
+    /// calculated
+    fn features_and_scripts_size(consensus_constants: &ConsensusConstants) -> Result<usize, OutputManagerError> {
+        Ok(consensus_constants
             .transaction_weight_params()
             .round_up_features_and_scripts_size(
                 TariScript::default()
@@ -1576,7 +1907,7 @@ where
         let unspent_remainder = MicroMinotari(accumulated_amount.as_u64() % amount_per_split.as_u64());
 
         // preliminary balance check
-        if self.get_balance(None)?.available_balance < (accumulated_amount + fee) {
+        if self.get_balance(Some(AccountId::DEFAULT), None)?.available_balance < (accumulated_amount + fee) {
             return Err(OutputManagerError::NotEnoughFunds);
         }
 
@@ -1610,7 +1941,12 @@ where
             };
 
             let (output, sender_offset_key_id) = self
-                .output_to_self(OutputFeatures::default(), amount_per_split, Covenant::default())
+                .output_to_self(
+                    OutputFeatures::default(),
+                    amount_per_split,
+                    Covenant::default(),
+                    AccountId::DEFAULT,
+                )
                 .await?;
 
             tx_builder
@@ -1730,7 +2066,7 @@ where
         }
 
         // preliminary balance check
-        if self.get_balance(None)?.available_balance < (total_split_amount + final_fee) {
+        if self.get_balance(Some(AccountId::DEFAULT), None)?.available_balance < (total_split_amount + final_fee) {
             return Err(OutputManagerError::NotEnoughFunds);
         }
 
@@ -1765,7 +2101,12 @@ where
 
         for _ in 0..number_of_splits {
             let (output, sender_offset_key_id) = self
-                .output_to_self(OutputFeatures::default(), amount_per_split, Covenant::default())
+                .output_to_self(
+                    OutputFeatures::default(),
+                    amount_per_split,
+                    Covenant::default(),
+                    AccountId::DEFAULT,
+                )
                 .await?;
 
             tx_builder
@@ -1858,9 +2199,10 @@ where
         output_features: OutputFeatures,
         amount: MicroMinotari,
         covenant: Covenant,
+        account: AccountId,
     ) -> Result<(DbWalletOutput, TariKeyId), OutputManagerError> {
         let (spending_key_id, _, script_key_id, script_public_key) =
-            self.resources.key_manager.get_next_spend_and_script_key_ids().await?;
+            self.get_next_spend_and_script_key_ids(account).await?;
         let script = script!(PushPubKey(Box::new(script_public_key.clone())));
 
         let encrypted_data = self
@@ -1924,17 +2266,19 @@ where
     }
 
     #[allow(clippy::too_many_lines)]
+    /// Join outputs of the given account into a single output of the same account
     pub async fn create_coin_join(
         &mut self,
         commitments: Vec<Commitment>,
         fee_per_gram: MicroMinotari,
+        account: AccountId,
     ) -> Result<(TxId, Transaction, MicroMinotari), OutputManagerError> {
         let default_features_and_scripts_size = self
             .default_features_and_scripts_size()
             .map_err(|e| OutputManagerError::ConversionError(e.to_string()))?;
 
         let src_outputs = self.resources.db.fetch_unspent_outputs_for_spending(
-            &UtxoSelectionCriteria::specific(commitments),
+            &UtxoSelectionCriteria::specific(commitments).for_account(account),
             MicroMinotari::zero(),
             None,
         )?;
@@ -1956,7 +2300,7 @@ where
         }
 
         // preliminary balance check
-        if self.get_balance(None)?.available_balance < accumulated_amount {
+        if self.get_balance(Some(account), None)?.available_balance < accumulated_amount {
             return Err(OutputManagerError::NotEnoughFunds);
         }
 
@@ -1985,7 +2329,12 @@ where
         }
 
         let (output, sender_offset_key_id) = self
-            .output_to_self(OutputFeatures::default(), accumulated_amount, Covenant::default())
+            .output_to_self(
+                OutputFeatures::default(),
+                accumulated_amount,
+                Covenant::default(),
+                account,
+            )
             .await?;
 
         tx_builder
@@ -2276,8 +2625,23 @@ where
             ));
         }
 
+        // The static keys of the non-default accounts, whose addresses also receive stealth payments
+        let account_keys = known_keys
+            .iter()
+            .filter_map(|(public_key, key_id)| {
+                let account = key_id
+                    .managed_branch()
+                    .and_then(|branch| AccountId::from_key_branch(&branch))?;
+                Some((public_key.clone(), key_id.clone(), account))
+            })
+            .collect::<Vec<_>>();
+
         let wallet_sk = self.resources.wallet_identity.wallet_node_key_id.clone();
         let wallet_pk = self.resources.key_manager.get_public_key_at_key_id(&wallet_sk).await?;
+        // Only derived once a stealth output that is not addressed to the primary address is encountered
+        let mut subaddress_keys: Option<HashMap<PublicKey, (u64, bool)>> = None;
+        // Only derived once a one-sided output that is not addressed to a known script is encountered
+        let mut invoice_keys: Option<HashMap<PublicKey, u64>> = None;
 
         let mut scanned_outputs = vec![];
 
@@ -2286,25 +2650,47 @@ where
                 // ----------------------------------------------------------------------------
                 // simple one-sided address
                 [Opcode::PushPubKey(scanned_pk)] => {
-                    match known_keys.iter().find(|x| &x.0 == scanned_pk.as_ref()) {
-                        // none of the keys match, skipping
-                        None => continue,
-
-                        // match found
-                        Some(matched_key) => {
-                            let shared_secret = self
-                                .resources
-                                .key_manager
-                                .get_diffie_hellman_shared_secret(&matched_key.1, &output.sender_offset_public_key)
-                                .await?;
-                            scanned_outputs.push((
-                                output.clone(),
-                                OutputSource::OneSided,
-                                matched_key.1.clone(),
-                                shared_secret,
-                            ));
+                    let matched_key_id = match known_keys.iter().find(|x| &x.0 == scanned_pk.as_ref()) {
+                        Some(matched_key) => matched_key.1.clone(),
+                        // The invoice addresses of a recovered wallet are not known until a payment to them is found
+                        None => {
+                            if invoice_keys.is_none() {
+                                invoice_keys = Some(self.get_invoice_keys(&known_keys).await?);
+                            }
+                            let found = invoice_keys
+                                .as_ref()
+                                .and_then(|keys| keys.get(scanned_pk.as_ref()).copied());
+                            match found {
+                                // none of the keys match, skipping
+                                None => continue,
+                                Some(index) => {
+                                    let key_id = self.record_recovered_invoice_key(index, scanned_pk.as_ref()).await?;
+                                    known_keys.push((scanned_pk.as_ref().clone(), key_id.clone()));
+                                    // Move the lookahead window past the recovered invoice address
+                                    invoice_keys = None;
+                                    key_id
+                                },
+                            }
                         },
-                    }
+                    };
+
+                    let shared_secret = self
+                        .resources
+                        .key_manager
+                        .get_diffie_hellman_shared_secret(&matched_key_id, &output.sender_offset_public_key)
+                        .await?;
+                    // Outputs sent to an account address are spendable with that account's static key
+                    let account = matched_key_id
+                        .managed_branch()
+                        .and_then(|branch| AccountId::from_key_branch(&branch))
+                        .unwrap_or_default();
+                    scanned_outputs.push((
+                        output.clone(),
+                        OutputSource::OneSided,
+                        matched_key_id,
+                        shared_secret,
+                        account,
+                    ));
                 },
 
                 // ----------------------------------------------------------------------------
@@ -2312,36 +2698,71 @@ where
                 // NOTE: Extracting the nonce R and a spending (public aka scan_key) key from the script
                 // NOTE: [RFC 203 on Stealth Addresses](https://rfc.tari.com/RFC-0203_StealthAddresses.html)
                 [Opcode::PushPubKey(nonce), Opcode::Drop, Opcode::PushPubKey(scanned_pk)] => {
-                    // matching spending (public) keys
                     let stealth_address_hasher = self
                         .resources
                         .key_manager
                         .get_diffie_hellman_stealth_domain_hasher(&wallet_sk, nonce.as_ref())
                         .await?;
-                    let script_spending_key = stealth_address_script_spending_key(&stealth_address_hasher, &wallet_pk);
-                    if &script_spending_key != scanned_pk.as_ref() {
-                        continue;
-                    }
-
                     // Compute the stealth address offset
-                    let stealth_address_offset = PrivateKey::from_uniform_bytes(stealth_address_hasher.as_ref())
+                    let mut stealth_address_offset = PrivateKey::from_uniform_bytes(stealth_address_hasher.as_ref())
                         .expect("'DomainSeparatedHash<Blake2b<U64>>' has correct size");
+
+                    // matching spending (public) keys, first against the primary address, then against the account
+                    // addresses and finally against the subaddresses, whose spend key is whatever remains of the
+                    // script key once the offset is removed
+                    let script_spending_key = stealth_address_script_spending_key(&stealth_address_hasher, &wallet_pk);
+                    let mut account = AccountId::DEFAULT;
+                    let spend_key_id = if &script_spending_key == scanned_pk.as_ref() {
+                        wallet_sk.clone()
+                    } else if let Some((key_id, offset, matched_account)) = self
+                        .match_account_stealth_output(&account_keys, nonce.as_ref(), scanned_pk.as_ref())
+                        .await?
+                    {
+                        account = matched_account;
+                        stealth_address_offset = offset;
+                        key_id
+                    } else {
+                        let spend_public_key =
+                            scanned_pk.as_ref() - &PublicKey::from_secret_key(&stealth_address_offset);
+                        if subaddress_keys.is_none() {
+                            subaddress_keys = Some(self.get_subaddress_spend_keys().await?);
+                        }
+                        let found = subaddress_keys
+                            .as_ref()
+                            .and_then(|keys| keys.get(&spend_public_key).copied());
+                        match found {
+                            None => continue,
+                            Some((index, is_stored)) => {
+                                if !is_stored {
+                                    self.record_recovered_subaddress(index, spend_public_key).await?;
+                                    // Move the lookahead window past the recovered subaddress
+                                    subaddress_keys = None;
+                                }
+                                TariKeyId::Managed {
+                                    branch: KEY_MANAGER_SUBADDRESS_BRANCH_KEY.to_string(),
+                                    index,
+                                }
+                            },
+                        }
+                    };
+
                     let stealth_key = self
                         .resources
                         .key_manager
-                        .import_add_offset_to_private_key(&wallet_sk, stealth_address_offset)
+                        .import_add_offset_to_private_key(&spend_key_id, stealth_address_offset)
                         .await?;
 
                     let shared_secret = self
                         .resources
                         .key_manager
-                        .get_diffie_hellman_shared_secret(&wallet_sk, &output.sender_offset_public_key)
+                        .get_diffie_hellman_shared_secret(&spend_key_id, &output.sender_offset_public_key)
                         .await?;
                     scanned_outputs.push((
                         output.clone(),
                         OutputSource::StealthOneSided,
                         stealth_key,
                         shared_secret,
+                        account,
                     ));
                 },
 
@@ -2352,14 +2773,180 @@ where
         self.import_onesided_outputs(scanned_outputs).await
     }
 
+    /// Returns the public keys of the next `INVOICE_LOOKAHEAD` invoice payment addresses after the last known one,
+    /// mapped to their index
+    async fn get_invoice_keys(
+        &self,
+        known_keys: &[(PublicKey, TariKeyId)],
+    ) -> Result<HashMap<PublicKey, u64>, OutputManagerError> {
+        let next_index = known_keys
+            .iter()
+            .filter_map(|(_, key_id)| match key_id {
+                TariKeyId::Managed { branch, index } if branch == KEY_MANAGER_INVOICE_BRANCH_KEY => Some(index + 1),
+                _ => None,
+            })
+            .max()
+            .unwrap_or_default();
+        let mut invoice_keys = HashMap::new();
+        for index in next_index..next_index + INVOICE_LOOKAHEAD {
+            let key_id = TariKeyId::Managed {
+                branch: KEY_MANAGER_INVOICE_BRANCH_KEY.to_string(),
+                index,
+            };
+            let public_key = self.resources.key_manager.get_public_key_at_key_id(&key_id).await?;
+            invoice_keys.insert(public_key, index);
+        }
+        Ok(invoice_keys)
+    }
+
+    /// Store the script of an invoice payment address that received a payment but is not known to the database, which
+    /// happens when a wallet is recovered from its seed words, and make sure the address will not be handed out again
+    async fn record_recovered_invoice_key(
+        &mut self,
+        index: u64,
+        public_key: &PublicKey,
+    ) -> Result<TariKeyId, OutputManagerError> {
+        self.resources
+            .key_manager
+            .update_current_key_index_if_higher(KEY_MANAGER_INVOICE_BRANCH_KEY.to_string(), index)
+            .await?;
+        let script_key_id = TariKeyId::Managed {
+            branch: KEY_MANAGER_INVOICE_BRANCH_KEY.to_string(),
+            index,
+        };
+        let script = one_sided_payment_script(public_key);
+        self.add_known_script(KnownOneSidedPaymentScript {
+            script_hash: script
+                .as_hash::<Blake2b<U32>>()
+                .map_err(OutputManagerError::ScriptError)?
+                .to_vec(),
+            script_key_id: script_key_id.clone(),
+            script,
+            input: ExecutionStack::default(),
+            script_lock_height: 0,
+        })?;
+        Ok(script_key_id)
+    }
+
+    /// Matches a stealth output against the addresses of the non-default accounts. Returns the account's static key
+    /// id, the stealth address offset and the account if the output is addressed to one of them.
+    async fn match_account_stealth_output(
+        &self,
+        account_keys: &[(PublicKey, TariKeyId, AccountId)],
+        nonce: &PublicKey,
+        scanned_pk: &PublicKey,
+    ) -> Result<Option<(TariKeyId, PrivateKey, AccountId)>, OutputManagerError> {
+        for (public_key, key_id, account) in account_keys {
+            let stealth_address_hasher = self
+                .resources
+                .key_manager
+                .get_diffie_hellman_stealth_domain_hasher(key_id, nonce)
+                .await?;
+            if &stealth_address_script_spending_key(&stealth_address_hasher, public_key) == scanned_pk {
+                let stealth_address_offset = PrivateKey::from_uniform_bytes(stealth_address_hasher.as_ref())
+                    .expect("'DomainSeparatedHash<Blake2b<U64>>' has correct size");
+                return Ok(Some((key_id.clone(), stealth_address_offset, *account)));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Derive the next subaddress from the subaddress key branch and store it with the given label
+    async fn create_subaddress(&mut self, label: String) -> Result<Subaddress, OutputManagerError> {
+        let (key_id, spend_public_key) = self
+            .resources
+            .key_manager
+            .get_next_key(KEY_MANAGER_SUBADDRESS_BRANCH_KEY)
+            .await?;
+        let index = match key_id {
+            TariKeyId::Managed { index, .. } => index,
+            _ => {
+                return Err(OutputManagerError::ServiceError(
+                    "Subaddress key is not a managed key".to_string(),
+                ))
+            },
+        };
+        let subaddress = self.derive_subaddress(index, spend_public_key, label).await?;
+        self.resources.db.add_subaddress(subaddress.clone())?;
+        Ok(subaddress)
+    }
+
+    /// The view key of a subaddress is its spend key multiplied by the wallet's private key, so that a sender's shared
+    /// secret with the view key matches the wallet's shared secret with the nonce it publishes
+    async fn derive_subaddress(
+        &self,
+        index: u64,
+        spend_public_key: PublicKey,
+        label: String,
+    ) -> Result<Subaddress, OutputManagerError> {
+        let view_key = self
+            .resources
+            .key_manager
+            .get_diffie_hellman_shared_secret(&self.resources.wallet_identity.wallet_node_key_id, &spend_public_key)
+            .await?;
+        let view_public_key = PublicKey::from_canonical_bytes(view_key.as_bytes())
+            .map_err(|e| OutputManagerError::ConversionError(e.to_string()))?;
+        Ok(Subaddress {
+            index,
+            spend_public_key,
+            view_public_key,
+            label,
+            created_at: Utc::now().naive_utc(),
+        })
+    }
+
+    /// Returns the spend keys of the stored subaddresses and of the next `SUBADDRESS_LOOKAHEAD` subaddresses, mapped to
+    /// their index and whether they are stored
+    async fn get_subaddress_spend_keys(&self) -> Result<HashMap<PublicKey, (u64, bool)>, OutputManagerError> {
+        let mut spend_keys = self
+            .resources
+            .db
+            .get_subaddresses()?
+            .into_iter()
+            .map(|subaddress| (subaddress.spend_public_key, (subaddress.index, true)))
+            .collect::<HashMap<_, _>>();
+        let next_index = spend_keys
+            .values()
+            .map(|(index, _)| index + 1)
+            .max()
+            .unwrap_or_default();
+        for index in next_index..next_index + SUBADDRESS_LOOKAHEAD {
+            let key_id = TariKeyId::Managed {
+                branch: KEY_MANAGER_SUBADDRESS_BRANCH_KEY.to_string(),
+                index,
+            };
+            let spend_public_key = self.resources.key_manager.get_public_key_at_key_id(&key_id).await?;
+            spend_keys.insert(spend_public_key, (index, false));
+        }
+        Ok(spend_keys)
+    }
+
+    /// Store a subaddress that received a payment but is not known to the database, which happens when a wallet is
+    /// recovered from its seed words, and make sure it will not be handed out again
+    async fn record_recovered_subaddress(
+        &self,
+        index: u64,
+        spend_public_key: PublicKey,
+    ) -> Result<(), OutputManagerError> {
+        self.resources
+            .key_manager
+            .update_current_key_index_if_higher(KEY_MANAGER_SUBADDRESS_BRANCH_KEY.to_string(), index)
+            .await?;
+        let subaddress = self.derive_subaddress(index, spend_public_key, String::new()).await?;
+        match self.resources.db.add_subaddress(subaddress) {
+            Ok(()) | Err(OutputManagerStorageError::DuplicateSubaddress) => Ok(()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
     // Import scanned outputs into the wallet
     async fn import_onesided_outputs(
         &self,
-        scanned_outputs: Vec<(TransactionOutput, OutputSource, TariKeyId, CommsDHKE)>,
+        scanned_outputs: Vec<(TransactionOutput, OutputSource, TariKeyId, CommsDHKE, AccountId)>,
     ) -> Result<Vec<RecoveredOutput>, OutputManagerError> {
         let mut rewound_outputs = Vec::with_capacity(scanned_outputs.len());
 
-        for (output, output_source, script_private_key, shared_secret) in scanned_outputs {
+        for (output, output_source, script_private_key, shared_secret, account_id) in scanned_outputs {
             let encryption_key = shared_secret_to_output_encryption_key(&shared_secret)?;
             if let Ok((committed_value, spending_key)) =
                 EncryptedData::decrypt_data(&encryption_key, &output.commitment, &output.encrypted_data)
@@ -2397,7 +2984,8 @@ where
                         Some(tx_id),
                         None,
                     )
-                    .await?;
+                    .await?
+                    .with_account_id(account_id);
 
                     match self.resources.db.add_unspent_output_with_tx_id(tx_id, db_output) {
                         Ok(_) => {
@@ -2435,6 +3023,214 @@ where
     fn get_fee_calc(&self) -> Fee {
         Fee::new(*self.resources.consensus_constants.transaction_weight_params())
     }
+
+    /// The average fee-per-gram of the transactions that will make it into the next block, according to the mempool of
+    /// the connected base node
+    async fn get_mempool_fee_per_gram(
+        connectivity: &mut TWalletConnectivity,
+    ) -> Result<MicroMinotari, OutputManagerError> {
+        let stats = connectivity
+            .obtain_base_node_wallet_rpc_client()
+            .await
+            .ok_or_else(|| {
+                OutputManagerError::InvalidResponseError("Could not connect to base node rpc client".to_string())
+            })?
+            .get_mempool_fee_per_gram_stats(GetMempoolFeePerGramStatsRequest { count: 1 })
+            .await?
+            .stats;
+        // If there are no transactions in the mempool, the minimal fee per gram is enough
+        Ok(stats
+            .first()
+            .map(|stat| MicroMinotari(stat.avg_fee_per_gram.max(1)))
+            .unwrap_or(MicroMinotari(1)))
+    }
+
+    /// The coin joins the consolidation policy would create at the given fee-per-gram, one for every account with
+    /// outputs to consolidate. The fees of all joins together stay within the maximum fee of the policy.
+    async fn preview_consolidation(
+        resources: &OutputManagerResources<TBackend, TWalletConnectivity, TKeyManagerInterface>,
+        policy: ConsolidationPolicy,
+        fee_per_gram: MicroMinotari,
+        tip_height: Option<u64>,
+    ) -> Result<Vec<ConsolidationPreview>, OutputManagerError> {
+        let fee_calc = Fee::new(*resources.consensus_constants.transaction_weight_params());
+        let features_and_scripts_size = Self::features_and_scripts_size(&resources.consensus_constants)?;
+        let max_weight = resources
+            .consensus_constants
+            .max_block_weight_excluding_coinbase()
+            .map_err(|e| OutputManagerError::ConversionError(e.to_string()))?;
+
+        let mut accounts = vec![AccountId::DEFAULT];
+        accounts.extend(registered_accounts(&resources.key_manager).await?);
+        let mut previews = Vec::new();
+        let mut remaining_fee = policy.max_fee;
+        for account in accounts {
+            if remaining_fee == MicroMinotari::zero() {
+                break;
+            }
+            let mut selection_criteria =
+                UtxoSelectionCriteria::smallest_first(resources.config.dust_ignore_value).for_account(account);
+            selection_criteria.excluding_onesided = resources.config.autoignore_onesided_utxos;
+            let outputs = resources
+                .db
+                .fetch_unspent_outputs_for_spending(&selection_criteria, MicroMinotari::zero(), tip_height)?
+                .into_iter()
+                .map(|output| (output.commitment, output.wallet_output.value))
+                .collect();
+            let account_policy = ConsolidationPolicy {
+                max_fee: remaining_fee,
+                ..policy
+            };
+            if let Some(preview) = select_outputs_to_consolidate(
+                account,
+                &account_policy,
+                outputs,
+                fee_per_gram,
+                &fee_calc,
+                features_and_scripts_size,
+                max_weight,
+            ) {
+                remaining_fee = remaining_fee.saturating_sub(preview.fee);
+                previews.push(preview);
+            }
+        }
+        Ok(previews)
+    }
+
+    /// Spawn a task that looks up the mempool fee and selects the outputs to consolidate, so that waiting on the base
+    /// node does not hold up the service. Nothing is spawned while a previous task is still running.
+    fn spawn_consolidation_task(
+        &self,
+        consolidation_tasks: &mut FuturesUnordered<JoinHandle<Result<ConsolidationSelection, OutputManagerError>>>,
+    ) {
+        if !consolidation_tasks.is_empty() {
+            debug!(target: LOG_TARGET, "Output consolidation already in progress");
+            return;
+        }
+        let mut resources = self.resources.clone();
+        let policy = self.consolidation_status.policy;
+        let tip_height = self.last_seen_tip_height;
+        consolidation_tasks.push(tokio::spawn(async move {
+            let fee_per_gram = Self::get_mempool_fee_per_gram(&mut resources.connectivity).await?;
+            if fee_per_gram > policy.max_fee_per_gram {
+                return Ok((fee_per_gram, Vec::new()));
+            }
+            let previews = Self::preview_consolidation(&resources, policy, fee_per_gram, tip_height).await?;
+            Ok((fee_per_gram, previews))
+        }));
+    }
+
+    /// Create a coin join for every account selected by a consolidation task and hand them to the transaction service
+    async fn submit_consolidations(
+        &mut self,
+        fee_per_gram: MicroMinotari,
+        previews: Vec<ConsolidationPreview>,
+    ) -> Result<ConsolidationOutcome, OutputManagerError> {
+        let max_fee_per_gram = self.consolidation_status.policy.max_fee_per_gram;
+        if fee_per_gram > max_fee_per_gram {
+            return Ok(ConsolidationOutcome::Skipped(ConsolidationSkipReason::FeeTooHigh {
+                fee_per_gram,
+                max_fee_per_gram,
+            }));
+        }
+        if previews.is_empty() {
+            return Ok(ConsolidationOutcome::Skipped(
+                ConsolidationSkipReason::NothingToConsolidate,
+            ));
+        }
+
+        let mut transactions = Vec::with_capacity(previews.len());
+        let mut last_error = None;
+        let mut remaining_fee = self.consolidation_status.policy.max_fee;
+        for preview in previews {
+            // The policy may have been changed since the task selected the outputs
+            if preview.fee > remaining_fee {
+                debug!(
+                    target: LOG_TARGET,
+                    "Not consolidating outputs of account {}, the fee budget of this run is spent", preview.account_id
+                );
+                continue;
+            }
+            // The outputs may have been spent since the task selected them, which fails the join of that account only
+            match self.submit_consolidation(&preview).await {
+                Ok(tx_id) => {
+                    remaining_fee = remaining_fee.saturating_sub(preview.fee);
+                    transactions.push(ConsolidationTransaction { tx_id, preview });
+                },
+                Err(e) => {
+                    warn!(
+                        target: LOG_TARGET,
+                        "Error consolidating outputs of account {}: {}", preview.account_id, e
+                    );
+                    last_error = Some(e);
+                },
+            }
+        }
+        match last_error {
+            Some(e) if transactions.is_empty() => Err(e),
+            _ => Ok(ConsolidationOutcome::Submitted(transactions)),
+        }
+    }
+
+    async fn submit_consolidation(&mut self, preview: &ConsolidationPreview) -> Result<TxId, OutputManagerError> {
+        let (tx_id, transaction, amount) = self
+            .create_coin_join(preview.commitments.clone(), preview.fee_per_gram, preview.account_id)
+            .await?;
+        // The transaction service submits the join for broadcast and cancels it again if that fails
+        let event = OutputManagerEvent::ConsolidationTransactionCreated {
+            tx_id,
+            transaction: Box::new(transaction),
+            amount,
+            account_id: preview.account_id,
+        };
+        if let Err(e) = self.resources.event_publisher.send(Arc::new(event)) {
+            debug!(
+                target: LOG_TARGET,
+                "Error sending event because there are no subscribers: {:?}", e
+            );
+            self.cancel_transaction(tx_id)?;
+            return Err(OutputManagerError::ServiceError(
+                "No subscriber to submit the consolidation transaction".to_string(),
+            ));
+        }
+        Ok(tx_id)
+    }
+
+    /// Join the outputs selected by a consolidation task and record the outcome in the consolidation status
+    async fn complete_consolidation(
+        &mut self,
+        selection: Result<ConsolidationSelection, OutputManagerError>,
+    ) -> ConsolidationOutcome {
+        let result = match selection {
+            Ok((fee_per_gram, previews)) => self.submit_consolidations(fee_per_gram, previews).await,
+            Err(e) => Err(e),
+        };
+        let outcome = result.unwrap_or_else(|e| {
+            warn!(target: LOG_TARGET, "Error consolidating outputs: {}", e);
+            ConsolidationOutcome::Skipped(ConsolidationSkipReason::Failed(e.to_string()))
+        });
+        match &outcome {
+            ConsolidationOutcome::Submitted(transactions) => {
+                for ConsolidationTransaction { tx_id, preview } in transactions {
+                    info!(
+                        target: LOG_TARGET,
+                        "Consolidating {} outputs of account {} into one output of {} (TxId: {}, fee: {})",
+                        preview.commitments.len(),
+                        preview.account_id,
+                        preview.output_value,
+                        tx_id,
+                        preview.fee
+                    );
+                }
+            },
+            ConsolidationOutcome::Skipped(reason) => {
+                debug!(target: LOG_TARGET, "Output consolidation skipped: {}", reason)
+            },
+        }
+        self.consolidation_status
+            .record(outcome.clone(), Utc::now().naive_utc());
+        outcome
+    }
 }
 
 /// This struct holds the detailed balance of the Output Manager Service.
@@ -7,13 +7,16 @@ use tari_common_types::{
 };
 use tari_core::transactions::transaction_components::{OutputType, TransactionOutput};
 
-use crate::output_manager_service::{
-    error::OutputManagerStorageError,
-    input_selection::UtxoSelectionCriteria,
-    service::Balance,
-    storage::{
-        database::{DbKey, DbValue, OutputBackendQuery, WriteOperation},
-        models::DbWalletOutput,
+use crate::{
+    accounts::AccountId,
+    output_manager_service::{
+        error::OutputManagerStorageError,
+        input_selection::UtxoSelectionCriteria,
+        service::Balance,
+        storage::{
+            database::{DbKey, DbValue, OutputBackendQuery, WriteOperation},
+            models::{DbWalletOutput, Subaddress},
+        },
     },
 };
 
@@ -91,7 +94,7 @@ pub trait OutputManagerBackend: Send + Sync + Clone {
     /// Reinstate a cancelled inbound output
     fn reinstate_cancelled_inbound_output(&self, tx_id: TxId) -> Result<(), OutputManagerStorageError>;
     /// Return the available, time locked, pending incoming and pending outgoing balance
-    fn get_balance(&self, tip: Option<u64>) -> Result<Balance, OutputManagerStorageError>;
+    fn get_balance(&self, account: Option<AccountId>, tip: Option<u64>) -> Result<Balance, OutputManagerStorageError>;
     /// Import unvalidated output
     fn add_unvalidated_output(&self, output: DbWalletOutput, tx_id: TxId) -> Result<(), OutputManagerStorageError>;
     fn fetch_unspent_outputs_for_spending(
@@ -102,4 +105,12 @@ pub trait OutputManagerBackend: Send + Sync + Clone {
     ) -> Result<Vec<DbWalletOutput>, OutputManagerStorageError>;
     fn fetch_outputs_by_tx_id(&self, tx_id: TxId) -> Result<Vec<DbWalletOutput>, OutputManagerStorageError>;
     fn fetch_outputs_by(&self, q: OutputBackendQuery) -> Result<Vec<DbWalletOutput>, OutputManagerStorageError>;
+    /// Count the outputs that match the query, ignoring its pagination
+    fn count_outputs_by(&self, q: &OutputBackendQuery) -> Result<u64, OutputManagerStorageError>;
+    /// Store a stealth subaddress
+    fn add_subaddress(&self, subaddress: Subaddress) -> Result<(), OutputManagerStorageError>;
+    /// Fetch all stealth subaddresses, ordered by index
+    fn fetch_subaddresses(&self) -> Result<Vec<Subaddress>, OutputManagerStorageError>;
+    /// Change the label of the stealth subaddress at the given index
+    fn update_subaddress_label(&self, index: u64, label: &str) -> Result<(), OutputManagerStorageError>;
 }
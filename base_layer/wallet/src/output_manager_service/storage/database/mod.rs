@@ -38,13 +38,16 @@ use tari_core::transactions::{
 };
 use tari_utilities::hex::Hex;
 
-use crate::output_manager_service::{
-    error::OutputManagerStorageError,
-    input_selection::UtxoSelectionCriteria,
-    service::Balance,
-    storage::{
-        models::{DbWalletOutput, KnownOneSidedPaymentScript},
-        OutputStatus,
+use crate::{
+    accounts::AccountId,
+    output_manager_service::{
+        error::OutputManagerStorageError,
+        input_selection::UtxoSelectionCriteria,
+        service::Balance,
+        storage::{
+            models::{DbWalletOutput, KnownOneSidedPaymentScript, Subaddress},
+            OutputStatus,
+        },
     },
 };
 
@@ -176,11 +179,13 @@ where T: OutputManagerBackend + 'static
         Ok(())
     }
 
+    /// Returns the balance of the whole wallet if `account` is `None`, otherwise the balance of the given account
     pub fn get_balance(
         &self,
+        account: Option<AccountId>,
         current_tip_for_time_lock_calculation: Option<u64>,
     ) -> Result<Balance, OutputManagerStorageError> {
-        self.db.get_balance(current_tip_for_time_lock_calculation)
+        self.db.get_balance(account, current_tip_for_time_lock_calculation)
     }
 
     /// This method is called when a transaction is built to be sent. It will encumber unspent outputs against a pending
@@ -437,6 +442,22 @@ where T: OutputManagerBackend + 'static
     pub fn fetch_outputs_by(&self, q: OutputBackendQuery) -> Result<Vec<DbWalletOutput>, OutputManagerStorageError> {
         self.db.fetch_outputs_by(q)
     }
+
+    pub fn count_outputs_by(&self, q: &OutputBackendQuery) -> Result<u64, OutputManagerStorageError> {
+        self.db.count_outputs_by(q)
+    }
+
+    pub fn add_subaddress(&self, subaddress: Subaddress) -> Result<(), OutputManagerStorageError> {
+        self.db.add_subaddress(subaddress)
+    }
+
+    pub fn get_subaddresses(&self) -> Result<Vec<Subaddress>, OutputManagerStorageError> {
+        self.db.fetch_subaddresses()
+    }
+
+    pub fn set_subaddress_label(&self, index: u64, label: &str) -> Result<(), OutputManagerStorageError> {
+        self.db.update_subaddress_label(index, label)
+    }
 }
 
 fn unexpected_result<T>(req: DbKey, res: DbValue) -> Result<T, OutputManagerStorageError> {
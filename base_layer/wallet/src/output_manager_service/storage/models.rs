@@ -24,9 +24,11 @@ use std::{cmp::Ordering, convert::TryFrom};
 
 use chrono::NaiveDateTime;
 use derivative::Derivative;
+use tari_common::configuration::Network;
 use tari_common_types::{
+    tari_subaddress::TariSubaddress,
     transaction::TxId,
-    types::{BlockHash, Commitment, HashOutput},
+    types::{BlockHash, Commitment, HashOutput, PublicKey},
 };
 use tari_core::transactions::{
     key_manager::{TariKeyId, TransactionKeyManagerInterface},
@@ -34,9 +36,12 @@ use tari_core::transactions::{
 };
 use tari_script::{ExecutionStack, TariScript};
 
-use crate::output_manager_service::{
-    error::OutputManagerStorageError,
-    storage::{OutputSource, OutputStatus},
+use crate::{
+    accounts::AccountId,
+    output_manager_service::{
+        error::OutputManagerStorageError,
+        storage::{OutputSource, OutputStatus},
+    },
 };
 
 // ---------------------------------------------------------------------------
@@ -56,6 +61,7 @@ pub struct DbWalletOutput {
     pub source: OutputSource,
     pub received_in_tx_id: Option<TxId>,
     pub spent_in_tx_id: Option<TxId>,
+    pub account_id: AccountId,
 }
 
 impl DbWalletOutput {
@@ -82,8 +88,15 @@ impl DbWalletOutput {
             source,
             received_in_tx_id,
             spent_in_tx_id,
+            account_id: AccountId::DEFAULT,
         })
     }
+
+    /// Assign this output to the given account
+    pub fn with_account_id(mut self, account_id: AccountId) -> Self {
+        self.account_id = account_id;
+        self
+    }
 }
 
 impl From<DbWalletOutput> for WalletOutput {
@@ -156,3 +169,20 @@ impl PartialEq for KnownOneSidedPaymentScript {
         self.script_hash == other.script_hash
     }
 }
+
+/// A stealth subaddress handed out by the wallet. The spend key is the key at `index` on the subaddress key branch and
+/// the view key is the spend key multiplied by the wallet's private key.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Subaddress {
+    pub index: u64,
+    pub spend_public_key: PublicKey,
+    pub view_public_key: PublicKey,
+    pub label: String,
+    pub created_at: NaiveDateTime,
+}
+
+impl Subaddress {
+    pub fn to_address(&self, network: Network) -> TariSubaddress {
+        TariSubaddress::new(self.view_public_key.clone(), self.spend_public_key.clone(), network)
+    }
+}
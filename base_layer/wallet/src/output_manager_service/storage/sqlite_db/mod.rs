@@ -36,7 +36,7 @@ pub use output_sql::OutputSql;
 use tari_common_sqlite::{sqlite_connection_pool::PooledDbConnection, util::diesel_ext::ExpectedRowsExtension};
 use tari_common_types::{
     transaction::TxId,
-    types::{Commitment, FixedHash},
+    types::{Commitment, FixedHash, PublicKey},
 };
 use tari_core::transactions::{
     key_manager::TariKeyId,
@@ -47,17 +47,18 @@ use tari_script::{ExecutionStack, TariScript};
 use tokio::time::Instant;
 
 use crate::{
+    accounts::AccountId,
     output_manager_service::{
         error::OutputManagerStorageError,
         service::Balance,
         storage::{
             database::{DbKey, DbKeyValuePair, DbValue, OutputBackendQuery, OutputManagerBackend, WriteOperation},
-            models::{DbWalletOutput, KnownOneSidedPaymentScript},
+            models::{DbWalletOutput, KnownOneSidedPaymentScript, Subaddress},
             OutputStatus,
         },
         UtxoSelectionCriteria,
     },
-    schema::{known_one_sided_payment_scripts, outputs},
+    schema::{known_one_sided_payment_scripts, outputs, subaddresses},
     storage::sqlite_utilities::wallet_db_connection::WalletDbConnection,
 };
 mod new_output_sql;
@@ -792,13 +793,14 @@ impl OutputManagerBackend for OutputManagerSqliteDatabase {
 
     fn get_balance(
         &self,
+        account: Option<AccountId>,
         current_tip_for_time_lock_calculation: Option<u64>,
     ) -> Result<Balance, OutputManagerStorageError> {
         let start = Instant::now();
         let mut conn = self.database_connection.get_pooled_connection()?;
         let acquire_lock = start.elapsed();
 
-        let result = OutputSql::get_balance(current_tip_for_time_lock_calculation, &mut conn);
+        let result = OutputSql::get_balance(account, current_tip_for_time_lock_calculation, &mut conn);
         if start.elapsed().as_millis() > 0 {
             trace!(
                 target: LOG_TARGET,
@@ -1049,6 +1051,34 @@ impl OutputManagerBackend for OutputManagerSqliteDatabase {
             })
             .collect())
     }
+
+    fn count_outputs_by(&self, q: &OutputBackendQuery) -> Result<u64, OutputManagerStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        let count = OutputSql::count_outputs_by(q, &mut conn)?;
+        Ok(u64::try_from(count).unwrap_or_default())
+    }
+
+    fn add_subaddress(&self, subaddress: Subaddress) -> Result<(), OutputManagerStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        let subaddress_sql = SubaddressSql::from(subaddress);
+        if SubaddressSql::find(subaddress_sql.key_index, &mut conn)?.is_some() {
+            return Err(OutputManagerStorageError::DuplicateSubaddress);
+        }
+        subaddress_sql.commit(&mut conn)
+    }
+
+    fn fetch_subaddresses(&self) -> Result<Vec<Subaddress>, OutputManagerStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        SubaddressSql::index(&mut conn)?
+            .into_iter()
+            .map(Subaddress::try_from)
+            .collect()
+    }
+
+    fn update_subaddress_label(&self, index: u64, label: &str) -> Result<(), OutputManagerStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        SubaddressSql::update_label(index as i64, label, &mut conn)
+    }
 }
 
 fn update_outputs_with_tx_id_and_status_to_new_status(
@@ -1253,6 +1283,89 @@ impl KnownOneSidedPaymentScriptSql {
     }
 }
 
+#[derive(Clone, Debug, Queryable, Insertable, PartialEq)]
+#[diesel(table_name = subaddresses)]
+pub struct SubaddressSql {
+    pub key_index: i64,
+    pub spend_public_key: Vec<u8>,
+    pub view_public_key: Vec<u8>,
+    pub label: String,
+    pub created_at: NaiveDateTime,
+}
+
+impl SubaddressSql {
+    /// Write this struct to the database
+    pub fn commit(&self, conn: &mut SqliteConnection) -> Result<(), OutputManagerStorageError> {
+        diesel::insert_into(subaddresses::table)
+            .values(self.clone())
+            .execute(conn)?;
+        Ok(())
+    }
+
+    /// Find the subaddress at the given index, if it exists
+    pub fn find(
+        key_index: i64,
+        conn: &mut SqliteConnection,
+    ) -> Result<Option<SubaddressSql>, OutputManagerStorageError> {
+        Ok(subaddresses::table
+            .filter(subaddresses::key_index.eq(key_index))
+            .first::<SubaddressSql>(conn)
+            .optional()?)
+    }
+
+    /// Return all subaddresses, ordered by index
+    pub fn index(conn: &mut SqliteConnection) -> Result<Vec<SubaddressSql>, OutputManagerStorageError> {
+        Ok(subaddresses::table
+            .order(subaddresses::key_index.asc())
+            .load::<SubaddressSql>(conn)?)
+    }
+
+    pub fn update_label(
+        key_index: i64,
+        label: &str,
+        conn: &mut SqliteConnection,
+    ) -> Result<(), OutputManagerStorageError> {
+        diesel::update(subaddresses::table.filter(subaddresses::key_index.eq(key_index)))
+            .set(subaddresses::label.eq(label))
+            .execute(conn)
+            .num_rows_affected_or_not_found(1)?;
+        Ok(())
+    }
+}
+
+impl From<Subaddress> for SubaddressSql {
+    fn from(subaddress: Subaddress) -> Self {
+        Self {
+            key_index: subaddress.index as i64,
+            spend_public_key: subaddress.spend_public_key.to_vec(),
+            view_public_key: subaddress.view_public_key.to_vec(),
+            label: subaddress.label,
+            created_at: subaddress.created_at,
+        }
+    }
+}
+
+impl TryFrom<SubaddressSql> for Subaddress {
+    type Error = OutputManagerStorageError;
+
+    fn try_from(subaddress: SubaddressSql) -> Result<Self, Self::Error> {
+        let to_public_key = |bytes: &[u8]| {
+            PublicKey::from_canonical_bytes(bytes).map_err(|_| OutputManagerStorageError::ConversionError {
+                reason: "Could not convert subaddress public key from bytes".to_string(),
+            })
+        };
+        Ok(Self {
+            index: u64::try_from(subaddress.key_index).map_err(|_| OutputManagerStorageError::ConversionError {
+                reason: format!("Invalid subaddress index: {}", subaddress.key_index),
+            })?,
+            spend_public_key: to_public_key(&subaddress.spend_public_key)?,
+            view_public_key: to_public_key(&subaddress.view_public_key)?,
+            label: subaddress.label,
+            created_at: subaddress.created_at,
+        })
+    }
+}
+
 #[cfg(test)]
 mod test {
 
@@ -28,7 +28,7 @@ use std::{
 use borsh::BorshDeserialize;
 use chrono::NaiveDateTime;
 use derivative::Derivative;
-use diesel::{prelude::*, sql_query, SqliteConnection};
+use diesel::{prelude::*, sql_query, sqlite::Sqlite, SqliteConnection};
 use log::*;
 use tari_common_sqlite::util::diesel_ext::ExpectedRowsExtension;
 use tari_common_types::{
@@ -44,6 +44,7 @@ use tari_key_manager::key_manager_service::KeyId;
 use tari_script::{ExecutionStack, TariScript};
 
 use crate::{
+    accounts::AccountId,
     output_manager_service::{
         error::OutputManagerStorageError,
         input_selection::{UtxoSelectionCriteria, UtxoSelectionMode},
@@ -100,6 +101,7 @@ pub struct OutputSql {
     pub minimum_value_promise: i64,
     pub source: i32,
     pub last_validation_timestamp: Option<NaiveDateTime>,
+    pub account_id: i64,
 }
 
 impl OutputSql {
@@ -119,25 +121,53 @@ impl OutputSql {
     }
 
     /// Retrieves UTXOs by a set of given rules
-    #[allow(clippy::cast_sign_loss)]
     pub fn fetch_outputs_by(
         q: OutputBackendQuery,
         conn: &mut SqliteConnection,
     ) -> Result<Vec<OutputSql>, OutputManagerStorageError> {
-        let mut query = outputs::table
-            .into_boxed()
-            .filter(outputs::script_lock_height.le(q.tip_height))
-            .filter(outputs::maturity.le(q.tip_height));
+        let mut query = Self::query_outputs_by(&q);
 
         if let Some((offset, limit)) = q.pagination {
             query = query.offset(offset).limit(limit);
         }
 
+        use SortDirection::{Asc, Desc};
+        Ok(q.sorting
+            .into_iter()
+            .fold(query, |query, s| match s {
+                ("value", d) => match d {
+                    Asc => query.then_order_by(outputs::value.asc()),
+                    Desc => query.then_order_by(outputs::value.desc()),
+                },
+                ("mined_height", d) => match d {
+                    Asc => query.then_order_by(outputs::mined_height.asc()),
+                    Desc => query.then_order_by(outputs::mined_height.desc()),
+                },
+                _ => query,
+            })
+            .load(conn)?)
+    }
+
+    /// Counts the UTXOs that match the rules of the query, regardless of its pagination
+    pub fn count_outputs_by(
+        q: &OutputBackendQuery,
+        conn: &mut SqliteConnection,
+    ) -> Result<i64, OutputManagerStorageError> {
+        Ok(Self::query_outputs_by(q).count().get_result(conn)?)
+    }
+
+    #[allow(clippy::cast_sign_loss)]
+    fn query_outputs_by(q: &OutputBackendQuery) -> outputs::BoxedQuery<'static, Sqlite> {
+        let mut query = outputs::table
+            .into_boxed()
+            .filter(outputs::script_lock_height.le(q.tip_height))
+            .filter(outputs::maturity.le(q.tip_height));
+
         // filtering by OutputStatus
         query = match q.status.len() {
             0 => query,
             1 => query.filter(outputs::status.eq(q.status[0] as i32)),
-            _ => query.filter(outputs::status.eq_any::<Vec<i32>>(q.status.into_iter().map(|s| s as i32).collect())),
+            _ => query.filter(outputs::status.eq_any::<Vec<i32>>(q.status.iter().map(|s| *s as i32).collect())),
         };
 
         // filtering by Commitment
@@ -146,7 +176,7 @@ impl OutputSql {
                 0 => query,
                 1 => query.filter(outputs::commitment.eq(q.commitments[0].to_vec())),
                 _ => query.filter(
-                    outputs::commitment.eq_any::<Vec<Vec<u8>>>(q.commitments.into_iter().map(|c| c.to_vec()).collect()),
+                    outputs::commitment.eq_any::<Vec<Vec<u8>>>(q.commitments.iter().map(|c| c.to_vec()).collect()),
                 ),
             };
         }
@@ -169,21 +199,7 @@ impl OutputSql {
             };
         }
 
-        use SortDirection::{Asc, Desc};
-        Ok(q.sorting
-            .into_iter()
-            .fold(query, |query, s| match s {
-                ("value", d) => match d {
-                    Asc => query.then_order_by(outputs::value.asc()),
-                    Desc => query.then_order_by(outputs::value.desc()),
-                },
-                ("mined_height", d) => match d {
-                    Asc => query.then_order_by(outputs::mined_height.asc()),
-                    Desc => query.then_order_by(outputs::mined_height.desc()),
-                },
-                _ => query,
-            })
-            .load(conn)?)
+        query
     }
 
     /// Retrieves UTXOs than can be spent, sorted by priority, then value from smallest to largest.
@@ -197,10 +213,13 @@ impl OutputSql {
         let i64_tip_height = tip_height.and_then(|h| i64::try_from(h).ok()).unwrap_or(i64::MAX);
         let i64_value = i64::try_from(selection_criteria.min_dust).unwrap_or(i64::MAX);
 
+        let i64_account_id = i64::from(selection_criteria.account);
+
         let mut query = outputs::table
             .into_boxed()
             .filter(outputs::status.eq(OutputStatus::Unspent as i32))
             .filter(outputs::value.gt(i64_value))
+            .filter(outputs::account_id.eq(i64_account_id))
             .order_by(outputs::spending_priority.desc());
 
         // NOTE: Safe mode presets `script_lock_height` and `maturity` filters for all queries
@@ -246,6 +265,7 @@ impl OutputSql {
                 // lets get the max value for all utxos
                 let max: Option<i64> = outputs::table
                     .filter(outputs::status.eq(OutputStatus::Unspent as i32))
+                    .filter(outputs::account_id.eq(i64_account_id))
                     .filter(outputs::script_lock_height.le(i64_tip_height))
                     .filter(outputs::maturity.le(i64_tip_height))
                     .order(outputs::value.desc())
@@ -378,9 +398,11 @@ impl OutputSql {
             .load(conn)?)
     }
 
-    /// Return the available, time locked, pending incoming and pending outgoing balance
+    /// Return the available, time locked, pending incoming and pending outgoing balance, either for the whole wallet
+    /// or only for the outputs belonging to the given account
     #[allow(clippy::cast_possible_wrap)]
     pub fn get_balance(
+        account: Option<AccountId>,
         current_tip_for_time_lock_calculation: Option<u64>,
         conn: &mut SqliteConnection,
     ) -> Result<Balance, OutputManagerStorageError> {
@@ -393,18 +415,22 @@ impl OutputSql {
         }
         let balance_query_result = if let Some(current_tip) = current_tip_for_time_lock_calculation {
             let balance_query = sql_query(
-                "SELECT coalesce(sum(value), 0) as amount, 'available_balance' as category \
-                 FROM outputs WHERE status = ? \
+                "WITH scoped_outputs AS (SELECT * FROM outputs WHERE ? OR account_id = ?) \
+                 SELECT coalesce(sum(value), 0) as amount, 'available_balance' as category \
+                 FROM scoped_outputs WHERE status = ? \
                  UNION ALL \
                  SELECT coalesce(sum(value), 0) as amount, 'time_locked_balance' as category \
-                 FROM outputs WHERE status = ? AND maturity > ? OR script_lock_height > ? \
+                 FROM scoped_outputs WHERE status = ? AND maturity > ? OR script_lock_height > ? \
                  UNION ALL \
                  SELECT coalesce(sum(value), 0) as amount, 'pending_incoming_balance' as category \
-                 FROM outputs WHERE source != ? AND status = ? OR status = ? OR status = ? \
+                 FROM scoped_outputs WHERE source != ? AND status = ? OR status = ? OR status = ? \
                  UNION ALL \
                  SELECT coalesce(sum(value), 0) as amount, 'pending_outgoing_balance' as category \
-                 FROM outputs WHERE status = ? OR status = ? OR status = ?",
+                 FROM scoped_outputs WHERE status = ? OR status = ? OR status = ?",
             )
+                // account scope
+                .bind::<diesel::sql_types::Bool, _>(account.is_none())
+                .bind::<diesel::sql_types::BigInt, _>(account.map(i64::from).unwrap_or_default())
                 // available_balance
                 .bind::<diesel::sql_types::Integer, _>(OutputStatus::Unspent as i32)
                 // time_locked_balance
@@ -423,15 +449,19 @@ impl OutputSql {
             balance_query.load::<BalanceQueryResult>(conn)?
         } else {
             let balance_query = sql_query(
-                "SELECT coalesce(sum(value), 0) as amount, 'available_balance' as category \
-                 FROM outputs WHERE status = ? \
+                "WITH scoped_outputs AS (SELECT * FROM outputs WHERE ? OR account_id = ?) \
+                 SELECT coalesce(sum(value), 0) as amount, 'available_balance' as category \
+                 FROM scoped_outputs WHERE status = ? \
                  UNION ALL \
                  SELECT coalesce(sum(value), 0) as amount, 'pending_incoming_balance' as category \
-                 FROM outputs WHERE source != ? AND status = ? OR status = ? OR status = ? \
+                 FROM scoped_outputs WHERE source != ? AND status = ? OR status = ? OR status = ? \
                  UNION ALL \
                  SELECT coalesce(sum(value), 0) as amount, 'pending_outgoing_balance' as category \
-                 FROM outputs WHERE status = ? OR status = ? OR status = ?",
+                 FROM scoped_outputs WHERE status = ? OR status = ? OR status = ?",
             )
+                // account scope
+                .bind::<diesel::sql_types::Bool, _>(account.is_none())
+                .bind::<diesel::sql_types::BigInt, _>(account.map(i64::from).unwrap_or_default())
                 // available_balance
                 .bind::<diesel::sql_types::Integer, _>(OutputStatus::Unspent as i32)
                 // pending_incoming_balance
@@ -771,6 +801,8 @@ impl OutputSql {
             source: self.source.try_into()?,
             received_in_tx_id: self.received_in_tx_id.map(|d| (d as u64).into()),
             spent_in_tx_id: self.spent_in_tx_id.map(|d| (d as u64).into()),
+            account_id: AccountId::try_from(self.account_id)
+                .map_err(|reason| OutputManagerStorageError::ConversionError { reason })?,
         })
     }
 }
@@ -1,5 +1,27 @@
 // @generated automatically by Diesel CLI.
 
+diesel::table! {
+    accounts (id) {
+        id -> BigInt,
+        name -> Text,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    batch_payouts (batch_id, row_index) {
+        batch_id -> Text,
+        row_index -> BigInt,
+        address -> Binary,
+        amount -> BigInt,
+        memo -> Text,
+        status -> Integer,
+        tx_id -> Nullable<BigInt>,
+        error -> Nullable<Text>,
+        updated_at -> Timestamp,
+    }
+}
+
 diesel::table! {
     burnt_proofs (id) {
         id -> Integer,
@@ -37,6 +59,7 @@ diesel::table! {
         mined_timestamp -> Nullable<Timestamp>,
         transaction_signature_nonce -> Binary,
         transaction_signature_key -> Binary,
+        account_id -> BigInt,
     }
 }
 
@@ -52,6 +75,22 @@ diesel::table! {
         direct_send_success -> Integer,
         send_count -> Integer,
         last_send_timestamp -> Nullable<Timestamp>,
+        account_id -> BigInt,
+    }
+}
+
+diesel::table! {
+    invoices (payment_id) {
+        payment_id -> Text,
+        address -> Binary,
+        amount -> BigInt,
+        amount_received -> BigInt,
+        message -> Text,
+        status -> Integer,
+        tx_id -> Nullable<BigInt>,
+        created_at -> Timestamp,
+        expires_at -> Nullable<Timestamp>,
+        paid_at -> Nullable<Timestamp>,
     }
 }
 
@@ -65,6 +104,16 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    labels (target_type, target_id) {
+        target_type -> Text,
+        target_id -> Text,
+        label -> Text,
+        note -> Text,
+        updated_at -> Timestamp,
+    }
+}
+
 diesel::table! {
     outbound_transactions (tx_id) {
         tx_id -> BigInt,
@@ -78,6 +127,7 @@ diesel::table! {
         direct_send_success -> Integer,
         send_count -> Integer,
         last_send_timestamp -> Nullable<Timestamp>,
+        account_id -> BigInt,
     }
 }
 
@@ -117,6 +167,7 @@ diesel::table! {
         minimum_value_promise -> BigInt,
         source -> Integer,
         last_validation_timestamp -> Nullable<Timestamp>,
+        account_id -> BigInt,
     }
 }
 
@@ -130,6 +181,51 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    scheduled_payments (schedule_id) {
+        schedule_id -> Text,
+        address -> Binary,
+        amount -> BigInt,
+        message -> Text,
+        account_id -> BigInt,
+        fee_per_gram -> BigInt,
+        cadence -> Text,
+        start_at -> Timestamp,
+        occurrence -> BigInt,
+        next_run_at -> Timestamp,
+        end_at -> Nullable<Timestamp>,
+        max_payments -> Nullable<BigInt>,
+        payments_made -> BigInt,
+        failed_attempts -> Integer,
+        last_error -> Nullable<Text>,
+        last_tx_id -> Nullable<BigInt>,
+        status -> Integer,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+        pending_tx_id -> Nullable<BigInt>,
+    }
+}
+
+diesel::table! {
+    subaddresses (key_index) {
+        key_index -> BigInt,
+        spend_public_key -> Binary,
+        view_public_key -> Binary,
+        label -> Text,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    transaction_status_history (id) {
+        id -> Integer,
+        tx_id -> BigInt,
+        status -> Integer,
+        cancelled -> Nullable<Integer>,
+        changed_at -> Timestamp,
+    }
+}
+
 diesel::table! {
     wallet_settings (key) {
         key -> Text,
@@ -137,14 +233,37 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    webhook_deliveries (id) {
+        id -> BigInt,
+        event_id -> Text,
+        url -> Text,
+        event -> Text,
+        payload -> Text,
+        attempts -> Integer,
+        next_attempt_at -> Timestamp,
+        last_error -> Nullable<Text>,
+        dead_lettered_at -> Nullable<Timestamp>,
+        created_at -> Timestamp,
+    }
+}
+
 diesel::allow_tables_to_appear_in_same_query!(
+    accounts,
+    batch_payouts,
     burnt_proofs,
     client_key_values,
     completed_transactions,
     inbound_transactions,
+    invoices,
     known_one_sided_payment_scripts,
+    labels,
     outbound_transactions,
     outputs,
     scanned_blocks,
+    scheduled_payments,
+    subaddresses,
+    transaction_status_history,
     wallet_settings,
+    webhook_deliveries,
 );
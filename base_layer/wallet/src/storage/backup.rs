@@ -0,0 +1,322 @@
+// Copyright 2024. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Encrypted backups of the full wallet database. Unlike seed recovery, restoring a backup keeps the transaction
+//! history, contacts, labels and the state of pending transactions.
+
+use std::{fs, mem::size_of, path::Path};
+
+use argon2::password_hash::{rand_core::OsRng, SaltString};
+use blake2::Blake2b;
+use chacha20poly1305::{Key, KeyInit, XChaCha20Poly1305};
+use chrono::{NaiveDateTime, Utc};
+use diesel::{prelude::*, sql_types::Text, SqliteConnection};
+use digest::{consts::U32, generic_array::GenericArray};
+use log::*;
+use serde::{Deserialize, Serialize};
+use tari_common::configuration::Network;
+use tari_common_sqlite::sqlite_connection_pool::PooledDbConnection;
+use tari_common_types::encryption::{decrypt_bytes_integral_nonce, encrypt_bytes_integral_nonce};
+use tari_crypto::{hash_domain, hashing::DomainSeparatedHasher};
+use tari_utilities::{
+    hex::{from_hex, Hex},
+    hidden_type,
+    safe_array::SafeArray,
+    Hidden,
+    SafePassword,
+};
+
+use crate::{
+    error::WalletStorageError,
+    schema::scanned_blocks,
+    storage::{
+        sqlite_db::wallet::{Argon2Parameters, WalletSqliteDatabase},
+        sqlite_utilities::run_migration_and_create_sqlite_connection,
+    },
+};
+
+const LOG_TARGET: &str = "wallet::storage::backup";
+
+/// The current version of the backup format
+pub const WALLET_BACKUP_VERSION: u8 = 1;
+
+// Authenticated data prefix for backup encryption; the backup header fields are appended
+const BACKUP_AAD_PREFIX: &str = "wallet_backup_v";
+
+hash_domain!(WalletBackupKeyDomain, "com.tari.base_layer.wallet.backup_key", 0);
+
+// The `XChaCha20-Poly1305` key used to encrypt a backup
+hidden_type!(WalletBackupKey, SafeArray<u8, { size_of::<Key>() }>);
+
+/// An encrypted snapshot of the wallet database. The header fields are authenticated along with the snapshot, so a
+/// backup that was modified in any way fails to decrypt.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WalletBackup {
+    pub version: u8,
+    pub network: String,
+    pub created_at: NaiveDateTime,
+    key_version: u8,
+    salt: String,
+    ciphertext: String,
+}
+
+impl WalletBackup {
+    /// Creates a backup of the wallet database at `db_path`. The wallet must not be running and the passphrase must be
+    /// the wallet passphrase, which is also needed to open the restored wallet.
+    pub fn create<P: AsRef<Path>>(
+        db_path: P,
+        passphrase: &SafePassword,
+        network: Network,
+    ) -> Result<Self, WalletStorageError> {
+        let snapshot = snapshot_database(db_path.as_ref(), passphrase)?;
+
+        let params = Argon2Parameters::from_version(None)?;
+        let mut backup = Self {
+            version: WALLET_BACKUP_VERSION,
+            network: network.to_string(),
+            created_at: Utc::now().naive_utc(),
+            key_version: params.id(),
+            salt: SaltString::generate(&mut OsRng).to_string(),
+            ciphertext: String::new(),
+        };
+        let cipher = backup.cipher(passphrase)?;
+        backup.ciphertext = encrypt_bytes_integral_nonce(&cipher, backup.aad(), Hidden::hide(snapshot))
+            .map_err(WalletStorageError::AeadError)?
+            .to_hex();
+        Ok(backup)
+    }
+
+    /// Restores the backup as a new wallet database at `db_path`, which must not exist yet
+    pub fn restore<P: AsRef<Path>>(
+        &self,
+        db_path: P,
+        passphrase: &SafePassword,
+        network: Network,
+    ) -> Result<(), WalletStorageError> {
+        let db_path = db_path.as_ref();
+        if self.network != network.to_string() {
+            return Err(WalletStorageError::InvalidBackup(format!(
+                "The backup is of a {} wallet, not {}",
+                self.network, network
+            )));
+        }
+        if db_path.exists() {
+            return Err(WalletStorageError::FileError(format!(
+                "A wallet already exists at {}, backups can only be restored into a new data directory",
+                db_path.display()
+            )));
+        }
+
+        let snapshot = self.decrypt(passphrase)?;
+        if let Some(parent) = db_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(db_path, snapshot)?;
+
+        if let Err(e) = verify_restored_database(db_path, passphrase) {
+            warn!(target: LOG_TARGET, "Restored wallet database failed verification: {}", e);
+            fs::remove_file(db_path)?;
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    pub fn write_to_file<P: AsRef<Path>>(&self, path: P) -> Result<(), WalletStorageError> {
+        fs::write(path, serde_json::to_string(self)?)?;
+        Ok(())
+    }
+
+    pub fn read_from_file<P: AsRef<Path>>(path: P) -> Result<Self, WalletStorageError> {
+        let backup: Self = serde_json::from_str(&fs::read_to_string(path)?)
+            .map_err(|e| WalletStorageError::InvalidBackup(e.to_string()))?;
+        if backup.version > WALLET_BACKUP_VERSION {
+            return Err(WalletStorageError::UnsupportedBackupVersion(backup.version));
+        }
+        Ok(backup)
+    }
+
+    fn decrypt(&self, passphrase: &SafePassword) -> Result<Vec<u8>, WalletStorageError> {
+        let cipher = self.cipher(passphrase)?;
+        let ciphertext = from_hex(&self.ciphertext)?;
+        decrypt_bytes_integral_nonce(&cipher, self.aad(), &ciphertext).map_err(|_| {
+            WalletStorageError::InvalidBackup("the passphrase is incorrect or the backup is corrupt".to_string())
+        })
+    }
+
+    fn cipher(&self, passphrase: &SafePassword) -> Result<XChaCha20Poly1305, WalletStorageError> {
+        let mut derivation_key = WalletBackupKey::from(SafeArray::default());
+        Argon2Parameters::from_version(Some(self.key_version))?.hash_password_into(
+            passphrase,
+            self.salt.as_bytes(),
+            derivation_key.reveal_mut(),
+        )?;
+
+        let mut key = WalletBackupKey::from(SafeArray::default());
+        DomainSeparatedHasher::<Blake2b<U32>, WalletBackupKeyDomain>::new()
+            .chain(derivation_key.reveal())
+            .finalize_into(GenericArray::from_mut_slice(key.reveal_mut()));
+
+        Ok(XChaCha20Poly1305::new(Key::from_slice(key.reveal())))
+    }
+
+    fn aad(&self) -> Vec<u8> {
+        format!(
+            "{}{}:{}:{}:{}:{}",
+            BACKUP_AAD_PREFIX,
+            self.version,
+            self.key_version,
+            self.network,
+            self.salt,
+            self.created_at.timestamp()
+        )
+        .into_bytes()
+    }
+}
+
+/// Takes a consistent copy of the wallet database, without the data that is re-derived by scanning the chain
+fn snapshot_database(db_path: &Path, passphrase: &SafePassword) -> Result<Vec<u8>, WalletStorageError> {
+    let connection = run_migration_and_create_sqlite_connection(db_path, 1)?;
+    // Only a wallet that can be opened with the passphrase is backed up
+    WalletSqliteDatabase::new(connection.clone(), passphrase.clone())?;
+
+    let snapshot_dir = tempfile::tempdir_in(db_path.parent().ok_or(WalletStorageError::DatabasePathIsRootPath)?)?;
+    let snapshot_path = snapshot_dir.path().join("snapshot.sqlite3");
+    let snapshot_path_str = snapshot_path.to_str().ok_or(WalletStorageError::InvalidUnicodePath)?;
+    diesel::sql_query("VACUUM INTO ?")
+        .bind::<Text, _>(snapshot_path_str)
+        .execute(&mut connection.get_pooled_connection()?)?;
+
+    let mut snapshot = SqliteConnection::establish(snapshot_path_str)?;
+    diesel::delete(scanned_blocks::table).execute(&mut snapshot)?;
+    diesel::sql_query("VACUUM").execute(&mut snapshot)?;
+    drop(snapshot);
+
+    Ok(fs::read(&snapshot_path)?)
+}
+
+#[derive(QueryableByName)]
+struct IntegrityCheck {
+    #[diesel(sql_type = Text)]
+    integrity_check: String,
+}
+
+fn verify_restored_database(db_path: &Path, passphrase: &SafePassword) -> Result<(), WalletStorageError> {
+    let path_str = db_path.to_str().ok_or(WalletStorageError::InvalidUnicodePath)?;
+    let mut conn = SqliteConnection::establish(path_str)?;
+    let results = diesel::sql_query("PRAGMA integrity_check").load::<IntegrityCheck>(&mut conn)?;
+    if results.iter().any(|result| result.integrity_check != "ok") {
+        return Err(WalletStorageError::InvalidBackup(format!(
+            "integrity check failed: {}",
+            results
+                .into_iter()
+                .map(|result| result.integrity_check)
+                .collect::<Vec<_>>()
+                .join(", ")
+        )));
+    }
+    drop(conn);
+
+    // Backups of older wallets are migrated to the current schema
+    let connection = run_migration_and_create_sqlite_connection(db_path, 1)?;
+    WalletSqliteDatabase::new(connection, passphrase.clone())?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use tari_test_utils::random::string;
+    use tempfile::tempdir;
+
+    use super::*;
+    use crate::{labels::LabelTarget, storage::database::WalletBackend};
+
+    fn create_wallet_db(db_path: &Path, passphrase: &SafePassword) {
+        let connection = run_migration_and_create_sqlite_connection(db_path, 1).unwrap();
+        let db = WalletSqliteDatabase::new(connection, passphrase.clone()).unwrap();
+        let target: LabelTarget = "transaction:7".parse().unwrap();
+        db.set_label(&target, Some("rent".to_string()), None).unwrap();
+    }
+
+    #[test]
+    fn it_restores_a_backup_into_a_new_data_dir() {
+        let tempdir = tempdir().unwrap();
+        let db_path = tempdir.path().join(format!("{}.sqlite3", string(8)));
+        let passphrase = SafePassword::from("an example very very secret key.");
+        create_wallet_db(&db_path, &passphrase);
+
+        let backup_path = tempdir.path().join("wallet.backup");
+        WalletBackup::create(&db_path, &passphrase, Network::LocalNet)
+            .unwrap()
+            .write_to_file(&backup_path)
+            .unwrap();
+        let backup = WalletBackup::read_from_file(&backup_path).unwrap();
+        assert_eq!(backup.version, WALLET_BACKUP_VERSION);
+
+        // Backups are only restored into a new data dir, with the wallet passphrase and on the same network
+        assert!(matches!(
+            backup.restore(&db_path, &passphrase, Network::LocalNet),
+            Err(WalletStorageError::FileError(_))
+        ));
+        let restored_path = tempdir.path().join("restored").join("console_wallet.db");
+        assert!(matches!(
+            backup.restore(&restored_path, &SafePassword::from("wrong"), Network::LocalNet),
+            Err(WalletStorageError::InvalidBackup(_))
+        ));
+        assert!(matches!(
+            backup.restore(&restored_path, &passphrase, Network::Esmeralda),
+            Err(WalletStorageError::InvalidBackup(_))
+        ));
+        assert!(!restored_path.exists());
+
+        backup.restore(&restored_path, &passphrase, Network::LocalNet).unwrap();
+        let connection = run_migration_and_create_sqlite_connection(&restored_path, 1).unwrap();
+        let db = WalletSqliteDatabase::new(connection, passphrase).unwrap();
+        let label = db.fetch_label(&"transaction:7".parse().unwrap()).unwrap().unwrap();
+        assert_eq!(label.label, "rent");
+    }
+
+    #[test]
+    fn it_rejects_tampered_backups() {
+        let tempdir = tempdir().unwrap();
+        let db_path = tempdir.path().join(format!("{}.sqlite3", string(8)));
+        let passphrase = SafePassword::from("an example very very secret key.");
+        create_wallet_db(&db_path, &passphrase);
+        let backup = WalletBackup::create(&db_path, &passphrase, Network::LocalNet).unwrap();
+
+        let restored_path = tempdir.path().join("restored").join("console_wallet.db");
+        let mut tampered = backup.clone();
+        tampered.created_at = backup.created_at + chrono::Duration::seconds(1);
+        assert!(matches!(
+            tampered.restore(&restored_path, &passphrase, Network::LocalNet),
+            Err(WalletStorageError::InvalidBackup(_))
+        ));
+
+        let mut tampered = backup;
+        let last = tampered.ciphertext.pop().unwrap();
+        tampered.ciphertext.push(if last == '0' { '1' } else { '0' });
+        assert!(matches!(
+            tampered.restore(&restored_path, &passphrase, Network::LocalNet),
+            Err(WalletStorageError::InvalidBackup(_))
+        ));
+        assert!(!restored_path.exists());
+    }
+}
@@ -36,7 +36,13 @@ use tari_comms::{
 use tari_key_manager::cipher_seed::CipherSeed;
 use tari_utilities::SafePassword;
 
-use crate::{error::WalletStorageError, utxo_scanner_service::service::ScannedBlock};
+use crate::{
+    accounts::{AccountId, WalletAccount},
+    error::WalletStorageError,
+    labels::{LabelTarget, WalletLabel},
+    utxo_scanner_service::service::ScannedBlock,
+    webhooks::WebhookDelivery,
+};
 
 const LOG_TARGET: &str = "wallet::database";
 
@@ -62,6 +68,8 @@ pub trait WalletBackend: Send + Sync + Clone {
 
     /// Change the passphrase used to encrypt the database
     fn change_passphrase(&self, existing: &SafePassword, new: &SafePassword) -> Result<(), WalletStorageError>;
+    /// Check that the given passphrase is the one used to encrypt the database
+    fn verify_passphrase(&self, passphrase: &SafePassword) -> Result<(), WalletStorageError>;
 
     fn create_burnt_proof(
         &self,
@@ -72,6 +80,35 @@ pub trait WalletBackend: Send + Sync + Clone {
     fn fetch_burnt_proof(&self, id: u32) -> Result<(u32, String, String, NaiveDateTime), WalletStorageError>;
     fn fetch_burnt_proofs(&self) -> Result<Vec<(u32, String, String, NaiveDateTime)>, WalletStorageError>;
     fn delete_burnt_proof(&self, id: u32) -> Result<(), WalletStorageError>;
+
+    /// Create a new account with the given (unique) name
+    fn create_account(&self, name: &str) -> Result<WalletAccount, WalletStorageError>;
+    fn fetch_account(&self, id: AccountId) -> Result<Option<WalletAccount>, WalletStorageError>;
+    fn fetch_account_by_name(&self, name: &str) -> Result<Option<WalletAccount>, WalletStorageError>;
+    fn fetch_accounts(&self) -> Result<Vec<WalletAccount>, WalletStorageError>;
+
+    /// Create or update the label and note of an item, fields that are `None` are left unchanged
+    fn set_label(
+        &self,
+        target: &LabelTarget,
+        label: Option<String>,
+        note: Option<String>,
+    ) -> Result<WalletLabel, WalletStorageError>;
+    fn fetch_label(&self, target: &LabelTarget) -> Result<Option<WalletLabel>, WalletStorageError>;
+    fn fetch_labels(&self) -> Result<Vec<WalletLabel>, WalletStorageError>;
+    fn delete_label(&self, target: &LabelTarget) -> Result<(), WalletStorageError>;
+
+    /// Queue event deliveries for webhook endpoints, all deliveries are stored or none are
+    fn insert_webhook_deliveries(&self, deliveries: Vec<WebhookDelivery>) -> Result<(), WalletStorageError>;
+    /// The oldest undelivered events for an endpoint, in the order they were queued. Dead-lettered deliveries are
+    /// not included.
+    fn fetch_pending_webhook_deliveries(
+        &self,
+        url: &str,
+        limit: usize,
+    ) -> Result<Vec<WebhookDelivery>, WalletStorageError>;
+    fn update_webhook_delivery(&self, delivery: &WebhookDelivery) -> Result<(), WalletStorageError>;
+    fn delete_webhook_delivery(&self, event_id: &str, url: &str) -> Result<(), WalletStorageError>;
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -165,6 +202,10 @@ where T: WalletBackend + 'static
         Ok(())
     }
 
+    pub fn verify_passphrase(&self, passphrase: &SafePassword) -> Result<(), WalletStorageError> {
+        self.db.verify_passphrase(passphrase)
+    }
+
     pub fn get_master_seed(&self) -> Result<Option<CipherSeed>, WalletStorageError> {
         let c = match self.db.fetch(&DbKey::MasterSeed) {
             Ok(None) => Ok(None),
@@ -384,6 +425,67 @@ where T: WalletBackend + 'static
     pub fn delete_burnt_proof(&self, id: u32) -> Result<(), WalletStorageError> {
         self.db.delete_burnt_proof(id)
     }
+
+    pub fn create_account(&self, name: &str) -> Result<WalletAccount, WalletStorageError> {
+        self.db.create_account(name)
+    }
+
+    pub fn get_account(&self, id: AccountId) -> Result<WalletAccount, WalletStorageError> {
+        self.db
+            .fetch_account(id)?
+            .ok_or_else(|| WalletStorageError::AccountNotFound(id.to_string()))
+    }
+
+    pub fn get_account_by_name(&self, name: &str) -> Result<WalletAccount, WalletStorageError> {
+        self.db
+            .fetch_account_by_name(name)?
+            .ok_or_else(|| WalletStorageError::AccountNotFound(name.to_string()))
+    }
+
+    pub fn get_accounts(&self) -> Result<Vec<WalletAccount>, WalletStorageError> {
+        self.db.fetch_accounts()
+    }
+
+    pub fn set_label(
+        &self,
+        target: &LabelTarget,
+        label: Option<String>,
+        note: Option<String>,
+    ) -> Result<WalletLabel, WalletStorageError> {
+        self.db.set_label(target, label, note)
+    }
+
+    pub fn get_label(&self, target: &LabelTarget) -> Result<Option<WalletLabel>, WalletStorageError> {
+        self.db.fetch_label(target)
+    }
+
+    pub fn get_labels(&self) -> Result<Vec<WalletLabel>, WalletStorageError> {
+        self.db.fetch_labels()
+    }
+
+    pub fn remove_label(&self, target: &LabelTarget) -> Result<(), WalletStorageError> {
+        self.db.delete_label(target)
+    }
+
+    pub fn queue_webhook_deliveries(&self, deliveries: Vec<WebhookDelivery>) -> Result<(), WalletStorageError> {
+        self.db.insert_webhook_deliveries(deliveries)
+    }
+
+    pub fn get_pending_webhook_deliveries(
+        &self,
+        url: &str,
+        limit: usize,
+    ) -> Result<Vec<WebhookDelivery>, WalletStorageError> {
+        self.db.fetch_pending_webhook_deliveries(url, limit)
+    }
+
+    pub fn update_webhook_delivery(&self, delivery: &WebhookDelivery) -> Result<(), WalletStorageError> {
+        self.db.update_webhook_delivery(delivery)
+    }
+
+    pub fn remove_webhook_delivery(&self, event_id: &str, url: &str) -> Result<(), WalletStorageError> {
+        self.db.delete_webhook_delivery(event_id, url)
+    }
 }
 
 impl Display for DbValue {
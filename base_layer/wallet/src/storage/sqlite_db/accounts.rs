@@ -0,0 +1,98 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::convert::TryFrom;
+
+use chrono::{NaiveDateTime, Utc};
+use diesel::{prelude::*, result::Error, SqliteConnection};
+
+use crate::{
+    accounts::{AccountId, WalletAccount},
+    error::WalletStorageError,
+    schema::accounts,
+};
+
+#[derive(Clone, Debug, Queryable, PartialEq)]
+#[diesel(table_name = accounts)]
+pub struct AccountSql {
+    id: i64,
+    name: String,
+    created_at: NaiveDateTime,
+}
+
+#[derive(Clone, Debug, Insertable)]
+#[diesel(table_name = accounts)]
+struct NewAccountSql {
+    name: String,
+    created_at: NaiveDateTime,
+}
+
+impl AccountSql {
+    pub fn index(conn: &mut SqliteConnection) -> Result<Vec<AccountSql>, WalletStorageError> {
+        Ok(accounts::table.order(accounts::id.asc()).load::<AccountSql>(conn)?)
+    }
+
+    pub fn find(id: AccountId, conn: &mut SqliteConnection) -> Result<Option<AccountSql>, WalletStorageError> {
+        accounts::table
+            .filter(accounts::id.eq(i64::from(id)))
+            .first::<AccountSql>(conn)
+            .optional()
+            .map_err(Into::into)
+    }
+
+    pub fn find_by_name(name: &str, conn: &mut SqliteConnection) -> Result<Option<AccountSql>, WalletStorageError> {
+        accounts::table
+            .filter(accounts::name.eq(name))
+            .first::<AccountSql>(conn)
+            .optional()
+            .map_err(Into::into)
+    }
+
+    /// Inserts a new account with the given name, returning the stored account. Account ids are allocated by the
+    /// database and are never reused while the account exists.
+    pub fn create(name: &str, conn: &mut SqliteConnection) -> Result<AccountSql, WalletStorageError> {
+        conn.transaction::<_, WalletStorageError, _>(|conn| {
+            if AccountSql::find_by_name(name, conn)?.is_some() {
+                return Err(WalletStorageError::AccountAlreadyExists(name.to_string()));
+            }
+            diesel::insert_into(accounts::table)
+                .values(NewAccountSql {
+                    name: name.to_string(),
+                    created_at: Utc::now().naive_utc(),
+                })
+                .execute(conn)?;
+            AccountSql::find_by_name(name, conn)?.ok_or(WalletStorageError::from(Error::NotFound))
+        })
+    }
+}
+
+impl TryFrom<AccountSql> for WalletAccount {
+    type Error = WalletStorageError;
+
+    fn try_from(account: AccountSql) -> Result<Self, Self::Error> {
+        Ok(Self {
+            id: AccountId::try_from(account.id).map_err(WalletStorageError::ConversionError)?,
+            name: account.name,
+            created_at: account.created_at,
+        })
+    }
+}
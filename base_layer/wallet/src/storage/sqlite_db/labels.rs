@@ -0,0 +1,132 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::convert::TryFrom;
+
+use chrono::{NaiveDateTime, Utc};
+use diesel::{prelude::*, SqliteConnection};
+use tari_common_sqlite::util::diesel_ext::ExpectedRowsExtension;
+
+use crate::{
+    error::WalletStorageError,
+    labels::{LabelTarget, WalletLabel},
+    schema::labels,
+};
+
+#[derive(Clone, Debug, Queryable, Insertable, PartialEq)]
+#[diesel(table_name = labels)]
+pub struct LabelSql {
+    target_type: String,
+    target_id: String,
+    label: String,
+    note: String,
+    updated_at: NaiveDateTime,
+}
+
+#[derive(AsChangeset)]
+#[diesel(table_name = labels)]
+struct UpdateLabelSql {
+    label: Option<String>,
+    note: Option<String>,
+    updated_at: NaiveDateTime,
+}
+
+impl LabelSql {
+    pub fn index(conn: &mut SqliteConnection) -> Result<Vec<LabelSql>, WalletStorageError> {
+        Ok(labels::table
+            .order((labels::target_type.asc(), labels::target_id.asc()))
+            .load::<LabelSql>(conn)?)
+    }
+
+    pub fn find(target: &LabelTarget, conn: &mut SqliteConnection) -> Result<Option<LabelSql>, WalletStorageError> {
+        labels::table
+            .filter(labels::target_type.eq(target.target_type()))
+            .filter(labels::target_id.eq(target.target_id()))
+            .first::<LabelSql>(conn)
+            .optional()
+            .map_err(Into::into)
+    }
+
+    /// Inserts the label of an item or updates the given fields of its existing label, returning the stored label
+    pub fn upsert(
+        target: &LabelTarget,
+        label: Option<String>,
+        note: Option<String>,
+        conn: &mut SqliteConnection,
+    ) -> Result<LabelSql, WalletStorageError> {
+        conn.transaction::<_, WalletStorageError, _>(|conn| {
+            let updated_at = Utc::now().naive_utc();
+            if LabelSql::find(target, conn)?.is_some() {
+                diesel::update(
+                    labels::table
+                        .filter(labels::target_type.eq(target.target_type()))
+                        .filter(labels::target_id.eq(target.target_id())),
+                )
+                .set(UpdateLabelSql {
+                    label,
+                    note,
+                    updated_at,
+                })
+                .execute(conn)
+                .num_rows_affected_or_not_found(1)?;
+            } else {
+                diesel::insert_into(labels::table)
+                    .values(LabelSql {
+                        target_type: target.target_type().to_string(),
+                        target_id: target.target_id(),
+                        label: label.unwrap_or_default(),
+                        note: note.unwrap_or_default(),
+                        updated_at,
+                    })
+                    .execute(conn)?;
+            }
+            LabelSql::find(target, conn)?.ok_or_else(|| WalletStorageError::LabelNotFound(target.to_string()))
+        })
+    }
+
+    pub fn delete(target: &LabelTarget, conn: &mut SqliteConnection) -> Result<(), WalletStorageError> {
+        let num_deleted = diesel::delete(
+            labels::table
+                .filter(labels::target_type.eq(target.target_type()))
+                .filter(labels::target_id.eq(target.target_id())),
+        )
+        .execute(conn)?;
+        if num_deleted == 0 {
+            return Err(WalletStorageError::LabelNotFound(target.to_string()));
+        }
+        Ok(())
+    }
+}
+
+impl TryFrom<LabelSql> for WalletLabel {
+    type Error = WalletStorageError;
+
+    fn try_from(label: LabelSql) -> Result<Self, Self::Error> {
+        Ok(Self {
+            target: LabelTarget::from_parts(&label.target_type, &label.target_id)
+                .map_err(WalletStorageError::ConversionError)?,
+            label: label.label,
+            note: label.note,
+            updated_at: label.updated_at,
+        })
+    }
+}
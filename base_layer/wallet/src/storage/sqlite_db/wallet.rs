@@ -21,7 +21,7 @@
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 use std::{
-    convert::TryFrom,
+    convert::{TryFrom, TryInto},
     mem::size_of,
     str::{from_utf8, FromStr},
     sync::{Arc, RwLock},
@@ -62,14 +62,22 @@ use tokio::time::Instant;
 use zeroize::Zeroize;
 
 use crate::{
+    accounts::{AccountId, WalletAccount},
     error::WalletStorageError,
+    labels::{LabelTarget, WalletLabel},
     schema::{burnt_proofs, client_key_values, wallet_settings},
     storage::{
         database::{DbKey, DbKeyValuePair, DbValue, WalletBackend, WriteOperation},
-        sqlite_db::scanned_blocks::ScannedBlockSql,
+        sqlite_db::{
+            accounts::AccountSql,
+            labels::LabelSql,
+            scanned_blocks::ScannedBlockSql,
+            webhooks::WebhookDeliverySql,
+        },
         sqlite_utilities::wallet_db_connection::WalletDbConnection,
     },
     utxo_scanner_service::service::ScannedBlock,
+    webhooks::WebhookDelivery,
 };
 
 const LOG_TARGET: &str = "wallet::storage::wallet";
@@ -122,6 +130,23 @@ impl Argon2Parameters {
             Some(id) => Err(WalletStorageError::BadEncryptionVersion(id.to_string())),
         }
     }
+
+    /// The version identifier of these parameters
+    pub fn id(&self) -> u8 {
+        self.id
+    }
+
+    /// Derive key material from a passphrase and salt, filling the output
+    pub(crate) fn hash_password_into(
+        self,
+        passphrase: &SafePassword,
+        salt: &[u8],
+        output: &mut [u8],
+    ) -> Result<(), WalletStorageError> {
+        argon2::Argon2::new(self.algorithm, self.version, self.params)
+            .hash_password_into(passphrase.reveal(), salt, output)
+            .map_err(|e| WalletStorageError::AeadError(e.to_string()))
+    }
 }
 
 /// A structure to hold encryption-related database field data, to make atomic operations cleaner
@@ -625,6 +650,25 @@ impl WalletBackend for WalletSqliteDatabase {
         Ok(())
     }
 
+    fn verify_passphrase(&self, passphrase: &SafePassword) -> Result<(), WalletStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+
+        match DatabaseEncryptionFields::read(&mut conn) {
+            Ok(Some(data)) => {
+                let argon2_params = Argon2Parameters::from_version(Some(data.secondary_key_version))?;
+                let (_, secondary_key_hash) =
+                    derive_secondary_key(passphrase, argon2_params, &data.secondary_key_salt)?;
+                if data.secondary_key_hash != secondary_key_hash {
+                    return Err(WalletStorageError::InvalidPassphrase);
+                }
+                Ok(())
+            },
+            _ => Err(WalletStorageError::UnexpectedResult(
+                "Unable to get valid key-related data from database".into(),
+            )),
+        }
+    }
+
     fn create_burnt_proof(
         &self,
         id: u32,
@@ -717,6 +761,103 @@ impl WalletBackend for WalletSqliteDatabase {
         BurntProofSql::delete(id, &mut conn)?;
         Ok(())
     }
+
+    fn create_account(&self, name: &str) -> Result<WalletAccount, WalletStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        AccountSql::create(name, &mut conn)?.try_into()
+    }
+
+    fn fetch_account(&self, id: AccountId) -> Result<Option<WalletAccount>, WalletStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        AccountSql::find(id, &mut conn)?
+            .map(WalletAccount::try_from)
+            .transpose()
+    }
+
+    fn fetch_account_by_name(&self, name: &str) -> Result<Option<WalletAccount>, WalletStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        AccountSql::find_by_name(name, &mut conn)?
+            .map(WalletAccount::try_from)
+            .transpose()
+    }
+
+    fn fetch_accounts(&self) -> Result<Vec<WalletAccount>, WalletStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        AccountSql::index(&mut conn)?
+            .into_iter()
+            .map(WalletAccount::try_from)
+            .collect()
+    }
+
+    fn set_label(
+        &self,
+        target: &LabelTarget,
+        label: Option<String>,
+        note: Option<String>,
+    ) -> Result<WalletLabel, WalletStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        LabelSql::upsert(target, label, note, &mut conn)?.try_into()
+    }
+
+    fn fetch_label(&self, target: &LabelTarget) -> Result<Option<WalletLabel>, WalletStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        LabelSql::find(target, &mut conn)?
+            .map(WalletLabel::try_from)
+            .transpose()
+    }
+
+    fn fetch_labels(&self) -> Result<Vec<WalletLabel>, WalletStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        LabelSql::index(&mut conn)?
+            .into_iter()
+            .map(WalletLabel::try_from)
+            .collect()
+    }
+
+    fn delete_label(&self, target: &LabelTarget) -> Result<(), WalletStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        LabelSql::delete(target, &mut conn)
+    }
+
+    fn insert_webhook_deliveries(&self, deliveries: Vec<WebhookDelivery>) -> Result<(), WalletStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        let cipher = acquire_read_lock!(self.cipher);
+        let entries = deliveries
+            .into_iter()
+            .map(|delivery| WebhookDeliverySql::new(delivery, &cipher))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        conn.transaction::<_, WalletStorageError, _>(|conn| {
+            for entry in &entries {
+                entry.insert(conn)?;
+            }
+            Ok(())
+        })
+    }
+
+    fn fetch_pending_webhook_deliveries(
+        &self,
+        url: &str,
+        limit: usize,
+    ) -> Result<Vec<WebhookDelivery>, WalletStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        let limit = i64::try_from(limit).unwrap_or(i64::MAX);
+        WebhookDeliverySql::pending(url, limit, &mut conn)?
+            .into_iter()
+            .map(|entry| WebhookDelivery::try_from(self.decrypt_value(entry)?))
+            .collect()
+    }
+
+    fn update_webhook_delivery(&self, delivery: &WebhookDelivery) -> Result<(), WalletStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        WebhookDeliverySql::update_attempt(delivery, &mut conn)
+    }
+
+    fn delete_webhook_delivery(&self, event_id: &str, url: &str) -> Result<(), WalletStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        WebhookDeliverySql::delete(event_id, url, &mut conn)?;
+        Ok(())
+    }
 }
 
 /// Derive a secondary database key and associated commitment
@@ -727,13 +868,7 @@ fn derive_secondary_key(
 ) -> Result<(WalletSecondaryEncryptionKey, Vec<u8>), WalletStorageError> {
     // Produce the secondary derivation key from the passphrase and salt
     let mut secondary_derivation_key = WalletSecondaryDerivationKey::from(SafeArray::default());
-    argon2::Argon2::new(params.algorithm, params.version, params.params)
-        .hash_password_into(
-            passphrase.reveal(),
-            salt.as_bytes(),
-            secondary_derivation_key.reveal_mut(),
-        )
-        .map_err(|e| WalletStorageError::AeadError(e.to_string()))?;
+    params.hash_password_into(passphrase, salt.as_bytes(), secondary_derivation_key.reveal_mut())?;
 
     // Derive the secondary key
     let mut secondary_key = WalletSecondaryEncryptionKey::from(SafeArray::default());
@@ -1077,6 +1212,9 @@ impl Encryptable<XChaCha20Poly1305> for BurntProofSql {
 
 #[cfg(test)]
 mod test {
+    use std::time::Duration;
+
+    use diesel::prelude::*;
     use tari_common_sqlite::sqlite_connection_pool::PooledDbConnection;
     use tari_common_types::encryption::{decrypt_bytes_integral_nonce, Encryptable};
     use tari_key_manager::cipher_seed::CipherSeed;
@@ -1088,10 +1226,17 @@ mod test {
     };
     use tempfile::tempdir;
 
-    use crate::storage::{
-        database::{DbKey, DbValue, WalletBackend},
-        sqlite_db::wallet::{ClientKeyValueSql, WalletSettingSql, WalletSqliteDatabase},
-        sqlite_utilities::run_migration_and_create_sqlite_connection,
+    use crate::{
+        accounts::{AccountId, DEFAULT_ACCOUNT_NAME},
+        error::WalletStorageError,
+        labels::LabelTarget,
+        schema::webhook_deliveries,
+        storage::{
+            database::{DbKey, DbValue, WalletBackend},
+            sqlite_db::wallet::{ClientKeyValueSql, WalletSettingSql, WalletSqliteDatabase},
+            sqlite_utilities::run_migration_and_create_sqlite_connection,
+        },
+        webhooks::WebhookDelivery,
     };
     #[test]
     fn test_passphrase() {
@@ -1111,6 +1256,13 @@ mod test {
         // Try to load with the wrong passphrase
         assert!(WalletSqliteDatabase::new(connection.clone(), "evil passphrase".to_string().into()).is_err());
 
+        // Verify the passphrase without loading
+        assert!(db.verify_passphrase(&"passphrase".to_string().into()).is_ok());
+        assert!(matches!(
+            db.verify_passphrase(&"evil passphrase".to_string().into()),
+            Err(WalletStorageError::InvalidPassphrase)
+        ));
+
         // Try to change the passphrase, but fail
         assert!(db
             .change_passphrase(
@@ -1132,6 +1284,8 @@ mod test {
 
         // The existing passphrase no longer works
         assert!(WalletSqliteDatabase::new(connection.clone(), "passphrase".to_string().into()).is_err());
+        assert!(db.verify_passphrase(&"passphrase".to_string().into()).is_err());
+        assert!(db.verify_passphrase(&"new passphrase".to_string().into()).is_ok());
 
         // The new passphrase does
         assert!(WalletSqliteDatabase::new(connection, "new passphrase".to_string().into()).is_ok());
@@ -1327,4 +1481,158 @@ mod test {
 
         assert_eq!(decrypted_db_seed, seed_bytes);
     }
+
+    #[test]
+    fn test_accounts() {
+        let db_name = format!("{}.sqlite3", string(8).as_str());
+        let db_tempdir = tempdir().unwrap();
+        let db_folder = db_tempdir.path().to_str().unwrap().to_string();
+        let connection = run_migration_and_create_sqlite_connection(format!("{}{}", db_folder, db_name), 16).unwrap();
+
+        let passphrase = SafePassword::from("an example very very secret key.".to_string());
+        let db = WalletSqliteDatabase::new(connection, passphrase).unwrap();
+
+        let accounts = db.fetch_accounts().unwrap();
+        assert_eq!(accounts.len(), 1);
+        assert!(accounts[0].is_default());
+        assert_eq!(accounts[0].name, DEFAULT_ACCOUNT_NAME);
+
+        let savings = db.create_account("savings").unwrap();
+        assert_eq!(savings.id, AccountId::from(1));
+        assert!(matches!(
+            db.create_account("savings"),
+            Err(WalletStorageError::AccountAlreadyExists(_))
+        ));
+
+        assert_eq!(db.fetch_account(savings.id).unwrap(), Some(savings.clone()));
+        assert_eq!(db.fetch_account_by_name("savings").unwrap(), Some(savings));
+        assert!(db.fetch_account(AccountId::from(5)).unwrap().is_none());
+        assert_eq!(db.fetch_accounts().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_labels() {
+        let db_name = format!("{}.sqlite3", string(8).as_str());
+        let db_tempdir = tempdir().unwrap();
+        let db_folder = db_tempdir.path().to_str().unwrap().to_string();
+        let connection = run_migration_and_create_sqlite_connection(format!("{}{}", db_folder, db_name), 16).unwrap();
+
+        let passphrase = SafePassword::from("an example very very secret key.".to_string());
+        let db = WalletSqliteDatabase::new(connection, passphrase).unwrap();
+
+        let target: LabelTarget = "transaction:7".parse().unwrap();
+        assert!(db.fetch_label(&target).unwrap().is_none());
+
+        let label = db.set_label(&target, Some("rent".to_string()), None).unwrap();
+        assert_eq!(label.label, "rent");
+        assert_eq!(label.note, "");
+
+        // Only the given fields are updated
+        let label = db
+            .set_label(&target, None, Some("March, paid late".to_string()))
+            .unwrap();
+        assert_eq!(label.label, "rent");
+        assert_eq!(label.note, "March, paid late");
+        assert!(label.matches("LATE"));
+        assert_eq!(db.fetch_label(&target).unwrap(), Some(label));
+
+        let other: LabelTarget = "transaction:8".parse().unwrap();
+        let _label = db.set_label(&other, Some("salary".to_string()), None).unwrap();
+        assert_eq!(db.fetch_labels().unwrap().len(), 2);
+
+        db.delete_label(&target).unwrap();
+        assert!(db.fetch_label(&target).unwrap().is_none());
+        assert!(matches!(
+            db.delete_label(&target),
+            Err(WalletStorageError::LabelNotFound(_))
+        ));
+        assert_eq!(db.fetch_labels().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_webhook_deliveries() {
+        let db_name = format!("{}.sqlite3", string(8).as_str());
+        let db_tempdir = tempdir().unwrap();
+        let db_folder = db_tempdir.path().to_str().unwrap().to_string();
+        let connection = run_migration_and_create_sqlite_connection(format!("{}{}", db_folder, db_name), 16).unwrap();
+
+        let passphrase = SafePassword::from("an example very very secret key.".to_string());
+        let db = WalletSqliteDatabase::new(connection.clone(), passphrase).unwrap();
+
+        let url = "https://example.com/hook";
+        let now = chrono::Utc::now().naive_utc();
+        // Deliveries are returned in the order they were queued, not by timestamp or event id
+        let deliveries = (0..3)
+            .map(|i| {
+                WebhookDelivery::new(
+                    format!("event-{}", 2 - i),
+                    url.to_string(),
+                    "received".to_string(),
+                    format!("{{\"tx_id\":{}}}", i),
+                    now,
+                )
+            })
+            .collect::<Vec<_>>();
+        db.insert_webhook_deliveries(deliveries.clone()).unwrap();
+        let other = WebhookDelivery::new(
+            "event-0".to_string(),
+            "https://example.org/hook".to_string(),
+            "received".to_string(),
+            "{}".to_string(),
+            now,
+        );
+        db.insert_webhook_deliveries(vec![other]).unwrap();
+
+        // Payloads are encrypted at rest
+        let mut conn = connection.get_pooled_connection().unwrap();
+        let stored = webhook_deliveries::table
+            .filter(webhook_deliveries::url.eq(url))
+            .select(webhook_deliveries::payload)
+            .load::<String>(&mut conn)
+            .unwrap();
+        assert_eq!(stored.len(), 3);
+        assert!(stored.iter().all(|payload| !payload.contains("tx_id")));
+
+        assert_eq!(db.fetch_pending_webhook_deliveries(url, 10).unwrap(), deliveries);
+        assert_eq!(db.fetch_pending_webhook_deliveries(url, 2).unwrap(), deliveries[..2]);
+
+        let mut delivery = deliveries[0].clone();
+        assert!(delivery.record_failure(
+            "connection refused".to_string(),
+            2,
+            Duration::from_secs(10),
+            Duration::from_secs(60),
+            now,
+        ));
+        db.update_webhook_delivery(&delivery).unwrap();
+        assert_eq!(db.fetch_pending_webhook_deliveries(url, 1).unwrap(), vec![
+            delivery.clone()
+        ]);
+
+        // Dead-lettered deliveries are kept but no longer hold up the endpoint
+        assert!(!delivery.record_failure(
+            "connection refused".to_string(),
+            2,
+            Duration::from_secs(10),
+            Duration::from_secs(60),
+            now,
+        ));
+        db.update_webhook_delivery(&delivery).unwrap();
+        assert_eq!(db.fetch_pending_webhook_deliveries(url, 10).unwrap(), deliveries[1..]);
+        let dead_lettered = webhook_deliveries::table
+            .filter(webhook_deliveries::dead_lettered_at.is_not_null())
+            .select(webhook_deliveries::event_id)
+            .load::<String>(&mut conn)
+            .unwrap();
+        assert_eq!(dead_lettered, vec!["event-2".to_string()]);
+
+        db.delete_webhook_delivery("event-1", url).unwrap();
+        assert_eq!(db.fetch_pending_webhook_deliveries(url, 10).unwrap(), deliveries[2..]);
+        assert_eq!(
+            db.fetch_pending_webhook_deliveries("https://example.org/hook", 10)
+                .unwrap()
+                .len(),
+            1
+        );
+    }
 }
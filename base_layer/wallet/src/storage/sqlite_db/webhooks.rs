@@ -0,0 +1,211 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{convert::TryFrom, str::from_utf8};
+
+use chacha20poly1305::XChaCha20Poly1305;
+use chrono::NaiveDateTime;
+use diesel::{prelude::*, SqliteConnection};
+use tari_common_sqlite::util::diesel_ext::ExpectedRowsExtension;
+use tari_common_types::encryption::{decrypt_bytes_integral_nonce, encrypt_bytes_integral_nonce, Encryptable};
+use tari_utilities::{
+    hex::{from_hex, Hex},
+    Hidden,
+};
+use zeroize::Zeroize;
+
+use crate::{error::WalletStorageError, schema::webhook_deliveries, webhooks::WebhookDelivery};
+
+#[derive(Clone, Debug, Queryable, PartialEq)]
+#[diesel(table_name = webhook_deliveries)]
+pub struct WebhookDeliverySql {
+    id: i64,
+    event_id: String,
+    url: String,
+    event: String,
+    payload: String,
+    attempts: i32,
+    next_attempt_at: NaiveDateTime,
+    last_error: Option<String>,
+    dead_lettered_at: Option<NaiveDateTime>,
+    created_at: NaiveDateTime,
+}
+
+#[derive(Clone, Debug, Insertable)]
+#[diesel(table_name = webhook_deliveries)]
+struct NewWebhookDeliverySql<'a> {
+    event_id: &'a str,
+    url: &'a str,
+    event: &'a str,
+    payload: &'a str,
+    attempts: i32,
+    next_attempt_at: NaiveDateTime,
+    last_error: Option<&'a str>,
+    dead_lettered_at: Option<NaiveDateTime>,
+    created_at: NaiveDateTime,
+}
+
+#[derive(AsChangeset)]
+#[diesel(table_name = webhook_deliveries)]
+#[diesel(treat_none_as_null = true)]
+struct UpdateWebhookDeliverySql {
+    attempts: i32,
+    next_attempt_at: NaiveDateTime,
+    last_error: Option<String>,
+    dead_lettered_at: Option<NaiveDateTime>,
+}
+
+impl WebhookDeliverySql {
+    /// Builds the row for a delivery, encrypting the payload. The id is allocated by the database on insert.
+    pub fn new(delivery: WebhookDelivery, cipher: &XChaCha20Poly1305) -> Result<Self, WalletStorageError> {
+        let entry = Self {
+            id: 0,
+            event_id: delivery.event_id,
+            url: delivery.url,
+            event: delivery.event,
+            payload: delivery.payload,
+            attempts: i32::try_from(delivery.attempts).unwrap_or(i32::MAX),
+            next_attempt_at: delivery.next_attempt_at,
+            last_error: delivery.last_error,
+            dead_lettered_at: delivery.dead_lettered_at,
+            created_at: delivery.created_at,
+        };
+        entry.encrypt(cipher).map_err(WalletStorageError::AeadError)
+    }
+
+    pub fn insert(&self, conn: &mut SqliteConnection) -> Result<(), WalletStorageError> {
+        diesel::insert_into(webhook_deliveries::table)
+            .values(NewWebhookDeliverySql {
+                event_id: &self.event_id,
+                url: &self.url,
+                event: &self.event,
+                payload: &self.payload,
+                attempts: self.attempts,
+                next_attempt_at: self.next_attempt_at,
+                last_error: self.last_error.as_deref(),
+                dead_lettered_at: self.dead_lettered_at,
+                created_at: self.created_at,
+            })
+            .execute(conn)?;
+        Ok(())
+    }
+
+    /// The oldest undelivered events for an endpoint that are not dead-lettered, in the order they were queued
+    pub fn pending(url: &str, limit: i64, conn: &mut SqliteConnection) -> Result<Vec<Self>, WalletStorageError> {
+        Ok(webhook_deliveries::table
+            .filter(webhook_deliveries::url.eq(url))
+            .filter(webhook_deliveries::dead_lettered_at.is_null())
+            .order(webhook_deliveries::id.asc())
+            .limit(limit)
+            .load::<WebhookDeliverySql>(conn)?)
+    }
+
+    /// Persists the retry state of a delivery
+    pub fn update_attempt(delivery: &WebhookDelivery, conn: &mut SqliteConnection) -> Result<(), WalletStorageError> {
+        diesel::update(
+            webhook_deliveries::table
+                .filter(webhook_deliveries::event_id.eq(&delivery.event_id))
+                .filter(webhook_deliveries::url.eq(&delivery.url)),
+        )
+        .set(UpdateWebhookDeliverySql {
+            attempts: i32::try_from(delivery.attempts).unwrap_or(i32::MAX),
+            next_attempt_at: delivery.next_attempt_at,
+            last_error: delivery.last_error.clone(),
+            dead_lettered_at: delivery.dead_lettered_at,
+        })
+        .execute(conn)
+        .num_rows_affected_or_not_found(1)?;
+        Ok(())
+    }
+
+    pub fn delete(event_id: &str, url: &str, conn: &mut SqliteConnection) -> Result<bool, WalletStorageError> {
+        let num_deleted = diesel::delete(
+            webhook_deliveries::table
+                .filter(webhook_deliveries::event_id.eq(event_id))
+                .filter(webhook_deliveries::url.eq(url)),
+        )
+        .execute(conn)?;
+        Ok(num_deleted > 0)
+    }
+}
+
+impl TryFrom<WebhookDeliverySql> for WebhookDelivery {
+    type Error = WalletStorageError;
+
+    fn try_from(delivery: WebhookDeliverySql) -> Result<Self, Self::Error> {
+        Ok(Self {
+            event_id: delivery.event_id,
+            url: delivery.url,
+            event: delivery.event,
+            payload: delivery.payload,
+            attempts: u32::try_from(delivery.attempts)
+                .map_err(|_| WalletStorageError::ConversionError("Invalid webhook delivery attempts".to_string()))?,
+            next_attempt_at: delivery.next_attempt_at,
+            last_error: delivery.last_error,
+            dead_lettered_at: delivery.dead_lettered_at,
+            created_at: delivery.created_at,
+        })
+    }
+}
+
+impl Encryptable<XChaCha20Poly1305> for WebhookDeliverySql {
+    fn domain(&self, field_name: &'static str) -> Vec<u8> {
+        [
+            Self::WEBHOOK_DELIVERY,
+            self.event_id.as_bytes(),
+            self.url.as_bytes(),
+            field_name.as_bytes(),
+        ]
+        .concat()
+        .to_vec()
+    }
+
+    #[allow(unused_assignments)]
+    fn encrypt(mut self, cipher: &XChaCha20Poly1305) -> Result<Self, String> {
+        self.payload = encrypt_bytes_integral_nonce(
+            cipher,
+            self.domain("payload"),
+            Hidden::hide(self.payload.as_bytes().to_vec()),
+        )?
+        .to_hex();
+
+        Ok(self)
+    }
+
+    #[allow(unused_assignments)]
+    fn decrypt(mut self, cipher: &XChaCha20Poly1305) -> Result<Self, String> {
+        let mut decrypted_value = decrypt_bytes_integral_nonce(
+            cipher,
+            self.domain("payload"),
+            &from_hex(self.payload.as_str()).map_err(|e| e.to_string())?,
+        )?;
+
+        self.payload = from_utf8(decrypted_value.as_slice())
+            .map_err(|e| e.to_string())?
+            .to_string();
+
+        // we zeroize the decrypted value
+        decrypted_value.zeroize();
+
+        Ok(self)
+    }
+}
@@ -0,0 +1,136 @@
+// Copyright 2024. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use blake2::Blake2b;
+use digest::consts::U32;
+use serde::{Deserialize, Serialize};
+use tari_common_types::tari_address::TariAddress;
+use tari_core::transactions::{tari_amount::MicroMinotari, weight::TransactionWeight};
+use tari_crypto::{hash_domain, hashing::DomainSeparatedHasher};
+use tari_utilities::hex::Hex;
+
+hash_domain!(BatchPayoutDomain, "com.tari.base_layer.wallet.batch_payout", 0);
+
+/// The share (in percent) of the maximum transaction weight that is kept free for the inputs of a batch transaction.
+/// The inputs are only known once coins have been selected, so the outputs may not use the full weight.
+const INPUT_WEIGHT_RESERVE_PERCENT: u64 = 10;
+
+/// A single payment requested in a batch payout
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BatchPayoutRecipient {
+    pub address: TariAddress,
+    pub amount: MicroMinotari,
+    pub memo: String,
+}
+
+impl BatchPayoutRecipient {
+    pub fn new(address: TariAddress, amount: MicroMinotari, memo: String) -> Self {
+        Self { address, amount, memo }
+    }
+}
+
+/// Derive a batch id from the recipients so that submitting the same payout file again resumes the existing batch
+/// instead of paying everyone a second time.
+pub fn batch_id_from_recipients(recipients: &[BatchPayoutRecipient]) -> String {
+    recipients
+        .iter()
+        .fold(
+            DomainSeparatedHasher::<Blake2b<U32>, BatchPayoutDomain>::new_with_label("batch_id"),
+            |hasher, recipient| {
+                hasher
+                    .chain(recipient.address.to_bytes())
+                    .chain(recipient.amount.as_u64().to_le_bytes())
+                    .chain((recipient.memo.len() as u64).to_le_bytes())
+                    .chain(recipient.memo.as_bytes())
+            },
+        )
+        .finalize()
+        .as_ref()
+        .to_hex()
+}
+
+/// The largest number of recipient outputs of the given (rounded up) features and scripts size that fit in a single
+/// transaction together with a kernel, a change output and the input reserve.
+pub fn max_outputs_per_transaction(
+    weighting: &TransactionWeight,
+    max_transaction_weight: u64,
+    rounded_up_features_and_scripts_byte_size: usize,
+) -> usize {
+    let available = max_transaction_weight - max_transaction_weight * INPUT_WEIGHT_RESERVE_PERCENT / 100;
+    let output_weight = weighting
+        .calculate(0, 0, 1, rounded_up_features_and_scripts_byte_size)
+        .max(1);
+    let fixed_weight = weighting.calculate(1, 0, 0, 0) + output_weight;
+    let max_outputs = available.saturating_sub(fixed_weight) / output_weight;
+    usize::try_from(max_outputs).unwrap_or(usize::MAX).max(1)
+}
+
+#[cfg(test)]
+mod test {
+    use rand::rngs::OsRng;
+    use tari_common::configuration::Network;
+    use tari_common_types::types::PublicKey;
+    use tari_crypto::keys::PublicKey as PublicKeyTrait;
+
+    use super::*;
+
+    fn recipient(amount: u64, memo: &str) -> BatchPayoutRecipient {
+        let (_, public_key) = PublicKey::random_keypair(&mut OsRng);
+        BatchPayoutRecipient::new(
+            TariAddress::new(public_key, Network::Esmeralda),
+            MicroMinotari::from(amount),
+            memo.to_string(),
+        )
+    }
+
+    #[test]
+    fn it_derives_a_stable_batch_id() {
+        let recipients = vec![recipient(100, "a"), recipient(200, "b")];
+        let batch_id = batch_id_from_recipients(&recipients);
+        assert_eq!(batch_id.len(), 64);
+        assert_eq!(batch_id_from_recipients(&recipients.clone()), batch_id);
+
+        let mut changed = recipients.clone();
+        changed[1].amount = MicroMinotari::from(201);
+        assert_ne!(batch_id_from_recipients(&changed), batch_id);
+
+        let mut reordered = recipients;
+        reordered.reverse();
+        assert_ne!(batch_id_from_recipients(&reordered), batch_id);
+    }
+
+    #[test]
+    fn it_limits_the_outputs_to_the_transaction_weight() {
+        let weighting = TransactionWeight::latest();
+        let features_and_scripts_size = weighting.round_up_features_and_scripts_size(40);
+        let max_outputs = max_outputs_per_transaction(&weighting, 10_000, features_and_scripts_size);
+        let weight = weighting.calculate(1, 0, max_outputs + 1, features_and_scripts_size * (max_outputs + 1));
+        assert!(weight <= 9_000);
+        let weight = weighting.calculate(1, 0, max_outputs + 2, features_and_scripts_size * (max_outputs + 2));
+        assert!(weight > 9_000);
+
+        assert_eq!(
+            max_outputs_per_transaction(&weighting, 10, features_and_scripts_size),
+            1
+        );
+    }
+}
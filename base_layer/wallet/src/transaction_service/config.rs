@@ -69,6 +69,14 @@ pub struct TransactionServiceConfig {
     /// This is the timeout period that will be used to re-submit transactions not found in the mempool
     #[serde(with = "serializers::seconds")]
     pub transaction_mempool_resubmission_window: Duration,
+    /// This is how often the scheduled payments are checked for payments that are due
+    #[serde(with = "serializers::seconds")]
+    pub scheduled_payment_check_interval: Duration,
+    /// The number of times a failed scheduled payment is retried before the occurrence is skipped
+    pub scheduled_payment_max_retries: u32,
+    /// The delay before the first retry of a failed scheduled payment, doubling with every further retry
+    #[serde(with = "serializers::seconds")]
+    pub scheduled_payment_retry_delay: Duration,
 }
 
 impl Default for TransactionServiceConfig {
@@ -87,6 +95,9 @@ impl Default for TransactionServiceConfig {
             transaction_routing_mechanism: TransactionRoutingMechanism::default(),
             transaction_event_channel_size: 1000,
             transaction_mempool_resubmission_window: Duration::from_secs(600),
+            scheduled_payment_check_interval: Duration::from_secs(60),
+            scheduled_payment_max_retries: 5,
+            scheduled_payment_retry_delay: Duration::from_secs(300),
         }
     }
 }
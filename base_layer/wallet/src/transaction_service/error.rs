@@ -47,6 +47,7 @@ use crate::{
     error::WalletStorageError,
     output_manager_service::error::OutputManagerError,
     transaction_service::{
+        scheduled_payment::PaymentCadenceError,
         storage::{database::DbKey, sqlite_db::CompletedTransactionConversionError},
         utc::NegativeDurationError,
     },
@@ -185,6 +186,29 @@ pub enum TransactionServiceError {
     InvalidKeyId(String),
     #[error("Invalid key manager data: `{0}`")]
     KeyManagerServiceError(#[from] KeyManagerServiceError),
+    #[error("Invalid invoice: `{0}`")]
+    InvalidInvoice(String),
+    #[error("Invalid batch payout: {0}")]
+    InvalidBatchPayout(String),
+    #[error("Invalid scheduled payment: {0}")]
+    InvalidScheduledPayment(String),
+}
+
+impl TransactionServiceError {
+    /// Returns true if the error is caused by the wallet's connection to the network or its funds being unavailable,
+    /// so that the same request may succeed later. A wallet without enough funds may be topped up before the next
+    /// attempt, callers that retry should limit the number of attempts.
+    pub fn is_transient(&self) -> bool {
+        matches!(
+            self,
+            TransactionServiceError::ConnectivityError { .. } |
+                TransactionServiceError::BaseNodeNotSynced |
+                TransactionServiceError::OutputManagerError(OutputManagerError::NotEnoughFunds) |
+                TransactionServiceError::OutputManagerError(OutputManagerError::FundsPending) |
+                TransactionServiceError::OutputManagerError(OutputManagerError::BaseNodeNotSynced) |
+                TransactionServiceError::OutputManagerError(OutputManagerError::ConnectivityError { .. })
+        )
+    }
 }
 
 impl From<RangeProofError> for TransactionServiceError {
@@ -263,6 +287,8 @@ pub enum TransactionStorageError {
     AeadError(String),
     #[error("Transaction (TxId: '{0}') is not mined")]
     TransactionNotMined(TxId),
+    #[error("Invalid account id: `{0}`")]
+    InvalidAccountId(String),
     #[error("Conversion error: `{0}`")]
     ByteArrayError(String),
     #[error("Tari address error: `{0}`")]
@@ -271,6 +297,18 @@ pub enum TransactionStorageError {
     SqliteStorageError(#[from] SqliteStorageError),
     #[error("Coinbase transactions are not supported in the wallet")]
     CoinbaseNotSupported,
+    #[error("An invoice with payment id `{0}` already exists")]
+    InvoiceAlreadyExists(String),
+    #[error("Invoice with payment id `{0}` not found")]
+    InvoiceNotFound(String),
+    #[error("A batch payout with id `{0}` already exists")]
+    BatchPayoutAlreadyExists(String),
+    #[error("A scheduled payment with id `{0}` already exists")]
+    ScheduledPaymentAlreadyExists(String),
+    #[error("Scheduled payment with id `{0}` not found")]
+    ScheduledPaymentNotFound(String),
+    #[error("Payment cadence error: `{0}`")]
+    PaymentCadenceError(#[from] PaymentCadenceError),
 }
 
 impl From<ByteArrayError> for TransactionStorageError {
@@ -31,6 +31,7 @@ use chrono::NaiveDateTime;
 use tari_common_types::{
     burnt_proof::BurntProof,
     tari_address::TariAddress,
+    tari_subaddress::TariSubaddress,
     transaction::{ImportStatus, TxId},
     types::{PublicKey, Signature},
 };
@@ -56,13 +57,20 @@ use tokio::sync::broadcast;
 use tower::Service;
 
 use crate::{
+    accounts::AccountId,
     output_manager_service::UtxoSelectionCriteria,
     transaction_service::{
+        batch_payout::BatchPayoutRecipient,
         error::TransactionServiceError,
         storage::models::{
+            BatchPayoutRow,
             CompletedTransaction,
             InboundTransaction,
+            Invoice,
             OutboundTransaction,
+            ScheduledPayment,
+            ScheduledPaymentStatus,
+            TransactionStatusChange,
             TxCancellationReason,
             WalletTransaction,
         },
@@ -77,6 +85,12 @@ pub enum TransactionServiceRequest {
     GetPendingInboundTransactions,
     GetPendingOutboundTransactions,
     GetCompletedTransactions,
+    GetAccountCompletedTransactions(AccountId),
+    GetCompletedTransactionsPage {
+        account: Option<AccountId>,
+        offset: u64,
+        limit: u64,
+    },
     GetCancelledPendingInboundTransactions,
     GetCancelledPendingOutboundTransactions,
     GetCancelledCompletedTransactions,
@@ -133,6 +147,14 @@ pub enum TransactionServiceRequest {
         fee_per_gram: MicroMinotari,
         message: String,
     },
+    SendOneSidedToStealthSubaddressTransaction {
+        destination: TariSubaddress,
+        amount: MicroMinotari,
+        selection_criteria: UtxoSelectionCriteria,
+        output_features: Box<OutputFeatures>,
+        fee_per_gram: MicroMinotari,
+        message: String,
+    },
     SendShaAtomicSwapTransaction(TariAddress, MicroMinotari, UtxoSelectionCriteria, MicroMinotari, String),
     CancelTransaction(TxId),
     ImportUtxoWithStatus {
@@ -158,6 +180,27 @@ pub enum TransactionServiceRequest {
     GetFeePerGramStatsPerBlock {
         count: usize,
     },
+    CreateInvoice {
+        payment_id: String,
+        amount: MicroMinotari,
+        message: String,
+        expires_at: Option<NaiveDateTime>,
+    },
+    GetInvoice(String),
+    GetInvoices,
+    CancelInvoice(String),
+    GetTransactionStatusHistory(TxId),
+    SendBatchPayout {
+        batch_id: Option<String>,
+        recipients: Vec<BatchPayoutRecipient>,
+        selection_criteria: UtxoSelectionCriteria,
+        fee_per_gram: MicroMinotari,
+    },
+    GetBatchPayout(String),
+    CreateScheduledPayment(Box<ScheduledPayment>),
+    GetScheduledPayment(String),
+    GetScheduledPayments,
+    SetScheduledPaymentStatus(String, ScheduledPaymentStatus),
 }
 
 impl fmt::Display for TransactionServiceRequest {
@@ -166,6 +209,14 @@ impl fmt::Display for TransactionServiceRequest {
             Self::GetPendingInboundTransactions => write!(f, "GetPendingInboundTransactions"),
             Self::GetPendingOutboundTransactions => write!(f, "GetPendingOutboundTransactions"),
             Self::GetCompletedTransactions => write!(f, "GetCompletedTransactions"),
+            Self::GetAccountCompletedTransactions(account) => {
+                write!(f, "GetAccountCompletedTransactions({})", account)
+            },
+            Self::GetCompletedTransactionsPage { account, offset, limit } => write!(
+                f,
+                "GetCompletedTransactionsPage(account: {:?}, offset: {}, limit: {})",
+                account, offset, limit
+            ),
             Self::ImportTransaction(tx) => write!(f, "ImportTransaction: {:?}", tx),
             Self::GetCancelledPendingInboundTransactions => write!(f, "GetCancelledPendingInboundTransactions"),
             Self::GetCancelledPendingOutboundTransactions => write!(f, "GetCancelledPendingOutboundTransactions"),
@@ -203,6 +254,16 @@ impl fmt::Display for TransactionServiceRequest {
                 "SendOneSidedToStealthAddressTransaction (to {}, {}, {})",
                 destination, amount, message
             ),
+            Self::SendOneSidedToStealthSubaddressTransaction {
+                destination,
+                amount,
+                message,
+                ..
+            } => write!(
+                f,
+                "SendOneSidedToStealthSubaddressTransaction (to {}, {}, {})",
+                destination, amount, message
+            ),
             Self::SendShaAtomicSwapTransaction(k, _, v, _, msg) => {
                 write!(f, "SendShaAtomicSwapTransaction (to {}, {}, {})", k, v, msg)
             },
@@ -237,6 +298,39 @@ impl fmt::Display for TransactionServiceRequest {
             TransactionServiceRequest::RegisterCodeTemplate { template_name, .. } => {
                 write!(f, "RegisterCodeTemplate: {}", template_name)
             },
+            Self::CreateInvoice {
+                payment_id,
+                amount,
+                expires_at,
+                ..
+            } => write!(
+                f,
+                "CreateInvoice ({}, {}, expires at {:?})",
+                payment_id, amount, expires_at
+            ),
+            Self::GetInvoice(payment_id) => write!(f, "GetInvoice({})", payment_id),
+            Self::GetInvoices => write!(f, "GetInvoices"),
+            Self::CancelInvoice(payment_id) => write!(f, "CancelInvoice({})", payment_id),
+            Self::GetTransactionStatusHistory(tx_id) => write!(f, "GetTransactionStatusHistory({})", tx_id),
+            Self::SendBatchPayout {
+                batch_id, recipients, ..
+            } => write!(
+                f,
+                "SendBatchPayout ({}, {} recipients)",
+                batch_id.as_deref().unwrap_or("new"),
+                recipients.len()
+            ),
+            Self::GetBatchPayout(batch_id) => write!(f, "GetBatchPayout({})", batch_id),
+            Self::CreateScheduledPayment(payment) => write!(
+                f,
+                "CreateScheduledPayment ({}, {} {})",
+                payment.schedule_id, payment.amount, payment.cadence
+            ),
+            Self::GetScheduledPayment(schedule_id) => write!(f, "GetScheduledPayment({})", schedule_id),
+            Self::GetScheduledPayments => write!(f, "GetScheduledPayments"),
+            Self::SetScheduledPaymentStatus(schedule_id, status) => {
+                write!(f, "SetScheduledPaymentStatus({}, {})", schedule_id, status)
+            },
         }
     }
 }
@@ -258,6 +352,7 @@ pub enum TransactionServiceResponse {
     PendingInboundTransactions(HashMap<TxId, InboundTransaction>),
     PendingOutboundTransactions(HashMap<TxId, OutboundTransaction>),
     CompletedTransactions(HashMap<TxId, CompletedTransaction>),
+    CompletedTransactionsPage(Vec<CompletedTransaction>, u64),
     CompletedTransaction(Box<CompletedTransaction>),
     BaseNodePublicKeySet,
     UtxoImported(TxId),
@@ -272,6 +367,13 @@ pub enum TransactionServiceResponse {
     CompletedTransactionValidityChanged,
     ShaAtomicSwapTransactionSent(Box<(TxId, PublicKey, TransactionOutput)>),
     FeePerGramStatsPerBlock(FeePerGramStatsResponse),
+    Invoice(Box<Invoice>),
+    Invoices(Vec<Invoice>),
+    InvoiceCancelled,
+    TransactionStatusHistory(Vec<TransactionStatusChange>),
+    BatchPayout(Vec<BatchPayoutRow>),
+    ScheduledPayment(Box<ScheduledPayment>),
+    ScheduledPayments(Vec<ScheduledPayment>),
 }
 
 #[derive(Clone, Debug, Hash, PartialEq, Eq, Default)]
@@ -327,6 +429,29 @@ pub enum TransactionEvent {
     TransactionValidationStateChanged(OperationId),
     TransactionValidationCompleted(OperationId),
     TransactionValidationFailed(OperationId, u64),
+    /// Payments to the invoice add up to at least the requested amount
+    InvoicePaid {
+        payment_id: String,
+        tx_id: TxId,
+    },
+    /// A payment was received for the invoice but the total received is less than the requested amount
+    InvoiceUnderpaid {
+        payment_id: String,
+        tx_id: TxId,
+    },
+    InvoiceExpired(String),
+    ScheduledPaymentSent {
+        schedule_id: String,
+        tx_id: TxId,
+    },
+    /// A scheduled payment could not be sent. If it will not be retried the occurrence is skipped.
+    ScheduledPaymentFailed {
+        schedule_id: String,
+        error: String,
+        will_retry: bool,
+    },
+    /// The end date or the maximum number of payments of the schedule was reached
+    ScheduledPaymentCompleted(String),
     Error(String),
 }
 
@@ -406,6 +531,31 @@ impl fmt::Display for TransactionEvent {
             TransactionEvent::TransactionValidationFailed(operation_id, reason) => {
                 write!(f, "Transaction validation(#{operation_id}) failed: {reason}")
             },
+            TransactionEvent::InvoicePaid { payment_id, tx_id } => {
+                write!(f, "Invoice {payment_id} paid by {tx_id}")
+            },
+            TransactionEvent::InvoiceUnderpaid { payment_id, tx_id } => {
+                write!(f, "Invoice {payment_id} underpaid by {tx_id}")
+            },
+            TransactionEvent::InvoiceExpired(payment_id) => {
+                write!(f, "Invoice {payment_id} expired")
+            },
+            TransactionEvent::ScheduledPaymentSent { schedule_id, tx_id } => {
+                write!(f, "Scheduled payment {schedule_id} sent in {tx_id}")
+            },
+            TransactionEvent::ScheduledPaymentFailed {
+                schedule_id,
+                error,
+                will_retry,
+            } => {
+                write!(
+                    f,
+                    "Scheduled payment {schedule_id} failed (will retry: {will_retry}): {error}"
+                )
+            },
+            TransactionEvent::ScheduledPaymentCompleted(schedule_id) => {
+                write!(f, "Scheduled payment {schedule_id} completed")
+            },
             TransactionEvent::NewBlockMined(tx_id) => {
                 write!(f, "New block mined {tx_id}")
             },
@@ -613,6 +763,32 @@ impl TransactionServiceHandle {
         }
     }
 
+    pub async fn send_one_sided_to_stealth_subaddress_transaction(
+        &mut self,
+        destination: TariSubaddress,
+        amount: MicroMinotari,
+        selection_criteria: UtxoSelectionCriteria,
+        output_features: OutputFeatures,
+        fee_per_gram: MicroMinotari,
+        message: String,
+    ) -> Result<TxId, TransactionServiceError> {
+        match self
+            .handle
+            .call(TransactionServiceRequest::SendOneSidedToStealthSubaddressTransaction {
+                destination,
+                amount,
+                selection_criteria,
+                output_features: Box::new(output_features),
+                fee_per_gram,
+                message,
+            })
+            .await??
+        {
+            TransactionServiceResponse::TransactionSent(tx_id) => Ok(tx_id),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
     pub async fn cancel_transaction(&mut self, tx_id: TxId) -> Result<(), TransactionServiceError> {
         match self
             .handle
@@ -689,6 +865,226 @@ impl TransactionServiceHandle {
         }
     }
 
+    /// Returns the completed transactions that were sent from, or received into, the given account
+    pub async fn get_account_completed_transactions(
+        &mut self,
+        account: AccountId,
+    ) -> Result<HashMap<TxId, CompletedTransaction>, TransactionServiceError> {
+        match self
+            .handle
+            .call(TransactionServiceRequest::GetAccountCompletedTransactions(account))
+            .await??
+        {
+            TransactionServiceResponse::CompletedTransactions(c) => Ok(c),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Returns a page of the non-cancelled completed transactions, newest first, optionally only those of the given
+    /// account, along with the number of transactions in all the pages
+    pub async fn get_completed_transactions_page(
+        &mut self,
+        account: Option<AccountId>,
+        offset: u64,
+        limit: u64,
+    ) -> Result<(Vec<CompletedTransaction>, u64), TransactionServiceError> {
+        match self
+            .handle
+            .call(TransactionServiceRequest::GetCompletedTransactionsPage { account, offset, limit })
+            .await??
+        {
+            TransactionServiceResponse::CompletedTransactionsPage(page, total) => Ok((page, total)),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Creates an invoice for the given amount with its own payment address. Payments to the address are matched to
+    /// the invoice as they are detected.
+    pub async fn create_invoice(
+        &mut self,
+        payment_id: String,
+        amount: MicroMinotari,
+        message: String,
+        expires_at: Option<NaiveDateTime>,
+    ) -> Result<Invoice, TransactionServiceError> {
+        match self
+            .handle
+            .call(TransactionServiceRequest::CreateInvoice {
+                payment_id,
+                amount,
+                message,
+                expires_at,
+            })
+            .await??
+        {
+            TransactionServiceResponse::Invoice(invoice) => Ok(*invoice),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    pub async fn get_invoice(&mut self, payment_id: String) -> Result<Invoice, TransactionServiceError> {
+        match self
+            .handle
+            .call(TransactionServiceRequest::GetInvoice(payment_id))
+            .await??
+        {
+            TransactionServiceResponse::Invoice(invoice) => Ok(*invoice),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    pub async fn get_invoices(&mut self) -> Result<Vec<Invoice>, TransactionServiceError> {
+        match self.handle.call(TransactionServiceRequest::GetInvoices).await?? {
+            TransactionServiceResponse::Invoices(invoices) => Ok(invoices),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    pub async fn cancel_invoice(&mut self, payment_id: String) -> Result<(), TransactionServiceError> {
+        match self
+            .handle
+            .call(TransactionServiceRequest::CancelInvoice(payment_id))
+            .await??
+        {
+            TransactionServiceResponse::InvoiceCancelled => Ok(()),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    pub async fn get_transaction_status_history(
+        &mut self,
+        tx_id: TxId,
+    ) -> Result<Vec<TransactionStatusChange>, TransactionServiceError> {
+        match self
+            .handle
+            .call(TransactionServiceRequest::GetTransactionStatusHistory(tx_id))
+            .await??
+        {
+            TransactionServiceResponse::TransactionStatusHistory(history) => Ok(history),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Pays all recipients with as few one-sided transactions as the maximum transaction weight allows. Without a
+    /// batch id one is derived from the recipients. Submitting a batch again only pays the rows that have not been
+    /// paid yet (or whose transaction was cancelled), so a payout can safely be re-run after a failure.
+    pub async fn send_batch_payout(
+        &mut self,
+        batch_id: Option<String>,
+        recipients: Vec<BatchPayoutRecipient>,
+        selection_criteria: UtxoSelectionCriteria,
+        fee_per_gram: MicroMinotari,
+    ) -> Result<Vec<BatchPayoutRow>, TransactionServiceError> {
+        match self
+            .handle
+            .call(TransactionServiceRequest::SendBatchPayout {
+                batch_id,
+                recipients,
+                selection_criteria,
+                fee_per_gram,
+            })
+            .await??
+        {
+            TransactionServiceResponse::BatchPayout(rows) => Ok(rows),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    pub async fn get_batch_payout(&mut self, batch_id: String) -> Result<Vec<BatchPayoutRow>, TransactionServiceError> {
+        match self
+            .handle
+            .call(TransactionServiceRequest::GetBatchPayout(batch_id))
+            .await??
+        {
+            TransactionServiceResponse::BatchPayout(rows) => Ok(rows),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Stores a new recurring payment. The transaction service sends the payment on every occurrence of its cadence,
+    /// retrying failed payments, until the schedule completes or is cancelled.
+    pub async fn create_scheduled_payment(
+        &mut self,
+        payment: ScheduledPayment,
+    ) -> Result<ScheduledPayment, TransactionServiceError> {
+        match self
+            .handle
+            .call(TransactionServiceRequest::CreateScheduledPayment(Box::new(payment)))
+            .await??
+        {
+            TransactionServiceResponse::ScheduledPayment(payment) => Ok(*payment),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    pub async fn get_scheduled_payment(
+        &mut self,
+        schedule_id: String,
+    ) -> Result<ScheduledPayment, TransactionServiceError> {
+        match self
+            .handle
+            .call(TransactionServiceRequest::GetScheduledPayment(schedule_id))
+            .await??
+        {
+            TransactionServiceResponse::ScheduledPayment(payment) => Ok(*payment),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    pub async fn get_scheduled_payments(&mut self) -> Result<Vec<ScheduledPayment>, TransactionServiceError> {
+        match self
+            .handle
+            .call(TransactionServiceRequest::GetScheduledPayments)
+            .await??
+        {
+            TransactionServiceResponse::ScheduledPayments(payments) => Ok(payments),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    pub async fn pause_scheduled_payment(
+        &mut self,
+        schedule_id: String,
+    ) -> Result<ScheduledPayment, TransactionServiceError> {
+        self.set_scheduled_payment_status(schedule_id, ScheduledPaymentStatus::Paused)
+            .await
+    }
+
+    /// Resumes a paused schedule. Occurrences that fell within the pause are skipped.
+    pub async fn resume_scheduled_payment(
+        &mut self,
+        schedule_id: String,
+    ) -> Result<ScheduledPayment, TransactionServiceError> {
+        self.set_scheduled_payment_status(schedule_id, ScheduledPaymentStatus::Active)
+            .await
+    }
+
+    pub async fn cancel_scheduled_payment(
+        &mut self,
+        schedule_id: String,
+    ) -> Result<ScheduledPayment, TransactionServiceError> {
+        self.set_scheduled_payment_status(schedule_id, ScheduledPaymentStatus::Cancelled)
+            .await
+    }
+
+    async fn set_scheduled_payment_status(
+        &mut self,
+        schedule_id: String,
+        status: ScheduledPaymentStatus,
+    ) -> Result<ScheduledPayment, TransactionServiceError> {
+        match self
+            .handle
+            .call(TransactionServiceRequest::SetScheduledPaymentStatus(
+                schedule_id,
+                status,
+            ))
+            .await??
+        {
+            TransactionServiceResponse::ScheduledPayment(payment) => Ok(*payment),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
     pub async fn get_cancelled_completed_transactions(
         &mut self,
     ) -> Result<HashMap<TxId, CompletedTransaction>, TransactionServiceError> {
@@ -63,10 +63,13 @@ use crate::{
     util::wallet_identity::WalletIdentity,
 };
 
+pub mod batch_payout;
 pub mod config;
 pub mod error;
 pub mod handle;
+pub mod payment_request;
 pub mod protocols;
+pub mod scheduled_payment;
 pub mod service;
 pub mod storage;
 pub mod tasks;
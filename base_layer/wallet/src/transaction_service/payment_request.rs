@@ -0,0 +1,333 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{
+    fmt::{Display, Formatter},
+    str::FromStr,
+};
+
+use tari_common::configuration::Network;
+use tari_common_types::tari_address::TariAddress;
+use tari_core::transactions::tari_amount::MicroMinotari;
+use thiserror::Error;
+
+const URI_SCHEME: &str = "tari://";
+const URI_PATH: &str = "/transactions/invoice";
+
+/// A request for payment that can be shared with a payer as a URI or QR code, in the form
+/// `tari://<network>/transactions/invoice?tariAddress=<address>&paymentType=<type>&...`. It uses its own path rather
+/// than the plain `transactions/send` link so that wallets which do not understand invoices do not pay it in a way
+/// that the payee cannot match to the invoice.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PaymentRequest {
+    pub address: TariAddress,
+    /// The only kind of payment to the address that is matched to the invoice
+    pub payment_type: PaymentType,
+    pub amount: MicroMinotari,
+    pub payment_id: String,
+    pub message: String,
+    /// Unix timestamp (in seconds) after which the request should no longer be paid
+    pub expires_at: Option<u64>,
+}
+
+/// The kind of payment that a payment request must be paid with
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PaymentType {
+    /// A one-sided payment to the plain (non-stealth) script of the address
+    OneSided,
+    /// A one-sided payment to a stealth script derived from the address
+    StealthOneSided,
+    /// An interactive payment to the address
+    Interactive,
+}
+
+impl PaymentType {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            PaymentType::OneSided => "oneSided",
+            PaymentType::StealthOneSided => "stealthOneSided",
+            PaymentType::Interactive => "interactive",
+        }
+    }
+}
+
+impl Display for PaymentType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for PaymentType {
+    type Err = PaymentRequestError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "oneSided" => Ok(PaymentType::OneSided),
+            "stealthOneSided" => Ok(PaymentType::StealthOneSided),
+            "interactive" => Ok(PaymentType::Interactive),
+            _ => Err(PaymentRequestError::InvalidParameter("paymentType")),
+        }
+    }
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum PaymentRequestError {
+    #[error("Payment request URI must start with `tari://`")]
+    InvalidScheme,
+    #[error("Payment request URI has an invalid path: `{0}`")]
+    InvalidPath(String),
+    #[error("Payment request URI is missing the `{0}` parameter")]
+    MissingParameter(&'static str),
+    #[error("Payment request URI has an invalid `{0}` parameter")]
+    InvalidParameter(&'static str),
+    #[error("Payment request is for network `{uri}` but the address is for network `{address}`")]
+    NetworkMismatch { uri: String, address: Network },
+}
+
+impl PaymentRequest {
+    pub fn to_uri(&self) -> String {
+        let mut uri = format!(
+            "{}{}{}?tariAddress={}&paymentType={}&amount={}&paymentId={}",
+            URI_SCHEME,
+            self.address.network(),
+            URI_PATH,
+            self.address.to_hex(),
+            self.payment_type,
+            self.amount.as_u64(),
+            percent_encode(&self.payment_id)
+        );
+        if !self.message.is_empty() {
+            uri.push_str("&message=");
+            uri.push_str(&percent_encode(&self.message));
+        }
+        if let Some(expires_at) = self.expires_at {
+            uri.push_str(&format!("&expiresAt={}", expires_at));
+        }
+        uri
+    }
+}
+
+impl Display for PaymentRequest {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.to_uri())
+    }
+}
+
+impl FromStr for PaymentRequest {
+    type Err = PaymentRequestError;
+
+    fn from_str(uri: &str) -> Result<Self, Self::Err> {
+        let rest = uri.strip_prefix(URI_SCHEME).ok_or(PaymentRequestError::InvalidScheme)?;
+        let (path, query) = rest.split_once('?').unwrap_or((rest, ""));
+        let network = path
+            .strip_suffix(URI_PATH)
+            .ok_or_else(|| PaymentRequestError::InvalidPath(path.to_string()))?;
+
+        let mut address = None;
+        let mut payment_type = None;
+        let mut amount = None;
+        let mut payment_id = None;
+        let mut message = String::new();
+        let mut expires_at = None;
+        for pair in query.split('&').filter(|p| !p.is_empty()) {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            match key {
+                "tariAddress" => {
+                    address = Some(
+                        TariAddress::from_hex(value)
+                            .map_err(|_| PaymentRequestError::InvalidParameter("tariAddress"))?,
+                    )
+                },
+                "paymentType" => payment_type = Some(value.parse()?),
+                "amount" => {
+                    amount = Some(MicroMinotari::from(
+                        value
+                            .parse::<u64>()
+                            .map_err(|_| PaymentRequestError::InvalidParameter("amount"))?,
+                    ))
+                },
+                "paymentId" => {
+                    payment_id = Some(percent_decode(value).ok_or(PaymentRequestError::InvalidParameter("paymentId"))?)
+                },
+                "message" => message = percent_decode(value).ok_or(PaymentRequestError::InvalidParameter("message"))?,
+                "expiresAt" => {
+                    expires_at = Some(
+                        value
+                            .parse::<u64>()
+                            .map_err(|_| PaymentRequestError::InvalidParameter("expiresAt"))?,
+                    )
+                },
+                // Ignore unknown parameters so that the format can be extended
+                _ => {},
+            }
+        }
+
+        let address = address.ok_or(PaymentRequestError::MissingParameter("tariAddress"))?;
+        if network != address.network().as_key_str() {
+            return Err(PaymentRequestError::NetworkMismatch {
+                uri: network.to_string(),
+                address: address.network(),
+            });
+        }
+        Ok(Self {
+            address,
+            payment_type: payment_type.ok_or(PaymentRequestError::MissingParameter("paymentType"))?,
+            amount: amount.ok_or(PaymentRequestError::MissingParameter("amount"))?,
+            payment_id: payment_id.ok_or(PaymentRequestError::MissingParameter("paymentId"))?,
+            message,
+            expires_at,
+        })
+    }
+}
+
+fn percent_encode(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'_' | b'.' | b'~') {
+            encoded.push(char::from(byte));
+        } else {
+            encoded.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    encoded
+}
+
+fn percent_decode(value: &str) -> Option<String> {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'%' => {
+                let hex = value.get(i + 1..i + 3)?;
+                decoded.push(u8::from_str_radix(hex, 16).ok()?);
+                i += 3;
+            },
+            b'+' => {
+                decoded.push(b' ');
+                i += 1;
+            },
+            b => {
+                decoded.push(b);
+                i += 1;
+            },
+        }
+    }
+    String::from_utf8(decoded).ok()
+}
+
+#[cfg(test)]
+mod test {
+    use rand::rngs::OsRng;
+    use tari_common_types::types::PublicKey;
+    use tari_crypto::keys::PublicKey as PublicKeyTrait;
+
+    use super::*;
+
+    fn address() -> TariAddress {
+        let (_, public_key) = PublicKey::random_keypair(&mut OsRng);
+        TariAddress::new(public_key, Network::Esmeralda)
+    }
+
+    #[test]
+    fn it_round_trips_through_a_uri() {
+        let request = PaymentRequest {
+            address: address(),
+            payment_type: PaymentType::OneSided,
+            amount: MicroMinotari::from(1_234_567),
+            payment_id: "order #42/a".to_string(),
+            message: "Thanks for your order & welcome!".to_string(),
+            expires_at: Some(1_700_000_000),
+        };
+        let uri = request.to_uri();
+        assert!(uri.starts_with("tari://esmeralda/transactions/invoice?tariAddress="));
+        assert!(uri.contains("paymentType=oneSided"));
+        assert!(uri.contains("paymentId=order%20%2342%2Fa"));
+        assert_eq!(uri.parse::<PaymentRequest>().unwrap(), request);
+
+        let request = PaymentRequest {
+            message: String::new(),
+            expires_at: None,
+            payment_type: PaymentType::StealthOneSided,
+            ..request
+        };
+        assert_eq!(request.to_uri().parse::<PaymentRequest>().unwrap(), request);
+    }
+
+    #[test]
+    fn it_rejects_invalid_uris() {
+        let address = address();
+        assert_eq!(
+            "http://esmeralda/transactions/invoice".parse::<PaymentRequest>(),
+            Err(PaymentRequestError::InvalidScheme)
+        );
+        assert_eq!(
+            format!(
+                "tari://esmeralda/transactions/invoice?tariAddress={}&paymentType=oneSided",
+                address.to_hex()
+            )
+            .parse::<PaymentRequest>(),
+            Err(PaymentRequestError::MissingParameter("amount"))
+        );
+        assert_eq!(
+            format!(
+                "tari://esmeralda/transactions/invoice?tariAddress={}&paymentType=oneSided&amount=x&paymentId=1",
+                address.to_hex()
+            )
+            .parse::<PaymentRequest>(),
+            Err(PaymentRequestError::InvalidParameter("amount"))
+        );
+        assert!(matches!(
+            format!(
+                "tari://mainnet/transactions/invoice?tariAddress={}&paymentType=oneSided&amount=1&paymentId=1",
+                address.to_hex()
+            )
+            .parse::<PaymentRequest>(),
+            Err(PaymentRequestError::NetworkMismatch { .. })
+        ));
+        // A plain send link is not an invoice
+        assert!(matches!(
+            format!(
+                "tari://esmeralda/transactions/send?tariAddress={}&amount=1&paymentId=1",
+                address.to_hex()
+            )
+            .parse::<PaymentRequest>(),
+            Err(PaymentRequestError::InvalidPath(_))
+        ));
+        assert_eq!(
+            format!(
+                "tari://esmeralda/transactions/invoice?tariAddress={}&amount=1&paymentId=1",
+                address.to_hex()
+            )
+            .parse::<PaymentRequest>(),
+            Err(PaymentRequestError::MissingParameter("paymentType"))
+        );
+        assert_eq!(
+            format!(
+                "tari://esmeralda/transactions/invoice?tariAddress={}&paymentType=other&amount=1&paymentId=1",
+                address.to_hex()
+            )
+            .parse::<PaymentRequest>(),
+            Err(PaymentRequestError::InvalidParameter("paymentType"))
+        );
+    }
+}
@@ -57,6 +57,7 @@ use tokio::{
 };
 
 use crate::{
+    accounts::AccountId,
     connectivity_service::WalletConnectivityInterface,
     output_manager_service::UtxoSelectionCriteria,
     transaction_service::{
@@ -99,6 +100,7 @@ pub struct TransactionSendProtocol<TBackend, TWalletConnectivity, TKeyManagerInt
     cancellation_receiver: Option<oneshot::Receiver<()>>,
     tx_meta: TransactionMetadata,
     sender_protocol: Option<SenderTransactionProtocol>,
+    account_id: AccountId,
 }
 
 impl<TBackend, TWalletConnectivity, TKeyManagerInterface>
@@ -123,6 +125,7 @@ where
         >,
         stage: TransactionSendProtocolStage,
         sender_protocol: Option<SenderTransactionProtocol>,
+        account_id: AccountId,
     ) -> Self {
         Self {
             id,
@@ -137,6 +140,7 @@ where
             stage,
             tx_meta,
             sender_protocol,
+            account_id,
         }
     }
 
@@ -219,7 +223,7 @@ where
             .prepare_transaction_to_send(
                 self.id,
                 self.amount,
-                UtxoSelectionCriteria::default(),
+                UtxoSelectionCriteria::default().for_account(self.account_id),
                 OutputFeatures::default(),
                 self.fee_per_gram,
                 self.tx_meta.clone(),
@@ -330,7 +334,8 @@ where
                 self.message.clone(),
                 Utc::now().naive_utc(),
                 direct_send_result,
-            );
+            )
+            .with_account_id(self.account_id);
             self.resources
                 .db
                 .add_pending_outbound_transaction(outbound_tx.tx_id, outbound_tx)
@@ -579,7 +584,8 @@ where
             None,
             None,
         )
-        .map_err(|e| TransactionServiceProtocolError::new(self.id, TransactionServiceError::from(e)))?;
+        .map_err(|e| TransactionServiceProtocolError::new(self.id, TransactionServiceError::from(e)))?
+        .with_account_id(outbound_tx.account_id);
 
         self.resources
             .db
@@ -0,0 +1,204 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{
+    convert::TryFrom,
+    fmt::{Display, Formatter},
+    str::FromStr,
+};
+
+use chrono::{Duration, Months, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+const SECONDS_PER_WEEK: u64 = 7 * SECONDS_PER_DAY;
+
+/// How often a scheduled payment is repeated. Every occurrence is calculated from the start of the schedule, so late
+/// or retried payments do not shift the occurrences that follow them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PaymentCadence {
+    /// A fixed interval in seconds
+    Interval(u64),
+    Daily,
+    Weekly,
+    /// The same day of every month. Occurrences that fall on a day the month does not have are paid on the last day of
+    /// that month.
+    Monthly,
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum PaymentCadenceError {
+    #[error("Invalid payment cadence `{0}`, expected `daily`, `weekly`, `monthly` or an interval such as `12h`")]
+    InvalidCadence(String),
+    #[error("The payment interval must be longer than zero")]
+    ZeroInterval,
+}
+
+impl PaymentCadence {
+    /// The time of occurrence `n` (counting from 0) of a schedule that starts at `start_at`
+    pub fn occurrence(self, start_at: NaiveDateTime, n: u64) -> Option<NaiveDateTime> {
+        match self.fixed_seconds() {
+            Some(seconds) => {
+                let offset = i64::try_from(n.checked_mul(seconds)?).ok()?;
+                start_at.checked_add_signed(Duration::seconds(offset))
+            },
+            None => start_at.checked_add_months(Months::new(u32::try_from(n).ok()?)),
+        }
+    }
+
+    /// The first occurrence from occurrence `from` onwards that is later than `after`
+    pub fn next_occurrence_after(self, start_at: NaiveDateTime, after: NaiveDateTime, from: u64) -> Option<u64> {
+        if let Some(seconds) = self.fixed_seconds() {
+            let elapsed = (after - start_at).num_seconds();
+            if elapsed < 0 {
+                return Some(from);
+            }
+            return Some(from.max(u64::try_from(elapsed).ok()? / seconds + 1));
+        }
+        let mut n = from;
+        while self.occurrence(start_at, n)? <= after {
+            n = n.checked_add(1)?;
+        }
+        Some(n)
+    }
+
+    fn fixed_seconds(self) -> Option<u64> {
+        match self {
+            PaymentCadence::Interval(seconds) => Some(seconds.max(1)),
+            PaymentCadence::Daily => Some(SECONDS_PER_DAY),
+            PaymentCadence::Weekly => Some(SECONDS_PER_WEEK),
+            PaymentCadence::Monthly => None,
+        }
+    }
+}
+
+impl Display for PaymentCadence {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PaymentCadence::Interval(seconds) => write!(f, "{}s", seconds),
+            PaymentCadence::Daily => f.write_str("daily"),
+            PaymentCadence::Weekly => f.write_str("weekly"),
+            PaymentCadence::Monthly => f.write_str("monthly"),
+        }
+    }
+}
+
+impl FromStr for PaymentCadence {
+    type Err = PaymentCadenceError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let cadence = s.trim().to_lowercase();
+        match cadence.as_str() {
+            "daily" => return Ok(PaymentCadence::Daily),
+            "weekly" => return Ok(PaymentCadence::Weekly),
+            "monthly" => return Ok(PaymentCadence::Monthly),
+            _ => {},
+        }
+        let invalid = || PaymentCadenceError::InvalidCadence(s.to_string());
+        let unit_start = cadence.find(|c: char| !c.is_ascii_digit()).ok_or_else(invalid)?;
+        let (value, unit) = cadence.split_at(unit_start);
+        let value = value.parse::<u64>().map_err(|_| invalid())?;
+        let multiplier = match unit {
+            "s" => 1,
+            "m" => 60,
+            "h" => 60 * 60,
+            "d" => SECONDS_PER_DAY,
+            "w" => SECONDS_PER_WEEK,
+            _ => return Err(invalid()),
+        };
+        match value.checked_mul(multiplier) {
+            Some(0) => Err(PaymentCadenceError::ZeroInterval),
+            Some(seconds) => Ok(PaymentCadence::Interval(seconds)),
+            None => Err(invalid()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::NaiveDate;
+
+    use super::*;
+
+    fn date_time(year: i32, month: u32, day: u32, hour: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(year, month, day)
+            .unwrap()
+            .and_hms_opt(hour, 0, 0)
+            .unwrap()
+    }
+
+    #[test]
+    fn it_parses_cadences() {
+        assert_eq!("daily".parse(), Ok(PaymentCadence::Daily));
+        assert_eq!("Weekly".parse(), Ok(PaymentCadence::Weekly));
+        assert_eq!("monthly".parse(), Ok(PaymentCadence::Monthly));
+        assert_eq!("90s".parse(), Ok(PaymentCadence::Interval(90)));
+        assert_eq!("12h".parse(), Ok(PaymentCadence::Interval(12 * 60 * 60)));
+        assert_eq!("2w".parse(), Ok(PaymentCadence::Interval(2 * SECONDS_PER_WEEK)));
+        assert_eq!("0d".parse::<PaymentCadence>(), Err(PaymentCadenceError::ZeroInterval));
+        assert!("fortnightly".parse::<PaymentCadence>().is_err());
+        assert!("12".parse::<PaymentCadence>().is_err());
+        assert!("h".parse::<PaymentCadence>().is_err());
+
+        for cadence in [
+            PaymentCadence::Interval(3600),
+            PaymentCadence::Daily,
+            PaymentCadence::Weekly,
+            PaymentCadence::Monthly,
+        ] {
+            assert_eq!(cadence.to_string().parse(), Ok(cadence));
+        }
+    }
+
+    #[test]
+    fn it_calculates_monthly_occurrences_without_drifting() {
+        let start_at = date_time(2024, 1, 31, 9);
+        let cadence = PaymentCadence::Monthly;
+        assert_eq!(cadence.occurrence(start_at, 0), Some(start_at));
+        assert_eq!(cadence.occurrence(start_at, 1), Some(date_time(2024, 2, 29, 9)));
+        assert_eq!(cadence.occurrence(start_at, 2), Some(date_time(2024, 3, 31, 9)));
+        assert_eq!(cadence.occurrence(start_at, 3), Some(date_time(2024, 4, 30, 9)));
+    }
+
+    #[test]
+    fn it_skips_missed_occurrences() {
+        let start_at = date_time(2024, 1, 1, 9);
+        let after = date_time(2024, 1, 10, 12);
+        assert_eq!(
+            PaymentCadence::Daily.next_occurrence_after(start_at, after, 1),
+            Some(10)
+        );
+        assert_eq!(
+            PaymentCadence::Daily.next_occurrence_after(start_at, start_at - Duration::days(1), 1),
+            Some(1)
+        );
+        assert_eq!(
+            PaymentCadence::Monthly.next_occurrence_after(start_at, date_time(2024, 4, 1, 9), 1),
+            Some(4)
+        );
+        assert_eq!(
+            PaymentCadence::Monthly.next_occurrence_after(start_at, after, 3),
+            Some(3)
+        );
+    }
+}
@@ -27,8 +27,9 @@ use std::{
     time::{Duration, Instant},
 };
 
+use blake2::Blake2b;
 use chrono::{NaiveDateTime, Utc};
-use digest::Digest;
+use digest::{consts::U32, Digest};
 use futures::{pin_mut, stream::FuturesUnordered, Stream, StreamExt};
 use log::*;
 use rand::rngs::OsRng;
@@ -36,12 +37,14 @@ use sha2::Sha256;
 use tari_common_types::{
     burnt_proof::BurntProof,
     tari_address::TariAddress,
+    tari_subaddress::TariSubaddress,
     transaction::{ImportStatus, TransactionDirection, TransactionStatus, TxId},
     types::{PrivateKey, PublicKey, Signature},
 };
 use tari_comms::types::CommsPublicKey;
 use tari_comms_dht::outbound::OutboundMessageRequester;
 use tari_core::{
+    borsh::SerializedSize,
     consensus::ConsensusManager,
     covenants::Covenant,
     mempool::FeePerGramStat,
@@ -79,26 +82,40 @@ use tari_crypto::{
 };
 use tari_key_manager::key_manager_service::KeyId;
 use tari_p2p::domain_message::DomainMessage;
-use tari_script::{inputs, one_sided_payment_script, script, stealth_payment_script, TariScript};
+use tari_script::{
+    inputs,
+    one_sided_payment_script,
+    script,
+    stealth_payment_script,
+    ExecutionStack,
+    Opcode,
+    TariScript,
+};
 use tari_service_framework::{reply_channel, reply_channel::Receiver};
 use tari_shutdown::ShutdownSignal;
 use tokio::{
     sync::{mpsc, mpsc::Sender, oneshot, Mutex},
     task::JoinHandle,
+    time,
+    time::MissedTickBehavior,
 };
 
 use crate::{
+    accounts::{registered_accounts, AccountId},
     base_node_service::handle::{BaseNodeEvent, BaseNodeServiceHandle},
+    config::KEY_MANAGER_INVOICE_BRANCH_KEY,
     connectivity_service::WalletConnectivityInterface,
     output_manager_service::{
+        error::OutputManagerError,
         handle::{OutputManagerEvent, OutputManagerHandle},
-        storage::models::SpendingPriority,
+        storage::models::{KnownOneSidedPaymentScript, SpendingPriority},
         UtxoSelectionCriteria,
     },
     storage::database::{WalletBackend, WalletDatabase},
     transaction_service::{
+        batch_payout::{batch_id_from_recipients, max_outputs_per_transaction, BatchPayoutRecipient},
         config::TransactionServiceConfig,
-        error::{TransactionServiceError, TransactionServiceProtocolError},
+        error::{TransactionServiceError, TransactionServiceProtocolError, TransactionStorageError},
         handle::{
             FeePerGramStatsResponse,
             TransactionEvent,
@@ -115,7 +132,13 @@ use crate::{
         storage::{
             database::{TransactionBackend, TransactionDatabase},
             models::{
+                BatchPayoutRow,
+                BatchPayoutStatus,
                 CompletedTransaction,
+                Invoice,
+                InvoiceStatus,
+                ScheduledPayment,
+                ScheduledPaymentStatus,
                 TxCancellationReason,
                 WalletTransaction::{Completed, PendingInbound, PendingOutbound},
             },
@@ -358,12 +381,25 @@ where
         let mut base_node_service_event_stream = self.base_node_service.get_event_stream();
         let mut output_manager_event_stream = self.resources.output_manager_service.get_event_stream();
 
+        let scheduled_payment_check_interval = self.resources.config.scheduled_payment_check_interval;
+        let mut scheduled_payment_interval = time::interval_at(
+            time::Instant::now() + scheduled_payment_check_interval,
+            scheduled_payment_check_interval,
+        );
+        scheduled_payment_interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        if let Err(e) = self.reconcile_in_flight_scheduled_payments() {
+            warn!(target: LOG_TARGET, "Could not reconcile in-flight scheduled payments: {}", e);
+        }
+
         debug!(target: LOG_TARGET, "Transaction Service started");
         loop {
             tokio::select! {
                 event = output_manager_event_stream.recv() => {
                     match event {
-                        Ok(msg) => self.handle_output_manager_service_event(msg).await,
+                        Ok(msg) => {
+                            self.handle_output_manager_service_event(msg, &mut transaction_broadcast_protocol_handles)
+                                .await
+                        },
                         Err(e) => debug!(target: LOG_TARGET, "Lagging read on base node event broadcast channel: {}", e),
                     };
                 },
@@ -556,6 +592,11 @@ where
                         ),
                         Err(e) => error!(target: LOG_TARGET, "Error resolving Transaction Validation protocol: {:?}", e),
                     };
+                }
+                _ = scheduled_payment_interval.tick() => {
+                    if let Err(e) = self.send_due_scheduled_payments(&mut transaction_broadcast_protocol_handles).await {
+                        warn!(target: LOG_TARGET, "Error sending scheduled payments: {}", e);
+                    }
                 }
                  _ = shutdown.wait() => {
                     info!(target: LOG_TARGET, "Transaction service shutting down because it received the shutdown signal");
@@ -623,6 +664,7 @@ where
                 message,
             } => self
                 .send_one_sided_transaction(
+                    TxId::new_random(),
                     destination,
                     amount,
                     selection_criteria,
@@ -652,6 +694,25 @@ where
                 )
                 .await
                 .map(TransactionServiceResponse::TransactionSent),
+            TransactionServiceRequest::SendOneSidedToStealthSubaddressTransaction {
+                destination,
+                amount,
+                selection_criteria,
+                output_features,
+                fee_per_gram,
+                message,
+            } => self
+                .send_one_sided_to_stealth_subaddress_transaction(
+                    destination,
+                    amount,
+                    selection_criteria,
+                    *output_features,
+                    fee_per_gram,
+                    message,
+                    transaction_broadcast_join_handles,
+                )
+                .await
+                .map(TransactionServiceResponse::TransactionSent),
             TransactionServiceRequest::BurnTari {
                 amount,
                 selection_criteria,
@@ -759,6 +820,13 @@ where
             TransactionServiceRequest::GetCompletedTransactions => Ok(
                 TransactionServiceResponse::CompletedTransactions(self.db.get_completed_transactions()?),
             ),
+            TransactionServiceRequest::GetAccountCompletedTransactions(account) => Ok(
+                TransactionServiceResponse::CompletedTransactions(self.db.get_account_completed_transactions(account)?),
+            ),
+            TransactionServiceRequest::GetCompletedTransactionsPage { account, offset, limit } => {
+                let (page, total) = self.db.get_completed_transactions_page(account, offset, limit)?;
+                Ok(TransactionServiceResponse::CompletedTransactionsPage(page, total))
+            },
             TransactionServiceRequest::GetCancelledPendingInboundTransactions => {
                 Ok(TransactionServiceResponse::PendingInboundTransactions(
                     self.db.get_cancelled_pending_inbound_transactions()?,
@@ -855,6 +923,55 @@ where
                 .start_transaction_revalidation(transaction_validation_join_handles)
                 .await
                 .map(TransactionServiceResponse::ValidationStarted),
+            TransactionServiceRequest::CreateInvoice {
+                payment_id,
+                amount,
+                message,
+                expires_at,
+            } => self
+                .create_invoice(payment_id, amount, message, expires_at)
+                .await
+                .map(|invoice| TransactionServiceResponse::Invoice(Box::new(invoice))),
+            TransactionServiceRequest::GetInvoice(payment_id) => Ok(TransactionServiceResponse::Invoice(Box::new(
+                self.db.get_invoice(&payment_id)?,
+            ))),
+            TransactionServiceRequest::GetInvoices => Ok(TransactionServiceResponse::Invoices(self.db.get_invoices()?)),
+            TransactionServiceRequest::CancelInvoice(payment_id) => self
+                .cancel_invoice(&payment_id)
+                .map(|_| TransactionServiceResponse::InvoiceCancelled),
+            TransactionServiceRequest::GetTransactionStatusHistory(tx_id) => Ok(
+                TransactionServiceResponse::TransactionStatusHistory(self.db.get_transaction_status_history(tx_id)?),
+            ),
+            TransactionServiceRequest::SendBatchPayout {
+                batch_id,
+                recipients,
+                selection_criteria,
+                fee_per_gram,
+            } => self
+                .send_batch_payout(
+                    batch_id,
+                    recipients,
+                    selection_criteria,
+                    fee_per_gram,
+                    transaction_broadcast_join_handles,
+                )
+                .await
+                .map(TransactionServiceResponse::BatchPayout),
+            TransactionServiceRequest::GetBatchPayout(batch_id) => Ok(TransactionServiceResponse::BatchPayout(
+                self.db.get_batch_payout(&batch_id)?,
+            )),
+            TransactionServiceRequest::CreateScheduledPayment(payment) => self
+                .create_scheduled_payment(*payment)
+                .map(|payment| TransactionServiceResponse::ScheduledPayment(Box::new(payment))),
+            TransactionServiceRequest::GetScheduledPayment(schedule_id) => Ok(
+                TransactionServiceResponse::ScheduledPayment(Box::new(self.db.get_scheduled_payment(&schedule_id)?)),
+            ),
+            TransactionServiceRequest::GetScheduledPayments => Ok(TransactionServiceResponse::ScheduledPayments(
+                self.db.get_scheduled_payments()?,
+            )),
+            TransactionServiceRequest::SetScheduledPaymentStatus(schedule_id, status) => self
+                .set_scheduled_payment_status(&schedule_id, status)
+                .map(|payment| TransactionServiceResponse::ScheduledPayment(Box::new(payment))),
             TransactionServiceRequest::GetFeePerGramStatsPerBlock { count } => {
                 let reply_channel = reply_channel.take().expect("reply_channel is Some");
                 self.handle_get_fee_per_gram_stats_per_block_request(count, reply_channel);
@@ -933,13 +1050,48 @@ where
                         warn!(target: LOG_TARGET, "Error validating  txos: {:?}", e);
                         e
                     });
+                if let Err(e) = self.expire_invoices() {
+                    warn!(target: LOG_TARGET, "Error expiring invoices: {:?}", e);
+                }
 
                 self.last_seen_tip_height = Some(height);
             },
         }
     }
 
-    async fn handle_output_manager_service_event(&mut self, event: Arc<OutputManagerEvent>) {
+    async fn handle_output_manager_service_event(
+        &mut self,
+        event: Arc<OutputManagerEvent>,
+        transaction_broadcast_join_handles: &mut FuturesUnordered<
+            JoinHandle<Result<TxId, TransactionServiceProtocolError<TxId>>>,
+        >,
+    ) {
+        if let OutputManagerEvent::ConsolidationTransactionCreated {
+            tx_id,
+            transaction,
+            amount,
+            account_id,
+        } = &*event
+        {
+            if let Err(e) = self.submit_consolidation_transaction(
+                transaction_broadcast_join_handles,
+                *tx_id,
+                (**transaction).clone(),
+                *amount,
+                *account_id,
+            ) {
+                warn!(
+                    target: LOG_TARGET,
+                    "Error submitting consolidation transaction (TxId: {}): {}", tx_id, e
+                );
+                if let Err(e) = self.resources.output_manager_service.cancel_transaction(*tx_id).await {
+                    warn!(
+                        target: LOG_TARGET,
+                        "Error cancelling consolidation transaction (TxId: {}): {}", tx_id, e
+                    );
+                }
+            }
+        }
         if let OutputManagerEvent::TxoValidationSuccess(_) = (*event).clone() {
             let db = self.db.clone();
             let output_manager_handle = self.resources.output_manager_service.clone();
@@ -983,6 +1135,7 @@ where
         >,
         reply_channel: oneshot::Sender<Result<TransactionServiceResponse, TransactionServiceError>>,
     ) -> Result<(), TransactionServiceError> {
+        let account_id = selection_criteria.account;
         let tx_id = TxId::new_random();
         if destination.network() != self.resources.wallet_identity.network {
             let _result = reply_channel
@@ -994,17 +1147,28 @@ where
             return Err(TransactionServiceError::InvalidNetwork);
         }
         let dest_pubkey = destination.public_key();
-        // If we're paying ourselves, let's complete and submit the transaction immediately
-        if self.resources.wallet_identity.address.public_key() == dest_pubkey {
+        // If we're paying ourselves, or another account of this wallet, let's complete and submit the transaction
+        // immediately. Accounts other than the default have no comms identity and cannot be paid interactively.
+        if let Some(destination_account) = self.find_account_for_public_key(dest_pubkey).await? {
             debug!(
                 target: LOG_TARGET,
-                "Received transaction with spend-to-self transaction"
+                "Received transaction with spend-to-self transaction (account {} to account {})",
+                account_id,
+                destination_account
             );
 
             let (fee, transaction) = self
                 .resources
                 .output_manager_service
-                .create_pay_to_self_transaction(tx_id, amount, selection_criteria, output_features, fee_per_gram, None)
+                .create_pay_to_self_transaction(
+                    tx_id,
+                    amount,
+                    selection_criteria,
+                    output_features,
+                    fee_per_gram,
+                    None,
+                    destination_account,
+                )
                 .await?;
 
             // Notify that the transaction was successfully resolved.
@@ -1017,7 +1181,7 @@ where
                 CompletedTransaction::new(
                     tx_id,
                     self.resources.wallet_identity.address.clone(),
-                    self.resources.wallet_identity.address.clone(),
+                    destination.clone(),
                     amount,
                     fee,
                     transaction,
@@ -1027,7 +1191,8 @@ where
                     TransactionDirection::Inbound,
                     None,
                     None,
-                )?,
+                )?
+                .with_account_id(account_id),
             )?;
 
             let _result = reply_channel
@@ -1059,6 +1224,7 @@ where
             Some(reply_channel),
             TransactionSendProtocolStage::Initial,
             None,
+            account_id,
         );
         let join_handle = tokio::spawn(protocol.execute());
         join_handles.push(join_handle);
@@ -1066,6 +1232,24 @@ where
         Ok(())
     }
 
+    /// Returns the account of this wallet whose address has the given public key, if any
+    async fn find_account_for_public_key(
+        &self,
+        public_key: &PublicKey,
+    ) -> Result<Option<AccountId>, TransactionServiceError> {
+        if self.resources.wallet_identity.address.public_key() == public_key {
+            return Ok(Some(AccountId::DEFAULT));
+        }
+        let key_manager = &self.resources.transaction_key_manager_service;
+        for account in registered_accounts(key_manager).await? {
+            let key_id = key_manager.get_static_key(account.key_branch()).await?;
+            if key_manager.get_public_key_at_key_id(&key_id).await? == *public_key {
+                return Ok(Some(account));
+            }
+        }
+        Ok(None)
+    }
+
     /// broadcasts a SHA-XTR atomic swap transaction
     /// # Arguments
     /// 'dest_pubkey': The Comms pubkey of the recipient node
@@ -1083,6 +1267,7 @@ where
             JoinHandle<Result<TxId, TransactionServiceProtocolError<TxId>>>,
         >,
     ) -> Result<Box<(TxId, PublicKey, TransactionOutput)>, TransactionServiceError> {
+        let account_id = selection_criteria.account;
         let dest_pubkey = destination.public_key();
         let tx_id = TxId::new_random();
         // this can be anything, so lets generate a random private key
@@ -1276,7 +1461,8 @@ where
                 TransactionDirection::Outbound,
                 None,
                 None,
-            )?,
+            )?
+            .with_account_id(account_id),
         )?;
 
         let tx_output = output
@@ -1289,6 +1475,7 @@ where
     #[allow(clippy::too_many_lines)]
     async fn send_one_sided_or_stealth(
         &mut self,
+        tx_id: TxId,
         dest_address: TariAddress,
         amount: MicroMinotari,
         selection_criteria: UtxoSelectionCriteria,
@@ -1300,7 +1487,7 @@ where
         >,
         script: TariScript,
     ) -> Result<TxId, TransactionServiceError> {
-        let tx_id = TxId::new_random();
+        let account_id = selection_criteria.account;
 
         // Prepare sender part of the transaction
         let mut stp = self
@@ -1464,7 +1651,8 @@ where
                 TransactionDirection::Outbound,
                 None,
                 None,
-            )?,
+            )?
+            .with_account_id(account_id),
         )?;
 
         Ok(tx_id)
@@ -1472,11 +1660,13 @@ where
 
     /// Sends a one side payment transaction to a recipient
     /// # Arguments
+    /// 'tx_id': The id the transaction is stored under
     /// 'dest_pubkey': The Comms pubkey of the recipient node
     /// 'amount': The amount of Tari to send to the recipient
     /// 'fee_per_gram': The amount of fee per transaction gram to be included in transaction
     pub async fn send_one_sided_transaction(
         &mut self,
+        tx_id: TxId,
         destination: TariAddress,
         amount: MicroMinotari,
         selection_criteria: UtxoSelectionCriteria,
@@ -1498,6 +1688,7 @@ where
         }
         let dest_pubkey = destination.public_key().clone();
         self.send_one_sided_or_stealth(
+            tx_id,
             destination,
             amount,
             selection_criteria,
@@ -1528,6 +1719,7 @@ where
             JoinHandle<Result<TxId, TransactionServiceProtocolError<TxId>>>,
         >,
     ) -> Result<(TxId, BurntProof), TransactionServiceError> {
+        let account_id = selection_criteria.account;
         let tx_id = TxId::new_random();
         trace!(target: LOG_TARGET, "Burning transaction start - TxId: {}", tx_id);
         let output_features = claim_public_key
@@ -1725,7 +1917,8 @@ where
                 TransactionDirection::Outbound,
                 None,
                 None,
-            )?,
+            )?
+            .with_account_id(account_id),
         )?;
         info!(target: LOG_TARGET, "Submitted burning transaction - TxId: {}", tx_id);
 
@@ -1835,6 +2028,7 @@ where
         let script_spending_key = stealth_address_script_spending_key(&c, &dest_pubkey);
 
         self.send_one_sided_or_stealth(
+            TxId::new_random(),
             destination,
             amount,
             selection_criteria,
@@ -1847,6 +2041,44 @@ where
         .await
     }
 
+    /// Sends a one-sided stealth payment to a subaddress. The nonce is placed on the subaddress spend key, `R=r⋅D`,
+    /// and the shared secret is taken with the view key, `c=H(r⋅C)=H(a⋅R)`, so that the recipient can recover `D`
+    /// from the script with its primary key alone.
+    pub async fn send_one_sided_to_stealth_subaddress_transaction(
+        &mut self,
+        destination: TariSubaddress,
+        amount: MicroMinotari,
+        selection_criteria: UtxoSelectionCriteria,
+        output_features: OutputFeatures,
+        fee_per_gram: MicroMinotari,
+        message: String,
+        transaction_broadcast_join_handles: &mut FuturesUnordered<
+            JoinHandle<Result<TxId, TransactionServiceProtocolError<TxId>>>,
+        >,
+    ) -> Result<TxId, TransactionServiceError> {
+        if destination.network() != self.resources.wallet_identity.network {
+            return Err(TransactionServiceError::InvalidNetwork);
+        }
+
+        let nonce_private_key = PrivateKey::random(&mut OsRng);
+        let nonce_public_key = destination.spend_public_key() * &nonce_private_key;
+        let c = diffie_hellman_stealth_domain_hasher(&nonce_private_key, destination.view_public_key());
+        let script_spending_key = stealth_address_script_spending_key(&c, destination.spend_public_key());
+
+        self.send_one_sided_or_stealth(
+            TxId::new_random(),
+            destination.to_tari_address(),
+            amount,
+            selection_criteria,
+            output_features,
+            fee_per_gram,
+            message,
+            transaction_broadcast_join_handles,
+            stealth_payment_script(&nonce_public_key, &script_spending_key),
+        )
+        .await
+    }
+
     /// Accept the public reply from a recipient and apply the reply to the relevant transaction protocol
     /// # Arguments
     /// 'recipient_reply' - The public response from a recipient with data required to complete the transaction
@@ -2180,6 +2412,7 @@ where
                     None,
                     stage,
                     sender_protocol,
+                    tx.account_id,
                 );
 
                 let join_handle = tokio::spawn(protocol.execute());
@@ -2824,6 +3057,13 @@ where
         scanned_output: TransactionOutput,
     ) -> Result<TxId, TransactionServiceError> {
         let tx_id = if let Some(id) = tx_id { id } else { TxId::new_random() };
+        let invoice_address = match scanned_output.script.as_slice() {
+            [Opcode::PushPubKey(public_key)] => Some(TariAddress::new(
+                public_key.as_ref().clone(),
+                self.resources.wallet_identity.network,
+            )),
+            _ => None,
+        };
         self.db.add_utxo_import_transaction_with_status(
             tx_id,
             value,
@@ -2856,9 +3096,617 @@ where
             );
             e
         });
+        if let Some(address) = invoice_address {
+            if let Err(e) = self.apply_invoice_payment(&address, tx_id, value) {
+                warn!(
+                    target: LOG_TARGET,
+                    "Could not reconcile imported transaction {} against invoices: {:?}", tx_id, e
+                );
+            }
+        }
         Ok(tx_id)
     }
 
+    /// Create an invoice with its own one-sided payment address derived from the invoice key branch, and persist the
+    /// script for that address so that payments to it are picked up by the UTXO scanner.
+    async fn create_invoice(
+        &mut self,
+        payment_id: String,
+        amount: MicroMinotari,
+        message: String,
+        expires_at: Option<NaiveDateTime>,
+    ) -> Result<Invoice, TransactionServiceError> {
+        if payment_id.is_empty() {
+            return Err(TransactionServiceError::InvalidInvoice(
+                "Payment id must not be empty".to_string(),
+            ));
+        }
+        if amount == MicroMinotari::zero() {
+            return Err(TransactionServiceError::InvalidInvoice(
+                "Amount must be greater than zero".to_string(),
+            ));
+        }
+        if self.db.get_invoice(&payment_id).is_ok() {
+            return Err(TransactionStorageError::InvoiceAlreadyExists(payment_id).into());
+        }
+
+        let key_manager = &self.resources.transaction_key_manager_service;
+        key_manager
+            .add_new_branch(KEY_MANAGER_INVOICE_BRANCH_KEY.to_string())
+            .await?;
+        let (script_key_id, public_key) = key_manager
+            .get_next_key(KEY_MANAGER_INVOICE_BRANCH_KEY.to_string())
+            .await?;
+        let script = one_sided_payment_script(&public_key);
+        let known_script = KnownOneSidedPaymentScript {
+            script_hash: script
+                .as_hash::<Blake2b<U32>>()
+                .map_err(|e| TransactionServiceError::OutputManagerError(OutputManagerError::ScriptError(e)))?
+                .to_vec(),
+            script_key_id,
+            script,
+            input: ExecutionStack::default(),
+            script_lock_height: 0,
+        };
+        self.resources
+            .output_manager_service
+            .add_known_script(known_script)
+            .await?;
+
+        let address = TariAddress::new(public_key, self.resources.wallet_identity.network);
+        let invoice = Invoice::new(payment_id, address, amount, message, Utc::now().naive_utc(), expires_at);
+        self.db.add_invoice(invoice.clone())?;
+        info!(
+            target: LOG_TARGET,
+            "Created invoice {} for {} at {}", invoice.payment_id, invoice.amount, invoice.address
+        );
+        Ok(invoice)
+    }
+
+    fn cancel_invoice(&mut self, payment_id: &str) -> Result<(), TransactionServiceError> {
+        let mut invoice = self.db.get_invoice(payment_id)?;
+        if !invoice.status.is_open() {
+            return Err(TransactionServiceError::InvalidInvoice(format!(
+                "Invoice {} is already {}",
+                payment_id, invoice.status
+            )));
+        }
+        invoice.status = InvoiceStatus::Cancelled;
+        self.db.update_invoice(&invoice)?;
+        Ok(())
+    }
+
+    /// Record a detected one-sided payment against the open invoice, if any, that owns the receiving address.
+    fn apply_invoice_payment(
+        &mut self,
+        address: &TariAddress,
+        tx_id: TxId,
+        amount: MicroMinotari,
+    ) -> Result<(), TransactionServiceError> {
+        let mut invoice = match self.db.get_invoice_by_address(address)? {
+            Some(invoice) => invoice,
+            None => return Ok(()),
+        };
+        if !invoice.status.is_open() {
+            warn!(
+                target: LOG_TARGET,
+                "Received payment of {} (TxId: {}) for invoice {} which is {}",
+                amount,
+                tx_id,
+                invoice.payment_id,
+                invoice.status
+            );
+            return Ok(());
+        }
+
+        let event = match invoice.apply_payment(tx_id, amount, Utc::now().naive_utc()) {
+            InvoiceStatus::Paid => TransactionEvent::InvoicePaid {
+                payment_id: invoice.payment_id.clone(),
+                tx_id,
+            },
+            _ => TransactionEvent::InvoiceUnderpaid {
+                payment_id: invoice.payment_id.clone(),
+                tx_id,
+            },
+        };
+        self.db.update_invoice(&invoice)?;
+        info!(
+            target: LOG_TARGET,
+            "Invoice {} received {} of {} (TxId: {})",
+            invoice.payment_id,
+            invoice.amount_received,
+            invoice.amount,
+            tx_id
+        );
+        let _size = self.event_publisher.send(Arc::new(event)).map_err(|e| {
+            trace!(
+                target: LOG_TARGET,
+                "Error sending event, usually because there are no subscribers: {:?}",
+                e
+            );
+            e
+        });
+        Ok(())
+    }
+
+    /// Mark all open invoices whose expiry time has passed as expired.
+    fn expire_invoices(&mut self) -> Result<(), TransactionServiceError> {
+        let now = Utc::now().naive_utc();
+        for mut invoice in self.db.get_invoices()? {
+            if !invoice.status.is_open() || !invoice.is_expired_at(now) {
+                continue;
+            }
+            invoice.status = InvoiceStatus::Expired;
+            self.db.update_invoice(&invoice)?;
+            debug!(target: LOG_TARGET, "Invoice {} expired", invoice.payment_id);
+            let _size = self
+                .event_publisher
+                .send(Arc::new(TransactionEvent::InvoiceExpired(invoice.payment_id)))
+                .map_err(|e| {
+                    trace!(
+                        target: LOG_TARGET,
+                        "Error sending event, usually because there are no subscribers: {:?}",
+                        e
+                    );
+                    e
+                });
+        }
+        Ok(())
+    }
+
+    /// Pay the recipients of a batch payout with as few multi-output one-sided transactions as the maximum
+    /// transaction weight allows. Rows that were already paid by a transaction that is still valid are skipped, so
+    /// a batch can be submitted again to retry the rows that failed or were interrupted.
+    async fn send_batch_payout(
+        &mut self,
+        batch_id: Option<String>,
+        recipients: Vec<BatchPayoutRecipient>,
+        selection_criteria: UtxoSelectionCriteria,
+        fee_per_gram: MicroMinotari,
+        transaction_broadcast_join_handles: &mut FuturesUnordered<
+            JoinHandle<Result<TxId, TransactionServiceProtocolError<TxId>>>,
+        >,
+    ) -> Result<Vec<BatchPayoutRow>, TransactionServiceError> {
+        self.validate_batch_payout_recipients(&recipients)?;
+        let batch_id = batch_id.unwrap_or_else(|| batch_id_from_recipients(&recipients));
+        let rows = self.prepare_batch_payout_rows(&batch_id, recipients)?;
+        let mut unpaid_rows = Vec::with_capacity(rows.len());
+        for mut row in rows {
+            // The transaction id is stored on the rows before the transaction is submitted, so a row is paid whenever
+            // its transaction exists, even if the wallet stopped before the row was marked as sent
+            let is_paid = match row.tx_id {
+                Some(tx_id) => match self.db.get_completed_transaction(tx_id) {
+                    Ok(_) => {
+                        if row.status != BatchPayoutStatus::Sent {
+                            row.mark_sent(tx_id, Utc::now().naive_utc());
+                            self.db.update_batch_payout_row(&row)?;
+                        }
+                        true
+                    },
+                    // The transaction was cancelled or never stored, so the row has to be paid again
+                    Err(TransactionStorageError::ValueNotFound(_)) => false,
+                    Err(e) => return Err(e.into()),
+                },
+                None => false,
+            };
+            if !is_paid {
+                unpaid_rows.push(row);
+            }
+        }
+        if unpaid_rows.is_empty() {
+            info!(target: LOG_TARGET, "All rows of batch payout {} have been paid", batch_id);
+            return Ok(self.db.get_batch_payout(&batch_id)?);
+        }
+
+        // All one-sided payment scripts have the same size, so the first recipient is representative for the batch
+        let tip_height = self.last_seen_tip_height.unwrap_or(0);
+        let consensus_constants = self.consensus_manager.consensus_constants(tip_height);
+        let weighting = consensus_constants.transaction_weight_params();
+        let features_and_scripts_size = OutputFeatures::default()
+            .get_serialized_size()
+            .map_err(|e| TransactionServiceError::ServiceError(e.to_string()))? +
+            Covenant::default()
+                .get_serialized_size()
+                .map_err(|e| TransactionServiceError::ServiceError(e.to_string()))? +
+            one_sided_payment_script(unpaid_rows[0].recipient.address.public_key())
+                .get_serialized_size()
+                .map_err(|e| TransactionServiceError::ServiceError(e.to_string()))?;
+        let max_outputs = max_outputs_per_transaction(
+            weighting,
+            consensus_constants.max_block_transaction_weight(),
+            weighting.round_up_features_and_scripts_size(features_and_scripts_size),
+        );
+
+        info!(
+            target: LOG_TARGET,
+            "Sending batch payout {} to {} recipients in at most {} outputs per transaction",
+            batch_id,
+            unpaid_rows.len(),
+            max_outputs
+        );
+        for chunk in unpaid_rows.chunks(max_outputs) {
+            self.send_batch_payout_transaction(
+                &batch_id,
+                chunk.to_vec(),
+                selection_criteria.clone(),
+                fee_per_gram,
+                transaction_broadcast_join_handles,
+            )
+            .await?;
+        }
+
+        Ok(self.db.get_batch_payout(&batch_id)?)
+    }
+
+    /// Check every recipient of a batch payout up front so that nothing is sent when any of the rows is invalid.
+    fn validate_batch_payout_recipients(
+        &self,
+        recipients: &[BatchPayoutRecipient],
+    ) -> Result<(), TransactionServiceError> {
+        if recipients.is_empty() {
+            return Err(TransactionServiceError::InvalidBatchPayout(
+                "A batch payout requires at least one recipient".to_string(),
+            ));
+        }
+        let errors = recipients
+            .iter()
+            .enumerate()
+            .filter_map(|(index, recipient)| {
+                let error = if recipient.address.network() != self.resources.wallet_identity.network {
+                    "address is for a different network"
+                } else if recipient.address.public_key() == self.resources.wallet_identity.node_identity.public_key() {
+                    "one-sided spend-to-self transactions are not supported"
+                } else if recipient.amount == MicroMinotari::zero() {
+                    "amount must be greater than zero"
+                } else {
+                    return None;
+                };
+                Some(format!("row {}: {}", index + 1, error))
+            })
+            .collect::<Vec<_>>();
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(TransactionServiceError::InvalidBatchPayout(errors.join(", ")))
+        }
+    }
+
+    /// Load the rows of an existing batch, which must contain exactly the same recipients, or store the rows of a new
+    /// batch.
+    fn prepare_batch_payout_rows(
+        &mut self,
+        batch_id: &str,
+        recipients: Vec<BatchPayoutRecipient>,
+    ) -> Result<Vec<BatchPayoutRow>, TransactionServiceError> {
+        let rows = self.db.get_batch_payout(batch_id)?;
+        if rows.is_empty() {
+            let now = Utc::now().naive_utc();
+            let rows = recipients
+                .into_iter()
+                .enumerate()
+                .map(|(index, recipient)| BatchPayoutRow::new(batch_id.to_string(), index as u64, recipient, now))
+                .collect::<Vec<_>>();
+            self.db.add_batch_payout(rows.clone())?;
+            return Ok(rows);
+        }
+        if rows.len() != recipients.len() || rows.iter().zip(recipients.iter()).any(|(row, r)| row.recipient != *r) {
+            return Err(TransactionServiceError::InvalidBatchPayout(format!(
+                "Batch payout {} already exists with different recipients",
+                batch_id
+            )));
+        }
+        Ok(rows)
+    }
+
+    /// Send a single transaction paying all of the given rows and record the outcome against each row. A failure is
+    /// recorded on the rows rather than returned, so that the remaining transactions of the batch are still sent.
+    async fn send_batch_payout_transaction(
+        &mut self,
+        batch_id: &str,
+        mut rows: Vec<BatchPayoutRow>,
+        selection_criteria: UtxoSelectionCriteria,
+        fee_per_gram: MicroMinotari,
+        transaction_broadcast_join_handles: &mut FuturesUnordered<
+            JoinHandle<Result<TxId, TransactionServiceProtocolError<TxId>>>,
+        >,
+    ) -> Result<(), TransactionServiceError> {
+        let account_id = selection_criteria.account;
+        let tx_id = TxId::new_random();
+        // Record the transaction against the rows before it exists, so that a batch that is interrupted after the
+        // transaction was submitted is never paid a second time
+        let now = Utc::now().naive_utc();
+        for row in &mut rows {
+            row.mark_in_flight(tx_id, now);
+            self.db.update_batch_payout_row(row)?;
+        }
+        let amount = rows.iter().map(|row| row.recipient.amount).sum();
+        let recipients = rows
+            .iter()
+            .map(|row| (row.recipient.address.clone(), row.recipient.amount))
+            .collect();
+        let result = self
+            .resources
+            .output_manager_service
+            .create_one_sided_batch_transaction(tx_id, recipients, selection_criteria, fee_per_gram)
+            .await;
+        let now = Utc::now().naive_utc();
+        let (fee, transaction) = match result {
+            Ok(result) => result,
+            Err(e) => {
+                warn!(
+                    target: LOG_TARGET,
+                    "Could not create transaction for {} rows of batch payout {}: {}",
+                    rows.len(),
+                    batch_id,
+                    e
+                );
+                for row in &mut rows {
+                    row.mark_failed(e.to_string(), now);
+                    self.db.update_batch_payout_row(row)?;
+                }
+                return Ok(());
+            },
+        };
+
+        // A transaction can only record a single destination. The recipients of a transaction that pays several rows
+        // are recorded against the rows instead.
+        let destination = match rows.as_slice() {
+            [row] => row.recipient.address.clone(),
+            _ => self.resources.wallet_identity.address.clone(),
+        };
+        let result = CompletedTransaction::new(
+            tx_id,
+            self.resources.wallet_identity.address.clone(),
+            destination,
+            amount,
+            fee,
+            transaction,
+            TransactionStatus::Completed,
+            format!("Batch payout {} ({} recipients)", batch_id, rows.len()),
+            now,
+            TransactionDirection::Outbound,
+            None,
+            None,
+        )
+        .map_err(TransactionServiceError::from)
+        .and_then(|transaction| {
+            self.submit_transaction(
+                transaction_broadcast_join_handles,
+                transaction.with_account_id(account_id),
+            )
+        });
+        if let Err(e) = result {
+            for row in &mut rows {
+                row.mark_failed(e.to_string(), now);
+                self.db.update_batch_payout_row(row)?;
+            }
+            return Err(e);
+        }
+        for row in &mut rows {
+            row.mark_sent(tx_id, now);
+            self.db.update_batch_payout_row(row)?;
+        }
+        info!(
+            target: LOG_TARGET,
+            "Sent {} rows of batch payout {} in transaction {}",
+            rows.len(),
+            batch_id,
+            tx_id
+        );
+
+        let _size = self
+            .event_publisher
+            .send(Arc::new(TransactionEvent::TransactionCompletedImmediately(tx_id)))
+            .map_err(|e| {
+                trace!(
+                    target: LOG_TARGET,
+                    "Error sending event, usually because there are no subscribers: {:?}",
+                    e
+                );
+                e
+            });
+        Ok(())
+    }
+
+    fn create_scheduled_payment(
+        &mut self,
+        mut payment: ScheduledPayment,
+    ) -> Result<ScheduledPayment, TransactionServiceError> {
+        let error = if payment.schedule_id.trim().is_empty() {
+            Some("the schedule id must not be empty")
+        } else if payment.address.network() != self.resources.wallet_identity.network {
+            Some("the address is for a different network")
+        } else if payment.address.public_key() == self.resources.wallet_identity.node_identity.public_key() {
+            Some("one-sided spend-to-self transactions are not supported")
+        } else if payment.amount == MicroMinotari::zero() {
+            Some("the amount must be greater than zero")
+        } else if payment.max_payments == Some(0) {
+            Some("the maximum number of payments must be greater than zero")
+        } else if payment.end_at.map_or(false, |end_at| end_at < payment.start_at) {
+            Some("the end date is before the start date")
+        } else {
+            None
+        };
+        if let Some(error) = error {
+            return Err(TransactionServiceError::InvalidScheduledPayment(error.to_string()));
+        }
+        payment.status = ScheduledPaymentStatus::Active;
+        self.db.add_scheduled_payment(payment.clone())?;
+        info!(
+            target: LOG_TARGET,
+            "Scheduled payment {} of {} {} starting at {}",
+            payment.schedule_id,
+            payment.amount,
+            payment.cadence,
+            payment.start_at
+        );
+        Ok(payment)
+    }
+
+    fn set_scheduled_payment_status(
+        &mut self,
+        schedule_id: &str,
+        status: ScheduledPaymentStatus,
+    ) -> Result<ScheduledPayment, TransactionServiceError> {
+        let mut payment = self.db.get_scheduled_payment(schedule_id)?;
+        if payment.status.is_finished() || payment.status == status || status == ScheduledPaymentStatus::Completed {
+            return Err(TransactionServiceError::InvalidScheduledPayment(format!(
+                "Scheduled payment {} is {} and cannot be changed to {}",
+                schedule_id, payment.status, status
+            )));
+        }
+        let now = Utc::now().naive_utc();
+        if status == ScheduledPaymentStatus::Active {
+            payment.resume(now);
+        } else {
+            payment.status = status;
+            payment.updated_at = now;
+        }
+        self.db.update_scheduled_payment(&payment)?;
+        if payment.status == ScheduledPaymentStatus::Completed {
+            self.publish_event(TransactionEvent::ScheduledPaymentCompleted(payment.schedule_id.clone()));
+        }
+        Ok(payment)
+    }
+
+    /// Reconcile the scheduled payments that were being sent when the wallet stopped. A payment whose transaction
+    /// was stored is recorded as paid, otherwise the occurrence is attempted again.
+    fn reconcile_in_flight_scheduled_payments(&mut self) -> Result<(), TransactionServiceError> {
+        for mut payment in self.db.get_scheduled_payments()? {
+            let tx_id = match payment.pending_tx_id {
+                Some(tx_id) => tx_id,
+                None => continue,
+            };
+            let now = Utc::now().naive_utc();
+            match self.db.get_completed_transaction(tx_id) {
+                Ok(_) => {
+                    info!(
+                        target: LOG_TARGET,
+                        "Scheduled payment {} was paid in transaction {} before the wallet stopped",
+                        payment.schedule_id,
+                        tx_id
+                    );
+                    payment.record_payment(tx_id, now);
+                },
+                Err(TransactionStorageError::ValueNotFound(_)) => {
+                    warn!(
+                        target: LOG_TARGET,
+                        "Scheduled payment {} was interrupted before transaction {} was stored, it will be sent again",
+                        payment.schedule_id,
+                        tx_id
+                    );
+                    payment.clear_in_flight(now);
+                },
+                Err(e) => return Err(e.into()),
+            }
+            self.db.update_scheduled_payment(&payment)?;
+            if payment.status == ScheduledPaymentStatus::Completed {
+                self.publish_event(TransactionEvent::ScheduledPaymentCompleted(payment.schedule_id));
+            }
+        }
+        Ok(())
+    }
+
+    /// Send every scheduled payment that is due. An occurrence that was missed while the wallet was offline is paid
+    /// once, after which the schedule moves on to its next future occurrence. Only failures that may succeed later are
+    /// retried.
+    async fn send_due_scheduled_payments(
+        &mut self,
+        transaction_broadcast_join_handles: &mut FuturesUnordered<
+            JoinHandle<Result<TxId, TransactionServiceProtocolError<TxId>>>,
+        >,
+    ) -> Result<(), TransactionServiceError> {
+        for mut payment in self.db.get_due_scheduled_payments(Utc::now().naive_utc())? {
+            if payment.pending_tx_id.is_some() {
+                // Only possible if reconciling on startup failed, the payment must not be sent a second time
+                warn!(
+                    target: LOG_TARGET,
+                    "Scheduled payment {} has a payment in flight, skipping", payment.schedule_id
+                );
+                continue;
+            }
+            let tx_id = TxId::new_random();
+            payment.mark_in_flight(tx_id, Utc::now().naive_utc());
+            self.db.update_scheduled_payment(&payment)?;
+            let result = self
+                .send_one_sided_transaction(
+                    tx_id,
+                    payment.address.clone(),
+                    payment.amount,
+                    UtxoSelectionCriteria::default().for_account(payment.account_id),
+                    OutputFeatures::default(),
+                    payment.fee_per_gram,
+                    payment.message.clone(),
+                    transaction_broadcast_join_handles,
+                )
+                .await;
+            let now = Utc::now().naive_utc();
+            let event = match result {
+                Ok(tx_id) => {
+                    payment.record_payment(tx_id, now);
+                    info!(
+                        target: LOG_TARGET,
+                        "Sent payment {} of schedule {} in transaction {}",
+                        payment.payments_made,
+                        payment.schedule_id,
+                        tx_id
+                    );
+                    TransactionEvent::ScheduledPaymentSent {
+                        schedule_id: payment.schedule_id.clone(),
+                        tx_id,
+                    }
+                },
+                Err(e) if e.is_transient() => {
+                    let will_retry = payment.record_failure(
+                        e.to_string(),
+                        self.resources.config.scheduled_payment_max_retries,
+                        self.resources.config.scheduled_payment_retry_delay,
+                        now,
+                    );
+                    warn!(
+                        target: LOG_TARGET,
+                        "Scheduled payment {} failed (will retry: {}): {}", payment.schedule_id, will_retry, e
+                    );
+                    TransactionEvent::ScheduledPaymentFailed {
+                        schedule_id: payment.schedule_id.clone(),
+                        error: e.to_string(),
+                        will_retry,
+                    }
+                },
+                Err(e) => {
+                    payment.record_rejection(e.to_string(), now);
+                    warn!(
+                        target: LOG_TARGET,
+                        "Scheduled payment {} failed and will not be retried: {}", payment.schedule_id, e
+                    );
+                    TransactionEvent::ScheduledPaymentFailed {
+                        schedule_id: payment.schedule_id.clone(),
+                        error: e.to_string(),
+                        will_retry: false,
+                    }
+                },
+            };
+            self.db.update_scheduled_payment(&payment)?;
+            self.publish_event(event);
+            if payment.status == ScheduledPaymentStatus::Completed {
+                info!(target: LOG_TARGET, "Scheduled payment {} completed", payment.schedule_id);
+                self.publish_event(TransactionEvent::ScheduledPaymentCompleted(payment.schedule_id));
+            }
+        }
+        Ok(())
+    }
+
+    fn publish_event(&self, event: TransactionEvent) {
+        let _size = self.event_publisher.send(Arc::new(event)).map_err(|e| {
+            trace!(
+                target: LOG_TARGET,
+                "Error sending event, usually because there are no subscribers: {:?}",
+                e
+            );
+            e
+        });
+    }
+
     /// Submit a completed transaction to the Transaction Manager
     fn submit_transaction(
         &mut self,
@@ -2918,6 +3766,37 @@ where
         Ok(())
     }
 
+    fn submit_consolidation_transaction(
+        &mut self,
+        transaction_broadcast_join_handles: &mut FuturesUnordered<
+            JoinHandle<Result<TxId, TransactionServiceProtocolError<TxId>>>,
+        >,
+        tx_id: TxId,
+        tx: Transaction,
+        amount: MicroMinotari,
+        account_id: AccountId,
+    ) -> Result<(), TransactionServiceError> {
+        let fee = tx.body.get_total_fee()?;
+        self.submit_transaction(
+            transaction_broadcast_join_handles,
+            CompletedTransaction::new(
+                tx_id,
+                self.resources.wallet_identity.address.clone(),
+                self.resources.wallet_identity.address.clone(),
+                amount,
+                fee,
+                tx,
+                TransactionStatus::Completed,
+                "Output consolidation".to_string(),
+                Utc::now().naive_utc(),
+                TransactionDirection::Inbound,
+                None,
+                None,
+            )?
+            .with_account_id(account_id),
+        )
+    }
+
     /// Check if a Recovery Status is currently stored in the databse, this indicates that a wallet recovery is in
     /// progress
     fn check_recovery_status(&self) -> Result<(), TransactionServiceError> {
@@ -3010,4 +3889,30 @@ mod tests {
             assert_eq!(scanning_key, receiver_spending_key);
         }
     }
+
+    #[test]
+    fn test_stealth_subaddresses() {
+        // recipient's primary key and a subaddress with spend key `D=d⋅G` and view key `C=a⋅D`
+        let (a, _big_a) = PublicKey::random_keypair(&mut OsRng);
+        let (_d, big_d) = PublicKey::random_keypair(&mut OsRng);
+        let big_c = &big_d * &a;
+
+        // Sender places the nonce on the spend key, `R=r⋅D`, and derives `c=H(r⋅C)`
+        let r = PrivateKey::random(&mut OsRng);
+        let big_r = &big_d * &r;
+        let c = diffie_hellman_stealth_domain_hasher(&r, &big_c);
+        let sender_spending_key = stealth_address_script_spending_key(&c, &big_d);
+        let script = stealth_payment_script(&big_r, &sender_spending_key);
+
+        // The recipient derives `c=H(a⋅R)` and recovers the subaddress spend key from the script key
+        if let [Opcode::PushPubKey(big_r), Opcode::Drop, Opcode::PushPubKey(provided_spending_key)] = script.as_slice()
+        {
+            let c = diffie_hellman_stealth_domain_hasher(&a, big_r);
+            let offset = RistrettoSecretKey::from_uniform_bytes(c.as_ref()).unwrap();
+            let recovered_spend_key = provided_spending_key.as_ref() - &PublicKey::from_secret_key(&offset);
+            assert_eq!(recovered_spend_key, big_d);
+        } else {
+            panic!("Unexpected stealth script");
+        }
+    }
 }
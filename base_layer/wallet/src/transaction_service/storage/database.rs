@@ -40,17 +40,24 @@ use tari_core::transactions::{
     transaction_components::{Transaction, TransactionOutput},
 };
 
-use crate::transaction_service::{
-    error::TransactionStorageError,
-    storage::{
-        models::{
-            CompletedTransaction,
-            InboundTransaction,
-            OutboundTransaction,
-            TxCancellationReason,
-            WalletTransaction,
+use crate::{
+    accounts::AccountId,
+    transaction_service::{
+        error::TransactionStorageError,
+        storage::{
+            models::{
+                BatchPayoutRow,
+                CompletedTransaction,
+                InboundTransaction,
+                Invoice,
+                OutboundTransaction,
+                ScheduledPayment,
+                TransactionStatusChange,
+                TxCancellationReason,
+                WalletTransaction,
+            },
+            sqlite_db::{InboundTransactionSenderInfo, UnconfirmedTransactionInfo},
         },
-        sqlite_db::{InboundTransactionSenderInfo, UnconfirmedTransactionInfo},
     },
 };
 
@@ -142,6 +149,19 @@ pub trait TransactionBackend: Send + Sync + Clone {
         &self,
     ) -> Result<Vec<InboundTransactionSenderInfo>, TransactionStorageError>;
     fn fetch_imported_transactions(&self) -> Result<Vec<CompletedTransaction>, TransactionStorageError>;
+    /// Retrieve the non-cancelled completed transactions that belong to the given account
+    fn fetch_account_completed_transactions(
+        &self,
+        account: AccountId,
+    ) -> Result<Vec<CompletedTransaction>, TransactionStorageError>;
+    /// Retrieve a page of the non-cancelled completed transactions, newest first, optionally only those of the given
+    /// account, along with the number of transactions in all the pages
+    fn fetch_completed_transactions_page(
+        &self,
+        account: Option<AccountId>,
+        offset: u64,
+        limit: u64,
+    ) -> Result<(Vec<CompletedTransaction>, u64), TransactionStorageError>;
     fn fetch_unconfirmed_detected_transactions(&self) -> Result<Vec<CompletedTransaction>, TransactionStorageError>;
     fn fetch_confirmed_detected_transactions_from_height(
         &self,
@@ -151,6 +171,36 @@ pub trait TransactionBackend: Send + Sync + Clone {
         &self,
         height: u64,
     ) -> Result<Vec<CompletedTransaction>, TransactionStorageError>;
+    /// Insert a new invoice, the payment id and address of the invoice must be unique
+    fn insert_invoice(&self, invoice: Invoice) -> Result<(), TransactionStorageError>;
+    fn fetch_invoice(&self, payment_id: &str) -> Result<Option<Invoice>, TransactionStorageError>;
+    fn fetch_invoices(&self) -> Result<Vec<Invoice>, TransactionStorageError>;
+    /// Retrieve the invoice that is paid to the given address
+    fn fetch_invoice_by_address(&self, address: &TariAddress) -> Result<Option<Invoice>, TransactionStorageError>;
+    /// Update the payment state (amount received, status, transaction and timestamps) of an existing invoice
+    fn update_invoice(&self, invoice: &Invoice) -> Result<(), TransactionStorageError>;
+    /// Retrieve the recorded status changes of a transaction, oldest first
+    fn fetch_transaction_status_history(
+        &self,
+        tx_id: TxId,
+    ) -> Result<Vec<TransactionStatusChange>, TransactionStorageError>;
+    /// Insert all the rows of a new batch payout, the batch id must not already be in use
+    fn insert_batch_payout(&self, rows: Vec<BatchPayoutRow>) -> Result<(), TransactionStorageError>;
+    /// Retrieve the rows of a batch payout ordered by their index
+    fn fetch_batch_payout(&self, batch_id: &str) -> Result<Vec<BatchPayoutRow>, TransactionStorageError>;
+    /// Update the status, transaction and error of an existing batch payout row
+    fn update_batch_payout_row(&self, row: &BatchPayoutRow) -> Result<(), TransactionStorageError>;
+    /// Insert a new scheduled payment, the schedule id must be unique
+    fn insert_scheduled_payment(&self, payment: ScheduledPayment) -> Result<(), TransactionStorageError>;
+    fn fetch_scheduled_payment(&self, schedule_id: &str) -> Result<Option<ScheduledPayment>, TransactionStorageError>;
+    fn fetch_scheduled_payments(&self) -> Result<Vec<ScheduledPayment>, TransactionStorageError>;
+    /// Retrieve the active scheduled payments whose next run is at or before the given time, earliest first
+    fn fetch_due_scheduled_payments(
+        &self,
+        timestamp: NaiveDateTime,
+    ) -> Result<Vec<ScheduledPayment>, TransactionStorageError>;
+    /// Update the progress (occurrence, next run, payments made, retries and status) of an existing scheduled payment
+    fn update_scheduled_payment(&self, payment: &ScheduledPayment) -> Result<(), TransactionStorageError>;
 }
 
 #[derive(Clone, PartialEq)]
@@ -444,6 +494,89 @@ where T: TransactionBackend + 'static
         Ok(t)
     }
 
+    pub fn get_account_completed_transactions(
+        &self,
+        account: AccountId,
+    ) -> Result<HashMap<TxId, CompletedTransaction>, TransactionStorageError> {
+        let t = self.db.fetch_account_completed_transactions(account)?;
+        Ok(t.into_iter().map(|tx| (tx.tx_id, tx)).collect())
+    }
+
+    pub fn get_completed_transactions_page(
+        &self,
+        account: Option<AccountId>,
+        offset: u64,
+        limit: u64,
+    ) -> Result<(Vec<CompletedTransaction>, u64), TransactionStorageError> {
+        self.db.fetch_completed_transactions_page(account, offset, limit)
+    }
+
+    pub fn add_invoice(&self, invoice: Invoice) -> Result<(), TransactionStorageError> {
+        self.db.insert_invoice(invoice)
+    }
+
+    pub fn get_invoice(&self, payment_id: &str) -> Result<Invoice, TransactionStorageError> {
+        self.db
+            .fetch_invoice(payment_id)?
+            .ok_or_else(|| TransactionStorageError::InvoiceNotFound(payment_id.to_string()))
+    }
+
+    pub fn get_invoices(&self) -> Result<Vec<Invoice>, TransactionStorageError> {
+        self.db.fetch_invoices()
+    }
+
+    pub fn get_invoice_by_address(&self, address: &TariAddress) -> Result<Option<Invoice>, TransactionStorageError> {
+        self.db.fetch_invoice_by_address(address)
+    }
+
+    pub fn update_invoice(&self, invoice: &Invoice) -> Result<(), TransactionStorageError> {
+        self.db.update_invoice(invoice)
+    }
+
+    pub fn get_transaction_status_history(
+        &self,
+        tx_id: TxId,
+    ) -> Result<Vec<TransactionStatusChange>, TransactionStorageError> {
+        self.db.fetch_transaction_status_history(tx_id)
+    }
+
+    pub fn add_batch_payout(&self, rows: Vec<BatchPayoutRow>) -> Result<(), TransactionStorageError> {
+        self.db.insert_batch_payout(rows)
+    }
+
+    pub fn get_batch_payout(&self, batch_id: &str) -> Result<Vec<BatchPayoutRow>, TransactionStorageError> {
+        self.db.fetch_batch_payout(batch_id)
+    }
+
+    pub fn update_batch_payout_row(&self, row: &BatchPayoutRow) -> Result<(), TransactionStorageError> {
+        self.db.update_batch_payout_row(row)
+    }
+
+    pub fn add_scheduled_payment(&self, payment: ScheduledPayment) -> Result<(), TransactionStorageError> {
+        self.db.insert_scheduled_payment(payment)
+    }
+
+    pub fn get_scheduled_payment(&self, schedule_id: &str) -> Result<ScheduledPayment, TransactionStorageError> {
+        self.db
+            .fetch_scheduled_payment(schedule_id)?
+            .ok_or_else(|| TransactionStorageError::ScheduledPaymentNotFound(schedule_id.to_string()))
+    }
+
+    pub fn get_scheduled_payments(&self) -> Result<Vec<ScheduledPayment>, TransactionStorageError> {
+        self.db.fetch_scheduled_payments()
+    }
+
+    pub fn get_due_scheduled_payments(
+        &self,
+        timestamp: NaiveDateTime,
+    ) -> Result<Vec<ScheduledPayment>, TransactionStorageError> {
+        self.db.fetch_due_scheduled_payments(timestamp)
+    }
+
+    pub fn update_scheduled_payment(&self, payment: &ScheduledPayment) -> Result<(), TransactionStorageError> {
+        self.db.update_scheduled_payment(payment)
+    }
+
     pub fn get_unconfirmed_detected_transactions(&self) -> Result<Vec<CompletedTransaction>, TransactionStorageError> {
         let t = self.db.fetch_unconfirmed_detected_transactions()?;
         Ok(t)
@@ -39,7 +39,15 @@ use tari_core::transactions::{
     SenderTransactionProtocol,
 };
 
-use crate::transaction_service::error::TransactionStorageError;
+use crate::{
+    accounts::AccountId,
+    transaction_service::{
+        batch_payout::BatchPayoutRecipient,
+        error::TransactionStorageError,
+        payment_request::{PaymentRequest, PaymentType},
+        scheduled_payment::PaymentCadence,
+    },
+};
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct InboundTransaction {
@@ -54,6 +62,8 @@ pub struct InboundTransaction {
     pub direct_send_success: bool,
     pub send_count: u32,
     pub last_send_timestamp: Option<NaiveDateTime>,
+    #[serde(default)]
+    pub account_id: AccountId,
 }
 
 impl InboundTransaction {
@@ -78,8 +88,15 @@ impl InboundTransaction {
             direct_send_success: false,
             send_count: 0,
             last_send_timestamp: None,
+            account_id: AccountId::DEFAULT,
         }
     }
+
+    /// Assign this transaction to the given account
+    pub fn with_account_id(mut self, account_id: AccountId) -> Self {
+        self.account_id = account_id;
+        self
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -96,6 +113,8 @@ pub struct OutboundTransaction {
     pub direct_send_success: bool,
     pub send_count: u32,
     pub last_send_timestamp: Option<NaiveDateTime>,
+    #[serde(default)]
+    pub account_id: AccountId,
 }
 
 impl OutboundTransaction {
@@ -123,8 +142,15 @@ impl OutboundTransaction {
             direct_send_success,
             send_count: 0,
             last_send_timestamp: None,
+            account_id: AccountId::DEFAULT,
         }
     }
+
+    /// Assign this transaction to the given account
+    pub fn with_account_id(mut self, account_id: AccountId) -> Self {
+        self.account_id = account_id;
+        self
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -147,6 +173,8 @@ pub struct CompletedTransaction {
     pub mined_height: Option<u64>,
     pub mined_in_block: Option<BlockHash>,
     pub mined_timestamp: Option<NaiveDateTime>,
+    #[serde(default)]
+    pub account_id: AccountId,
 }
 
 impl CompletedTransaction {
@@ -191,8 +219,15 @@ impl CompletedTransaction {
             mined_height,
             mined_in_block: None,
             mined_timestamp,
+            account_id: AccountId::DEFAULT,
         })
     }
+
+    /// Assign this transaction to the given account
+    pub fn with_account_id(mut self, account_id: AccountId) -> Self {
+        self.account_id = account_id;
+        self
+    }
 }
 
 impl From<CompletedTransaction> for InboundTransaction {
@@ -209,6 +244,7 @@ impl From<CompletedTransaction> for InboundTransaction {
             direct_send_success: false,
             send_count: 0,
             last_send_timestamp: None,
+            account_id: ct.account_id,
         }
     }
 }
@@ -228,6 +264,7 @@ impl From<CompletedTransaction> for OutboundTransaction {
             direct_send_success: false,
             send_count: 0,
             last_send_timestamp: None,
+            account_id: ct.account_id,
         }
     }
 }
@@ -270,6 +307,7 @@ impl From<OutboundTransaction> for CompletedTransaction {
             mined_height: None,
             mined_in_block: None,
             mined_timestamp: None,
+            account_id: tx.account_id,
         }
     }
 }
@@ -299,6 +337,7 @@ impl From<InboundTransaction> for CompletedTransaction {
             mined_height: None,
             mined_in_block: None,
             mined_timestamp: None,
+            account_id: tx.account_id,
         }
     }
 }
@@ -365,3 +404,418 @@ impl Display for TxCancellationReason {
         fmt.write_str(response)
     }
 }
+
+/// The lifecycle of an invoice. An invoice is `Open` until payments to its address add up to the requested amount, it
+/// expires or it is cancelled.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum InvoiceStatus {
+    Open,          // 0
+    PartiallyPaid, // 1
+    Paid,          // 2
+    Expired,       // 3
+    Cancelled,     // 4
+}
+
+impl InvoiceStatus {
+    /// Returns true if payments can still be credited to the invoice
+    pub fn is_open(self) -> bool {
+        matches!(self, InvoiceStatus::Open | InvoiceStatus::PartiallyPaid)
+    }
+}
+
+impl TryFrom<i32> for InvoiceStatus {
+    type Error = TransactionConversionError;
+
+    fn try_from(value: i32) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(InvoiceStatus::Open),
+            1 => Ok(InvoiceStatus::PartiallyPaid),
+            2 => Ok(InvoiceStatus::Paid),
+            3 => Ok(InvoiceStatus::Expired),
+            4 => Ok(InvoiceStatus::Cancelled),
+            code => Err(TransactionConversionError { code }),
+        }
+    }
+}
+
+impl Display for InvoiceStatus {
+    fn fmt(&self, fmt: &mut Formatter<'_>) -> Result<(), Error> {
+        #[allow(clippy::enum_glob_use)]
+        use InvoiceStatus::*;
+        let response = match self {
+            Open => "Open",
+            PartiallyPaid => "Partially Paid",
+            Paid => "Paid",
+            Expired => "Expired",
+            Cancelled => "Cancelled",
+        };
+        fmt.write_str(response)
+    }
+}
+
+/// A request for payment of a specific amount. Every invoice is paid to its own address, derived from the wallet's
+/// invoice key branch, so received one-sided payments can be matched to the invoice without relying on the message.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Invoice {
+    pub payment_id: String,
+    pub address: TariAddress,
+    pub amount: MicroMinotari,
+    pub amount_received: MicroMinotari,
+    pub message: String,
+    pub status: InvoiceStatus,
+    /// The last transaction that paid towards this invoice
+    pub tx_id: Option<TxId>,
+    pub created_at: NaiveDateTime,
+    pub expires_at: Option<NaiveDateTime>,
+    pub paid_at: Option<NaiveDateTime>,
+}
+
+impl Invoice {
+    pub fn new(
+        payment_id: String,
+        address: TariAddress,
+        amount: MicroMinotari,
+        message: String,
+        created_at: NaiveDateTime,
+        expires_at: Option<NaiveDateTime>,
+    ) -> Self {
+        Self {
+            payment_id,
+            address,
+            amount,
+            amount_received: MicroMinotari::zero(),
+            message,
+            status: InvoiceStatus::Open,
+            tx_id: None,
+            created_at,
+            expires_at,
+            paid_at: None,
+        }
+    }
+
+    /// Credits a received payment to the invoice and updates its status. Returns the new status.
+    pub fn apply_payment(&mut self, tx_id: TxId, amount: MicroMinotari, timestamp: NaiveDateTime) -> InvoiceStatus {
+        self.amount_received = self.amount_received.saturating_add(amount);
+        self.tx_id = Some(tx_id);
+        if self.amount_received >= self.amount {
+            self.status = InvoiceStatus::Paid;
+            self.paid_at = Some(timestamp);
+        } else {
+            self.status = InvoiceStatus::PartiallyPaid;
+        }
+        self.status
+    }
+
+    pub fn is_expired_at(&self, timestamp: NaiveDateTime) -> bool {
+        self.expires_at.map_or(false, |expires_at| expires_at <= timestamp)
+    }
+
+    pub fn to_payment_request(&self) -> PaymentRequest {
+        PaymentRequest {
+            address: self.address.clone(),
+            // Only payments to the invoice's plain one-sided script are matched to the invoice
+            payment_type: PaymentType::OneSided,
+            amount: self.amount,
+            payment_id: self.payment_id.clone(),
+            message: self.message.clone(),
+            expires_at: self.expires_at.and_then(|t| u64::try_from(t.timestamp()).ok()),
+        }
+    }
+}
+
+/// A single entry in the status history of a transaction, recorded by the database whenever a transaction is created,
+/// changes status or is cancelled
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TransactionStatusChange {
+    pub tx_id: TxId,
+    pub status: TransactionStatus,
+    pub cancelled: Option<TxCancellationReason>,
+    pub changed_at: NaiveDateTime,
+}
+
+/// The state of a single row of a batch payout. A `Sending` row already holds the id of the transaction that pays it,
+/// which may or may not have been stored before the wallet stopped.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum BatchPayoutStatus {
+    Pending, // 0
+    Sent,    // 1
+    Failed,  // 2
+    Sending, // 3
+}
+
+impl TryFrom<i32> for BatchPayoutStatus {
+    type Error = TransactionConversionError;
+
+    fn try_from(value: i32) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(BatchPayoutStatus::Pending),
+            1 => Ok(BatchPayoutStatus::Sent),
+            2 => Ok(BatchPayoutStatus::Failed),
+            3 => Ok(BatchPayoutStatus::Sending),
+            code => Err(TransactionConversionError { code }),
+        }
+    }
+}
+
+impl Display for BatchPayoutStatus {
+    fn fmt(&self, fmt: &mut Formatter<'_>) -> Result<(), Error> {
+        let response = match self {
+            BatchPayoutStatus::Pending => "Pending",
+            BatchPayoutStatus::Sent => "Sent",
+            BatchPayoutStatus::Failed => "Failed",
+            BatchPayoutStatus::Sending => "Sending",
+        };
+        fmt.write_str(response)
+    }
+}
+
+/// A payment to a single recipient of a batch payout, together with the transaction that paid it once it was sent
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct BatchPayoutRow {
+    pub batch_id: String,
+    /// The position of the row in the payout file, starting at 0
+    pub index: u64,
+    pub recipient: BatchPayoutRecipient,
+    pub status: BatchPayoutStatus,
+    pub tx_id: Option<TxId>,
+    /// The reason the last attempt to pay this row failed
+    pub error: Option<String>,
+    pub updated_at: NaiveDateTime,
+}
+
+impl BatchPayoutRow {
+    pub fn new(batch_id: String, index: u64, recipient: BatchPayoutRecipient, updated_at: NaiveDateTime) -> Self {
+        Self {
+            batch_id,
+            index,
+            recipient,
+            status: BatchPayoutStatus::Pending,
+            tx_id: None,
+            error: None,
+            updated_at,
+        }
+    }
+
+    pub fn mark_in_flight(&mut self, tx_id: TxId, timestamp: NaiveDateTime) {
+        self.status = BatchPayoutStatus::Sending;
+        self.tx_id = Some(tx_id);
+        self.error = None;
+        self.updated_at = timestamp;
+    }
+
+    pub fn mark_sent(&mut self, tx_id: TxId, timestamp: NaiveDateTime) {
+        self.status = BatchPayoutStatus::Sent;
+        self.tx_id = Some(tx_id);
+        self.error = None;
+        self.updated_at = timestamp;
+    }
+
+    pub fn mark_failed(&mut self, error: String, timestamp: NaiveDateTime) {
+        self.status = BatchPayoutStatus::Failed;
+        self.tx_id = None;
+        self.error = Some(error);
+        self.updated_at = timestamp;
+    }
+}
+
+/// The state of a scheduled payment. Only `Active` schedules are paid; `Completed` and `Cancelled` are final.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ScheduledPaymentStatus {
+    Active,    // 0
+    Paused,    // 1
+    Completed, // 2
+    Cancelled, // 3
+}
+
+impl ScheduledPaymentStatus {
+    pub fn is_finished(self) -> bool {
+        matches!(
+            self,
+            ScheduledPaymentStatus::Completed | ScheduledPaymentStatus::Cancelled
+        )
+    }
+}
+
+impl TryFrom<i32> for ScheduledPaymentStatus {
+    type Error = TransactionConversionError;
+
+    fn try_from(value: i32) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(ScheduledPaymentStatus::Active),
+            1 => Ok(ScheduledPaymentStatus::Paused),
+            2 => Ok(ScheduledPaymentStatus::Completed),
+            3 => Ok(ScheduledPaymentStatus::Cancelled),
+            code => Err(TransactionConversionError { code }),
+        }
+    }
+}
+
+impl Display for ScheduledPaymentStatus {
+    fn fmt(&self, fmt: &mut Formatter<'_>) -> Result<(), Error> {
+        let response = match self {
+            ScheduledPaymentStatus::Active => "Active",
+            ScheduledPaymentStatus::Paused => "Paused",
+            ScheduledPaymentStatus::Completed => "Completed",
+            ScheduledPaymentStatus::Cancelled => "Cancelled",
+        };
+        fmt.write_str(response)
+    }
+}
+
+/// A recurring one-sided payment that the transaction service sends on every occurrence of its cadence until the
+/// end date or the maximum number of payments is reached.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ScheduledPayment {
+    pub schedule_id: String,
+    pub address: TariAddress,
+    pub amount: MicroMinotari,
+    pub message: String,
+    /// The account the payments are funded from
+    pub account_id: AccountId,
+    pub fee_per_gram: MicroMinotari,
+    pub cadence: PaymentCadence,
+    pub start_at: NaiveDateTime,
+    /// The occurrence that is paid next, counted from `start_at`
+    pub occurrence: u64,
+    /// When the next attempt is made. This is later than the occurrence itself while a failed payment is retried.
+    pub next_run_at: NaiveDateTime,
+    pub end_at: Option<NaiveDateTime>,
+    pub max_payments: Option<u64>,
+    pub payments_made: u64,
+    /// The number of failed attempts to pay the current occurrence
+    pub failed_attempts: u32,
+    pub last_error: Option<String>,
+    pub last_tx_id: Option<TxId>,
+    pub status: ScheduledPaymentStatus,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+    /// The transaction of the current occurrence while it is being sent. It is stored before the transaction is
+    /// submitted so that a payment interrupted by a restart can be reconciled instead of being paid twice.
+    pub pending_tx_id: Option<TxId>,
+}
+
+impl ScheduledPayment {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        schedule_id: String,
+        address: TariAddress,
+        amount: MicroMinotari,
+        message: String,
+        account_id: AccountId,
+        fee_per_gram: MicroMinotari,
+        cadence: PaymentCadence,
+        start_at: NaiveDateTime,
+        end_at: Option<NaiveDateTime>,
+        max_payments: Option<u64>,
+        created_at: NaiveDateTime,
+    ) -> Self {
+        Self {
+            schedule_id,
+            address,
+            amount,
+            message,
+            account_id,
+            fee_per_gram,
+            cadence,
+            start_at,
+            occurrence: 0,
+            next_run_at: start_at,
+            end_at,
+            max_payments,
+            payments_made: 0,
+            failed_attempts: 0,
+            last_error: None,
+            last_tx_id: None,
+            status: ScheduledPaymentStatus::Active,
+            created_at,
+            updated_at: created_at,
+            pending_tx_id: None,
+        }
+    }
+
+    /// Records that the current occurrence is about to be paid by the given transaction
+    pub fn mark_in_flight(&mut self, tx_id: TxId, timestamp: NaiveDateTime) {
+        self.pending_tx_id = Some(tx_id);
+        self.updated_at = timestamp;
+    }
+
+    /// Records a successful payment of the current occurrence and moves on to the next one
+    pub fn record_payment(&mut self, tx_id: TxId, timestamp: NaiveDateTime) {
+        self.payments_made += 1;
+        self.last_tx_id = Some(tx_id);
+        self.pending_tx_id = None;
+        self.last_error = None;
+        self.advance(self.occurrence + 1, timestamp);
+    }
+
+    /// Records a failed payment attempt. The payment is retried after `retry_delay`, doubling with every further
+    /// attempt, until `max_retries` retries have failed and the occurrence is skipped. Returns true if the payment will
+    /// be retried.
+    pub fn record_failure(
+        &mut self,
+        error: String,
+        max_retries: u32,
+        retry_delay: std::time::Duration,
+        timestamp: NaiveDateTime,
+    ) -> bool {
+        self.pending_tx_id = None;
+        self.last_error = Some(error);
+        if self.failed_attempts >= max_retries {
+            self.advance(self.occurrence + 1, timestamp);
+            return false;
+        }
+        let backoff = retry_delay.saturating_mul(1 << self.failed_attempts.min(16));
+        self.failed_attempts += 1;
+        self.next_run_at = chrono::Duration::from_std(backoff)
+            .ok()
+            .and_then(|backoff| timestamp.checked_add_signed(backoff))
+            .unwrap_or(NaiveDateTime::MAX);
+        self.updated_at = timestamp;
+        true
+    }
+
+    /// Records a payment attempt that failed for a reason that retrying will not fix, such as an invalid address or
+    /// amount, and skips the current occurrence
+    pub fn record_rejection(&mut self, error: String, timestamp: NaiveDateTime) {
+        self.pending_tx_id = None;
+        self.last_error = Some(error);
+        self.advance(self.occurrence + 1, timestamp);
+    }
+
+    /// Records that an in-flight payment never reached the wallet database, so the occurrence is attempted again
+    pub fn clear_in_flight(&mut self, timestamp: NaiveDateTime) {
+        self.pending_tx_id = None;
+        self.updated_at = timestamp;
+    }
+
+    /// Resumes a paused schedule. Occurrences that fell within the pause are not paid.
+    pub fn resume(&mut self, timestamp: NaiveDateTime) {
+        self.status = ScheduledPaymentStatus::Active;
+        if self.next_run_at < timestamp {
+            self.advance(self.occurrence, timestamp);
+        } else {
+            self.updated_at = timestamp;
+        }
+    }
+
+    /// Moves the schedule to the first occurrence from `from` onwards that is still in the future, so that missed
+    /// occurrences are not paid one after another, and completes it once the end condition is reached.
+    fn advance(&mut self, from: u64, timestamp: NaiveDateTime) {
+        self.failed_attempts = 0;
+        self.updated_at = timestamp;
+        let next = self
+            .cadence
+            .next_occurrence_after(self.start_at, timestamp, from)
+            .and_then(|n| Some((n, self.cadence.occurrence(self.start_at, n)?)));
+        let max_payments_reached = self.max_payments.map_or(false, |max| self.payments_made >= max);
+        match next {
+            Some((occurrence, next_run_at))
+                if !max_payments_reached && self.end_at.map_or(true, |end_at| next_run_at <= end_at) =>
+            {
+                self.occurrence = occurrence;
+                self.next_run_at = next_run_at;
+            },
+            _ => self.status = ScheduledPaymentStatus::Completed,
+        }
+    }
+}
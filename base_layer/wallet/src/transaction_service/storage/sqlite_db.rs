@@ -28,7 +28,7 @@ use std::{
 
 use chacha20poly1305::XChaCha20Poly1305;
 use chrono::{NaiveDateTime, Utc};
-use diesel::{prelude::*, result::Error as DieselError, SqliteConnection};
+use diesel::{prelude::*, result::Error as DieselError, sqlite::Sqlite, SqliteConnection};
 use log::*;
 use tari_common_sqlite::{sqlite_connection_pool::PooledDbConnection, util::diesel_ext::ExpectedRowsExtension};
 use tari_common_types::{
@@ -50,16 +50,34 @@ use tokio::time::Instant;
 use zeroize::Zeroize;
 
 use crate::{
-    schema::{completed_transactions, inbound_transactions, outbound_transactions},
+    accounts::AccountId,
+    schema::{
+        batch_payouts,
+        completed_transactions,
+        inbound_transactions,
+        invoices,
+        outbound_transactions,
+        scheduled_payments,
+        transaction_status_history,
+    },
     storage::sqlite_utilities::wallet_db_connection::WalletDbConnection,
     transaction_service::{
+        batch_payout::BatchPayoutRecipient,
         error::{TransactionKeyError, TransactionStorageError},
+        scheduled_payment::PaymentCadence,
         storage::{
             database::{DbKey, DbKeyValuePair, DbValue, TransactionBackend, WriteOperation},
             models::{
+                BatchPayoutRow,
+                BatchPayoutStatus,
                 CompletedTransaction,
                 InboundTransaction,
+                Invoice,
+                InvoiceStatus,
                 OutboundTransaction,
+                ScheduledPayment,
+                ScheduledPaymentStatus,
+                TransactionStatusChange,
                 TxCancellationReason,
                 WalletTransaction,
             },
@@ -1012,6 +1030,45 @@ impl TransactionBackend for TransactionServiceSqliteDatabase {
             .collect::<Result<Vec<CompletedTransaction>, TransactionStorageError>>()
     }
 
+    fn fetch_account_completed_transactions(
+        &self,
+        account: AccountId,
+    ) -> Result<Vec<CompletedTransaction>, TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        let cipher = acquire_read_lock!(self.cipher);
+
+        CompletedTransactionSql::index_by_account_and_cancelled(account, false, &mut conn)?
+            .into_iter()
+            .map(|ct: CompletedTransactionSql| {
+                CompletedTransaction::try_from(ct, &cipher).map_err(TransactionStorageError::from)
+            })
+            .collect::<Result<Vec<CompletedTransaction>, TransactionStorageError>>()
+    }
+
+    fn fetch_completed_transactions_page(
+        &self,
+        account: Option<AccountId>,
+        offset: u64,
+        limit: u64,
+    ) -> Result<(Vec<CompletedTransaction>, u64), TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        let cipher = acquire_read_lock!(self.cipher);
+
+        let (page, total) = CompletedTransactionSql::index_page_by_account(
+            account,
+            i64::try_from(offset).unwrap_or(i64::MAX),
+            i64::try_from(limit).unwrap_or(i64::MAX),
+            &mut conn,
+        )?;
+        let page = page
+            .into_iter()
+            .map(|ct: CompletedTransactionSql| {
+                CompletedTransaction::try_from(ct, &cipher).map_err(TransactionStorageError::from)
+            })
+            .collect::<Result<Vec<CompletedTransaction>, TransactionStorageError>>()?;
+        Ok((page, u64::try_from(total).unwrap_or_default()))
+    }
+
     fn fetch_unconfirmed_detected_transactions(&self) -> Result<Vec<CompletedTransaction>, TransactionStorageError> {
         let mut conn = self.database_connection.get_pooled_connection()?;
         let cipher = acquire_read_lock!(self.cipher);
@@ -1093,6 +1150,123 @@ impl TransactionBackend for TransactionServiceSqliteDatabase {
         coinbases.append(&mut one_sided);
         Ok(coinbases)
     }
+
+    fn insert_invoice(&self, invoice: Invoice) -> Result<(), TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        if InvoiceSql::find(&invoice.payment_id, &mut conn)?.is_some() {
+            return Err(TransactionStorageError::InvoiceAlreadyExists(invoice.payment_id));
+        }
+        InvoiceSql::from(invoice).commit(&mut conn)
+    }
+
+    fn fetch_invoice(&self, payment_id: &str) -> Result<Option<Invoice>, TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        InvoiceSql::find(payment_id, &mut conn)?
+            .map(Invoice::try_from)
+            .transpose()
+    }
+
+    fn fetch_invoices(&self) -> Result<Vec<Invoice>, TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        InvoiceSql::index(&mut conn)?
+            .into_iter()
+            .map(Invoice::try_from)
+            .collect()
+    }
+
+    fn fetch_invoice_by_address(&self, address: &TariAddress) -> Result<Option<Invoice>, TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        InvoiceSql::find_by_address(address, &mut conn)?
+            .map(Invoice::try_from)
+            .transpose()
+    }
+
+    fn update_invoice(&self, invoice: &Invoice) -> Result<(), TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        InvoiceSql::update(invoice, &mut conn)
+    }
+
+    fn fetch_transaction_status_history(
+        &self,
+        tx_id: TxId,
+    ) -> Result<Vec<TransactionStatusChange>, TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        TransactionStatusHistorySql::index_by_tx_id(tx_id, &mut conn)?
+            .into_iter()
+            .map(TransactionStatusChange::try_from)
+            .collect()
+    }
+
+    fn insert_batch_payout(&self, rows: Vec<BatchPayoutRow>) -> Result<(), TransactionStorageError> {
+        let batch_id = match rows.first() {
+            Some(row) => row.batch_id.clone(),
+            None => return Ok(()),
+        };
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        conn.transaction::<_, TransactionStorageError, _>(|conn| {
+            if !BatchPayoutRowSql::index_by_batch_id(&batch_id, conn)?.is_empty() {
+                return Err(TransactionStorageError::BatchPayoutAlreadyExists(batch_id));
+            }
+            for row in rows {
+                BatchPayoutRowSql::from(row).commit(conn)?;
+            }
+            Ok(())
+        })
+    }
+
+    fn fetch_batch_payout(&self, batch_id: &str) -> Result<Vec<BatchPayoutRow>, TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        BatchPayoutRowSql::index_by_batch_id(batch_id, &mut conn)?
+            .into_iter()
+            .map(BatchPayoutRow::try_from)
+            .collect()
+    }
+
+    fn update_batch_payout_row(&self, row: &BatchPayoutRow) -> Result<(), TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        BatchPayoutRowSql::update(row, &mut conn)
+    }
+
+    fn insert_scheduled_payment(&self, payment: ScheduledPayment) -> Result<(), TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        if ScheduledPaymentSql::find(&payment.schedule_id, &mut conn)?.is_some() {
+            return Err(TransactionStorageError::ScheduledPaymentAlreadyExists(
+                payment.schedule_id,
+            ));
+        }
+        ScheduledPaymentSql::from(payment).commit(&mut conn)
+    }
+
+    fn fetch_scheduled_payment(&self, schedule_id: &str) -> Result<Option<ScheduledPayment>, TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        ScheduledPaymentSql::find(schedule_id, &mut conn)?
+            .map(ScheduledPayment::try_from)
+            .transpose()
+    }
+
+    fn fetch_scheduled_payments(&self) -> Result<Vec<ScheduledPayment>, TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        ScheduledPaymentSql::index(&mut conn)?
+            .into_iter()
+            .map(ScheduledPayment::try_from)
+            .collect()
+    }
+
+    fn fetch_due_scheduled_payments(
+        &self,
+        timestamp: NaiveDateTime,
+    ) -> Result<Vec<ScheduledPayment>, TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        ScheduledPaymentSql::index_due(timestamp, &mut conn)?
+            .into_iter()
+            .map(ScheduledPayment::try_from)
+            .collect()
+    }
+
+    fn update_scheduled_payment(&self, payment: &ScheduledPayment) -> Result<(), TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        ScheduledPaymentSql::update(payment, &mut conn)
+    }
 }
 
 #[derive(Debug, PartialEq)]
@@ -1144,6 +1318,7 @@ struct InboundTransactionSql {
     direct_send_success: i32,
     send_count: i32,
     last_send_timestamp: Option<NaiveDateTime>,
+    account_id: i64,
 }
 
 impl InboundTransactionSql {
@@ -1329,6 +1504,7 @@ impl InboundTransactionSql {
             direct_send_success: i32::from(i.direct_send_success),
             send_count: i.send_count as i32,
             last_send_timestamp: i.last_send_timestamp,
+            account_id: i.account_id.into(),
         };
         i.encrypt(cipher).map_err(TransactionStorageError::AeadError)
     }
@@ -1379,6 +1555,7 @@ impl InboundTransaction {
             direct_send_success: i.direct_send_success != 0,
             send_count: i.send_count as u32,
             last_send_timestamp: i.last_send_timestamp,
+            account_id: AccountId::try_from(i.account_id).map_err(TransactionStorageError::InvalidAccountId)?,
         })
     }
 }
@@ -1408,6 +1585,7 @@ struct OutboundTransactionSql {
     direct_send_success: i32,
     send_count: i32,
     last_send_timestamp: Option<NaiveDateTime>,
+    account_id: i64,
 }
 
 impl OutboundTransactionSql {
@@ -1578,6 +1756,7 @@ impl OutboundTransactionSql {
             direct_send_success: i32::from(o.direct_send_success),
             send_count: o.send_count as i32,
             last_send_timestamp: o.last_send_timestamp,
+            account_id: o.account_id.into(),
         };
 
         outbound_tx.encrypt(cipher).map_err(TransactionStorageError::AeadError)
@@ -1630,6 +1809,7 @@ impl OutboundTransaction {
             direct_send_success: o.direct_send_success != 0,
             send_count: o.send_count as u32,
             last_send_timestamp: o.last_send_timestamp,
+            account_id: AccountId::try_from(o.account_id).map_err(TransactionStorageError::InvalidAccountId)?,
         };
 
         // zeroize decrypted data
@@ -1672,6 +1852,7 @@ pub struct CompletedTransactionSql {
     mined_timestamp: Option<NaiveDateTime>,
     transaction_signature_nonce: Vec<u8>,
     transaction_signature_key: Vec<u8>,
+    account_id: i64,
 }
 
 impl CompletedTransactionSql {
@@ -1718,6 +1899,49 @@ impl CompletedTransactionSql {
             .load::<CompletedTransactionSql>(conn)?)
     }
 
+    pub fn index_by_account_and_cancelled(
+        account: AccountId,
+        cancelled: bool,
+        conn: &mut SqliteConnection,
+    ) -> Result<Vec<CompletedTransactionSql>, TransactionStorageError> {
+        let mut query = completed_transactions::table.into_boxed();
+        query = if cancelled {
+            query.filter(completed_transactions::cancelled.is_not_null())
+        } else {
+            query.filter(completed_transactions::cancelled.is_null())
+        };
+        Ok(query
+            .filter(completed_transactions::account_id.eq(i64::from(account)))
+            .load::<CompletedTransactionSql>(conn)?)
+    }
+
+    /// Returns a page of the non-cancelled transactions, newest first, and the number of transactions in all the pages
+    pub fn index_page_by_account(
+        account: Option<AccountId>,
+        offset: i64,
+        limit: i64,
+        conn: &mut SqliteConnection,
+    ) -> Result<(Vec<CompletedTransactionSql>, i64), TransactionStorageError> {
+        let total = Self::non_cancelled_query(account).count().get_result(conn)?;
+        let page = Self::non_cancelled_query(account)
+            .order_by(completed_transactions::timestamp.desc())
+            .then_order_by(completed_transactions::tx_id.desc())
+            .offset(offset)
+            .limit(limit)
+            .load::<CompletedTransactionSql>(conn)?;
+        Ok((page, total))
+    }
+
+    fn non_cancelled_query(account: Option<AccountId>) -> completed_transactions::BoxedQuery<'static, Sqlite> {
+        let query = completed_transactions::table
+            .filter(completed_transactions::cancelled.is_null())
+            .into_boxed();
+        match account {
+            Some(account) => query.filter(completed_transactions::account_id.eq(i64::from(account))),
+            None => query,
+        }
+    }
+
     pub fn index_by_status_and_cancelled_from_block_height(
         status: TransactionStatus,
         cancelled: bool,
@@ -1957,6 +2181,7 @@ impl CompletedTransactionSql {
             mined_timestamp: c.mined_timestamp,
             transaction_signature_nonce: c.transaction_signature.get_public_nonce().to_vec(),
             transaction_signature_key: c.transaction_signature.get_signature().to_vec(),
+            account_id: c.account_id.into(),
         };
 
         output.encrypt(cipher).map_err(TransactionStorageError::AeadError)
@@ -2006,6 +2231,8 @@ pub enum CompletedTransactionConversionError {
     AeadError(String),
     #[error("Bincode error: `{0}`")]
     BincodeDeserialize(String),
+    #[error("CompletedTransaction conversion failed with invalid account id: {0}")]
+    InvalidAccountId(String),
 }
 
 impl CompletedTransaction {
@@ -2054,6 +2281,8 @@ impl CompletedTransaction {
             mined_height: c.mined_height.map(|ic| ic as u64),
             mined_in_block,
             mined_timestamp: c.mined_timestamp,
+            account_id: AccountId::try_from(c.account_id)
+                .map_err(CompletedTransactionConversionError::InvalidAccountId)?,
         };
 
         // zeroize sensitive data
@@ -2153,6 +2382,398 @@ impl UnconfirmedTransactionInfoSql {
     }
 }
 
+#[derive(Clone, Debug, Queryable, Insertable, PartialEq)]
+#[diesel(table_name = invoices)]
+pub struct InvoiceSql {
+    pub payment_id: String,
+    pub address: Vec<u8>,
+    pub amount: i64,
+    pub amount_received: i64,
+    pub message: String,
+    pub status: i32,
+    pub tx_id: Option<i64>,
+    pub created_at: NaiveDateTime,
+    pub expires_at: Option<NaiveDateTime>,
+    pub paid_at: Option<NaiveDateTime>,
+}
+
+impl InvoiceSql {
+    pub fn commit(&self, conn: &mut SqliteConnection) -> Result<(), TransactionStorageError> {
+        diesel::insert_into(invoices::table)
+            .values(self.clone())
+            .execute(conn)?;
+        Ok(())
+    }
+
+    pub fn index(conn: &mut SqliteConnection) -> Result<Vec<InvoiceSql>, TransactionStorageError> {
+        Ok(invoices::table
+            .order_by(invoices::created_at.desc())
+            .load::<InvoiceSql>(conn)?)
+    }
+
+    pub fn find(payment_id: &str, conn: &mut SqliteConnection) -> Result<Option<InvoiceSql>, TransactionStorageError> {
+        Ok(invoices::table
+            .filter(invoices::payment_id.eq(payment_id))
+            .first::<InvoiceSql>(conn)
+            .optional()?)
+    }
+
+    pub fn find_by_address(
+        address: &TariAddress,
+        conn: &mut SqliteConnection,
+    ) -> Result<Option<InvoiceSql>, TransactionStorageError> {
+        Ok(invoices::table
+            .filter(invoices::address.eq(address.to_bytes().to_vec()))
+            .first::<InvoiceSql>(conn)
+            .optional()?)
+    }
+
+    pub fn update(invoice: &Invoice, conn: &mut SqliteConnection) -> Result<(), TransactionStorageError> {
+        diesel::update(invoices::table.filter(invoices::payment_id.eq(&invoice.payment_id)))
+            .set(UpdateInvoiceSql {
+                amount_received: invoice.amount_received.as_u64() as i64,
+                status: invoice.status as i32,
+                tx_id: invoice.tx_id.map(|id| id.as_u64() as i64),
+                expires_at: invoice.expires_at,
+                paid_at: invoice.paid_at,
+            })
+            .execute(conn)
+            .num_rows_affected_or_not_found(1)?;
+        Ok(())
+    }
+}
+
+#[derive(AsChangeset)]
+#[diesel(table_name = invoices)]
+#[diesel(treat_none_as_null = true)]
+struct UpdateInvoiceSql {
+    amount_received: i64,
+    status: i32,
+    tx_id: Option<i64>,
+    expires_at: Option<NaiveDateTime>,
+    paid_at: Option<NaiveDateTime>,
+}
+
+impl From<Invoice> for InvoiceSql {
+    fn from(invoice: Invoice) -> Self {
+        Self {
+            payment_id: invoice.payment_id,
+            address: invoice.address.to_bytes().to_vec(),
+            amount: invoice.amount.as_u64() as i64,
+            amount_received: invoice.amount_received.as_u64() as i64,
+            message: invoice.message,
+            status: invoice.status as i32,
+            tx_id: invoice.tx_id.map(|id| id.as_u64() as i64),
+            created_at: invoice.created_at,
+            expires_at: invoice.expires_at,
+            paid_at: invoice.paid_at,
+        }
+    }
+}
+
+impl TryFrom<InvoiceSql> for Invoice {
+    type Error = TransactionStorageError;
+
+    fn try_from(i: InvoiceSql) -> Result<Self, Self::Error> {
+        Ok(Self {
+            payment_id: i.payment_id,
+            address: TariAddress::from_bytes(&i.address)?,
+            amount: MicroMinotari::from(i.amount as u64),
+            amount_received: MicroMinotari::from(i.amount_received as u64),
+            message: i.message,
+            status: InvoiceStatus::try_from(i.status)?,
+            tx_id: i.tx_id.map(|id| TxId::from(id as u64)),
+            created_at: i.created_at,
+            expires_at: i.expires_at,
+            paid_at: i.paid_at,
+        })
+    }
+}
+
+#[derive(Clone, Debug, Queryable, PartialEq)]
+#[diesel(table_name = transaction_status_history)]
+pub struct TransactionStatusHistorySql {
+    pub id: i32,
+    pub tx_id: i64,
+    pub status: i32,
+    pub cancelled: Option<i32>,
+    pub changed_at: NaiveDateTime,
+}
+
+impl TransactionStatusHistorySql {
+    pub fn index_by_tx_id(
+        tx_id: TxId,
+        conn: &mut SqliteConnection,
+    ) -> Result<Vec<TransactionStatusHistorySql>, TransactionStorageError> {
+        Ok(transaction_status_history::table
+            .filter(transaction_status_history::tx_id.eq(tx_id.as_u64() as i64))
+            .order_by(transaction_status_history::id.asc())
+            .load::<TransactionStatusHistorySql>(conn)?)
+    }
+}
+
+impl TryFrom<TransactionStatusHistorySql> for TransactionStatusChange {
+    type Error = TransactionStorageError;
+
+    fn try_from(s: TransactionStatusHistorySql) -> Result<Self, Self::Error> {
+        Ok(Self {
+            tx_id: TxId::from(s.tx_id as u64),
+            status: TransactionStatus::try_from(s.status)?,
+            cancelled: s
+                .cancelled
+                .map(|v| TxCancellationReason::try_from(v as u32))
+                .transpose()?,
+            changed_at: s.changed_at,
+        })
+    }
+}
+
+#[derive(Clone, Debug, Queryable, Insertable, PartialEq)]
+#[diesel(table_name = batch_payouts)]
+pub struct BatchPayoutRowSql {
+    pub batch_id: String,
+    pub row_index: i64,
+    pub address: Vec<u8>,
+    pub amount: i64,
+    pub memo: String,
+    pub status: i32,
+    pub tx_id: Option<i64>,
+    pub error: Option<String>,
+    pub updated_at: NaiveDateTime,
+}
+
+impl BatchPayoutRowSql {
+    pub fn commit(&self, conn: &mut SqliteConnection) -> Result<(), TransactionStorageError> {
+        diesel::insert_into(batch_payouts::table)
+            .values(self.clone())
+            .execute(conn)?;
+        Ok(())
+    }
+
+    pub fn index_by_batch_id(
+        batch_id: &str,
+        conn: &mut SqliteConnection,
+    ) -> Result<Vec<BatchPayoutRowSql>, TransactionStorageError> {
+        Ok(batch_payouts::table
+            .filter(batch_payouts::batch_id.eq(batch_id))
+            .order_by(batch_payouts::row_index.asc())
+            .load::<BatchPayoutRowSql>(conn)?)
+    }
+
+    pub fn update(row: &BatchPayoutRow, conn: &mut SqliteConnection) -> Result<(), TransactionStorageError> {
+        diesel::update(
+            batch_payouts::table
+                .filter(batch_payouts::batch_id.eq(&row.batch_id))
+                .filter(batch_payouts::row_index.eq(row.index as i64)),
+        )
+        .set(UpdateBatchPayoutRowSql {
+            status: row.status as i32,
+            tx_id: row.tx_id.map(|id| id.as_u64() as i64),
+            error: row.error.clone(),
+            updated_at: row.updated_at,
+        })
+        .execute(conn)
+        .num_rows_affected_or_not_found(1)?;
+        Ok(())
+    }
+}
+
+#[derive(AsChangeset)]
+#[diesel(table_name = batch_payouts)]
+#[diesel(treat_none_as_null = true)]
+struct UpdateBatchPayoutRowSql {
+    status: i32,
+    tx_id: Option<i64>,
+    error: Option<String>,
+    updated_at: NaiveDateTime,
+}
+
+impl From<BatchPayoutRow> for BatchPayoutRowSql {
+    fn from(row: BatchPayoutRow) -> Self {
+        Self {
+            batch_id: row.batch_id,
+            row_index: row.index as i64,
+            address: row.recipient.address.to_bytes().to_vec(),
+            amount: row.recipient.amount.as_u64() as i64,
+            memo: row.recipient.memo,
+            status: row.status as i32,
+            tx_id: row.tx_id.map(|id| id.as_u64() as i64),
+            error: row.error,
+            updated_at: row.updated_at,
+        }
+    }
+}
+
+impl TryFrom<BatchPayoutRowSql> for BatchPayoutRow {
+    type Error = TransactionStorageError;
+
+    fn try_from(r: BatchPayoutRowSql) -> Result<Self, Self::Error> {
+        Ok(Self {
+            batch_id: r.batch_id,
+            index: r.row_index as u64,
+            recipient: BatchPayoutRecipient::new(
+                TariAddress::from_bytes(&r.address)?,
+                MicroMinotari::from(r.amount as u64),
+                r.memo,
+            ),
+            status: BatchPayoutStatus::try_from(r.status)?,
+            tx_id: r.tx_id.map(|id| TxId::from(id as u64)),
+            error: r.error,
+            updated_at: r.updated_at,
+        })
+    }
+}
+
+#[derive(Clone, Debug, Queryable, Insertable, PartialEq)]
+#[diesel(table_name = scheduled_payments)]
+pub struct ScheduledPaymentSql {
+    pub schedule_id: String,
+    pub address: Vec<u8>,
+    pub amount: i64,
+    pub message: String,
+    pub account_id: i64,
+    pub fee_per_gram: i64,
+    pub cadence: String,
+    pub start_at: NaiveDateTime,
+    pub occurrence: i64,
+    pub next_run_at: NaiveDateTime,
+    pub end_at: Option<NaiveDateTime>,
+    pub max_payments: Option<i64>,
+    pub payments_made: i64,
+    pub failed_attempts: i32,
+    pub last_error: Option<String>,
+    pub last_tx_id: Option<i64>,
+    pub status: i32,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+    pub pending_tx_id: Option<i64>,
+}
+
+impl ScheduledPaymentSql {
+    pub fn commit(&self, conn: &mut SqliteConnection) -> Result<(), TransactionStorageError> {
+        diesel::insert_into(scheduled_payments::table)
+            .values(self.clone())
+            .execute(conn)?;
+        Ok(())
+    }
+
+    pub fn index(conn: &mut SqliteConnection) -> Result<Vec<ScheduledPaymentSql>, TransactionStorageError> {
+        Ok(scheduled_payments::table
+            .order_by(scheduled_payments::created_at.asc())
+            .load::<ScheduledPaymentSql>(conn)?)
+    }
+
+    pub fn index_due(
+        timestamp: NaiveDateTime,
+        conn: &mut SqliteConnection,
+    ) -> Result<Vec<ScheduledPaymentSql>, TransactionStorageError> {
+        Ok(scheduled_payments::table
+            .filter(scheduled_payments::status.eq(ScheduledPaymentStatus::Active as i32))
+            .filter(scheduled_payments::next_run_at.le(timestamp))
+            .order_by(scheduled_payments::next_run_at.asc())
+            .load::<ScheduledPaymentSql>(conn)?)
+    }
+
+    pub fn find(
+        schedule_id: &str,
+        conn: &mut SqliteConnection,
+    ) -> Result<Option<ScheduledPaymentSql>, TransactionStorageError> {
+        Ok(scheduled_payments::table
+            .filter(scheduled_payments::schedule_id.eq(schedule_id))
+            .first::<ScheduledPaymentSql>(conn)
+            .optional()?)
+    }
+
+    pub fn update(payment: &ScheduledPayment, conn: &mut SqliteConnection) -> Result<(), TransactionStorageError> {
+        diesel::update(scheduled_payments::table.filter(scheduled_payments::schedule_id.eq(&payment.schedule_id)))
+            .set(UpdateScheduledPaymentSql {
+                occurrence: payment.occurrence as i64,
+                next_run_at: payment.next_run_at,
+                payments_made: payment.payments_made as i64,
+                failed_attempts: payment.failed_attempts as i32,
+                last_error: payment.last_error.clone(),
+                last_tx_id: payment.last_tx_id.map(|id| id.as_u64() as i64),
+                status: payment.status as i32,
+                updated_at: payment.updated_at,
+                pending_tx_id: payment.pending_tx_id.map(|id| id.as_u64() as i64),
+            })
+            .execute(conn)
+            .num_rows_affected_or_not_found(1)?;
+        Ok(())
+    }
+}
+
+#[derive(AsChangeset)]
+#[diesel(table_name = scheduled_payments)]
+#[diesel(treat_none_as_null = true)]
+struct UpdateScheduledPaymentSql {
+    occurrence: i64,
+    next_run_at: NaiveDateTime,
+    payments_made: i64,
+    failed_attempts: i32,
+    last_error: Option<String>,
+    last_tx_id: Option<i64>,
+    status: i32,
+    updated_at: NaiveDateTime,
+    pending_tx_id: Option<i64>,
+}
+
+impl From<ScheduledPayment> for ScheduledPaymentSql {
+    fn from(payment: ScheduledPayment) -> Self {
+        Self {
+            schedule_id: payment.schedule_id,
+            address: payment.address.to_bytes().to_vec(),
+            amount: payment.amount.as_u64() as i64,
+            message: payment.message,
+            account_id: payment.account_id.into(),
+            fee_per_gram: payment.fee_per_gram.as_u64() as i64,
+            cadence: payment.cadence.to_string(),
+            start_at: payment.start_at,
+            occurrence: payment.occurrence as i64,
+            next_run_at: payment.next_run_at,
+            end_at: payment.end_at,
+            max_payments: payment.max_payments.map(|max| max as i64),
+            payments_made: payment.payments_made as i64,
+            failed_attempts: payment.failed_attempts as i32,
+            last_error: payment.last_error,
+            last_tx_id: payment.last_tx_id.map(|id| id.as_u64() as i64),
+            status: payment.status as i32,
+            created_at: payment.created_at,
+            updated_at: payment.updated_at,
+            pending_tx_id: payment.pending_tx_id.map(|id| id.as_u64() as i64),
+        }
+    }
+}
+
+impl TryFrom<ScheduledPaymentSql> for ScheduledPayment {
+    type Error = TransactionStorageError;
+
+    fn try_from(p: ScheduledPaymentSql) -> Result<Self, Self::Error> {
+        Ok(Self {
+            schedule_id: p.schedule_id,
+            address: TariAddress::from_bytes(&p.address)?,
+            amount: MicroMinotari::from(p.amount as u64),
+            message: p.message,
+            account_id: AccountId::try_from(p.account_id).map_err(TransactionStorageError::InvalidAccountId)?,
+            fee_per_gram: MicroMinotari::from(p.fee_per_gram as u64),
+            cadence: p.cadence.parse::<PaymentCadence>()?,
+            start_at: p.start_at,
+            occurrence: p.occurrence as u64,
+            next_run_at: p.next_run_at,
+            end_at: p.end_at,
+            max_payments: p.max_payments.map(|max| max as u64),
+            payments_made: p.payments_made as u64,
+            failed_attempts: p.failed_attempts as u32,
+            last_error: p.last_error,
+            last_tx_id: p.last_tx_id.map(|id| TxId::from(id as u64)),
+            status: ScheduledPaymentStatus::try_from(p.status)?,
+            created_at: p.created_at,
+            updated_at: p.updated_at,
+            pending_tx_id: p.pending_tx_id.map(|id| TxId::from(id as u64)),
+        })
+    }
+}
+
 #[cfg(test)]
 mod test {
     use std::{default::Default, mem::size_of, time::Duration};
@@ -2185,6 +2806,7 @@ mod test {
     use tempfile::tempdir;
 
     use crate::{
+        accounts::AccountId,
         storage::sqlite_utilities::wallet_db_connection::WalletDbConnection,
         test_utils::create_consensus_constants,
         transaction_service::storage::{
@@ -2293,6 +2915,7 @@ mod test {
             direct_send_success: false,
             send_count: 0,
             last_send_timestamp: None,
+            account_id: AccountId::DEFAULT,
         };
         let address = TariAddress::new(
             PublicKey::from_secret_key(&PrivateKey::random(&mut OsRng)),
@@ -2312,6 +2935,7 @@ mod test {
                 direct_send_success: false,
                 send_count: 0,
                 last_send_timestamp: None,
+                account_id: AccountId::DEFAULT,
             },
             &cipher,
         )
@@ -2377,6 +3001,7 @@ mod test {
             direct_send_success: false,
             send_count: 0,
             last_send_timestamp: None,
+            account_id: AccountId::DEFAULT,
         };
         let address = TariAddress::new(
             PublicKey::from_secret_key(&PrivateKey::random(&mut OsRng)),
@@ -2394,6 +3019,7 @@ mod test {
             direct_send_success: false,
             send_count: 0,
             last_send_timestamp: None,
+            account_id: AccountId::DEFAULT,
         };
 
         InboundTransactionSql::try_from(inbound_tx1.clone(), &cipher)
@@ -2458,6 +3084,7 @@ mod test {
             mined_height: None,
             mined_in_block: None,
             mined_timestamp: None,
+            account_id: AccountId::DEFAULT,
         };
         let source_address = TariAddress::new(
             PublicKey::from_secret_key(&PrivateKey::random(&mut OsRng)),
@@ -2486,6 +3113,7 @@ mod test {
             mined_height: None,
             mined_in_block: None,
             mined_timestamp: None,
+            account_id: AccountId::DEFAULT,
         };
 
         CompletedTransactionSql::try_from(completed_tx1.clone(), &cipher)
@@ -2650,6 +3278,7 @@ mod test {
             direct_send_success: false,
             send_count: 0,
             last_send_timestamp: None,
+            account_id: AccountId::DEFAULT,
         };
         let inbound_tx_sql = InboundTransactionSql::try_from(inbound_tx.clone(), &cipher).unwrap();
         inbound_tx_sql.commit(&mut conn).unwrap();
@@ -2677,6 +3306,7 @@ mod test {
             direct_send_success: false,
             send_count: 0,
             last_send_timestamp: None,
+            account_id: AccountId::DEFAULT,
         };
 
         let outbound_tx_sql = OutboundTransactionSql::try_from(outbound_tx.clone(), &cipher).unwrap();
@@ -2721,6 +3351,7 @@ mod test {
             mined_height: None,
             mined_in_block: None,
             mined_timestamp: None,
+            account_id: AccountId::DEFAULT,
         };
 
         let completed_tx_sql = CompletedTransactionSql::try_from(completed_tx.clone(), &cipher).unwrap();
@@ -2789,6 +3420,7 @@ mod test {
                 direct_send_success: false,
                 send_count: 0,
                 last_send_timestamp: None,
+                account_id: AccountId::DEFAULT,
             };
             let inbound_tx_sql = InboundTransactionSql::try_from(inbound_tx, &cipher).unwrap();
 
@@ -2811,6 +3443,7 @@ mod test {
                 direct_send_success: false,
                 send_count: 0,
                 last_send_timestamp: None,
+                account_id: AccountId::DEFAULT,
             };
             let outbound_tx_sql = OutboundTransactionSql::try_from(outbound_tx, &cipher).unwrap();
 
@@ -2849,6 +3482,7 @@ mod test {
                 mined_height: None,
                 mined_in_block: None,
                 mined_timestamp: None,
+                account_id: AccountId::DEFAULT,
             };
             let completed_tx_sql = CompletedTransactionSql::try_from(completed_tx, &cipher).unwrap();
 
@@ -2989,6 +3623,7 @@ mod test {
                 mined_height: None,
                 mined_in_block: None,
                 mined_timestamp: None,
+                account_id: AccountId::DEFAULT,
             };
             let completed_tx_sql = CompletedTransactionSql::try_from(completed_tx.clone(), &cipher).unwrap();
 
@@ -63,7 +63,7 @@ use tari_crypto::{hash_domain, signatures::SchnorrSignatureError};
 use tari_key_manager::{
     cipher_seed::CipherSeed,
     key_manager::KeyManager,
-    key_manager_service::{storage::database::KeyManagerBackend, KeyDigest},
+    key_manager_service::{storage::database::KeyManagerBackend, KeyDigest, KeyManagerInterface},
     mnemonic::{Mnemonic, MnemonicLanguage},
     SeedWords,
 };
@@ -82,8 +82,14 @@ use tari_shutdown::ShutdownSignal;
 use tari_utilities::{hex::Hex, ByteArray};
 
 use crate::{
+    accounts::{AccountId, WalletAccount},
     base_node_service::{handle::BaseNodeServiceHandle, BaseNodeServiceInitializer},
-    config::{WalletConfig, KEY_MANAGER_COMMS_SECRET_KEY_BRANCH_KEY},
+    config::{
+        WalletConfig,
+        KEY_MANAGER_COMMS_SECRET_KEY_BRANCH_KEY,
+        KEY_MANAGER_INVOICE_BRANCH_KEY,
+        KEY_MANAGER_SUBADDRESS_BRANCH_KEY,
+    },
     connectivity_service::{WalletConnectivityHandle, WalletConnectivityInitializer, WalletConnectivityInterface},
     consts,
     error::{WalletError, WalletStorageError},
@@ -263,6 +269,8 @@ where
             let wallet_db = wallet_database.clone();
             let node_id = comms.node_identity();
             let moved_ts_clone = transaction_service_handle.clone();
+            // Called with the current identity on startup, when the onion key is rotated and when the previous onion
+            // address is retired at the end of the grace period
             let after_comms = move |identity: TorIdentity| {
                 // we do this so that we dont have to move in a mut ref and making the closure a FnMut.
                 let mut ts = moved_ts_clone.clone();
@@ -321,6 +329,21 @@ where
                 e
             })?;
 
+        for account in wallet_database.get_accounts()? {
+            if account.is_default() {
+                continue;
+            }
+            register_account_keys(&key_manager_handle, &mut output_manager_handle, account.id).await?;
+        }
+        // Invoice payment addresses and stealth subaddresses are derived from their own branches, which must exist
+        // before outputs received on them can be spent
+        key_manager_handle
+            .add_new_branch(KEY_MANAGER_INVOICE_BRANCH_KEY.to_string())
+            .await?;
+        key_manager_handle
+            .add_new_branch(KEY_MANAGER_SUBADDRESS_BRANCH_KEY.to_string())
+            .await?;
+
         wallet_database.set_node_features(comms.node_identity().features())?;
         let identity_sig = comms.node_identity().identity_signature_read().as_ref().cloned();
         if let Some(identity_sig) = identity_sig {
@@ -559,6 +582,41 @@ where
         signature.verify(public_key, message)
     }
 
+    /// Create a new named account within this wallet. Each account has its own key branch, balance, address and
+    /// transaction history.
+    pub async fn create_account(&mut self, name: &str) -> Result<WalletAccount, WalletError> {
+        let account = self.db.create_account(name)?;
+        register_account_keys(&self.key_manager_service, &mut self.output_manager_service, account.id).await?;
+        info!(target: LOG_TARGET, "Created wallet account {}", account);
+        Ok(account)
+    }
+
+    pub fn get_accounts(&self) -> Result<Vec<WalletAccount>, WalletError> {
+        Ok(self.db.get_accounts()?)
+    }
+
+    pub fn get_account_by_name(&self, name: &str) -> Result<WalletAccount, WalletError> {
+        Ok(self.db.get_account_by_name(name)?)
+    }
+
+    /// Returns the address that payments to the given account should be sent to. The default account uses the wallet's
+    /// node identity address. Other accounts have no comms identity of their own, so their address receives one-sided
+    /// (simple or stealth) payments, and interactive payments only from the other accounts of this wallet.
+    pub async fn get_account_address(&self, account: AccountId) -> Result<TariAddress, WalletError> {
+        let network = self.network.as_network();
+        if account.is_default() {
+            return Ok(TariAddress::new(
+                self.comms.node_identity().public_key().clone(),
+                network,
+            ));
+        }
+        // Make sure the account exists before deriving an address for it
+        self.db.get_account(account)?;
+        let key_id = self.key_manager_service.get_static_key(account.key_branch()).await?;
+        let public_key = self.key_manager_service.get_public_key_at_key_id(&key_id).await?;
+        Ok(TariAddress::new(public_key, network))
+    }
+
     /// Appraise the expected outputs and a fee
     pub async fn preview_coin_split_with_commitments_no_amount(
         &mut self,
@@ -778,3 +836,32 @@ async fn persist_one_sided_payment_script_for_node_identity(
     output_manager_service.add_known_script(known_script).await?;
     Ok(())
 }
+
+/// Add the key branches for a non-default account and persist the one-sided payment script for its static key, so
+/// that outputs sent to the account address are detected during scanning and attributed to the account. The spend and
+/// script keys of the account's own outputs are derived from separate branches.
+async fn register_account_keys<TKeyManagerInterface: SecretTransactionKeyManagerInterface>(
+    key_manager_service: &TKeyManagerInterface,
+    output_manager_service: &mut OutputManagerHandle,
+    account: AccountId,
+) -> Result<(), WalletError> {
+    key_manager_service.add_new_branch(account.key_branch()).await?;
+    key_manager_service.add_new_branch(account.spend_key_branch()).await?;
+    key_manager_service.add_new_branch(account.script_key_branch()).await?;
+    let script_key_id = key_manager_service.get_static_key(account.key_branch()).await?;
+    let public_key = key_manager_service.get_public_key_at_key_id(&script_key_id).await?;
+    let script = one_sided_payment_script(&public_key);
+    let known_script = KnownOneSidedPaymentScript {
+        script_hash: script
+            .as_hash::<Blake2b<U32>>()
+            .map_err(|e| WalletError::OutputManagerError(OutputManagerError::ScriptError(e)))?
+            .to_vec(),
+        script_key_id,
+        script,
+        input: ExecutionStack::default(),
+        script_lock_height: 0,
+    };
+
+    output_manager_service.add_known_script(known_script).await?;
+    Ok(())
+}
@@ -0,0 +1,147 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::time::Duration;
+
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+
+/// A signed event notification that is waiting to be delivered to a single webhook endpoint
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WebhookDelivery {
+    pub event_id: String,
+    pub url: String,
+    pub event: String,
+    pub payload: String,
+    pub attempts: u32,
+    pub next_attempt_at: NaiveDateTime,
+    pub last_error: Option<String>,
+    /// Set when the delivery failed too often to be retried, it is kept for inspection but no longer delivered
+    pub dead_lettered_at: Option<NaiveDateTime>,
+    pub created_at: NaiveDateTime,
+}
+
+impl WebhookDelivery {
+    pub fn new(event_id: String, url: String, event: String, payload: String, timestamp: NaiveDateTime) -> Self {
+        Self {
+            event_id,
+            url,
+            event,
+            payload,
+            attempts: 0,
+            next_attempt_at: timestamp,
+            last_error: None,
+            dead_lettered_at: None,
+            created_at: timestamp,
+        }
+    }
+
+    /// Whether the delivery may be attempted at the given time
+    pub fn is_due(&self, timestamp: NaiveDateTime) -> bool {
+        self.dead_lettered_at.is_none() && self.next_attempt_at <= timestamp
+    }
+
+    /// Records a failed delivery attempt. The delivery is retried after `retry_delay`, doubling with every further
+    /// attempt up to `max_retry_delay`. After `max_attempts` attempts, 0 meaning no limit, the delivery is moved to the
+    /// dead letters instead. Returns true if the delivery will be retried.
+    pub fn record_failure(
+        &mut self,
+        error: String,
+        max_attempts: u32,
+        retry_delay: Duration,
+        max_retry_delay: Duration,
+        timestamp: NaiveDateTime,
+    ) -> bool {
+        let backoff = retry_delay
+            .saturating_mul(1 << self.attempts.min(16))
+            .min(max_retry_delay);
+        self.attempts = self.attempts.saturating_add(1);
+        self.last_error = Some(error);
+        if max_attempts > 0 && self.attempts >= max_attempts {
+            self.dead_lettered_at = Some(timestamp);
+            return false;
+        }
+        self.next_attempt_at = chrono::Duration::from_std(backoff)
+            .ok()
+            .and_then(|backoff| timestamp.checked_add_signed(backoff))
+            .unwrap_or(NaiveDateTime::MAX);
+        true
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn it_backs_off_up_to_the_maximum_delay() {
+        let now = chrono::Utc::now().naive_utc();
+        let mut delivery = WebhookDelivery::new(
+            "id".to_string(),
+            "https://example.com/hook".to_string(),
+            "received".to_string(),
+            "{}".to_string(),
+            now,
+        );
+        assert!(delivery.is_due(now));
+
+        let retry_delay = Duration::from_secs(10);
+        let max_retry_delay = Duration::from_secs(60);
+        assert!(delivery.record_failure("timeout".to_string(), 0, retry_delay, max_retry_delay, now));
+        assert_eq!(delivery.attempts, 1);
+        assert_eq!(delivery.next_attempt_at, now + chrono::Duration::seconds(10));
+        assert!(!delivery.is_due(now));
+
+        assert!(delivery.record_failure("timeout".to_string(), 0, retry_delay, max_retry_delay, now));
+        assert_eq!(delivery.next_attempt_at, now + chrono::Duration::seconds(20));
+
+        for _ in 0..40 {
+            assert!(delivery.record_failure("timeout".to_string(), 0, retry_delay, max_retry_delay, now));
+        }
+        assert_eq!(delivery.attempts, 42);
+        assert_eq!(delivery.next_attempt_at, now + chrono::Duration::seconds(60));
+        assert_eq!(delivery.last_error.as_deref(), Some("timeout"));
+        assert_eq!(delivery.dead_lettered_at, None);
+    }
+
+    #[test]
+    fn it_dead_letters_after_the_maximum_attempts() {
+        let now = chrono::Utc::now().naive_utc();
+        let mut delivery = WebhookDelivery::new(
+            "id".to_string(),
+            "https://example.com/hook".to_string(),
+            "received".to_string(),
+            "{}".to_string(),
+            now,
+        );
+        let retry_delay = Duration::from_secs(10);
+        assert!(delivery.record_failure("timeout".to_string(), 2, retry_delay, retry_delay, now));
+        assert_eq!(delivery.dead_lettered_at, None);
+
+        let later = now + chrono::Duration::seconds(10);
+        assert!(!delivery.record_failure("refused".to_string(), 2, retry_delay, retry_delay, later));
+        assert_eq!(delivery.attempts, 2);
+        assert_eq!(delivery.dead_lettered_at, Some(later));
+        assert_eq!(delivery.last_error.as_deref(), Some("refused"));
+        assert!(!delivery.is_due(later + chrono::Duration::days(1)));
+    }
+}
@@ -152,8 +152,23 @@ async fn create_wallet(
         listener_liveness_max_sessions: 0,
         user_agent: "tari/test-wallet".to_string(),
         auxiliary_tcp_listener_address: None,
+        quic_listener_address: None,
         rpc_max_simultaneous_sessions: 0,
         rpc_max_sessions_per_peer: 0,
+        rpc_max_requests_per_minute_per_peer: None,
+        rpc_max_concurrent_requests_per_peer: None,
+        rpc_max_streamed_bytes_per_minute_per_peer: None,
+        rpc_max_concurrent_streams: None,
+        reputation_ban_threshold: -100.0,
+        reputation_decay_half_life: Duration::from_secs(30 * 60),
+        reputation_ban_duration: Duration::from_secs(10 * 60),
+        max_upload_rate: None,
+        max_download_rate: None,
+        max_peer_upload_rate: None,
+        max_peer_download_rate: None,
+        enable_port_mapping: false,
+        nat_gateway_address: None,
+        advertise_observed_address: false,
         listener_liveness_check_interval: None,
     };
 
@@ -690,8 +705,23 @@ async fn test_import_utxo() {
         listener_liveness_max_sessions: 0,
         user_agent: "tari/test-wallet".to_string(),
         auxiliary_tcp_listener_address: None,
+        quic_listener_address: None,
         rpc_max_simultaneous_sessions: 0,
         rpc_max_sessions_per_peer: 0,
+        rpc_max_requests_per_minute_per_peer: None,
+        rpc_max_concurrent_requests_per_peer: None,
+        rpc_max_streamed_bytes_per_minute_per_peer: None,
+        rpc_max_concurrent_streams: None,
+        reputation_ban_threshold: -100.0,
+        reputation_decay_half_life: Duration::from_secs(30 * 60),
+        reputation_ban_duration: Duration::from_secs(10 * 60),
+        max_upload_rate: None,
+        max_download_rate: None,
+        max_peer_upload_rate: None,
+        max_peer_download_rate: None,
+        enable_port_mapping: false,
+        nat_gateway_address: None,
+        advertise_observed_address: false,
         listener_liveness_check_interval: None,
     };
     let config = WalletConfig {
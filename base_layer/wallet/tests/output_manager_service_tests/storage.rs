@@ -20,23 +20,32 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use minotari_wallet::output_manager_service::{
-    error::OutputManagerStorageError,
-    service::Balance,
-    storage::{
-        database::{OutputManagerBackend, OutputManagerDatabase},
-        models::DbWalletOutput,
-        sqlite_db::OutputManagerSqliteDatabase,
-        OutputSource,
+use chrono::Utc;
+use minotari_wallet::{
+    accounts::AccountId,
+    output_manager_service::{
+        error::OutputManagerStorageError,
+        service::Balance,
+        storage::{
+            database::{OutputManagerBackend, OutputManagerDatabase},
+            models::{DbWalletOutput, Subaddress},
+            sqlite_db::OutputManagerSqliteDatabase,
+            OutputSource,
+        },
+        UtxoSelectionCriteria,
     },
 };
 use rand::{rngs::OsRng, RngCore};
-use tari_common_types::{transaction::TxId, types::FixedHash};
+use tari_common_types::{
+    transaction::TxId,
+    types::{FixedHash, PublicKey},
+};
 use tari_core::transactions::{
     key_manager::create_memory_db_key_manager,
     tari_amount::MicroMinotari,
     transaction_components::OutputFeatures,
 };
+use tari_crypto::keys::PublicKey as PublicKeyTrait;
 
 use crate::support::{data::get_temp_sqlite_database_connection, utils::make_input};
 
@@ -72,7 +81,7 @@ pub async fn test_db_backend<T: OutputManagerBackend + 'static>(backend: T) {
     let time_locked_balance = unspent_outputs[4].wallet_output.value;
 
     for i in 0..4usize {
-        let balance = db.get_balance(Some(i as u64)).unwrap();
+        let balance = db.get_balance(None, Some(i as u64)).unwrap();
         let mut sum = MicroMinotari::from(0);
         for output in unspent_outputs.iter().take(5).skip(i + 1) {
             sum += output.wallet_output.value;
@@ -153,7 +162,7 @@ pub async fn test_db_backend<T: OutputManagerBackend + 'static>(backend: T) {
             .fold(MicroMinotari::from(0), |acc, x| acc + x.wallet_output.value);
     }
 
-    let balance = db.get_balance(None).unwrap();
+    let balance = db.get_balance(None, None).unwrap();
     assert_eq!(balance, Balance {
         available_balance,
         time_locked_balance: None,
@@ -161,7 +170,7 @@ pub async fn test_db_backend<T: OutputManagerBackend + 'static>(backend: T) {
         pending_outgoing_balance
     });
 
-    let balance = db.get_balance(Some(3)).unwrap();
+    let balance = db.get_balance(None, Some(3)).unwrap();
     assert_eq!(balance, Balance {
         available_balance,
         time_locked_balance: Some(time_locked_balance),
@@ -173,7 +182,7 @@ pub async fn test_db_backend<T: OutputManagerBackend + 'static>(backend: T) {
         db.confirm_encumbered_outputs(v.tx_id).unwrap();
     }
 
-    let balance = db.get_balance(None).unwrap();
+    let balance = db.get_balance(None, None).unwrap();
     assert_eq!(balance, Balance {
         available_balance,
         time_locked_balance: None,
@@ -191,7 +200,7 @@ pub async fn test_db_backend<T: OutputManagerBackend + 'static>(backend: T) {
     }
 
     // Balance shouldn't change
-    let balance = db.get_balance(None).unwrap();
+    let balance = db.get_balance(None, None).unwrap();
 
     assert_eq!(balance, Balance {
         available_balance,
@@ -238,7 +247,7 @@ pub async fn test_db_backend<T: OutputManagerBackend + 'static>(backend: T) {
         .iter()
         .fold(MicroMinotari::from(0), |acc, x| acc + x.wallet_output.value);
 
-    let balance = db.get_balance(None).unwrap();
+    let balance = db.get_balance(None, None).unwrap();
     assert_eq!(
         balance,
         Balance {
@@ -266,7 +275,7 @@ pub async fn test_db_backend<T: OutputManagerBackend + 'static>(backend: T) {
         .unwrap();
     pending_incoming_balance += output_to_be_received.wallet_output.value;
 
-    let balance = db.get_balance(None).unwrap();
+    let balance = db.get_balance(None, None).unwrap();
     assert_eq!(
         balance,
         Balance {
@@ -363,7 +372,7 @@ pub async fn test_short_term_encumberance() {
     db.encumber_outputs(1u64.into(), unspent_outputs[0..=2].to_vec(), vec![])
         .unwrap();
 
-    let balance = db.get_balance(None).unwrap();
+    let balance = db.get_balance(None, None).unwrap();
     assert_eq!(
         balance.available_balance,
         unspent_outputs[3..5]
@@ -373,7 +382,7 @@ pub async fn test_short_term_encumberance() {
 
     db.clear_short_term_encumberances().unwrap();
 
-    let balance = db.get_balance(None).unwrap();
+    let balance = db.get_balance(None, None).unwrap();
     assert_eq!(
         balance.available_balance,
         unspent_outputs
@@ -387,7 +396,7 @@ pub async fn test_short_term_encumberance() {
     db.confirm_encumbered_outputs(TxId::from(2u64)).unwrap();
     db.clear_short_term_encumberances().unwrap();
 
-    let balance = db.get_balance(None).unwrap();
+    let balance = db.get_balance(None, None).unwrap();
     assert_eq!(
         balance.available_balance,
         unspent_outputs[3..5]
@@ -470,3 +479,109 @@ pub async fn test_mark_as_unmined() {
     assert!(o.mined_height.is_none());
     assert!(o.mined_in_block.is_none());
 }
+
+#[tokio::test]
+pub async fn test_account_scoped_balance_and_selection() {
+    let (connection, _tempdir) = get_temp_sqlite_database_connection();
+    let backend = OutputManagerSqliteDatabase::new(connection);
+    let db = OutputManagerDatabase::new(backend);
+
+    let key_manager = create_memory_db_key_manager();
+    let savings = AccountId::from(1);
+    let mut default_outputs = Vec::new();
+    let mut savings_outputs = Vec::new();
+    for i in 0..4 {
+        let uo = make_input(
+            &mut OsRng,
+            MicroMinotari::from(100 + OsRng.next_u64() % 1000),
+            &OutputFeatures::default(),
+            &key_manager,
+        )
+        .await;
+        let mut kmo = DbWalletOutput::from_wallet_output(uo, &key_manager, None, OutputSource::Standard, None, None)
+            .await
+            .unwrap();
+        if i % 2 == 1 {
+            kmo = kmo.with_account_id(savings);
+        }
+        db.add_unspent_output(kmo.clone()).unwrap();
+        db.mark_output_as_unspent(kmo.hash).unwrap();
+        if kmo.account_id == savings {
+            savings_outputs.push(kmo);
+        } else {
+            default_outputs.push(kmo);
+        }
+    }
+
+    let sum = |outputs: &[DbWalletOutput]| {
+        outputs
+            .iter()
+            .fold(MicroMinotari::from(0), |acc, x| acc + x.wallet_output.value)
+    };
+    assert_eq!(
+        db.get_balance(None, None).unwrap().available_balance,
+        sum(&default_outputs) + sum(&savings_outputs)
+    );
+    assert_eq!(
+        db.get_balance(Some(AccountId::DEFAULT), None)
+            .unwrap()
+            .available_balance,
+        sum(&default_outputs)
+    );
+    assert_eq!(
+        db.get_balance(Some(savings), None).unwrap().available_balance,
+        sum(&savings_outputs)
+    );
+
+    let selected = db
+        .fetch_unspent_outputs_for_spending(
+            &UtxoSelectionCriteria::default().for_account(savings),
+            MicroMinotari::from(1),
+            None,
+        )
+        .unwrap();
+    assert_eq!(selected.len(), savings_outputs.len());
+    assert!(selected.iter().all(|o| o.account_id == savings));
+
+    let fetched = db.fetch_by_commitment(savings_outputs[0].commitment.clone()).unwrap();
+    assert_eq!(fetched.account_id, savings);
+}
+
+#[tokio::test]
+pub async fn test_subaddress_storage() {
+    let (connection, _tempdir) = get_temp_sqlite_database_connection();
+    let backend = OutputManagerSqliteDatabase::new(connection);
+    let db = OutputManagerDatabase::new(backend);
+
+    let mut subaddresses = Vec::new();
+    for index in (0..3u64).rev() {
+        let subaddress = Subaddress {
+            index,
+            spend_public_key: PublicKey::random_keypair(&mut OsRng).1,
+            view_public_key: PublicKey::random_keypair(&mut OsRng).1,
+            label: format!("customer {}", index),
+            created_at: Utc::now().naive_utc(),
+        };
+        db.add_subaddress(subaddress.clone()).unwrap();
+        subaddresses.push(subaddress);
+    }
+    subaddresses.reverse();
+
+    let stored = db.get_subaddresses().unwrap();
+    assert_eq!(stored.len(), 3);
+    for (stored, expected) in stored.iter().zip(subaddresses.iter()) {
+        assert_eq!(stored.index, expected.index);
+        assert_eq!(stored.spend_public_key, expected.spend_public_key);
+        assert_eq!(stored.view_public_key, expected.view_public_key);
+        assert_eq!(stored.label, expected.label);
+    }
+
+    assert!(matches!(
+        db.add_subaddress(subaddresses[1].clone()),
+        Err(OutputManagerStorageError::DuplicateSubaddress)
+    ));
+
+    db.set_subaddress_label(1, "cold storage").unwrap();
+    assert_eq!(db.get_subaddresses().unwrap()[1].label, "cold storage");
+    assert!(db.set_subaddress_label(7, "missing").is_err());
+}
@@ -38,6 +38,7 @@ use futures::{
     SinkExt,
 };
 use minotari_wallet::{
+    accounts::AccountId,
     base_node_service::{config::BaseNodeServiceConfig, handle::BaseNodeServiceHandle, BaseNodeServiceInitializer},
     connectivity_service::{
         create_wallet_connectivity_mock,
@@ -2532,6 +2533,7 @@ async fn test_power_mode_updates() {
         mined_height: None,
         mined_in_block: None,
         mined_timestamp: None,
+        account_id: AccountId::DEFAULT,
     };
 
     let source_address = TariAddress::new(
@@ -2561,6 +2563,7 @@ async fn test_power_mode_updates() {
         mined_height: None,
         mined_in_block: None,
         mined_timestamp: None,
+        account_id: AccountId::DEFAULT,
     };
 
     tx_backend
@@ -3718,6 +3721,7 @@ async fn test_restarting_transaction_protocols() {
         direct_send_success: false,
         send_count: 0,
         last_send_timestamp: None,
+        account_id: AccountId::DEFAULT,
     };
 
     alice_backend
@@ -3741,6 +3745,7 @@ async fn test_restarting_transaction_protocols() {
         direct_send_success: false,
         send_count: 0,
         last_send_timestamp: None,
+        account_id: AccountId::DEFAULT,
     };
     bob_backend
         .write(WriteOperation::Insert(DbKeyValuePair::PendingOutboundTransaction(
@@ -4119,6 +4124,7 @@ async fn test_resend_on_startup() {
         direct_send_success: false,
         send_count: 1,
         last_send_timestamp: Some(Utc::now().naive_utc()),
+        account_id: AccountId::DEFAULT,
     };
     let (connection, _temp_dir) = make_wallet_database_connection(None);
 
@@ -4251,6 +4257,7 @@ async fn test_resend_on_startup() {
         direct_send_success: false,
         send_count: 0,
         last_send_timestamp: Some(Utc::now().naive_utc()),
+        account_id: AccountId::DEFAULT,
     };
     let (bob_connection, _temp_dir) = make_wallet_database_connection(None);
 
@@ -4636,6 +4643,7 @@ async fn test_transaction_timeout_cancellation() {
         direct_send_success: false,
         send_count: 1,
         last_send_timestamp: Some(Utc::now().naive_utc()),
+        account_id: AccountId::DEFAULT,
     };
     let (bob_connection, _temp_dir) = make_wallet_database_connection(None);
 
@@ -5172,6 +5180,7 @@ async fn broadcast_all_completed_transactions_on_startup() {
         mined_height: None,
         mined_in_block: None,
         mined_timestamp: None,
+        account_id: AccountId::DEFAULT,
     };
 
     let completed_tx2 = CompletedTransaction {
@@ -21,23 +21,36 @@
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 use core::default::Default;
-use std::mem::size_of;
+use std::{mem::size_of, time::Duration};
 
 use chacha20poly1305::{Key, KeyInit, XChaCha20Poly1305};
 use chrono::{NaiveDateTime, Utc};
 use minotari_wallet::{
+    accounts::AccountId,
+    output_manager_service::error::OutputManagerError,
     storage::sqlite_utilities::run_migration_and_create_sqlite_connection,
     test_utils::create_consensus_constants,
-    transaction_service::storage::{
-        database::{DbKeyValuePair, TransactionBackend, TransactionDatabase, WriteOperation},
-        models::{
-            CompletedTransaction,
-            InboundTransaction,
-            OutboundTransaction,
-            TxCancellationReason,
-            WalletTransaction,
+    transaction_service::{
+        batch_payout::BatchPayoutRecipient,
+        error::{TransactionServiceError, TransactionStorageError},
+        scheduled_payment::PaymentCadence,
+        storage::{
+            database::{DbKeyValuePair, TransactionBackend, TransactionDatabase, WriteOperation},
+            models::{
+                BatchPayoutRow,
+                BatchPayoutStatus,
+                CompletedTransaction,
+                InboundTransaction,
+                Invoice,
+                InvoiceStatus,
+                OutboundTransaction,
+                ScheduledPayment,
+                ScheduledPaymentStatus,
+                TxCancellationReason,
+                WalletTransaction,
+            },
+            sqlite_db::TransactionServiceSqliteDatabase,
         },
-        sqlite_db::TransactionServiceSqliteDatabase,
     },
 };
 use rand::{rngs::OsRng, RngCore};
@@ -143,6 +156,7 @@ pub async fn test_db_backend<T: TransactionBackend + 'static>(backend: T) {
             direct_send_success: false,
             send_count: 0,
             last_send_timestamp: None,
+            account_id: AccountId::DEFAULT,
         });
         assert!(!db.transaction_exists(tx_id).unwrap(), "TxId should not exist");
 
@@ -247,6 +261,7 @@ pub async fn test_db_backend<T: TransactionBackend + 'static>(backend: T) {
             direct_send_success: false,
             send_count: 0,
             last_send_timestamp: None,
+            account_id: AccountId::DEFAULT,
         });
         assert!(!db.transaction_exists(tx_id).unwrap(), "TxId should not exist");
         db.add_pending_inbound_transaction(tx_id, inbound_txs[i].clone())
@@ -331,6 +346,7 @@ pub async fn test_db_backend<T: TransactionBackend + 'static>(backend: T) {
             mined_height: None,
             mined_in_block: None,
             mined_timestamp: None,
+            account_id: AccountId::DEFAULT,
         });
         db.complete_outbound_transaction(outbound_txs[i].tx_id, completed_txs[i].clone())
             .unwrap();
@@ -358,6 +374,19 @@ pub async fn test_db_backend<T: TransactionBackend + 'static>(backend: T) {
         );
     }
 
+    let (page, total) = db.get_completed_transactions_page(None, 1, 2).unwrap();
+    assert_eq!(total, 2 * messages.len() as u64);
+    assert_eq!(page.len(), 2);
+    let (all, _) = db.get_completed_transactions_page(None, 0, u64::MAX).unwrap();
+    assert_eq!(all.len(), 2 * messages.len());
+    assert_eq!(&all[1..3], page.as_slice());
+    assert!(all.windows(2).all(|txs| txs[0].timestamp >= txs[1].timestamp));
+    let (page, total) = db
+        .get_completed_transactions_page(Some(AccountId::from(1)), 0, u64::MAX)
+        .unwrap();
+    assert!(page.is_empty());
+    assert_eq!(total, 0);
+
     db.increment_send_count(completed_txs[0].tx_id).unwrap();
     db.increment_send_count(completed_txs[0].tx_id).unwrap();
     let retrieved_completed_tx = db.get_completed_transaction(completed_txs[0].tx_id).unwrap();
@@ -417,6 +446,8 @@ pub async fn test_db_backend<T: TransactionBackend + 'static>(backend: T) {
     } else {
         panic!("Should have found cancelled completed tx");
     }
+    let history = db.get_transaction_status_history(cancelled_tx_id).unwrap();
+    assert_eq!(history.last().unwrap().cancelled, Some(TxCancellationReason::Unknown));
     let address = TariAddress::new(
         PublicKey::from_secret_key(&PrivateKey::random(&mut OsRng)),
         Network::LocalNet,
@@ -524,6 +555,12 @@ pub async fn test_db_backend<T: TransactionBackend + 'static>(backend: T) {
         panic!("Should have found cancelled outbound tx");
     }
 
+    let history = db.get_transaction_status_history(998u64.into()).unwrap();
+    assert_eq!(history.len(), 2);
+    assert!(history.iter().all(|change| change.status == TransactionStatus::Pending));
+    assert_eq!(history[0].cancelled, None);
+    assert_eq!(history[1].cancelled, Some(TxCancellationReason::Unknown));
+
     // Transactions with empty kernel signatures should not be returned with this method, as those will be considered
     // as faux transactions (imported or one-sided)
     let unmined_txs = db.fetch_unconfirmed_transactions_info().unwrap();
@@ -664,3 +701,230 @@ async fn import_tx_and_read_it_from_db() {
     assert_eq!(db_tx.first().unwrap().tx_id, TxId::from(3u64));
     assert_eq!(db_tx.first().unwrap().mined_height, Some(7));
 }
+
+#[tokio::test]
+async fn test_invoice_storage() {
+    let db_name = format!("{}.sqlite3", random::string(8));
+    let db_tempdir = tempdir().unwrap();
+    let db_folder = db_tempdir.path().to_str().unwrap().to_string();
+    let db_path = format!("{}/{}", db_folder, db_name);
+    let connection = run_migration_and_create_sqlite_connection(db_path, 16).unwrap();
+
+    let mut key = [0u8; size_of::<Key>()];
+    OsRng.fill_bytes(&mut key);
+    let key_ga = Key::from_slice(&key);
+    let cipher = XChaCha20Poly1305::new(key_ga);
+    let db = TransactionDatabase::new(TransactionServiceSqliteDatabase::new(connection, cipher));
+
+    let address = TariAddress::new(
+        PublicKey::from_secret_key(&PrivateKey::random(&mut OsRng)),
+        Network::LocalNet,
+    );
+    let now = Utc::now().naive_utc();
+    let invoice = Invoice::new(
+        "order-1".to_string(),
+        address.clone(),
+        MicroMinotari::from(10_000),
+        "Order 1".to_string(),
+        now,
+        Some(now + chrono::Duration::hours(1)),
+    );
+    db.add_invoice(invoice.clone()).unwrap();
+    assert!(matches!(
+        db.add_invoice(invoice.clone()),
+        Err(TransactionStorageError::InvoiceAlreadyExists(_))
+    ));
+    assert_eq!(db.get_invoice("order-1").unwrap(), invoice);
+    assert_eq!(db.get_invoice_by_address(&address).unwrap(), Some(invoice.clone()));
+    assert!(matches!(
+        db.get_invoice("order-2"),
+        Err(TransactionStorageError::InvoiceNotFound(_))
+    ));
+
+    let mut invoice = invoice;
+    assert_eq!(
+        invoice.apply_payment(TxId::from(1u64), MicroMinotari::from(4_000), now),
+        InvoiceStatus::PartiallyPaid
+    );
+    db.update_invoice(&invoice).unwrap();
+    assert_eq!(db.get_invoice("order-1").unwrap(), invoice);
+
+    assert_eq!(
+        invoice.apply_payment(TxId::from(2u64), MicroMinotari::from(6_000), now),
+        InvoiceStatus::Paid
+    );
+    db.update_invoice(&invoice).unwrap();
+    let stored = db.get_invoice("order-1").unwrap();
+    assert_eq!(stored.amount_received, MicroMinotari::from(10_000));
+    assert_eq!(stored.tx_id, Some(TxId::from(2u64)));
+    assert_eq!(stored.paid_at, Some(now));
+    assert_eq!(db.get_invoices().unwrap(), vec![stored]);
+}
+
+#[tokio::test]
+async fn test_batch_payout_storage() {
+    let db_name = format!("{}.sqlite3", random::string(8));
+    let db_tempdir = tempdir().unwrap();
+    let db_folder = db_tempdir.path().to_str().unwrap().to_string();
+    let db_path = format!("{}/{}", db_folder, db_name);
+    let connection = run_migration_and_create_sqlite_connection(db_path, 16).unwrap();
+
+    let mut key = [0u8; size_of::<Key>()];
+    OsRng.fill_bytes(&mut key);
+    let key_ga = Key::from_slice(&key);
+    let cipher = XChaCha20Poly1305::new(key_ga);
+    let db = TransactionDatabase::new(TransactionServiceSqliteDatabase::new(connection, cipher));
+
+    let now = Utc::now().naive_utc();
+    let rows = (0..3u64)
+        .map(|i| {
+            let address = TariAddress::new(
+                PublicKey::from_secret_key(&PrivateKey::random(&mut OsRng)),
+                Network::LocalNet,
+            );
+            let recipient =
+                BatchPayoutRecipient::new(address, MicroMinotari::from(1_000 * (i + 1)), format!("Row {}", i));
+            BatchPayoutRow::new("payout-1".to_string(), i, recipient, now)
+        })
+        .collect::<Vec<_>>();
+    db.add_batch_payout(rows.clone()).unwrap();
+    assert!(matches!(
+        db.add_batch_payout(rows.clone()),
+        Err(TransactionStorageError::BatchPayoutAlreadyExists(_))
+    ));
+    assert_eq!(db.get_batch_payout("payout-1").unwrap(), rows);
+    assert!(db.get_batch_payout("payout-2").unwrap().is_empty());
+
+    let mut sent = rows[0].clone();
+    sent.mark_sent(TxId::from(1u64), now);
+    db.update_batch_payout_row(&sent).unwrap();
+    let mut in_flight = rows[1].clone();
+    in_flight.mark_in_flight(TxId::from(2u64), now);
+    db.update_batch_payout_row(&in_flight).unwrap();
+    let mut failed = rows[2].clone();
+    failed.mark_failed("Not enough funds".to_string(), now);
+    db.update_batch_payout_row(&failed).unwrap();
+
+    let stored = db.get_batch_payout("payout-1").unwrap();
+    assert_eq!(stored[0].status, BatchPayoutStatus::Sent);
+    assert_eq!(stored[0].tx_id, Some(TxId::from(1u64)));
+    assert_eq!(stored[1].status, BatchPayoutStatus::Sending);
+    assert_eq!(stored[1].tx_id, Some(TxId::from(2u64)));
+
+    in_flight.status = BatchPayoutStatus::Pending;
+    in_flight.tx_id = None;
+    db.update_batch_payout_row(&in_flight).unwrap();
+    let stored = db.get_batch_payout("payout-1").unwrap();
+    assert_eq!(stored[1].status, BatchPayoutStatus::Pending);
+    assert_eq!(stored[2].status, BatchPayoutStatus::Failed);
+    assert_eq!(stored[2].error, Some("Not enough funds".to_string()));
+    assert_eq!(stored[2].tx_id, None);
+
+    let mut missing = rows[0].clone();
+    missing.batch_id = "payout-2".to_string();
+    assert!(db.update_batch_payout_row(&missing).is_err());
+}
+
+#[tokio::test]
+async fn test_scheduled_payment_storage() {
+    let db_name = format!("{}.sqlite3", random::string(8));
+    let db_tempdir = tempdir().unwrap();
+    let db_folder = db_tempdir.path().to_str().unwrap().to_string();
+    let db_path = format!("{}/{}", db_folder, db_name);
+    let connection = run_migration_and_create_sqlite_connection(db_path, 16).unwrap();
+
+    let mut key = [0u8; size_of::<Key>()];
+    OsRng.fill_bytes(&mut key);
+    let key_ga = Key::from_slice(&key);
+    let cipher = XChaCha20Poly1305::new(key_ga);
+    let db = TransactionDatabase::new(TransactionServiceSqliteDatabase::new(connection, cipher));
+
+    let now = Utc::now().naive_utc();
+    let address = TariAddress::new(
+        PublicKey::from_secret_key(&PrivateKey::random(&mut OsRng)),
+        Network::LocalNet,
+    );
+    let payment = ScheduledPayment::new(
+        "salary-1".to_string(),
+        address,
+        MicroMinotari::from(10_000),
+        "Salary".to_string(),
+        AccountId::from(1),
+        MicroMinotari::from(5),
+        PaymentCadence::Daily,
+        now - chrono::Duration::days(3),
+        None,
+        Some(2),
+        now,
+    );
+    db.add_scheduled_payment(payment.clone()).unwrap();
+    assert!(matches!(
+        db.add_scheduled_payment(payment.clone()),
+        Err(TransactionStorageError::ScheduledPaymentAlreadyExists(_))
+    ));
+    assert_eq!(db.get_scheduled_payment("salary-1").unwrap(), payment);
+    assert!(matches!(
+        db.get_scheduled_payment("salary-2"),
+        Err(TransactionStorageError::ScheduledPaymentNotFound(_))
+    ));
+    assert_eq!(db.get_due_scheduled_payments(now).unwrap(), vec![payment.clone()]);
+
+    // The transaction is recorded before it is sent so that an interrupted payment can be reconciled
+    let mut in_flight = payment.clone();
+    in_flight.mark_in_flight(TxId::from(1u64), now);
+    db.update_scheduled_payment(&in_flight).unwrap();
+    assert_eq!(
+        db.get_scheduled_payment("salary-1").unwrap().pending_tx_id,
+        Some(TxId::from(1u64))
+    );
+
+    // The missed occurrences are paid once, the next payment is the next future occurrence
+    let mut paid = in_flight;
+    paid.record_payment(TxId::from(1u64), now);
+    db.update_scheduled_payment(&paid).unwrap();
+    let stored = db.get_scheduled_payment("salary-1").unwrap();
+    assert_eq!(stored.payments_made, 1);
+    assert_eq!(stored.occurrence, 4);
+    assert_eq!(stored.next_run_at, payment.start_at + chrono::Duration::days(4));
+    assert_eq!(stored.last_tx_id, Some(TxId::from(1u64)));
+    assert_eq!(stored.pending_tx_id, None);
+    assert!(db.get_due_scheduled_payments(now).unwrap().is_empty());
+
+    // A wallet without enough funds may be topped up, so the payment is retried up to the retry limit
+    let not_enough_funds = TransactionServiceError::from(OutputManagerError::NotEnoughFunds);
+    assert!(not_enough_funds.is_transient());
+    let mut failed = stored.clone();
+    let retry_at = stored.next_run_at;
+    assert!(failed.record_failure(not_enough_funds.to_string(), 1, Duration::from_secs(60), retry_at));
+    assert_eq!(failed.next_run_at, retry_at + chrono::Duration::seconds(60));
+    assert!(!failed.record_failure(not_enough_funds.to_string(), 1, Duration::from_secs(60), retry_at));
+    assert_eq!(failed.occurrence, 5);
+    assert_eq!(failed.failed_attempts, 0);
+    db.update_scheduled_payment(&failed).unwrap();
+    let stored = db.get_scheduled_payment("salary-1").unwrap();
+    assert_eq!(stored.last_error, Some(not_enough_funds.to_string()));
+    assert_eq!(stored.status, ScheduledPaymentStatus::Active);
+
+    // A payment that can never succeed skips the occurrence without being retried
+    let invalid = TransactionServiceError::InvalidScheduledPayment("Invalid network".to_string());
+    assert!(!invalid.is_transient());
+    let mut rejected = stored.clone();
+    rejected.record_rejection(invalid.to_string(), stored.next_run_at);
+    assert_eq!(rejected.occurrence, stored.occurrence + 1);
+    assert_eq!(rejected.failed_attempts, 0);
+    assert_eq!(rejected.status, ScheduledPaymentStatus::Active);
+
+    let mut completed = stored;
+    completed.record_payment(TxId::from(2u64), completed.next_run_at);
+    assert_eq!(completed.status, ScheduledPaymentStatus::Completed);
+    db.update_scheduled_payment(&completed).unwrap();
+    assert_eq!(db.get_scheduled_payments().unwrap(), vec![completed.clone()]);
+    assert!(db
+        .get_due_scheduled_payments(now + chrono::Duration::days(30))
+        .unwrap()
+        .is_empty());
+
+    let mut missing = completed;
+    missing.schedule_id = "salary-2".to_string();
+    assert!(db.update_scheduled_payment(&missing).is_err());
+}
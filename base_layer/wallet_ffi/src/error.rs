@@ -322,6 +322,14 @@ impl From<WalletError> for LibWalletError {
                 code: 434,
                 message: format!("{:?}", w),
             },
+            WalletError::WalletStorageError(WalletStorageError::AccountAlreadyExists(_)) => Self {
+                code: 435,
+                message: format!("{:?}", w),
+            },
+            WalletError::WalletStorageError(WalletStorageError::AccountNotFound(_)) => Self {
+                code: 436,
+                message: format!("{:?}", w),
+            },
             // these are general catch errors to try and reduce 999 when we get it with zero additional logging
             WalletError::SetLoggerError(_) => Self {
                 code: 994,
@@ -81,6 +81,7 @@ use log4rs::{
     encode::pattern::PatternEncoder,
 };
 use minotari_wallet::{
+    accounts::AccountId,
     base_node_service::config::BaseNodeServiceConfig,
     connectivity_service::{WalletConnectivityHandle, WalletConnectivityInterface},
     error::{WalletError, WalletStorageError},
@@ -4838,6 +4839,7 @@ pub unsafe extern "C" fn comms_config_create(
                 public_addresses: addresses,
                 transport: (*transport).clone(),
                 auxiliary_tcp_listener_address: None,
+                quic_listener_address: None,
                 datastore_path,
                 peer_database_name: database_name_string,
                 max_concurrent_inbound_tasks: 25,
@@ -4860,6 +4862,20 @@ pub unsafe extern "C" fn comms_config_create(
                 user_agent: format!("tari/mobile_wallet/{}", env!("CARGO_PKG_VERSION")),
                 rpc_max_simultaneous_sessions: 0,
                 rpc_max_sessions_per_peer: 0,
+                rpc_max_requests_per_minute_per_peer: None,
+                rpc_max_concurrent_requests_per_peer: None,
+                rpc_max_streamed_bytes_per_minute_per_peer: None,
+                rpc_max_concurrent_streams: None,
+                reputation_ban_threshold: -100.0,
+                reputation_decay_half_life: Duration::from_secs(30 * 60),
+                reputation_ban_duration: Duration::from_secs(10 * 60),
+                max_upload_rate: None,
+                max_download_rate: None,
+                max_peer_upload_rate: None,
+                max_peer_download_rate: None,
+                enable_port_mapping: false,
+                nat_gateway_address: None,
+                advertise_observed_address: false,
                 listener_liveness_check_interval: None,
             };
 
@@ -7506,6 +7522,226 @@ pub unsafe extern "C" fn wallet_get_tari_address(
     Box::into_raw(Box::new(address))
 }
 
+/// Creates a new named account in a TariWallet. Each account has its own keys, address, balance and transaction
+/// history.
+///
+/// ## Arguments
+/// `wallet` - The TariWallet pointer
+/// `name` - The pointer to a char array containing the unique account name
+/// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+/// as an out parameter.
+///
+/// ## Returns
+/// `c_uint` - Returns the id of the new account, 0 is returned if an error occurred
+///
+/// # Safety
+/// None
+#[no_mangle]
+pub unsafe extern "C" fn wallet_create_account(
+    wallet: *mut TariWallet,
+    name: *const c_char,
+    error_out: *mut c_int,
+) -> c_uint {
+    let mut error = 0;
+    ptr::swap(error_out, &mut error as *mut c_int);
+    if wallet.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("wallet".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return 0;
+    }
+    if name.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("name".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return 0;
+    }
+    let name = match CStr::from_ptr(name).to_str() {
+        Ok(v) if !v.is_empty() => v.to_owned(),
+        _ => {
+            error = LibWalletError::from(InterfaceError::InvalidArgument("name".to_string())).code;
+            ptr::swap(error_out, &mut error as *mut c_int);
+            return 0;
+        },
+    };
+
+    match (*wallet).runtime.block_on((*wallet).wallet.create_account(&name)) {
+        Ok(account) => account.id.as_u32(),
+        Err(e) => {
+            error = LibWalletError::from(e).code;
+            ptr::swap(error_out, &mut error as *mut c_int);
+            0
+        },
+    }
+}
+
+/// Get the TariWalletAddress of an account in a TariWallet. One-sided payments to this address are credited to the
+/// account.
+///
+/// ## Arguments
+/// `wallet` - The TariWallet pointer
+/// `account_id` - The id of the account, 0 is the default account
+/// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+/// as an out parameter.
+///
+/// ## Returns
+/// `*mut TariWalletAddress` - returns the address, note that ptr::null_mut() is returned if an error occurred
+///
+/// # Safety
+/// The ```tari_address_destroy``` method must be called when finished with a TariWalletAddress to prevent a memory leak
+#[no_mangle]
+pub unsafe extern "C" fn wallet_get_account_address(
+    wallet: *mut TariWallet,
+    account_id: c_uint,
+    error_out: *mut c_int,
+) -> *mut TariWalletAddress {
+    let mut error = 0;
+    ptr::swap(error_out, &mut error as *mut c_int);
+    if wallet.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("wallet".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return ptr::null_mut();
+    }
+    match (*wallet)
+        .runtime
+        .block_on((*wallet).wallet.get_account_address(AccountId::from(account_id)))
+    {
+        Ok(address) => Box::into_raw(Box::new(address)),
+        Err(e) => {
+            error = LibWalletError::from(e).code;
+            ptr::swap(error_out, &mut error as *mut c_int);
+            ptr::null_mut()
+        },
+    }
+}
+
+/// Retrieves the balance of an account in a wallet
+///
+/// ## Arguments
+/// `wallet` - The TariWallet pointer.
+/// `account_id` - The id of the account, 0 is the default account
+/// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+/// as an out parameter.
+/// ## Returns
+/// `*mut Balance` - Returns the pointer to the TariBalance or null if error occurs
+///
+/// # Safety
+/// The ```balance_destroy``` method must be called when finished with a TariBalance to prevent a memory leak
+#[no_mangle]
+pub unsafe extern "C" fn wallet_get_account_balance(
+    wallet: *mut TariWallet,
+    account_id: c_uint,
+    error_out: *mut c_int,
+) -> *mut TariBalance {
+    let mut error = 0;
+    ptr::swap(error_out, &mut error as *mut c_int);
+    if wallet.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("wallet".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return ptr::null_mut();
+    }
+    let balance = (*wallet).runtime.block_on(
+        (*wallet)
+            .wallet
+            .output_manager_service
+            .get_account_balance(AccountId::from(account_id)),
+    );
+    match balance {
+        Ok(balance) => Box::into_raw(Box::new(balance)),
+        Err(_) => {
+            error = LibWalletError::from(InterfaceError::BalanceError).code;
+            ptr::swap(error_out, &mut error as *mut c_int);
+            ptr::null_mut()
+        },
+    }
+}
+
+/// Sends a transaction that is funded from the outputs of the given account. Any change is returned to the same
+/// account.
+///
+/// ## Arguments
+/// `wallet` - The TariWallet pointer
+/// `account_id` - The id of the account to spend from, 0 is the default account
+/// `destination` - The TariWalletAddress pointer of the peer
+/// `amount` - The amount
+/// `fee_per_gram` - The transaction fee
+/// `message` - The pointer to a char array
+/// `one_sided` - Whether to send a one-sided transaction to the stealth address of the destination
+/// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+/// as an out parameter.
+///
+/// ## Returns
+/// `unsigned long long` - Returns 0 if unsuccessful or the TxId of the sent transaction if successful
+///
+/// # Safety
+/// None
+#[no_mangle]
+pub unsafe extern "C" fn wallet_send_transaction_from_account(
+    wallet: *mut TariWallet,
+    account_id: c_uint,
+    destination: *mut TariWalletAddress,
+    amount: c_ulonglong,
+    fee_per_gram: c_ulonglong,
+    message: *const c_char,
+    one_sided: bool,
+    error_out: *mut c_int,
+) -> c_ulonglong {
+    let mut error = 0;
+    ptr::swap(error_out, &mut error as *mut c_int);
+    if wallet.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("wallet".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return 0;
+    }
+    if destination.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("destination".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return 0;
+    }
+    let message_string = if message.is_null() {
+        String::new()
+    } else {
+        match CStr::from_ptr(message).to_str() {
+            Ok(v) => v.to_owned(),
+            _ => {
+                error = LibWalletError::from(InterfaceError::InvalidArgument("message".to_string())).code;
+                ptr::swap(error_out, &mut error as *mut c_int);
+                return 0;
+            },
+        }
+    };
+
+    let selection_criteria = UtxoSelectionCriteria::default().for_account(AccountId::from(account_id));
+    let mut transaction_service = (*wallet).wallet.transaction_service.clone();
+    let result = if one_sided {
+        (*wallet)
+            .runtime
+            .block_on(transaction_service.send_one_sided_to_stealth_address_transaction(
+                (*destination).clone(),
+                MicroMinotari::from(amount),
+                selection_criteria,
+                OutputFeatures::default(),
+                MicroMinotari::from(fee_per_gram),
+                message_string,
+            ))
+    } else {
+        (*wallet).runtime.block_on(transaction_service.send_transaction(
+            (*destination).clone(),
+            MicroMinotari::from(amount),
+            selection_criteria,
+            OutputFeatures::default(),
+            MicroMinotari::from(fee_per_gram),
+            message_string,
+        ))
+    };
+    match result {
+        Ok(tx_id) => tx_id.as_u64(),
+        Err(e) => {
+            error = LibWalletError::from(WalletError::TransactionServiceError(e)).code;
+            ptr::swap(error_out, &mut error as *mut c_int);
+            0
+        },
+    }
+}
+
 /// Cancel a Pending Transaction
 ///
 /// ## Arguments
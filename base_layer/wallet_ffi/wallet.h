@@ -3415,6 +3415,94 @@ TariCompletedTransaction *wallet_get_cancelled_transaction_by_id(struct TariWall
 TariWalletAddress *wallet_get_tari_address(struct TariWallet *wallet,
                                            int *error_out);
 
+/**
+ * Creates a new named account in a TariWallet. Each account has its own keys, address, balance and transaction
+ * history.
+ *
+ * ## Arguments
+ * `wallet` - The TariWallet pointer
+ * `name` - The pointer to a char array containing the unique account name
+ * `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+ * as an out parameter.
+ *
+ * ## Returns
+ * `c_uint` - Returns the id of the new account, 0 is returned if an error occurred
+ *
+ * # Safety
+ * None
+ */
+unsigned int wallet_create_account(struct TariWallet *wallet,
+                                   const char *name,
+                                   int *error_out);
+
+/**
+ * Get the TariWalletAddress of an account in a TariWallet. One-sided payments to this address are credited to the
+ * account.
+ *
+ * ## Arguments
+ * `wallet` - The TariWallet pointer
+ * `account_id` - The id of the account, 0 is the default account
+ * `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+ * as an out parameter.
+ *
+ * ## Returns
+ * `*mut TariWalletAddress` - returns the address, note that ptr::null_mut() is returned if an error occurred
+ *
+ * # Safety
+ * The ```tari_address_destroy``` method must be called when finished with a TariWalletAddress to prevent a memory leak
+ */
+TariWalletAddress *wallet_get_account_address(struct TariWallet *wallet,
+                                              unsigned int account_id,
+                                              int *error_out);
+
+/**
+ * Retrieves the balance of an account in a wallet
+ *
+ * ## Arguments
+ * `wallet` - The TariWallet pointer.
+ * `account_id` - The id of the account, 0 is the default account
+ * `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+ * as an out parameter.
+ * ## Returns
+ * `*mut Balance` - Returns the pointer to the TariBalance or null if error occurs
+ *
+ * # Safety
+ * The ```balance_destroy``` method must be called when finished with a TariBalance to prevent a memory leak
+ */
+TariBalance *wallet_get_account_balance(struct TariWallet *wallet,
+                                        unsigned int account_id,
+                                        int *error_out);
+
+/**
+ * Sends a transaction that is funded from the outputs of the given account. Any change is returned to the same
+ * account.
+ *
+ * ## Arguments
+ * `wallet` - The TariWallet pointer
+ * `account_id` - The id of the account to spend from, 0 is the default account
+ * `destination` - The TariWalletAddress pointer of the peer
+ * `amount` - The amount
+ * `fee_per_gram` - The transaction fee
+ * `message` - The pointer to a char array
+ * `one_sided` - Whether to send a one-sided transaction to the stealth address of the destination
+ * `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+ * as an out parameter.
+ *
+ * ## Returns
+ * `unsigned long long` - Returns 0 if unsuccessful or the TxId of the sent transaction if successful
+ *
+ * # Safety
+ * None
+ */
+unsigned long long wallet_send_transaction_from_account(struct TariWallet *wallet,
+                                                        unsigned int account_id,
+                                                        TariWalletAddress *destination,
+                                                        unsigned long long amount,
+                                                        unsigned long long fee_per_gram,
+                                                        const char *message,
+                                                        bool one_sided,
+                                                        int *error_out);
+
 /**
  * Cancel a Pending Transaction
  *
@@ -0,0 +1,114 @@
+// Copyright 2024. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+/// A token bucket rate limiter that allows a burst of up to one second of traffic.
+///
+/// Transfers are recorded after they happen, so the balance may become negative. Callers wait for [RateLimiter::delay]
+/// before transferring more data, which keeps the average rate at the configured limit without having to split
+/// reads and writes.
+#[derive(Debug)]
+pub(super) struct RateLimiter {
+    bytes_per_sec: f64,
+    state: Mutex<BucketState>,
+}
+
+#[derive(Debug)]
+struct BucketState {
+    balance: f64,
+    last_refill: Instant,
+}
+
+impl RateLimiter {
+    pub fn new(bytes_per_sec: u64) -> Self {
+        // A zero rate would never refill
+        let bytes_per_sec = bytes_per_sec.max(1) as f64;
+        Self {
+            bytes_per_sec,
+            state: Mutex::new(BucketState {
+                balance: bytes_per_sec,
+                last_refill: Instant::now(),
+            }),
+        }
+    }
+
+    /// Records that `num_bytes` have been transferred
+    pub fn consume(&self, num_bytes: usize, now: Instant) {
+        let mut state = self.state.lock().expect("RateLimiter lock poisoned");
+        self.refill(&mut state, now);
+        state.balance -= num_bytes as f64;
+    }
+
+    /// Returns the time to wait before more bytes may be transferred, or None if bytes may be transferred now.
+    pub fn delay(&self, now: Instant) -> Option<Duration> {
+        let mut state = self.state.lock().expect("RateLimiter lock poisoned");
+        self.refill(&mut state, now);
+        if state.balance >= 0.0 {
+            return None;
+        }
+        Some(Duration::from_secs_f64(-state.balance / self.bytes_per_sec))
+    }
+
+    fn refill(&self, state: &mut BucketState, now: Instant) {
+        let elapsed = now.saturating_duration_since(state.last_refill);
+        state.balance = (state.balance + elapsed.as_secs_f64() * self.bytes_per_sec).min(self.bytes_per_sec);
+        state.last_refill = now;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn it_allows_a_one_second_burst() {
+        let limiter = RateLimiter::new(1000);
+        let now = Instant::now();
+        assert!(limiter.delay(now).is_none());
+        limiter.consume(1000, now);
+        assert!(limiter.delay(now).is_none());
+        limiter.consume(500, now);
+        assert_eq!(limiter.delay(now), Some(Duration::from_millis(500)));
+    }
+
+    #[test]
+    fn it_refills_over_time() {
+        let limiter = RateLimiter::new(1000);
+        let now = Instant::now();
+        limiter.consume(2000, now);
+        assert_eq!(limiter.delay(now), Some(Duration::from_secs(1)));
+        assert_eq!(
+            limiter.delay(now + Duration::from_millis(250)),
+            Some(Duration::from_millis(750))
+        );
+        assert!(limiter.delay(now + Duration::from_secs(1)).is_none());
+        // The balance never exceeds one second's worth of bytes
+        limiter.consume(1000, now + Duration::from_secs(10));
+        assert!(limiter.delay(now + Duration::from_secs(10)).is_none());
+        limiter.consume(1, now + Duration::from_secs(10));
+        assert!(limiter.delay(now + Duration::from_secs(10)).is_some());
+    }
+}
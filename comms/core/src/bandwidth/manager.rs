@@ -0,0 +1,254 @@
+// Copyright 2024. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{
+    collections::HashMap,
+    fmt,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+        Mutex,
+    },
+};
+
+use super::{config::BandwidthConfig, limiter::RateLimiter, meter::SubstreamMeter};
+use crate::{peer_manager::NodeId, protocol::ProtocolId};
+
+/// Idle peer entries are discarded once more than this number of peers are being tracked
+const MAX_TRACKED_PEERS: usize = 1000;
+
+/// Byte counts in each direction
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TrafficStats {
+    pub bytes_read: u64,
+    pub bytes_written: u64,
+}
+
+impl TrafficStats {
+    pub fn total(&self) -> u64 {
+        self.bytes_read.saturating_add(self.bytes_written)
+    }
+}
+
+/// Traffic exchanged with a single peer
+#[derive(Debug, Clone)]
+pub struct PeerTrafficStats {
+    pub node_id: NodeId,
+    pub stats: TrafficStats,
+    pub num_active_substreams: usize,
+}
+
+/// Traffic for a single protocol across all peers
+#[derive(Debug, Clone)]
+pub struct ProtocolTrafficStats {
+    pub protocol: ProtocolId,
+    pub stats: TrafficStats,
+}
+
+#[derive(Debug, Default)]
+pub(super) struct TrafficCounters {
+    bytes_read: AtomicU64,
+    bytes_written: AtomicU64,
+}
+
+impl TrafficCounters {
+    pub fn add_read(&self, num_bytes: usize) {
+        self.bytes_read.fetch_add(num_bytes as u64, Ordering::Relaxed);
+    }
+
+    pub fn add_written(&self, num_bytes: usize) {
+        self.bytes_written.fetch_add(num_bytes as u64, Ordering::Relaxed);
+    }
+
+    pub fn stats(&self) -> TrafficStats {
+        TrafficStats {
+            bytes_read: self.bytes_read.load(Ordering::Relaxed),
+            bytes_written: self.bytes_written.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Traffic counters and rate limits for a single peer, shared by all of the peer's substream meters
+#[derive(Debug)]
+pub(super) struct PeerBandwidth {
+    pub counters: TrafficCounters,
+    pub upload_limiter: Option<RateLimiter>,
+    pub download_limiter: Option<RateLimiter>,
+}
+
+/// Global traffic counters and rate limits
+#[derive(Debug)]
+pub(super) struct GlobalBandwidth {
+    pub counters: TrafficCounters,
+    pub upload_limiter: Option<RateLimiter>,
+    pub download_limiter: Option<RateLimiter>,
+}
+
+/// Keeps track of traffic per peer and per protocol, and hands out [SubstreamMeter]s that enforce the configured rate
+/// limits. This is a cheaply cloneable handle.
+#[derive(Clone)]
+pub struct BandwidthManager {
+    config: BandwidthConfig,
+    global: Arc<GlobalBandwidth>,
+    peers: Arc<Mutex<HashMap<NodeId, Arc<PeerBandwidth>>>>,
+    protocols: Arc<Mutex<HashMap<ProtocolId, Arc<TrafficCounters>>>>,
+}
+
+impl BandwidthManager {
+    pub fn new(config: BandwidthConfig) -> Self {
+        Self {
+            config,
+            global: Arc::new(GlobalBandwidth {
+                counters: TrafficCounters::default(),
+                upload_limiter: config.max_upload_rate.map(RateLimiter::new),
+                download_limiter: config.max_download_rate.map(RateLimiter::new),
+            }),
+            peers: Default::default(),
+            protocols: Default::default(),
+        }
+    }
+
+    pub fn config(&self) -> &BandwidthConfig {
+        &self.config
+    }
+
+    /// Returns the total traffic across all peers and protocols
+    pub fn total_traffic(&self) -> TrafficStats {
+        self.global.counters.stats()
+    }
+
+    /// Returns the `n` peers that have exchanged the most bytes with this node, ordered from most to least
+    pub fn top_peers(&self, n: usize) -> Vec<PeerTrafficStats> {
+        let peers = self.peers.lock().expect("BandwidthManager lock poisoned");
+        let mut stats = peers
+            .iter()
+            .map(|(node_id, peer)| PeerTrafficStats {
+                node_id: node_id.clone(),
+                stats: peer.counters.stats(),
+                // One reference is held by this map, the rest by substream meters
+                num_active_substreams: Arc::strong_count(peer) - 1,
+            })
+            .collect::<Vec<_>>();
+        stats.sort_by(|a, b| b.stats.total().cmp(&a.stats.total()));
+        stats.truncate(n);
+        stats
+    }
+
+    /// Returns the traffic for each protocol, ordered from most to least bytes
+    pub fn protocol_traffic(&self) -> Vec<ProtocolTrafficStats> {
+        let protocols = self.protocols.lock().expect("BandwidthManager lock poisoned");
+        let mut stats = protocols
+            .iter()
+            .map(|(protocol, counters)| ProtocolTrafficStats {
+                protocol: protocol.clone(),
+                stats: counters.stats(),
+            })
+            .collect::<Vec<_>>();
+        stats.sort_by(|a, b| b.stats.total().cmp(&a.stats.total()));
+        stats
+    }
+
+    /// Returns a meter for a new substream to the given peer on which the given protocol was negotiated
+    pub(crate) fn meter(&self, node_id: &NodeId, protocol: &ProtocolId) -> SubstreamMeter {
+        let peer = {
+            let mut peers = self.peers.lock().expect("BandwidthManager lock poisoned");
+            if peers.len() >= MAX_TRACKED_PEERS && !peers.contains_key(node_id) {
+                peers.retain(|_, peer| Arc::strong_count(peer) > 1);
+            }
+            peers
+                .entry(node_id.clone())
+                .or_insert_with(|| {
+                    Arc::new(PeerBandwidth {
+                        counters: TrafficCounters::default(),
+                        upload_limiter: self.config.max_peer_upload_rate.map(RateLimiter::new),
+                        download_limiter: self.config.max_peer_download_rate.map(RateLimiter::new),
+                    })
+                })
+                .clone()
+        };
+        let protocol = self
+            .protocols
+            .lock()
+            .expect("BandwidthManager lock poisoned")
+            .entry(protocol.clone())
+            .or_default()
+            .clone();
+
+        SubstreamMeter::new(self.global.clone(), peer, protocol)
+    }
+}
+
+impl Default for BandwidthManager {
+    fn default() -> Self {
+        Self::new(BandwidthConfig::default())
+    }
+}
+
+impl fmt::Debug for BandwidthManager {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BandwidthManager")
+            .field("config", &self.config)
+            .field("total_traffic", &self.total_traffic())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{peer_manager::PeerFeatures, test_utils::node_identity::build_node_identity};
+
+    #[test]
+    fn it_accounts_traffic_per_peer_and_protocol() {
+        let manager = BandwidthManager::default();
+        let peer1 = build_node_identity(PeerFeatures::COMMUNICATION_NODE).node_id().clone();
+        let peer2 = build_node_identity(PeerFeatures::COMMUNICATION_NODE).node_id().clone();
+        let messaging = ProtocolId::from_static(b"t/msg/0.1");
+        let rpc = ProtocolId::from_static(b"t/rpc/1");
+
+        let meter1 = manager.meter(&peer1, &messaging);
+        let meter2 = manager.meter(&peer2, &rpc);
+        meter1.record_read(100);
+        meter1.record_written(50);
+        meter2.record_written(500);
+
+        assert_eq!(manager.total_traffic(), TrafficStats {
+            bytes_read: 100,
+            bytes_written: 550,
+        });
+        let peers = manager.top_peers(10);
+        assert_eq!(peers.len(), 2);
+        assert_eq!(peers[0].node_id, peer2);
+        assert_eq!(peers[0].stats.total(), 500);
+        assert_eq!(peers[0].num_active_substreams, 1);
+        assert_eq!(peers[1].node_id, peer1);
+        assert_eq!(manager.top_peers(1).len(), 1);
+
+        let protocols = manager.protocol_traffic();
+        assert_eq!(protocols[0].protocol, rpc);
+        assert_eq!(protocols[1].protocol, messaging);
+        assert_eq!(protocols[1].stats.bytes_read, 100);
+
+        drop(meter1);
+        assert_eq!(manager.top_peers(10)[1].num_active_substreams, 0);
+    }
+}
@@ -0,0 +1,127 @@
+// Copyright 2024. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+    time::Instant,
+};
+
+use futures::ready;
+use tokio::{time, time::Sleep};
+
+use super::{
+    limiter::RateLimiter,
+    manager::{GlobalBandwidth, PeerBandwidth, TrafficCounters},
+};
+
+/// Accounts the traffic of a single substream and enforces the global and per-peer rate limits.
+#[derive(Debug)]
+pub(crate) struct SubstreamMeter {
+    global: Arc<GlobalBandwidth>,
+    peer: Arc<PeerBandwidth>,
+    protocol: Arc<TrafficCounters>,
+    read_delay: Option<Pin<Box<Sleep>>>,
+    write_delay: Option<Pin<Box<Sleep>>>,
+}
+
+impl SubstreamMeter {
+    pub(super) fn new(global: Arc<GlobalBandwidth>, peer: Arc<PeerBandwidth>, protocol: Arc<TrafficCounters>) -> Self {
+        Self {
+            global,
+            peer,
+            protocol,
+            read_delay: None,
+            write_delay: None,
+        }
+    }
+
+    /// Resolves once the download limits allow more bytes to be read
+    pub fn poll_read_ready(&mut self, cx: &mut Context<'_>) -> Poll<()> {
+        poll_limiters(&mut self.read_delay, cx, [
+            self.global.download_limiter.as_ref(),
+            self.peer.download_limiter.as_ref(),
+        ])
+    }
+
+    /// Resolves once the upload limits allow more bytes to be written
+    pub fn poll_write_ready(&mut self, cx: &mut Context<'_>) -> Poll<()> {
+        poll_limiters(&mut self.write_delay, cx, [
+            self.global.upload_limiter.as_ref(),
+            self.peer.upload_limiter.as_ref(),
+        ])
+    }
+
+    pub fn record_read(&self, num_bytes: usize) {
+        if num_bytes == 0 {
+            return;
+        }
+        self.global.counters.add_read(num_bytes);
+        self.peer.counters.add_read(num_bytes);
+        self.protocol.add_read(num_bytes);
+        let now = Instant::now();
+        for limiter in self
+            .global
+            .download_limiter
+            .iter()
+            .chain(self.peer.download_limiter.iter())
+        {
+            limiter.consume(num_bytes, now);
+        }
+    }
+
+    pub fn record_written(&self, num_bytes: usize) {
+        if num_bytes == 0 {
+            return;
+        }
+        self.global.counters.add_written(num_bytes);
+        self.peer.counters.add_written(num_bytes);
+        self.protocol.add_written(num_bytes);
+        let now = Instant::now();
+        for limiter in self.global.upload_limiter.iter().chain(self.peer.upload_limiter.iter()) {
+            limiter.consume(num_bytes, now);
+        }
+    }
+}
+
+fn poll_limiters(
+    delay: &mut Option<Pin<Box<Sleep>>>,
+    cx: &mut Context<'_>,
+    limiters: [Option<&RateLimiter>; 2],
+) -> Poll<()> {
+    loop {
+        if let Some(sleep) = delay.as_mut() {
+            ready!(sleep.as_mut().poll(cx));
+            *delay = None;
+        }
+
+        let now = Instant::now();
+        match limiters.iter().flatten().filter_map(|limiter| limiter.delay(now)).max() {
+            Some(wait) => {
+                *delay = Some(Box::pin(time::sleep(wait)));
+            },
+            None => return Poll::Ready(()),
+        }
+    }
+}
@@ -31,6 +31,7 @@ use tokio::{
 
 use super::{CommsBuilderError, CommsShutdown};
 use crate::{
+    bandwidth::BandwidthManager,
     connection_manager::{
         ConnectionManager,
         ConnectionManagerEvent,
@@ -41,6 +42,7 @@ use crate::{
     },
     connectivity::{ConnectivityEventRx, ConnectivityManager, ConnectivityRequest, ConnectivityRequester},
     multiaddr::Multiaddr,
+    nat::NatService,
     peer_manager::{NodeIdentity, PeerManager},
     protocol::{
         ProtocolExtension,
@@ -155,6 +157,9 @@ impl UnspawnedCommsNode {
             dial_backoff,
             connection_manager_config,
             connectivity_config,
+            bandwidth_config,
+            nat_config,
+            port_mappers,
             ..
         } = builder;
 
@@ -184,6 +189,7 @@ impl UnspawnedCommsNode {
 
         //---------------------------------- Connection Manager --------------------------------------------//
 
+        let bandwidth = BandwidthManager::new(bandwidth_config);
         let mut connection_manager = ConnectionManager::new(
             connection_manager_config.clone(),
             transport.clone(),
@@ -193,6 +199,7 @@ impl UnspawnedCommsNode {
             peer_manager.clone(),
             connection_manager_requester.get_event_publisher(),
             shutdown_signal.clone(),
+            bandwidth.clone(),
         );
 
         ext_context.register_complete_signal(connection_manager.complete_signal());
@@ -201,6 +208,16 @@ impl UnspawnedCommsNode {
 
         //---------------------------------- Spawn Actors --------------------------------------------//
         connectivity_manager.spawn();
+        if nat_config.is_enabled() {
+            NatService::new(
+                nat_config,
+                port_mappers,
+                node_identity.clone(),
+                connection_manager_requester.clone(),
+                shutdown_signal.clone(),
+            )
+            .spawn();
+        }
         connection_manager.spawn();
 
         debug!(target: LOG_TARGET, "Hello from comms!");
@@ -243,6 +260,7 @@ impl UnspawnedCommsNode {
             node_identity,
             peer_manager,
             liveness_watch,
+            bandwidth,
             complete_signals: ext_context.drain_complete_signals(),
         })
     }
@@ -286,6 +304,8 @@ pub struct CommsNode {
     peer_manager: Arc<PeerManager>,
     /// Current liveness status
     liveness_watch: watch::Receiver<LivenessStatus>,
+    /// Traffic accounting and rate limits for peer substreams
+    bandwidth: BandwidthManager,
     /// The 'reciprocal' shutdown signals for each comms service
     complete_signals: Vec<ShutdownSignal>,
 }
@@ -330,6 +350,11 @@ impl CommsNode {
         self.connectivity_requester.clone()
     }
 
+    /// Return a handle to the per-peer and per-protocol traffic statistics
+    pub fn bandwidth(&self) -> BandwidthManager {
+        self.bandwidth.clone()
+    }
+
     /// Returns a new `ShutdownSignal`
     pub fn shutdown_signal(&self) -> ShutdownSignal {
         self.shutdown_signal.clone()
@@ -42,9 +42,11 @@ use tokio::sync::{broadcast, mpsc};
 
 use crate::{
     backoff::{Backoff, BoxedBackoff, ConstantBackoff},
+    bandwidth::BandwidthConfig,
     connection_manager::{ConnectionManagerConfig, ConnectionManagerRequester},
-    connectivity::{ConnectivityConfig, ConnectivityRequester},
+    connectivity::{ConnectivityConfig, ConnectivityRequester, PeerReputationConfig},
     multiaddr::Multiaddr,
+    nat::{NatConfig, PortMapper},
     peer_manager::{NodeIdentity, PeerManager},
     peer_validator::PeerValidatorConfig,
     protocol::{NodeNetworkInfo, ProtocolExtensions},
@@ -125,6 +127,9 @@ pub struct CommsBuilder {
     hidden_service_ctl: Option<tor::HiddenServiceController>,
     connection_manager_config: ConnectionManagerConfig,
     connectivity_config: ConnectivityConfig,
+    bandwidth_config: BandwidthConfig,
+    nat_config: NatConfig,
+    port_mappers: Vec<Arc<dyn PortMapper>>,
 
     shutdown_signal: Option<ShutdownSignal>,
 }
@@ -139,6 +144,9 @@ impl Default for CommsBuilder {
             hidden_service_ctl: None,
             connection_manager_config: ConnectionManagerConfig::default(),
             connectivity_config: ConnectivityConfig::default(),
+            bandwidth_config: BandwidthConfig::default(),
+            nat_config: NatConfig::default(),
+            port_mappers: Vec::new(),
             shutdown_signal: None,
         }
     }
@@ -233,6 +241,13 @@ impl CommsBuilder {
         self
     }
 
+    /// Sets a QUIC listener address that can accept peer connections. Setting this also enables dialing peers on
+    /// their QUIC addresses. This is optional.
+    pub fn with_quic_listener_address(mut self, listener_address: Multiaddr) -> Self {
+        self.connection_manager_config.quic_listener_address = Some(listener_address);
+        self
+    }
+
     /// Sets the maximum allowed liveness sessions. Liveness is typically used by tools like docker or kubernetes to
     /// detect that the node is live. Defaults to 0 (disabled)
     pub fn with_listener_liveness_max_sessions(mut self, max_sessions: usize) -> Self {
@@ -265,6 +280,31 @@ impl CommsBuilder {
         self
     }
 
+    /// Sets the peer reputation scoring configuration.
+    pub fn with_peer_reputation_config(mut self, config: PeerReputationConfig) -> Self {
+        self.connectivity_config.reputation = config;
+        self
+    }
+
+    /// Sets the global and per-peer upload/download rate limits.
+    pub fn with_bandwidth_config(mut self, config: BandwidthConfig) -> Self {
+        self.bandwidth_config = config;
+        self
+    }
+
+    /// Sets the NAT traversal configuration. NAT traversal is disabled by default.
+    pub fn with_nat_config(mut self, config: NatConfig) -> Self {
+        self.nat_config = config;
+        self
+    }
+
+    /// Adds a port mapper that is used to forward the listener port on the NAT gateway. Port mappers are tried in the
+    /// order they were added. If none are added, UPnP and NAT-PMP are used.
+    pub fn add_port_mapper<T: PortMapper + 'static>(mut self, port_mapper: T) -> Self {
+        self.port_mappers.push(Arc::new(port_mapper));
+        self
+    }
+
     /// Call to disable connection reaping. Usually you would want to have this enabled, however there are some test
     /// cases where disabling this is desirable.
     pub fn disable_connection_reaping(mut self) -> Self {
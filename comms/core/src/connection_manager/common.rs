@@ -26,10 +26,13 @@ use std::{
 };
 
 use log::*;
-use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::{
+    io::{AsyncRead, AsyncWrite},
+    sync::mpsc,
+};
 
 use crate::{
-    connection_manager::error::ConnectionManagerError,
+    connection_manager::{error::ConnectionManagerError, ConnectionManagerEvent},
     multiaddr::Multiaddr,
     net_address::{MultiaddressesWithStats, PeerAddressSource},
     peer_manager::{NodeId, NodeIdentity, Peer, PeerFeatures, PeerFlags, PeerIdentityClaim, PeerManagerError},
@@ -47,6 +50,8 @@ const LOG_TARGET: &str = "comms::connection_manager::common";
 pub struct ValidatedPeerIdentityExchange {
     pub claim: PeerIdentityClaim,
     pub metadata: PeerIdentityMetadata,
+    /// The address of this node as observed by the peer, if the peer provided a valid one
+    pub observed_address: Option<Multiaddr>,
 }
 
 impl ValidatedPeerIdentityExchange {
@@ -80,9 +85,16 @@ pub(super) async fn perform_identity_exchange<
     node_identity: &NodeIdentity,
     our_supported_protocols: P,
     network_info: NodeNetworkInfo,
+    observed_address: &Multiaddr,
 ) -> Result<PeerIdentityMsg, ConnectionManagerError> {
-    let peer_identity =
-        protocol::identity_exchange(node_identity, our_supported_protocols, network_info, socket).await?;
+    let peer_identity = protocol::identity_exchange(
+        node_identity,
+        our_supported_protocols,
+        network_info,
+        Some(observed_address),
+        socket,
+    )
+    .await?;
 
     Ok(peer_identity)
 }
@@ -107,6 +119,7 @@ pub(super) fn validate_peer_identity_message(
         supported_protocols,
         user_agent,
         identity_signature,
+        observed_address,
     } = peer_identity_msg;
 
     // Perform basic length checks before parsing
@@ -167,15 +180,39 @@ pub(super) fn validate_peer_identity_message(
 
     validate_peer_identity_claim(config, authenticated_public_key, &peer_identity_claim)?;
 
+    // The observed address is informational only, so an invalid address is ignored rather than treated as an offence
+    let observed_address = if observed_address.is_empty() {
+        None
+    } else {
+        Multiaddr::try_from(observed_address).ok()
+    };
+
     Ok(ValidatedPeerIdentityExchange {
         claim: peer_identity_claim,
         metadata: PeerIdentityMetadata {
             user_agent,
             supported_protocols,
         },
+        observed_address,
     })
 }
 
+/// Notifies the connection manager of the address that the peer observed for this node, if one was provided.
+pub(super) async fn notify_observed_address(
+    conn_man_notifier: &mpsc::Sender<ConnectionManagerEvent>,
+    peer_node_id: &NodeId,
+    peer_identity: &ValidatedPeerIdentityExchange,
+) {
+    if let Some(ref address) = peer_identity.observed_address {
+        let _result = conn_man_notifier
+            .send(ConnectionManagerEvent::ObservedAddress {
+                peer_node_id: peer_node_id.clone(),
+                address: address.clone(),
+            })
+            .await;
+    }
+}
+
 /// Validate the peer identity info.
 ///
 /// The following process is used to validate the peer:
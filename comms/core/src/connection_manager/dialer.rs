@@ -46,26 +46,29 @@ use super::{direction::ConnectionDirection, error::ConnectionManagerError, peer_
 use crate::connection_manager::metrics;
 use crate::{
     backoff::Backoff,
+    bandwidth::BandwidthManager,
     connection_manager::{
         common,
         common::ValidatedPeerIdentityExchange,
         dial_state::DialState,
         manager::{ConnectionManagerConfig, ConnectionManagerEvent},
         peer_connection,
+        quic::{QuicConfig, QuicHandshake},
     },
     multiaddr::Multiaddr,
     multiplexing::Yamux,
     net_address::PeerAddressSource,
     noise::{NoiseConfig, NoiseSocket},
     peer_manager::{NodeId, NodeIdentity, Peer, PeerManager},
-    protocol::ProtocolId,
-    transports::Transport,
+    proto::identity::PeerIdentityMsg,
+    protocol::{NodeNetworkInfo, ProtocolId},
+    transports::{predicate::is_quic_address, Transport},
     types::CommsPublicKey,
 };
 
 const LOG_TARGET: &str = "comms::connection_manager::dialer";
 
-type DialResult<TSocket> = Result<(NoiseSocket<TSocket>, Multiaddr), ConnectionManagerError>;
+type DialResult<TSocket> = Result<(DialedConnection<TSocket>, Multiaddr), ConnectionManagerError>;
 type DialFuturesUnordered = FuturesUnordered<
     BoxFuture<
         'static,
@@ -76,6 +79,62 @@ type DialFuturesUnordered = FuturesUnordered<
     >,
 >;
 
+/// An authenticated outbound connection, either a noise socket over the transport or a QUIC connection
+enum DialedConnection<TSocket> {
+    Noise(NoiseSocket<TSocket>),
+    Quic(QuicHandshake),
+}
+
+impl<TSocket> DialedConnection<TSocket>
+where TSocket: AsyncRead + AsyncWrite + Send + Unpin + 'static
+{
+    fn remote_public_key(&self) -> Option<CommsPublicKey> {
+        match self {
+            DialedConnection::Noise(socket) => socket.get_remote_public_key(),
+            DialedConnection::Quic(handshake) => Some(handshake.authenticated_public_key.clone()),
+        }
+    }
+
+    async fn perform_identity_exchange(
+        &mut self,
+        node_identity: &NodeIdentity,
+        our_supported_protocols: &[ProtocolId],
+        network_info: NodeNetworkInfo,
+        dialed_addr: &Multiaddr,
+    ) -> Result<PeerIdentityMsg, ConnectionManagerError> {
+        match self {
+            DialedConnection::Noise(socket) => {
+                common::perform_identity_exchange(
+                    socket,
+                    node_identity,
+                    our_supported_protocols,
+                    network_info,
+                    dialed_addr,
+                )
+                .await
+            },
+            DialedConnection::Quic(handshake) => {
+                common::perform_identity_exchange(
+                    &mut handshake.stream,
+                    node_identity,
+                    our_supported_protocols,
+                    network_info,
+                    dialed_addr,
+                )
+                .await
+            },
+        }
+    }
+
+    fn into_muxer(self) -> Result<Yamux, ConnectionManagerError> {
+        match self {
+            DialedConnection::Noise(socket) => Yamux::upgrade_connection(socket, ConnectionDirection::Outbound)
+                .map_err(|err| ConnectionManagerError::YamuxUpgradeFailure(err.to_string())),
+            DialedConnection::Quic(handshake) => Ok(Yamux::upgrade_quic_connection(handshake.connection)),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub(crate) enum DialerRequest {
     Dial(
@@ -93,6 +152,7 @@ pub struct Dialer<TTransport, TBackoff> {
     node_identity: Arc<NodeIdentity>,
     transport: TTransport,
     noise_config: NoiseConfig,
+    quic_config: Option<QuicConfig>,
     backoff: Arc<TBackoff>,
     request_rx: mpsc::Receiver<DialerRequest>,
     cancel_signals: HashMap<NodeId, Shutdown>,
@@ -100,6 +160,7 @@ pub struct Dialer<TTransport, TBackoff> {
     shutdown: Option<ShutdownSignal>,
     pending_dial_requests: HashMap<NodeId, Vec<oneshot::Sender<Result<PeerConnection, ConnectionManagerError>>>>,
     our_supported_protocols: Arc<Vec<ProtocolId>>,
+    bandwidth: BandwidthManager,
 }
 
 impl<TTransport, TBackoff> Dialer<TTransport, TBackoff>
@@ -125,6 +186,7 @@ where
             peer_manager,
             transport,
             noise_config,
+            quic_config: None,
             backoff: Arc::new(backoff),
             request_rx,
             cancel_signals: Default::default(),
@@ -132,6 +194,7 @@ where
             shutdown: Some(shutdown),
             pending_dial_requests: Default::default(),
             our_supported_protocols: Arc::new(Vec::new()),
+            bandwidth: BandwidthManager::default(),
         }
     }
 
@@ -141,6 +204,18 @@ where
         self
     }
 
+    /// Set the bandwidth manager used to account and rate limit the substreams of new connections
+    pub fn set_bandwidth_manager(&mut self, bandwidth: BandwidthManager) -> &mut Self {
+        self.bandwidth = bandwidth;
+        self
+    }
+
+    /// Enables dialing peers on their QUIC addresses
+    pub(super) fn set_quic_config(&mut self, quic_config: Option<QuicConfig>) -> &mut Self {
+        self.quic_config = quic_config;
+        self
+    }
+
     pub fn spawn(self) -> JoinHandle<()> {
         tokio::spawn(self.run())
     }
@@ -341,14 +416,16 @@ where
         let node_identity = Arc::clone(&self.node_identity);
         let conn_man_notifier = self.conn_man_notifier.clone();
         let supported_protocols = self.our_supported_protocols.clone();
+        let bandwidth = self.bandwidth.clone();
         let noise_config = self.noise_config.clone();
+        let quic_config = self.quic_config.clone();
         let config = self.config.clone();
         let peer_manager = self.peer_manager.clone();
 
         let span = span!(Level::TRACE, "handle_dial_peer_request_inner1");
         let dial_fut = async move {
             let (dial_state, dial_result) =
-                Self::dial_peer_with_retry(dial_state, noise_config, transport, backoff, &config).await;
+                Self::dial_peer_with_retry(dial_state, noise_config, quic_config, transport, backoff, &config).await;
 
             let cancel_signal = dial_state.get_cancel_signal();
 
@@ -375,6 +452,7 @@ where
                         authenticated_public_key,
                         conn_man_notifier,
                         supported_protocols,
+                        bandwidth,
                         &config,
                         cancel_signal,
                     )
@@ -400,11 +478,11 @@ where
     }
 
     fn check_authenticated_public_key(
-        socket: &NoiseSocket<TTransport::Output>,
+        socket: &DialedConnection<TTransport::Output>,
         expected_public_key: &CommsPublicKey,
     ) -> Result<CommsPublicKey, ConnectionManagerError> {
         let authenticated_public_key = socket
-            .get_remote_public_key()
+            .remote_public_key()
             .ok_or(ConnectionManagerError::InvalidStaticPublicKey)?;
 
         if &authenticated_public_key != expected_public_key {
@@ -420,11 +498,12 @@ where
     async fn perform_socket_upgrade_procedure(
         peer_manager: &PeerManager,
         node_identity: &NodeIdentity,
-        mut socket: NoiseSocket<TTransport::Output>,
+        mut socket: DialedConnection<TTransport::Output>,
         dialed_addr: Multiaddr,
         authenticated_public_key: CommsPublicKey,
         conn_man_notifier: mpsc::Sender<ConnectionManagerEvent>,
         our_supported_protocols: Arc<Vec<ProtocolId>>,
+        bandwidth: BandwidthManager,
         config: &ConnectionManagerConfig,
         cancel_signal: ShutdownSignal,
     ) -> Result<(PeerConnection, ValidatedPeerIdentityExchange), ConnectionManagerError> {
@@ -434,13 +513,14 @@ where
             "Starting peer identity exchange for peer with public key '{}'", authenticated_public_key
         );
 
-        let peer_identity_result = common::perform_identity_exchange(
-            &mut socket,
-            node_identity,
-            &*our_supported_protocols,
-            config.network_info.clone(),
-        )
-        .await;
+        let peer_identity_result = socket
+            .perform_identity_exchange(
+                node_identity,
+                &our_supported_protocols,
+                config.network_info.clone(),
+                &dialed_addr,
+            )
+            .await;
 
         let peer_identity =
             common::ban_on_offence(peer_manager, &authenticated_public_key, peer_identity_result).await?;
@@ -456,13 +536,14 @@ where
         );
         let peer_identity =
             common::ban_on_offence(peer_manager, &authenticated_public_key, peer_identity_result).await?;
+        let peer_node_id = NodeId::from_public_key(&authenticated_public_key);
+        common::notify_observed_address(&conn_man_notifier, &peer_node_id, &peer_identity).await;
 
         if cancel_signal.is_terminated() {
             return Err(ConnectionManagerError::DialCancelled);
         }
 
-        let muxer = Yamux::upgrade_connection(socket, CONNECTION_DIRECTION)
-            .map_err(|err| ConnectionManagerError::YamuxUpgradeFailure(err.to_string()))?;
+        let muxer = socket.into_muxer()?;
 
         if cancel_signal.is_terminated() {
             muxer.get_yamux_control().close().await?;
@@ -472,12 +553,13 @@ where
         let peer_connection = peer_connection::create(
             muxer,
             dialed_addr,
-            NodeId::from_public_key(&authenticated_public_key),
+            peer_node_id,
             peer_identity.claim.features,
             CONNECTION_DIRECTION,
             conn_man_notifier,
             our_supported_protocols,
             peer_identity.metadata.supported_protocols.clone(),
+            bandwidth,
         );
 
         Ok((peer_connection, peer_identity))
@@ -486,6 +568,7 @@ where
     async fn dial_peer_with_retry(
         dial_state: DialState,
         noise_config: NoiseConfig,
+        quic_config: Option<QuicConfig>,
         transport: TTransport,
         backoff: Arc<TBackoff>,
         config: &ConnectionManagerConfig,
@@ -511,7 +594,7 @@ where
             tokio::select! {
                 _ = delay => {
                     debug!(target: LOG_TARGET, "[Attempt {}] Connecting to peer '{}'", current_state.num_attempts(), current_state.peer().node_id.short_str());
-                    match Self::dial_peer(current_state, &noise_config, &current_transport, config.network_info.network_byte).await {
+                    match Self::dial_peer(current_state, &noise_config, quic_config.as_ref(), &current_transport, config.network_info.network_byte).await {
                         (state, Ok((socket, addr))) => {
                             debug!(target: LOG_TARGET, "Dial succeeded for peer '{}' after {} attempt(s)", state.peer().node_id.short_str(), state.num_attempts());
                             break (state, Ok((socket, addr)));
@@ -539,19 +622,29 @@ where
         }
     }
 
-    /// Attempts to dial a peer sequentially on all addresses.
+    /// Attempts to dial a peer sequentially on all addresses. If QUIC is enabled, QUIC addresses are attempted first,
+    /// otherwise they are skipped.
     /// Returns ownership of the given `DialState` and a success or failure result for the dial,
     /// or None if the dial was cancelled inflight
     async fn dial_peer(
         mut dial_state: DialState,
         noise_config: &NoiseConfig,
+        quic_config: Option<&QuicConfig>,
         transport: &TTransport,
         network_byte: u8,
-    ) -> (
-        DialState,
-        Result<(NoiseSocket<TTransport::Output>, Multiaddr), ConnectionManagerError>,
-    ) {
-        let addresses = dial_state.peer().addresses.clone().into_vec();
+    ) -> (DialState, DialResult<TTransport::Output>) {
+        let (quic_addresses, other_addresses): (Vec<_>, Vec<_>) = dial_state
+            .peer()
+            .addresses
+            .clone()
+            .into_vec()
+            .into_iter()
+            .partition(is_quic_address);
+        let addresses = if quic_config.is_some() {
+            quic_addresses.into_iter().chain(other_addresses).collect()
+        } else {
+            other_addresses
+        };
         let cancel_signal = dial_state.get_cancel_signal();
         for address in addresses {
             debug!(
@@ -564,6 +657,20 @@ where
             let moved_address = address.clone();
             let node_id = dial_state.peer().node_id.clone();
             let dial_fut = async move {
+                if let Some(quic_config) = quic_config.filter(|_| is_quic_address(&moved_address)) {
+                    let timer = Instant::now();
+                    let handshake = quic_config.dial(&moved_address).await?;
+                    let dial_time = timer.elapsed();
+                    debug!(
+                        target: LOG_TARGET,
+                        "Dial - QUIC handshake completed: {} on address: {} after: {}",
+                        node_id.short_str(),
+                        moved_address,
+                        dial_time.as_millis()
+                    );
+                    return Ok((dial_time, dial_time, DialedConnection::Quic(handshake)));
+                }
+
                 let mut timer = Instant::now();
                 let mut socket =
                     transport
@@ -604,20 +711,24 @@ where
                     timer.elapsed().as_millis()
                 );
 
-                Result::<_, ConnectionManagerError>::Ok((initial_dial_time, noise_upgrade_time, noise_socket))
+                Result::<_, ConnectionManagerError>::Ok((
+                    initial_dial_time,
+                    noise_upgrade_time,
+                    DialedConnection::Noise(noise_socket),
+                ))
             };
 
             pin_mut!(dial_fut);
             let either = future::select(dial_fut, cancel_signal.clone()).await;
             match either {
-                Either::Left((Ok((initial_dial_time, noise_upgrade_time, noise_socket)), _)) => {
+                Either::Left((Ok((initial_dial_time, upgrade_time, socket)), _)) => {
                     dial_state.peer_mut().addresses.mark_last_seen_now(&address);
                     dial_state.peer_mut().addresses.update_address_stats(&address, |addr| {
                         // Initial dial time can be much slower due to tor discovery.
                         addr.update_initial_dial_time(initial_dial_time);
-                        addr.update_latency(noise_upgrade_time);
+                        addr.update_latency(upgrade_time);
                     });
-                    return (dial_state, Ok((noise_socket, address.clone())));
+                    return (dial_state, Ok((socket, address.clone())));
                 },
                 Either::Left((Err(err), _)) => {
                     debug!(
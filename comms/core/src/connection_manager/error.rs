@@ -68,6 +68,8 @@ pub enum ConnectionManagerError {
     // send the same response to multiple requesters
     #[error("Noise error: {0}")]
     NoiseError(String),
+    #[error("QUIC handshake failed: {0}")]
+    QuicHandshakeFailed(String),
     #[error("Peer is banned, denying connection")]
     PeerBanned,
     #[error("Identity protocol failed: {0}")]
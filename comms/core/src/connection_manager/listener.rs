@@ -53,6 +53,7 @@ use super::{
 #[cfg(feature = "metrics")]
 use crate::connection_manager::metrics;
 use crate::{
+    bandwidth::BandwidthManager,
     bounded_executor::BoundedExecutor,
     connection_manager::{
         liveness::LivenessSession,
@@ -82,6 +83,7 @@ pub struct PeerListener<TTransport> {
     peer_manager: Arc<PeerManager>,
     node_identity: Arc<NodeIdentity>,
     our_supported_protocols: Arc<Vec<ProtocolId>>,
+    bandwidth: BandwidthManager,
     liveness_session_count: Arc<AtomicUsize>,
     on_listening: OneshotTrigger<Result<Multiaddr, ConnectionManagerError>>,
 }
@@ -110,6 +112,7 @@ where
             node_identity,
             shutdown_signal,
             our_supported_protocols: Arc::new(Vec::new()),
+            bandwidth: BandwidthManager::default(),
             bounded_executor: BoundedExecutor::new(config.max_simultaneous_inbound_connects),
             liveness_session_count: Arc::new(AtomicUsize::new(config.liveness_max_sessions)),
             config,
@@ -132,6 +135,12 @@ where
         self
     }
 
+    /// Set the bandwidth manager used to account and rate limit the substreams of new connections
+    pub fn set_bandwidth_manager(&mut self, bandwidth: BandwidthManager) -> &mut Self {
+        self.bandwidth = bandwidth;
+        self
+    }
+
     pub async fn listen(self) -> Result<Multiaddr, ConnectionManagerError> {
         let on_listening = self.on_listening();
         tokio::spawn(self.run());
@@ -236,6 +245,7 @@ where
         let noise_config = self.noise_config.clone();
         let config = self.config.clone();
         let our_supported_protocols = self.our_supported_protocols.clone();
+        let bandwidth = self.bandwidth.clone();
         let liveness_session_count = self.liveness_session_count.clone();
         let shutdown_signal = self.shutdown_signal.clone();
 
@@ -254,6 +264,7 @@ where
                         socket,
                         peer_addr,
                         our_supported_protocols,
+                        bandwidth,
                         &config,
                     )
                     .await;
@@ -345,6 +356,7 @@ where
         socket: TTransport::Output,
         peer_addr: Multiaddr,
         our_supported_protocols: Arc<Vec<ProtocolId>>,
+        bandwidth: BandwidthManager,
         config: &ConnectionManagerConfig,
     ) -> Result<PeerConnection, ConnectionManagerError> {
         const CONNECTION_DIRECTION: ConnectionDirection = ConnectionDirection::Inbound;
@@ -380,6 +392,7 @@ where
             node_identity,
             &*our_supported_protocols,
             config.network_info.clone(),
+            &peer_addr,
         )
         .await;
 
@@ -400,6 +413,7 @@ where
             authenticated_public_key,
             &valid_peer_identity,
         );
+        common::notify_observed_address(&conn_man_notifier, &peer.node_id, &valid_peer_identity).await;
 
         let muxer = Yamux::upgrade_connection(noise_socket, CONNECTION_DIRECTION)
             .map_err(|err| ConnectionManagerError::YamuxUpgradeFailure(err.to_string()))?;
@@ -413,6 +427,7 @@ where
             conn_man_notifier,
             our_supported_protocols,
             valid_peer_identity.metadata.supported_protocols,
+            bandwidth,
         );
 
         peer_manager.add_peer(peer).await?;
@@ -39,6 +39,7 @@ use super::{
     error::ConnectionManagerError,
     listener::PeerListener,
     peer_connection::PeerConnection,
+    quic::{QuicConfig, QuicPeerListener},
     requester::ConnectionManagerRequest,
 };
 #[cfg(feature = "metrics")]
@@ -47,13 +48,14 @@ use crate::connection_manager::metrics;
 use crate::connection_manager::ConnectionDirection;
 use crate::{
     backoff::Backoff,
+    bandwidth::BandwidthManager,
     connection_manager::ConnectionId,
     multiplexing::Substream,
     noise::NoiseConfig,
     peer_manager::{NodeId, NodeIdentity, PeerManagerError},
     peer_validator::PeerValidatorConfig,
     protocol::{NodeNetworkInfo, ProtocolEvent, ProtocolId, Protocols},
-    transports::{TcpTransport, Transport},
+    transports::{QuicTransport, TcpTransport, Transport},
     PeerManager,
 };
 
@@ -75,7 +77,15 @@ pub enum ConnectionManagerEvent {
     NewInboundSubstream(NodeId, ProtocolId, Substream),
 
     // Other
-    PeerViolation { peer_node_id: NodeId, details: String },
+    PeerViolation {
+        peer_node_id: NodeId,
+        details: String,
+    },
+    /// A peer reported the address from which it observed this node's side of the connection
+    ObservedAddress {
+        peer_node_id: NodeId,
+        address: Multiaddr,
+    },
 }
 
 impl fmt::Display for ConnectionManagerEvent {
@@ -96,6 +106,9 @@ impl fmt::Display for ConnectionManagerEvent {
             PeerViolation { peer_node_id, details } => {
                 write!(f, "PeerViolation({}, {})", peer_node_id.short_str(), details)
             },
+            ObservedAddress { peer_node_id, address } => {
+                write!(f, "ObservedAddress({}, {})", peer_node_id.short_str(), address)
+            },
         }
     }
 }
@@ -128,6 +141,10 @@ pub struct ConnectionManagerConfig {
     /// If set, an additional TCP-only p2p listener will be started. This is useful for local wallet connections.
     /// Default: None (disabled)
     pub auxiliary_tcp_listener_address: Option<Multiaddr>,
+    /// If set, a QUIC listener is started on this address (e.g. `/ip4/0.0.0.0/udp/18189/quic`) in addition to the
+    /// primary listener, and peers advertising QUIC addresses are dialed over QUIC. QUIC streams are natively
+    /// multiplexed, so a slow substream does not block the others on the connection. Default: None (disabled)
+    pub quic_listener_address: Option<Multiaddr>,
     /// Peer validation configuration. See [PeerValidatorConfig]
     pub peer_validation_config: PeerValidatorConfig,
 }
@@ -149,6 +166,7 @@ impl Default for ConnectionManagerConfig {
             liveness_cidr_allowlist: vec![cidr::AnyIpCidr::V4("127.0.0.1/32".parse().unwrap())],
             liveness_self_check_interval: None,
             auxiliary_tcp_listener_address: None,
+            quic_listener_address: None,
             peer_validation_config: PeerValidatorConfig::default(),
             noise_handshake_recv_timeout: Duration::from_secs(6),
         }
@@ -160,6 +178,7 @@ impl Default for ConnectionManagerConfig {
 pub struct ListenerInfo {
     bind_address: Multiaddr,
     aux_bind_address: Option<Multiaddr>,
+    quic_bind_address: Option<Multiaddr>,
 }
 
 impl ListenerInfo {
@@ -173,6 +192,11 @@ impl ListenerInfo {
     pub fn auxiliary_bind_address(&self) -> Option<&Multiaddr> {
         self.aux_bind_address.as_ref()
     }
+
+    /// The QUIC address that was bound on if enabled.
+    pub fn quic_bind_address(&self) -> Option<&Multiaddr> {
+        self.quic_bind_address.as_ref()
+    }
 }
 
 /// The actor responsible for connection management.
@@ -183,6 +207,7 @@ pub(crate) struct ConnectionManager<TTransport, TBackoff> {
     dialer: Option<Dialer<TTransport, TBackoff>>,
     listener: Option<PeerListener<TTransport>>,
     aux_listener: Option<PeerListener<TcpTransport>>,
+    quic_listener: Option<QuicPeerListener>,
     peer_manager: Arc<PeerManager>,
     shutdown_signal: Option<ShutdownSignal>,
     protocols: Protocols<Substream>,
@@ -190,6 +215,7 @@ pub(crate) struct ConnectionManager<TTransport, TBackoff> {
     listening_notifiers: Vec<oneshot::Sender<ListenerInfo>>,
     connection_manager_events_tx: broadcast::Sender<Arc<ConnectionManagerEvent>>,
     complete_trigger: Shutdown,
+    bandwidth: BandwidthManager,
 }
 
 impl<TTransport, TBackoff> ConnectionManager<TTransport, TBackoff>
@@ -207,6 +233,7 @@ where
         peer_manager: Arc<PeerManager>,
         connection_manager_events_tx: broadcast::Sender<Arc<ConnectionManagerEvent>>,
         shutdown_signal: ShutdownSignal,
+        bandwidth: BandwidthManager,
     ) -> Self {
         let (internal_event_tx, internal_event_rx) = mpsc::channel(EVENT_CHANNEL_SIZE);
         let (dialer_tx, dialer_rx) = mpsc::channel(DIALER_REQUEST_CHANNEL_SIZE);
@@ -244,7 +271,33 @@ where
             )
         });
 
-        let dialer = Dialer::new(
+        let quic_config = config.quic_listener_address.as_ref().map(|_| {
+            QuicConfig::new(
+                QuicTransport::new(),
+                node_identity.clone(),
+                config.network_info.network_byte,
+                config.noise_handshake_recv_timeout,
+            )
+        });
+
+        let quic_listener = config
+            .quic_listener_address
+            .take()
+            .zip(quic_config.clone())
+            .map(|(addr, quic_config)| {
+                info!(target: LOG_TARGET, "Starting QUIC listener on {}", addr);
+                QuicPeerListener::new(
+                    config.clone(),
+                    addr,
+                    quic_config,
+                    internal_event_tx.clone(),
+                    peer_manager.clone(),
+                    node_identity.clone(),
+                    shutdown_signal.clone(),
+                )
+            });
+
+        let mut dialer = Dialer::new(
             config,
             node_identity,
             peer_manager.clone(),
@@ -255,6 +308,7 @@ where
             internal_event_tx,
             shutdown_signal.clone(),
         );
+        dialer.set_quic_config(quic_config);
 
         Self {
             shutdown_signal: Some(shutdown_signal),
@@ -267,9 +321,11 @@ where
             listener: Some(listener),
             listener_info: None,
             aux_listener,
+            quic_listener,
             listening_notifiers: Vec::new(),
             connection_manager_events_tx,
             complete_trigger: Shutdown::new(),
+            bandwidth,
         }
     }
 
@@ -344,23 +400,37 @@ where
             .take()
             .expect("ConnectionManager initialized without a listener");
 
-        listener.set_supported_protocols(self.protocols.get_supported_protocols());
+        listener
+            .set_supported_protocols(self.protocols.get_supported_protocols())
+            .set_bandwidth_manager(self.bandwidth.clone());
 
         let mut listener_info = match listener.listen().await {
             Ok(bind_address) => ListenerInfo {
                 bind_address,
                 aux_bind_address: None,
+                quic_bind_address: None,
             },
             Err(err) => return Err(err),
         };
 
         if let Some(mut listener) = self.aux_listener.take() {
-            listener.set_supported_protocols(self.protocols.get_supported_protocols());
+            listener
+                .set_supported_protocols(self.protocols.get_supported_protocols())
+                .set_bandwidth_manager(self.bandwidth.clone());
             let addr = listener.listen().await?;
             debug!(target: LOG_TARGET, "Aux TCP listener bound to address {}", addr);
             listener_info.aux_bind_address = Some(addr);
         }
 
+        if let Some(mut listener) = self.quic_listener.take() {
+            listener
+                .set_supported_protocols(self.protocols.get_supported_protocols())
+                .set_bandwidth_manager(self.bandwidth.clone());
+            let addr = listener.listen().await?;
+            debug!(target: LOG_TARGET, "QUIC listener bound to address {}", addr);
+            listener_info.quic_bind_address = Some(addr);
+        }
+
         Ok(listener_info)
     }
 
@@ -370,7 +440,9 @@ where
             .take()
             .expect("ConnectionManager initialized without a dialer");
 
-        dialer.set_supported_protocols(self.protocols.get_supported_protocols());
+        dialer
+            .set_supported_protocols(self.protocols.get_supported_protocols())
+            .set_bandwidth_manager(self.bandwidth.clone());
         dialer.spawn();
     }
 
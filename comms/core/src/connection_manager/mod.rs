@@ -24,7 +24,7 @@
 //! This component is responsible for orchestrating PeerConnections, specifically:
 //! - dialing peers,
 //! - listening for peer connections on the configured transport,
-//! - performing connection upgrades (noise protocol, identity and multiplexing),
+//! - performing connection upgrades (noise protocol, identity and multiplexing, or QUIC authentication and identity),
 //! - and, notifying the connectivity manager of changes in connection state (new connections, disconnects, etc)
 
 mod dial_state;
@@ -32,6 +32,7 @@ mod dialer;
 mod listener;
 #[cfg(feature = "metrics")]
 mod metrics;
+mod quic;
 
 mod common;
 
@@ -52,6 +52,7 @@ use crate::protocol::rpc::{
     RPC_MAX_FRAME_SIZE,
 };
 use crate::{
+    bandwidth::BandwidthManager,
     framing,
     framing::CanonicalFraming,
     multiplexing::{Control, IncomingSubstreams, Substream, Yamux},
@@ -75,6 +76,7 @@ pub fn create(
     event_notifier: mpsc::Sender<ConnectionManagerEvent>,
     our_supported_protocols: Arc<Vec<ProtocolId>>,
     their_supported_protocols: Vec<ProtocolId>,
+    bandwidth: BandwidthManager,
 ) -> PeerConnection {
     trace!(
         target: LOG_TARGET,
@@ -103,6 +105,7 @@ pub fn create(
         event_notifier,
         our_supported_protocols,
         their_supported_protocols,
+        bandwidth,
     );
     tokio::spawn(peer_actor.run());
 
@@ -332,6 +335,7 @@ struct PeerConnectionActor {
     inbound_protocol_negotiations:
         FuturesUnordered<BoxFuture<'static, Result<(ProtocolId, Substream), PeerConnectionError>>>,
     their_supported_protocols: Vec<ProtocolId>,
+    bandwidth: BandwidthManager,
 }
 
 impl PeerConnectionActor {
@@ -344,6 +348,7 @@ impl PeerConnectionActor {
         event_notifier: mpsc::Sender<ConnectionManagerEvent>,
         our_supported_protocols: Arc<Vec<ProtocolId>>,
         their_supported_protocols: Vec<ProtocolId>,
+        bandwidth: BandwidthManager,
     ) -> Self {
         Self {
             id,
@@ -356,6 +361,7 @@ impl PeerConnectionActor {
             our_supported_protocols,
             inbound_protocol_negotiations: FuturesUnordered::new(),
             their_supported_protocols,
+            bandwidth,
         }
     }
 
@@ -447,7 +453,8 @@ impl PeerConnectionActor {
         result: Result<(ProtocolId, Substream), PeerConnectionError>,
     ) {
         match result {
-            Ok((selected_protocol, stream)) => {
+            Ok((selected_protocol, mut stream)) => {
+                stream.set_meter(self.bandwidth.meter(&self.peer_node_id, &selected_protocol));
                 self.notify_event(ConnectionManagerEvent::NewInboundSubstream(
                     self.peer_node_id.clone(),
                     selected_protocol,
@@ -506,6 +513,7 @@ impl PeerConnectionActor {
             time::timeout(PROTOCOL_NEGOTIATION_TIMEOUT, fut).await??
         };
 
+        stream.set_meter(self.bandwidth.meter(&self.peer_node_id, &selected_protocol));
         Ok(NegotiatedSubstream::new(selected_protocol, stream))
     }
 
@@ -0,0 +1,536 @@
+// Copyright 2024. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! # QUIC connection upgrade
+//!
+//! QUIC connections are encrypted by TLS using self-signed certificates, so peers are authenticated here rather than by
+//! the noise protocol. Each side signs a challenge with its node identity key that commits to keying material exported
+//! from the TLS session. Because the exported keying material is unique to the session, a signature cannot be replayed
+//! on another connection, and a man-in-the-middle cannot relay the authentication between two sessions.
+//!
+//! The dialer opens the first stream and sends the network byte followed by its authentication message, and the
+//! listener responds with its own. The same stream is then used for the peer identity exchange. Protocol substreams are
+//! opened as separate QUIC streams, avoiding the head-of-line blocking of multiplexing over a single TCP stream.
+
+use std::{fmt::Display, sync::Arc, time::Duration};
+
+use blake2::Blake2b;
+use digest::consts::U64;
+use log::*;
+use rand::rngs::OsRng;
+use tari_crypto::{hashing::DomainSeparatedHasher, keys::PublicKey};
+use tari_shutdown::ShutdownSignal;
+use tari_utilities::ByteArray;
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    sync::mpsc,
+    time,
+};
+use tokio_stream::StreamExt;
+
+use super::{
+    common,
+    direction::ConnectionDirection,
+    error::ConnectionManagerError,
+    peer_connection::{self, PeerConnection},
+    ConnectionManagerConfig,
+    ConnectionManagerEvent,
+};
+#[cfg(feature = "metrics")]
+use crate::connection_manager::metrics;
+use crate::{
+    bandwidth::BandwidthManager,
+    bounded_executor::BoundedExecutor,
+    multiaddr::Multiaddr,
+    multiplexing::{QuicStream, Yamux},
+    peer_manager::NodeIdentity,
+    protocol::ProtocolId,
+    transports::{QuicTransport, Transport},
+    types::{CommsCoreHashDomain, CommsPublicKey, CommsSecretKey, Signature},
+    PeerManager,
+};
+
+const LOG_TARGET: &str = "comms::connection_manager::quic";
+
+/// The label used to export keying material from the TLS session
+const EXPORTER_LABEL: &[u8] = b"EXPORTER-tari-comms-quic-auth";
+const EXPORTED_KEY_LEN: usize = 32;
+/// Public key, public nonce and signature, 32 bytes each
+const AUTH_MESSAGE_LEN: usize = 96;
+const QUIC_AUTH_HASH_LABEL: &str = "quic_auth";
+
+/// An authenticated QUIC connection
+pub(super) struct QuicHandshake {
+    pub connection: quinn::Connection,
+    /// The first stream of the connection, used for the peer identity exchange
+    pub stream: QuicStream,
+    pub authenticated_public_key: CommsPublicKey,
+}
+
+/// Dials and authenticates QUIC connections using the node identity
+#[derive(Clone)]
+pub(super) struct QuicConfig {
+    transport: QuicTransport,
+    node_identity: Arc<NodeIdentity>,
+    network_byte: u8,
+    handshake_timeout: Duration,
+}
+
+impl QuicConfig {
+    pub fn new(
+        transport: QuicTransport,
+        node_identity: Arc<NodeIdentity>,
+        network_byte: u8,
+        handshake_timeout: Duration,
+    ) -> Self {
+        Self {
+            transport,
+            node_identity,
+            network_byte,
+            handshake_timeout,
+        }
+    }
+
+    /// Dial the given QUIC address and authenticate the peer
+    pub async fn dial(&self, addr: &Multiaddr) -> Result<QuicHandshake, ConnectionManagerError> {
+        let connection = self
+            .transport
+            .dial(addr)
+            .await
+            .map_err(|err| ConnectionManagerError::TransportError {
+                address: addr.to_string(),
+                details: err.to_string(),
+            })?;
+        time::timeout(self.handshake_timeout, self.upgrade_outbound(connection))
+            .await
+            .map_err(|_| ConnectionManagerError::QuicHandshakeFailed("Handshake timed out".to_string()))?
+    }
+
+    /// Authenticate a peer that connected to our QUIC listener
+    pub async fn upgrade_inbound(
+        &self,
+        connection: quinn::Connection,
+    ) -> Result<QuicHandshake, ConnectionManagerError> {
+        time::timeout(self.handshake_timeout, self.upgrade_inbound_inner(connection))
+            .await
+            .map_err(|_| ConnectionManagerError::QuicHandshakeFailed("Handshake timed out".to_string()))?
+    }
+
+    async fn upgrade_outbound(&self, connection: quinn::Connection) -> Result<QuicHandshake, ConnectionManagerError> {
+        let (send, recv) = connection.open_bi().await.map_err(handshake_failed)?;
+        let mut stream = QuicStream::new(send, recv);
+        let exported_key = export_keying_material(&connection)?;
+
+        stream.write_all(&[self.network_byte]).await.map_err(handshake_failed)?;
+        let auth_message = create_auth_message(&self.node_identity, &exported_key, ConnectionDirection::Outbound);
+        stream.write_all(&auth_message).await.map_err(handshake_failed)?;
+        stream.flush().await.map_err(handshake_failed)?;
+
+        let authenticated_public_key =
+            read_auth_message(&mut stream, &exported_key, ConnectionDirection::Inbound).await?;
+
+        Ok(QuicHandshake {
+            connection,
+            stream,
+            authenticated_public_key,
+        })
+    }
+
+    async fn upgrade_inbound_inner(
+        &self,
+        connection: quinn::Connection,
+    ) -> Result<QuicHandshake, ConnectionManagerError> {
+        let (send, recv) = connection.accept_bi().await.map_err(handshake_failed)?;
+        let mut stream = QuicStream::new(send, recv);
+        let exported_key = export_keying_material(&connection)?;
+
+        let mut network_byte = [0u8; 1];
+        stream.read_exact(&mut network_byte).await.map_err(handshake_failed)?;
+        if network_byte[0] != self.network_byte {
+            return Err(ConnectionManagerError::QuicHandshakeFailed(format!(
+                "Invalid network byte. Expected {:x?} got {:x?}",
+                self.network_byte, network_byte[0]
+            )));
+        }
+
+        let authenticated_public_key =
+            read_auth_message(&mut stream, &exported_key, ConnectionDirection::Outbound).await?;
+
+        let auth_message = create_auth_message(&self.node_identity, &exported_key, ConnectionDirection::Inbound);
+        stream.write_all(&auth_message).await.map_err(handshake_failed)?;
+        stream.flush().await.map_err(handshake_failed)?;
+
+        Ok(QuicHandshake {
+            connection,
+            stream,
+            authenticated_public_key,
+        })
+    }
+}
+
+fn export_keying_material(connection: &quinn::Connection) -> Result<[u8; EXPORTED_KEY_LEN], ConnectionManagerError> {
+    let mut exported_key = [0u8; EXPORTED_KEY_LEN];
+    connection
+        .export_keying_material(&mut exported_key, EXPORTER_LABEL, &[])
+        .map_err(|_| {
+            ConnectionManagerError::QuicHandshakeFailed("Failed to export keying material from the session".to_string())
+        })?;
+    Ok(exported_key)
+}
+
+/// Creates the authentication message for the side of the connection given by `direction`
+fn create_auth_message(
+    node_identity: &NodeIdentity,
+    exported_key: &[u8],
+    direction: ConnectionDirection,
+) -> [u8; AUTH_MESSAGE_LEN] {
+    let (secret_nonce, public_nonce) = CommsPublicKey::random_keypair(&mut OsRng);
+    let challenge = auth_challenge(node_identity.public_key(), &public_nonce, exported_key, direction);
+    let signature = Signature::sign_raw_uniform(node_identity.secret_key(), secret_nonce, &challenge)
+        .expect("unreachable panic: challenge hash digest is the correct length");
+
+    let mut message = [0u8; AUTH_MESSAGE_LEN];
+    message[..32].copy_from_slice(node_identity.public_key().as_bytes());
+    message[32..64].copy_from_slice(public_nonce.as_bytes());
+    message[64..].copy_from_slice(signature.get_signature().as_bytes());
+    message
+}
+
+/// Reads and verifies the authentication message from the side of the connection given by `direction`, returning the
+/// authenticated public key
+async fn read_auth_message(
+    stream: &mut QuicStream,
+    exported_key: &[u8],
+    direction: ConnectionDirection,
+) -> Result<CommsPublicKey, ConnectionManagerError> {
+    let mut message = [0u8; AUTH_MESSAGE_LEN];
+    stream.read_exact(&mut message).await.map_err(handshake_failed)?;
+
+    let public_key = CommsPublicKey::from_canonical_bytes(&message[..32])
+        .map_err(|_| ConnectionManagerError::InvalidStaticPublicKey)?;
+    let public_nonce = CommsPublicKey::from_canonical_bytes(&message[32..64]).map_err(handshake_failed)?;
+    let signature = CommsSecretKey::from_canonical_bytes(&message[64..]).map_err(handshake_failed)?;
+
+    let challenge = auth_challenge(&public_key, &public_nonce, exported_key, direction);
+    if !Signature::new(public_nonce, signature).verify_raw_uniform(&public_key, &challenge) {
+        return Err(ConnectionManagerError::QuicHandshakeFailed(
+            "Invalid authentication signature".to_string(),
+        ));
+    }
+
+    Ok(public_key)
+}
+
+fn auth_challenge(
+    public_key: &CommsPublicKey,
+    public_nonce: &CommsPublicKey,
+    exported_key: &[u8],
+    direction: ConnectionDirection,
+) -> [u8; 64] {
+    // e = H(P||R||d||k)
+    let hash = DomainSeparatedHasher::<Blake2b<U64>, CommsCoreHashDomain>::new_with_label(QUIC_AUTH_HASH_LABEL)
+        .chain(public_key.as_bytes())
+        .chain(public_nonce.as_bytes())
+        .chain([u8::from(direction.is_inbound())])
+        .chain(exported_key)
+        .finalize();
+    let mut challenge = [0u8; 64];
+    challenge.copy_from_slice(hash.as_ref());
+    challenge
+}
+
+fn handshake_failed<E: Display>(err: E) -> ConnectionManagerError {
+    ConnectionManagerError::QuicHandshakeFailed(err.to_string())
+}
+
+/// Listens for QUIC peer connections and notifies when a new inbound peer connection is established.
+pub(super) struct QuicPeerListener {
+    config: ConnectionManagerConfig,
+    bind_address: Multiaddr,
+    quic_config: QuicConfig,
+    bounded_executor: BoundedExecutor,
+    conn_man_notifier: mpsc::Sender<ConnectionManagerEvent>,
+    shutdown_signal: ShutdownSignal,
+    peer_manager: Arc<PeerManager>,
+    node_identity: Arc<NodeIdentity>,
+    our_supported_protocols: Arc<Vec<ProtocolId>>,
+    bandwidth: BandwidthManager,
+}
+
+impl QuicPeerListener {
+    pub fn new(
+        config: ConnectionManagerConfig,
+        bind_address: Multiaddr,
+        quic_config: QuicConfig,
+        conn_man_notifier: mpsc::Sender<ConnectionManagerEvent>,
+        peer_manager: Arc<PeerManager>,
+        node_identity: Arc<NodeIdentity>,
+        shutdown_signal: ShutdownSignal,
+    ) -> Self {
+        Self {
+            bind_address,
+            quic_config,
+            bounded_executor: BoundedExecutor::new(config.max_simultaneous_inbound_connects),
+            conn_man_notifier,
+            shutdown_signal,
+            peer_manager,
+            node_identity,
+            our_supported_protocols: Arc::new(Vec::new()),
+            bandwidth: BandwidthManager::default(),
+            config,
+        }
+    }
+
+    /// Set the supported protocols of this node to send to peers during the peer identity exchange
+    pub fn set_supported_protocols(&mut self, our_supported_protocols: Vec<ProtocolId>) -> &mut Self {
+        self.our_supported_protocols = Arc::new(our_supported_protocols);
+        self
+    }
+
+    /// Set the bandwidth manager used to account and rate limit the substreams of new connections
+    pub fn set_bandwidth_manager(&mut self, bandwidth: BandwidthManager) -> &mut Self {
+        self.bandwidth = bandwidth;
+        self
+    }
+
+    /// Binds the listener and spawns a task to accept connections, returning the bound address
+    pub async fn listen(self) -> Result<Multiaddr, ConnectionManagerError> {
+        let (inbound, address) = self
+            .quic_config
+            .transport
+            .listen(&self.bind_address)
+            .await
+            .map_err(|err| ConnectionManagerError::ListenerError {
+                address: self.bind_address.to_string(),
+                details: err.to_string(),
+            })?;
+        info!(target: LOG_TARGET, "Listening for QUIC peer connections on '{}'", address);
+        tokio::spawn(self.run(inbound));
+        Ok(address)
+    }
+
+    async fn run(self, mut inbound: <QuicTransport as Transport>::Listener) {
+        let mut shutdown_signal = self.shutdown_signal.clone();
+        loop {
+            tokio::select! {
+                biased;
+
+                _ = &mut shutdown_signal => {
+                    info!(target: LOG_TARGET, "QuicPeerListener is shutting down because the shutdown signal was triggered");
+                    break;
+                },
+                Some(inbound_result) = inbound.next() => {
+                    if let Some((connection, peer_addr)) = log_if_error!(target: LOG_TARGET, inbound_result, "Inbound QUIC connection failed because '{error}'",) {
+                        self.spawn_listen_task(connection, peer_addr).await;
+                    }
+                },
+            }
+        }
+    }
+
+    async fn spawn_listen_task(&self, connection: quinn::Connection, peer_addr: Multiaddr) {
+        let quic_config = self.quic_config.clone();
+        let node_identity = self.node_identity.clone();
+        let peer_manager = self.peer_manager.clone();
+        let conn_man_notifier = self.conn_man_notifier.clone();
+        let config = self.config.clone();
+        let our_supported_protocols = self.our_supported_protocols.clone();
+        let bandwidth = self.bandwidth.clone();
+
+        let inbound_fut = async move {
+            #[cfg(feature = "metrics")]
+            metrics::pending_connections(None, ConnectionDirection::Inbound).inc();
+
+            let result = Self::perform_upgrade_procedure(
+                &quic_config,
+                &node_identity,
+                &peer_manager,
+                conn_man_notifier.clone(),
+                connection,
+                peer_addr,
+                our_supported_protocols,
+                bandwidth,
+                &config,
+            )
+            .await;
+
+            let event = match result {
+                Ok(peer_conn) => ConnectionManagerEvent::PeerConnected(peer_conn.into()),
+                Err(err) => {
+                    debug!(
+                        target: LOG_TARGET,
+                        "[ThisNode={}] QUIC peer connection upgrade failed for peer because '{:?}'",
+                        node_identity.node_id().short_str(),
+                        err
+                    );
+                    ConnectionManagerEvent::PeerInboundConnectFailed(err)
+                },
+            };
+            log_if_error!(
+                target: LOG_TARGET,
+                conn_man_notifier.send(event).await,
+                "Failed to publish event because '{error}'",
+            );
+
+            #[cfg(feature = "metrics")]
+            metrics::pending_connections(None, ConnectionDirection::Inbound).dec();
+        };
+
+        // This will block (asynchronously) if we have reached the maximum simultaneous connections, creating
+        // back-pressure on nodes connecting to this node
+        self.bounded_executor.spawn(inbound_fut).await;
+    }
+
+    async fn perform_upgrade_procedure(
+        quic_config: &QuicConfig,
+        node_identity: &NodeIdentity,
+        peer_manager: &PeerManager,
+        conn_man_notifier: mpsc::Sender<ConnectionManagerEvent>,
+        connection: quinn::Connection,
+        peer_addr: Multiaddr,
+        our_supported_protocols: Arc<Vec<ProtocolId>>,
+        bandwidth: BandwidthManager,
+        config: &ConnectionManagerConfig,
+    ) -> Result<PeerConnection, ConnectionManagerError> {
+        const CONNECTION_DIRECTION: ConnectionDirection = ConnectionDirection::Inbound;
+        let mut handshake = quic_config.upgrade_inbound(connection).await?;
+        let authenticated_public_key = handshake.authenticated_public_key.clone();
+        debug!(
+            target: LOG_TARGET,
+            "QUIC handshake completed for peer at address '{}' with public key '{}'", peer_addr, authenticated_public_key
+        );
+
+        // Check if we know the peer and if it is banned
+        let known_peer = common::find_unbanned_peer(peer_manager, &authenticated_public_key).await?;
+
+        let peer_identity_result = common::perform_identity_exchange(
+            &mut handshake.stream,
+            node_identity,
+            &*our_supported_protocols,
+            config.network_info.clone(),
+            &peer_addr,
+        )
+        .await;
+        let peer_identity =
+            common::ban_on_offence(peer_manager, &authenticated_public_key, peer_identity_result).await?;
+
+        let valid_peer_identity_result = common::validate_peer_identity_message(
+            &config.peer_validation_config,
+            &authenticated_public_key,
+            peer_identity,
+        );
+        let valid_peer_identity =
+            common::ban_on_offence(peer_manager, &authenticated_public_key, valid_peer_identity_result).await?;
+
+        let peer = common::create_or_update_peer_from_validated_peer_identity(
+            known_peer,
+            authenticated_public_key,
+            &valid_peer_identity,
+        );
+        common::notify_observed_address(&conn_man_notifier, &peer.node_id, &valid_peer_identity).await;
+
+        let muxer = Yamux::upgrade_quic_connection(handshake.connection);
+        let conn = peer_connection::create(
+            muxer,
+            peer_addr,
+            peer.node_id.clone(),
+            peer.features,
+            CONNECTION_DIRECTION,
+            conn_man_notifier,
+            our_supported_protocols,
+            valid_peer_identity.metadata.supported_protocols,
+            bandwidth,
+        );
+
+        peer_manager.add_peer(peer).await?;
+
+        Ok(conn)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use tari_test_utils::unpack_enum;
+
+    use super::*;
+    use crate::peer_manager::PeerFeatures;
+
+    fn create_quic_config(network_byte: u8) -> QuicConfig {
+        let node_identity = Arc::new(NodeIdentity::random_for_test(None, PeerFeatures::COMMUNICATION_NODE));
+        QuicConfig::new(
+            QuicTransport::new(),
+            node_identity,
+            network_byte,
+            Duration::from_secs(5),
+        )
+    }
+
+    #[tokio::test]
+    async fn handshake_authenticates_both_peers() {
+        let dialer = create_quic_config(0x01);
+        let listener = create_quic_config(0x01);
+        let (mut inbound, addr) = listener
+            .transport
+            .listen(&"/ip4/127.0.0.1/udp/0/quic".parse().unwrap())
+            .await
+            .unwrap();
+
+        let listener_public_key = listener.node_identity.public_key().clone();
+
+        let listener_task = tokio::spawn(async move {
+            let (connection, _) = inbound.next().await.unwrap().unwrap();
+            listener
+                .upgrade_inbound(connection)
+                .await
+                .map(|h| h.authenticated_public_key)
+        });
+
+        let outbound = dialer.dial(&addr).await.unwrap();
+        let inbound_public_key = listener_task.await.unwrap().unwrap();
+
+        assert_eq!(inbound_public_key, *dialer.node_identity.public_key());
+        assert_eq!(outbound.authenticated_public_key, listener_public_key);
+    }
+
+    #[tokio::test]
+    async fn handshake_rejects_invalid_network_byte() {
+        let dialer = create_quic_config(0x01);
+        let listener = create_quic_config(0x02);
+        let (mut inbound, addr) = listener
+            .transport
+            .listen(&"/ip4/127.0.0.1/udp/0/quic".parse().unwrap())
+            .await
+            .unwrap();
+
+        let listener_task = tokio::spawn(async move {
+            let (connection, _) = inbound.next().await.unwrap().unwrap();
+            listener
+                .upgrade_inbound(connection)
+                .await
+                .map(|h| h.authenticated_public_key)
+        });
+
+        let _result = dialer.dial(&addr).await;
+        let err = listener_task.await.unwrap().unwrap_err();
+        unpack_enum!(ConnectionManagerError::QuicHandshakeFailed(_details) = err);
+    }
+}
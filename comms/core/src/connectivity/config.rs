@@ -22,6 +22,8 @@
 
 use std::time::Duration;
 
+use super::PeerReputationConfig;
+
 /// Connectivity actor configuration
 #[derive(Debug, Clone, Copy)]
 pub struct ConnectivityConfig {
@@ -49,6 +51,8 @@ pub struct ConnectivityConfig {
     /// next connection attempt.
     /// Default: 24 hours
     pub expire_peer_last_seen_duration: Duration,
+    /// Peer reputation scoring configuration
+    pub reputation: PeerReputationConfig,
 }
 
 impl Default for ConnectivityConfig {
@@ -62,6 +66,7 @@ impl Default for ConnectivityConfig {
             max_failures_mark_offline: 1,
             connection_tie_break_linger: Duration::from_secs(2),
             expire_peer_last_seen_duration: Duration::from_secs(24 * 60 * 60),
+            reputation: PeerReputationConfig::default(),
         }
     }
 }
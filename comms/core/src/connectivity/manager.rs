@@ -42,9 +42,11 @@ use super::{
     connection_pool::{ConnectionPool, ConnectionStatus},
     connection_stats::PeerConnectionStats,
     error::ConnectivityError,
+    reputation::PeerReputationTable,
     requester::{ConnectivityEvent, ConnectivityRequest},
     selection::ConnectivitySelection,
     ConnectivityEventTx,
+    ReputationEvent,
 };
 use crate::{
     connection_manager::{
@@ -89,12 +91,14 @@ impl ConnectivityManager {
             peer_manager: self.peer_manager.clone(),
             event_tx: self.event_tx,
             connection_stats: HashMap::new(),
+            reputation: PeerReputationTable::new(self.config.reputation),
             node_identity: self.node_identity,
             pool: ConnectionPool::new(),
             shutdown_signal: self.shutdown_signal,
             #[cfg(feature = "metrics")]
             uptime: Some(Instant::now()),
             allow_list: vec![],
+            static_peer_groups: HashMap::new(),
         }
         .spawn()
     }
@@ -147,11 +151,13 @@ struct ConnectivityManagerActor {
     peer_manager: Arc<PeerManager>,
     event_tx: ConnectivityEventTx,
     connection_stats: HashMap<NodeId, PeerConnectionStats>,
+    reputation: PeerReputationTable,
     pool: ConnectionPool,
     shutdown_signal: ShutdownSignal,
     #[cfg(feature = "metrics")]
     uptime: Option<Instant>,
     allow_list: Vec<NodeId>,
+    static_peer_groups: HashMap<String, Vec<NodeId>>,
 }
 
 impl ConnectivityManagerActor {
@@ -197,6 +203,7 @@ impl ConnectivityManagerActor {
 
                 _ = ticker.tick() => {
                     self.cleanup_connection_stats();
+                    self.prune_reputations();
                     if let Err(err) = self.refresh_connection_pool().await {
                         error!(target: LOG_TARGET, "Error when refreshing connection pools: {:?}", err);
                     }
@@ -256,7 +263,12 @@ impl ConnectivityManagerActor {
                 let _result = reply.send(states);
             },
             BanPeer(node_id, duration, reason) => {
-                if self.allow_list.contains(&node_id) {
+                if self.is_static_peer(&node_id) {
+                    info!(
+                        target: LOG_TARGET,
+                        "Peer is excluded from being banned as it is a static peer, NodeId: {:?}", node_id
+                    );
+                } else if self.allow_list.contains(&node_id) {
                     info!(
                         target: LOG_TARGET,
                         "Peer is excluded from being banned as it was found in the AllowList, NodeId: {:?}", node_id
@@ -267,6 +279,23 @@ impl ConnectivityManagerActor {
                     // we banned the peer
                 }
             },
+            ReportPeer(node_id, event) => {
+                if let Err(err) = self.handle_report_peer(node_id, event).await {
+                    error!(target: LOG_TARGET, "Error when handling peer reputation event: {:?}", err);
+                }
+            },
+            GetPeerReputation(node_id, reply) => {
+                let _result = reply.send(self.reputation.get(&node_id, Instant::now()));
+            },
+            GetAllPeerReputations(reply) => {
+                let _result = reply.send(self.reputation.all(Instant::now()));
+            },
+            SetStaticPeerGroup(name, node_ids) => {
+                self.set_static_peer_group(name, node_ids).await;
+            },
+            GetStaticPeerGroups(reply) => {
+                let _result = reply.send(self.static_peer_groups.clone());
+            },
             AddPeerToAllowList(node_id) => {
                 if !self.allow_list.contains(&node_id) {
                     self.allow_list.push(node_id)
@@ -389,6 +418,7 @@ impl ConnectivityManagerActor {
         if self.config.is_connection_reaping_enabled {
             self.reap_inactive_connections().await;
         }
+        self.dial_static_peers().await;
         self.update_connectivity_status();
         self.update_connectivity_metrics();
         Ok(())
@@ -403,9 +433,16 @@ impl ConnectivityManagerActor {
             return;
         }
 
+        let static_peer_groups = &self.static_peer_groups;
         let mut connections = self
             .pool
             .get_inactive_outbound_connections_mut(self.config.reaper_min_inactive_age);
+        // Static peers are never reaped
+        connections.retain(|conn| {
+            !static_peer_groups
+                .values()
+                .any(|group| group.contains(conn.peer_node_id()))
+        });
         connections.truncate(excess_connections);
         for conn in connections {
             if !conn.is_connected() {
@@ -459,7 +496,7 @@ impl ConnectivityManagerActor {
             self.pool.count_connected_nodes()
         );
 
-        let conns = selection.select(&self.pool);
+        let conns = selection.select(&self.pool, &self.reputation);
         debug!(target: LOG_TARGET, "Selected {} connections(s)", conns.len());
 
         Ok(conns.into_iter().cloned().collect())
@@ -896,6 +933,90 @@ impl ConnectivityManagerActor {
         Ok(())
     }
 
+    async fn handle_report_peer(&mut self, node_id: NodeId, event: ReputationEvent) -> Result<(), ConnectivityError> {
+        let ban_duration = match self.reputation.record(&node_id, event, Instant::now()) {
+            Some(duration) => duration,
+            None => {
+                trace!(
+                    target: LOG_TARGET,
+                    "Reputation event {} recorded for peer {}",
+                    event,
+                    node_id.short_str()
+                );
+                return Ok(());
+            },
+        };
+
+        if self.allow_list.contains(&node_id) || self.is_static_peer(&node_id) {
+            info!(
+                target: LOG_TARGET,
+                "Peer {} reputation fell below the ban threshold but it is excluded from being banned as it was found \
+                 in the AllowList or is a static peer",
+                node_id
+            );
+            return Ok(());
+        }
+
+        self.ban_peer(
+            &node_id,
+            ban_duration,
+            format!("Reputation score fell below the ban threshold (last event: {})", event),
+        )
+        .await
+    }
+
+    fn is_static_peer(&self, node_id: &NodeId) -> bool {
+        self.static_peer_groups.values().any(|group| group.contains(node_id))
+    }
+
+    async fn set_static_peer_group(&mut self, name: String, node_ids: Vec<NodeId>) {
+        if node_ids.is_empty() {
+            if self.static_peer_groups.remove(&name).is_some() {
+                info!(target: LOG_TARGET, "Removed static peer group '{}'", name);
+            }
+            return;
+        }
+
+        info!(
+            target: LOG_TARGET,
+            "Static peer group '{}' set with {} peer(s)",
+            name,
+            node_ids.len()
+        );
+        self.static_peer_groups.insert(name, node_ids);
+        self.dial_static_peers().await;
+    }
+
+    /// Dials any static peer that is not currently connected or being connected to
+    async fn dial_static_peers(&mut self) {
+        let mut to_dial = self
+            .static_peer_groups
+            .values()
+            .flatten()
+            .filter(|node_id| {
+                !matches!(
+                    self.pool.get_connection_status(node_id),
+                    ConnectionStatus::Connected | ConnectionStatus::Connecting | ConnectionStatus::Retrying
+                )
+            })
+            .cloned()
+            .collect::<Vec<_>>();
+        to_dial.sort();
+        to_dial.dedup();
+
+        for node_id in to_dial {
+            debug!(target: LOG_TARGET, "Dialing static peer {}", node_id.short_str());
+            self.handle_dial_peer(node_id, None).await;
+        }
+    }
+
+    fn prune_reputations(&mut self) {
+        let num_pruned = self.reputation.prune(Instant::now());
+        if num_pruned > 0 {
+            debug!(target: LOG_TARGET, "Pruned {} neutral peer reputation(s)", num_pruned);
+        }
+    }
+
     fn cleanup_connection_stats(&mut self) {
         let mut to_remove = Vec::new();
         for node_id in self.connection_stats.keys() {
@@ -26,6 +26,13 @@
 //!
 //! It emits [ConnectivityEvent](crate::connectivity::ConnectivityEvent)s that can keep client components
 //! in the loop with the state of the node's connectivity.
+//!
+//! The actor also maintains a decaying reputation score for each peer. Components report
+//! [ReputationEvent](crate::connectivity::ReputationEvent)s and peers with poor reputations are deprioritised when
+//! selecting connections and are temporarily banned once their score falls below a threshold.
+//!
+//! Named static peer groups may be registered with the actor. Connections to static peers are redialed on every
+//! connection pool refresh and are never reaped or banned.
 
 mod connection_stats;
 
@@ -44,6 +51,9 @@ pub use manager::ConnectivityStatus;
 #[cfg(feature = "metrics")]
 mod metrics;
 
+mod reputation;
+pub use reputation::{PeerReputation, PeerReputationConfig, ReputationEvent};
+
 mod requester;
 pub(crate) use requester::ConnectivityRequest;
 pub use requester::{ConnectivityEvent, ConnectivityEventRx, ConnectivityEventTx, ConnectivityRequester};
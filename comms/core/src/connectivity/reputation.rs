@@ -0,0 +1,387 @@
+// Copyright 2024. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! # Peer reputation
+//!
+//! Tracks a decaying reputation score for each peer. Components report weighted positive and negative
+//! [ReputationEvent]s for a peer. Scores decay exponentially towards zero over time so that old behaviour is
+//! eventually forgotten. Once a peer's score falls to or below the configured ban threshold, the peer is temporarily
+//! banned and the score is reset. Each subsequent reputation ban doubles the ban duration, up to a maximum.
+
+use std::{
+    collections::HashMap,
+    fmt,
+    fmt::{Display, Formatter},
+    time::{Duration, Instant},
+};
+
+use crate::{peer_manager::NodeId, BAN_DURATION_LONG};
+
+/// Scores with an absolute value less than this are considered neutral and may be pruned
+const NEUTRAL_SCORE_EPSILON: f64 = 0.5;
+
+/// Events that affect a peer's reputation score.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReputationEvent {
+    /// The peer provided a useful response to a request
+    UsefulResponse,
+    /// The peer propagated a valid block
+    ValidBlock,
+    /// The peer responded, but took longer than expected
+    SlowResponse,
+    /// The peer did not respond in time or the request failed
+    Unresponsive,
+    /// The peer committed a minor offence that is likely not malicious
+    MinorOffence,
+    /// The peer sent an invalid or malformed message
+    InvalidMessage,
+    /// The peer sent an invalid block or invalid chain data
+    InvalidBlock,
+}
+
+impl ReputationEvent {
+    /// The amount that this event adds to (or subtracts from) a peer's reputation score
+    pub fn weight(self) -> f64 {
+        #[allow(clippy::enum_glob_use)]
+        use ReputationEvent::*;
+        match self {
+            UsefulResponse => 1.0,
+            ValidBlock => 3.0,
+            SlowResponse => -2.0,
+            Unresponsive => -5.0,
+            MinorOffence => -10.0,
+            InvalidMessage => -25.0,
+            InvalidBlock => -40.0,
+        }
+    }
+
+    /// Returns true if this event increases a peer's reputation score
+    pub fn is_positive(self) -> bool {
+        self.weight() > 0.0
+    }
+}
+
+impl Display for ReputationEvent {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+/// Peer reputation configuration
+#[derive(Debug, Clone, Copy)]
+pub struct PeerReputationConfig {
+    /// The maximum positive score a peer can accumulate. This prevents a long history of good behaviour from
+    /// shielding a peer that starts misbehaving.
+    /// Default: 100
+    pub max_score: f64,
+    /// A peer is temporarily banned when its score falls to or below this value.
+    /// Default: -100
+    pub ban_threshold: f64,
+    /// The time it takes for a score to decay to half its value.
+    /// Default: 30 minutes
+    pub decay_half_life: Duration,
+    /// The duration of the first reputation ban. Each subsequent ban doubles the previous duration.
+    /// Default: 10 minutes
+    pub ban_duration: Duration,
+    /// The maximum duration of a reputation ban.
+    /// Default: 2 hours
+    pub max_ban_duration: Duration,
+}
+
+impl Default for PeerReputationConfig {
+    fn default() -> Self {
+        Self {
+            max_score: 100.0,
+            ban_threshold: -100.0,
+            decay_half_life: Duration::from_secs(30 * 60),
+            ban_duration: Duration::from_secs(10 * 60),
+            max_ban_duration: BAN_DURATION_LONG,
+        }
+    }
+}
+
+/// The reputation of a single peer.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PeerReputation {
+    node_id: NodeId,
+    score: f64,
+    last_updated: Instant,
+    num_positive_events: u64,
+    num_negative_events: u64,
+    num_bans: u32,
+    last_event: Option<ReputationEvent>,
+}
+
+impl PeerReputation {
+    fn new(node_id: NodeId, now: Instant) -> Self {
+        Self {
+            node_id,
+            score: 0.0,
+            last_updated: now,
+            num_positive_events: 0,
+            num_negative_events: 0,
+            num_bans: 0,
+            last_event: None,
+        }
+    }
+
+    pub fn node_id(&self) -> &NodeId {
+        &self.node_id
+    }
+
+    /// The reputation score at the time this reputation was retrieved
+    pub fn score(&self) -> f64 {
+        self.score
+    }
+
+    pub fn last_updated(&self) -> Instant {
+        self.last_updated
+    }
+
+    pub fn num_positive_events(&self) -> u64 {
+        self.num_positive_events
+    }
+
+    pub fn num_negative_events(&self) -> u64 {
+        self.num_negative_events
+    }
+
+    /// The number of times this peer has been banned because of its reputation
+    pub fn num_bans(&self) -> u32 {
+        self.num_bans
+    }
+
+    pub fn last_event(&self) -> Option<ReputationEvent> {
+        self.last_event
+    }
+
+    fn score_at(&self, now: Instant, half_life: Duration) -> f64 {
+        let elapsed = now.saturating_duration_since(self.last_updated);
+        if elapsed.is_zero() {
+            return self.score;
+        }
+        self.score * 0.5f64.powf(elapsed.as_secs_f64() / half_life.as_secs_f64())
+    }
+
+    fn decayed(&self, now: Instant, half_life: Duration) -> Self {
+        Self {
+            score: self.score_at(now, half_life),
+            ..self.clone()
+        }
+    }
+}
+
+impl Display for PeerReputation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: score = {:.1}, events = +{}/-{}, bans = {}",
+            self.node_id, self.score, self.num_positive_events, self.num_negative_events, self.num_bans
+        )
+    }
+}
+
+/// Reputation scores for all peers that have had at least one reputation event reported.
+#[derive(Debug, Clone)]
+pub struct PeerReputationTable {
+    config: PeerReputationConfig,
+    peers: HashMap<NodeId, PeerReputation>,
+}
+
+impl PeerReputationTable {
+    pub fn new(config: PeerReputationConfig) -> Self {
+        Self {
+            config,
+            peers: HashMap::new(),
+        }
+    }
+
+    /// Records a reputation event for a peer. If the peer's score falls to or below the ban threshold, the score is
+    /// reset and the duration that the peer should be banned for is returned.
+    pub fn record(&mut self, node_id: &NodeId, event: ReputationEvent, now: Instant) -> Option<Duration> {
+        let config = self.config;
+        let reputation = self
+            .peers
+            .entry(node_id.clone())
+            .or_insert_with(|| PeerReputation::new(node_id.clone(), now));
+
+        reputation.score = (reputation.score_at(now, config.decay_half_life) + event.weight()).min(config.max_score);
+        reputation.last_updated = now;
+        reputation.last_event = Some(event);
+        if event.is_positive() {
+            reputation.num_positive_events += 1;
+        } else {
+            reputation.num_negative_events += 1;
+        }
+
+        if reputation.score > config.ban_threshold {
+            return None;
+        }
+
+        reputation.score = 0.0;
+        let ban_duration = config
+            .ban_duration
+            .saturating_mul(2u32.saturating_pow(reputation.num_bans))
+            .min(config.max_ban_duration);
+        reputation.num_bans = reputation.num_bans.saturating_add(1);
+        Some(ban_duration)
+    }
+
+    /// Returns the current reputation score for a peer. Peers without any recorded events have a neutral score of
+    /// zero.
+    pub fn score(&self, node_id: &NodeId, now: Instant) -> f64 {
+        self.peers
+            .get(node_id)
+            .map(|r| r.score_at(now, self.config.decay_half_life))
+            .unwrap_or(0.0)
+    }
+
+    /// Returns the current reputation for a peer, if any events have been recorded for it.
+    pub fn get(&self, node_id: &NodeId, now: Instant) -> Option<PeerReputation> {
+        self.peers
+            .get(node_id)
+            .map(|r| r.decayed(now, self.config.decay_half_life))
+    }
+
+    /// Returns the current reputations for all peers, ordered from highest to lowest score.
+    pub fn all(&self, now: Instant) -> Vec<PeerReputation> {
+        let mut reputations = self
+            .peers
+            .values()
+            .map(|r| r.decayed(now, self.config.decay_half_life))
+            .collect::<Vec<_>>();
+        reputations.sort_by(|a, b| b.score.total_cmp(&a.score));
+        reputations
+    }
+
+    /// Removes peers that have decayed to a neutral score and have not had an event recorded for longer than the
+    /// maximum ban duration. Returns the number of peers removed.
+    pub fn prune(&mut self, now: Instant) -> usize {
+        let config = self.config;
+        let before = self.peers.len();
+        self.peers.retain(|_, r| {
+            r.score_at(now, config.decay_half_life).abs() >= NEUTRAL_SCORE_EPSILON ||
+                now.saturating_duration_since(r.last_updated) < config.max_ban_duration
+        });
+        before - self.peers.len()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_utils::node_id;
+
+    fn create_table() -> PeerReputationTable {
+        PeerReputationTable::new(PeerReputationConfig::default())
+    }
+
+    #[test]
+    fn it_accumulates_and_caps_scores() {
+        let mut table = create_table();
+        let peer = node_id::random();
+        let now = Instant::now();
+        assert_eq!(table.score(&peer, now), 0.0);
+
+        table.record(&peer, ReputationEvent::UsefulResponse, now);
+        table.record(&peer, ReputationEvent::SlowResponse, now);
+        assert_eq!(table.score(&peer, now), -1.0);
+
+        for _ in 0..100 {
+            table.record(&peer, ReputationEvent::ValidBlock, now);
+        }
+        assert_eq!(table.score(&peer, now), 100.0);
+
+        let reputation = table.get(&peer, now).unwrap();
+        assert_eq!(reputation.num_positive_events(), 101);
+        assert_eq!(reputation.num_negative_events(), 1);
+        assert_eq!(reputation.last_event(), Some(ReputationEvent::ValidBlock));
+    }
+
+    #[test]
+    fn it_decays_scores_over_time() {
+        let mut table = create_table();
+        let peer = node_id::random();
+        let now = Instant::now();
+        table.record(&peer, ReputationEvent::InvalidMessage, now);
+
+        let half_life = PeerReputationConfig::default().decay_half_life;
+        let score = table.score(&peer, now + half_life);
+        assert!((score - -12.5).abs() < 0.001);
+        let score = table.score(&peer, now + half_life * 2);
+        assert!((score - -6.25).abs() < 0.001);
+    }
+
+    #[test]
+    fn it_returns_an_escalating_ban_duration_below_the_threshold() {
+        let mut table = create_table();
+        let peer = node_id::random();
+        let now = Instant::now();
+        let config = PeerReputationConfig::default();
+
+        assert!(table.record(&peer, ReputationEvent::InvalidBlock, now).is_none());
+        assert!(table.record(&peer, ReputationEvent::InvalidBlock, now).is_none());
+        let ban_duration = table.record(&peer, ReputationEvent::InvalidBlock, now).unwrap();
+        assert_eq!(ban_duration, config.ban_duration);
+        assert_eq!(table.score(&peer, now), 0.0);
+
+        for _ in 0..2 {
+            table.record(&peer, ReputationEvent::InvalidBlock, now);
+        }
+        let ban_duration = table.record(&peer, ReputationEvent::InvalidBlock, now).unwrap();
+        assert_eq!(ban_duration, config.ban_duration * 2);
+
+        for _ in 0..10 {
+            for _ in 0..3 {
+                table.record(&peer, ReputationEvent::InvalidBlock, now);
+            }
+        }
+        let reputation = table.get(&peer, now).unwrap();
+        assert_eq!(reputation.num_bans(), 12);
+        for _ in 0..2 {
+            table.record(&peer, ReputationEvent::InvalidBlock, now);
+        }
+        let ban_duration = table.record(&peer, ReputationEvent::InvalidBlock, now).unwrap();
+        assert_eq!(ban_duration, config.max_ban_duration);
+    }
+
+    #[test]
+    fn it_prunes_neutral_peers() {
+        let mut table = create_table();
+        let peer1 = node_id::random();
+        let peer2 = node_id::random();
+        let now = Instant::now();
+        table.record(&peer1, ReputationEvent::UsefulResponse, now);
+        table.record(&peer2, ReputationEvent::InvalidBlock, now);
+
+        assert_eq!(table.prune(now), 0);
+        let config = PeerReputationConfig::default();
+        let later = now + config.max_ban_duration;
+        assert_eq!(table.prune(later), 1);
+        assert!(table.get(&peer1, later).is_none());
+        assert!(table.get(&peer2, later).is_some());
+
+        let all = table.all(later);
+        assert_eq!(all.len(), 1);
+        assert_eq!(all[0].node_id(), &peer2);
+    }
+}
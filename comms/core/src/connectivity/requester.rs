@@ -21,6 +21,7 @@
 //  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 use std::{
+    collections::HashMap,
     fmt,
     time::{Duration, Instant},
 };
@@ -37,6 +38,8 @@ use super::{
     error::ConnectivityError,
     manager::ConnectivityStatus,
     ConnectivitySelection,
+    PeerReputation,
+    ReputationEvent,
 };
 use crate::{
     connection_manager::ConnectionManagerError,
@@ -101,6 +104,11 @@ pub enum ConnectivityRequest {
     AddPeerToAllowList(NodeId),
     RemovePeerFromAllowList(NodeId),
     GetPeerStats(NodeId, oneshot::Sender<Option<Peer>>),
+    ReportPeer(NodeId, ReputationEvent),
+    GetPeerReputation(NodeId, oneshot::Sender<Option<PeerReputation>>),
+    GetAllPeerReputations(oneshot::Sender<Vec<PeerReputation>>),
+    SetStaticPeerGroup(String, Vec<NodeId>),
+    GetStaticPeerGroups(oneshot::Sender<HashMap<String, Vec<NodeId>>>),
 }
 
 /// Handle to make requests and read events from the ConnectivityManager actor.
@@ -263,6 +271,37 @@ impl ConnectivityRequester {
             .await
     }
 
+    /// Reports a reputation event for a peer. If the peer's reputation score falls below the configured threshold, the
+    /// peer is temporarily banned.
+    pub async fn report_peer(&mut self, node_id: NodeId, event: ReputationEvent) -> Result<(), ConnectivityError> {
+        self.sender
+            .send(ConnectivityRequest::ReportPeer(node_id, event))
+            .await
+            .map_err(|_| ConnectivityError::ActorDisconnected)?;
+        Ok(())
+    }
+
+    /// Get the current reputation of a peer. None is returned if no reputation events have been reported for the peer.
+    pub async fn get_peer_reputation(&mut self, node_id: NodeId) -> Result<Option<PeerReputation>, ConnectivityError> {
+        let (reply_tx, reply_rx) = oneshot::channel();
+        self.sender
+            .send(ConnectivityRequest::GetPeerReputation(node_id, reply_tx))
+            .await
+            .map_err(|_| ConnectivityError::ActorDisconnected)?;
+        reply_rx.await.map_err(|_| ConnectivityError::ActorResponseCancelled)
+    }
+
+    /// Get the current reputations of all peers that have had reputation events reported, ordered from highest to
+    /// lowest score.
+    pub async fn get_all_peer_reputations(&mut self) -> Result<Vec<PeerReputation>, ConnectivityError> {
+        let (reply_tx, reply_rx) = oneshot::channel();
+        self.sender
+            .send(ConnectivityRequest::GetAllPeerReputations(reply_tx))
+            .await
+            .map_err(|_| ConnectivityError::ActorDisconnected)?;
+        reply_rx.await.map_err(|_| ConnectivityError::ActorResponseCancelled)
+    }
+
     /// Adds a peer to an allow list, preventing it from being banned.
     pub async fn add_peer_to_allow_list(&mut self, node_id: NodeId) -> Result<(), ConnectivityError> {
         self.sender
@@ -281,6 +320,31 @@ impl ConnectivityRequester {
         Ok(())
     }
 
+    /// Sets the peers in a named static peer group, replacing any existing members of that group. Connectivity will
+    /// always attempt to maintain connections to static peers and they are excluded from being banned or reaped. An
+    /// empty list removes the group.
+    pub async fn set_static_peer_group(
+        &mut self,
+        name: String,
+        node_ids: Vec<NodeId>,
+    ) -> Result<(), ConnectivityError> {
+        self.sender
+            .send(ConnectivityRequest::SetStaticPeerGroup(name, node_ids))
+            .await
+            .map_err(|_| ConnectivityError::ActorDisconnected)?;
+        Ok(())
+    }
+
+    /// Get all static peer groups
+    pub async fn get_static_peer_groups(&mut self) -> Result<HashMap<String, Vec<NodeId>>, ConnectivityError> {
+        let (reply_tx, reply_rx) = oneshot::channel();
+        self.sender
+            .send(ConnectivityRequest::GetStaticPeerGroups(reply_tx))
+            .await
+            .map_err(|_| ConnectivityError::ActorDisconnected)?;
+        reply_rx.await.map_err(|_| ConnectivityError::ActorResponseCancelled)
+    }
+
     /// Returns a Future that resolves when the connectivity actor has started.
     pub async fn wait_started(&mut self) -> Result<(), ConnectivityError> {
         let (reply_tx, reply_rx) = oneshot::channel();
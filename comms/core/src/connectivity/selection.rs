@@ -20,13 +20,16 @@
 //  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 //  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use std::{fmt, fmt::Display};
+use std::{fmt, fmt::Display, time::Instant};
 
 use rand::{rngs::OsRng, seq::SliceRandom};
 
-use super::connection_pool::ConnectionPool;
+use super::{connection_pool::ConnectionPool, reputation::PeerReputationTable};
 use crate::{connectivity::connection_pool::ConnectionStatus, peer_manager::NodeId, PeerConnection};
 
+/// The reputation score difference that makes a peer e (~2.7) times more likely to be randomly selected
+const REPUTATION_WEIGHT_SCALE: f64 = 20.0;
+
 /// Selection query for PeerConnections.
 ///
 /// ```ignore
@@ -60,7 +63,7 @@ impl ConnectivitySelection {
     }
 
     /// Returns a query that will return `n` connections for peers with `PeerFeatures::COMMUNICATION_NODES` excluding
-    /// the given [NodeId]s.
+    /// the given [NodeId]s. Peers with a higher reputation are more likely to be selected.
     ///
     /// [NodeId](crate::peer_manager::NodeId)
     pub fn random_nodes(n: usize, exclude: Vec<NodeId>) -> Self {
@@ -70,7 +73,8 @@ impl ConnectivitySelection {
         }
     }
 
-    /// Select `n` peer connections ordered by closeness to `node_id`, exclusing the given `exclude` [NodeId]s.
+    /// Select `n` peer connections ordered by closeness to `node_id`, exclusing the given `exclude` [NodeId]s. Peers
+    /// with a negative reputation are only selected if there are not enough other peers.
     ///
     /// [NodeId](crate::peer_manager::NodeId)
     pub fn closest_to(node_id: NodeId, n: usize, exclude: Vec<NodeId>) -> Self {
@@ -81,13 +85,13 @@ impl ConnectivitySelection {
     }
 
     /// Select peers from the pool according to the ConnectivitySelection
-    pub fn select<'a>(&self, pool: &'a ConnectionPool) -> Vec<&'a PeerConnection> {
+    pub fn select<'a>(&self, pool: &'a ConnectionPool, reputation: &PeerReputationTable) -> Vec<&'a PeerConnection> {
         use SelectionMode::{AllNodes, ClosestTo, RandomNodes};
         match &self.selection_mode {
             AllNodes => select_connected_nodes(pool, &self.excluded_peers),
-            RandomNodes(n) => select_random_nodes(pool, *n, &self.excluded_peers),
+            RandomNodes(n) => select_random_nodes(pool, *n, &self.excluded_peers, reputation),
             ClosestTo(dest_node_id, n) => {
-                let mut connections = select_closest(pool, dest_node_id, &self.excluded_peers);
+                let mut connections = select_closest(pool, dest_node_id, &self.excluded_peers, reputation);
                 connections.truncate(*n);
                 connections.to_vec()
             },
@@ -107,21 +111,39 @@ fn select_connected_nodes<'a>(pool: &'a ConnectionPool, exclude: &[NodeId]) -> V
     })
 }
 
-fn select_closest<'a>(pool: &'a ConnectionPool, node_id: &NodeId, exclude: &[NodeId]) -> Vec<&'a PeerConnection> {
+fn select_closest<'a>(
+    pool: &'a ConnectionPool,
+    node_id: &NodeId,
+    exclude: &[NodeId],
+    reputation: &PeerReputationTable,
+) -> Vec<&'a PeerConnection> {
     let mut nodes = select_connected_nodes(pool, exclude);
+    let now = Instant::now();
 
-    nodes.sort_by(|a, b| {
-        let dist_a = a.peer_node_id().distance(node_id);
-        let dist_b = b.peer_node_id().distance(node_id);
-        dist_a.cmp(&dist_b)
+    // Peers with a poor reputation are ordered after all other peers
+    nodes.sort_by_cached_key(|conn| {
+        let has_poor_reputation = reputation.score(conn.peer_node_id(), now) < 0.0;
+        (has_poor_reputation, conn.peer_node_id().distance(node_id))
     });
 
     nodes
 }
 
-fn select_random_nodes<'a>(pool: &'a ConnectionPool, n: usize, exclude: &[NodeId]) -> Vec<&'a PeerConnection> {
+fn select_random_nodes<'a>(
+    pool: &'a ConnectionPool,
+    n: usize,
+    exclude: &[NodeId],
+    reputation: &PeerReputationTable,
+) -> Vec<&'a PeerConnection> {
     let nodes = select_connected_nodes(pool, exclude);
-    nodes.choose_multiple(&mut OsRng, n).copied().collect()
+    let now = Instant::now();
+    match nodes.choose_multiple_weighted(&mut OsRng, n, |conn| {
+        (reputation.score(conn.peer_node_id(), now) / REPUTATION_WEIGHT_SCALE).exp()
+    }) {
+        Ok(selected) => selected.copied().collect(),
+        // Only occurs if a weight is invalid, fall back to uniform selection
+        Err(_) => nodes.choose_multiple(&mut OsRng, n).copied().collect(),
+    }
 }
 
 impl Display for ConnectivitySelection {
@@ -155,6 +177,7 @@ mod test {
     use super::*;
     use crate::{
         connection_manager::PeerConnectionRequest,
+        connectivity::{PeerReputationConfig, ReputationEvent},
         peer_manager::NodeDistance,
         test_utils::{mocks::create_dummy_peer_connection, node_id, node_identity::build_node_identity},
     };
@@ -172,14 +195,19 @@ mod test {
         (pool, receivers)
     }
 
+    fn empty_reputation_table() -> PeerReputationTable {
+        PeerReputationTable::new(PeerReputationConfig::default())
+    }
+
     #[test]
     fn select_random() {
         let (pool, _receivers) = create_pool_with_connections(10);
-        let conns = select_random_nodes(&pool, 500, &[]);
+        let reputation = empty_reputation_table();
+        let conns = select_random_nodes(&pool, 500, &[], &reputation);
         assert_eq!(conns.len(), 10);
 
         let first_node = conns.first().unwrap().peer_node_id().clone();
-        let conns = select_random_nodes(&pool, 10, &[first_node.clone()]);
+        let conns = select_random_nodes(&pool, 10, &[first_node.clone()], &reputation);
         assert_eq!(conns.len(), 9);
         assert!(conns.iter().all(|c| c.peer_node_id() != &first_node));
     }
@@ -188,7 +216,7 @@ mod test {
     fn select_closest_ordering() {
         let (pool, _receivers) = create_pool_with_connections(10);
         let subject_node_identity = build_node_identity(Default::default());
-        let conns = select_closest(&pool, subject_node_identity.node_id(), &[]);
+        let conns = select_closest(&pool, subject_node_identity.node_id(), &[], &empty_reputation_table());
         assert_eq!(conns.len(), 10);
 
         let mut last_dist = NodeDistance::zero();
@@ -203,7 +231,21 @@ mod test {
     fn select_closest_empty() {
         let pool = ConnectionPool::new();
         let node_identity = build_node_identity(Default::default());
-        let conns = select_closest(&pool, node_identity.node_id(), &[]);
+        let conns = select_closest(&pool, node_identity.node_id(), &[], &empty_reputation_table());
         assert!(conns.is_empty());
     }
+
+    #[test]
+    fn select_closest_deprioritises_poor_reputation() {
+        let (pool, _receivers) = create_pool_with_connections(10);
+        let subject_node_identity = build_node_identity(Default::default());
+        let mut reputation = empty_reputation_table();
+        let conns = select_closest(&pool, subject_node_identity.node_id(), &[], &reputation);
+        let closest = conns.first().unwrap().peer_node_id().clone();
+
+        reputation.record(&closest, ReputationEvent::InvalidMessage, Instant::now());
+        let conns = select_closest(&pool, subject_node_identity.node_id(), &[], &reputation);
+        assert_eq!(conns.len(), 10);
+        assert_eq!(conns.last().unwrap().peer_node_id(), &closest);
+    }
 }
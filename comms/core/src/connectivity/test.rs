@@ -24,7 +24,7 @@ use std::{sync::Arc, time::Duration};
 
 use futures::{future, StreamExt};
 use tari_shutdown::Shutdown;
-use tari_test_utils::{collect_try_recv, streams, unpack_enum};
+use tari_test_utils::{async_assert_eventually, collect_try_recv, streams, unpack_enum};
 use tokio::sync::{broadcast, mpsc};
 
 use super::{
@@ -33,6 +33,7 @@ use super::{
     manager::ConnectivityManager,
     requester::{ConnectivityEvent, ConnectivityRequester},
     selection::ConnectivitySelection,
+    ReputationEvent,
 };
 use crate::{
     connection_manager::{ConnectionManagerError, ConnectionManagerEvent},
@@ -310,6 +311,83 @@ async fn ban_peer() {
     assert!(conn.is_none());
 }
 
+#[tokio::test]
+async fn ban_peer_on_poor_reputation() {
+    let (mut connectivity, mut event_stream, node_identity, peer_manager, cm_mock_state, _shutdown) =
+        setup_connectivity_manager(Default::default());
+    let peer = add_test_peers(&peer_manager, 1).await.pop().unwrap();
+    let (conn, _, _, _) = create_peer_connection_mock_pair(node_identity.to_peer(), peer.clone()).await;
+
+    let mut events = collect_try_recv!(event_stream, take = 1, timeout = Duration::from_secs(10));
+    unpack_enum!(ConnectivityEvent::ConnectivityStateInitialized = events.remove(0));
+
+    cm_mock_state.publish_event(ConnectionManagerEvent::PeerConnected(conn.clone().into()));
+    let mut events = collect_try_recv!(event_stream, take = 2, timeout = Duration::from_secs(10));
+    unpack_enum!(ConnectivityEvent::PeerConnected(_conn) = events.remove(0));
+    unpack_enum!(ConnectivityEvent::ConnectivityStateOnline(_n) = events.remove(0));
+
+    // A single timeout does not result in a ban
+    connectivity
+        .report_peer(peer.node_id.clone(), ReputationEvent::Unresponsive)
+        .await
+        .unwrap();
+    let reputation = connectivity
+        .get_peer_reputation(peer.node_id.clone())
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(reputation.score() < 0.0);
+    assert_eq!(reputation.num_bans(), 0);
+    assert!(!peer_manager.is_peer_banned(&peer.node_id).await.unwrap());
+
+    for _ in 0..3 {
+        connectivity
+            .report_peer(peer.node_id.clone(), ReputationEvent::InvalidBlock)
+            .await
+            .unwrap();
+    }
+
+    let event = collect_try_recv!(event_stream, take = 1, timeout = Duration::from_secs(10))
+        .pop()
+        .unwrap();
+    unpack_enum!(ConnectivityEvent::PeerBanned(node_id) = event);
+    assert_eq!(node_id, peer.node_id);
+    assert!(peer_manager.is_peer_banned(&peer.node_id).await.unwrap());
+
+    let reputations = connectivity.get_all_peer_reputations().await.unwrap();
+    assert_eq!(reputations.len(), 1);
+    assert_eq!(reputations[0].num_bans(), 1);
+}
+
+#[tokio::test]
+async fn static_peers_are_dialed_and_not_banned() {
+    let (mut connectivity, _event_stream, _node_identity, peer_manager, cm_mock_state, _shutdown) =
+        setup_connectivity_manager(Default::default());
+    let peer = add_test_peers(&peer_manager, 1).await.pop().unwrap();
+
+    connectivity
+        .set_static_peer_group("fleet".to_string(), vec![peer.node_id.clone()])
+        .await
+        .unwrap();
+    let groups = connectivity.get_static_peer_groups().await.unwrap();
+    assert_eq!(groups.get("fleet").unwrap(), &vec![peer.node_id.clone()]);
+    async_assert_eventually!(cm_mock_state.call_count(), expect = 1);
+
+    connectivity
+        .ban_peer_until(peer.node_id.clone(), Duration::from_secs(60), "test".to_string())
+        .await
+        .unwrap();
+    // Ensure the ban request has been processed
+    connectivity.get_static_peer_groups().await.unwrap();
+    assert!(!peer_manager.is_peer_banned(&peer.node_id).await.unwrap());
+
+    connectivity
+        .set_static_peer_group("fleet".to_string(), vec![])
+        .await
+        .unwrap();
+    assert!(connectivity.get_static_peer_groups().await.unwrap().is_empty());
+}
+
 #[tokio::test]
 async fn peer_selection() {
     let config = ConnectivityConfig {
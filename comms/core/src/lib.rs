@@ -19,6 +19,8 @@ pub use connection_manager::{PeerConnection, PeerConnectionError};
 
 pub mod connectivity;
 
+pub mod bandwidth;
+
 pub mod peer_manager;
 pub use peer_manager::{NodeIdentity, OrNotFound, PeerManager};
 
@@ -37,6 +39,7 @@ pub mod memsocket;
 pub mod protocol;
 #[macro_use]
 pub mod message;
+pub mod nat;
 pub mod net_address;
 pub mod pipeline;
 pub mod socks;
@@ -20,10 +20,14 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-//! Stream multiplexers typically used to allow multiplexed substreams over an ordered reliable byte stream.
+//! Stream multiplexers typically used to allow multiplexed substreams over an ordered reliable byte stream. QUIC
+//! connections are natively multiplexed and are exposed through the same interface.
 
 #[cfg(feature = "metrics")]
 mod metrics;
 
+mod quic;
+pub use self::quic::QuicStream;
+
 mod yamux;
 pub use self::yamux::{ConnectionError, Control, IncomingSubstreams, Substream, Yamux};
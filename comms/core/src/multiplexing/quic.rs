@@ -0,0 +1,145 @@
+// Copyright 2024. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! QUIC natively multiplexes streams over a connection, so no yamux session is required. This module adapts a QUIC
+//! connection to the substream interface used by peer connections.
+
+use std::{
+    convert::TryFrom,
+    io,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use tokio::{
+    io::{AsyncRead, AsyncWrite, ReadBuf},
+    sync::mpsc,
+};
+use tracing::debug;
+use yamux::ConnectionError;
+
+use super::yamux::RawSubstream;
+use crate::stream_id;
+
+const LOG_TARGET: &str = "comms::multiplexing::quic";
+
+/// Application error code sent to the peer when closing a QUIC connection
+const CONNECTION_CLOSED_CODE: u32 = 0;
+
+/// A bidirectional QUIC stream
+#[derive(Debug)]
+pub struct QuicStream {
+    send: quinn::SendStream,
+    recv: quinn::RecvStream,
+}
+
+impl QuicStream {
+    pub fn new(send: quinn::SendStream, recv: quinn::RecvStream) -> Self {
+        Self { send, recv }
+    }
+
+    pub(crate) fn id(&self) -> stream_id::Id {
+        stream_id::Id::new(u32::try_from(self.send.id().index()).unwrap_or(u32::MAX))
+    }
+}
+
+impl AsyncRead for QuicStream {
+    fn poll_read(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.recv).poll_read(cx, buf)
+    }
+}
+
+impl AsyncWrite for QuicStream {
+    fn poll_write(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.send).poll_write(cx, buf)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.send).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.send).poll_shutdown(cx)
+    }
+}
+
+/// Opens a new bidirectional stream on the connection
+pub(super) async fn open_stream(connection: &quinn::Connection) -> Result<QuicStream, ConnectionError> {
+    let (send, recv) = connection.open_bi().await.map_err(to_connection_error)?;
+    Ok(QuicStream::new(send, recv))
+}
+
+/// Closes the connection. Returns `ConnectionError::Closed` if the connection was already closed.
+pub(super) fn close(connection: &quinn::Connection) -> Result<(), ConnectionError> {
+    if connection.close_reason().is_some() {
+        return Err(ConnectionError::Closed);
+    }
+    connection.close(CONNECTION_CLOSED_CODE.into(), b"closed");
+    Ok(())
+}
+
+/// Accepts streams opened by the remote and sends them on the given channel until the connection closes. The
+/// connection is closed once the receiver is dropped.
+pub(super) async fn run_incoming_worker(connection: quinn::Connection, sender: mpsc::Sender<RawSubstream>) {
+    loop {
+        tokio::select! {
+            _ = sender.closed() => {
+                let _result = close(&connection);
+                break;
+            },
+
+            result = connection.accept_bi() => {
+                match result {
+                    Ok((send, recv)) => {
+                        if sender.send(RawSubstream::Quic(QuicStream::new(send, recv))).await.is_err() {
+                            debug!(
+                                target: LOG_TARGET,
+                                "{} Incoming peer substream task is stopping because the internal stream sender channel \
+                                 was closed",
+                                connection.remote_address()
+                            );
+                            break;
+                        }
+                    },
+                    Err(err) => {
+                        debug!(
+                            target: LOG_TARGET,
+                            "{} Incoming peer substream ended because '{}'",
+                            connection.remote_address(),
+                            err
+                        );
+                        break;
+                    },
+                }
+            }
+        }
+    }
+}
+
+fn to_connection_error(err: quinn::ConnectionError) -> ConnectionError {
+    match err {
+        quinn::ConnectionError::LocallyClosed |
+        quinn::ConnectionError::ApplicationClosed(_) |
+        quinn::ConnectionError::ConnectionClosed(_) => ConnectionError::Closed,
+        err => ConnectionError::Io(err.into()),
+    }
+}
@@ -20,7 +20,7 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use std::{future::Future, io, pin::Pin, task::Poll};
+use std::{io, pin::Pin, task::Poll};
 
 use futures::{task::Context, Stream};
 use tokio::{
@@ -33,7 +33,9 @@ use tracing::{self, debug, error};
 pub use yamux::ConnectionError;
 use yamux::Mode;
 
+use super::quic::{self, QuicStream};
 use crate::{
+    bandwidth::SubstreamMeter,
     connection_manager::ConnectionDirection,
     stream_id,
     stream_id::StreamId,
@@ -95,6 +97,25 @@ impl Yamux {
         IncomingSubstreams::new(incoming_rx, counter)
     }
 
+    /// Wrap an authenticated QUIC connection. QUIC streams are natively multiplexed so substreams are opened directly
+    /// on the QUIC connection rather than over a yamux session.
+    pub fn upgrade_quic_connection(connection: quinn::Connection) -> Self {
+        let substream_counter = AtomicRefCounter::new();
+        let control = Control {
+            inner: ControlInner::Quic(connection.clone()),
+            substream_counter: substream_counter.clone(),
+        };
+        let (incoming_tx, incoming_rx) = mpsc::channel(10);
+        tokio::spawn(quic::run_incoming_worker(connection, incoming_tx));
+        let incoming = IncomingSubstreams::new(incoming_rx, substream_counter.clone());
+
+        Self {
+            control,
+            incoming,
+            substream_counter,
+        }
+    }
+
     /// Get the yamux control struct
     pub fn get_yamux_control(&self) -> Control {
         self.control.clone()
@@ -123,14 +144,20 @@ impl Yamux {
 
 #[derive(Clone)]
 pub struct Control {
-    inner: yamux::Control,
+    inner: ControlInner,
     substream_counter: AtomicRefCounter,
 }
 
+#[derive(Clone)]
+enum ControlInner {
+    Yamux(yamux::Control),
+    Quic(quinn::Connection),
+}
+
 impl Control {
     pub fn new(inner: yamux::Control, substream_counter: AtomicRefCounter) -> Self {
         Self {
-            inner,
+            inner: ControlInner::Yamux(inner),
             substream_counter,
         }
     }
@@ -139,16 +166,23 @@ impl Control {
     pub async fn open_stream(&mut self) -> Result<Substream, ConnectionError> {
         // Ensure that this counts as used while the substream is being opened
         let counter_guard = self.substream_counter.new_guard();
-        let stream = self.inner.open_stream().await?;
+        let stream = match &mut self.inner {
+            ControlInner::Yamux(control) => RawSubstream::Yamux(control.open_stream().await?.compat()),
+            ControlInner::Quic(connection) => RawSubstream::Quic(quic::open_stream(connection).await?),
+        };
         Ok(Substream {
-            stream: stream.compat(),
+            stream,
+            meter: None,
             _counter_guard: counter_guard,
         })
     }
 
     /// Close the connection.
-    pub fn close(&mut self) -> impl Future<Output = Result<(), ConnectionError>> + '_ {
-        self.inner.close()
+    pub async fn close(&mut self) -> Result<(), ConnectionError> {
+        match &mut self.inner {
+            ControlInner::Yamux(control) => control.close().await,
+            ControlInner::Quic(connection) => quic::close(connection),
+        }
     }
 
     pub fn substream_count(&self) -> usize {
@@ -161,12 +195,12 @@ impl Control {
 }
 
 pub struct IncomingSubstreams {
-    inner: mpsc::Receiver<yamux::Stream>,
+    inner: mpsc::Receiver<RawSubstream>,
     substream_counter: AtomicRefCounter,
 }
 
 impl IncomingSubstreams {
-    pub(self) fn new(inner: mpsc::Receiver<yamux::Stream>, substream_counter: AtomicRefCounter) -> Self {
+    pub(self) fn new(inner: mpsc::Receiver<RawSubstream>, substream_counter: AtomicRefCounter) -> Self {
         Self {
             inner,
             substream_counter,
@@ -184,7 +218,8 @@ impl Stream for IncomingSubstreams {
     fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
         match futures::ready!(Pin::new(&mut self.inner).poll_recv(cx)) {
             Some(stream) => Poll::Ready(Some(Substream {
-                stream: stream.compat(),
+                stream,
+                meter: None,
                 _counter_guard: self.substream_counter.new_guard(),
             })),
             None => Poll::Ready(None),
@@ -192,25 +227,82 @@ impl Stream for IncomingSubstreams {
     }
 }
 
-/// A yamux stream wrapper that can be read from and written to.
+/// A multiplexed stream wrapper that can be read from and written to.
 #[derive(Debug)]
 pub struct Substream {
-    stream: Compat<yamux::Stream>,
+    stream: RawSubstream,
+    meter: Option<SubstreamMeter>,
     _counter_guard: AtomicRefCounterGuard,
 }
 
+impl Substream {
+    /// Account all further traffic on this substream with the given meter and apply its rate limits
+    pub(crate) fn set_meter(&mut self, meter: SubstreamMeter) {
+        self.meter = Some(meter);
+    }
+}
+
 impl StreamId for Substream {
     fn stream_id(&self) -> stream_id::Id {
-        self.stream.get_ref().id().into()
+        match &self.stream {
+            RawSubstream::Yamux(stream) => stream.get_ref().id().into(),
+            RawSubstream::Quic(stream) => stream.id(),
+        }
+    }
+}
+
+/// The underlying stream of a [Substream]
+#[derive(Debug)]
+pub(super) enum RawSubstream {
+    Yamux(Compat<yamux::Stream>),
+    Quic(QuicStream),
+}
+
+impl AsyncRead for RawSubstream {
+    fn poll_read(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            RawSubstream::Yamux(stream) => Pin::new(stream).poll_read(cx, buf),
+            RawSubstream::Quic(stream) => Pin::new(stream).poll_read(cx, buf),
+        }
+    }
+}
+
+impl AsyncWrite for RawSubstream {
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        match self.get_mut() {
+            RawSubstream::Yamux(stream) => Pin::new(stream).poll_write(cx, buf),
+            RawSubstream::Quic(stream) => Pin::new(stream).poll_write(cx, buf),
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            RawSubstream::Yamux(stream) => Pin::new(stream).poll_flush(cx),
+            RawSubstream::Quic(stream) => Pin::new(stream).poll_flush(cx),
+        }
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            RawSubstream::Yamux(stream) => Pin::new(stream).poll_shutdown(cx),
+            RawSubstream::Quic(stream) => Pin::new(stream).poll_shutdown(cx),
+        }
     }
 }
 
 impl tokio::io::AsyncRead for Substream {
     fn poll_read(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<io::Result<()>> {
+        if let Some(meter) = self.meter.as_mut() {
+            futures::ready!(meter.poll_read_ready(cx));
+        }
+        let filled_before = buf.filled().len();
         match Pin::new(&mut self.stream).poll_read(cx, buf) {
             Poll::Ready(Ok(())) => {
                 #[cfg(feature = "metrics")]
                 super::metrics::TOTAL_BYTES_READ.inc_by(buf.filled().len() as u64);
+                if let Some(meter) = self.meter.as_ref() {
+                    meter.record_read(buf.filled().len() - filled_before);
+                }
                 Poll::Ready(Ok(()))
             },
             res => res,
@@ -220,9 +312,20 @@ impl tokio::io::AsyncRead for Substream {
 
 impl tokio::io::AsyncWrite for Substream {
     fn poll_write(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        if let Some(meter) = self.meter.as_mut() {
+            futures::ready!(meter.poll_write_ready(cx));
+        }
         #[cfg(feature = "metrics")]
         super::metrics::TOTAL_BYTES_WRITTEN.inc_by(buf.len() as u64);
-        Pin::new(&mut self.stream).poll_write(cx, buf)
+        match Pin::new(&mut self.stream).poll_write(cx, buf) {
+            Poll::Ready(Ok(num_bytes)) => {
+                if let Some(meter) = self.meter.as_ref() {
+                    meter.record_written(num_bytes);
+                }
+                Poll::Ready(Ok(num_bytes))
+            },
+            res => res,
+        }
     }
 
     fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
@@ -242,13 +345,13 @@ impl From<yamux::StreamId> for stream_id::Id {
 
 struct IncomingWorker<TSocket> {
     connection: yamux::Connection<TSocket>,
-    sender: mpsc::Sender<yamux::Stream>,
+    sender: mpsc::Sender<RawSubstream>,
 }
 
 impl<TSocket> IncomingWorker<TSocket>
 where TSocket: futures::AsyncRead + futures::AsyncWrite + Unpin + Send + 'static /*  */
 {
-    pub fn new(connection: yamux::Connection<TSocket>, sender: mpsc::Sender<yamux::Stream>) -> Self {
+    pub fn new(connection: yamux::Connection<TSocket>, sender: mpsc::Sender<RawSubstream>) -> Self {
         Self { connection, sender }
     }
 
@@ -263,7 +366,7 @@ where TSocket: futures::AsyncRead + futures::AsyncWrite + Unpin + Send + 'static
                 result = self.connection.next_stream() => {
                      match result {
                         Ok(Some(stream)) => {
-                            if self.sender.send(stream).await.is_err() {
+                            if self.sender.send(RawSubstream::Yamux(stream.compat())).await.is_err() {
                                 debug!(
                                     target: LOG_TARGET,
                                     "{} Incoming peer substream task is stopping because the internal stream sender channel \
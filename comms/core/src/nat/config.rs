@@ -0,0 +1,67 @@
+// Copyright 2024. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{net::Ipv4Addr, time::Duration};
+
+/// NAT traversal configuration
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NatConfig {
+    /// Attempt to forward the TCP listener port using UPnP IGD or NAT-PMP and advertise the mapped external address.
+    /// Default: false
+    pub enable_port_mapping: bool,
+    /// The gateway to which NAT-PMP requests are sent. If None, the default gateway is used if it can be determined.
+    /// Default: None
+    pub gateway_address: Option<Ipv4Addr>,
+    /// The requested lifetime of a port mapping. Mappings are renewed after half of their lifetime.
+    /// Default: 1 hour
+    pub port_mapping_lifetime: Duration,
+    /// The maximum time to wait for a response from the gateway.
+    /// Default: 5 seconds
+    pub gateway_timeout: Duration,
+    /// If no port mapping is active, advertise the external IP address observed by peers together with the listener
+    /// port. This is useful if the port has been forwarded manually but the external IP address is dynamic.
+    /// Default: false
+    pub advertise_observed_address: bool,
+    /// The number of distinct peers that must report the same external IP address before it is trusted.
+    /// Default: 3
+    pub min_observed_address_confirmations: usize,
+}
+
+impl NatConfig {
+    /// Returns true if any NAT traversal feature is enabled
+    pub fn is_enabled(&self) -> bool {
+        self.enable_port_mapping || self.advertise_observed_address
+    }
+}
+
+impl Default for NatConfig {
+    fn default() -> Self {
+        Self {
+            enable_port_mapping: false,
+            gateway_address: None,
+            port_mapping_lifetime: Duration::from_secs(60 * 60),
+            gateway_timeout: Duration::from_secs(5),
+            advertise_observed_address: false,
+            min_observed_address_confirmations: 3,
+        }
+    }
+}
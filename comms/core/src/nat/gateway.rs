@@ -0,0 +1,122 @@
+// Copyright 2024. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{
+    io,
+    net::{IpAddr, Ipv4Addr, SocketAddr},
+};
+
+use tokio::net::UdpSocket;
+
+/// Returns the IPv4 default gateway, if it can be determined on this platform
+pub(super) fn default_gateway() -> Option<Ipv4Addr> {
+    #[cfg(target_os = "linux")]
+    {
+        let routes = std::fs::read_to_string("/proc/net/route").ok()?;
+        parse_linux_default_gateway(&routes)
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        None
+    }
+}
+
+/// Parses the default gateway from the contents of `/proc/net/route`. Addresses are little-endian hex encoded.
+#[cfg_attr(not(any(test, target_os = "linux")), allow(dead_code))]
+fn parse_linux_default_gateway(routes: &str) -> Option<Ipv4Addr> {
+    routes.lines().skip(1).find_map(|line| {
+        let mut columns = line.split_whitespace();
+        let destination = columns.nth(1)?;
+        let gateway = columns.next()?;
+        if destination != "00000000" {
+            return None;
+        }
+        let gateway = u32::from_str_radix(gateway, 16).ok()?;
+        Some(Ipv4Addr::from(gateway.to_le_bytes()))
+    })
+}
+
+/// Returns the local IP address of the interface used to reach the given address. No packets are sent.
+pub(super) async fn local_ip_for(addr: SocketAddr) -> io::Result<IpAddr> {
+    let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0)).await?;
+    socket.connect(addr).await?;
+    Ok(socket.local_addr()?.ip())
+}
+
+/// Returns true if the address may be reachable from the public internet, i.e. it is not a private, shared (carrier
+/// grade NAT), loopback, link local, documentation or otherwise reserved address.
+pub(super) fn is_publicly_routable(ip: &IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let octets = ip.octets();
+            let is_shared = octets[0] == 100 && (octets[1] & 0b1100_0000) == 64;
+            let is_reserved = octets[0] >= 240;
+            !(ip.is_private() ||
+                ip.is_loopback() ||
+                ip.is_link_local() ||
+                ip.is_broadcast() ||
+                ip.is_documentation() ||
+                ip.is_unspecified() ||
+                ip.is_multicast() ||
+                is_shared ||
+                is_reserved)
+        },
+        IpAddr::V6(ip) => {
+            let segments = ip.segments();
+            let is_unique_local = (segments[0] & 0xfe00) == 0xfc00;
+            let is_unicast_link_local = (segments[0] & 0xffc0) == 0xfe80;
+            let is_documentation = segments[0] == 0x2001 && segments[1] == 0x0db8;
+            !(ip.is_loopback() ||
+                ip.is_unspecified() ||
+                ip.is_multicast() ||
+                is_unique_local ||
+                is_unicast_link_local ||
+                is_documentation)
+        },
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn it_parses_the_default_gateway() {
+        let routes = "Iface\tDestination\tGateway \
+                      \tFlags\tRefCnt\tUse\tMetric\tMask\t\tMTU\tWindow\tIRTT\neth0\t0001A8C0\t00000000\t0001\t0\t0\\
+                      t0\t00FFFFFF\t0\t0\t0\neth0\t00000000\t0101A8C0\t0003\t0\t0\t0\t00000000\t0\t0\t0\n";
+        assert_eq!(parse_linux_default_gateway(routes), Some(Ipv4Addr::new(192, 168, 1, 1)));
+        assert_eq!(parse_linux_default_gateway("Iface\tDestination\tGateway\n"), None);
+    }
+
+    #[test]
+    fn it_identifies_publicly_routable_addresses() {
+        assert!(is_publicly_routable(&"1.2.3.4".parse().unwrap()));
+        assert!(is_publicly_routable(&"2a00:1450::1".parse().unwrap()));
+        assert!(!is_publicly_routable(&"192.168.1.10".parse().unwrap()));
+        assert!(!is_publicly_routable(&"10.0.0.1".parse().unwrap()));
+        assert!(!is_publicly_routable(&"100.64.0.1".parse().unwrap()));
+        assert!(!is_publicly_routable(&"127.0.0.1".parse().unwrap()));
+        assert!(!is_publicly_routable(&"fd00::1".parse().unwrap()));
+        assert!(!is_publicly_routable(&"fe80::1".parse().unwrap()));
+    }
+}
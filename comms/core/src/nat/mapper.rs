@@ -0,0 +1,64 @@
+// Copyright 2024. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{fmt, net::Ipv4Addr, time::Duration};
+
+use async_trait::async_trait;
+
+use super::NatError;
+
+/// A TCP port forwarded by the gateway
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PortMapping {
+    /// The port on this host to which connections are forwarded
+    pub internal_port: u16,
+    /// The port on the gateway's external interface
+    pub external_port: u16,
+    /// The external IP address of the gateway
+    pub external_ip: Ipv4Addr,
+    /// The time the gateway will keep the mapping if it is not renewed
+    pub lifetime: Duration,
+}
+
+impl fmt::Display for PortMapping {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}:{} -> :{} (lifetime: {:.0?})",
+            self.external_ip, self.external_port, self.internal_port, self.lifetime
+        )
+    }
+}
+
+/// A method of asking a NAT gateway to forward a TCP port to this host
+#[async_trait]
+pub trait PortMapper: Send + Sync {
+    /// A short name for the mapping method, used for logging
+    fn name(&self) -> &'static str;
+
+    /// Maps (or renews the mapping of) the given internal TCP port. Gateways usually try to map the same external
+    /// port, but may choose a different one.
+    async fn map_port(&self, internal_port: u16, lifetime: Duration) -> Result<PortMapping, NatError>;
+
+    /// Removes a mapping previously returned by `map_port`
+    async fn unmap_port(&self, mapping: &PortMapping) -> Result<(), NatError>;
+}
@@ -0,0 +1,60 @@
+// Copyright 2024. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! # NAT traversal
+//!
+//! Nodes behind a NAT gateway are not reachable by other peers unless the listener port is forwarded to them. This
+//! module provides:
+//!
+//! - [PortMapper] implementations that ask the gateway to forward the TCP listener port using [UPnP
+//!   IGD](UpnpPortMapper) or [NAT-PMP](NatPmpPortMapper). Additional mappers can be added with
+//!   [CommsBuilder::add_port_mapper](crate::CommsBuilder::add_port_mapper).
+//! - Public address detection. During the identity exchange, each peer tells the other the address it observed for
+//!   them. Once enough distinct peers agree on the external IP address, it is trusted.
+//!
+//! The NAT service keeps the port mapping alive and updates the public addresses of the [NodeIdentity], which in turn
+//! updates the signed identity claim that is sent to peers.
+//!
+//! [NodeIdentity]: crate::peer_manager::NodeIdentity
+
+mod config;
+pub use config::NatConfig;
+
+mod error;
+pub use error::NatError;
+
+mod gateway;
+
+mod mapper;
+pub use mapper::{PortMapper, PortMapping};
+
+mod natpmp;
+pub use natpmp::NatPmpPortMapper;
+
+mod observed;
+pub use observed::ObservedAddresses;
+
+mod service;
+pub(crate) use service::NatService;
+
+mod upnp;
+pub use upnp::UpnpPortMapper;
@@ -0,0 +1,247 @@
+// Copyright 2024. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! NAT Port Mapping Protocol client ([RFC 6886](https://www.rfc-editor.org/rfc/rfc6886))
+
+use std::{
+    convert::TryFrom,
+    net::{Ipv4Addr, SocketAddr, SocketAddrV4},
+    time::Duration,
+};
+
+use async_trait::async_trait;
+use log::*;
+use tokio::{net::UdpSocket, time};
+
+use super::{gateway, NatError, PortMapper, PortMapping};
+
+const LOG_TARGET: &str = "comms::nat::natpmp";
+
+/// The port on which NAT-PMP gateways listen
+pub const NAT_PMP_PORT: u16 = 5351;
+
+const VERSION: u8 = 0;
+const OPCODE_EXTERNAL_ADDRESS: u8 = 0;
+const OPCODE_MAP_TCP: u8 = 2;
+const RESPONSE_OPCODE_OFFSET: u8 = 128;
+const RESULT_SUCCESS: u16 = 0;
+/// The initial retransmission interval, doubled after each attempt
+const INITIAL_RETRY_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Maps ports using NAT-PMP
+#[derive(Debug, Clone)]
+pub struct NatPmpPortMapper {
+    gateway: Option<SocketAddr>,
+    timeout: Duration,
+}
+
+impl NatPmpPortMapper {
+    /// Creates a NAT-PMP port mapper for the given gateway. If no gateway is given, the default gateway is used if it
+    /// can be determined.
+    pub fn new(gateway: Option<Ipv4Addr>, timeout: Duration) -> Self {
+        let gateway = gateway
+            .or_else(gateway::default_gateway)
+            .map(|ip| SocketAddr::V4(SocketAddrV4::new(ip, NAT_PMP_PORT)));
+        Self { gateway, timeout }
+    }
+
+    /// Creates a NAT-PMP port mapper that sends requests to the given socket address. This is useful for testing.
+    pub fn with_gateway_socket_address(gateway: SocketAddr, timeout: Duration) -> Self {
+        Self {
+            gateway: Some(gateway),
+            timeout,
+        }
+    }
+
+    fn gateway(&self) -> Result<SocketAddr, NatError> {
+        self.gateway.ok_or(NatError::GatewayNotFound)
+    }
+
+    /// Requests the external IP address of the gateway
+    pub async fn external_address(&self) -> Result<Ipv4Addr, NatError> {
+        let response = self
+            .send_request(&[VERSION, OPCODE_EXTERNAL_ADDRESS], OPCODE_EXTERNAL_ADDRESS, 12)
+            .await?;
+        Ok(Ipv4Addr::new(response[8], response[9], response[10], response[11]))
+    }
+
+    async fn send_mapping_request(
+        &self,
+        internal_port: u16,
+        external_port: u16,
+        lifetime: Duration,
+    ) -> Result<(u16, u16, Duration), NatError> {
+        let lifetime = u32::try_from(lifetime.as_secs()).unwrap_or(u32::MAX);
+        let mut request = [0u8; 12];
+        request[0] = VERSION;
+        request[1] = OPCODE_MAP_TCP;
+        request[4..6].copy_from_slice(&internal_port.to_be_bytes());
+        request[6..8].copy_from_slice(&external_port.to_be_bytes());
+        request[8..12].copy_from_slice(&lifetime.to_be_bytes());
+
+        let response = self.send_request(&request, OPCODE_MAP_TCP, 16).await?;
+        let mapped_internal_port = u16::from_be_bytes([response[8], response[9]]);
+        let mapped_external_port = u16::from_be_bytes([response[10], response[11]]);
+        let lifetime = u32::from_be_bytes([response[12], response[13], response[14], response[15]]);
+        Ok((
+            mapped_internal_port,
+            mapped_external_port,
+            Duration::from_secs(u64::from(lifetime)),
+        ))
+    }
+
+    /// Sends a request, retransmitting until a valid response is received or the timeout is reached
+    async fn send_request(&self, request: &[u8], opcode: u8, response_len: usize) -> Result<Vec<u8>, NatError> {
+        let gateway = self.gateway()?;
+        let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0)).await?;
+        socket.connect(gateway).await?;
+
+        let exchange = async {
+            let mut retry_interval = INITIAL_RETRY_INTERVAL;
+            let mut buf = [0u8; 16];
+            loop {
+                socket.send(request).await?;
+                match time::timeout(retry_interval, socket.recv(&mut buf)).await {
+                    Ok(Ok(len)) => {
+                        let response = &buf[..len];
+                        if len < 4 || response[0] != VERSION || response[1] != opcode + RESPONSE_OPCODE_OFFSET {
+                            debug!(target: LOG_TARGET, "Ignoring unexpected NAT-PMP response from {}", gateway);
+                            continue;
+                        }
+                        let result_code = u16::from_be_bytes([response[2], response[3]]);
+                        if result_code != RESULT_SUCCESS {
+                            return Err(NatError::Rejected(format!("NAT-PMP result code {}", result_code)));
+                        }
+                        if len < response_len {
+                            return Err(NatError::InvalidResponse(format!(
+                                "NAT-PMP response was {} bytes, expected {}",
+                                len, response_len
+                            )));
+                        }
+                        return Ok(response.to_vec());
+                    },
+                    Ok(Err(err)) => return Err(err.into()),
+                    Err(_) => {
+                        retry_interval *= 2;
+                    },
+                }
+            }
+        };
+
+        time::timeout(self.timeout, exchange).await?
+    }
+}
+
+#[async_trait]
+impl PortMapper for NatPmpPortMapper {
+    fn name(&self) -> &'static str {
+        "NAT-PMP"
+    }
+
+    async fn map_port(&self, internal_port: u16, lifetime: Duration) -> Result<PortMapping, NatError> {
+        let external_ip = self.external_address().await?;
+        let (mapped_internal_port, external_port, lifetime) = self
+            .send_mapping_request(internal_port, internal_port, lifetime)
+            .await?;
+        if mapped_internal_port != internal_port {
+            return Err(NatError::InvalidResponse(format!(
+                "Gateway mapped internal port {} but {} was requested",
+                mapped_internal_port, internal_port
+            )));
+        }
+        Ok(PortMapping {
+            internal_port,
+            external_port,
+            external_ip,
+            lifetime,
+        })
+    }
+
+    async fn unmap_port(&self, mapping: &PortMapping) -> Result<(), NatError> {
+        // A mapping is deleted by requesting a lifetime of zero
+        self.send_mapping_request(mapping.internal_port, 0, Duration::ZERO)
+            .await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// A minimal NAT-PMP gateway that maps every requested port to `external_port`
+    async fn spawn_fake_gateway(external_ip: Ipv4Addr, external_port: u16) -> SocketAddr {
+        let socket = UdpSocket::bind((Ipv4Addr::LOCALHOST, 0)).await.unwrap();
+        let addr = socket.local_addr().unwrap();
+        tokio::spawn(async move {
+            let mut buf = [0u8; 12];
+            loop {
+                let (len, peer) = socket.recv_from(&mut buf).await.unwrap();
+                let mut response = vec![VERSION, buf[1] + RESPONSE_OPCODE_OFFSET, 0, 0, 0, 0, 0, 1];
+                match buf[1] {
+                    OPCODE_EXTERNAL_ADDRESS if len == 2 => {
+                        response.extend_from_slice(&external_ip.octets());
+                    },
+                    OPCODE_MAP_TCP if len == 12 => {
+                        // Internal port, mapped external port and the requested lifetime
+                        response.extend_from_slice(&buf[4..6]);
+                        response.extend_from_slice(&external_port.to_be_bytes());
+                        response.extend_from_slice(&buf[8..12]);
+                    },
+                    _ => {
+                        // Unsupported opcode
+                        response[3] = 5;
+                    },
+                }
+                socket.send_to(&response, peer).await.unwrap();
+            }
+        });
+        addr
+    }
+
+    #[tokio::test]
+    async fn it_maps_a_port() {
+        let external_ip = Ipv4Addr::new(1, 2, 3, 4);
+        let gateway = spawn_fake_gateway(external_ip, 12345).await;
+        let mapper = NatPmpPortMapper::with_gateway_socket_address(gateway, Duration::from_secs(5));
+
+        assert_eq!(mapper.external_address().await.unwrap(), external_ip);
+        let mapping = mapper.map_port(18189, Duration::from_secs(3600)).await.unwrap();
+        assert_eq!(mapping, PortMapping {
+            internal_port: 18189,
+            external_port: 12345,
+            external_ip,
+            lifetime: Duration::from_secs(3600),
+        });
+        mapper.unmap_port(&mapping).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn it_times_out_if_the_gateway_does_not_respond() {
+        // Bind a socket that never responds
+        let socket = UdpSocket::bind((Ipv4Addr::LOCALHOST, 0)).await.unwrap();
+        let mapper =
+            NatPmpPortMapper::with_gateway_socket_address(socket.local_addr().unwrap(), Duration::from_millis(100));
+        let err = mapper.external_address().await.unwrap_err();
+        assert!(matches!(err, NatError::Timeout));
+    }
+}
@@ -0,0 +1,154 @@
+// Copyright 2024. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{collections::VecDeque, net::IpAddr};
+
+use super::gateway;
+use crate::{
+    multiaddr::{Multiaddr, Protocol},
+    peer_manager::NodeId,
+};
+
+/// The maximum number of peer observations that are kept. The oldest observation is evicted when this is exceeded.
+const MAX_OBSERVATIONS: usize = 100;
+
+/// Tracks the external IP addresses that peers have observed for this node. An address is only confirmed once a
+/// minimum number of distinct peers report it, so that a single peer cannot cause the node to advertise a bogus
+/// address. Only publicly routable addresses are considered.
+#[derive(Debug, Clone)]
+pub struct ObservedAddresses {
+    min_confirmations: usize,
+    observations: VecDeque<(NodeId, IpAddr)>,
+    confirmed_ip: Option<IpAddr>,
+}
+
+impl ObservedAddresses {
+    pub fn new(min_confirmations: usize) -> Self {
+        Self {
+            min_confirmations: min_confirmations.max(1),
+            observations: VecDeque::with_capacity(MAX_OBSERVATIONS),
+            confirmed_ip: None,
+        }
+    }
+
+    /// Records the address that a peer observed for this node. Returns true if the confirmed IP address changed as a
+    /// result.
+    pub fn add(&mut self, node_id: NodeId, address: &Multiaddr) -> bool {
+        let ip = match extract_ip(address) {
+            Some(ip) if gateway::is_publicly_routable(&ip) => ip,
+            _ => return false,
+        };
+
+        // Only the most recent observation from each peer counts
+        self.observations.retain(|(n, _)| *n != node_id);
+        if self.observations.len() >= MAX_OBSERVATIONS {
+            self.observations.pop_front();
+        }
+        self.observations.push_back((node_id, ip));
+
+        let confirmed_ip = self.most_observed_ip();
+        if confirmed_ip == self.confirmed_ip {
+            return false;
+        }
+        self.confirmed_ip = confirmed_ip;
+        true
+    }
+
+    /// The external IP address that has been confirmed by enough peers, if any
+    pub fn confirmed_ip(&self) -> Option<IpAddr> {
+        self.confirmed_ip
+    }
+
+    /// The number of peers that have reported an observed address
+    pub fn num_observations(&self) -> usize {
+        self.observations.len()
+    }
+
+    fn most_observed_ip(&self) -> Option<IpAddr> {
+        let mut counts = Vec::<(IpAddr, usize)>::new();
+        for (_, ip) in &self.observations {
+            match counts.iter_mut().find(|(addr, _)| addr == ip) {
+                Some((_, count)) => *count += 1,
+                None => counts.push((*ip, 1)),
+            }
+        }
+        let (ip, count) = counts.into_iter().max_by_key(|(_, count)| *count)?;
+        if count < self.min_confirmations {
+            return None;
+        }
+        Some(ip)
+    }
+}
+
+fn extract_ip(address: &Multiaddr) -> Option<IpAddr> {
+    address.iter().find_map(|p| match p {
+        Protocol::Ip4(ip) => Some(IpAddr::V4(ip)),
+        Protocol::Ip6(ip) => Some(IpAddr::V6(ip)),
+        _ => None,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{peer_manager::PeerFeatures, test_utils::node_identity::build_node_identity};
+
+    fn random_node_id() -> NodeId {
+        build_node_identity(PeerFeatures::COMMUNICATION_NODE).node_id().clone()
+    }
+
+    #[test]
+    fn it_confirms_an_address_reported_by_enough_peers() {
+        let mut observed = ObservedAddresses::new(2);
+        let address = "/ip4/1.2.3.4/tcp/1234".parse::<Multiaddr>().unwrap();
+        let node_id = random_node_id();
+        assert!(!observed.add(node_id.clone(), &address));
+        // The same peer reporting again does not count twice
+        assert!(!observed.add(node_id, &address));
+        assert!(observed.confirmed_ip().is_none());
+        assert!(observed.add(random_node_id(), &address));
+        assert_eq!(observed.confirmed_ip().unwrap().to_string(), "1.2.3.4");
+    }
+
+    #[test]
+    fn it_ignores_private_addresses() {
+        let mut observed = ObservedAddresses::new(1);
+        assert!(!observed.add(random_node_id(), &"/ip4/192.168.1.2/tcp/1234".parse().unwrap()));
+        assert!(!observed.add(random_node_id(), &"/ip4/127.0.0.1/tcp/1234".parse().unwrap()));
+        assert!(!observed.add(random_node_id(), &"/memory/1234".parse().unwrap()));
+        assert_eq!(observed.num_observations(), 0);
+    }
+
+    #[test]
+    fn it_follows_the_majority() {
+        let mut observed = ObservedAddresses::new(2);
+        let peers = (0..3).map(|_| random_node_id()).collect::<Vec<_>>();
+        let old = "/ip4/1.2.3.4/tcp/1234".parse::<Multiaddr>().unwrap();
+        let new = "/ip4/5.6.7.8/tcp/1234".parse::<Multiaddr>().unwrap();
+        observed.add(peers[0].clone(), &old);
+        assert!(observed.add(peers[1].clone(), &old));
+        observed.add(peers[0].clone(), &new);
+        assert_eq!(observed.confirmed_ip().unwrap().to_string(), "1.2.3.4");
+        assert!(observed.add(peers[2].clone(), &new));
+        assert_eq!(observed.confirmed_ip().unwrap().to_string(), "5.6.7.8");
+    }
+}
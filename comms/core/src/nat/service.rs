@@ -0,0 +1,259 @@
+// Copyright 2024. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{
+    net::IpAddr,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use log::*;
+use tari_shutdown::ShutdownSignal;
+use tokio::{task::JoinHandle, time};
+
+use super::{
+    gateway,
+    NatConfig,
+    NatError,
+    NatPmpPortMapper,
+    ObservedAddresses,
+    PortMapper,
+    PortMapping,
+    UpnpPortMapper,
+};
+use crate::{
+    connection_manager::{ConnectionManagerEvent, ConnectionManagerRequester},
+    multiaddr::{Multiaddr, Protocol},
+    peer_manager::NodeIdentity,
+};
+
+const LOG_TARGET: &str = "comms::nat::service";
+
+/// The time to wait before retrying if no port mapper was able to map the listener port
+const PORT_MAPPING_RETRY_INTERVAL: Duration = Duration::from_secs(10 * 60);
+/// Mappings are never renewed more often than this, regardless of the lifetime granted by the gateway
+const MIN_PORT_MAPPING_RENEWAL_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Maintains a port mapping for the TCP listener and advertises the node's external address
+pub(crate) struct NatService {
+    config: NatConfig,
+    port_mappers: Vec<Arc<dyn PortMapper>>,
+    node_identity: Arc<NodeIdentity>,
+    connection_manager: ConnectionManagerRequester,
+    observed_addresses: ObservedAddresses,
+    active_mapping: Option<(Arc<dyn PortMapper>, PortMapping)>,
+    /// The external address that was added to the node identity by this service
+    advertised_address: Option<Multiaddr>,
+    shutdown_signal: ShutdownSignal,
+}
+
+impl NatService {
+    /// Creates a new NAT service. If no port mappers are given, UPnP and NAT-PMP are used (in that order).
+    pub fn new(
+        config: NatConfig,
+        mut port_mappers: Vec<Arc<dyn PortMapper>>,
+        node_identity: Arc<NodeIdentity>,
+        connection_manager: ConnectionManagerRequester,
+        shutdown_signal: ShutdownSignal,
+    ) -> Self {
+        if port_mappers.is_empty() {
+            port_mappers.push(Arc::new(UpnpPortMapper::new(config.gateway_timeout)));
+            port_mappers.push(Arc::new(NatPmpPortMapper::new(
+                config.gateway_address,
+                config.gateway_timeout,
+            )));
+        }
+        Self {
+            observed_addresses: ObservedAddresses::new(config.min_observed_address_confirmations),
+            config,
+            port_mappers,
+            node_identity,
+            connection_manager,
+            active_mapping: None,
+            advertised_address: None,
+            shutdown_signal,
+        }
+    }
+
+    pub fn spawn(self) -> JoinHandle<()> {
+        tokio::spawn(self.run())
+    }
+
+    async fn run(mut self) {
+        let mut connection_manager_events = self.connection_manager.get_event_subscription();
+        let listener_info = tokio::select! {
+            result = self.connection_manager.wait_until_listening() => match result {
+                Ok(info) => info,
+                Err(err) => {
+                    warn!(target: LOG_TARGET, "NAT service exiting because the listener failed to start: {}", err);
+                    return;
+                },
+            },
+            _ = self.shutdown_signal.wait() => return,
+        };
+
+        let listener_port = match tcp_listener_port(listener_info.bind_address()) {
+            Ok(port) => port,
+            Err(err) => {
+                warn!(target: LOG_TARGET, "NAT traversal is not supported for this node: {}", err);
+                return;
+            },
+        };
+        debug!(target: LOG_TARGET, "NAT service started for listener port {}", listener_port);
+
+        let mut next_mapping_attempt = Some(Instant::now()).filter(|_| self.config.enable_port_mapping);
+        loop {
+            let mapping_deadline = next_mapping_attempt.unwrap_or_else(Instant::now);
+            tokio::select! {
+                _ = time::sleep_until(mapping_deadline.into()), if next_mapping_attempt.is_some() => {
+                    next_mapping_attempt = Some(self.refresh_port_mapping(listener_port).await);
+                    self.update_advertised_address(listener_port);
+                },
+
+                event = connection_manager_events.recv() => {
+                    if let Ok(event) = event {
+                        if let ConnectionManagerEvent::ObservedAddress { peer_node_id, address } = &*event {
+                            if self.observed_addresses.add(peer_node_id.clone(), address) {
+                                info!(
+                                    target: LOG_TARGET,
+                                    "Peers agree that this node's external IP address is {:?}",
+                                    self.observed_addresses.confirmed_ip()
+                                );
+                                self.update_advertised_address(listener_port);
+                            }
+                        }
+                    }
+                },
+
+                _ = self.shutdown_signal.wait() => {
+                    debug!(target: LOG_TARGET, "NAT service is shutting down because it received the shutdown signal");
+                    self.remove_port_mapping().await;
+                    break;
+                }
+            }
+        }
+    }
+
+    /// Requests or renews the port mapping, returning the time at which it should be renewed
+    async fn refresh_port_mapping(&mut self, listener_port: u16) -> Instant {
+        for mapper in self.port_mappers.clone() {
+            match mapper.map_port(listener_port, self.config.port_mapping_lifetime).await {
+                Ok(mapping) => {
+                    if self.active_mapping.as_ref().map(|(_, m)| m) != Some(&mapping) {
+                        info!(target: LOG_TARGET, "{} port mapping created: {}", mapper.name(), mapping);
+                    }
+                    let renewal_interval = (mapping.lifetime / 2).max(MIN_PORT_MAPPING_RENEWAL_INTERVAL);
+                    self.active_mapping = Some((mapper, mapping));
+                    return Instant::now() + renewal_interval;
+                },
+                Err(err) => {
+                    debug!(target: LOG_TARGET, "{} port mapping failed: {}", mapper.name(), err);
+                },
+            }
+        }
+
+        warn!(
+            target: LOG_TARGET,
+            "Unable to map listener port {} on the gateway. Retrying in {:.0?}",
+            listener_port,
+            PORT_MAPPING_RETRY_INTERVAL
+        );
+        self.active_mapping = None;
+        Instant::now() + PORT_MAPPING_RETRY_INTERVAL
+    }
+
+    async fn remove_port_mapping(&mut self) {
+        if let Some((mapper, mapping)) = self.active_mapping.take() {
+            match mapper.unmap_port(&mapping).await {
+                Ok(_) => debug!(target: LOG_TARGET, "{} port mapping removed: {}", mapper.name(), mapping),
+                Err(err) => debug!(target: LOG_TARGET, "Failed to remove {} port mapping: {}", mapper.name(), err),
+            }
+        }
+    }
+
+    fn external_address(&self, listener_port: u16) -> Option<Multiaddr> {
+        match self.active_mapping {
+            Some((_, ref mapping)) => {
+                // If the gateway is itself behind a NAT, its external IP is not the address that peers observe
+                let ip = Some(IpAddr::V4(mapping.external_ip))
+                    .filter(gateway::is_publicly_routable)
+                    .or_else(|| self.observed_addresses.confirmed_ip())?;
+                Some(to_tcp_multiaddr(ip, mapping.external_port))
+            },
+            None if self.config.advertise_observed_address => self
+                .observed_addresses
+                .confirmed_ip()
+                .map(|ip| to_tcp_multiaddr(ip, listener_port)),
+            None => None,
+        }
+    }
+
+    fn update_advertised_address(&mut self, listener_port: u16) {
+        let address = self.external_address(listener_port);
+        if address == self.advertised_address {
+            return;
+        }
+
+        if let Some(old_address) = self.advertised_address.take() {
+            info!(target: LOG_TARGET, "No longer advertising external address {}", old_address);
+            self.node_identity.remove_public_address(&old_address);
+        }
+        // Addresses that were configured explicitly are left alone
+        if let Some(address) = address.filter(|a| !self.node_identity.public_addresses().contains(a)) {
+            info!(target: LOG_TARGET, "Advertising external address {}", address);
+            self.node_identity.add_public_address(address.clone());
+            self.advertised_address = Some(address);
+        }
+    }
+}
+
+fn tcp_listener_port(address: &Multiaddr) -> Result<u16, NatError> {
+    let mut iter = address.iter();
+    match (iter.next(), iter.next()) {
+        (Some(Protocol::Ip4(ip)), Some(Protocol::Tcp(port))) if !ip.is_loopback() => Ok(port),
+        _ => Err(NatError::UnsupportedListenerAddress(address.to_string())),
+    }
+}
+
+fn to_tcp_multiaddr(ip: IpAddr, port: u16) -> Multiaddr {
+    let ip = match ip {
+        IpAddr::V4(ip) => Protocol::Ip4(ip),
+        IpAddr::V6(ip) => Protocol::Ip6(ip),
+    };
+    Multiaddr::empty().with(ip).with(Protocol::Tcp(port))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn it_only_supports_non_loopback_tcp_listeners() {
+        assert_eq!(
+            tcp_listener_port(&"/ip4/0.0.0.0/tcp/18189".parse().unwrap()).unwrap(),
+            18189
+        );
+        tcp_listener_port(&"/ip4/127.0.0.1/tcp/18189".parse().unwrap()).unwrap_err();
+        tcp_listener_port(&"/memory/1234".parse().unwrap()).unwrap_err();
+        tcp_listener_port(&"/ip6/::/tcp/18189".parse().unwrap()).unwrap_err();
+    }
+}
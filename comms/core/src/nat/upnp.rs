@@ -0,0 +1,508 @@
+// Copyright 2024. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! UPnP Internet Gateway Device client
+//!
+//! Gateways are discovered using SSDP. The gateway's device description is fetched to find the control URL of its
+//! WAN connection service, and port mappings are requested using SOAP. Only the small subset of HTTP and XML that is
+//! needed to talk to typical home routers is implemented.
+
+use std::{
+    net::{IpAddr, Ipv4Addr, SocketAddr, SocketAddrV4},
+    time::Duration,
+};
+
+use async_trait::async_trait;
+use log::*;
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{lookup_host, TcpStream, UdpSocket},
+    time,
+};
+
+use super::{gateway, NatError, PortMapper, PortMapping};
+
+const LOG_TARGET: &str = "comms::nat::upnp";
+
+const SSDP_MULTICAST_ADDRESS: SocketAddrV4 = SocketAddrV4::new(Ipv4Addr::new(239, 255, 255, 250), 1900);
+const SSDP_SEARCH_TARGET: &str = "urn:schemas-upnp-org:device:InternetGatewayDevice:1";
+/// WAN connection services that support port mapping, in order of preference
+const WAN_CONNECTION_SERVICE_TYPES: &[&str] = &[
+    "urn:schemas-upnp-org:service:WANIPConnection:2",
+    "urn:schemas-upnp-org:service:WANIPConnection:1",
+    "urn:schemas-upnp-org:service:WANPPPConnection:1",
+];
+const PORT_MAPPING_DESCRIPTION: &str = "Tari comms";
+/// UPnP error code returned by gateways that only support mappings without a lease duration
+const ONLY_PERMANENT_LEASES_SUPPORTED: &str = "725";
+const MAX_HTTP_RESPONSE_SIZE: u64 = 64 * 1024;
+
+/// Maps ports using UPnP IGD
+#[derive(Debug, Clone)]
+pub struct UpnpPortMapper {
+    description_url: Option<String>,
+    timeout: Duration,
+}
+
+impl UpnpPortMapper {
+    /// Creates a UPnP port mapper that discovers the gateway using SSDP
+    pub fn new(timeout: Duration) -> Self {
+        Self {
+            description_url: None,
+            timeout,
+        }
+    }
+
+    /// Creates a UPnP port mapper that uses the gateway with the given device description URL, skipping discovery
+    pub fn with_description_url<T: Into<String>>(description_url: T, timeout: Duration) -> Self {
+        Self {
+            description_url: Some(description_url.into()),
+            timeout,
+        }
+    }
+
+    async fn find_gateway(&self) -> Result<Gateway, NatError> {
+        let location = match self.description_url {
+            Some(ref url) => url.clone(),
+            None => time::timeout(self.timeout, discover_gateway_location()).await??,
+        };
+        let location = HttpUrl::parse(&location)?;
+        let (status, description) = time::timeout(self.timeout, http_request(&location, "GET", &[], "")).await??;
+        if status != 200 {
+            return Err(NatError::InvalidResponse(format!(
+                "Gateway returned HTTP status {} for the device description",
+                status
+            )));
+        }
+
+        let (service_type, control_url) = WAN_CONNECTION_SERVICE_TYPES
+            .iter()
+            .find_map(|service_type| {
+                find_control_url(&description, service_type).map(|control_url| (*service_type, control_url))
+            })
+            .ok_or_else(|| {
+                NatError::InvalidResponse("Gateway does not provide a WAN connection service".to_string())
+            })?;
+        let control_url = location.join(control_url)?;
+        let local_ip = gateway::local_ip_for(control_url.resolve().await?).await?;
+
+        Ok(Gateway {
+            control_url,
+            service_type,
+            local_ip,
+        })
+    }
+
+    async fn soap_request(&self, gateway: &Gateway, action: &str, args: &[(&str, String)]) -> Result<String, NatError> {
+        let args = args
+            .iter()
+            .map(|(name, value)| format!("<{name}>{value}</{name}>", name = name, value = value))
+            .collect::<String>();
+        let body = format!(
+            "<?xml version=\"1.0\"?>\r\n<s:Envelope xmlns:s=\"http://schemas.xmlsoap.org/soap/envelope/\" \
+             s:encodingStyle=\"http://schemas.xmlsoap.org/soap/encoding/\"><s:Body><u:{action} \
+             xmlns:u=\"{service_type}\">{args}</u:{action}></s:Body></s:Envelope>\r\n",
+            action = action,
+            service_type = gateway.service_type,
+            args = args
+        );
+        let soap_action = format!("\"{}#{}\"", gateway.service_type, action);
+        let headers = [
+            ("Content-Type", "text/xml; charset=\"utf-8\""),
+            ("SOAPAction", soap_action.as_str()),
+        ];
+
+        let (status, response) = time::timeout(
+            self.timeout,
+            http_request(&gateway.control_url, "POST", &headers, &body),
+        )
+        .await??;
+        if status == 200 {
+            return Ok(response);
+        }
+        let error_code = xml_tag_value(&response, "errorCode").unwrap_or_default();
+        let error_description = xml_tag_value(&response, "errorDescription").unwrap_or_default();
+        Err(NatError::Rejected(format!(
+            "{} failed with HTTP status {}, UPnP error {} {}",
+            action, status, error_code, error_description
+        )))
+    }
+
+    async fn add_port_mapping(&self, gateway: &Gateway, internal_port: u16, lease: Duration) -> Result<(), NatError> {
+        self.soap_request(gateway, "AddPortMapping", &[
+            ("NewRemoteHost", String::new()),
+            ("NewExternalPort", internal_port.to_string()),
+            ("NewProtocol", "TCP".to_string()),
+            ("NewInternalPort", internal_port.to_string()),
+            ("NewInternalClient", gateway.local_ip.to_string()),
+            ("NewEnabled", "1".to_string()),
+            ("NewPortMappingDescription", PORT_MAPPING_DESCRIPTION.to_string()),
+            ("NewLeaseDuration", lease.as_secs().to_string()),
+        ])
+        .await?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl PortMapper for UpnpPortMapper {
+    fn name(&self) -> &'static str {
+        "UPnP"
+    }
+
+    async fn map_port(&self, internal_port: u16, lifetime: Duration) -> Result<PortMapping, NatError> {
+        let gateway = self.find_gateway().await?;
+        debug!(
+            target: LOG_TARGET,
+            "Using UPnP gateway at {} ({})", gateway.control_url, gateway.service_type
+        );
+
+        let response = self.soap_request(&gateway, "GetExternalIPAddress", &[]).await?;
+        let external_ip = xml_tag_value(&response, "NewExternalIPAddress")
+            .and_then(|ip| ip.trim().parse::<Ipv4Addr>().ok())
+            .ok_or_else(|| NatError::InvalidResponse("Gateway did not return a valid external IP".to_string()))?;
+
+        match self.add_port_mapping(&gateway, internal_port, lifetime).await {
+            Ok(_) => {},
+            Err(NatError::Rejected(err)) if err.contains(ONLY_PERMANENT_LEASES_SUPPORTED) => {
+                debug!(
+                    target: LOG_TARGET,
+                    "Gateway only supports permanent leases, requesting a mapping without a lease duration"
+                );
+                self.add_port_mapping(&gateway, internal_port, Duration::ZERO).await?;
+            },
+            Err(err) => return Err(err),
+        }
+
+        Ok(PortMapping {
+            internal_port,
+            external_port: internal_port,
+            external_ip,
+            lifetime,
+        })
+    }
+
+    async fn unmap_port(&self, mapping: &PortMapping) -> Result<(), NatError> {
+        let gateway = self.find_gateway().await?;
+        self.soap_request(&gateway, "DeletePortMapping", &[
+            ("NewRemoteHost", String::new()),
+            ("NewExternalPort", mapping.external_port.to_string()),
+            ("NewProtocol", "TCP".to_string()),
+        ])
+        .await?;
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+struct Gateway {
+    control_url: HttpUrl,
+    service_type: &'static str,
+    local_ip: IpAddr,
+}
+
+/// Sends an SSDP search and returns the device description URL of the first gateway that responds
+async fn discover_gateway_location() -> Result<String, NatError> {
+    let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0)).await?;
+    let request = format!(
+        "M-SEARCH * HTTP/1.1\r\nHOST: {}\r\nST: {}\r\nMAN: \"ssdp:discover\"\r\nMX: 2\r\n\r\n",
+        SSDP_MULTICAST_ADDRESS, SSDP_SEARCH_TARGET
+    );
+    socket.send_to(request.as_bytes(), SSDP_MULTICAST_ADDRESS).await?;
+
+    let mut buf = [0u8; 2048];
+    loop {
+        let (len, addr) = socket.recv_from(&mut buf).await?;
+        let response = String::from_utf8_lossy(&buf[..len]);
+        match header_value(&response, "location") {
+            Some(location) => {
+                debug!(target: LOG_TARGET, "UPnP gateway at {} responded with location {}", addr, location);
+                return Ok(location.to_string());
+            },
+            None => {
+                trace!(target: LOG_TARGET, "Ignoring SSDP response without a location from {}", addr);
+            },
+        }
+    }
+}
+
+/// Returns the value of the given header (case-insensitive) from an HTTP message
+fn header_value<'a>(message: &'a str, name: &str) -> Option<&'a str> {
+    message
+        .lines()
+        .take_while(|line| !line.is_empty())
+        .filter_map(|line| line.split_once(':'))
+        .find(|(header, _)| header.trim().eq_ignore_ascii_case(name))
+        .map(|(_, value)| value.trim())
+}
+
+/// Returns the text content of the first element with the given name. Namespace prefixes are ignored.
+fn xml_tag_value<'a>(xml: &'a str, tag: &str) -> Option<&'a str> {
+    let open_tag = format!("{}>", tag);
+    let mut search_from = 0;
+    loop {
+        let pos = search_from + xml[search_from..].find(&open_tag)?;
+        search_from = pos + open_tag.len();
+        let tag_start = xml[..pos].rfind('<')?;
+        let prefix = &xml[tag_start + 1..pos];
+        // Skip closing tags and tags that merely end with the given name
+        if prefix.starts_with('/') || !(prefix.is_empty() || prefix.ends_with(':')) {
+            continue;
+        }
+        let content = &xml[search_from..];
+        let end = content.find("</")?;
+        return Some(&content[..end]);
+    }
+}
+
+/// Returns the control URL of the given service type from a device description
+fn find_control_url<'a>(description: &'a str, service_type: &str) -> Option<&'a str> {
+    let service_start = description.find(&format!("<serviceType>{}</serviceType>", service_type))?;
+    let service = &description[service_start..];
+    let service = &service[..service.find("</service>").unwrap_or(service.len())];
+    xml_tag_value(service, "controlURL").map(str::trim)
+}
+
+/// A parsed `http://` URL
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct HttpUrl {
+    host: String,
+    port: u16,
+    path: String,
+}
+
+impl HttpUrl {
+    fn parse(url: &str) -> Result<Self, NatError> {
+        let rest = url
+            .strip_prefix("http://")
+            .ok_or_else(|| NatError::InvalidResponse(format!("Unsupported URL '{}'", url)))?;
+        let (authority, path) = match rest.find('/') {
+            Some(pos) => (&rest[..pos], &rest[pos..]),
+            None => (rest, "/"),
+        };
+        let (host, port) = match authority.rsplit_once(':') {
+            Some((host, port)) => {
+                let port = port
+                    .parse()
+                    .map_err(|_| NatError::InvalidResponse(format!("Invalid port in URL '{}'", url)))?;
+                (host, port)
+            },
+            None => (authority, 80),
+        };
+        if host.is_empty() {
+            return Err(NatError::InvalidResponse(format!("Missing host in URL '{}'", url)));
+        }
+        Ok(Self {
+            host: host.to_string(),
+            port,
+            path: path.to_string(),
+        })
+    }
+
+    /// Resolves a URL that is either absolute or relative to this URL's host
+    fn join(&self, url: &str) -> Result<Self, NatError> {
+        if url.starts_with("http://") {
+            return Self::parse(url);
+        }
+        let path = if url.starts_with('/') {
+            url.to_string()
+        } else {
+            format!("/{}", url)
+        };
+        Ok(Self {
+            host: self.host.clone(),
+            port: self.port,
+            path,
+        })
+    }
+
+    async fn resolve(&self) -> Result<SocketAddr, NatError> {
+        lookup_host((self.host.as_str(), self.port))
+            .await?
+            .next()
+            .ok_or_else(|| NatError::InvalidResponse(format!("Unable to resolve host '{}'", self.host)))
+    }
+}
+
+impl std::fmt::Display for HttpUrl {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "http://{}:{}{}", self.host, self.port, self.path)
+    }
+}
+
+/// Sends an HTTP/1.0 request and returns the status code and body of the response. HTTP/1.0 is used so that the
+/// response is never chunked and the body ends when the connection is closed.
+async fn http_request(
+    url: &HttpUrl,
+    method: &str,
+    headers: &[(&str, &str)],
+    body: &str,
+) -> Result<(u16, String), NatError> {
+    let mut stream = TcpStream::connect(url.resolve().await?).await?;
+    let mut request = format!(
+        "{} {} HTTP/1.0\r\nHost: {}:{}\r\nConnection: close\r\nContent-Length: {}\r\n",
+        method,
+        url.path,
+        url.host,
+        url.port,
+        body.len()
+    );
+    for (name, value) in headers {
+        request.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    request.push_str("\r\n");
+    request.push_str(body);
+    stream.write_all(request.as_bytes()).await?;
+
+    let mut response = Vec::new();
+    stream.take(MAX_HTTP_RESPONSE_SIZE).read_to_end(&mut response).await?;
+    let response = String::from_utf8_lossy(&response);
+    let (head, body) = response
+        .split_once("\r\n\r\n")
+        .ok_or_else(|| NatError::InvalidResponse("Malformed HTTP response".to_string()))?;
+    let status = head
+        .split_whitespace()
+        .nth(1)
+        .and_then(|status| status.parse::<u16>().ok())
+        .ok_or_else(|| NatError::InvalidResponse("Malformed HTTP status line".to_string()))?;
+    Ok((status, body.to_string()))
+}
+
+#[cfg(test)]
+mod test {
+    use tokio::net::TcpListener;
+
+    use super::*;
+
+    const DESCRIPTION: &str = "<?xml version=\"1.0\"?><root><device><serviceList><service><serviceType>urn:\
+                               schemas-upnp-org:service:Layer3Forwarding:1</serviceType><controlURL>/ctl/L3F</\
+                               controlURL></service><service><serviceType>urn:schemas-upnp-org:service:\
+                               WANIPConnection:1</serviceType><controlURL>/ctl/IPConn</controlURL></service></\
+                               serviceList></device></root>";
+
+    async fn read_request(stream: &mut TcpStream) -> String {
+        let mut buf = Vec::new();
+        let mut chunk = [0u8; 1024];
+        loop {
+            let n = stream.read(&mut chunk).await.unwrap();
+            buf.extend_from_slice(&chunk[..n]);
+            let request = String::from_utf8_lossy(&buf).to_string();
+            if let Some((head, body)) = request.split_once("\r\n\r\n") {
+                let len = header_value(head, "content-length")
+                    .and_then(|len| len.parse::<usize>().ok())
+                    .unwrap_or(0);
+                if body.len() >= len {
+                    return request;
+                }
+            }
+            if n == 0 {
+                return request;
+            }
+        }
+    }
+
+    async fn spawn_fake_gateway(reject_leases: bool) -> SocketAddr {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            loop {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let request = read_request(&mut stream).await;
+                let soap_action = header_value(&request, "soapaction").unwrap_or_default().to_string();
+                let (status, body) = if request.starts_with("GET /rootDesc.xml") {
+                    ("200 OK", DESCRIPTION.to_string())
+                } else if soap_action.ends_with("#GetExternalIPAddress\"") {
+                    (
+                        "200 OK",
+                        "<u:GetExternalIPAddressResponse><NewExternalIPAddress>1.2.3.4</NewExternalIPAddress></u:\
+                         GetExternalIPAddressResponse>"
+                            .to_string(),
+                    )
+                } else if soap_action.ends_with("#AddPortMapping\"") &&
+                    reject_leases &&
+                    !request.contains("<NewLeaseDuration>0</NewLeaseDuration>")
+                {
+                    (
+                        "500 Internal Server Error",
+                        "<UPnPError><errorCode>725</errorCode><errorDescription>OnlyPermanentLeasesSupported</\
+                         errorDescription></UPnPError>"
+                            .to_string(),
+                    )
+                } else if soap_action.ends_with("#AddPortMapping\"") || soap_action.ends_with("#DeletePortMapping\"") {
+                    ("200 OK", String::new())
+                } else {
+                    ("404 Not Found", String::new())
+                };
+                let response = format!("HTTP/1.0 {}\r\nContent-Length: {}\r\n\r\n{}", status, body.len(), body);
+                stream.write_all(response.as_bytes()).await.unwrap();
+            }
+        });
+        addr
+    }
+
+    #[test]
+    fn it_parses_urls() {
+        let url = HttpUrl::parse("http://192.168.1.1:5000/rootDesc.xml").unwrap();
+        assert_eq!(url.host, "192.168.1.1");
+        assert_eq!(url.port, 5000);
+        assert_eq!(url.path, "/rootDesc.xml");
+        assert_eq!(url.join("ctl/IPConn").unwrap().path, "/ctl/IPConn");
+        assert_eq!(HttpUrl::parse("http://gateway").unwrap().port, 80);
+        HttpUrl::parse("https://gateway").unwrap_err();
+    }
+
+    #[test]
+    fn it_finds_the_control_url() {
+        assert_eq!(
+            find_control_url(DESCRIPTION, "urn:schemas-upnp-org:service:WANIPConnection:1"),
+            Some("/ctl/IPConn")
+        );
+        assert!(find_control_url(DESCRIPTION, "urn:schemas-upnp-org:service:WANPPPConnection:1").is_none());
+        assert_eq!(
+            xml_tag_value(
+                "<u:NewExternalIPAddress>1.2.3.4</u:NewExternalIPAddress>",
+                "NewExternalIPAddress"
+            ),
+            Some("1.2.3.4")
+        );
+    }
+
+    #[tokio::test]
+    async fn it_maps_and_unmaps_a_port() {
+        let addr = spawn_fake_gateway(false).await;
+        let mapper =
+            UpnpPortMapper::with_description_url(format!("http://{}/rootDesc.xml", addr), Duration::from_secs(5));
+        let mapping = mapper.map_port(18189, Duration::from_secs(3600)).await.unwrap();
+        assert_eq!(mapping.external_ip, Ipv4Addr::new(1, 2, 3, 4));
+        assert_eq!(mapping.external_port, 18189);
+        mapper.unmap_port(&mapping).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn it_falls_back_to_a_permanent_lease() {
+        let addr = spawn_fake_gateway(true).await;
+        let mapper =
+            UpnpPortMapper::with_description_url(format!("http://{}/rootDesc.xml", addr), Duration::from_secs(5));
+        let mapping = mapper.map_port(18189, Duration::from_secs(3600)).await.unwrap();
+        assert_eq!(mapping.internal_port, 18189);
+    }
+}
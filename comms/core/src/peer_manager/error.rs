@@ -55,6 +55,8 @@ pub enum PeerManagerError {
     AddressNotFoundError { address: Multiaddr, node_id: NodeId },
     #[error("Protocol error: {0}")]
     ProtocolError(String),
+    #[error("Peer export error: {0}")]
+    PeerExportError(String),
 }
 
 impl PeerManagerError {
@@ -29,6 +29,7 @@ use tari_crypto::{
 hash_domain!(CommsCorePeerManagerDomain, "com.tari.comms.core.peer_manager", 1);
 
 pub(crate) const IDENTITY_SIGNATURE: &str = "identity_signature";
+pub(crate) const PEER_EXPORT_SIGNATURE: &str = "peer_export_signature";
 
 pub(crate) fn comms_core_peer_manager_domain<D: Digest + LengthExtensionAttackResistant>(
     label: &'static str,
@@ -36,9 +36,13 @@ use crate::{
         peer_id::PeerId,
         peer_storage::PeerStorage,
         wrapper::KeyValueWrapper,
+        ExportedPeer,
         NodeDistance,
         NodeId,
+        NodeIdentity,
+        PeerExport,
         PeerFeatures,
+        PeerImportSummary,
         PeerManagerError,
         PeerQuery,
     },
@@ -356,6 +360,85 @@ impl PeerManager {
     ) -> Result<Option<Vec<u8>>, PeerManagerError> {
         self.peer_storage.write().await.set_peer_metadata(node_id, key, data)
     }
+
+    /// Exports every non-banned peer that has a signed identity claim, including the local node itself, so that the
+    /// peer set can be imported by another node.
+    pub async fn export_peers(&self, node_identity: &NodeIdentity) -> Result<PeerExport, PeerManagerError> {
+        let mut peers = self
+            .peer_storage
+            .read()
+            .await
+            .all()?
+            .iter()
+            .filter(|peer| !peer.is_banned() && peer.deleted_at.is_none())
+            .filter_map(ExportedPeer::from_peer)
+            .collect::<Vec<_>>();
+        peers.extend(ExportedPeer::from_peer(&node_identity.to_peer()));
+        Ok(PeerExport::new(node_identity, peers))
+    }
+
+    /// Imports the peers in the given export, which must be signed by the exporting node. Entries whose identity claim
+    /// does not verify, peers that are banned locally and the local node itself are skipped. Addresses are recorded as
+    /// having come from the exporting node.
+    pub async fn import_peers(
+        &self,
+        export: PeerExport,
+        node_identity: &NodeIdentity,
+    ) -> Result<PeerImportSummary, PeerManagerError> {
+        if !export.is_valid() {
+            return Err(PeerManagerError::PeerExportError(format!(
+                "The export is not signed by {}",
+                export.exported_by
+            )));
+        }
+        let mut summary = PeerImportSummary::default();
+        let mut lock = self.peer_storage.write().await;
+        for exported in export.peers {
+            if exported.public_key == *node_identity.public_key() {
+                continue;
+            }
+            if !exported.is_valid() {
+                summary.num_invalid += 1;
+                continue;
+            }
+
+            let addresses = exported.identity_claim.addresses.clone();
+            let features = exported.identity_claim.features;
+            let source = PeerAddressSource::FromAnotherPeer {
+                peer_identity_claim: exported.identity_claim,
+                source_peer: export.exported_by.clone(),
+            };
+            match lock.find_by_public_key(&exported.public_key)? {
+                Some(peer) if peer.is_banned() => {
+                    summary.num_banned += 1;
+                },
+                Some(mut peer) => {
+                    peer.update_addresses(&addresses, &source).set_features(features);
+                    lock.add_peer(peer)?;
+                    summary.num_updated += 1;
+                },
+                None => {
+                    let node_id = NodeId::from_public_key(&exported.public_key);
+                    lock.add_peer(Peer::new(
+                        exported.public_key,
+                        node_id,
+                        MultiaddressesWithStats::from_addresses_with_source(addresses, &source),
+                        PeerFlags::empty(),
+                        features,
+                        Default::default(),
+                        Default::default(),
+                    ))?;
+                    summary.num_added += 1;
+                },
+            }
+        }
+        #[cfg(feature = "metrics")]
+        {
+            let count = lock.count();
+            metrics::peer_list_size().set(count as i64);
+        }
+        Ok(summary)
+    }
 }
 
 impl fmt::Debug for PeerManager {
@@ -380,6 +463,7 @@ mod test {
             peer::{Peer, PeerFlags},
             PeerFeatures,
         },
+        test_utils::node_identity::build_node_identity,
     };
 
     fn create_test_peer(ban_flag: bool, features: PeerFeatures) -> Peer {
@@ -653,4 +737,50 @@ mod test {
 
         assert!(!peer.is_offline());
     }
+
+    #[tokio::test]
+    async fn test_export_import_peers() {
+        let exporter_identity = build_node_identity(PeerFeatures::COMMUNICATION_NODE);
+        let exporter = PeerManager::new(HashmapDatabase::new(), None).unwrap();
+        let known = build_node_identity(PeerFeatures::COMMUNICATION_NODE);
+        exporter.add_peer(known.to_peer()).await.unwrap();
+        // Config-only peers have no signed claim and are not exported
+        exporter
+            .add_peer(create_test_peer(false, PeerFeatures::COMMUNICATION_NODE))
+            .await
+            .unwrap();
+
+        let export = exporter.export_peers(&exporter_identity).await.unwrap();
+        assert_eq!(export.peers.len(), 2);
+        assert!(export.is_valid());
+        // An entry whose claim does not verify is rejected even when the export itself is signed
+        let mut peers = export.peers.clone();
+        let mut forged = peers[0].clone();
+        forged.public_key = build_node_identity(PeerFeatures::COMMUNICATION_NODE)
+            .public_key()
+            .clone();
+        peers.push(forged);
+        let export = PeerExport::new(&exporter_identity, peers);
+
+        let importer_identity = build_node_identity(PeerFeatures::COMMUNICATION_NODE);
+        let importer = PeerManager::new(HashmapDatabase::new(), None).unwrap();
+        let mut tampered = export.clone();
+        tampered.peers.pop();
+        assert!(importer.import_peers(tampered, &importer_identity).await.is_err());
+
+        let summary = importer.import_peers(export.clone(), &importer_identity).await.unwrap();
+        assert_eq!(summary.num_added, 2);
+        assert_eq!(summary.num_invalid, 1);
+        assert!(importer.exists(known.public_key()).await);
+        assert!(importer.exists(exporter_identity.public_key()).await);
+
+        importer
+            .ban_peer(known.public_key(), Duration::from_secs(60), "test".to_string())
+            .await
+            .unwrap();
+        let summary = importer.import_peers(export, &importer_identity).await.unwrap();
+        assert_eq!(summary.num_added, 0);
+        assert_eq!(summary.num_updated, 1);
+        assert_eq!(summary.num_banned, 1);
+    }
 }
@@ -108,6 +108,9 @@ pub use peer_storage::PeerStorage;
 mod peer_identity_claim;
 pub use peer_identity_claim::PeerIdentityClaim;
 
+mod peer_export;
+pub use peer_export::{ExportedPeer, PeerExport, PeerImportSummary, PEER_EXPORT_VERSION};
+
 mod migrations;
 
 mod or_not_found;
@@ -145,6 +145,19 @@ impl NodeIdentity {
         }
     }
 
+    /// Remove a public address. The identity signature is only updated if the address was present.
+    pub fn remove_public_address(&self, address: &Multiaddr) {
+        let must_sign = {
+            let mut lock = acquire_write_lock!(self.public_addresses);
+            let len = lock.len();
+            lock.retain(|a| a != address);
+            lock.len() != len
+        };
+        if must_sign {
+            self.sign()
+        }
+    }
+
     /// Set the available addresses. If none of the addresses have changed, the identity signature remains unchanged.
     pub fn set_public_addresses(&self, addresses: Vec<Multiaddr>) {
         let mut must_sign = false;
@@ -0,0 +1,283 @@
+// Copyright 2024. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! A portable, verifiable representation of the peer database.
+//!
+//! Peers are exported along with the most recent [PeerIdentityClaim] they signed. Because every claim carries the
+//! peer's own signature over its addresses and features, an importing node does not need to trust the node that
+//! produced the file: any entry whose claim does not verify against its public key is rejected.
+//!
+//! The export as a whole is signed by the exporting node, so that imported addresses can be attributed to it.
+
+use std::{convert::TryFrom, fmt};
+
+use blake2::Blake2b;
+use chrono::{DateTime, Utc};
+use digest::consts::U64;
+use rand::rngs::OsRng;
+use serde_derive::{Deserialize, Serialize};
+use tari_crypto::{hashing::DomainSeparatedHasher, keys::PublicKey as PublicKeyTrait};
+use tari_utilities::ByteArray;
+
+use super::hashing::{comms_core_peer_manager_domain, CommsCorePeerManagerDomain, PEER_EXPORT_SIGNATURE};
+use crate::{
+    peer_manager::{NodeIdentity, Peer, PeerIdentityClaim, PeerManagerError},
+    types::{CommsPublicKey, Signature},
+};
+
+/// The current version of the peer export format
+pub const PEER_EXPORT_VERSION: u16 = 2;
+
+/// A set of peers exported from a node's peer database.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PeerExport {
+    pub version: u16,
+    /// The public key of the node that produced this export
+    pub exported_by: CommsPublicKey,
+    pub exported_at: DateTime<Utc>,
+    pub peers: Vec<ExportedPeer>,
+    /// The signature of `exported_by` over the rest of the export
+    pub signature: Signature,
+}
+
+impl PeerExport {
+    /// Creates an export of the given peers signed by the exporting node
+    pub fn new(node_identity: &NodeIdentity, peers: Vec<ExportedPeer>) -> Self {
+        let exported_by = node_identity.public_key().clone();
+        let exported_at = Utc::now();
+        let (secret_nonce, public_nonce) = CommsPublicKey::random_keypair(&mut OsRng);
+        let challenge =
+            Self::construct_challenge(&exported_by, &public_nonce, PEER_EXPORT_VERSION, exported_at, &peers).finalize();
+        let signature = Signature::sign_raw_uniform(node_identity.secret_key(), secret_nonce, challenge.as_ref())
+            .expect("unreachable panic: challenge hash digest is the correct length");
+        Self {
+            version: PEER_EXPORT_VERSION,
+            exported_by,
+            exported_at,
+            peers,
+            signature,
+        }
+    }
+
+    /// Returns true if the export was signed by `exported_by` and has not been modified since
+    pub fn is_valid(&self) -> bool {
+        let challenge = Self::construct_challenge(
+            &self.exported_by,
+            self.signature.get_public_nonce(),
+            self.version,
+            self.exported_at,
+            &self.peers,
+        )
+        .finalize();
+        self.signature.verify_raw_uniform(&self.exported_by, challenge.as_ref())
+    }
+
+    fn construct_challenge(
+        exported_by: &CommsPublicKey,
+        public_nonce: &CommsPublicKey,
+        version: u16,
+        exported_at: DateTime<Utc>,
+        peers: &[ExportedPeer],
+    ) -> DomainSeparatedHasher<Blake2b<U64>, CommsCorePeerManagerDomain> {
+        // e = H(P||R||m)
+        let challenge = comms_core_peer_manager_domain::<Blake2b<U64>>(PEER_EXPORT_SIGNATURE)
+            .chain(exported_by.as_bytes())
+            .chain(public_nonce.as_bytes())
+            .chain(version.to_le_bytes())
+            .chain(exported_at.timestamp().to_le_bytes())
+            .chain(u64::try_from(peers.len()).unwrap_or(u64::MAX).to_le_bytes());
+        peers.iter().fold(challenge, |challenge, peer| {
+            let claim = &peer.identity_claim;
+            let challenge = challenge
+                .chain(peer.public_key.as_bytes())
+                .chain(claim.signature.to_bytes())
+                .chain(claim.features.bits().to_le_bytes())
+                .chain(u64::try_from(claim.addresses.len()).unwrap_or(u64::MAX).to_le_bytes());
+            claim
+                .addresses
+                .iter()
+                .fold(challenge, |challenge, addr| challenge.chain(addr))
+        })
+    }
+
+    pub fn to_json(&self) -> Result<String, PeerManagerError> {
+        serde_json::to_string_pretty(self).map_err(|err| PeerManagerError::PeerExportError(err.to_string()))
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, PeerManagerError> {
+        let export =
+            serde_json::from_str::<Self>(json).map_err(|err| PeerManagerError::PeerExportError(err.to_string()))?;
+        if export.version != PEER_EXPORT_VERSION {
+            return Err(PeerManagerError::PeerExportError(format!(
+                "Unsupported peer export version {} (expected {})",
+                export.version, PEER_EXPORT_VERSION
+            )));
+        }
+        if !export.is_valid() {
+            return Err(PeerManagerError::PeerExportError(format!(
+                "The export is not signed by {}",
+                export.exported_by
+            )));
+        }
+        Ok(export)
+    }
+}
+
+/// A single exported peer and the latest identity claim it signed
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ExportedPeer {
+    pub public_key: CommsPublicKey,
+    pub identity_claim: PeerIdentityClaim,
+}
+
+impl ExportedPeer {
+    /// Returns an exportable entry for the peer using the most recent valid identity claim of any of its addresses.
+    /// Returns None if the peer has no signed claim (e.g. it was only ever added from config).
+    pub fn from_peer(peer: &Peer) -> Option<Self> {
+        let claim = peer
+            .addresses
+            .iter()
+            .filter_map(|addr| addr.source().peer_identity_claim())
+            .filter(|claim| claim.is_valid(&peer.public_key))
+            .max_by_key(|claim| claim.signature.updated_at())?;
+
+        Some(Self {
+            public_key: peer.public_key.clone(),
+            identity_claim: claim.clone(),
+        })
+    }
+
+    /// Returns true if the identity claim was signed by this peer's public key
+    pub fn is_valid(&self) -> bool {
+        self.identity_claim.is_valid(&self.public_key)
+    }
+}
+
+/// The outcome of importing a [PeerExport]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PeerImportSummary {
+    pub num_added: usize,
+    pub num_updated: usize,
+    /// Entries rejected because their identity claim did not verify
+    pub num_invalid: usize,
+    /// Entries skipped because the peer is banned locally
+    pub num_banned: usize,
+}
+
+impl fmt::Display for PeerImportSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} added, {} updated, {} invalid, {} banned",
+            self.num_added, self.num_updated, self.num_invalid, self.num_banned
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        net_address::{MultiaddressesWithStats, PeerAddressSource},
+        peer_manager::{NodeIdentity, PeerFeatures, PeerFlags},
+        test_utils::node_identity::build_node_identity,
+    };
+
+    #[test]
+    fn it_exports_the_latest_valid_claim() {
+        let node_identity = build_node_identity(PeerFeatures::COMMUNICATION_NODE);
+        let peer = node_identity.to_peer();
+        let exported = ExportedPeer::from_peer(&peer).unwrap();
+        assert_eq!(&exported.public_key, node_identity.public_key());
+        assert_eq!(exported.identity_claim.addresses, node_identity.public_addresses());
+        assert!(exported.is_valid());
+    }
+
+    #[test]
+    fn it_does_not_export_config_peers() {
+        let node_identity = build_node_identity(PeerFeatures::COMMUNICATION_NODE);
+        let peer = Peer::new(
+            node_identity.public_key().clone(),
+            node_identity.node_id().clone(),
+            MultiaddressesWithStats::from_addresses_with_source(
+                node_identity.public_addresses(),
+                &PeerAddressSource::Config,
+            ),
+            PeerFlags::empty(),
+            PeerFeatures::COMMUNICATION_NODE,
+            Default::default(),
+            Default::default(),
+        );
+        assert!(ExportedPeer::from_peer(&peer).is_none());
+    }
+
+    #[test]
+    fn it_detects_a_claim_for_another_key() {
+        let node_identity = build_node_identity(PeerFeatures::COMMUNICATION_NODE);
+        let other = NodeIdentity::random_for_test(None, PeerFeatures::COMMUNICATION_NODE);
+        let mut exported = ExportedPeer::from_peer(&node_identity.to_peer()).unwrap();
+        exported.public_key = other.public_key().clone();
+        assert!(!exported.is_valid());
+    }
+
+    #[test]
+    fn it_rejects_a_modified_export() {
+        let node_identity = build_node_identity(PeerFeatures::COMMUNICATION_NODE);
+        let other = build_node_identity(PeerFeatures::COMMUNICATION_NODE);
+        let export = PeerExport::new(&node_identity, vec![
+            ExportedPeer::from_peer(&node_identity.to_peer()).unwrap()
+        ]);
+        assert!(export.is_valid());
+
+        let mut modified = export.clone();
+        modified.peers.push(ExportedPeer::from_peer(&other.to_peer()).unwrap());
+        assert!(!modified.is_valid());
+
+        let mut modified = export.clone();
+        modified.exported_by = other.public_key().clone();
+        assert!(!modified.is_valid());
+        assert!(PeerExport::from_json(&modified.to_json().unwrap()).is_err());
+
+        let mut modified = export;
+        modified.exported_at = modified.exported_at + chrono::Duration::seconds(1);
+        assert!(!modified.is_valid());
+    }
+
+    #[test]
+    fn it_round_trips_through_json() {
+        let node_identity = build_node_identity(PeerFeatures::COMMUNICATION_NODE);
+        let export = PeerExport::new(&node_identity, vec![
+            ExportedPeer::from_peer(&node_identity.to_peer()).unwrap()
+        ]);
+        let json = export.to_json().unwrap();
+        let decoded = PeerExport::from_json(&json).unwrap();
+        assert_eq!(decoded.exported_by, export.exported_by);
+        assert_eq!(decoded.peers, export.peers);
+        assert!(decoded.is_valid());
+        assert!(decoded.peers[0].is_valid());
+
+        let mut export = export;
+        export.version = PEER_EXPORT_VERSION + 1;
+        let json = export.to_json().unwrap();
+        assert!(PeerExport::from_json(&json).is_err());
+    }
+}
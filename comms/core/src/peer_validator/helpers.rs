@@ -86,14 +86,7 @@ fn validate_address(addr: &Multiaddr, allow_test_addrs: bool) -> Result<(), Peer
     }
 
     match proto {
-        Protocol::Dns4(_) | Protocol::Dns6(_) | Protocol::Dnsaddr(_) => {
-            let tcp = addr_iter.next().ok_or_else(|| {
-                PeerValidatorError::InvalidMultiaddr("Address does not include a TCP port".to_string())
-            })?;
-
-            validate_tcp_port(tcp)?;
-            expect_end_of_address(addr_iter)
-        },
+        Protocol::Dns4(_) | Protocol::Dns6(_) | Protocol::Dnsaddr(_) => validate_transport(addr_iter),
 
         Protocol::Ip4(addr)
             if !allow_test_addrs && (addr.is_loopback() || addr.is_link_local() || addr.is_unspecified()) =>
@@ -109,14 +102,7 @@ fn validate_address(addr: &Multiaddr, allow_test_addrs: bool) -> Result<(), Peer
                 "Non-global IP addresses are invalid".to_string(),
             ))
         },
-        Protocol::Ip4(_) | Protocol::Ip6(_) => {
-            let tcp = addr_iter.next().ok_or_else(|| {
-                PeerValidatorError::InvalidMultiaddr("Address does not include a TCP port".to_string())
-            })?;
-
-            validate_tcp_port(tcp)?;
-            expect_end_of_address(addr_iter)
-        },
+        Protocol::Ip4(_) | Protocol::Ip6(_) => validate_transport(addr_iter),
         Protocol::Memory(0) => Err(PeerValidatorError::InvalidMultiaddr(
             "Cannot connect to a zero memory port".to_string(),
         )),
@@ -153,14 +139,32 @@ fn expect_end_of_address(mut iter: multiaddr::Iter<'_>) -> Result<(), PeerValida
     }
 }
 
-fn validate_tcp_port(expected_tcp: Protocol) -> Result<(), PeerValidatorError> {
-    match expected_tcp {
+/// Validates the transport components following a host i.e. `/tcp/<port>` or `/udp/<port>/quic`
+fn validate_transport(mut iter: multiaddr::Iter<'_>) -> Result<(), PeerValidatorError> {
+    let transport = iter.next().ok_or_else(|| {
+        PeerValidatorError::InvalidMultiaddr("Address does not include a TCP or UDP port".to_string())
+    })?;
+
+    match transport {
         Protocol::Tcp(0) => Err(PeerValidatorError::InvalidMultiaddr(
             "Cannot connect to a zero TCP port".to_string(),
         )),
-        Protocol::Tcp(_) => Ok(()),
+        Protocol::Tcp(_) => expect_end_of_address(iter),
+        Protocol::Udp(0) => Err(PeerValidatorError::InvalidMultiaddr(
+            "Cannot connect to a zero UDP port".to_string(),
+        )),
+        Protocol::Udp(_) => match iter.next() {
+            Some(Protocol::Quic) => expect_end_of_address(iter),
+            Some(p) => Err(PeerValidatorError::InvalidMultiaddr(format!(
+                "Expected QUIC address component but got '{}'",
+                p
+            ))),
+            None => Err(PeerValidatorError::InvalidMultiaddr(
+                "UDP addresses must use the QUIC protocol".to_string(),
+            )),
+        },
         p => Err(PeerValidatorError::InvalidMultiaddr(format!(
-            "Expected TCP address component but got '{}'",
+            "Expected TCP or UDP address component but got '{}'",
             p
         ))),
     }
@@ -211,11 +215,16 @@ mod test {
                 .parse()
                 .unwrap(),
             multiaddr!(Dnsaddr("mike-magic-nodes.com"), Tcp(1u16)),
+            multiaddr!(Ip4([172, 0, 0, 1]), Udp(1u16), Quic),
+            multiaddr!(Dns4("mike-magic-nodes.com"), Udp(1u16), Quic),
         ];
 
         let invalid = &[
             "/onion/aaimaq4ygg2iegci:1234".parse().unwrap(),
             multiaddr!(Ip4([127, 0, 0, 1]), Tcp(1u16)),
+            multiaddr!(Ip4([172, 0, 0, 1]), Udp(1u16)),
+            multiaddr!(Ip4([172, 0, 0, 1]), Udp(0u16), Quic),
+            multiaddr!(Ip4([172, 0, 0, 1]), Tcp(1u16), Quic),
             multiaddr!(Ip4([169, 254, 0, 1]), Tcp(1u16)),
             multiaddr!(Ip4([172, 0, 0, 1])),
             "/onion/aaimaq4ygg2iegci:1234/http".parse().unwrap(),
@@ -244,11 +253,13 @@ mod test {
                 .unwrap(),
             multiaddr!(Dnsaddr("mike-magic-nodes.com"), Tcp(1u16)),
             multiaddr!(Memory(1234u64)),
+            multiaddr!(Ip4([127, 0, 0, 1]), Udp(1u16), Quic),
         ];
 
         let invalid = &[
             "/onion/aaimaq4ygg2iegci:1234".parse().unwrap(),
             multiaddr!(Ip4([172, 0, 0, 1])),
+            multiaddr!(Ip4([127, 0, 0, 1]), Udp(1u16)),
             "/onion/aaimaq4ygg2iegci:1234/http".parse().unwrap(),
             multiaddr!(Dnsaddr("mike-magic-nodes.com")),
             multiaddr!(Memory(0u64)),
@@ -33,6 +33,7 @@ use tokio::{
 use crate::{
     bans::{BAN_DURATION_LONG, BAN_DURATION_SHORT},
     message::MessageExt,
+    multiaddr::Multiaddr,
     peer_manager::NodeIdentity,
     proto::identity::PeerIdentityMsg,
     protocol::{NodeNetworkInfo, ProtocolId},
@@ -52,10 +53,14 @@ const MAX_IDENTITY_PROTOCOL_MSG_SIZE: u16 = 1024;
 ///   |  ---------[identity]--------> |
 ///   |  <---------[identity]-------- |
 /// ```
+///
+/// The `observed_address` is the address of the remote peer as seen by this node and is sent to the peer so that it can
+/// learn its external address.
 pub async fn identity_exchange<'p, TSocket, P>(
     node_identity: &NodeIdentity,
     our_supported_protocols: P,
     network_info: NodeNetworkInfo,
+    observed_address: Option<&Multiaddr>,
     socket: &mut TSocket,
 ) -> Result<PeerIdentityMsg, IdentityProtocolError>
 where
@@ -71,6 +76,7 @@ where
         supported_protocols,
         user_agent: network_info.user_agent,
         identity_signature: node_identity.identity_signature_read().as_ref().map(Into::into),
+        observed_address: observed_address.map(|a| a.to_vec()).unwrap_or_default(),
     }
     .to_encoded_bytes();
 
@@ -219,6 +225,7 @@ mod test {
     use futures::{future, StreamExt};
 
     use crate::{
+        multiaddr::Multiaddr,
         peer_manager::PeerFeatures,
         protocol::{IdentityProtocolError, NodeNetworkInfo},
         test_utils::node_identity::build_node_identity,
@@ -238,6 +245,7 @@ mod test {
 
         let node_identity1 = build_node_identity(PeerFeatures::COMMUNICATION_NODE);
         let node_identity2 = build_node_identity(PeerFeatures::COMMUNICATION_CLIENT);
+        let observed_address = "/ip4/1.2.3.4/tcp/1234".parse::<Multiaddr>().unwrap();
 
         let (result1, result2) = future::join(
             super::identity_exchange(
@@ -247,6 +255,7 @@ mod test {
                     minor_version: 1,
                     ..Default::default()
                 },
+                Some(&observed_address),
                 &mut in_sock,
             ),
             super::identity_exchange(
@@ -256,6 +265,7 @@ mod test {
                     minor_version: 2,
                     ..Default::default()
                 },
+                None,
                 &mut out_sock,
             ),
         )
@@ -275,6 +285,10 @@ mod test {
                 .collect::<Vec<_>>()
         );
 
+        // Node 2 learns the address that node 1 observed for it
+        assert_eq!(identity1.observed_address, observed_address.to_vec());
+        assert!(identity2.observed_address.is_empty());
+
         assert_eq!(identity2.features, node_identity2.features().bits());
         assert_eq!(
             identity2.addresses,
@@ -308,6 +322,7 @@ mod test {
                     major_version: 0,
                     ..Default::default()
                 },
+                None,
                 &mut in_sock,
             ),
             super::identity_exchange(
@@ -317,6 +332,7 @@ mod test {
                     major_version: 1,
                     ..Default::default()
                 },
+                None,
                 &mut out_sock,
             ),
         )
@@ -81,6 +81,11 @@ impl RpcError {
         RpcError::ClientInternalError(err.to_string())
     }
 
+    /// Returns true if the server asked the client to back off before retrying (i.e. it is rate limited or busy)
+    pub fn is_back_off(&self) -> bool {
+        matches!(self, RpcError::RequestFailed(status) if status.is_back_off())
+    }
+
     /// Returns true if the server directly caused the error, otherwise false
     pub fn is_caused_by_server(&self) -> bool {
         match self {
@@ -35,9 +35,11 @@ use crate::{framing::CanonicalFraming, message::MessageExt, proto, protocol::rpc
 
 const LOG_TARGET: &str = "comms::rpc::handshake";
 
-/// Supported RPC protocol versions.
-/// Currently only v0 is supported
-pub(super) const SUPPORTED_RPC_VERSIONS: &[u32] = &[0];
+/// Supported RPC protocol versions, in order of preference. The server accepts the first of these that the client
+/// supports.
+/// - v1 clients understand the `RateLimited` and `Busy` status codes
+/// - v0 clients receive those as a `General` status with the same details
+pub(super) const SUPPORTED_RPC_VERSIONS: &[u32] = &[1, 0];
 
 #[derive(Debug, thiserror::Error)]
 pub enum RpcHandshakeError {
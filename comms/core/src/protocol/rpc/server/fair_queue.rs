@@ -0,0 +1,209 @@
+// Copyright 2024. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+#[cfg(feature = "metrics")]
+use std::convert::TryFrom;
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{Arc, Mutex},
+};
+
+use tokio::sync::oneshot;
+
+use crate::peer_manager::NodeId;
+
+/// Limits the number of response streams that are sent at once, across all peers. When the limit is reached, waiting
+/// streams are queued per peer and slots are handed out to peers in round-robin order, so a peer that opens many
+/// streams cannot starve other peers.
+#[derive(Debug, Clone)]
+pub(super) struct FairQueue {
+    state: Arc<Mutex<FairQueueState>>,
+}
+
+#[derive(Debug)]
+struct FairQueueState {
+    num_available: usize,
+    waiters: HashMap<NodeId, VecDeque<oneshot::Sender<FairQueuePermit>>>,
+    order: VecDeque<NodeId>,
+}
+
+impl FairQueueState {
+    #[cfg(any(test, feature = "metrics"))]
+    fn num_waiting(&self) -> usize {
+        self.waiters.values().map(VecDeque::len).sum()
+    }
+
+    #[cfg(feature = "metrics")]
+    fn update_metrics(&self) {
+        super::metrics::num_queued_streams().set(i64::try_from(self.num_waiting()).unwrap_or(i64::MAX));
+    }
+}
+
+impl FairQueue {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(FairQueueState {
+                num_available: capacity,
+                waiters: HashMap::new(),
+                order: VecDeque::new(),
+            })),
+        }
+    }
+
+    /// Waits for a slot for the given peer. The slot is released when the returned permit is dropped.
+    pub async fn acquire(&self, node_id: &NodeId) -> FairQueuePermit {
+        let rx = {
+            let mut state = self.state.lock().expect("FairQueue lock poisoned");
+            if state.num_available > 0 && state.order.is_empty() {
+                state.num_available -= 1;
+                return FairQueuePermit { queue: self.clone() };
+            }
+            let (tx, rx) = oneshot::channel();
+            let peer_waiters = state.waiters.entry(node_id.clone()).or_default();
+            let is_new_peer = peer_waiters.is_empty();
+            peer_waiters.push_back(tx);
+            if is_new_peer {
+                state.order.push_back(node_id.clone());
+            }
+            #[cfg(feature = "metrics")]
+            state.update_metrics();
+            rx
+        };
+
+        // The sender is only dropped after a permit has been sent, so this cannot fail
+        rx.await.expect("FairQueue dropped a waiter without a permit")
+    }
+
+    /// Returns the number of streams waiting for a slot
+    #[cfg(test)]
+    pub fn num_waiting(&self) -> usize {
+        let state = self.state.lock().expect("FairQueue lock poisoned");
+        state.num_waiting()
+    }
+
+    fn release(&self) {
+        let next_waiter = {
+            let mut state = self.state.lock().expect("FairQueue lock poisoned");
+            match state.order.pop_front() {
+                Some(node_id) => {
+                    let peer_waiters = state
+                        .waiters
+                        .get_mut(&node_id)
+                        .expect("FairQueue peer in order without waiters");
+                    let waiter = peer_waiters.pop_front();
+                    if peer_waiters.is_empty() {
+                        state.waiters.remove(&node_id);
+                    } else {
+                        state.order.push_back(node_id);
+                    }
+                    #[cfg(feature = "metrics")]
+                    state.update_metrics();
+                    waiter
+                },
+                None => {
+                    state.num_available += 1;
+                    None
+                },
+            }
+        };
+
+        // If the waiter has gone away, the returned permit is dropped outside of the lock and passed to the next waiter
+        if let Some(waiter) = next_waiter {
+            let _result = waiter.send(FairQueuePermit { queue: self.clone() });
+        }
+    }
+}
+
+/// A slot in the [FairQueue]. The slot is passed to the next waiting peer when this is dropped.
+#[derive(Debug)]
+pub(super) struct FairQueuePermit {
+    queue: FairQueue,
+}
+
+impl Drop for FairQueuePermit {
+    fn drop(&mut self) {
+        self.queue.release();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use futures::FutureExt;
+    use tokio::time;
+
+    use super::*;
+    use crate::{peer_manager::PeerFeatures, test_utils::node_identity::build_node_identity};
+
+    fn node_id() -> NodeId {
+        build_node_identity(PeerFeatures::COMMUNICATION_NODE).node_id().clone()
+    }
+
+    #[tokio::test]
+    async fn it_serves_peers_in_round_robin_order() {
+        let queue = FairQueue::new(1);
+        let greedy_peer = node_id();
+        let other_peer = node_id();
+
+        let permit = queue.acquire(&greedy_peer).await;
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let waiters = vec![greedy_peer.clone(), greedy_peer.clone(), other_peer.clone()];
+        for (i, peer) in waiters.into_iter().enumerate() {
+            let queue = queue.clone();
+            let tx = tx.clone();
+            tokio::spawn(async move {
+                let permit = queue.acquire(&peer).await;
+                tx.send(peer).unwrap();
+                time::sleep(Duration::from_millis(10)).await;
+                drop(permit);
+            });
+            // Ensure that the waiters are queued in the order they were spawned
+            while queue.num_waiting() <= i {
+                tokio::task::yield_now().await;
+            }
+        }
+        assert_eq!(queue.num_waiting(), 3);
+
+        drop(permit);
+        // The other peer is served before the greedy peer's second stream
+        assert_eq!(rx.recv().await.unwrap(), greedy_peer);
+        assert_eq!(rx.recv().await.unwrap(), other_peer);
+        assert_eq!(rx.recv().await.unwrap(), greedy_peer);
+    }
+
+    #[tokio::test]
+    async fn it_passes_slots_past_abandoned_waiters() {
+        let queue = FairQueue::new(1);
+        let peer1 = node_id();
+        let peer2 = node_id();
+
+        let permit = queue.acquire(&peer1).await;
+        assert!(queue.acquire(&peer1).now_or_never().is_none());
+        assert_eq!(queue.num_waiting(), 1);
+
+        let mut waiter = Box::pin(queue.acquire(&peer2));
+        assert!(waiter.as_mut().now_or_never().is_none());
+        drop(permit);
+        let _permit = waiter.await;
+    }
+}
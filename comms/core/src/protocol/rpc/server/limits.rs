@@ -0,0 +1,336 @@
+// Copyright 2024. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use crate::{
+    peer_manager::NodeId,
+    protocol::{rpc::message::RpcMethod, ProtocolId},
+};
+
+/// The window over which per-minute limits are counted
+const LIMIT_WINDOW: Duration = Duration::from_secs(60);
+
+/// Limits applied to each peer for a single RPC method. Every peer is accounted separately, so one peer reaching a
+/// limit does not affect requests from other peers.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RpcMethodLimits {
+    /// The maximum number of requests a peer may make to the method per minute. None for unlimited.
+    pub max_requests_per_minute: Option<u32>,
+    /// The maximum number of requests to the method that a peer may have in progress at once, across all of its
+    /// sessions. None for unlimited.
+    pub max_concurrent_requests: Option<usize>,
+    /// The maximum number of response bytes that may be streamed to a peer from the method per minute. A response
+    /// stream that exceeds this limit is terminated. None for unlimited.
+    pub max_bytes_per_minute: Option<u64>,
+}
+
+impl RpcMethodLimits {
+    pub fn unlimited() -> Self {
+        Self::default()
+    }
+}
+
+/// The limit that a peer exceeded
+#[derive(Debug, Clone, thiserror::Error, PartialEq, Eq)]
+pub(super) enum LimitExceeded {
+    #[error("Request limit of {limit} per minute reached. Retry in {retry_after:.0?}")]
+    RequestsPerMinute { limit: u32, retry_after: Duration },
+    #[error("Concurrent request limit of {limit} reached")]
+    ConcurrentRequests { limit: usize },
+    #[error("Streamed bytes limit of {limit} per minute reached. Retry in {retry_after:.0?}")]
+    BytesPerMinute { limit: u64, retry_after: Duration },
+}
+
+impl LimitExceeded {
+    #[cfg(feature = "metrics")]
+    pub fn as_label(&self) -> &'static str {
+        match self {
+            LimitExceeded::RequestsPerMinute { .. } => "requests_per_minute",
+            LimitExceeded::ConcurrentRequests { .. } => "concurrent_requests",
+            LimitExceeded::BytesPerMinute { .. } => "bytes_per_minute",
+        }
+    }
+}
+
+type UsageKey = (NodeId, ProtocolId, u32);
+
+#[derive(Debug)]
+struct MethodUsage {
+    window_start: Instant,
+    num_requests: u32,
+    num_bytes: u64,
+    num_active: usize,
+}
+
+impl MethodUsage {
+    fn new(now: Instant) -> Self {
+        Self {
+            window_start: now,
+            num_requests: 0,
+            num_bytes: 0,
+            num_active: 0,
+        }
+    }
+
+    fn roll_window(&mut self, now: Instant) {
+        if now.saturating_duration_since(self.window_start) >= LIMIT_WINDOW {
+            self.window_start = now;
+            self.num_requests = 0;
+            self.num_bytes = 0;
+        }
+    }
+
+    fn retry_after(&self, now: Instant) -> Duration {
+        LIMIT_WINDOW.saturating_sub(now.saturating_duration_since(self.window_start))
+    }
+
+    fn is_idle(&self, now: Instant) -> bool {
+        self.num_active == 0 && now.saturating_duration_since(self.window_start) >= LIMIT_WINDOW
+    }
+}
+
+/// Tracks per-peer, per-method usage for all sessions of an RPC server
+#[derive(Debug, Clone)]
+pub(super) struct RpcRequestLimiter {
+    default_limits: RpcMethodLimits,
+    method_limits: Arc<HashMap<(ProtocolId, u32), RpcMethodLimits>>,
+    usage: Arc<Mutex<HashMap<UsageKey, MethodUsage>>>,
+}
+
+impl RpcRequestLimiter {
+    pub fn new(default_limits: RpcMethodLimits, method_limits: HashMap<(ProtocolId, u32), RpcMethodLimits>) -> Self {
+        Self {
+            default_limits,
+            method_limits: Arc::new(method_limits),
+            usage: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    fn limits_for(&self, protocol: &ProtocolId, method: u32) -> RpcMethodLimits {
+        self.method_limits
+            .get(&(protocol.clone(), method))
+            .copied()
+            .unwrap_or(self.default_limits)
+    }
+
+    /// Checks the limits for a new request from the peer. If no limit has been reached, the request is counted and a
+    /// permit is returned that must be held until the request is complete.
+    pub fn acquire(
+        &self,
+        node_id: &NodeId,
+        protocol: &ProtocolId,
+        method: RpcMethod,
+    ) -> Result<RequestPermit, LimitExceeded> {
+        let limits = self.limits_for(protocol, method.id());
+        let key = (node_id.clone(), protocol.clone(), method.id());
+        let now = Instant::now();
+        let mut usage = self.usage.lock().expect("RpcRequestLimiter lock poisoned");
+        let method_usage = usage.entry(key.clone()).or_insert_with(|| MethodUsage::new(now));
+        method_usage.roll_window(now);
+
+        if let Some(limit) = limits.max_requests_per_minute {
+            if method_usage.num_requests >= limit {
+                return Err(LimitExceeded::RequestsPerMinute {
+                    limit,
+                    retry_after: method_usage.retry_after(now),
+                });
+            }
+        }
+        if let Some(limit) = limits.max_concurrent_requests {
+            if method_usage.num_active >= limit {
+                return Err(LimitExceeded::ConcurrentRequests { limit });
+            }
+        }
+        if let Some(limit) = limits.max_bytes_per_minute {
+            if method_usage.num_bytes >= limit {
+                return Err(LimitExceeded::BytesPerMinute {
+                    limit,
+                    retry_after: method_usage.retry_after(now),
+                });
+            }
+        }
+
+        method_usage.num_requests += 1;
+        method_usage.num_active += 1;
+        Ok(RequestPermit {
+            limiter: self.clone(),
+            key,
+            max_bytes_per_minute: limits.max_bytes_per_minute,
+        })
+    }
+
+    /// Removes usage records for peers that have no requests in progress and whose limit window has expired
+    pub fn prune(&self) {
+        let now = Instant::now();
+        let mut usage = self.usage.lock().expect("RpcRequestLimiter lock poisoned");
+        usage.retain(|_, method_usage| !method_usage.is_idle(now));
+    }
+
+    #[cfg(test)]
+    fn num_tracked(&self) -> usize {
+        self.usage.lock().unwrap().len()
+    }
+
+    fn record_bytes(&self, key: &UsageKey, num_bytes: usize, limit: Option<u64>) -> Result<(), LimitExceeded> {
+        let now = Instant::now();
+        let mut usage = self.usage.lock().expect("RpcRequestLimiter lock poisoned");
+        let method_usage = match usage.get_mut(key) {
+            Some(method_usage) => method_usage,
+            None => return Ok(()),
+        };
+        method_usage.roll_window(now);
+        if let Some(limit) = limit {
+            if method_usage.num_bytes >= limit {
+                return Err(LimitExceeded::BytesPerMinute {
+                    limit,
+                    retry_after: method_usage.retry_after(now),
+                });
+            }
+        }
+        method_usage.num_bytes = method_usage.num_bytes.saturating_add(num_bytes as u64);
+        Ok(())
+    }
+
+    fn release(&self, key: &UsageKey) {
+        let mut usage = self.usage.lock().expect("RpcRequestLimiter lock poisoned");
+        if let Some(method_usage) = usage.get_mut(key) {
+            method_usage.num_active = method_usage.num_active.saturating_sub(1);
+        }
+    }
+}
+
+/// Held for the duration of a request. The request is no longer counted as in progress once this is dropped.
+#[derive(Debug)]
+pub(super) struct RequestPermit {
+    limiter: RpcRequestLimiter,
+    key: UsageKey,
+    max_bytes_per_minute: Option<u64>,
+}
+
+impl RequestPermit {
+    /// Records response bytes about to be sent to the peer. An error is returned if the peer had already reached its
+    /// streamed bytes limit, in which case the bytes should not be sent.
+    pub fn record_bytes(&self, num_bytes: usize) -> Result<(), LimitExceeded> {
+        self.limiter
+            .record_bytes(&self.key, num_bytes, self.max_bytes_per_minute)
+    }
+}
+
+impl Drop for RequestPermit {
+    fn drop(&mut self) {
+        self.limiter.release(&self.key);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{peer_manager::PeerFeatures, test_utils::node_identity::build_node_identity};
+
+    fn node_id() -> NodeId {
+        build_node_identity(PeerFeatures::COMMUNICATION_NODE).node_id().clone()
+    }
+
+    #[test]
+    fn it_limits_requests_per_peer_and_method() {
+        let protocol = ProtocolId::from_static(b"/test/1");
+        let limiter = RpcRequestLimiter::new(
+            RpcMethodLimits {
+                max_requests_per_minute: Some(2),
+                ..Default::default()
+            },
+            HashMap::new(),
+        );
+        let peer1 = node_id();
+        let peer2 = node_id();
+
+        let _permit = limiter.acquire(&peer1, &protocol, RpcMethod::from(1)).unwrap();
+        let _permit = limiter.acquire(&peer1, &protocol, RpcMethod::from(1)).unwrap();
+        let err = limiter.acquire(&peer1, &protocol, RpcMethod::from(1)).unwrap_err();
+        assert!(matches!(err, LimitExceeded::RequestsPerMinute { limit: 2, .. }));
+
+        // Other methods and other peers are counted separately
+        let _permit = limiter.acquire(&peer1, &protocol, RpcMethod::from(2)).unwrap();
+        let _permit = limiter.acquire(&peer2, &protocol, RpcMethod::from(1)).unwrap();
+    }
+
+    #[test]
+    fn it_limits_concurrent_requests() {
+        let protocol = ProtocolId::from_static(b"/test/1");
+        let mut method_limits = HashMap::new();
+        method_limits.insert((protocol.clone(), 1), RpcMethodLimits {
+            max_concurrent_requests: Some(1),
+            ..Default::default()
+        });
+        let limiter = RpcRequestLimiter::new(RpcMethodLimits::unlimited(), method_limits);
+        let peer = node_id();
+
+        let permit = limiter.acquire(&peer, &protocol, RpcMethod::from(1)).unwrap();
+        let err = limiter.acquire(&peer, &protocol, RpcMethod::from(1)).unwrap_err();
+        assert_eq!(err, LimitExceeded::ConcurrentRequests { limit: 1 });
+        drop(permit);
+        let _permit = limiter.acquire(&peer, &protocol, RpcMethod::from(1)).unwrap();
+    }
+
+    #[test]
+    fn it_limits_streamed_bytes() {
+        let protocol = ProtocolId::from_static(b"/test/1");
+        let limiter = RpcRequestLimiter::new(
+            RpcMethodLimits {
+                max_bytes_per_minute: Some(100),
+                ..Default::default()
+            },
+            HashMap::new(),
+        );
+        let peer = node_id();
+
+        let permit = limiter.acquire(&peer, &protocol, RpcMethod::from(1)).unwrap();
+        permit.record_bytes(60).unwrap();
+        permit.record_bytes(60).unwrap();
+        let err = permit.record_bytes(1).unwrap_err();
+        assert!(matches!(err, LimitExceeded::BytesPerMinute { limit: 100, .. }));
+        drop(permit);
+
+        let err = limiter.acquire(&peer, &protocol, RpcMethod::from(1)).unwrap_err();
+        assert!(matches!(err, LimitExceeded::BytesPerMinute { limit: 100, .. }));
+    }
+
+    #[test]
+    fn it_prunes_idle_usage() {
+        let protocol = ProtocolId::from_static(b"/test/1");
+        let limiter = RpcRequestLimiter::new(RpcMethodLimits::unlimited(), HashMap::new());
+        let peer = node_id();
+
+        let permit = limiter.acquire(&peer, &protocol, RpcMethod::from(1)).unwrap();
+        limiter.prune();
+        assert_eq!(limiter.num_tracked(), 1);
+        drop(permit);
+        // The window has not expired yet
+        limiter.prune();
+        assert_eq!(limiter.num_tracked(), 1);
+    }
+}
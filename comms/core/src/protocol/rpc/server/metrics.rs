@@ -117,3 +117,32 @@ pub fn outbound_response_bytes(node_id: &NodeId, protocol: &ProtocolId) -> Histo
 
     METER.with_label_values(&[node_id.to_string().as_str(), String::from_utf8_lossy(protocol).as_ref()])
 }
+
+pub fn limit_exceeded_counter(node_id: &NodeId, protocol: &ProtocolId, limit: &str) -> IntCounter {
+    static METER: Lazy<IntCounterVec> = Lazy::new(|| {
+        tari_metrics::register_int_counter_vec(
+            "comms::rpc::server::limit_exceeded_count",
+            "The number of requests rejected or streams terminated because a peer exceeded a method limit",
+            &["peer_id", "protocol", "limit"],
+        )
+        .unwrap()
+    });
+
+    METER.with_label_values(&[
+        node_id.to_string().as_str(),
+        String::from_utf8_lossy(protocol).as_ref(),
+        limit,
+    ])
+}
+
+pub fn num_queued_streams() -> IntGauge {
+    static METER: Lazy<IntGauge> = Lazy::new(|| {
+        tari_metrics::register_int_gauge(
+            "comms::rpc::server::num_queued_streams",
+            "The number of response streams waiting for a streaming slot",
+        )
+        .unwrap()
+    });
+
+    METER.clone()
+}
@@ -36,6 +36,13 @@ mod metrics;
 pub mod mock;
 
 mod early_close;
+mod fair_queue;
+use fair_queue::FairQueue;
+
+mod limits;
+pub use limits::RpcMethodLimits;
+use limits::{RequestPermit, RpcRequestLimiter};
+
 mod router;
 
 use std::{
@@ -179,6 +186,9 @@ pub struct RpcServerBuilder {
     maximum_sessions_per_client: Option<usize>,
     minimum_client_deadline: Duration,
     handshake_timeout: Duration,
+    default_method_limits: RpcMethodLimits,
+    method_limits: HashMap<(ProtocolId, u32), RpcMethodLimits>,
+    maximum_concurrent_streams: Option<usize>,
 }
 
 impl RpcServerBuilder {
@@ -211,6 +221,37 @@ impl RpcServerBuilder {
         self
     }
 
+    /// Sets the per-peer limits that apply to every method that does not have limits set with
+    /// [with_method_limits](Self::with_method_limits).
+    pub fn with_default_method_limits(mut self, limits: RpcMethodLimits) -> Self {
+        self.default_method_limits = limits;
+        self
+    }
+
+    /// Sets the per-peer limits for a single method of a protocol, overriding the default method limits
+    pub fn with_method_limits<P: Into<ProtocolId>>(
+        mut self,
+        protocol: P,
+        method: u32,
+        limits: RpcMethodLimits,
+    ) -> Self {
+        self.method_limits.insert((protocol.into(), method), limits);
+        self
+    }
+
+    /// Sets the maximum number of streaming responses that are sent at once across all peers. Streams over this limit
+    /// wait for a slot, which is given to waiting peers in turn. A stream that cannot start within the client deadline
+    /// is rejected with a `Busy` status.
+    pub fn with_maximum_concurrent_streams(mut self, limit: usize) -> Self {
+        self.maximum_concurrent_streams = Some(limit);
+        self
+    }
+
+    pub fn with_unlimited_concurrent_streams(mut self) -> Self {
+        self.maximum_concurrent_streams = None;
+        self
+    }
+
     pub fn finish(self) -> RpcServer {
         let (request_tx, request_rx) = mpsc::channel(10);
         RpcServer {
@@ -228,6 +269,9 @@ impl Default for RpcServerBuilder {
             maximum_sessions_per_client: None,
             minimum_client_deadline: Duration::from_secs(1),
             handshake_timeout: Duration::from_secs(15),
+            default_method_limits: RpcMethodLimits::unlimited(),
+            method_limits: HashMap::new(),
+            maximum_concurrent_streams: None,
         }
     }
 }
@@ -241,6 +285,8 @@ pub(super) struct PeerRpcServer<TSvc, TCommsProvider> {
     request_rx: mpsc::Receiver<RpcServerRequest>,
     sessions: HashMap<NodeId, usize>,
     tasks: FuturesUnordered<JoinHandle<NodeId>>,
+    limiter: RpcRequestLimiter,
+    stream_queue: Option<FairQueue>,
 }
 
 impl<TSvc, TCommsProvider> PeerRpcServer<TSvc, TCommsProvider>
@@ -271,6 +317,8 @@ where
                 Some(num) => BoundedExecutor::new(num),
                 None => BoundedExecutor::allow_maximum(),
             },
+            limiter: RpcRequestLimiter::new(config.default_method_limits, config.method_limits.clone()),
+            stream_queue: config.maximum_concurrent_streams.map(FairQueue::new),
             config,
             service,
             protocol_notifications: Some(protocol_notifications),
@@ -392,6 +440,7 @@ where
                 self.sessions.remove(node_id);
             }
         }
+        self.limiter.prune();
     }
 
     async fn try_initiate_service(
@@ -456,10 +505,13 @@ where
         let service = ActivePeerRpcService::new(
             self.config.clone(),
             protocol,
+            version,
             node_id.clone(),
             service,
             framed,
             self.comms_provider.clone(),
+            self.limiter.clone(),
+            self.stream_queue.clone(),
         );
 
         let node_id = node_id.clone();
@@ -488,11 +540,15 @@ where
 struct ActivePeerRpcService<TSvc, TCommsProvider> {
     config: RpcServerBuilder,
     protocol: ProtocolId,
+    /// The RPC protocol version negotiated with the client, which determines the status codes it understands
+    protocol_version: u32,
     node_id: NodeId,
     service: TSvc,
     framed: EarlyClose<CanonicalFraming<Substream>>,
     comms_provider: TCommsProvider,
     logging_context_string: Arc<String>,
+    limiter: RpcRequestLimiter,
+    stream_queue: Option<FairQueue>,
 }
 
 impl<TSvc, TCommsProvider> ActivePeerRpcService<TSvc, TCommsProvider>
@@ -503,10 +559,13 @@ where
     pub(self) fn new(
         config: RpcServerBuilder,
         protocol: ProtocolId,
+        protocol_version: u32,
         node_id: NodeId,
         service: TSvc,
         framed: CanonicalFraming<Substream>,
         comms_provider: TCommsProvider,
+        limiter: RpcRequestLimiter,
+        stream_queue: Option<FairQueue>,
     ) -> Self {
         Self {
             logging_context_string: Arc::new(format!(
@@ -518,10 +577,13 @@ where
 
             config,
             protocol,
+            protocol_version,
             node_id,
             service,
             framed: EarlyClose::new(framed),
             comms_provider,
+            limiter,
+            stream_queue,
         }
     }
 
@@ -671,6 +733,24 @@ where
             method.id()
         );
 
+        let permit = match self.limiter.acquire(&self.node_id, &self.protocol, method) {
+            Ok(permit) => permit,
+            Err(err) => {
+                debug!(
+                    target: LOG_TARGET,
+                    "({}) Rejecting request {} for method {}: {}",
+                    self.logging_context_string,
+                    request_id,
+                    method.id(),
+                    err
+                );
+                #[cfg(feature = "metrics")]
+                metrics::limit_exceeded_counter(&self.node_id, &self.protocol, err.as_label()).inc();
+                self.send_status(request_id, RpcStatus::rate_limited(&err)).await?;
+                return Ok(());
+            },
+        };
+
         let req = Request::with_context(
             self.create_request_context(request_id),
             method,
@@ -707,13 +787,14 @@ where
 
         match service_result {
             Ok(body) => {
-                self.process_body(request_id, deadline, body).await?;
+                self.process_body(request_id, deadline, body, &permit).await?;
             },
             Err(err) => {
                 debug!(
                     target: LOG_TARGET,
                     "{} Service returned an error: {}", self.logging_context_string, err
                 );
+                let err = err.for_protocol_version(self.protocol_version);
                 let resp = proto::rpc::RpcResponse {
                     request_id,
                     status: err.as_code(),
@@ -734,21 +815,84 @@ where
         String::from_utf8_lossy(&self.protocol)
     }
 
+    async fn send_status(&mut self, request_id: u32, status: RpcStatus) -> Result<(), RpcServerError> {
+        let status = status.for_protocol_version(self.protocol_version);
+        let resp = proto::rpc::RpcResponse {
+            request_id,
+            status: status.as_code(),
+            flags: RpcMessageFlags::FIN.bits().into(),
+            payload: status.to_details_bytes(),
+        };
+        #[cfg(feature = "metrics")]
+        metrics::status_error_counter(&self.node_id, &self.protocol, status.as_status_code()).inc();
+        self.framed.send(resp.to_encoded_bytes().into()).await?;
+        Ok(())
+    }
+
     async fn process_body(
         &mut self,
         request_id: u32,
         deadline: Duration,
         body: Response<Body>,
+        permit: &RequestPermit,
     ) -> Result<(), RpcServerError> {
         trace!(target: LOG_TARGET, "Service call succeeded");
 
+        // Streaming responses wait for a slot so that the server's streaming capacity is shared fairly between peers
+        let _stream_permit = match self.stream_queue.clone() {
+            Some(queue) if body.payload.is_streaming() => {
+                match time::timeout(deadline, queue.acquire(&self.node_id)).await {
+                    Ok(stream_permit) => Some(stream_permit),
+                    Err(_) => {
+                        debug!(
+                            target: LOG_TARGET,
+                            "({}) No stream slot became available within the client deadline ({:.0?})",
+                            self.logging_context_string,
+                            deadline
+                        );
+                        self.send_status(request_id, RpcStatus::busy(&"Server is at streaming capacity"))
+                            .await?;
+                        return Ok(());
+                    },
+                }
+            },
+            Some(_) | None => None,
+        };
+
         #[cfg(feature = "metrics")]
         let node_id = self.node_id.clone();
         #[cfg(feature = "metrics")]
         let protocol = self.protocol.clone();
+        #[cfg(feature = "metrics")]
+        let (limit_node_id, limit_protocol) = (self.node_id.clone(), self.protocol.clone());
+        let logging_context_string = self.logging_context_string.clone();
+        let protocol_version = self.protocol_version;
         let mut stream = body
             .into_message()
-            .map(|result| into_response(request_id, result))
+            .map(move |result| into_response(request_id, protocol_version, result))
+            // Terminate the stream with a RateLimited status once the peer has reached its streamed bytes limit
+            .scan(false, move |is_limit_reached, message| {
+                if *is_limit_reached {
+                    return future::ready(None);
+                }
+                match permit.record_bytes(message.payload.len()) {
+                    Ok(()) => future::ready(Some(message)),
+                    Err(err) => {
+                        debug!(
+                            target: LOG_TARGET,
+                            "({}) Terminating response stream: {}", logging_context_string, err
+                        );
+                        #[cfg(feature = "metrics")]
+                        metrics::limit_exceeded_counter(&limit_node_id, &limit_protocol, err.as_label()).inc();
+                        *is_limit_reached = true;
+                        future::ready(Some(into_response(
+                            request_id,
+                            protocol_version,
+                            Err(RpcStatus::rate_limited(&err)),
+                        )))
+                    },
+                }
+            })
             .flat_map(move |message| {
                 #[cfg(feature = "metrics")]
                 if !message.status.is_ok() {
@@ -895,7 +1039,7 @@ async fn log_timing<R, F: Future<Output = R>>(context_str: Arc<String>, request_
     ret
 }
 
-fn into_response(request_id: u32, result: Result<BodyBytes, RpcStatus>) -> RpcResponse {
+fn into_response(request_id: u32, protocol_version: u32, result: Result<BodyBytes, RpcStatus>) -> RpcResponse {
     match result {
         Ok(msg) => {
             let mut flags = RpcMessageFlags::empty();
@@ -911,6 +1055,7 @@ fn into_response(request_id: u32, result: Result<BodyBytes, RpcStatus>) -> RpcRe
         },
         Err(err) => {
             debug!(target: LOG_TARGET, "Body contained an error: {}", err);
+            let err = err.for_protocol_version(protocol_version);
             RpcResponse {
                 request_id,
                 status: err.as_status_code(),
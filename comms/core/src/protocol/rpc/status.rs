@@ -106,6 +106,23 @@ impl RpcStatus {
         }
     }
 
+    /// Returns a status indicating that the client has exceeded a limit and should back off before retrying
+    pub fn rate_limited<T: ToString + ?Sized>(details: &T) -> Self {
+        Self {
+            code: RpcStatusCode::RateLimited,
+            details: details.to_string(),
+        }
+    }
+
+    /// Returns a status indicating that the server does not have capacity to serve the request and the client should
+    /// back off before retrying
+    pub fn busy<T: ToString + ?Sized>(details: &T) -> Self {
+        Self {
+            code: RpcStatusCode::Busy,
+            details: details.to_string(),
+        }
+    }
+
     /// Returns a closure that logs the given error and returns a generic general error that does not leak any
     /// potentially sensitive error information. Use this function with map_err to catch "miscellaneous" errors.
     pub fn log_internal_error<'a, E: std::error::Error + 'a>(target: &'a str) -> impl Fn(E) -> Self + 'a {
@@ -145,6 +162,24 @@ impl RpcStatus {
     pub fn is_not_found(&self) -> bool {
         self.code.is_not_found()
     }
+
+    /// Returns true if the server asked the client to back off before retrying
+    pub fn is_back_off(&self) -> bool {
+        self.code.is_back_off()
+    }
+
+    /// The status as it is sent to a client that negotiated the given RPC protocol version. Clients before
+    /// `BACK_OFF_STATUS_MIN_RPC_VERSION` decode the `RateLimited` and `Busy` codes as `InvalidRpcStatusCode`, so they
+    /// receive a `General` status with the same details instead.
+    pub(super) fn for_protocol_version(self, version: u32) -> Self {
+        if version < BACK_OFF_STATUS_MIN_RPC_VERSION && self.code.is_back_off() {
+            return Self {
+                code: RpcStatusCode::General,
+                details: self.details,
+            };
+        }
+        self
+    }
 }
 
 impl Display for RpcStatus {
@@ -215,6 +250,9 @@ impl<T> OrOptional<T> for Result<T, RpcStatus> {
     }
 }
 
+/// The first RPC protocol version that supports the `RateLimited` and `Busy` status codes
+pub(super) const BACK_OFF_STATUS_MIN_RPC_VERSION: u32 = 1;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum RpcStatusCode {
     /// Request succeeded
@@ -239,6 +277,11 @@ pub enum RpcStatusCode {
     Forbidden = 9,
     /// RPC conflict error
     Conflict = 10,
+    /// The client exceeded a request, concurrency or bandwidth limit and should back off. Only sent to clients that
+    /// negotiated RPC v1 or later.
+    RateLimited = 11,
+    /// The server is at capacity and the client should back off. Only sent to clients that negotiated RPC v1 or later.
+    Busy = 12,
     // The following status represents anything that is not recognised (i.e not one of the above codes).
     /// Unrecognised RPC status code
     InvalidRpcStatusCode,
@@ -257,6 +300,10 @@ impl RpcStatusCode {
         self == Self::Timeout
     }
 
+    pub fn is_back_off(self) -> bool {
+        matches!(self, Self::RateLimited | Self::Busy)
+    }
+
     pub fn as_u32(&self) -> u32 {
         *self as u32
     }
@@ -282,6 +329,8 @@ impl From<u32> for RpcStatusCode {
             8 => ProtocolError,
             9 => Forbidden,
             10 => Conflict,
+            11 => RateLimited,
+            12 => Busy,
             _ => InvalidRpcStatusCode,
         }
     }
@@ -307,9 +356,28 @@ mod test {
         assert_eq!(RpcStatusCode::from(ProtocolError as u32), ProtocolError);
         assert_eq!(RpcStatusCode::from(Forbidden as u32), Forbidden);
         assert_eq!(RpcStatusCode::from(Conflict as u32), Conflict);
+        assert_eq!(RpcStatusCode::from(RateLimited as u32), RateLimited);
+        assert_eq!(RpcStatusCode::from(Busy as u32), Busy);
         assert_eq!(RpcStatusCode::from(123), InvalidRpcStatusCode);
     }
 
+    #[test]
+    fn back_off_statuses_are_general_errors_for_v0_clients() {
+        let status = RpcStatus::rate_limited("Too many requests");
+        assert_eq!(status.clone().for_protocol_version(1), status);
+        let status = status.for_protocol_version(0);
+        assert_eq!(status.as_status_code(), RpcStatusCode::General);
+        assert_eq!(status.details(), "Too many requests");
+        assert_eq!(
+            RpcStatus::busy("At capacity").for_protocol_version(0).as_status_code(),
+            RpcStatusCode::General
+        );
+        assert_eq!(
+            RpcStatus::not_found("foo").for_protocol_version(0),
+            RpcStatus::not_found("foo")
+        );
+    }
+
     #[test]
     fn rpc_status_or_optional() {
         assert!(Result::<(), RpcStatus>::Ok(()).or_optional().is_ok());
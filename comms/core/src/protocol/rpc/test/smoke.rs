@@ -54,6 +54,7 @@ use crate::{
                 mock::create_mocked_rpc_context,
             },
             RpcError,
+            RpcMethodLimits,
             RpcServer,
             RpcServerBuilder,
             RpcStatusCode,
@@ -583,3 +584,51 @@ async fn max_per_client_sessions() {
         .await
         .unwrap();
 }
+
+#[tokio::test]
+async fn per_peer_method_request_limit() {
+    let builder = RpcServer::builder()
+        .with_minimum_client_deadline(Duration::from_secs(0))
+        .with_method_limits(ProtocolId::from_static(b"/test/greeting/1.0"), 1, RpcMethodLimits {
+            max_requests_per_minute: Some(2),
+            ..Default::default()
+        });
+    let (muxer, _outbound, context, _shutdown) = setup_service_with_builder(GreetingService::default(), builder).await;
+    let (_, mut inbound, outbound) = build_multiplexed_connections().await;
+
+    let node_identity = build_node_identity(Default::default());
+    context.peer_manager().add_peer(node_identity.to_peer()).await.unwrap();
+    let substream = outbound.get_yamux_control().open_stream().await.unwrap();
+    muxer
+        .send(ProtocolNotification::new(
+            ProtocolId::from_static(b"/test/greeting/1.0"),
+            ProtocolEvent::NewInboundSubstream(node_identity.node_id().clone(), substream),
+        ))
+        .await
+        .unwrap();
+
+    let socket = inbound.incoming_mut().next().await.unwrap();
+    let framed = framing::canonical(socket, 1024);
+    let mut client = GreetingClient::builder()
+        .with_deadline(Duration::from_secs(5))
+        .connect(framed)
+        .await
+        .unwrap();
+
+    let request = || SayHelloRequest {
+        name: "Alice".to_string(),
+        language: 0,
+    };
+    for _ in 0..2 {
+        client.say_hello(request()).await.unwrap();
+    }
+    let err = client.say_hello(request()).await.unwrap_err();
+    unpack_enum!(RpcError::RequestFailed(status) = err);
+    assert_eq!(status.as_status_code(), RpcStatusCode::RateLimited);
+    assert!(status.is_back_off());
+
+    // Other methods are not limited and the session remains usable
+    let greetings = client.get_greetings(1).await.unwrap();
+    let greetings = greetings.map(|r| r.unwrap()).collect::<Vec<_>>().await;
+    assert_eq!(greetings, ["Sawubona"]);
+}
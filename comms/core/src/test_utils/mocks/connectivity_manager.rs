@@ -285,6 +285,17 @@ impl ConnectivityManagerMock {
                     })
                     .await
             },
+            ReportPeer(_, _) => {},
+            GetPeerReputation(_, reply) => {
+                let _result = reply.send(None);
+            },
+            GetAllPeerReputations(reply) => {
+                let _result = reply.send(vec![]);
+            },
+            SetStaticPeerGroup(_, _) => {},
+            GetStaticPeerGroups(reply) => {
+                let _result = reply.send(HashMap::new());
+            },
             AddPeerToAllowList(_) => {},
             RemovePeerFromAllowList(_) => {},
             GetActiveConnections(reply) => {
@@ -94,6 +94,7 @@ where
         peer_manager,
         event_tx,
         shutdown,
+        Default::default(),
     );
     connection_manager.add_protocols(protocols);
 
@@ -35,7 +35,7 @@ use super::{
     commands::{AddOnionFlag, AddOnionResponse, TorCommand},
     error::TorClientError,
     response::ResponseLine,
-    types::{KeyBlob, KeyType, PortMapping},
+    types::{ClientAuthPublicKey, ClientAuthSecretKey, KeyBlob, KeyType, PortMapping},
     PrivateKey,
     LOG_TARGET,
 };
@@ -187,6 +187,48 @@ impl TorControlPortClient {
             .await
     }
 
+    /// The ADD_ONION command for a restricted onion service. Only clients holding the secret key for one of the
+    /// `authorized_clients` are able to connect to the service. If `private_key` is None, tor will generate a new key.
+    pub async fn add_onion_with_client_auth<P: Into<PortMapping>>(
+        &mut self,
+        private_key: Option<&PrivateKey>,
+        mut flags: Vec<AddOnionFlag>,
+        port: P,
+        num_streams: Option<NonZeroU16>,
+        authorized_clients: &[ClientAuthPublicKey],
+    ) -> Result<AddOnionResponse, TorClientError> {
+        let (key_type, key_blob) = match private_key {
+            Some(PrivateKey::Rsa1024(key)) => (KeyType::Rsa1024, KeyBlob::String(key)),
+            Some(PrivateKey::Ed25519V3(key)) => (KeyType::Ed25519V3, KeyBlob::String(key)),
+            None => (KeyType::New, KeyBlob::Ed25519V3),
+        };
+        if !authorized_clients.is_empty() && !flags.iter().any(|f| matches!(f, AddOnionFlag::V3Auth)) {
+            flags.push(AddOnionFlag::V3Auth);
+        }
+        let command = commands::AddOnion::new(key_type, key_blob, flags, port.into(), num_streams)
+            .with_client_auth_v3(authorized_clients);
+        self.request_response(command).await
+    }
+
+    /// The ONION_CLIENT_AUTH_ADD command. Registers the client authorization secret key for a restricted onion service
+    /// so that this tor proxy is able to connect to it. If `is_permanent` is true, tor persists the credentials.
+    pub async fn onion_client_auth_add(
+        &mut self,
+        service_id: &str,
+        secret_key: &ClientAuthSecretKey,
+        is_permanent: bool,
+    ) -> Result<(), TorClientError> {
+        let command = commands::OnionClientAuthAdd::new(service_id, secret_key);
+        let command = if is_permanent { command.permanent() } else { command };
+        self.request_response(command).await
+    }
+
+    /// The ONION_CLIENT_AUTH_REMOVE command.
+    pub async fn onion_client_auth_remove(&mut self, service_id: &str) -> Result<(), TorClientError> {
+        let command = commands::OnionClientAuthRemove::new(service_id);
+        self.request_response(command).await
+    }
+
     /// The DEL_ONION command.
     pub async fn del_onion(&mut self, service_id: &str) -> Result<(), TorClientError> {
         let command = commands::DelOnion::new(service_id);
@@ -279,7 +321,11 @@ mod test {
     use tokio_stream::StreamExt;
 
     use super::*;
-    use crate::tor::control_client::{test_server, test_server::canned_responses, types::PrivateKey};
+    use crate::tor::control_client::{
+        test_server,
+        test_server::canned_responses,
+        types::{ClientAuthKeypair, PrivateKey},
+    };
 
     async fn setup_test() -> (TorControlPortClient, test_server::State) {
         let (_, mock_state, socket) = test_server::spawn().await;
@@ -497,6 +543,85 @@ mod test {
         unpack_enum!(TorClientError::TorCommandFailed(_s) = err);
     }
 
+    #[tokio::test]
+    async fn add_onion_with_client_auth_ok() {
+        let (mut tor, mock_state) = setup_test().await;
+
+        mock_state.set_canned_response(canned_responses::ADD_ONION_OK).await;
+
+        let client = ClientAuthKeypair::random().unwrap();
+        let response = tor
+            .add_onion_with_client_auth(None, vec![AddOnionFlag::Detach], 8080, None, &[client
+                .public_key
+                .clone()])
+            .await
+            .unwrap();
+
+        assert_eq!(
+            response.service_id,
+            "qigbgbs4ue3ghbupsotgh73cmmkjrin2aprlyxsrnrvpmcmzy3g4wbid"
+        );
+        assert!(response.private_key.is_some());
+
+        let request = mock_state.take_requests().await.pop().unwrap();
+        assert_eq!(
+            request,
+            format!(
+                "ADD_ONION NEW:ED25519-V3 Flags=Detach,V3Auth Port=8080,127.0.0.1:8080 ClientAuthV3={}",
+                client.public_key.to_base32()
+            )
+        );
+    }
+
+    #[tokio::test]
+    async fn onion_client_auth_add_ok() {
+        let (mut tor, mock_state) = setup_test().await;
+
+        let client = ClientAuthKeypair::random().unwrap();
+        tor.onion_client_auth_add("some-fake-id", &client.secret_key, false)
+            .await
+            .unwrap();
+        let request = mock_state.take_requests().await.pop().unwrap();
+        assert_eq!(
+            request,
+            format!(
+                "ONION_CLIENT_AUTH_ADD some-fake-id x25519:{}",
+                client.secret_key.to_base64()
+            )
+        );
+
+        // Replacing existing credentials is not an error
+        mock_state
+            .set_canned_response(canned_responses::ONION_CLIENT_AUTH_ADD_REPLACED)
+            .await;
+        tor.onion_client_auth_add("some-fake-id", &client.secret_key, true)
+            .await
+            .unwrap();
+        let request = mock_state.take_requests().await.pop().unwrap();
+        assert!(request.ends_with(" Flags=Permanent"));
+
+        mock_state.set_canned_response(canned_responses::ERR_552).await;
+        let err = tor
+            .onion_client_auth_add("some-fake-id", &client.secret_key, false)
+            .await
+            .unwrap_err();
+        unpack_enum!(TorClientError::TorCommandFailed(_s) = err);
+    }
+
+    #[tokio::test]
+    async fn onion_client_auth_remove_ok() {
+        let (mut tor, mock_state) = setup_test().await;
+
+        mock_state
+            .set_canned_response(canned_responses::ONION_CLIENT_AUTH_REMOVE_NO_CREDENTIALS)
+            .await;
+
+        tor.onion_client_auth_remove("some-fake-id").await.unwrap();
+
+        let request = mock_state.take_requests().await.pop().unwrap();
+        assert_eq!(request, "ONION_CLIENT_AUTH_REMOVE some-fake-id");
+    }
+
     #[tokio::test]
     async fn del_onion_ok() {
         let (mut tor, mock_state) = setup_test().await;
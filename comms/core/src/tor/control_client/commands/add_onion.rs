@@ -30,7 +30,7 @@ use crate::tor::control_client::{
     parsers,
     parsers::ParseError,
     response::ResponseLine,
-    types::{KeyBlob, KeyType, PortMapping, PrivateKey},
+    types::{ClientAuthPublicKey, KeyBlob, KeyType, PortMapping, PrivateKey},
 };
 
 #[derive(Debug, Copy, Clone)]
@@ -41,6 +41,9 @@ pub enum AddOnionFlag {
     Detach,
     /// Client authorization is required using the "basic" method (v2 only).
     BasicAuth,
+    /// Client authorization is required using the "v3" method. Only clients with a key given in `ClientAuthV3` can
+    /// connect (v3 only).
+    V3Auth,
     /// Add a non-anonymous Single Onion Service. Tor checks this flag matches its configured hidden service anonymity
     /// mode.
     NonAnonymous,
@@ -50,11 +53,12 @@ pub enum AddOnionFlag {
 
 impl fmt::Display for AddOnionFlag {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        use AddOnionFlag::{BasicAuth, Detach, DiscardPK, MaxStreamsCloseCircuit, NonAnonymous};
+        use AddOnionFlag::{BasicAuth, Detach, DiscardPK, MaxStreamsCloseCircuit, NonAnonymous, V3Auth};
         match self {
             DiscardPK => write!(f, "DiscardPK"),
             Detach => write!(f, "Detach"),
             BasicAuth => write!(f, "BasicAuth"),
+            V3Auth => write!(f, "V3Auth"),
             NonAnonymous => write!(f, "NonAnonymous"),
             MaxStreamsCloseCircuit => write!(f, "MaxStreamsCloseCircuit"),
         }
@@ -70,6 +74,7 @@ pub struct AddOnion<'a> {
     flags: Vec<AddOnionFlag>,
     port_mapping: PortMapping,
     num_streams: Option<NonZeroU16>,
+    client_auth_v3: &'a [ClientAuthPublicKey],
 }
 
 impl<'a> AddOnion<'a> {
@@ -86,8 +91,16 @@ impl<'a> AddOnion<'a> {
             flags,
             port_mapping,
             num_streams,
+            client_auth_v3: &[],
         }
     }
+
+    /// Restricts the onion service to the clients with the given x25519 public keys. The `V3Auth` flag should be set
+    /// when using this option.
+    pub fn with_client_auth_v3(mut self, client_auth_v3: &'a [ClientAuthPublicKey]) -> Self {
+        self.client_auth_v3 = client_auth_v3;
+        self
+    }
 }
 
 impl TorCommand for AddOnion<'_> {
@@ -116,6 +129,10 @@ impl TorCommand for AddOnion<'_> {
             self.port_mapping.proxied_address()
         ));
 
+        for public_key in self.client_auth_v3 {
+            s.push_str(&format!(" ClientAuthV3={}", public_key.to_base32()));
+        }
+
         Ok(s)
     }
 
@@ -179,13 +196,14 @@ impl fmt::Display for AddOnion<'_> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
             f,
-            "ADD_ONION (KeyType={} KeyBlob={} Flags={} PortMapping={})",
+            "ADD_ONION (KeyType={} KeyBlob={} Flags={} PortMapping={} NumAuthorizedClients={})",
             self.key_type.as_tor_repr(),
             self.key_blob,
             self.flags
                 .iter()
                 .fold(String::new(), |acc, f| format!("{}, {}", acc, f)),
-            self.port_mapping
+            self.port_mapping,
+            self.client_auth_v3.len()
         )
     }
 }
@@ -218,4 +236,28 @@ mod test {
             format!("ADD_ONION NEW:{} Port=9090,127.0.0.1:9090", key)
         );
     }
+
+    #[test]
+    fn to_command_string_with_client_auth() {
+        let clients = vec![
+            ClientAuthPublicKey::from_bytes(&[1u8; 32]).unwrap(),
+            ClientAuthPublicKey::from_bytes(&[2u8; 32]).unwrap(),
+        ];
+        let command = AddOnion::new(
+            KeyType::New,
+            KeyBlob::Ed25519V3,
+            vec![AddOnionFlag::V3Auth],
+            PortMapping::from_port(9090),
+            None,
+        )
+        .with_client_auth_v3(&clients);
+        assert_eq!(
+            command.to_command_string().unwrap(),
+            format!(
+                "ADD_ONION NEW:ED25519-V3 Flags=V3Auth Port=9090,127.0.0.1:9090 ClientAuthV3={} ClientAuthV3={}",
+                clients[0].to_base32(),
+                clients[1].to_base32()
+            )
+        );
+    }
 }
@@ -0,0 +1,186 @@
+// Copyright 2024. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::fmt;
+
+use crate::tor::control_client::{
+    commands::TorCommand,
+    error::TorClientError,
+    response::ResponseLine,
+    types::ClientAuthSecretKey,
+};
+
+/// Returned by ONION_CLIENT_AUTH_ADD when credentials for the onion service already existed and were replaced, and by
+/// ONION_CLIENT_AUTH_REMOVE when there were no credentials to remove.
+const CLIENT_AUTH_NOOP_CODE: u16 = 251;
+/// Returned by ONION_CLIENT_AUTH_ADD when the credentials were registered but could not be persisted to disk.
+const CLIENT_AUTH_NOT_PERMANENT_CODE: u16 = 252;
+
+/// The ONION_CLIENT_AUTH_ADD command.
+///
+/// This command registers v3 client authorization credentials with tor, allowing connections to a restricted onion
+/// service.
+pub struct OnionClientAuthAdd<'a> {
+    service_id: &'a str,
+    secret_key: &'a ClientAuthSecretKey,
+    client_name: Option<&'a str>,
+    is_permanent: bool,
+}
+
+impl<'a> OnionClientAuthAdd<'a> {
+    pub fn new(service_id: &'a str, secret_key: &'a ClientAuthSecretKey) -> Self {
+        Self {
+            service_id,
+            secret_key,
+            client_name: None,
+            is_permanent: false,
+        }
+    }
+
+    /// An optional nickname for the client
+    pub fn with_client_name(mut self, client_name: &'a str) -> Self {
+        self.client_name = Some(client_name);
+        self
+    }
+
+    /// Instructs tor to persist the credentials to `ClientOnionAuthDir` so that they survive a restart
+    pub fn permanent(mut self) -> Self {
+        self.is_permanent = true;
+        self
+    }
+}
+
+impl TorCommand for OnionClientAuthAdd<'_> {
+    type Error = TorClientError;
+    type Output = ();
+
+    fn to_command_string(&self) -> Result<String, Self::Error> {
+        let mut s = format!(
+            "ONION_CLIENT_AUTH_ADD {} x25519:{}",
+            self.service_id,
+            self.secret_key.to_base64()
+        );
+
+        if let Some(client_name) = self.client_name {
+            s.push_str(&format!(" ClientName={}", client_name));
+        }
+
+        if self.is_permanent {
+            s.push_str(" Flags=Permanent");
+        }
+
+        Ok(s)
+    }
+
+    fn parse_responses(&self, mut responses: Vec<ResponseLine>) -> Result<Self::Output, Self::Error> {
+        let last_response = responses.pop().ok_or(TorClientError::UnexpectedEof)?;
+        match last_response.code {
+            CLIENT_AUTH_NOOP_CODE | CLIENT_AUTH_NOT_PERMANENT_CODE => Ok(()),
+            _ => match last_response.err() {
+                Some(err) => Err(TorClientError::TorCommandFailed(err.to_owned())),
+                None => Ok(()),
+            },
+        }
+    }
+}
+
+impl fmt::Display for OnionClientAuthAdd<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "ONION_CLIENT_AUTH_ADD (ServiceId = {}, ClientName = {}, Permanent = {})",
+            self.service_id,
+            self.client_name.unwrap_or("<none>"),
+            self.is_permanent
+        )
+    }
+}
+
+/// The ONION_CLIENT_AUTH_REMOVE command.
+///
+/// This command removes the v3 client authorization credentials for an onion service.
+pub struct OnionClientAuthRemove<'a> {
+    service_id: &'a str,
+}
+
+impl<'a> OnionClientAuthRemove<'a> {
+    pub fn new(service_id: &'a str) -> Self {
+        Self { service_id }
+    }
+}
+
+impl TorCommand for OnionClientAuthRemove<'_> {
+    type Error = TorClientError;
+    type Output = ();
+
+    fn to_command_string(&self) -> Result<String, Self::Error> {
+        Ok(format!("ONION_CLIENT_AUTH_REMOVE {}", self.service_id))
+    }
+
+    fn parse_responses(&self, mut responses: Vec<ResponseLine>) -> Result<Self::Output, Self::Error> {
+        let last_response = responses.pop().ok_or(TorClientError::UnexpectedEof)?;
+        if last_response.code == CLIENT_AUTH_NOOP_CODE {
+            return Ok(());
+        }
+        if let Some(err) = last_response.err() {
+            return Err(TorClientError::TorCommandFailed(err.to_owned()));
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for OnionClientAuthRemove<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ONION_CLIENT_AUTH_REMOVE (ServiceId = {})", self.service_id)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn to_command_string() {
+        let secret_key = ClientAuthSecretKey::from_bytes(&[0u8; 32]).unwrap();
+        let command = OnionClientAuthAdd::new("some-service-id", &secret_key);
+        assert_eq!(
+            command.to_command_string().unwrap(),
+            "ONION_CLIENT_AUTH_ADD some-service-id x25519:AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA="
+        );
+
+        let command = OnionClientAuthAdd::new("some-service-id", &secret_key)
+            .with_client_name("wallet")
+            .permanent();
+        assert_eq!(
+            command.to_command_string().unwrap(),
+            "ONION_CLIENT_AUTH_ADD some-service-id x25519:AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA= \
+             ClientName=wallet Flags=Permanent"
+        );
+
+        let command = OnionClientAuthRemove::new("some-service-id");
+        assert_eq!(
+            command.to_command_string().unwrap(),
+            "ONION_CLIENT_AUTH_REMOVE some-service-id"
+        );
+    }
+}
@@ -52,6 +52,8 @@ pub enum TorClientError {
     KeyValueNoValue,
     #[error("The command sender disconnected")]
     CommandSenderDisconnected,
+    #[error("The onion service client authorization key is invalid")]
+    InvalidClientAuthKey,
 }
 
 impl From<LinesCodecError> for TorClientError {
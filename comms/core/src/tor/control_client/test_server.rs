@@ -125,6 +125,9 @@ pub mod canned_responses {
         "250 OK",
     ];
 
+    pub const ONION_CLIENT_AUTH_ADD_REPLACED: &[&str] = &["251 Client for onion existed and replaced"];
+    pub const ONION_CLIENT_AUTH_REMOVE_NO_CREDENTIALS: &[&str] = &["251 No credentials for \"some-fake-id\""];
+
     pub const ERR_552: &[&str] = &["552 Unrecognised configuration key \"dummy\""];
     pub const PROTOCOL_INFO_NO_AUTH_OK: &[&str] = &[
         "250-PROTOCOLINFO 1",
@@ -22,9 +22,12 @@
 
 use std::{fmt, net::SocketAddr};
 
+use data_encoding::{BASE32_NOPAD, BASE64};
 use serde_derive::{Deserialize, Serialize};
 use zeroize::Zeroize;
 
+use crate::tor::control_client::error::TorClientError;
+
 #[derive(Clone, Copy, Debug)]
 pub enum KeyType {
     /// The server should generate a key of algorithm KeyBlob
@@ -88,6 +91,107 @@ pub enum PrivateKey {
     Ed25519V3(String),
 }
 
+/// The length in bytes of an x25519 key used for v3 onion service client authorization
+const CLIENT_AUTH_KEY_LEN: usize = 32;
+
+/// The x25519 public key of a client that is authorized to connect to a restricted (v3 client auth) onion service.
+/// Tor expects this key to be base32 encoded (ADD_ONION ClientAuthV3).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ClientAuthPublicKey([u8; CLIENT_AUTH_KEY_LEN]);
+
+impl ClientAuthPublicKey {
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, TorClientError> {
+        to_key_bytes(bytes).map(Self)
+    }
+
+    /// Parses a base32 encoded public key, as found in a tor `.auth` file (`descriptor:x25519:<base32>`)
+    pub fn from_base32(s: &str) -> Result<Self, TorClientError> {
+        let bytes = BASE32_NOPAD
+            .decode(s.trim_end_matches('=').to_uppercase().as_bytes())
+            .map_err(|_| TorClientError::InvalidClientAuthKey)?;
+        Self::from_bytes(&bytes)
+    }
+
+    pub fn to_base32(&self) -> String {
+        BASE32_NOPAD.encode(&self.0)
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl fmt::Display for ClientAuthPublicKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.to_base32())
+    }
+}
+
+/// The x25519 secret key used by a client to connect to a restricted onion service. Tor expects this key to be base64
+/// encoded (ONION_CLIENT_AUTH_ADD).
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize, Zeroize)]
+#[zeroize(drop)]
+pub struct ClientAuthSecretKey([u8; CLIENT_AUTH_KEY_LEN]);
+
+impl ClientAuthSecretKey {
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, TorClientError> {
+        to_key_bytes(bytes).map(Self)
+    }
+
+    /// Parses a base64 encoded secret key, as found in a tor `.auth_private` file
+    /// (`<onion-addr>:descriptor:x25519:<base64>`)
+    pub fn from_base64(s: &str) -> Result<Self, TorClientError> {
+        let bytes = BASE64
+            .decode(s.as_bytes())
+            .map_err(|_| TorClientError::InvalidClientAuthKey)?;
+        Self::from_bytes(&bytes)
+    }
+
+    pub fn to_base64(&self) -> String {
+        BASE64.encode(&self.0)
+    }
+}
+
+impl fmt::Debug for ClientAuthSecretKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ClientAuthSecretKey([redacted])")
+    }
+}
+
+/// An x25519 key pair for v3 onion service client authorization. The public key is given to the operator of the
+/// restricted onion service and the secret key is registered with the tor proxy of the client.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClientAuthKeypair {
+    pub public_key: ClientAuthPublicKey,
+    pub secret_key: ClientAuthSecretKey,
+}
+
+impl ClientAuthKeypair {
+    /// Generates a new random x25519 key pair
+    pub fn random() -> Result<Self, TorClientError> {
+        let keypair = snow::Builder::new(
+            "Noise_NN_25519_ChaChaPoly_BLAKE2s"
+                .parse()
+                .expect("hardcoded noise parameters are valid"),
+        )
+        .generate_keypair()
+        .map_err(|_| TorClientError::InvalidClientAuthKey)?;
+        Ok(Self {
+            public_key: ClientAuthPublicKey::from_bytes(&keypair.public)?,
+            secret_key: ClientAuthSecretKey::from_bytes(&keypair.private)?,
+        })
+    }
+}
+
+fn to_key_bytes(bytes: &[u8]) -> Result<[u8; CLIENT_AUTH_KEY_LEN], TorClientError> {
+    if bytes.len() != CLIENT_AUTH_KEY_LEN {
+        return Err(TorClientError::InvalidClientAuthKey);
+    }
+    let mut buf = [0u8; CLIENT_AUTH_KEY_LEN];
+    buf.copy_from_slice(bytes);
+    Ok(buf)
+}
+
 /// Represents a mapping between an onion port and a proxied address (usually 127.0.0.1:xxxx).
 /// If the proxied_address is not specified, the default `127.0.0.1:[onion_port]` will be used.
 #[derive(Clone, Copy, Serialize, Deserialize)]
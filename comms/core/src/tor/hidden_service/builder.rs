@@ -20,7 +20,7 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use std::sync::Arc;
+use std::{sync::Arc, time::Duration};
 
 use bitflags::bitflags;
 use log::*;
@@ -32,7 +32,13 @@ use crate::{
     multiaddr::Multiaddr,
     socks,
     tor::{
-        hidden_service::{controller::HiddenServiceController, TorProxyOpts},
+        hidden_service::{
+            controller::HiddenServiceController,
+            KeyRotationConfig,
+            OnionClientAuth,
+            OnionServiceConfig,
+            TorProxyOpts,
+        },
         Authentication,
         PortMapping,
         TorIdentity,
@@ -47,6 +53,8 @@ pub enum HiddenServiceBuilderError {
     ProxiedPortMappingNotProvided,
     #[error("The control server address was not provided. Use `with_control_server_address` to set it.")]
     TorControlServerAddressNotProvided,
+    #[error("The key rotation grace period must be less than the key rotation interval")]
+    InvalidKeyRotationGracePeriod,
     #[error("HiddenServiceControllerError: {0}")]
     HiddenServiceControllerError(#[from] HiddenServiceControllerError),
 }
@@ -72,6 +80,9 @@ pub struct HiddenServiceBuilder {
     control_server_auth: Authentication,
     socks_auth: socks::Authentication,
     hs_flags: HsFlags,
+    additional_services: Vec<OnionServiceConfig>,
+    onion_client_auth: Vec<OnionClientAuth>,
+    key_rotation: Option<KeyRotationConfig>,
     shutdown_signal: OptionalShutdownSignal,
 }
 
@@ -125,6 +136,29 @@ impl HiddenServiceBuilder {
         HsFlags
     );
 
+    setter!(
+        /// Client authorization credentials for restricted onion services that this node connects to. These are
+        /// registered with the tor proxy before any connections are made.
+        with_onion_client_auth,
+        onion_client_auth,
+        Vec<OnionClientAuth>
+    );
+
+    /// Add an onion service in addition to the primary hidden service e.g. a separate onion address for the gRPC
+    /// server. All services share the same Tor control port connection. Additional services can be restricted to
+    /// authorized clients (see [OnionServiceConfig::authorized_clients]).
+    pub fn add_onion_service(mut self, service: OnionServiceConfig) -> Self {
+        self.additional_services.push(service);
+        self
+    }
+
+    /// Periodically replace the hidden service key with a new one. The previous onion service remains reachable for
+    /// `grace_period` after each rotation.
+    pub fn with_key_rotation(mut self, interval: Duration, grace_period: Duration) -> Self {
+        self.key_rotation = Some(KeyRotationConfig { interval, grace_period });
+        self
+    }
+
     /// Use a direct TCP/IP connection if a TCP address is given instead of the tor proxy. This is worse for privacy
     /// but can use the full available connection bandwidth
     pub fn bypass_tor_for_tcp_addresses(mut self) -> Self {
@@ -171,6 +205,12 @@ impl HiddenServiceBuilder {
             proxied_port_mapping
         );
 
+        if let Some(ref key_rotation) = self.key_rotation {
+            if key_rotation.grace_period >= key_rotation.interval {
+                return Err(HiddenServiceBuilderError::InvalidKeyRotationGracePeriod);
+            }
+        }
+
         let controller = HiddenServiceController::new(
             control_server_addr,
             self.control_server_auth,
@@ -179,6 +219,9 @@ impl HiddenServiceBuilder {
             self.socks_auth,
             self.identity,
             self.hs_flags,
+            self.additional_services,
+            self.onion_client_auth,
+            self.key_rotation,
             self.proxy_opts,
             self.shutdown_signal,
         );
@@ -27,7 +27,11 @@ use log::*;
 use tari_shutdown::OptionalShutdownSignal;
 use tari_utilities::hex::Hex;
 use thiserror::Error;
-use tokio::{sync::broadcast, time};
+use tokio::{
+    sync::{broadcast, watch},
+    time,
+    time::Instant,
+};
 
 use crate::{
     multiaddr::Multiaddr,
@@ -37,8 +41,9 @@ use crate::{
             commands::{AddOnionFlag, AddOnionResponse},
             TorControlEvent,
         },
-        hidden_service::TorProxyOpts,
+        hidden_service::{KeyRotationConfig, OnionClientAuth, OnionServiceConfig, TorProxyOpts},
         Authentication,
+        ClientAuthPublicKey,
         HiddenService,
         HsFlags,
         PortMapping,
@@ -85,6 +90,13 @@ pub struct HiddenServiceController {
     socks_auth: socks::Authentication,
     pub identity: Option<TorIdentity>,
     hs_flags: HsFlags,
+    additional_services: Vec<OnionServiceConfig>,
+    onion_client_auth: Vec<OnionClientAuth>,
+    key_rotation: Option<KeyRotationConfig>,
+    /// A previous identity that was replaced by key rotation and the time at which its onion service is deleted
+    retired_service: Option<(TorIdentity, Instant)>,
+    /// Publishes each retired identity once its onion service has been deleted
+    retired_identities: watch::Sender<Option<TorIdentity>>,
     is_authenticated: bool,
     proxy_opts: TorProxyOpts,
     shutdown_signal: OptionalShutdownSignal,
@@ -99,6 +111,9 @@ impl HiddenServiceController {
         socks_auth: socks::Authentication,
         identity: Option<TorIdentity>,
         hs_flags: HsFlags,
+        additional_services: Vec<OnionServiceConfig>,
+        onion_client_auth: Vec<OnionClientAuth>,
+        key_rotation: Option<KeyRotationConfig>,
         proxy_opts: TorProxyOpts,
         shutdown_signal: OptionalShutdownSignal,
     ) -> Self {
@@ -111,6 +126,11 @@ impl HiddenServiceController {
             socks_auth,
             hs_flags,
             identity,
+            additional_services,
+            onion_client_auth,
+            key_rotation,
+            retired_service: None,
+            retired_identities: watch::channel(None).0,
             is_authenticated: false,
             proxy_opts,
             shutdown_signal,
@@ -125,6 +145,7 @@ impl HiddenServiceController {
 
     pub async fn initialize_transport(&mut self) -> Result<SocksTransport, HiddenServiceControllerError> {
         self.connect_and_auth().await?;
+        self.register_onion_client_auth().await?;
 
         let socks_addr = self.get_socks_address().await?;
         Ok(SocksTransport::new(SocksConfig {
@@ -137,48 +158,88 @@ impl HiddenServiceController {
     /// Connects, authenticates to the Tor control port and creates a hidden service using the tor identity if provided,
     /// otherwise a new tor identity will be created. The creation of a hidden service is idempotent i.e. if the
     /// hidden service exists, the
+    ///
+    /// Any additional onion services are created on the same control port connection. If key rotation is configured,
+    /// the primary onion service is periodically replaced with a new one and the new identity is published to
+    /// [HiddenService::identity_updates].
     pub async fn create_hidden_service(mut self) -> Result<HiddenService, HiddenServiceControllerError> {
         self.connect_and_auth().await?;
         self.set_events().await?;
 
-        let hidden_service = self.create_hidden_service_from_identity().await?;
+        self.create_hidden_services_from_identity().await?;
+        let identity = self
+            .identity
+            .clone()
+            .expect("identity set in create_hidden_services_from_identity");
+        let (identity_tx, identity_rx) = watch::channel(identity.clone());
+        let hidden_service = HiddenService {
+            identity,
+            proxied_addr: self.proxied_address(),
+            additional_services: self.additional_service_identities(),
+            identity_updates: identity_rx,
+            retired_identities: self.retired_identities.subscribe(),
+            shutdown_signal: self.shutdown_signal.clone(),
+        };
         let mut shutdown_signal = hidden_service.shutdown_signal.clone();
         let mut event_stream = self.client.as_ref().unwrap().get_event_stream();
 
         tokio::spawn({
             async move {
+                let rotation_interval = self.key_rotation.as_ref().map(|c| c.interval);
+                let mut next_rotation = rotation_interval.map(|interval| Instant::now() + interval);
                 loop {
-                    let either = future::select(&mut shutdown_signal, event_stream.next()).await;
-                    match either {
-                        Either::Left(_) => {
+                    let rotation_deadline = next_rotation.unwrap_or_else(Instant::now);
+                    let retirement_deadline = self
+                        .retired_service
+                        .as_ref()
+                        .map(|(_, at)| *at)
+                        .unwrap_or_else(Instant::now);
+                    tokio::select! {
+                        _ = &mut shutdown_signal => {
                             debug!(
                                 target: LOG_TARGET,
                                 "Tor controller shut down because the shutdown signal was received"
                             );
                             break;
                         },
-                        Either::Right((Some(Ok(TorControlEvent::TorControlDisconnected)), shutdown_signal)) => {
-                            let event_tx = self
-                                .client
-                                .as_ref()
-                                .map(|c| c.event_sender().clone())
-                                .expect("HiddenServiceController::client was None");
-                            warn!(
-                                target: LOG_TARGET,
-                                "Tor control server disconnected. Attempting to reestablish connection..."
-                            );
-                            if let Err(err) = self.reestablish_hidden_service(event_tx, shutdown_signal).await {
-                                error!(
+                        Some(event) = event_stream.next() => match event {
+                            Ok(TorControlEvent::TorControlDisconnected) => {
+                                let event_tx = self
+                                    .client
+                                    .as_ref()
+                                    .map(|c| c.event_sender().clone())
+                                    .expect("HiddenServiceController::client was None");
+                                warn!(
                                     target: LOG_TARGET,
-                                    "Failed to reestablish connection to tor control server because '{:?}'", err
+                                    "Tor control server disconnected. Attempting to reestablish connection..."
                                 );
-                                break;
+                                if let Err(err) = self.reestablish_hidden_service(event_tx, &mut shutdown_signal).await {
+                                    error!(
+                                        target: LOG_TARGET,
+                                        "Failed to reestablish connection to tor control server because '{:?}'", err
+                                    );
+                                    break;
+                                }
+                            },
+                            Ok(evt) => {
+                                trace!(target: LOG_TARGET, "Tor control event: {:?}", evt);
+                            },
+                            Err(_) => {},
+                        },
+                        _ = time::sleep_until(rotation_deadline), if next_rotation.is_some() => {
+                            next_rotation = rotation_interval.map(|interval| Instant::now() + interval);
+                            match self.rotate_identity().await {
+                                Ok(identity) => {
+                                    identity_tx.send_replace(identity);
+                                },
+                                Err(err) => {
+                                    warn!(target: LOG_TARGET, "Failed to rotate the onion service key: {}", err);
+                                },
                             }
                         },
-                        Either::Right((Some(Ok(evt)), _)) => {
-                            trace!(target: LOG_TARGET, "Tor control event: {:?}", evt);
+                        _ = time::sleep_until(retirement_deadline), if self.retired_service.is_some() => {
+                            self.delete_retired_service().await;
                         },
-                        _ => {},
                     }
                 }
             }
@@ -215,7 +276,10 @@ impl HiddenServiceController {
                     self.client = Some(client);
                     self.authenticate().await?;
                     self.set_events().await?;
-                    let _result = self.create_hidden_service_from_identity().await;
+                    if let Err(err) = self.register_onion_client_auth().await {
+                        warn!(target: LOG_TARGET, "Failed to register onion client authorization: {}", err);
+                    }
+                    let _result = self.create_hidden_services_from_identity().await;
                     break Ok(());
                 },
                 Either::Left((Err(err), shutdown_signal)) => {
@@ -336,48 +400,153 @@ impl HiddenServiceController {
         }
     }
 
-    async fn create_hidden_service_from_identity(&mut self) -> Result<HiddenService, HiddenServiceControllerError> {
+    async fn create_hidden_services_from_identity(&mut self) -> Result<(), HiddenServiceControllerError> {
         let socks_addr = self.get_socks_address().await?;
         debug!(target: LOG_TARGET, "Tor SOCKS address is '{}'", socks_addr);
 
         // Initialize a onion hidden service - either from the given private key or by creating a new one
-        match self.identity.take() {
+        let identity = self.identity.take();
+        let port_mapping = self.proxied_port_mapping;
+        let identity = self.create_onion(identity, port_mapping, &[]).await?;
+        debug!(
+            target: LOG_TARGET,
+            "Added hidden service with service id '{}' on port '{}'", identity.service_id, identity.onion_port
+        );
+        self.identity = Some(identity);
+
+        let mut additional_services = std::mem::take(&mut self.additional_services);
+        let result = self.create_additional_services(&mut additional_services).await;
+        self.additional_services = additional_services;
+        result
+    }
+
+    async fn create_additional_services(
+        &mut self,
+        services: &mut [OnionServiceConfig],
+    ) -> Result<(), HiddenServiceControllerError> {
+        for service in services {
+            let identity = self
+                .create_onion(
+                    service.identity.take(),
+                    service.port_mapping,
+                    &service.authorized_clients,
+                )
+                .await?;
+            debug!(
+                target: LOG_TARGET,
+                "Added additional hidden service with service id '{}' on port '{}' ({} authorized client(s))",
+                identity.service_id,
+                identity.onion_port,
+                service.authorized_clients.len()
+            );
+            service.identity = Some(identity);
+        }
+        Ok(())
+    }
+
+    /// Creates an onion service from the identity if provided, otherwise tor generates a new identity.
+    async fn create_onion(
+        &mut self,
+        identity: Option<TorIdentity>,
+        port_mapping: PortMapping,
+        authorized_clients: &[ClientAuthPublicKey],
+    ) -> Result<TorIdentity, HiddenServiceControllerError> {
+        match identity {
             Some(identity) => {
-                let resp = self.create_or_reuse_onion(&identity).await?;
-                self.identity = Some(TorIdentity {
+                let resp = self
+                    .create_or_reuse_onion(&identity, port_mapping, authorized_clients)
+                    .await?;
+                Ok(TorIdentity {
                     onion_port: resp.onion_port,
                     ..identity
-                });
+                })
             },
             None => {
-                let port_mapping = self.proxied_port_mapping;
-                let resp = self.client_mut()?.add_onion(vec![], port_mapping, None).await?;
+                let resp = self
+                    .client_mut()?
+                    .add_onion_with_client_auth(None, vec![], port_mapping, None, authorized_clients)
+                    .await?;
                 let private_key = resp
                     .private_key
                     .clone()
                     .expect("Tor server MUST return private key according to spec");
 
-                self.identity = Some(TorIdentity {
+                Ok(TorIdentity {
                     private_key,
                     service_id: resp.service_id,
                     onion_port: resp.onion_port,
-                });
+                })
             },
-        };
+        }
+    }
 
-        let identity = self.identity.as_ref().map(Clone::clone).expect("already checked");
-        debug!(
+    fn additional_service_identities(&self) -> Vec<TorIdentity> {
+        self.additional_services
+            .iter()
+            .filter_map(|s| s.identity.clone())
+            .collect()
+    }
+
+    /// Registers the client authorization credentials for restricted onion services that this node connects to
+    async fn register_onion_client_auth(&mut self) -> Result<(), HiddenServiceControllerError> {
+        let credentials = self.onion_client_auth.clone();
+        let client = self.client_mut()?;
+        for auth in credentials {
+            client
+                .onion_client_auth_add(&auth.service_id, &auth.secret_key, false)
+                .await?;
+            debug!(
+                target: LOG_TARGET,
+                "Registered client authorization for onion service '{}'", auth.service_id
+            );
+        }
+        Ok(())
+    }
+
+    /// Replaces the primary onion service with a newly generated one. The previous onion service remains reachable for
+    /// the configured grace period, giving peers time to learn the new address.
+    async fn rotate_identity(&mut self) -> Result<TorIdentity, HiddenServiceControllerError> {
+        let grace_period = self.key_rotation.as_ref().map(|c| c.grace_period).unwrap_or_default();
+        let port_mapping = self.proxied_port_mapping;
+        let identity = self.create_onion(None, port_mapping, &[]).await?;
+        info!(
             target: LOG_TARGET,
-            "Added hidden service with service id '{}' on port '{}'", identity.service_id, identity.onion_port
+            "Rotated onion service key. New service id is '{}'", identity.service_id
         );
 
-        let proxied_addr = socketaddr_to_multiaddr(self.proxied_port_mapping.proxied_address());
+        if let Some(previous) = self.identity.replace(identity.clone()) {
+            // A retired service that has not expired yet is deleted immediately to avoid accumulating services
+            if self.retired_service.is_some() {
+                self.delete_retired_service().await;
+            }
+            self.retired_service = Some((previous, Instant::now() + grace_period));
+        }
+
+        Ok(identity)
+    }
 
-        Ok(HiddenService {
-            identity,
-            proxied_addr,
-            shutdown_signal: self.shutdown_signal.clone(),
-        })
+    /// Deletes the onion service of the retired identity and publishes the identity to
+    /// [HiddenService::retired_identities], so that its address is no longer advertised
+    async fn delete_retired_service(&mut self) {
+        let retired = match self.retired_service.take() {
+            Some((identity, _)) => identity,
+            None => return,
+        };
+        let service_id = retired.service_id.clone();
+        match self.client_mut() {
+            Ok(client) => match client.del_onion(&service_id).await {
+                Ok(_) => debug!(target: LOG_TARGET, "Deleted retired onion service '{}'", service_id),
+                Err(err) => warn!(
+                    target: LOG_TARGET,
+                    "Failed to delete retired onion service '{}': {}", service_id, err
+                ),
+            },
+            Err(err) => warn!(
+                target: LOG_TARGET,
+                "Unable to delete retired onion service '{}': {}", service_id, err
+            ),
+        }
+        self.retired_identities.send_replace(Some(retired));
     }
 
     pub fn set_proxied_addr(&mut self, addr: &Multiaddr) {
@@ -389,19 +558,25 @@ impl HiddenServiceController {
     async fn create_or_reuse_onion(
         &mut self,
         identity: &TorIdentity,
+        port_mapping: PortMapping,
+        authorized_clients: &[ClientAuthPublicKey],
     ) -> Result<AddOnionResponse, HiddenServiceControllerError> {
         let mut flags = Vec::new();
         if self.hs_flags.contains(HsFlags::DETACH) {
             flags.push(AddOnionFlag::Detach);
         }
 
-        let port_mapping = self.proxied_port_mapping;
-
         let client = self.client_mut()?;
 
         loop {
             let result = client
-                .add_onion_from_private_key(&identity.private_key, flags.clone(), port_mapping, None)
+                .add_onion_with_client_auth(
+                    Some(&identity.private_key),
+                    flags.clone(),
+                    port_mapping,
+                    None,
+                    authorized_clients,
+                )
                 .await;
 
             match result {
@@ -432,3 +607,111 @@ impl HiddenServiceController {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use tari_shutdown::Shutdown;
+
+    use super::*;
+    use crate::tor::{
+        control_client::{test_server, test_server::canned_responses},
+        ClientAuthKeypair,
+        HiddenServiceBuilder,
+        OnionServiceConfig,
+    };
+
+    async fn setup_controller(builder: HiddenServiceBuilder) -> (HiddenServiceController, test_server::State) {
+        let (addr, mock_state, socket) = test_server::spawn().await;
+        let mut controller = builder
+            .with_control_server_address(addr)
+            .with_socks_address_override(Some("/ip4/127.0.0.1/tcp/9050".parse().unwrap()))
+            .build()
+            .unwrap();
+        let (event_tx, _) = broadcast::channel(1);
+        controller.client = Some(TorControlPortClient::new(socket, event_tx));
+        controller.is_authenticated = true;
+        (controller, mock_state)
+    }
+
+    #[tokio::test]
+    async fn it_creates_additional_restricted_services() {
+        let client = ClientAuthKeypair::random().unwrap();
+        let builder = HiddenServiceBuilder::new()
+            .with_port_mapping(8080)
+            .add_onion_service(OnionServiceConfig::new(8081).with_authorized_clients(vec![client.public_key.clone()]))
+            .with_onion_client_auth(vec![OnionClientAuth {
+                service_id: "some-restricted-service".to_string(),
+                secret_key: client.secret_key.clone(),
+            }]);
+        let (mut controller, mock_state) = setup_controller(builder).await;
+        mock_state.set_canned_response(canned_responses::ADD_ONION_OK).await;
+
+        controller.initialize_transport().await.unwrap();
+        let hidden_service = controller.create_hidden_service().await.unwrap();
+        assert_eq!(
+            hidden_service.service_id(),
+            "qigbgbs4ue3ghbupsotgh73cmmkjrin2aprlyxsrnrvpmcmzy3g4wbid"
+        );
+        assert_eq!(hidden_service.additional_services().len(), 1);
+
+        let requests = mock_state.take_requests().await;
+        assert_eq!(requests, vec![
+            format!(
+                "ONION_CLIENT_AUTH_ADD some-restricted-service x25519:{}",
+                client.secret_key.to_base64()
+            ),
+            "SETEVENTS NETWORK_LIVENESS".to_string(),
+            "ADD_ONION NEW:ED25519-V3 Port=8080,127.0.0.1:8080".to_string(),
+            format!(
+                "ADD_ONION NEW:ED25519-V3 Flags=V3Auth Port=8081,127.0.0.1:8081 ClientAuthV3={}",
+                client.public_key.to_base32()
+            ),
+        ]);
+    }
+
+    #[tokio::test]
+    async fn it_rotates_the_hidden_service_key() {
+        let mut shutdown = Shutdown::new();
+        let builder = HiddenServiceBuilder::new()
+            .with_port_mapping(8080)
+            .with_key_rotation(Duration::from_millis(200), Duration::from_millis(50))
+            .with_shutdown_signal(shutdown.to_signal());
+        let (controller, mock_state) = setup_controller(builder).await;
+        mock_state.set_canned_response(canned_responses::ADD_ONION_OK).await;
+
+        let hidden_service = controller.create_hidden_service().await.unwrap();
+        let mut identity_updates = hidden_service.identity_updates();
+        time::timeout(Duration::from_secs(5), identity_updates.changed())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            identity_updates.borrow().service_id,
+            "qigbgbs4ue3ghbupsotgh73cmmkjrin2aprlyxsrnrvpmcmzy3g4wbid"
+        );
+
+        // The previous onion service is deleted once the grace period has elapsed
+        time::sleep(Duration::from_millis(100)).await;
+        shutdown.trigger();
+        let requests = mock_state.take_requests().await;
+        assert_eq!(
+            requests
+                .iter()
+                .filter(|r| r.starts_with("ADD_ONION NEW:ED25519-V3 Port=8080"))
+                .count(),
+            2
+        );
+        assert_eq!(
+            requests.last().unwrap(),
+            &format!("DEL_ONION {}", hidden_service.service_id())
+        );
+        assert_eq!(
+            hidden_service
+                .retired_identities()
+                .borrow()
+                .as_ref()
+                .map(|i| i.service_id.as_str()),
+            Some(hidden_service.service_id())
+        );
+    }
+}
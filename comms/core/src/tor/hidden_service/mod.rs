@@ -27,16 +27,17 @@ mod controller;
 pub use controller::{HiddenServiceController, HiddenServiceControllerError};
 
 mod proxy_opts;
-use std::fmt;
+use std::{fmt, time::Duration};
 
 use derivative::Derivative;
 pub use proxy_opts::TorProxyOpts;
 use serde_derive::{Deserialize, Serialize};
 use tari_shutdown::OptionalShutdownSignal;
+use tokio::sync::watch;
 
 use crate::{
     multiaddr::Multiaddr,
-    tor::{PrivateKey, TorClientError},
+    tor::{ClientAuthPublicKey, ClientAuthSecretKey, PortMapping, PrivateKey, TorClientError},
 };
 
 /// Handle for a Tor Hidden Service. This handle keeps the session to the Tor control port alive.
@@ -47,6 +48,12 @@ pub struct HiddenService {
     pub(super) identity: TorIdentity,
     /// The address where incoming traffic to the `onion_addr` will be forwarded to.
     pub(super) proxied_addr: Multiaddr,
+    /// The identities of the additional onion services created alongside this one
+    pub(super) additional_services: Vec<TorIdentity>,
+    /// Receives the new identity whenever the onion service key is rotated
+    pub(super) identity_updates: watch::Receiver<TorIdentity>,
+    /// Receives each identity replaced by key rotation once its grace period has ended
+    pub(super) retired_identities: watch::Receiver<Option<TorIdentity>>,
     /// Shutdown signal for hidden service
     pub(super) shutdown_signal: OptionalShutdownSignal,
 }
@@ -66,9 +73,78 @@ impl HiddenService {
         &self.proxied_addr
     }
 
+    /// The identity with which the hidden service was created. If key rotation is enabled, the current identity is
+    /// available from [HiddenService::identity_updates].
     pub fn tor_identity(&self) -> &TorIdentity {
         &self.identity
     }
+
+    /// The identities of the additional onion services, in the order they were added to the builder.
+    pub fn additional_services(&self) -> &[TorIdentity] {
+        &self.additional_services
+    }
+
+    /// Returns a receiver that is notified with the new identity each time the onion service key is rotated.
+    pub fn identity_updates(&self) -> watch::Receiver<TorIdentity> {
+        self.identity_updates.clone()
+    }
+
+    /// Returns a receiver that is notified with the previous identity each time the onion service of an identity that
+    /// was replaced by key rotation is deleted at the end of its grace period.
+    pub fn retired_identities(&self) -> watch::Receiver<Option<TorIdentity>> {
+        self.retired_identities.clone()
+    }
+}
+
+/// An additional onion service that is created on the same Tor control port connection as the primary hidden service,
+/// for example a separate onion address for the gRPC server.
+#[derive(Debug, Clone)]
+pub struct OnionServiceConfig {
+    /// Maps the onion port to the local address that receives the traffic
+    pub port_mapping: PortMapping,
+    /// The identity of the onion service. If None, a new identity is generated. Unlike the primary hidden service,
+    /// additional services are not subject to key rotation.
+    pub identity: Option<TorIdentity>,
+    /// If not empty, only clients holding the secret key for one of these public keys are able to connect (v3 client
+    /// authorization).
+    pub authorized_clients: Vec<ClientAuthPublicKey>,
+}
+
+impl OnionServiceConfig {
+    pub fn new<P: Into<PortMapping>>(port_mapping: P) -> Self {
+        Self {
+            port_mapping: port_mapping.into(),
+            identity: None,
+            authorized_clients: Vec::new(),
+        }
+    }
+
+    pub fn with_identity(mut self, identity: TorIdentity) -> Self {
+        self.identity = Some(identity);
+        self
+    }
+
+    pub fn with_authorized_clients(mut self, authorized_clients: Vec<ClientAuthPublicKey>) -> Self {
+        self.authorized_clients = authorized_clients;
+        self
+    }
+}
+
+/// Client authorization credentials for a restricted onion service that this node connects to.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OnionClientAuth {
+    /// The service id of the restricted onion service i.e. the onion address without the `.onion` suffix
+    pub service_id: String,
+    pub secret_key: ClientAuthSecretKey,
+}
+
+/// Periodic rotation of the hidden service key.
+#[derive(Debug, Clone, Copy)]
+pub struct KeyRotationConfig {
+    /// The time between key rotations
+    pub interval: Duration,
+    /// The time for which the previous onion service remains reachable after a rotation
+    pub grace_period: Duration,
 }
 
 fn multiaddr_from_service_id_and_port(service_id: &str, onion_port: u16) -> Result<Multiaddr, TorClientError> {
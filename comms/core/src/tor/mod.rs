@@ -33,6 +33,9 @@
 mod control_client;
 pub use control_client::{
     Authentication,
+    ClientAuthKeypair,
+    ClientAuthPublicKey,
+    ClientAuthSecretKey,
     KeyBlob,
     KeyType,
     PortMapping,
@@ -50,5 +53,8 @@ pub use hidden_service::{
     HiddenServiceController,
     HiddenServiceControllerError,
     HsFlags,
+    KeyRotationConfig,
+    OnionClientAuth,
+    OnionServiceConfig,
     TorIdentity,
 };
@@ -27,7 +27,7 @@ use multiaddr::{multiaddr, Multiaddr, Protocol};
 use tokio::sync::RwLock;
 
 use crate::{
-    tor::{HiddenServiceController, TorIdentity},
+    tor::{HiddenService, HiddenServiceController, TorIdentity},
     transports::{tcp::TcpInbound, SocksTransport, Transport},
 };
 
@@ -46,13 +46,17 @@ struct HiddenServiceTransportInner {
     hidden_service_ctl: Option<HiddenServiceController>,
 }
 
+/// Called with an identity that was replaced by key rotation once its onion service has been deleted
+pub type RetiredIdentityCallback = Arc<dyn Fn(TorIdentity) + Send + Sync>;
+
 #[derive(Clone)]
 pub struct HiddenServiceTransport<F: Fn(TorIdentity)> {
     inner: Arc<RwLock<HiddenServiceTransportInner>>,
     after_init: F,
+    after_retire: Option<RetiredIdentityCallback>,
 }
 
-impl<F: Fn(TorIdentity)> HiddenServiceTransport<F> {
+impl<F: Fn(TorIdentity) + Clone + Send + 'static> HiddenServiceTransport<F> {
     pub fn new(hidden_service_ctl: HiddenServiceController, after_init: F) -> Self {
         Self {
             inner: Arc::new(RwLock::new(HiddenServiceTransportInner {
@@ -60,9 +64,17 @@ impl<F: Fn(TorIdentity)> HiddenServiceTransport<F> {
                 hidden_service_ctl: Some(hidden_service_ctl),
             })),
             after_init,
+            after_retire: None,
         }
     }
 
+    /// Sets a callback that is called with each identity that was replaced by key rotation once its grace period has
+    /// ended and its onion service has been deleted
+    pub fn with_after_retire(mut self, after_retire: RetiredIdentityCallback) -> Self {
+        self.after_retire = Some(after_retire);
+        self
+    }
+
     async fn is_initialized(&self) -> bool {
         self.inner.read().await.socks_transport.is_some()
     }
@@ -105,11 +117,45 @@ impl<F: Fn(TorIdentity)> HiddenServiceTransport<F> {
         })?;
 
         (self.after_init)(hidden_service.tor_identity().clone());
+        self.spawn_identity_update_listener(&hidden_service);
         Ok((inbound, listen_addr))
     }
+
+    /// Calls `after_init` with the new identity each time the hidden service key is rotated, and `after_retire` with
+    /// the previous identity once its grace period has ended
+    fn spawn_identity_update_listener(&self, hidden_service: &HiddenService) {
+        let mut identity_updates = hidden_service.identity_updates();
+        let after_init = self.after_init.clone();
+        tokio::spawn(async move {
+            while identity_updates.changed().await.is_ok() {
+                let identity = identity_updates.borrow().clone();
+                debug!(
+                    target: LOG_TARGET,
+                    "Hidden service identity rotated to service id '{}'", identity.service_id
+                );
+                after_init(identity);
+            }
+        });
+
+        if let Some(after_retire) = self.after_retire.clone() {
+            let mut retired_identities = hidden_service.retired_identities();
+            tokio::spawn(async move {
+                while retired_identities.changed().await.is_ok() {
+                    let retired = retired_identities.borrow().clone();
+                    if let Some(identity) = retired {
+                        debug!(
+                            target: LOG_TARGET,
+                            "Hidden service identity with service id '{}' retired", identity.service_id
+                        );
+                        after_retire(identity);
+                    }
+                }
+            });
+        }
+    }
 }
 #[crate::async_trait]
-impl<F: Fn(TorIdentity) + Send + Sync> Transport for HiddenServiceTransport<F> {
+impl<F: Fn(TorIdentity) + Clone + Send + Sync + 'static> Transport for HiddenServiceTransport<F> {
     type Error = <SocksTransport as Transport>::Error;
     type Listener = <SocksTransport as Transport>::Listener;
     type Output = <SocksTransport as Transport>::Output;
@@ -30,6 +30,7 @@
 //! - [TCP](self::TcpTransport) - communication over TCP and IP4/IP6 and DNS
 //! - [SOCKS](self::SocksTransport) - communication over a SOCKS5 proxy.
 //! - [Memory](self::MemoryTransport) - in-process communication (mpsc channel), typically for testing.
+//! - [QUIC](self::QuicTransport) - natively multiplexed communication over UDP.
 
 use multiaddr::Multiaddr;
 use tokio_stream::Stream;
@@ -41,6 +42,9 @@ pub mod predicate;
 mod memory;
 pub use memory::MemoryTransport;
 
+mod quic;
+pub use quic::QuicTransport;
+
 mod socks;
 pub use socks::{SocksConfig, SocksTransport};
 
@@ -49,7 +53,7 @@ pub use tcp::TcpTransport;
 
 mod hidden_service_transport;
 mod tcp_with_tor;
-pub use hidden_service_transport::HiddenServiceTransport;
+pub use hidden_service_transport::{HiddenServiceTransport, RetiredIdentityCallback};
 pub use tcp_with_tor::TcpWithTorTransport;
 
 /// Defines an abstraction for implementations that can dial and listen for connections over a provided address.
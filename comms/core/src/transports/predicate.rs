@@ -58,6 +58,20 @@ pub fn is_onion_address(addr: &Multiaddr) -> bool {
     matches!(protocol, Some(Protocol::Onion(_, _)) | Some(Protocol::Onion3(_)))
 }
 
+/// Returns true if the address is a QUIC address i.e. `/<ip4|ip6|dns4|dns6>/<host>/udp/<port>/quic`
+pub fn is_quic_address(addr: &Multiaddr) -> bool {
+    let mut iter = addr.iter();
+    matches!(
+        (iter.next(), iter.next(), iter.next(), iter.next()),
+        (
+            Some(Protocol::Ip4(_) | Protocol::Ip6(_) | Protocol::Dns4(_) | Protocol::Dns6(_)),
+            Some(Protocol::Udp(_)),
+            Some(Protocol::Quic),
+            None
+        )
+    )
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -81,4 +95,30 @@ mod test {
             assert!(!is_onion_address(&addr));
         });
     }
+
+    #[test]
+    fn is_quic_address_test() {
+        let expect_true = [
+            "/ip4/1.2.3.4/udp/1234/quic",
+            "/ip6/::1/udp/1234/quic",
+            "/dns4/tari.com/udp/80/quic",
+        ];
+
+        let expect_false = [
+            "/ip4/1.2.3.4/tcp/1234",
+            "/ip4/1.2.3.4/udp/1234",
+            "/ip4/1.2.3.4/tcp/1234/quic",
+            "/ip4/1.2.3.4/udp/1234/quic/http",
+        ];
+
+        expect_true.iter().for_each(|addr| {
+            let addr = addr.parse().unwrap();
+            assert!(is_quic_address(&addr));
+        });
+
+        expect_false.iter().for_each(|addr| {
+            let addr = addr.parse().unwrap();
+            assert!(!is_quic_address(&addr));
+        });
+    }
 }
@@ -0,0 +1,255 @@
+// Copyright 2024. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{
+    convert::TryInto,
+    io,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr},
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime},
+};
+
+use futures::{stream, stream::BoxStream, StreamExt};
+use multiaddr::{Multiaddr, Protocol};
+use rustls::{
+    client::{ServerCertVerified, ServerCertVerifier},
+    Certificate,
+    PrivateKey,
+    ServerName,
+};
+
+use super::Transport;
+use crate::utils::multiaddr::socketaddr_to_quic_multiaddr;
+
+/// ALPN protocol identifier for comms QUIC connections
+const ALPN_PROTOCOL: &[u8] = b"tari-comms/1";
+/// The server name used in the self-signed certificate
+const SERVER_NAME: &str = "tari-comms";
+/// The maximum number of inbound connections performing the QUIC handshake at the same time
+const MAX_CONCURRENT_INBOUND_HANDSHAKES: usize = 64;
+const KEEP_ALIVE_INTERVAL: Duration = Duration::from_secs(10);
+const MAX_IDLE_TIMEOUT: Duration = Duration::from_secs(30);
+const MAX_CONCURRENT_STREAMS: u32 = 1024;
+
+/// Transport implementation for QUIC over UDP. Addresses take the form `/ip4/<ip>/udp/<port>/quic`.
+///
+/// The TLS layer uses a self-signed certificate and does not authenticate the peer. Peers are authenticated against
+/// their node identity once the connection is established, by signing over keying material exported from the TLS
+/// session (see the connection manager). Streams opened on the connection are natively multiplexed.
+#[derive(Clone, Default)]
+pub struct QuicTransport {
+    endpoints: Arc<Mutex<Vec<quinn::Endpoint>>>,
+}
+
+impl QuicTransport {
+    /// Create a new QuicTransport
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Returns an endpoint that can dial the given address, reusing the listener endpoint if one is bound to the same
+    /// address family so that peers see connections originating from our listening port.
+    fn get_or_create_client_endpoint(&self, remote: &SocketAddr) -> io::Result<quinn::Endpoint> {
+        let mut endpoints = self.endpoints.lock().expect("QuicTransport endpoints lock poisoned");
+        let existing = endpoints.iter().find(|endpoint| {
+            endpoint
+                .local_addr()
+                .map_or(false, |addr| addr.is_ipv4() == remote.is_ipv4())
+        });
+        if let Some(endpoint) = existing {
+            return Ok(endpoint.clone());
+        }
+
+        let bind_addr = if remote.is_ipv4() {
+            SocketAddr::new(IpAddr::V4(Ipv4Addr::UNSPECIFIED), 0)
+        } else {
+            SocketAddr::new(IpAddr::V6(Ipv6Addr::UNSPECIFIED), 0)
+        };
+        let mut endpoint = quinn::Endpoint::client(bind_addr)?;
+        endpoint.set_default_client_config(client_config()?);
+        endpoints.push(endpoint.clone());
+        Ok(endpoint)
+    }
+}
+
+#[crate::async_trait]
+impl Transport for QuicTransport {
+    type Error = io::Error;
+    type Listener = BoxStream<'static, io::Result<(quinn::Connection, Multiaddr)>>;
+    type Output = quinn::Connection;
+
+    async fn listen(&self, addr: &Multiaddr) -> Result<(Self::Listener, Multiaddr), Self::Error> {
+        let socket_addr = resolve_quic_address(addr).await?;
+        let mut endpoint = quinn::Endpoint::server(server_config()?, socket_addr)?;
+        endpoint.set_default_client_config(client_config()?);
+        let local_addr = socketaddr_to_quic_multiaddr(&endpoint.local_addr()?);
+        self.endpoints
+            .lock()
+            .expect("QuicTransport endpoints lock poisoned")
+            .insert(0, endpoint.clone());
+
+        let inbound = stream::unfold(endpoint, |endpoint| async move {
+            let connecting = endpoint.accept().await?;
+            Some((connecting, endpoint))
+        })
+        .map(|connecting| async move {
+            let connection = connecting.await?;
+            let peer_addr = socketaddr_to_quic_multiaddr(&connection.remote_address());
+            Ok::<_, io::Error>((connection, peer_addr))
+        })
+        .buffer_unordered(MAX_CONCURRENT_INBOUND_HANDSHAKES)
+        .boxed();
+
+        Ok((inbound, local_addr))
+    }
+
+    async fn dial(&self, addr: &Multiaddr) -> Result<Self::Output, Self::Error> {
+        let socket_addr = resolve_quic_address(addr).await?;
+        let endpoint = self.get_or_create_client_endpoint(&socket_addr)?;
+        let connecting = endpoint
+            .connect(socket_addr, SERVER_NAME)
+            .map_err(|err| io::Error::new(io::ErrorKind::Other, err))?;
+        let connection = connecting.await?;
+        Ok(connection)
+    }
+}
+
+/// Resolves a `/<ip4|ip6|dns4|dns6>/<host>/udp/<port>/quic` address to a socket address
+async fn resolve_quic_address(addr: &Multiaddr) -> io::Result<SocketAddr> {
+    let invalid_address = || io::Error::new(io::ErrorKind::InvalidInput, format!("Invalid QUIC address '{}'", addr));
+    let mut iter = addr.iter();
+    let (host, port) = match (iter.next(), iter.next(), iter.next(), iter.next()) {
+        (Some(host), Some(Protocol::Udp(port)), Some(Protocol::Quic), None) => (host, port),
+        _ => return Err(invalid_address()),
+    };
+
+    let (domain, want_ipv4) = match host {
+        Protocol::Ip4(ip) => return Ok(SocketAddr::new(IpAddr::V4(ip), port)),
+        Protocol::Ip6(ip) => return Ok(SocketAddr::new(IpAddr::V6(ip), port)),
+        Protocol::Dns4(domain) => (domain, true),
+        Protocol::Dns6(domain) => (domain, false),
+        _ => return Err(invalid_address()),
+    };
+    tokio::net::lookup_host((domain.as_ref(), port))
+        .await?
+        .find(|addr| addr.is_ipv4() == want_ipv4)
+        .ok_or_else(invalid_address)
+}
+
+fn transport_config() -> io::Result<quinn::TransportConfig> {
+    let mut config = quinn::TransportConfig::default();
+    config
+        .keep_alive_interval(Some(KEEP_ALIVE_INTERVAL))
+        .max_idle_timeout(Some(
+            MAX_IDLE_TIMEOUT
+                .try_into()
+                .map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err))?,
+        ))
+        .max_concurrent_bidi_streams(MAX_CONCURRENT_STREAMS.into())
+        .max_concurrent_uni_streams(0u32.into());
+    Ok(config)
+}
+
+fn server_config() -> io::Result<quinn::ServerConfig> {
+    let cert = rcgen::generate_simple_self_signed(vec![SERVER_NAME.to_string()]).map_err(to_io_error)?;
+    let cert_der = cert.serialize_der().map_err(to_io_error)?;
+    let key_der = cert.serialize_private_key_der();
+
+    let mut crypto = rustls::ServerConfig::builder()
+        .with_safe_defaults()
+        .with_no_client_auth()
+        .with_single_cert(vec![Certificate(cert_der)], PrivateKey(key_der))
+        .map_err(to_io_error)?;
+    crypto.alpn_protocols = vec![ALPN_PROTOCOL.to_vec()];
+
+    let mut config = quinn::ServerConfig::with_crypto(Arc::new(crypto));
+    config.transport_config(Arc::new(transport_config()?));
+    Ok(config)
+}
+
+fn client_config() -> io::Result<quinn::ClientConfig> {
+    let mut crypto = rustls::ClientConfig::builder()
+        .with_safe_defaults()
+        .with_custom_certificate_verifier(Arc::new(AcceptAnyServerCertificate))
+        .with_no_client_auth();
+    crypto.alpn_protocols = vec![ALPN_PROTOCOL.to_vec()];
+
+    let mut config = quinn::ClientConfig::new(Arc::new(crypto));
+    config.transport_config(Arc::new(transport_config()?));
+    Ok(config)
+}
+
+fn to_io_error<E: std::error::Error + Send + Sync + 'static>(err: E) -> io::Error {
+    io::Error::new(io::ErrorKind::Other, err)
+}
+
+/// Certificates are self-signed and carry no identity. The peer's node identity is authenticated over the established
+/// connection instead.
+struct AcceptAnyServerCertificate;
+
+impl ServerCertVerifier for AcceptAnyServerCertificate {
+    fn verify_server_cert(
+        &self,
+        _end_entity: &Certificate,
+        _intermediates: &[Certificate],
+        _server_name: &ServerName,
+        _scts: &mut dyn Iterator<Item = &[u8]>,
+        _ocsp_response: &[u8],
+        _now: SystemTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        Ok(ServerCertVerified::assertion())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[tokio::test]
+    async fn dial_and_listen() {
+        let transport = QuicTransport::new();
+        let (mut listener, addr) = transport
+            .listen(&"/ip4/127.0.0.1/udp/0/quic".parse().unwrap())
+            .await
+            .unwrap();
+
+        let dialer = QuicTransport::new();
+        let outbound = dialer.dial(&addr).await.unwrap();
+        let (inbound, _) = listener.next().await.unwrap().unwrap();
+
+        let (mut send, _recv) = outbound.open_bi().await.unwrap();
+        send.write_all(b"QUIC").await.unwrap();
+        send.finish().await.unwrap();
+
+        let (_send, mut recv) = inbound.accept_bi().await.unwrap();
+        let buf = recv.read_to_end(1024).await.unwrap();
+        assert_eq!(buf, b"QUIC");
+    }
+
+    #[tokio::test]
+    async fn resolve_invalid_address() {
+        let err = resolve_quic_address(&"/ip4/127.0.0.1/tcp/1234".parse().unwrap())
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    }
+}
@@ -79,6 +79,17 @@ pub fn socketaddr_to_multiaddr(socket_addr: &SocketAddr) -> Multiaddr {
     addr
 }
 
+/// Convert a socket address to a QUIC multiaddress i.e. `/ip4/<ip>/udp/<port>/quic`
+pub fn socketaddr_to_quic_multiaddr(socket_addr: &SocketAddr) -> Multiaddr {
+    let mut addr: Multiaddr = match socket_addr.ip() {
+        IpAddr::V4(addr) => Protocol::Ip4(addr).into(),
+        IpAddr::V6(addr) => Protocol::Ip6(addr).into(),
+    };
+    addr.push(Protocol::Udp(socket_addr.port()));
+    addr.push(Protocol::Quic);
+    addr
+}
+
 #[cfg(test)]
 mod test {
     use std::{net::Ipv4Addr, str::FromStr};
@@ -122,6 +133,12 @@ mod test {
         expect_fail("/dns4/doesntexist.theresnotldlikethis/tcp/1234")
     }
 
+    #[test]
+    fn socketaddr_to_quic_multiaddr_ok() {
+        let addr = super::socketaddr_to_quic_multiaddr(&"127.0.0.1:1234".parse().unwrap());
+        assert_eq!(addr, Multiaddr::from_str("/ip4/127.0.0.1/udp/1234/quic").unwrap());
+    }
+
     #[test]
     fn multiaddr_from_components() {
         let ip: Ipv4Addr = "127.0.0.1".parse().unwrap();
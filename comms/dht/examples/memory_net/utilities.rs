@@ -665,6 +665,14 @@ fn connection_manager_logger(
                     details
                 );
             },
+            ObservedAddress { peer_node_id, address } => {
+                println!(
+                    "'{}' observed '{}' at address '{}'",
+                    get_name(peer_node_id),
+                    node_name,
+                    address
+                );
+            },
         }
         event
     }
@@ -0,0 +1,3 @@
+-- Stored messages are now encrypted at rest. Existing plaintext messages cannot be told apart from encrypted ones,
+-- so they are removed.
+DELETE FROM stored_messages;
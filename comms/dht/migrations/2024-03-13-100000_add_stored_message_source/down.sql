@@ -0,0 +1,4 @@
+DROP INDEX idx_stored_messages_source_node_id;
+
+ALTER TABLE stored_messages
+    DROP COLUMN source_node_id;
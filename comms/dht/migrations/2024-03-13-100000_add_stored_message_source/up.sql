@@ -0,0 +1,4 @@
+ALTER TABLE stored_messages
+    ADD source_node_id TEXT;
+
+CREATE INDEX idx_stored_messages_source_node_id ON stored_messages (source_node_id);
@@ -34,7 +34,7 @@ use futures::{future::BoxFuture, stream::FuturesUnordered, StreamExt};
 use log::*;
 use tari_comms::{
     connection_manager::ConnectionManagerError,
-    connectivity::{ConnectivityError, ConnectivityRequester, ConnectivitySelection},
+    connectivity::{ConnectivityError, ConnectivityRequester, ConnectivitySelection, ReputationEvent},
     peer_manager::{NodeId, NodeIdentity, PeerFeatures, PeerManager, PeerManagerError, PeerQuery, PeerQuerySortBy},
     types::CommsPublicKey,
     PeerConnection,
@@ -132,6 +132,18 @@ pub enum OffenceSeverity {
     High,
 }
 
+impl OffenceSeverity {
+    /// Returns the reputation event for offences that are penalised through the peer's reputation rather than an
+    /// immediate ban. None is returned for offences that warrant an immediate ban.
+    pub fn reputation_event(self) -> Option<ReputationEvent> {
+        match self {
+            OffenceSeverity::Low => Some(ReputationEvent::MinorOffence),
+            OffenceSeverity::Medium => Some(ReputationEvent::InvalidMessage),
+            OffenceSeverity::High => None,
+        }
+    }
+}
+
 impl Display for DhtRequest {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         #[allow(clippy::enum_glob_use)]
@@ -254,6 +266,8 @@ impl DhtRequester {
         reply_rx.await.map_err(|_| DhtActorError::ReplyCanceled)?
     }
 
+    /// Penalises a peer for an offence. High severity offences result in an immediate ban, lower severity offences are
+    /// reported to the peer reputation service, which temporarily bans the peer if its reputation becomes too poor.
     pub async fn ban_peer<T: ToString>(&mut self, public_key: CommsPublicKey, severity: OffenceSeverity, reason: T) {
         if self
             .sender
@@ -480,9 +494,22 @@ impl DhtActor {
                 let mut connectivity = self.connectivity.clone();
                 let ban_duration = self.config.ban_duration_from_severity(severity);
                 Box::pin(async move {
-                    connectivity
-                        .ban_peer_until(NodeId::from_public_key(&public_key), ban_duration, reason)
-                        .await?;
+                    let node_id = NodeId::from_public_key(&public_key);
+                    match severity.reputation_event() {
+                        Some(event) => {
+                            debug!(
+                                target: LOG_TARGET,
+                                "Reporting {} offence for peer {} because: {}",
+                                event,
+                                node_id.short_str(),
+                                reason
+                            );
+                            connectivity.report_peer(node_id, event).await?;
+                        },
+                        None => {
+                            connectivity.ban_peer_until(node_id, ban_duration, reason).await?;
+                        },
+                    }
                     Ok(())
                 })
             },
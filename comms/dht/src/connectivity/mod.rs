@@ -47,6 +47,7 @@ use tari_comms::{
         ConnectivitySelection,
     },
     multiaddr,
+    multiaddr::Multiaddr,
     peer_manager::{NodeDistance, NodeId, PeerManagerError, PeerQuery, PeerQuerySortBy},
     NodeIdentity,
     PeerConnection,
@@ -178,6 +179,9 @@ impl DhtConnectivity {
                     if let Err(err) = self.refresh_random_pool_if_required().await {
                         error!(target: LOG_TARGET, "Error refreshing random peer pool: {:?}", err);
                     }
+                    if let Err(err) = self.send_join_if_addresses_changed().await {
+                        error!(target: LOG_TARGET, "Error announcing changed public addresses: {:?}", err);
+                    }
                     self.log_status();
                     if let Err(err) = self.check_minimum_required_tcp_nodes().await {
                         error!(target: LOG_TARGET, "Error checking minimum required TCP nodes: {:?}", err);
@@ -603,7 +607,7 @@ impl DhtConnectivity {
                         .await
                         .map_err(DhtConnectivityError::SendJoinFailed)?;
 
-                    self.stats.mark_join_sent();
+                    self.stats.mark_join_sent(self.node_identity.public_addresses());
                 }
             },
             ConnectivityStateOffline => {
@@ -800,6 +804,33 @@ impl DhtConnectivity {
         Ok(peers.into_iter().map(|p| p.node_id).collect())
     }
 
+    /// Sends a join message if the public addresses of this node have changed since the last join was sent (e.g. after
+    /// the onion service key was rotated), so that peers learn the re-signed identity claim without waiting for the
+    /// join cooldown to elapse.
+    async fn send_join_if_addresses_changed(&mut self) -> Result<(), DhtConnectivityError> {
+        if !self.config.auto_join {
+            return Ok(());
+        }
+        // If no join has been sent yet, the join sent when the node comes online will contain the current addresses
+        let announced_addresses = match self.stats.join_last_sent_addresses() {
+            Some(addresses) => addresses,
+            None => return Ok(()),
+        };
+        let addresses = self.node_identity.public_addresses();
+        if announced_addresses == addresses.as_slice() {
+            return Ok(());
+        }
+
+        info!(
+            target: LOG_TARGET,
+            "Public addresses changed to [{}]. Sending network join message.",
+            addresses.iter().map(|a| a.to_string()).collect::<Vec<_>>().join(", ")
+        );
+        self.dht_requester.send_join().await?;
+        self.stats.mark_join_sent(addresses);
+        Ok(())
+    }
+
     fn should_send_join(&self) -> bool {
         let cooldown = self.config.join_cooldown_interval;
         self.stats
@@ -810,10 +841,11 @@ impl DhtConnectivity {
 }
 
 /// Basic connectivity stats. Right now, it is only used to track the last time a join message was sent to prevent the
-/// node spamming the network if local connectivity changes.
+/// node spamming the network if local connectivity changes, and the addresses that were announced in it.
 #[derive(Debug, Default)]
 struct Stats {
     join_last_sent_at: Option<Instant>,
+    join_last_sent_addresses: Option<Vec<Multiaddr>>,
 }
 
 impl Stats {
@@ -825,7 +857,12 @@ impl Stats {
         self.join_last_sent_at
     }
 
-    pub fn mark_join_sent(&mut self) {
+    pub fn join_last_sent_addresses(&self) -> Option<&[Multiaddr]> {
+        self.join_last_sent_addresses.as_deref()
+    }
+
+    pub fn mark_join_sent(&mut self, addresses: Vec<Multiaddr>) {
         self.join_last_sent_at = Some(Instant::now());
+        self.join_last_sent_addresses = Some(addresses);
     }
 }
@@ -53,7 +53,13 @@ use crate::{
     rpc,
     storage::{DbConnection, StorageError},
     store_forward,
-    store_forward::{StoreAndForwardError, StoreAndForwardRequest, StoreAndForwardRequester, StoreAndForwardService},
+    store_forward::{
+        SafStorageCipher,
+        StoreAndForwardError,
+        StoreAndForwardRequest,
+        StoreAndForwardRequester,
+        StoreAndForwardService,
+    },
     DedupLayer,
     DhtActorError,
     DhtBuilder,
@@ -239,6 +245,7 @@ impl Dht {
         StoreAndForwardService::new(
             self.config.saf.clone(),
             conn,
+            SafStorageCipher::new(self.node_identity.secret_key()),
             self.peer_manager.clone(),
             self.dht_requester(),
             &self.connectivity.clone(),
@@ -143,6 +143,11 @@ pub fn comms_dht_hash_domain_key_mask() -> DomainSeparatedHasher<Blake2b<U64>, D
     DomainSeparatedHasher::<Blake2b<U64>, DHTCommsHashDomain>::new_with_label("key_mask")
 }
 
+/// Hash domain used to derive the key that encrypts stored messages at rest
+pub fn comms_dht_hash_domain_saf_storage_key() -> DomainSeparatedHasher<Blake2b<U32>, DHTCommsHashDomain> {
+    DomainSeparatedHasher::<Blake2b<U32>, DHTCommsHashDomain>::new_with_label("saf_storage_key")
+}
+
 /// Hash domain used for message signing
 pub fn comms_dht_hash_domain_message_signature() -> DomainSeparatedHasher<Blake2b<U64>, DHTCommsHashDomain> {
     DomainSeparatedHasher::<Blake2b<U64>, DHTCommsHashDomain>::new_with_label("message_signature")
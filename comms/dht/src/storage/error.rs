@@ -40,6 +40,10 @@ pub enum StorageError {
     MessageFormatError(String),
     #[error("Unexpected result: {0}")]
     UnexpectedResult(String),
+    #[error("Failed to encrypt stored message")]
+    EncryptionFailed,
+    #[error("Failed to decrypt stored message")]
+    DecryptionFailed,
     #[error("Diesel R2d2 error: `{0}`")]
     DieselR2d2Error(#[from] SqliteStorageError),
 }
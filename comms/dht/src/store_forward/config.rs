@@ -36,6 +36,20 @@ pub struct SafConfig {
     /// The maximum number of messages that can be stored using the Store-and-forward middleware.
     /// Default: 100,000
     pub msg_storage_capacity: usize,
+    /// The maximum number of messages that can be stored for a single destination. When the quota is reached, the
+    /// oldest messages for the destination are evicted, lowest priority first. A message is not stored if the quota is
+    /// full of messages with a higher priority.
+    /// Default: 1,000
+    pub msg_storage_capacity_per_destination: usize,
+    /// The maximum number of messages received from a single peer that can be stored, whatever their destinations, so
+    /// that a peer sending messages for many destinations can only evict its own messages. Eviction works in the same
+    /// way as for `msg_storage_capacity_per_destination`.
+    /// Default: 10,000 (10% of `msg_storage_capacity`)
+    pub msg_storage_capacity_per_source: usize,
+    /// The maximum number of messages without a destination that can be stored. Eviction works in the same way as for
+    /// `msg_storage_capacity_per_destination`.
+    /// Default: 10,000
+    pub anonymous_msg_storage_capacity: usize,
     /// A request to retrieve stored messages will be ignored if the requesting node is
     /// not within one of this nodes _n_ closest nodes.
     /// Default 8
@@ -70,6 +84,9 @@ impl Default for SafConfig {
             num_closest_nodes: 10,
             max_returned_messages: 50,
             msg_storage_capacity: 100_000,
+            msg_storage_capacity_per_destination: 1_000,
+            msg_storage_capacity_per_source: 10_000,
+            anonymous_msg_storage_capacity: 10_000,
             low_priority_msg_storage_ttl: Duration::from_secs(6 * 60 * 60), // 6 hours
             high_priority_msg_storage_ttl: Duration::from_secs(3 * 24 * 60 * 60), // 3 days
             auto_request: true,
@@ -0,0 +1,112 @@
+// Copyright 2024. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::mem::size_of;
+
+use chacha20poly1305::{
+    aead::{Aead, Payload},
+    KeyInit,
+    XChaCha20Poly1305,
+    XNonce,
+};
+use digest::{generic_array::GenericArray, FixedOutput};
+use rand::{rngs::OsRng, RngCore};
+use tari_comms::types::CommsSecretKey;
+use tari_crypto::tari_utilities::ByteArray;
+use tari_utilities::{hidden_type, safe_array::SafeArray, Hidden};
+use zeroize::Zeroize;
+
+use crate::{comms_dht_hash_domain_saf_storage_key, storage::StorageError};
+
+// `XChaCha20` key used to encrypt stored messages at rest
+hidden_type!(SafStorageKey, SafeArray<u8, { size_of::<chacha20poly1305::Key>() }>);
+
+/// Encrypts the header and body of stored messages. The key is derived from this node's secret key, so the stored
+/// messages can only be read by this node.
+#[derive(Clone)]
+pub struct SafStorageCipher {
+    cipher: XChaCha20Poly1305,
+}
+
+impl SafStorageCipher {
+    pub fn new(secret_key: &CommsSecretKey) -> Self {
+        let mut key = SafStorageKey::from(SafeArray::default());
+        FixedOutput::finalize_into(
+            comms_dht_hash_domain_saf_storage_key().chain(secret_key.as_bytes()),
+            GenericArray::from_mut_slice(key.reveal_mut()),
+        );
+        Self {
+            cipher: XChaCha20Poly1305::new(GenericArray::from_slice(key.reveal())),
+        }
+    }
+
+    /// Encrypts the plaintext, returning the nonce followed by the ciphertext and tag
+    pub fn encrypt(&self, plaintext: &[u8], associated_data: &[u8]) -> Result<Vec<u8>, StorageError> {
+        let mut nonce = XNonce::default();
+        OsRng.fill_bytes(&mut nonce);
+        let ciphertext = self
+            .cipher
+            .encrypt(&nonce, Payload {
+                msg: plaintext,
+                aad: associated_data,
+            })
+            .map_err(|_| StorageError::EncryptionFailed)?;
+        let mut encrypted = Vec::with_capacity(nonce.len() + ciphertext.len());
+        encrypted.extend_from_slice(&nonce);
+        encrypted.extend_from_slice(&ciphertext);
+        Ok(encrypted)
+    }
+
+    /// Decrypts a value produced by [SafStorageCipher::encrypt]
+    pub fn decrypt(&self, encrypted: &[u8], associated_data: &[u8]) -> Result<Vec<u8>, StorageError> {
+        if encrypted.len() < size_of::<XNonce>() {
+            return Err(StorageError::DecryptionFailed);
+        }
+        let (nonce, ciphertext) = encrypted.split_at(size_of::<XNonce>());
+        self.cipher
+            .decrypt(XNonce::from_slice(nonce), Payload {
+                msg: ciphertext,
+                aad: associated_data,
+            })
+            .map_err(|_| StorageError::DecryptionFailed)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use tari_crypto::keys::SecretKey;
+
+    use super::*;
+
+    #[test]
+    fn it_encrypts_and_decrypts() {
+        let cipher = SafStorageCipher::new(&CommsSecretKey::random(&mut OsRng));
+        let encrypted = cipher.encrypt(b"stored message", b"hash").unwrap();
+        assert_ne!(&encrypted[size_of::<XNonce>()..], b"stored message");
+        assert_eq!(cipher.decrypt(&encrypted, b"hash").unwrap(), b"stored message");
+        cipher.decrypt(&encrypted, b"other hash").unwrap_err();
+        cipher.decrypt(&encrypted[..10], b"hash").unwrap_err();
+
+        let other_cipher = SafStorageCipher::new(&CommsSecretKey::random(&mut OsRng));
+        other_cipher.decrypt(&encrypted, b"hash").unwrap_err();
+    }
+}
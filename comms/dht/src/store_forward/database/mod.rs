@@ -20,9 +20,23 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
+mod encryption;
+pub use encryption::SafStorageCipher;
+
 mod stored_message;
 use chrono::{DateTime, NaiveDateTime, Utc};
-use diesel::{dsl, result::DatabaseErrorKind, BoolExpressionMethods, ExpressionMethods, QueryDsl, RunQueryDsl};
+use diesel::{
+    dsl,
+    result::DatabaseErrorKind,
+    sql_types,
+    BoolExpressionMethods,
+    Connection,
+    ExpressionMethods,
+    QueryDsl,
+    RunQueryDsl,
+    SqliteConnection,
+};
+use log::*;
 pub use stored_message::{NewStoredMessage, StoredMessage};
 use tari_comms::{peer_manager::NodeId, types::CommsPublicKey};
 use tari_utilities::hex::Hex;
@@ -31,20 +45,29 @@ use crate::{
     envelope::DhtMessageType,
     schema::stored_messages,
     storage::{DbConnection, StorageError},
-    store_forward::message::StoredMessagePriority,
+    store_forward::{
+        message::StoredMessagePriority,
+        stats::{SafDestinationStats, SafStorageStats},
+    },
 };
 
+const LOG_TARGET: &str = "comms::dht::storeforward::database";
+
 pub struct StoreAndForwardDatabase {
     connection: DbConnection,
+    cipher: SafStorageCipher,
 }
 
 impl StoreAndForwardDatabase {
-    pub fn new(connection: DbConnection) -> Self {
-        Self { connection }
+    pub fn new(connection: DbConnection, cipher: SafStorageCipher) -> Self {
+        Self { connection, cipher }
     }
 
-    /// Inserts and returns Ok(true) if the item already existed and Ok(false) if it didn't
-    pub fn insert_message_if_unique(&self, message: NewStoredMessage) -> Result<bool, StorageError> {
+    /// Inserts and returns Ok(true) if the item already existed and Ok(false) if it didn't. The header and body of the
+    /// message are encrypted before they are stored.
+    pub fn insert_message_if_unique(&self, mut message: NewStoredMessage) -> Result<bool, StorageError> {
+        message.header = self.cipher.encrypt(&message.header, message.body_hash.as_bytes())?;
+        message.body = self.cipher.encrypt(&message.body, message.body_hash.as_bytes())?;
         let mut conn = self.connection.get_pooled_connection()?;
         match diesel::insert_into(stored_messages::table)
             .values(message)
@@ -59,6 +82,52 @@ impl StoreAndForwardDatabase {
         }
     }
 
+    /// Returns true if a message with the given body hash is stored
+    pub fn message_exists(&self, body_hash: &str) -> Result<bool, StorageError> {
+        let mut conn = self.connection.get_pooled_connection()?;
+        let count = stored_messages::table
+            .filter(stored_messages::body_hash.eq(body_hash))
+            .count()
+            .get_result::<i64>(&mut conn)?;
+        Ok(count > 0)
+    }
+
+    /// Evicts stored messages until there is room for the given message within each of the quotas. Messages with the
+    /// lowest priority are evicted first, oldest first, and messages with a higher priority than the given message are
+    /// never evicted. Nothing is evicted if any of the quotas is full of messages with a higher priority. Returns the
+    /// number of evicted messages, or None if there is no room for the message.
+    pub fn make_room_for_message(
+        &self,
+        message: &NewStoredMessage,
+        quotas: &[(QuotaScope<'_>, usize)],
+    ) -> Result<Option<usize>, StorageError> {
+        let mut conn = self.connection.get_pooled_connection()?;
+        let result = conn.transaction::<_, diesel::result::Error, _>(|conn| {
+            let mut num_removed = 0;
+            for (scope, quota) in quotas {
+                let count = count_messages_in(conn, *scope)?;
+                if count < *quota {
+                    continue;
+                }
+                // Make room for one more message
+                let remove_count = count + 1 - quota;
+                let message_ids = evictable_messages_in(conn, *scope, message.priority, remove_count)?;
+                if message_ids.len() < remove_count {
+                    return Err(diesel::result::Error::RollbackTransaction);
+                }
+                num_removed += diesel::delete(stored_messages::table)
+                    .filter(stored_messages::id.eq_any(message_ids))
+                    .execute(conn)?;
+            }
+            Ok(num_removed)
+        });
+        match result {
+            Ok(num_removed) => Ok(Some(num_removed)),
+            Err(diesel::result::Error::RollbackTransaction) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
     pub fn remove_message(&self, message_ids: Vec<i32>) -> Result<usize, StorageError> {
         let mut conn = self.connection.get_pooled_connection()?;
         diesel::delete(stored_messages::table)
@@ -91,11 +160,11 @@ impl StoreAndForwardDatabase {
             query = query.filter(stored_messages::stored_at.gt(since.naive_utc()));
         }
 
-        query
+        let messages = query
             .order_by(stored_messages::stored_at.desc())
             .limit(limit)
-            .get_results(&mut conn)
-            .map_err(Into::into)
+            .get_results(&mut conn)?;
+        self.decrypt_messages(&mut conn, messages)
     }
 
     pub fn find_anonymous_messages(
@@ -116,11 +185,11 @@ impl StoreAndForwardDatabase {
             query = query.filter(stored_messages::stored_at.gt(since.naive_utc()));
         }
 
-        query
+        let messages = query
             .order_by(stored_messages::stored_at.desc())
             .limit(limit)
-            .get_results(&mut conn)
-            .map_err(Into::into)
+            .get_results(&mut conn)?;
+        self.decrypt_messages(&mut conn, messages)
     }
 
     pub fn find_join_messages(
@@ -138,11 +207,11 @@ impl StoreAndForwardDatabase {
             query = query.filter(stored_messages::stored_at.gt(since.naive_utc()));
         }
 
-        query
+        let messages = query
             .order_by(stored_messages::stored_at.desc())
             .limit(limit)
-            .get_results(&mut conn)
-            .map_err(Into::into)
+            .get_results(&mut conn)?;
+        self.decrypt_messages(&mut conn, messages)
     }
 
     pub fn find_messages_of_type_for_pubkey(
@@ -164,20 +233,101 @@ impl StoreAndForwardDatabase {
             query = query.filter(stored_messages::stored_at.gt(since.naive_utc()));
         }
 
-        query
+        let messages = query
             .order_by(stored_messages::stored_at.desc())
             .limit(limit)
-            .get_results(&mut conn)
-            .map_err(Into::into)
+            .get_results(&mut conn)?;
+        self.decrypt_messages(&mut conn, messages)
     }
 
     #[cfg(test)]
     pub(crate) fn get_all_messages(&self) -> Result<Vec<StoredMessage>, StorageError> {
         let mut conn = self.connection.get_pooled_connection()?;
-        stored_messages::table
+        let messages = stored_messages::table
             .select(stored_messages::all_columns)
-            .get_results(&mut conn)
-            .map_err(Into::into)
+            .get_results(&mut conn)?;
+        self.decrypt_messages(&mut conn, messages)
+    }
+
+    /// Decrypts the header and body of the given messages. Messages that cannot be decrypted, for instance because
+    /// the node identity has changed since they were stored, are deleted.
+    fn decrypt_messages(
+        &self,
+        conn: &mut SqliteConnection,
+        messages: Vec<StoredMessage>,
+    ) -> Result<Vec<StoredMessage>, StorageError> {
+        let mut undecryptable = Vec::new();
+        let messages = messages
+            .into_iter()
+            .filter_map(|mut message| {
+                let associated_data = message.body_hash.as_bytes();
+                match (
+                    self.cipher.decrypt(&message.header, associated_data),
+                    self.cipher.decrypt(&message.body, associated_data),
+                ) {
+                    (Ok(header), Ok(body)) => {
+                        message.header = header;
+                        message.body = body;
+                        Some(message)
+                    },
+                    _ => {
+                        undecryptable.push(message.id);
+                        None
+                    },
+                }
+            })
+            .collect();
+        if !undecryptable.is_empty() {
+            warn!(
+                target: LOG_TARGET,
+                "Deleting {} stored message(s) that could not be decrypted",
+                undecryptable.len()
+            );
+            diesel::delete(stored_messages::table)
+                .filter(stored_messages::id.eq_any(undecryptable))
+                .execute(conn)?;
+        }
+        Ok(messages)
+    }
+
+    /// Returns storage statistics, including the `num_destinations` destinations with the most stored messages
+    pub(crate) fn get_stats(&self, num_destinations: usize) -> Result<SafStorageStats, StorageError> {
+        let mut conn = self.connection.get_pooled_connection()?;
+        let num_low_priority = stored_messages::table
+            .filter(stored_messages::priority.eq(StoredMessagePriority::Low as i32))
+            .count()
+            .get_result::<i64>(&mut conn)?;
+        let num_high_priority = stored_messages::table
+            .filter(stored_messages::priority.eq(StoredMessagePriority::High as i32))
+            .count()
+            .get_result::<i64>(&mut conn)?;
+
+        let destinations = diesel::sql_query(
+            "SELECT destination_node_id, COUNT(*) AS num_messages, SUM(LENGTH(header) + LENGTH(body)) AS total_bytes \
+             FROM stored_messages GROUP BY destination_node_id ORDER BY num_messages DESC",
+        )
+        .load::<DestinationStatsRow>(&mut conn)?;
+
+        #[allow(clippy::cast_sign_loss)]
+        let stats = SafStorageStats {
+            num_messages: (num_low_priority + num_high_priority) as usize,
+            num_low_priority: num_low_priority as usize,
+            num_high_priority: num_high_priority as usize,
+            total_bytes: destinations.iter().map(|d| d.total_bytes as u64).sum(),
+            num_destinations: destinations.iter().filter(|d| d.destination_node_id.is_some()).count(),
+            top_destinations: destinations
+                .into_iter()
+                .take(num_destinations)
+                .map(|d| SafDestinationStats {
+                    destination: d
+                        .destination_node_id
+                        .and_then(|node_id| NodeId::from_hex(&node_id).ok()),
+                    num_messages: d.num_messages as usize,
+                    total_bytes: d.total_bytes as u64,
+                })
+                .collect(),
+        };
+        Ok(stats)
     }
 
     pub(crate) fn delete_messages_with_priority_older_than(
@@ -201,23 +351,18 @@ impl StoreAndForwardDatabase {
             .map_err(Into::into)
     }
 
+    /// Removes messages until no more than `max_size` messages are stored. All low priority messages are removed,
+    /// oldest first, before any high priority message is removed.
     pub(crate) fn truncate_messages(&self, max_size: usize) -> Result<usize, StorageError> {
-        let mut num_removed = 0;
         let mut conn = self.connection.get_pooled_connection()?;
-        let max_size = max_size as u64;
-        #[allow(clippy::cast_sign_loss)]
-        let msg_count = stored_messages::table
-            .select(dsl::count(stored_messages::id))
-            .first::<i64>(&mut conn)? as u64;
-        if msg_count > max_size {
-            let remove_count = msg_count - max_size;
-            #[allow(clippy::cast_possible_wrap)]
-            let message_ids: Vec<i32> = stored_messages::table
-                .select(stored_messages::id)
-                .order_by(stored_messages::stored_at.asc())
-                .limit(remove_count as i64)
-                .get_results(&mut conn)?;
-            num_removed = diesel::delete(stored_messages::table)
+        let mut num_removed = 0;
+        for priority in [StoredMessagePriority::Low, StoredMessagePriority::High] {
+            let msg_count = count_messages_in(&mut conn, QuotaScope::All)?;
+            if msg_count <= max_size {
+                break;
+            }
+            let message_ids = evictable_messages_in(&mut conn, QuotaScope::All, priority as i32, msg_count - max_size)?;
+            num_removed += diesel::delete(stored_messages::table)
                 .filter(stored_messages::id.eq_any(message_ids))
                 .execute(&mut conn)?;
         }
@@ -225,17 +370,95 @@ impl StoreAndForwardDatabase {
     }
 }
 
+/// The stored messages that a storage quota applies to
+#[derive(Debug, Clone, Copy)]
+pub enum QuotaScope<'a> {
+    /// Messages for the destination with the given hex node id
+    Destination(&'a str),
+    /// Messages without a destination
+    Anonymous,
+    /// Messages received from the peer with the given hex node id, whatever their destination
+    Source(&'a str),
+    /// All stored messages
+    All,
+}
+
+fn count_messages_in(conn: &mut SqliteConnection, scope: QuotaScope<'_>) -> Result<usize, diesel::result::Error> {
+    let query = stored_messages::table
+        .select(dsl::count(stored_messages::id))
+        .into_boxed();
+    let query = match scope {
+        QuotaScope::Destination(node_id) => query.filter(stored_messages::destination_node_id.eq(node_id)),
+        QuotaScope::Anonymous => query.filter(stored_messages::destination_node_id.is_null()),
+        QuotaScope::Source(node_id) => query.filter(stored_messages::source_node_id.eq(node_id)),
+        QuotaScope::All => query,
+    };
+    let count = query.first::<i64>(conn)?;
+    Ok(usize::try_from(count).unwrap_or(usize::MAX))
+}
+
+/// The ids of up to `limit` messages in the scope with a priority no higher than `max_priority`, in the order that
+/// they are evicted: lowest priority first, oldest first
+fn evictable_messages_in(
+    conn: &mut SqliteConnection,
+    scope: QuotaScope<'_>,
+    max_priority: i32,
+    limit: usize,
+) -> Result<Vec<i32>, diesel::result::Error> {
+    let query = stored_messages::table
+        .select(stored_messages::id)
+        .filter(stored_messages::priority.le(max_priority))
+        .into_boxed();
+    let query = match scope {
+        QuotaScope::Destination(node_id) => query.filter(stored_messages::destination_node_id.eq(node_id)),
+        QuotaScope::Anonymous => query.filter(stored_messages::destination_node_id.is_null()),
+        QuotaScope::Source(node_id) => query.filter(stored_messages::source_node_id.eq(node_id)),
+        QuotaScope::All => query,
+    };
+    query
+        .order_by((stored_messages::priority.asc(), stored_messages::stored_at.asc()))
+        .limit(i64::try_from(limit).unwrap_or(i64::MAX))
+        .get_results(conn)
+}
+
+#[derive(QueryableByName)]
+struct DestinationStatsRow {
+    #[diesel(sql_type = sql_types::Nullable<sql_types::Text>)]
+    destination_node_id: Option<String>,
+    #[diesel(sql_type = sql_types::BigInt)]
+    num_messages: i64,
+    #[diesel(sql_type = sql_types::BigInt)]
+    total_bytes: i64,
+}
+
 #[cfg(test)]
 mod test {
+    use rand::rngs::OsRng;
+    use tari_comms::types::CommsSecretKey;
+    use tari_crypto::keys::SecretKey;
     use tari_test_utils::random;
 
     use super::*;
 
-    #[tokio::test]
-    async fn insert_messages() {
+    fn create_database() -> StoreAndForwardDatabase {
         let conn = DbConnection::connect_memory(random::string(8)).unwrap();
         conn.migrate().unwrap();
-        let db = StoreAndForwardDatabase::new(conn);
+        StoreAndForwardDatabase::new(conn, SafStorageCipher::new(&CommsSecretKey::random(&mut OsRng)))
+    }
+
+    fn new_message(body_hash: &str, destination: Option<&str>, priority: StoredMessagePriority) -> NewStoredMessage {
+        NewStoredMessage {
+            destination_node_id: destination.map(ToString::to_string),
+            priority: priority as i32,
+            body_hash: body_hash.to_string(),
+            body: b"body".to_vec(),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn insert_messages() {
+        let db = create_database();
         let mut msg1 = NewStoredMessage::default();
         msg1.body_hash.push('1');
         let mut msg2 = NewStoredMessage::default();
@@ -253,9 +476,7 @@ mod test {
 
     #[tokio::test]
     async fn remove_messages() {
-        let conn = DbConnection::connect_memory(random::string(8)).unwrap();
-        conn.migrate().unwrap();
-        let db = StoreAndForwardDatabase::new(conn);
+        let db = create_database();
         // Create 3 unique messages
         let mut msg1 = NewStoredMessage::default();
         msg1.body_hash.push('1');
@@ -280,9 +501,7 @@ mod test {
 
     #[tokio::test]
     async fn truncate_messages() {
-        let conn = DbConnection::connect_memory(random::string(8)).unwrap();
-        conn.migrate().unwrap();
-        let db = StoreAndForwardDatabase::new(conn);
+        let db = create_database();
         let mut msg1 = NewStoredMessage::default();
         msg1.body_hash.push('1');
         let mut msg2 = NewStoredMessage::default();
@@ -302,4 +521,137 @@ mod test {
         assert_eq!(messages[0].body_hash, msg3.body_hash);
         assert_eq!(messages[1].body_hash, msg4.body_hash);
     }
+
+    #[tokio::test]
+    async fn messages_are_encrypted_at_rest() {
+        let conn = DbConnection::connect_memory(random::string(8)).unwrap();
+        conn.migrate().unwrap();
+        let db = StoreAndForwardDatabase::new(conn.clone(), SafStorageCipher::new(&CommsSecretKey::random(&mut OsRng)));
+        db.insert_message_if_unique(new_message("1", None, StoredMessagePriority::Low))
+            .unwrap();
+
+        let raw: Vec<StoredMessage> = stored_messages::table
+            .select(stored_messages::all_columns)
+            .get_results(&mut conn.get_pooled_connection().unwrap())
+            .unwrap();
+        assert_ne!(raw[0].body, b"body");
+        let messages = db.get_all_messages().unwrap();
+        assert_eq!(messages[0].body, b"body");
+
+        // Messages encrypted with a different key are deleted
+        let db = StoreAndForwardDatabase::new(conn.clone(), SafStorageCipher::new(&CommsSecretKey::random(&mut OsRng)));
+        assert!(db.get_all_messages().unwrap().is_empty());
+        let count = stored_messages::table
+            .count()
+            .get_result::<i64>(&mut conn.get_pooled_connection().unwrap())
+            .unwrap();
+        assert_eq!(count, 0);
+    }
+
+    #[tokio::test]
+    async fn make_room_for_message() {
+        let db = create_database();
+        db.insert_message_if_unique(new_message("1", Some("aa01"), StoredMessagePriority::High))
+            .unwrap();
+        db.insert_message_if_unique(new_message("2", Some("aa01"), StoredMessagePriority::Low))
+            .unwrap();
+        db.insert_message_if_unique(new_message("3", Some("aa02"), StoredMessagePriority::Low))
+            .unwrap();
+
+        // Another destination's quota is not affected
+        let msg = new_message("4", Some("aa03"), StoredMessagePriority::Low);
+        assert_eq!(
+            db.make_room_for_message(&msg, &[(QuotaScope::Destination("aa03"), 2)])
+                .unwrap(),
+            Some(0)
+        );
+        // The low priority message is evicted before the older high priority message
+        let msg = new_message("5", Some("aa01"), StoredMessagePriority::High);
+        assert_eq!(
+            db.make_room_for_message(&msg, &[(QuotaScope::Destination("aa01"), 2)])
+                .unwrap(),
+            Some(1)
+        );
+        db.insert_message_if_unique(msg).unwrap();
+        let hashes = db
+            .get_all_messages()
+            .unwrap()
+            .into_iter()
+            .map(|m| m.body_hash)
+            .collect::<Vec<_>>();
+        assert_eq!(hashes, vec!["1", "3", "5"]);
+        // A low priority message cannot evict high priority messages, and nothing is evicted from the other quotas if
+        // one of them is full
+        let msg = new_message("6", Some("aa01"), StoredMessagePriority::Low);
+        assert_eq!(
+            db.make_room_for_message(&msg, &[(QuotaScope::All, 2), (QuotaScope::Destination("aa01"), 2)])
+                .unwrap(),
+            None
+        );
+        assert_eq!(db.get_all_messages().unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn make_room_for_message_from_source() {
+        let db = create_database();
+        let from = |body_hash: &str, destination: &str, source: &str| NewStoredMessage {
+            source_node_id: Some(source.to_string()),
+            ..new_message(body_hash, Some(destination), StoredMessagePriority::Low)
+        };
+        db.insert_message_if_unique(from("1", "aa01", "bb01")).unwrap();
+        db.insert_message_if_unique(from("2", "aa02", "bb02")).unwrap();
+        db.insert_message_if_unique(from("3", "aa03", "bb02")).unwrap();
+
+        // A peer that sends messages for many destinations only evicts its own messages
+        let msg = from("4", "aa04", "bb02");
+        let quotas = [(QuotaScope::Destination("aa04"), 2), (QuotaScope::Source("bb02"), 2)];
+        assert_eq!(db.make_room_for_message(&msg, &quotas).unwrap(), Some(1));
+        db.insert_message_if_unique(msg).unwrap();
+        let hashes = db
+            .get_all_messages()
+            .unwrap()
+            .into_iter()
+            .map(|m| m.body_hash)
+            .collect::<Vec<_>>();
+        assert_eq!(hashes, vec!["1", "3", "4"]);
+    }
+
+    #[tokio::test]
+    async fn truncate_messages_by_priority() {
+        let db = create_database();
+        db.insert_message_if_unique(new_message("1", None, StoredMessagePriority::High))
+            .unwrap();
+        db.insert_message_if_unique(new_message("2", None, StoredMessagePriority::Low))
+            .unwrap();
+        db.insert_message_if_unique(new_message("3", None, StoredMessagePriority::High))
+            .unwrap();
+        db.insert_message_if_unique(new_message("4", None, StoredMessagePriority::Low))
+            .unwrap();
+        assert_eq!(db.truncate_messages(1).unwrap(), 3);
+        let messages = db.get_all_messages().unwrap();
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0].body_hash, "3");
+    }
+
+    #[tokio::test]
+    async fn get_stats() {
+        let db = create_database();
+        let node_id = NodeId::default().to_hex();
+        db.insert_message_if_unique(new_message("1", Some(&node_id), StoredMessagePriority::High))
+            .unwrap();
+        db.insert_message_if_unique(new_message("2", Some(&node_id), StoredMessagePriority::Low))
+            .unwrap();
+        db.insert_message_if_unique(new_message("3", None, StoredMessagePriority::Low))
+            .unwrap();
+
+        let stats = db.get_stats(1).unwrap();
+        assert_eq!(stats.num_messages, 3);
+        assert_eq!(stats.num_low_priority, 2);
+        assert_eq!(stats.num_high_priority, 1);
+        assert_eq!(stats.num_destinations, 1);
+        assert!(stats.total_bytes > 0);
+        assert_eq!(stats.top_destinations.len(), 1);
+        assert_eq!(stats.top_destinations[0].destination, Some(NodeId::default()));
+        assert_eq!(stats.top_destinations[0].num_messages, 2);
+    }
 }
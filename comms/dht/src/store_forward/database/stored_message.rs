@@ -45,6 +45,7 @@ pub struct NewStoredMessage {
     pub is_encrypted: bool,
     pub priority: i32,
     pub body_hash: String,
+    pub source_node_id: Option<String>,
 }
 
 impl NewStoredMessage {
@@ -53,6 +54,7 @@ impl NewStoredMessage {
     #[allow(clippy::cast_possible_wrap)]
     pub fn new(message: DecryptedDhtMessage, priority: StoredMessagePriority) -> Self {
         let DecryptedDhtMessage {
+            source_peer,
             authenticated_origin,
             decryption_result,
             dht_header,
@@ -82,6 +84,7 @@ impl NewStoredMessage {
             },
             body_hash,
             body,
+            source_node_id: Some(source_peer.node_id.to_hex()),
         }
     }
 }
@@ -100,4 +103,6 @@ pub struct StoredMessage {
     pub priority: i32,
     pub stored_at: NaiveDateTime,
     pub body_hash: String,
+    /// The peer that the message was received from
+    pub source_node_id: Option<String>,
 }
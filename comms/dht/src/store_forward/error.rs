@@ -87,6 +87,8 @@ pub enum StoreAndForwardError {
     SafMessagesReceivedAfterDeadline { peer: NodeId, message_age: Duration },
     #[error("Invalid SAF request: `stored_at` cannot be in the future")]
     StoredAtWasInFuture,
+    #[error("The storage quota for the message destination is full of higher priority messages")]
+    StorageQuotaExceeded,
     #[error("Invariant error (POSSIBLE BUG): {0}")]
     InvariantError(String),
 }
@@ -28,6 +28,7 @@ mod service;
 pub use service::{StoreAndForwardRequest, StoreAndForwardRequester, StoreAndForwardService};
 
 mod database;
+pub(crate) use database::SafStorageCipher;
 pub use database::StoredMessage;
 
 mod error;
@@ -43,5 +44,8 @@ pub use saf_handler::MessageHandlerLayer;
 
 mod local_state;
 
+mod stats;
+pub use stats::{SafDestinationStats, SafStorageStats};
+
 mod store;
 pub use store::StoreLayer;
@@ -800,6 +800,7 @@ mod test {
             priority: StoredMessagePriority::High as i32,
             stored_at,
             body_hash: msg_hash,
+            source_node_id: None,
         }
     }
 
@@ -43,9 +43,10 @@ use tokio::{
 };
 
 use super::{
-    database::{NewStoredMessage, StoreAndForwardDatabase, StoredMessage},
+    database::{NewStoredMessage, QuotaScope, SafStorageCipher, StoreAndForwardDatabase, StoredMessage},
     message::StoredMessagePriority,
     SafResult,
+    SafStorageStats,
     StoreAndForwardError,
 };
 use crate::{
@@ -120,6 +121,7 @@ pub enum StoreAndForwardRequest {
     SendStoreForwardRequestToPeer(NodeId),
     SendStoreForwardRequestNeighbours,
     MarkSafResponseReceived(NodeId, oneshot::Sender<Option<Duration>>),
+    GetStorageStats(usize, oneshot::Sender<SafResult<SafStorageStats>>),
 }
 
 /// Store and forward actor handle.
@@ -189,6 +191,17 @@ impl StoreAndForwardRequester {
         Ok(())
     }
 
+    /// Returns statistics for the local storage DB, including the `num_destinations` destinations with the most stored
+    /// messages.
+    pub async fn get_storage_stats(&mut self, num_destinations: usize) -> SafResult<SafStorageStats> {
+        let (reply_tx, reply_rx) = oneshot::channel();
+        self.sender
+            .send(StoreAndForwardRequest::GetStorageStats(num_destinations, reply_tx))
+            .await
+            .map_err(|_| StoreAndForwardError::RequesterChannelClosed)?;
+        reply_rx.await.map_err(|_| StoreAndForwardError::RequestCancelled)?
+    }
+
     /// Updates internal SAF state that a SAF response has been received, removing it from the pending list.
     pub(crate) async fn mark_saf_response_received(&mut self, peer: NodeId) -> SafResult<Option<Duration>> {
         let (reply_tx, reply_rx) = oneshot::channel();
@@ -222,6 +235,7 @@ impl StoreAndForwardService {
     pub(crate) fn new(
         config: SafConfig,
         conn: DbConnection,
+        cipher: SafStorageCipher,
         peer_manager: Arc<PeerManager>,
         dht_requester: DhtRequester,
         connectivity: &ConnectivityRequester,
@@ -233,7 +247,7 @@ impl StoreAndForwardService {
     ) -> Self {
         Self {
             config,
-            database: StoreAndForwardDatabase::new(conn),
+            database: StoreAndForwardDatabase::new(conn, cipher),
             peer_manager,
             dht_requester,
             request_rx,
@@ -310,26 +324,12 @@ impl StoreAndForwardService {
                 },
             },
             InsertMessage(msg, reply_tx) => {
-                let public_key = msg.destination_pubkey.clone();
-                let node_id = msg.destination_node_id.clone();
-                match self.database.insert_message_if_unique(msg) {
-                    Ok(existed) => {
-                        let pub_key = public_key
-                            .map(|p| format!("public key '{}'", p))
-                            .or_else(|| node_id.map(|n| format!("node id '{}'", n)))
-                            .unwrap_or_else(|| "<Anonymous>".to_string());
-                        if existed {
-                            info!(target: LOG_TARGET, "SAF message for {} already stored", pub_key);
-                        } else {
-                            info!(target: LOG_TARGET, "Stored message for {}", pub_key);
-                        }
-                        let _result = reply_tx.send(Ok(existed));
-                    },
-                    Err(err) => {
-                        error!(target: LOG_TARGET, "InsertMessage failed because '{:?}'", err);
-                        let _result = reply_tx.send(Err(err.into()));
-                    },
+                let result = self.insert_message(msg);
+                match &result {
+                    Ok(_) | Err(StoreAndForwardError::StorageQuotaExceeded) => {},
+                    Err(err) => error!(target: LOG_TARGET, "InsertMessage failed because '{:?}'", err),
                 }
+                let _result = reply_tx.send(result);
             },
             RemoveMessages(message_ids) => match self.database.remove_message(message_ids.clone()) {
                 Ok(_) => trace!(target: LOG_TARGET, "Removed messages: {:?}", message_ids),
@@ -357,7 +357,62 @@ impl StoreAndForwardService {
             MarkSafResponseReceived(peer, reply) => {
                 let _ = reply.send(self.local_state.mark_infight_response_received(peer));
             },
+            GetStorageStats(num_destinations, reply) => {
+                let _result = reply.send(self.database.get_stats(num_destinations).map_err(Into::into));
+            },
+        }
+    }
+
+    /// Stores the message if it is unique and there is room for it in the quotas of its destination, of the peer it
+    /// was received from and of the whole store. Returns true if the message was already stored.
+    fn insert_message(&mut self, msg: NewStoredMessage) -> SafResult<bool> {
+        let destination = msg
+            .destination_pubkey
+            .as_ref()
+            .map(|p| format!("public key '{}'", p))
+            .or_else(|| msg.destination_node_id.as_ref().map(|n| format!("node id '{}'", n)))
+            .unwrap_or_else(|| "<Anonymous>".to_string());
+
+        if self.database.message_exists(&msg.body_hash)? {
+            info!(target: LOG_TARGET, "SAF message for {} already stored", destination);
+            return Ok(true);
+        }
+
+        let mut quotas = vec![match msg.destination_node_id {
+            Some(ref node_id) => (
+                QuotaScope::Destination(node_id),
+                self.config.msg_storage_capacity_per_destination,
+            ),
+            None => (QuotaScope::Anonymous, self.config.anonymous_msg_storage_capacity),
+        }];
+        if let Some(ref node_id) = msg.source_node_id {
+            quotas.push((QuotaScope::Source(node_id), self.config.msg_storage_capacity_per_source));
+        }
+        quotas.push((QuotaScope::All, self.config.msg_storage_capacity));
+        match self.database.make_room_for_message(&msg, &quotas)? {
+            Some(0) => {},
+            Some(num_evicted) => {
+                debug!(
+                    target: LOG_TARGET,
+                    "Storage quota reached for {}, evicted {} message(s)", destination, num_evicted
+                );
+            },
+            None => {
+                info!(
+                    target: LOG_TARGET,
+                    "Not storing SAF message for {} because its storage quota is full", destination
+                );
+                return Err(StoreAndForwardError::StorageQuotaExceeded);
+            },
+        }
+
+        let existed = self.database.insert_message_if_unique(msg)?;
+        if existed {
+            info!(target: LOG_TARGET, "SAF message for {} already stored", destination);
+        } else {
+            info!(target: LOG_TARGET, "Stored message for {}", destination);
         }
+        Ok(existed)
     }
 
     async fn handle_connectivity_event(&mut self, event: &ConnectivityEvent) -> SafResult<()> {
@@ -522,7 +577,7 @@ impl StoreAndForwardService {
         if num_removed > 0 {
             debug!(
                 target: LOG_TARGET,
-                "Storage limits exceeded, removing {} oldest messages", num_removed
+                "Storage limits exceeded, removed {} messages, lowest priority first", num_removed
             );
         }
 
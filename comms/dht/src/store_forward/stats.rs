@@ -0,0 +1,51 @@
+// Copyright 2024. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use tari_comms::peer_manager::NodeId;
+
+/// Store and forward storage statistics
+#[derive(Debug, Clone, Default)]
+pub struct SafStorageStats {
+    /// The total number of stored messages
+    pub num_messages: usize,
+    /// The number of stored low priority messages
+    pub num_low_priority: usize,
+    /// The number of stored high priority messages
+    pub num_high_priority: usize,
+    /// The total size of all stored messages in bytes, as stored (i.e. encrypted)
+    pub total_bytes: u64,
+    /// The number of distinct destinations for which messages are stored
+    pub num_destinations: usize,
+    /// The destinations with the most stored messages, in descending order
+    pub top_destinations: Vec<SafDestinationStats>,
+}
+
+/// Storage statistics for a single destination
+#[derive(Debug, Clone)]
+pub struct SafDestinationStats {
+    /// The destination node id, or None for messages without a destination
+    pub destination: Option<NodeId>,
+    /// The number of messages stored for this destination
+    pub num_messages: usize,
+    /// The total size of messages stored for this destination in bytes
+    pub total_bytes: u64,
+}
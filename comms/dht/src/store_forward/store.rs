@@ -34,7 +34,13 @@ use tower::{layer::Layer, Service, ServiceExt};
 use super::StoreAndForwardRequester;
 use crate::{
     inbound::DecryptedDhtMessage,
-    store_forward::{database::NewStoredMessage, message::StoredMessagePriority, SafConfig, SafResult},
+    store_forward::{
+        database::NewStoredMessage,
+        message::StoredMessagePriority,
+        SafConfig,
+        SafResult,
+        StoreAndForwardError,
+    },
 };
 
 const LOG_TARGET: &str = "comms::dht::storeforward::store";
@@ -201,9 +207,15 @@ where S: Service<DecryptedDhtMessage, Response = (), Error = PipelineError> + Se
         message.set_saf_stored(false);
         if self.is_valid_for_storage(&message) {
             if let Some(priority) = self.get_storage_priority(&message).await? {
-                message.set_saf_stored(true);
-                let existing = self.store(priority, message.clone()).await?;
-                message.set_already_forwarded(existing);
+                match self.store(priority, message.clone()).await {
+                    Ok(existing) => {
+                        message.set_saf_stored(true);
+                        message.set_already_forwarded(existing);
+                    },
+                    // The message is still passed on, it just isn't stored
+                    Err(StoreAndForwardError::StorageQuotaExceeded) => {},
+                    Err(err) => return Err(err.into()),
+                }
             }
         }
 
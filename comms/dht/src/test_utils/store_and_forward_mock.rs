@@ -36,7 +36,7 @@ use tokio::{
     sync::{mpsc, RwLock},
 };
 
-use crate::store_forward::{StoreAndForwardRequest, StoreAndForwardRequester, StoredMessage};
+use crate::store_forward::{SafStorageStats, StoreAndForwardRequest, StoreAndForwardRequester, StoredMessage};
 
 const LOG_TARGET: &str = "comms::dht::discovery_mock";
 
@@ -151,6 +151,7 @@ impl StoreAndForwardMock {
                     priority: msg.priority,
                     stored_at: Utc::now().naive_utc(),
                     body_hash: msg.body_hash,
+                    source_node_id: msg.source_node_id,
                 });
                 reply_tx.send(Ok(false)).unwrap();
             },
@@ -171,6 +172,13 @@ impl StoreAndForwardMock {
             MarkSafResponseReceived(_, reply) => {
                 let _ = reply.send(*self.state.inflight_request.read().await);
             },
+            GetStorageStats(_, reply) => {
+                let num_messages = self.state.stored_messages.read().await.len();
+                let _result = reply.send(Ok(SafStorageStats {
+                    num_messages,
+                    ..Default::default()
+                }));
+            },
         }
     }
 }
@@ -212,6 +212,8 @@ pub fn get_default_cli() -> Cli {
         },
         password: None,
         change_password: false,
+        create_backup: None,
+        restore_backup: None,
         recovery: false,
         seed_words: None,
         seed_words_file_name: None,
@@ -154,6 +154,7 @@ async fn send_from_cli(world: &mut TariWorld, amount: u64, wallet_a: String, wal
         amount: MicroMinotari(amount),
         message: format!("Send amount {} from {} to {}", amount, wallet_a, wallet_b),
         destination: wallet_b_address,
+        from_account: None,
     };
     cli.command2 = Some(CliCommands::SendMinotari(args));
 
@@ -207,6 +208,7 @@ async fn send_one_sided_tx_via_cli(world: &mut TariWorld, amount: u64, wallet_a:
         amount: MicroMinotari(amount),
         message: format!("Send one sided amount {} from {} to {}", amount, wallet_a, wallet_b),
         destination: wallet_b_address,
+        from_account: None,
     };
     cli.command2 = Some(CliCommands::SendOneSided(args));
 
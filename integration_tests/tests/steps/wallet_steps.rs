@@ -110,7 +110,7 @@ async fn wait_for_wallet_to_have_micro_tari(world: &mut TariWorld, wallet: Strin
     for _ in 0..=num_retries {
         let _result = client.validate_all_transactions(ValidateRequest {}).await;
         curr_amount = client
-            .get_balance(GetBalanceRequest {})
+            .get_balance(GetBalanceRequest { account: String::new() })
             .await
             .unwrap()
             .into_inner()
@@ -157,7 +157,7 @@ async fn wallet_detects_all_txs_as_mined_status(world: &mut TariWorld, wallet_na
     let mut client = create_wallet_client(world, wallet_name.clone()).await.unwrap();
 
     let mut completed_tx_stream = client
-        .get_completed_transactions(GetCompletedTransactionsRequest {})
+        .get_completed_transactions(GetCompletedTransactionsRequest { account: String::new() })
         .await
         .unwrap()
         .into_inner();
@@ -491,7 +491,7 @@ async fn list_all_txs_for_wallet(world: &mut TariWorld, transaction_type: String
     }
     let mut client = create_wallet_client(world, wallet.clone()).await.unwrap();
 
-    let request = GetCompletedTransactionsRequest {};
+    let request = GetCompletedTransactionsRequest { account: String::new() };
     let mut completed_txs = client.get_completed_transactions(request).await.unwrap().into_inner();
 
     while let Some(tx) = completed_txs.next().await {
@@ -535,7 +535,7 @@ async fn wallet_has_at_least_num_txs(world: &mut TariWorld, wallet: String, num_
 
     for _ in 0..num_retries {
         let mut txs = client
-            .get_completed_transactions(grpc::GetCompletedTransactionsRequest {})
+            .get_completed_transactions(grpc::GetCompletedTransactionsRequest { account: String::new() })
             .await
             .unwrap()
             .into_inner();
@@ -616,7 +616,7 @@ async fn wait_for_wallet_to_have_less_than_micro_tari(world: &mut TariWorld, wal
     println!("Waiting for wallet {} to have less than {} uT", wallet, amount);
 
     let num_retries = 100;
-    let request = GetBalanceRequest {};
+    let request = GetBalanceRequest { account: String::new() };
 
     for _ in 0..num_retries {
         let balance_res = client.get_balance(request.clone()).await.unwrap().into_inner();
@@ -703,6 +703,7 @@ async fn send_amount_from_source_wallet_to_dest_wallet_without_broadcast(
             dest_wallet.as_str()
         ),
         payment_type: 0, // normal mimblewimble payment type
+        from_account: String::new(),
     };
     let transfer_req = TransferRequest {
         recipients: vec![payment_recipient],
@@ -763,6 +764,7 @@ async fn send_one_sided_transaction_from_source_wallet_to_dest_wallt(
             dest_wallet.as_str()
         ),
         payment_type: 1, // one sided transaction
+        from_account: String::new(),
     };
     let transfer_req = TransferRequest {
         recipients: vec![payment_recipient],
@@ -862,6 +864,7 @@ async fn send_amount_from_wallet_to_wallet_at_fee(
             fee_per_gram
         ),
         payment_type: 0, // mimblewimble transaction
+        from_account: String::new(),
     };
     let transfer_req = TransferRequest {
         recipients: vec![payment_recipient],
@@ -939,7 +942,7 @@ async fn send_amount_from_wallet_to_wallet_at_fee(
 async fn wallet_detects_at_least_coinbase_transactions(world: &mut TariWorld, wallet_name: String, coinbases: u64) {
     let mut client = create_wallet_client(world, wallet_name.clone()).await.unwrap();
     let mut completed_tx_res = client
-        .get_completed_transactions(GetCompletedTransactionsRequest {})
+        .get_completed_transactions(GetCompletedTransactionsRequest { account: String::new() })
         .await
         .unwrap()
         .into_inner();
@@ -991,7 +994,7 @@ async fn wallet_detects_at_least_coinbase_transactions(world: &mut TariWorld, wa
 async fn wallet_detects_at_least_unmined_transactions(world: &mut TariWorld, wallet_name: String, coinbases: u64) {
     let mut client = create_wallet_client(world, wallet_name.clone()).await.unwrap();
     let mut completed_tx_res = client
-        .get_completed_transactions(GetCompletedTransactionsRequest {})
+        .get_completed_transactions(GetCompletedTransactionsRequest { account: String::new() })
         .await
         .unwrap()
         .into_inner();
@@ -1205,7 +1208,7 @@ async fn wallets_should_have_at_least_num_spendable_coinbase_outs(
 
         'inner: for _ in 0..num_retries {
             let mut stream = client
-                .get_completed_transactions(GetCompletedTransactionsRequest {})
+                .get_completed_transactions(GetCompletedTransactionsRequest { account: String::new() })
                 .await
                 .unwrap()
                 .into_inner();
@@ -1279,6 +1282,7 @@ async fn send_num_transactions_to_wallets_at_fee(
                 receiver_wallet.as_str()
             ),
             payment_type: 0, // standard mimblewimble transaction
+            from_account: String::new(),
         };
         let transfer_req = TransferRequest {
             recipients: vec![payment_recipient],
@@ -1420,6 +1424,7 @@ async fn transfer_tari_from_wallet_to_receiver(world: &mut TariWorld, amount: u6
             receiver.as_str()
         ),
         payment_type: 0, // normal mimblewimble payment type
+        from_account: String::new(),
     };
     let transfer_req = TransferRequest {
         recipients: vec![payment_recipient],
@@ -1505,7 +1510,7 @@ async fn wallet_has_tari(world: &mut TariWorld, wallet: String, amount: u64) {
     for _ in 0..num_retries {
         let _result = wallet_client.validate_all_transactions(ValidateRequest {}).await;
         let balance_res = wallet_client
-            .get_balance(GetBalanceRequest {})
+            .get_balance(GetBalanceRequest { account: String::new() })
             .await
             .unwrap()
             .into_inner();
@@ -1572,7 +1577,7 @@ async fn wallet_with_tari_connected_to_base_node(
     for _ in 0..num_retries {
         let _result = wallet_client.validate_all_transactions(ValidateRequest {}).await;
         let balance_res = wallet_client
-            .get_balance(GetBalanceRequest {})
+            .get_balance(GetBalanceRequest { account: String::new() })
             .await
             .unwrap()
             .into_inner();
@@ -1614,6 +1619,7 @@ async fn transfer_from_wallet_to_two_recipients_at_fee(
             receiver1.as_str()
         ),
         payment_type: 0, // normal mimblewimble payment type
+        from_account: String::new(),
     };
 
     let payment_recipient2 = PaymentRecipient {
@@ -1627,6 +1633,7 @@ async fn transfer_from_wallet_to_two_recipients_at_fee(
             receiver2.as_str()
         ),
         payment_type: 0, // normal mimblewimble payment type
+        from_account: String::new(),
     };
     let transfer_req = TransferRequest {
         recipients: vec![payment_recipient1, payment_recipient2],
@@ -1738,6 +1745,7 @@ async fn transfer_tari_to_self(world: &mut TariWorld, amount: u64, sender: Strin
         fee_per_gram,
         message: format!("transfer amount {} from {} to self", amount, sender.as_str(),),
         payment_type: 0, // normal mimblewimble payment type
+        from_account: String::new(),
     };
     let transfer_req = TransferRequest {
         recipients: vec![payment_recipient],
@@ -1823,6 +1831,7 @@ async fn htlc_transaction(world: &mut TariWorld, amount: u64, sender: String, re
             fee_per_gram
         ),
         payment_type: 0, // normal mimblewimble transaction
+        from_account: String::new(),
     };
 
     let atomic_swap_request = SendShaAtomicSwapRequest {
@@ -2053,7 +2062,7 @@ async fn wait_for_wallet_to_have_less_than_amount(world: &mut TariWorld, wallet:
     for _ in 0..=num_retries {
         let _result = client.validate_all_transactions(ValidateRequest {}).await;
         curr_amount = client
-            .get_balance(GetBalanceRequest {})
+            .get_balance(GetBalanceRequest { account: String::new() })
             .await
             .unwrap()
             .into_inner()
@@ -2112,6 +2121,7 @@ async fn send_one_sided_stealth_transaction(
             receiver.as_str()
         ),
         payment_type: 2, // one sided stealth transaction
+        from_account: String::new(),
     };
     let transfer_req = TransferRequest {
         recipients: vec![payment_recipient],
@@ -2530,7 +2540,7 @@ async fn restart_wallet(world: &mut TariWorld, wallet: String) {
 async fn check_if_wallet_has_num_transactions(world: &mut TariWorld, wallet: String, num_txs: u64) {
     let mut client = create_wallet_client(world, wallet.clone()).await.unwrap();
     let mut get_completed_txs_res = client
-        .get_completed_transactions(GetCompletedTransactionsRequest {})
+        .get_completed_transactions(GetCompletedTransactionsRequest { account: String::new() })
         .await
         .unwrap()
         .into_inner();
@@ -2593,6 +2603,7 @@ async fn multi_send_txs_from_wallet(
                 fee_per_gram
             ),
             payment_type: 0, // mimblewimble transaction
+            from_account: String::new(),
         };
 
         let transfer_req = TransferRequest {
@@ -2677,7 +2688,7 @@ async fn multi_send_txs_from_wallet(
 async fn check_if_last_imported_txs_are_invalid_in_wallet(world: &mut TariWorld, wallet: String) {
     let mut client = create_wallet_client(world, wallet.clone()).await.unwrap();
     let mut get_completed_txs_res = client
-        .get_completed_transactions(GetCompletedTransactionsRequest {})
+        .get_completed_transactions(GetCompletedTransactionsRequest { account: String::new() })
         .await
         .unwrap()
         .into_inner();
@@ -2700,7 +2711,7 @@ async fn check_if_last_imported_txs_are_invalid_in_wallet(world: &mut TariWorld,
 async fn check_if_last_imported_txs_are_valid_in_wallet(world: &mut TariWorld, wallet: String) {
     let mut client = create_wallet_client(world, wallet.clone()).await.unwrap();
     let mut get_completed_txs_res = client
-        .get_completed_transactions(GetCompletedTransactionsRequest {})
+        .get_completed_transactions(GetCompletedTransactionsRequest { account: String::new() })
         .await
         .unwrap()
         .into_inner();